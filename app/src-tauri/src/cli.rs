@@ -0,0 +1,514 @@
+//! Command-line companion mode: add and list tasks, notes and projects from a
+//! terminal without opening a window. Commands open the same database as the
+//! app and go through the same services, so they can run while the app is
+//! open; writes wait out the app's locks through the busy retry.
+
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Days, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use serde_json::{json, Value};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateNoteDto, CreateTaskDto, ListOptions, Note, Project, ProjectSort, ProjectStatus, Task, TaskPriority, UpdateNoteDto};
+use crate::services::{NoteService, ProjectService, SettingsService, TaskService, VaultService, VAULT_DB_FILE};
+use crate::state::AppState;
+use crate::utils::{logging, sanitize, timezone};
+
+/// Bundle identifier of the app, which names its data folder; keep in step
+/// with tauri.conf.json
+const APP_IDENTIFIER: &str = "com.research.management";
+
+/// First arguments that start the command line instead of the window
+const CLI_ARGUMENTS: [&str; 9] = ["task", "note", "project", "help", "--help", "-h", "--version", "-V", "--json"];
+
+/// Tasks listed when no limit is given
+const DEFAULT_TASK_LIMIT: u32 = 100;
+
+#[derive(Debug, Parser)]
+#[command(name = "research-vault", version, about = "Work with the Research Vault database from a terminal")]
+struct Cli {
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Database to open instead of the app's current vault
+    #[arg(long, global = true, value_name = "PATH")]
+    db: Option<PathBuf>,
+
+    /// Print the app's log messages
+    #[arg(long, short, global = true)]
+    verbose: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Add or list tasks
+    #[command(subcommand)]
+    Task(TaskCommand),
+    /// Add notes or append to them
+    #[command(subcommand)]
+    Note(NoteCommand),
+    /// List projects
+    #[command(subcommand)]
+    Project(ProjectCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum TaskCommand {
+    /// Add a task to a project
+    Add {
+        #[command(flatten)]
+        project: ProjectArg,
+        #[arg(long)]
+        title: String,
+        #[arg(long)]
+        description: Option<String>,
+        /// "today", "tomorrow", "+3" for days from now, or a date such as 2026-05-01
+        #[arg(long)]
+        due: Option<String>,
+        /// low, medium or high
+        #[arg(long, value_parser = parse_priority)]
+        priority: Option<TaskPriority>,
+        /// Status such as todo or in_progress; the project's first status when missing
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// List the tasks of a project
+    List {
+        #[command(flatten)]
+        project: ProjectArg,
+        /// Only tasks with this status
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long, default_value_t = DEFAULT_TASK_LIMIT)]
+        limit: u32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum NoteCommand {
+    /// Add a note to a project
+    Add {
+        #[command(flatten)]
+        project: ProjectArg,
+        #[arg(long)]
+        title: String,
+        /// Content of the note; "-" reads it from standard input
+        #[arg(long, default_value = "")]
+        content: String,
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Append text to the end of a note
+    Append {
+        /// Note ID, or its title together with --project
+        #[arg(long)]
+        note: String,
+        /// Project whose note is named by title
+        #[arg(long)]
+        project: Option<String>,
+        /// Text to append; "-" reads it from standard input
+        #[arg(long)]
+        text: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ProjectCommand {
+    /// List projects, most recently modified first
+    List {
+        /// Include archived projects
+        #[arg(long)]
+        archived: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+struct ProjectArg {
+    /// Project name or ID
+    #[arg(long)]
+    project: String,
+}
+
+/// Result of a command, printed as text or JSON
+#[derive(Debug)]
+enum CliOutput {
+    Projects(Vec<Project>),
+    Tasks(Vec<Task>),
+    TaskAdded(Task),
+    NoteAdded(Note),
+    NoteAppended(Note),
+}
+
+/// Whether the process was started from the command line with one of its
+/// commands, in which case `run` handles it instead of the window
+pub fn is_cli_invocation(args: &[OsString]) -> bool {
+    args.get(1)
+        .and_then(|arg| arg.to_str())
+        .is_some_and(|arg| CLI_ARGUMENTS.contains(&arg) || arg.starts_with("--db"))
+}
+
+/// Run one command and return the process exit code: 0 on success, 1 when
+/// the command failed and 2 for invalid arguments
+pub fn run(args: Vec<OsString>) -> i32 {
+    attach_parent_console();
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return e.exit_code();
+        }
+    };
+    let level = if cli.verbose { logging::LogLevel::Debug } else { logging::LogLevel::Warn };
+    let _ = logging::init(None, level);
+
+    let json = cli.json;
+    match tauri::async_runtime::block_on(open_and_execute(cli)) {
+        Ok(text) => {
+            println!("{}", text);
+            0
+        }
+        Err(e) if json => {
+            eprintln!("{}", json!({ "error": e }));
+            1
+        }
+        Err(e) => {
+            eprintln!("error: {}", e.user_message());
+            1
+        }
+    }
+}
+
+async fn open_and_execute(cli: Cli) -> AppResult<String> {
+    let db_path = match cli.db {
+        Some(path) => path,
+        None => {
+            let data_dir = app_data_dir()?;
+            sanitize::set_app_data_dir(&data_dir.to_string_lossy());
+            VaultService::startup_vault(&data_dir).join(VAULT_DB_FILE)
+        }
+    };
+    if !db_path.is_file() {
+        return Err(AppError::NotFound("Database", db_path.to_string_lossy().into_owned()));
+    }
+
+    let state = AppState::new();
+    state.init_db(&db_path.to_string_lossy())?;
+    let now = chrono::Utc::now().timestamp();
+    let offset = state.run(move |conn| SettingsService::utc_offset(conn, now)).await?;
+    let output = execute(&state, cli.command, now, offset).await?;
+    Ok(if cli.json { output.to_json().to_string() } else { output.to_text(offset) })
+}
+
+async fn execute(state: &AppState, command: Command, now: i64, offset: i32) -> AppResult<CliOutput> {
+    match command {
+        Command::Task(TaskCommand::Add { project, title, description, due, priority, status, tags }) => {
+            let project = find_project(state, &project.project).await?;
+            let due_date = due
+                .map(|due| parse_due(&due, now, offset).ok_or_else(|| AppError::InvalidInput(format!("'{}' is not a due date", due))))
+                .transpose()?;
+            let task = TaskService::create_task(state, CreateTaskDto {
+                project_id: project.id,
+                parent_id: None,
+                title,
+                description,
+                status,
+                priority,
+                due_date,
+                order: None,
+                tags: Some(tags).filter(|tags| !tags.is_empty()),
+                recurrence: None,
+                remind_at: None,
+                remind_before: None,
+            }).await?;
+            Ok(CliOutput::TaskAdded(task))
+        }
+        Command::Task(TaskCommand::List { project, status, limit }) => {
+            let project = find_project(state, &project.project).await?;
+            let tasks = match status {
+                Some(status) => TaskService::list_tasks_by_status(state, project.id, status).await?,
+                None => {
+                    let options = ListOptions { limit: Some(limit), offset: None, sort_by: None, sort_order: None };
+                    TaskService::list_tasks(state, project.id, options).await?.items
+                }
+            };
+            Ok(CliOutput::Tasks(tasks))
+        }
+        Command::Note(NoteCommand::Add { project, title, content, tags }) => {
+            let project = find_project(state, &project.project).await?;
+            let note = NoteService::create_note(state, CreateNoteDto {
+                project_id: project.id,
+                title,
+                content: read_argument(content)?,
+                tags: Some(tags).filter(|tags| !tags.is_empty()),
+                is_pinned: None,
+            }).await?;
+            Ok(CliOutput::NoteAdded(note))
+        }
+        Command::Note(NoteCommand::Append { note, project, text }) => {
+            let note = find_note(state, &note, project.as_deref()).await?;
+            let text = read_argument(text)?;
+            let mut content = note.content;
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(text.trim_end_matches('\n'));
+            content.push('\n');
+            // Fails instead of overwriting an edit made in the app meanwhile
+            let note = NoteService::update_note(state, note.id, UpdateNoteDto {
+                title: None,
+                content: Some(content),
+                tags: None,
+                is_pinned: None,
+                expected_updated_at: Some(note.updated_at),
+            }, false).await?;
+            Ok(CliOutput::NoteAppended(note))
+        }
+        Command::Project(ProjectCommand::List { archived }) => {
+            let projects = ProjectService::list_projects(state, Some(archived), Some(ProjectSort::Recent)).await?;
+            Ok(CliOutput::Projects(projects))
+        }
+    }
+}
+
+/// Project by ID, or by name ignoring case
+async fn find_project(state: &AppState, name_or_id: &str) -> AppResult<Project> {
+    let mut projects = ProjectService::list_projects(state, Some(true), None).await?;
+    if let Some(position) = projects.iter().position(|project| project.id == name_or_id) {
+        return Ok(projects.swap_remove(position));
+    }
+
+    let wanted = name_or_id.trim().to_lowercase();
+    let mut matches = projects.into_iter().filter(|project| project.name.to_lowercase() == wanted);
+    match (matches.next(), matches.next()) {
+        (Some(project), None) => Ok(project),
+        (Some(_), Some(_)) => Err(AppError::Conflict(format!("More than one project is named '{}'; give its ID", name_or_id))),
+        (None, _) => Err(AppError::NotFound("Project", name_or_id.to_string())),
+    }
+}
+
+/// Note by ID, or by title ignoring case within `project`
+async fn find_note(state: &AppState, id_or_title: &str, project: Option<&str>) -> AppResult<Note> {
+    let Some(project) = project else {
+        return NoteService::get_note(state, id_or_title.to_string()).await;
+    };
+
+    let project = find_project(state, project).await?;
+    let wanted = id_or_title.trim().to_lowercase();
+    let mut matches = NoteService::list_notes_by_title(state, project.id, None)
+        .await?
+        .into_iter()
+        .filter(|note| note.id == id_or_title || note.title.to_lowercase() == wanted);
+    match (matches.next(), matches.next()) {
+        (Some(note), None) => Ok(note),
+        (Some(_), Some(_)) => Err(AppError::Conflict(format!("More than one note is titled '{}'; give its ID", id_or_title))),
+        (None, _) => Err(AppError::NotFound("Note", id_or_title.to_string())),
+    }
+}
+
+/// Due date as the last second of a day counted in the timezone `offset`
+/// seconds from UTC: "today", "tomorrow", "+N" days from now, or anything
+/// `timezone::parse_local_datetime` understands
+fn parse_due(value: &str, now: i64, offset: i32) -> Option<i64> {
+    let value = value.trim();
+    let days = match value.to_ascii_lowercase().as_str() {
+        "today" => Some(0),
+        "tomorrow" => Some(1),
+        other => other.strip_prefix('+').map(|days| days.trim_end_matches('d').parse::<u64>()).transpose().ok()?,
+    };
+    let Some(days) = days else {
+        return timezone::parse_local_datetime(value, offset);
+    };
+
+    let today: NaiveDate = DateTime::from_timestamp(now.checked_add(i64::from(offset))?, 0)?.date_naive();
+    let end_of_day = today.checked_add_days(Days::new(days))?.and_hms_opt(23, 59, 59)?;
+    Some(end_of_day.and_utc().timestamp() - i64::from(offset))
+}
+
+fn parse_priority(value: &str) -> Result<TaskPriority, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "low" => Ok(TaskPriority::Low),
+        "medium" => Ok(TaskPriority::Medium),
+        "high" => Ok(TaskPriority::High),
+        _ => Err(format!("'{}' is not low, medium or high", value)),
+    }
+}
+
+/// An argument's value, or standard input when it is "-"
+fn read_argument(value: String) -> AppResult<String> {
+    if value != "-" {
+        return Ok(value);
+    }
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    Ok(input)
+}
+
+/// Data folder of the app, as the window finds it
+fn app_data_dir() -> AppResult<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home().map(|home| home.join(".local").join("share")))
+    };
+    base.map(|base| base.join(APP_IDENTIFIER))
+        .ok_or_else(|| AppError::System("Cannot find the app's data folder; pass --db".into()))
+}
+
+/// Release builds on Windows start without a console; write to the one of
+/// the terminal the command was typed in
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // SAFETY: AttachConsole takes a plain process ID and fails harmlessly
+    // when there is no parent console or one is already attached
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
+impl CliOutput {
+    fn to_json(&self) -> Value {
+        match self {
+            CliOutput::Projects(projects) => json!(projects),
+            CliOutput::Tasks(tasks) => json!(tasks),
+            CliOutput::TaskAdded(task) => json!(task),
+            CliOutput::NoteAdded(note) | CliOutput::NoteAppended(note) => json!(note),
+        }
+    }
+
+    fn to_text(&self, offset: i32) -> String {
+        match self {
+            CliOutput::Projects(projects) if projects.is_empty() => "No projects".to_string(),
+            CliOutput::Projects(projects) => projects
+                .iter()
+                .map(|project| match project.status {
+                    ProjectStatus::Active => format!("{}  {}", project.name, project.path),
+                    status => format!("{} ({})  {}", project.name, status, project.path),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            CliOutput::Tasks(tasks) if tasks.is_empty() => "No tasks".to_string(),
+            CliOutput::Tasks(tasks) => tasks.iter().map(|task| task_line(task, offset)).collect::<Vec<_>>().join("\n"),
+            CliOutput::TaskAdded(task) => format!("Added {}", task_line(task, offset)),
+            CliOutput::NoteAdded(note) => format!("Added note '{}' ({})", note.title, note.id),
+            CliOutput::NoteAppended(note) => format!("Appended to note '{}' ({})", note.title, note.id),
+        }
+    }
+}
+
+fn task_line(task: &Task, offset: i32) -> String {
+    let mut line = format!("{}  [{}]  {}", task.task_key.as_deref().unwrap_or(&task.id), task.status, task.title);
+    let due = task.due_date.and_then(|due| DateTime::from_timestamp(due.checked_add(i64::from(offset))?, 0));
+    if let Some(due) = due {
+        line.push_str(&format!("  due {}", due.format("%Y-%m-%d")));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    fn parse(args: &[&str]) -> Command {
+        Cli::try_parse_from(std::iter::once("research-vault").chain(args.iter().copied())).unwrap().command
+    }
+
+    #[test]
+    fn cli_invocations_are_told_from_window_launches() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        assert!(is_cli_invocation(&args(&["research-vault", "task", "list", "--project", "NLP"])));
+        assert!(is_cli_invocation(&args(&["research-vault", "--db=/tmp/research.db", "project", "list"])));
+        assert!(!is_cli_invocation(&args(&["research-vault"])));
+        assert!(!is_cli_invocation(&args(&["research-vault", "-psn_0_12345"])));
+        assert!(Cli::try_parse_from(["research-vault", "task", "add", "--title", "no project"]).is_err());
+    }
+
+    #[test]
+    fn exit_codes_tell_failures_from_bad_arguments() {
+        let db = test_support::temp_dir().join("research.db");
+        AppState::new().init_db(&db.to_string_lossy()).unwrap();
+        let db = db.to_string_lossy().into_owned();
+        let run_with = |args: &[&str]| run(["research-vault", "--db", db.as_str()].iter().chain(args).map(OsString::from).collect());
+
+        assert_eq!(run_with(&["project", "list"]), 0);
+        assert_eq!(run_with(&["task", "list", "--project", "Missing"]), 1);
+        assert_eq!(run_with(&["task", "frobnicate"]), 2);
+        assert_eq!(run(vec!["research-vault".into(), "--db".into(), "/nonexistent/research.db".into(), "project".into(), "list".into()]), 1);
+    }
+
+    #[test]
+    fn due_dates_end_on_the_named_local_day() {
+        // 2026-03-10 22:00 UTC is already 2026-03-11 in UTC+2
+        let now = 1_773_180_000;
+        let day_end = |date: &str, offset: i32| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap().and_hms_opt(23, 59, 59).unwrap().and_utc().timestamp()
+                - i64::from(offset)
+        };
+        assert_eq!(parse_due("today", now, 0), Some(day_end("2026-03-10", 0)));
+        assert_eq!(parse_due("Tomorrow", now, 0), Some(day_end("2026-03-11", 0)));
+        assert_eq!(parse_due("tomorrow", now, 7200), Some(day_end("2026-03-12", 7200)));
+        assert_eq!(parse_due("+3d", now, 0), Some(day_end("2026-03-13", 0)));
+        assert_eq!(parse_due("2026-05-01", now, 0), Some(day_end("2026-05-01", 0)));
+        assert_eq!(parse_due("+x", now, 0), None);
+        assert_eq!(parse_due("someday", now, 0), None);
+    }
+
+    #[tokio::test]
+    async fn commands_go_through_the_services() {
+        let state = test_support::open_state();
+        let project = test_support::project(&state.conn().unwrap(), "NLP");
+        let now = chrono::Utc::now().timestamp();
+
+        let added = execute(&state, parse(&["task", "add", "--project", "nlp", "--title", "rerun ablation", "--due", "tomorrow", "--priority", "high"]), now, 0)
+            .await
+            .unwrap();
+        let CliOutput::TaskAdded(task) = added else { panic!("expected a task") };
+        assert_eq!(task.project_id, project.id);
+        assert_eq!(task.priority, TaskPriority::High);
+        assert_eq!(task.due_date, parse_due("tomorrow", now, 0));
+
+        let CliOutput::Tasks(tasks) = execute(&state, parse(&["task", "list", "--project", &project.id]), now, 0).await.unwrap() else {
+            panic!("expected tasks")
+        };
+        assert_eq!(tasks.len(), 1);
+        assert!(CliOutput::Tasks(tasks).to_text(0).contains("rerun ablation"));
+
+        execute(&state, parse(&["note", "add", "--project", "NLP", "--title", "Log", "--content", "first line"]), now, 0).await.unwrap();
+        let appended = execute(&state, parse(&["note", "append", "--project", "NLP", "--note", "log", "--text", "second line"]), now, 0)
+            .await
+            .unwrap();
+        let CliOutput::NoteAppended(note) = appended else { panic!("expected a note") };
+        assert_eq!(note.content, "first line\nsecond line\n");
+
+        let CliOutput::Projects(projects) = execute(&state, parse(&["project", "list"]), now, 0).await.unwrap() else {
+            panic!("expected projects")
+        };
+        assert_eq!(projects.len(), 1);
+
+        let missing = execute(&state, parse(&["task", "list", "--project", "Vision"]), now, 0).await;
+        assert!(matches!(missing, Err(AppError::NotFound("Project", _))));
+    }
+}
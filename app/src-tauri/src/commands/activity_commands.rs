@@ -0,0 +1,41 @@
+use crate::error::AppResult;
+use crate::models::{ActivityDay, ActivityEntry};
+use crate::services::{ActivityService, AuditService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Get daily activity counts for a contribution calendar
+#[tauri::command]
+pub async fn get_activity_heatmap(
+    state: State<'_, AppState>,
+    days: i32,
+    project_id: Option<String>,
+    timezone: Option<String>,
+) -> AppResult<Vec<ActivityDay>> {
+    logging::timed("get_activity_heatmap", ActivityService::get_activity_heatmap(&state, days, project_id, timezone)).await
+}
+
+/// List recent changes across the vault or one project, newest first
+#[tauri::command]
+pub async fn list_activity(
+    state: State<'_, AppState>,
+    project_id: Option<String>,
+    limit: Option<i64>,
+    before_timestamp: Option<i64>,
+    before_id: Option<i64>,
+) -> AppResult<Vec<ActivityEntry>> {
+    logging::timed(
+        "list_activity",
+        ActivityService::list_activity(&state, project_id, limit, before_timestamp, before_id),
+    )
+    .await
+}
+
+/// Remove activity entries, optionally only those older than some days
+#[tauri::command]
+pub async fn clear_activity(state: State<'_, AppState>, older_than_days: Option<u32>) -> AppResult<usize> {
+    let args = json!({ "older_than_days": older_than_days });
+    AuditService::track(&state, "clear_activity", args, ActivityService::clear_activity(&state, older_than_days)).await
+}
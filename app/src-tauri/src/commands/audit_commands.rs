@@ -0,0 +1,27 @@
+use crate::error::AppResult;
+use crate::models::{AuditEntry, AuditLogFilter};
+use crate::services::AuditService;
+use crate::state::AppState;
+use crate::utils::logging;
+use tauri::State;
+
+/// List audit log entries, newest first
+#[tauri::command]
+pub async fn list_audit_log(
+    state: State<'_, AppState>,
+    filter: Option<AuditLogFilter>,
+    limit: Option<i64>,
+) -> AppResult<Vec<AuditEntry>> {
+    logging::timed("list_audit_log", AuditService::list_audit_log(&state, filter, limit)).await
+}
+
+/// Export audit log entries to a CSV file
+#[tauri::command]
+pub async fn export_audit_log_csv(
+    state: State<'_, AppState>,
+    path: String,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> AppResult<usize> {
+    logging::timed("export_audit_log_csv", AuditService::export_audit_log_csv(&state, path, from, to)).await
+}
@@ -0,0 +1,31 @@
+use crate::error::AppResult;
+use crate::models::AutomationStatus;
+use crate::services::{AuditService, AutomationService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// Turn on the local automation API and start listening on 127.0.0.1
+#[tauri::command]
+pub async fn start_automation_server<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>) -> AppResult<AutomationStatus> {
+    AuditService::track(&state, "start_automation_server", json!({}), AutomationService::start(&app, &state)).await
+}
+
+/// Turn off the local automation API
+#[tauri::command]
+pub async fn stop_automation_server(state: State<'_, AppState>) -> AppResult<AutomationStatus> {
+    AuditService::track(&state, "stop_automation_server", json!({}), AutomationService::stop(&state)).await
+}
+
+/// Whether the automation API is on and which port it listens on
+#[tauri::command]
+pub async fn get_automation_status(state: State<'_, AppState>) -> AppResult<AutomationStatus> {
+    logging::timed("get_automation_status", AutomationService::get_status(&state)).await
+}
+
+/// Bearer token of the automation API, for scripts to send
+#[tauri::command]
+pub async fn get_automation_token(state: State<'_, AppState>) -> AppResult<String> {
+    logging::timed("get_automation_token", AutomationService::get_token(&state)).await
+}
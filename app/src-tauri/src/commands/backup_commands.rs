@@ -0,0 +1,37 @@
+use crate::error::AppResult;
+use crate::models::{BackupResult, PortableBackupResult, PortableRestoreResult};
+use crate::services::{AuditService, BackupService};
+use crate::state::AppState;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// Back up the database now, the same way the scheduled backup does
+#[tauri::command]
+pub async fn run_backup_now<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>) -> AppResult<BackupResult> {
+    AuditService::track(&state, "run_backup_now", json!({}), BackupService::run_backup(&app, &state)).await
+}
+
+/// Write the database and every project's attachments to one portable archive
+#[tauri::command]
+pub async fn create_portable_backup<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, path: String) -> AppResult<PortableBackupResult> {
+    let args = json!({ "path": &path });
+    AuditService::track(&state, "create_portable_backup", args, BackupService::create_portable_backup(&app, &state, path)).await
+}
+
+/// Replace the database with a portable backup, unpacking its projects under `restore_projects_to`
+#[tauri::command]
+pub async fn restore_portable_backup<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    path: String,
+    restore_projects_to: String,
+) -> AppResult<PortableRestoreResult> {
+    let args = json!({ "path": &path, "restore_projects_to": &restore_projects_to });
+    AuditService::track(
+        &state,
+        "restore_portable_backup",
+        args,
+        BackupService::restore_portable_backup(&app, &state, path, restore_projects_to),
+    )
+    .await
+}
@@ -0,0 +1,12 @@
+use crate::error::AppResult;
+use crate::models::BootstrapData;
+use crate::services::BootstrapService;
+use crate::state::AppState;
+use crate::utils::logging;
+use tauri::State;
+
+/// Load what the first screen needs in one call; invoked once at startup
+#[tauri::command]
+pub async fn bootstrap(state: State<'_, AppState>) -> AppResult<BootstrapData> {
+    logging::timed("bootstrap", BootstrapService::bootstrap(&state)).await
+}
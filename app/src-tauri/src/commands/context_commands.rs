@@ -0,0 +1,16 @@
+use crate::error::AppResult;
+use crate::models::{ContextExportOptions, ProjectContextExport};
+use crate::services::ContextService;
+use crate::state::AppState;
+use crate::utils::logging;
+use tauri::State;
+
+/// Export a project's context (metadata, open tasks, recent notes) for assistants
+#[tauri::command]
+pub async fn export_project_context(
+    state: State<'_, AppState>,
+    project_id: String,
+    options: Option<ContextExportOptions>,
+) -> AppResult<ProjectContextExport> {
+    logging::timed("export_project_context", ContextService::export_project_context(&state, project_id, options)).await
+}
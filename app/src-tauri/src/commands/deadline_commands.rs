@@ -0,0 +1,86 @@
+use crate::error::AppResult;
+use crate::models::{CreateDeadlineDto, Deadline, DeadlineImportReport, TodayView, UpdateDeadlineDto};
+use crate::services::{AuditService, DeadlineService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Create a new deadline
+#[tauri::command]
+pub async fn create_deadline(state: State<'_, AppState>, data: CreateDeadlineDto) -> AppResult<Deadline> {
+    let args = json!({ "data": &data });
+    AuditService::track(&state, "create_deadline", args, DeadlineService::create_deadline(&state, data)).await
+}
+
+/// List deadlines for a project (plus global ones), or all deadlines
+#[tauri::command]
+pub async fn list_deadlines(
+    state: State<'_, AppState>,
+    project_id: Option<String>,
+    include_past: Option<bool>,
+) -> AppResult<Vec<Deadline>> {
+    logging::timed("list_deadlines", DeadlineService::list_deadlines(&state, project_id, include_past.unwrap_or(false))).await
+}
+
+/// List deadlines within the next `days` days
+#[tauri::command]
+pub async fn list_upcoming_deadlines(
+    state: State<'_, AppState>,
+    days: i64,
+    include_past: Option<bool>,
+) -> AppResult<Vec<Deadline>> {
+    logging::timed("list_upcoming_deadlines", DeadlineService::list_upcoming_deadlines(&state, days, include_past.unwrap_or(false))).await
+}
+
+/// Overdue tasks and tasks due today, with today's and the coming days' deadlines
+#[tauri::command]
+pub async fn get_today_view(
+    state: State<'_, AppState>,
+    days: Option<i64>,
+) -> AppResult<TodayView> {
+    logging::timed("get_today_view", DeadlineService::get_today_view(&state, days, None)).await
+}
+
+/// Get deadline by ID
+#[tauri::command]
+pub async fn get_deadline(state: State<'_, AppState>, id: String) -> AppResult<Deadline> {
+    logging::timed("get_deadline", DeadlineService::get_deadline(&state, id)).await
+}
+
+/// Update deadline
+#[tauri::command]
+pub async fn update_deadline(
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateDeadlineDto,
+) -> AppResult<Deadline> {
+    let args = json!({ "id": &id, "data": &data });
+    AuditService::track(&state, "update_deadline", args, DeadlineService::update_deadline(&state, id, data)).await
+}
+
+/// Delete deadline
+#[tauri::command]
+pub async fn delete_deadline(state: State<'_, AppState>, id: String) -> AppResult<()> {
+    let args = json!({ "id": &id });
+    AuditService::track(&state, "delete_deadline", args, DeadlineService::delete_deadline(&state, id)).await
+}
+
+/// Import deadlines from a JSON feed file, optionally as a dry-run preview
+#[tauri::command]
+pub async fn import_deadlines_feed(
+    state: State<'_, AppState>,
+    url_or_path: String,
+    project_id: Option<String>,
+    dry_run: Option<bool>,
+) -> AppResult<DeadlineImportReport> {
+    let dry_run = dry_run.unwrap_or(false);
+    let args = json!({ "url_or_path": &url_or_path, "project_id": &project_id, "dry_run": dry_run });
+    AuditService::track(
+        &state,
+        "import_deadlines_feed",
+        args,
+        DeadlineService::import_deadlines_feed(&state, url_or_path, project_id, dry_run),
+    )
+    .await
+}
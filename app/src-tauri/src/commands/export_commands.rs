@@ -0,0 +1,186 @@
+use crate::error::AppResult;
+use crate::models::{
+    CsvImportResult, ExportSummary, HtmlExportSummary, IcalComponent, ImportSummary, MarkdownImportResult, NoteBundleExportSummary,
+    NoteBundleImportSummary, SiteExportSummary, VerificationReport,
+};
+use crate::services::{AuditService, ExportService, JumpIndexService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// Export a project with its tasks, notes and tags to a JSON archive
+#[tauri::command]
+pub async fn export_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_path: String,
+) -> AppResult<ExportSummary> {
+    logging::timed("export_project", ExportService::export_project(&state, project_id, dest_path)).await
+}
+
+/// Create a new project from a JSON archive
+#[tauri::command]
+pub async fn import_project<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    src_path: String,
+    new_path: String,
+) -> AppResult<ImportSummary> {
+    let args = json!({ "src_path": &src_path, "new_path": &new_path });
+    let result = AuditService::track(&state, "import_project", args, ExportService::import_project(&state, src_path, new_path)).await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Write the notes of a project as Markdown files, by default into its notes/ folder
+#[tauri::command]
+pub async fn export_notes_markdown(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_dir: Option<String>,
+    include_backlinks: Option<bool>,
+) -> AppResult<Vec<String>> {
+    let export = ExportService::export_notes_markdown(&state, project_id, dest_dir, include_backlinks);
+    logging::timed("export_notes_markdown", export).await
+}
+
+/// Import the Markdown files of a folder as notes of a project
+#[tauri::command]
+pub async fn import_notes_markdown<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_id: String,
+    src_dir: String,
+) -> AppResult<Vec<MarkdownImportResult>> {
+    let args = json!({ "project_id": &project_id, "src_dir": &src_dir });
+    let result = AuditService::track(&state, "import_notes_markdown", args, ExportService::import_notes_markdown(&state, project_id, src_dir)).await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Write a project's tasks that have a due date, and its deadlines, to an iCalendar file, tasks as to-dos by default
+#[tauri::command]
+pub async fn export_tasks_ical(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_path: String,
+    component: Option<IcalComponent>,
+) -> AppResult<usize> {
+    let component = component.unwrap_or_default();
+    logging::timed("export_tasks_ical", ExportService::export_tasks_ical(&state, Some(project_id), dest_path, component)).await
+}
+
+/// Write the dated tasks of every non-archived project and every deadline to one iCalendar file
+#[tauri::command]
+pub async fn export_all_tasks_ical(
+    state: State<'_, AppState>,
+    dest_path: String,
+    component: Option<IcalComponent>,
+) -> AppResult<usize> {
+    let component = component.unwrap_or_default();
+    logging::timed("export_all_tasks_ical", ExportService::export_tasks_ical(&state, None, dest_path, component)).await
+}
+
+/// Write a project's tasks to a CSV file in tree order
+#[tauri::command]
+pub async fn export_tasks_csv(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_path: String,
+) -> AppResult<usize> {
+    logging::timed("export_tasks_csv", ExportService::export_tasks_csv(&state, project_id, dest_path)).await
+}
+
+/// Create tasks of a project from a CSV file, reporting rows that could not be imported
+#[tauri::command]
+pub async fn import_tasks_csv<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_id: String,
+    src_path: String,
+) -> AppResult<CsvImportResult> {
+    let args = json!({ "project_id": &project_id, "src_path": &src_path });
+    let result = AuditService::track(&state, "import_tasks_csv", args, ExportService::import_tasks_csv(&state, project_id, src_path)).await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Export one note as a single self-contained HTML file
+#[tauri::command]
+pub async fn export_note_html(
+    state: State<'_, AppState>,
+    note_id: String,
+    dest_path: String,
+) -> AppResult<HtmlExportSummary> {
+    logging::timed("export_note_html", ExportService::export_note_html(&state, note_id, dest_path)).await
+}
+
+/// Export every note of a project as a single self-contained HTML file with a table of contents by tag
+#[tauri::command]
+pub async fn export_project_html(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_path: String,
+) -> AppResult<HtmlExportSummary> {
+    logging::timed("export_project_html", ExportService::export_project_html(&state, project_id, dest_path)).await
+}
+
+/// Export a project's references to CSV, optionally only some columns and only references with a tag or reading status
+#[tauri::command]
+pub async fn export_references_csv(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_path: String,
+    columns: Option<Vec<String>>,
+    tag: Option<String>,
+    reading_status: Option<String>,
+) -> AppResult<usize> {
+    logging::timed(
+        "export_references_csv",
+        ExportService::export_references_csv(&state, project_id, dest_path, columns, tag, reading_status),
+    )
+    .await
+}
+
+/// Export a project as a static HTML site with relative links, regenerating an earlier export in place
+#[tauri::command]
+pub async fn export_project_site(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_dir: String,
+) -> AppResult<SiteExportSummary> {
+    logging::timed("export_project_site", ExportService::export_project_site(&state, project_id, dest_dir)).await
+}
+
+/// Drop every cached note rendering, e.g. after the renderer changed; returns how many were dropped
+#[tauri::command]
+pub async fn clear_render_cache(state: State<'_, AppState>) -> AppResult<usize> {
+    logging::timed("clear_render_cache", ExportService::clear_render_cache(&state)).await
+}
+
+/// Write one note with its attachments to a zip bundle for sharing
+#[tauri::command]
+pub async fn export_note_bundle(
+    state: State<'_, AppState>,
+    note_id: String,
+    dest_path: String,
+) -> AppResult<NoteBundleExportSummary> {
+    logging::timed("export_note_bundle", ExportService::export_note_bundle(&state, note_id, dest_path)).await
+}
+
+/// Create a note with its attachments in a project from a zip bundle
+#[tauri::command]
+pub async fn import_note_bundle<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_id: String,
+    src_path: String,
+) -> AppResult<NoteBundleImportSummary> {
+    let args = json!({ "project_id": &project_id, "src_path": &src_path });
+    let result = AuditService::track(&state, "import_note_bundle", args, ExportService::import_note_bundle(&state, project_id, src_path)).await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Compare a project archive or note bundle with the database and report what differs
+#[tauri::command]
+pub async fn verify_export(state: State<'_, AppState>, path: String) -> AppResult<VerificationReport> {
+    logging::timed("verify_export", ExportService::verify_export(&state, path)).await
+}
@@ -0,0 +1,134 @@
+use crate::error::AppResult;
+use crate::models::{ChangedFiles, DuplicateReport, DuplicateScanProgress, FileIndexSummary, FileInfo, FileMetadata, FileSearchResult};
+use crate::services::{AuditService, FileIndexService, DUPLICATE_SCAN_PROGRESS_EVENT};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+/// Index the files of a project directory
+#[tauri::command]
+pub async fn index_project_files(state: State<'_, AppState>, project_id: String) -> AppResult<FileIndexSummary> {
+    let args = json!({ "project_id": &project_id });
+    AuditService::track(
+        &state,
+        "index_project_files",
+        args,
+        FileIndexService::index_project_files(&state, project_id),
+    )
+    .await
+}
+
+/// List the indexed files of a project
+#[tauri::command]
+pub async fn list_project_files(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<FileMetadata>> {
+    logging::timed("list_project_files", FileIndexService::list_project_files(&state, project_id)).await
+}
+
+/// List the indexed files of a project directory, optionally with its subdirectories,
+/// flagging files that changed on disk; `refresh` re-reads them from the disk first
+#[tauri::command]
+pub async fn list_files(
+    state: State<'_, AppState>,
+    project_id: String,
+    subdir: Option<String>,
+    recursive: Option<bool>,
+    refresh: Option<bool>,
+) -> AppResult<Vec<FileInfo>> {
+    logging::timed(
+        "list_files",
+        FileIndexService::list_files(&state, project_id, subdir, recursive.unwrap_or(false), refresh.unwrap_or(false)),
+    )
+    .await
+}
+
+/// Get an indexed file by its path in the project
+#[tauri::command]
+pub async fn get_file_info(
+    state: State<'_, AppState>,
+    project_id: String,
+    relative_path: String,
+    refresh: Option<bool>,
+) -> AppResult<FileInfo> {
+    logging::timed(
+        "get_file_info",
+        FileIndexService::get_file_info(&state, project_id, relative_path, refresh.unwrap_or(false)),
+    )
+    .await
+}
+
+/// List the largest indexed files of a project
+#[tauri::command]
+pub async fn get_largest_files(state: State<'_, AppState>, project_id: String, limit: Option<usize>) -> AppResult<Vec<FileInfo>> {
+    logging::timed("get_largest_files", FileIndexService::get_largest_files(&state, project_id, limit)).await
+}
+
+/// List the files of a project added, modified or deleted since a time, as
+/// seen by the last index runs, with a one-line summary for a commit message
+#[tauri::command]
+pub async fn get_changed_files(state: State<'_, AppState>, project_id: String, since: i64) -> AppResult<ChangedFiles> {
+    logging::timed("get_changed_files", FileIndexService::get_changed_files(&state, project_id, since)).await
+}
+
+/// Flag an indexed file as ignored or not
+#[tauri::command]
+pub async fn set_file_ignored(state: State<'_, AppState>, file_id: String, ignored: bool) -> AppResult<FileMetadata> {
+    let args = json!({ "file_id": &file_id, "ignored": ignored });
+    AuditService::track(
+        &state,
+        "set_file_ignored",
+        args,
+        FileIndexService::set_file_ignored(&state, file_id, ignored),
+    )
+    .await
+}
+
+/// Get the ignore patterns of a project
+#[tauri::command]
+pub async fn get_ignore_patterns(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<String>> {
+    logging::timed("get_ignore_patterns", FileIndexService::get_ignore_patterns(&state, project_id)).await
+}
+
+/// Replace the ignore patterns of a project
+#[tauri::command]
+pub async fn set_ignore_patterns(state: State<'_, AppState>, project_id: String, patterns: Vec<String>) -> AppResult<Vec<String>> {
+    let args = json!({ "project_id": &project_id, "patterns": &patterns });
+    AuditService::track(
+        &state,
+        "set_ignore_patterns",
+        args,
+        FileIndexService::set_ignore_patterns(&state, project_id, patterns),
+    )
+    .await
+}
+
+/// Search the contents of a project's indexed files
+#[tauri::command]
+pub async fn search_project_files(state: State<'_, AppState>, project_id: String, query: String) -> AppResult<Vec<FileSearchResult>> {
+    logging::timed("search_project_files", FileIndexService::search_project_files(&state, project_id, query)).await
+}
+
+/// Find files with identical content across all projects, sending progress as
+/// `duplicate-scan-progress` events
+#[tauri::command]
+pub async fn find_duplicate_files<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, min_size_bytes: u64) -> AppResult<DuplicateReport> {
+    let on_progress = move |progress: &DuplicateScanProgress| {
+        if let Err(e) = app.emit(DUPLICATE_SCAN_PROGRESS_EVENT, progress) {
+            tracing::warn!("Failed to emit {}: {}", DUPLICATE_SCAN_PROGRESS_EVENT, e);
+        }
+    };
+    logging::timed("find_duplicate_files", FileIndexService::find_duplicate_files(&state, min_size_bytes, on_progress)).await
+}
+
+/// Replace a duplicate file with a symbolic link to the file kept
+#[tauri::command]
+pub async fn replace_with_symlink(state: State<'_, AppState>, keep_file_id: String, duplicate_file_id: String) -> AppResult<()> {
+    let args = json!({ "keep_file_id": &keep_file_id, "duplicate_file_id": &duplicate_file_id });
+    AuditService::track(
+        &state,
+        "replace_with_symlink",
+        args,
+        FileIndexService::replace_with_symlink(&state, keep_file_id, duplicate_file_id),
+    )
+    .await
+}
@@ -0,0 +1,100 @@
+use crate::error::AppResult;
+use crate::models::{CheckpointResult, DatabaseHealthReport, DbInfo, MigrationStatus, OptimizeResult, OrphanReport, RepairReport, SchemaVersion};
+use crate::services::{AuditService, HealthService, JumpIndexService};
+use crate::state::AppState;
+use crate::utils::logging::{self, LogLevel};
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// List tasks, notes and deadlines whose project no longer exists
+#[tauri::command]
+pub async fn list_orphaned_entities(state: State<'_, AppState>) -> AppResult<OrphanReport> {
+    logging::timed("list_orphaned_entities", HealthService::list_orphaned_entities(&state)).await
+}
+
+/// Move all orphaned rows into a project
+#[tauri::command]
+pub async fn adopt_orphans<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    target_project_id: String,
+) -> AppResult<usize> {
+    let args = json!({ "target_project_id": &target_project_id });
+    let result = AuditService::track(
+        &state,
+        "adopt_orphans",
+        args,
+        HealthService::adopt_orphans(&state, target_project_id),
+    )
+    .await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Delete all orphaned rows
+#[tauri::command]
+pub async fn purge_orphans(state: State<'_, AppState>) -> AppResult<usize> {
+    AuditService::track(&state, "purge_orphans", json!({}), HealthService::purge_orphans(&state)).await
+}
+
+/// Find rows left inconsistent by outside edits and repair them, or only report them in a dry run
+#[tauri::command]
+pub async fn repair_database(state: State<'_, AppState>, dry_run: Option<bool>) -> AppResult<RepairReport> {
+    let dry_run = dry_run.unwrap_or(false);
+    let args = json!({ "dry_run": dry_run });
+    AuditService::track(&state, "repair_database", args, HealthService::repair_database(&state, dry_run)).await
+}
+
+/// Report database settings, size and page statistics
+#[tauri::command]
+pub async fn get_db_info(state: State<'_, AppState>) -> AppResult<DbInfo> {
+    logging::timed("get_db_info", HealthService::get_db_info(&state)).await
+}
+
+/// Report the schema version of the database
+#[tauri::command]
+pub async fn get_schema_version(state: State<'_, AppState>) -> AppResult<SchemaVersion> {
+    logging::timed("get_schema_version", HealthService::get_schema_version(&state)).await
+}
+
+/// Progress of the database upgrade run at startup; works while it runs
+#[tauri::command]
+pub async fn get_migration_status(state: State<'_, AppState>) -> AppResult<MigrationStatus> {
+    Ok(HealthService::get_migration_status(&state))
+}
+
+/// Reopen the database connections after they stopped working
+#[tauri::command]
+pub async fn reconnect_database(state: State<'_, AppState>) -> AppResult<DbInfo> {
+    AuditService::track(&state, "reconnect_database", json!({}), HealthService::reconnect_database(&state)).await
+}
+
+/// Fold the write-ahead log back into the database file
+#[tauri::command]
+pub async fn checkpoint_database(state: State<'_, AppState>) -> AppResult<CheckpointResult> {
+    logging::timed("checkpoint_database", HealthService::checkpoint_database(&state)).await
+}
+
+/// Check the database file for damage and broken references
+#[tauri::command]
+pub async fn check_database_health(state: State<'_, AppState>) -> AppResult<DatabaseHealthReport> {
+    logging::timed("check_database_health", HealthService::check_database_health(&state)).await
+}
+
+/// Compact the database file and refresh query statistics
+#[tauri::command]
+pub async fn optimize_database(state: State<'_, AppState>) -> AppResult<OptimizeResult> {
+    AuditService::track(&state, "optimize_database", json!({}), HealthService::optimize_database(&state)).await
+}
+
+/// Last lines of the application log for the debug panel
+#[tauri::command]
+pub async fn get_recent_logs(lines: Option<usize>) -> AppResult<Vec<String>> {
+    HealthService::get_recent_logs(lines.unwrap_or(200)).await
+}
+
+/// Change how verbose the application log is
+#[tauri::command]
+pub async fn set_log_level(state: State<'_, AppState>, level: LogLevel) -> AppResult<LogLevel> {
+    let args = json!({ "level": level });
+    AuditService::track(&state, "set_log_level", args, HealthService::set_log_level(level)).await
+}
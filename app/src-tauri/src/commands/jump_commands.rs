@@ -0,0 +1,12 @@
+use crate::error::AppResult;
+use crate::models::JumpIndex;
+use crate::services::JumpIndexService;
+use crate::state::AppState;
+use crate::utils::logging;
+use tauri::State;
+
+/// Get the title index for the command palette
+#[tauri::command]
+pub async fn get_jump_index(state: State<'_, AppState>) -> AppResult<JumpIndex> {
+    logging::timed("get_jump_index", JumpIndexService::get_jump_index(&state)).await
+}
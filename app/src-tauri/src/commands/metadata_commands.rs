@@ -0,0 +1,36 @@
+use crate::error::AppResult;
+use crate::models::EntityType;
+use crate::services::{AuditService, MetadataService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::{json, Value};
+use tauri::State;
+
+/// Get the metadata object of a project, task or note
+#[tauri::command]
+pub async fn get_entity_metadata(
+    state: State<'_, AppState>,
+    entity_type: EntityType,
+    id: String,
+) -> AppResult<Value> {
+    logging::timed("get_entity_metadata", MetadataService::get_entity_metadata(&state, entity_type, id)).await
+}
+
+/// Merge or replace the metadata object of a project, task or note
+#[tauri::command]
+pub async fn set_entity_metadata(
+    state: State<'_, AppState>,
+    entity_type: EntityType,
+    id: String,
+    patch: Value,
+    merge: bool,
+) -> AppResult<Value> {
+    let args = json!({ "entity_type": entity_type, "id": &id, "patch": &patch, "merge": merge });
+    AuditService::track(
+        &state,
+        "set_entity_metadata",
+        args,
+        MetadataService::set_entity_metadata(&state, entity_type, id, patch, merge),
+    )
+    .await
+}
@@ -1,8 +1,64 @@
+pub mod activity_commands;
+pub mod audit_commands;
+pub mod automation_commands;
+pub mod backup_commands;
+pub mod bootstrap_commands;
+pub mod context_commands;
+pub mod deadline_commands;
+pub mod export_commands;
+pub mod file_commands;
+pub mod health_commands;
+pub mod jump_commands;
+pub mod metadata_commands;
+pub mod onboarding_commands;
 pub mod project_commands;
+pub mod project_template_commands;
+pub mod reference_commands;
+pub mod reminder_commands;
+pub mod report_commands;
+pub mod research_question_commands;
+pub mod search_commands;
+pub mod settings_commands;
+pub mod startup_scan_commands;
+pub mod tag_commands;
+pub mod time_commands;
+pub mod trash_commands;
+pub mod undo_commands;
+pub mod vault_commands;
 pub mod task_commands;
+pub mod note_attachment_commands;
 pub mod note_commands;
+pub mod note_template_commands;
 
+pub use activity_commands::*;
+pub use audit_commands::*;
+pub use automation_commands::*;
+pub use backup_commands::*;
+pub use bootstrap_commands::*;
+pub use context_commands::*;
+pub use deadline_commands::*;
+pub use export_commands::*;
+pub use file_commands::*;
+pub use health_commands::*;
+pub use jump_commands::*;
+pub use metadata_commands::*;
+pub use onboarding_commands::*;
 pub use project_commands::*;
+pub use project_template_commands::*;
+pub use reference_commands::*;
+pub use reminder_commands::*;
+pub use report_commands::*;
+pub use research_question_commands::*;
+pub use search_commands::*;
+pub use settings_commands::*;
+pub use startup_scan_commands::*;
+pub use tag_commands::*;
+pub use time_commands::*;
+pub use trash_commands::*;
+pub use undo_commands::*;
+pub use vault_commands::*;
 pub use task_commands::*;
+pub use note_attachment_commands::*;
 pub use note_commands::*;
+pub use note_template_commands::*;
 
@@ -0,0 +1,52 @@
+use crate::error::AppResult;
+use crate::models::{AttachmentStoreStats, NoteAttachment};
+use crate::services::{AuditService, NoteAttachmentService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Copy a file into the project and attach it to a note
+#[tauri::command]
+pub async fn attach_file_to_note(state: State<'_, AppState>, note_id: String, source_path: String) -> AppResult<NoteAttachment> {
+    let args = json!({ "note_id": &note_id, "source_path": &source_path });
+    AuditService::track(
+        &state,
+        "attach_file_to_note",
+        args,
+        NoteAttachmentService::attach_file(&state, note_id, source_path),
+    )
+    .await
+}
+
+/// List the attachments of a note
+#[tauri::command]
+pub async fn list_note_attachments(state: State<'_, AppState>, note_id: String) -> AppResult<Vec<NoteAttachment>> {
+    logging::timed("list_note_attachments", NoteAttachmentService::list_attachments(&state, note_id)).await
+}
+
+/// Detach a file from its note, deleting the copy with `delete_file`
+#[tauri::command]
+pub async fn remove_attachment(state: State<'_, AppState>, attachment_id: String, delete_file: Option<bool>) -> AppResult<()> {
+    let delete_file = delete_file.unwrap_or(false);
+    let args = json!({ "attachment_id": &attachment_id, "delete_file": delete_file });
+    AuditService::track(
+        &state,
+        "remove_attachment",
+        args,
+        NoteAttachmentService::remove_attachment(&state, attachment_id, delete_file),
+    )
+    .await
+}
+
+/// Absolute path of an attachment's file, for the frontend to open
+#[tauri::command]
+pub async fn open_attachment(state: State<'_, AppState>, attachment_id: String) -> AppResult<String> {
+    logging::timed("open_attachment", NoteAttachmentService::open_attachment(&state, attachment_id)).await
+}
+
+/// Counts and sizes of the shared attachment store
+#[tauri::command]
+pub async fn get_attachment_store_stats(state: State<'_, AppState>) -> AppResult<AttachmentStoreStats> {
+    logging::timed("get_attachment_store_stats", NoteAttachmentService::get_attachment_store_stats(&state)).await
+}
@@ -1,75 +1,380 @@
 use crate::error::AppResult;
-use crate::models::{CreateNoteDto, Note, UpdateNoteDto};
-use crate::services::NoteService;
+use crate::models::{BulkCreateResult, ChangeAction, ChangeEvent, Changed, CreateNoteDto, EntityType, ListOptions, ListSortField, MoveResult, Note, NoteLink, NoteStats, NoteSummary, NoteViewState, Paginated, SaveNoteViewStateDto, SearchDateRange, SearchHit, SortOrder, TagMatchMode, TitleCollation, UpdateNoteDto, WritingProgress, WritingStats};
+use crate::services::{AuditService, ChangeEventService, JumpIndexService, NoteService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
 
 /// Create a new note
 #[tauri::command]
-pub async fn create_note(data: CreateNoteDto) -> AppResult<Note> {
-    NoteService::create_note(data).await
+pub async fn create_note<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, data: CreateNoteDto) -> AppResult<Note> {
+    let args = json!({ "data": &data });
+    let result = AuditService::track(&state, "create_note", args, NoteService::create_note(&state, data)).await;
+    let result = ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Created)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// List all notes for a project
+/// Create many notes in a project in one transaction, reporting the invalid ones;
+/// with `atomic`, any invalid note means none are created
 #[tauri::command]
-pub async fn list_notes(project_id: String) -> AppResult<Vec<Note>> {
-    NoteService::list_notes(project_id).await
+pub async fn create_notes_bulk<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_id: String,
+    notes: Vec<CreateNoteDto>,
+    atomic: Option<bool>,
+) -> AppResult<BulkCreateResult> {
+    let atomic = atomic.unwrap_or(false);
+    // The notes themselves would swamp the audit log
+    let args = json!({ "project_id": &project_id, "count": notes.len(), "atomic": atomic });
+    let events_project_id = project_id.clone();
+    let result = AuditService::track(
+        &state,
+        "create_notes_bulk",
+        args,
+        NoteService::create_notes_bulk(&state, project_id, notes, atomic),
+    )
+    .await;
+    let result = ChangeEventService::notify(&app, result, |created| {
+        created
+            .created_ids
+            .iter()
+            .map(|id| ChangeEvent::note(id, Some(&events_project_id), ChangeAction::Created))
+            .collect()
+    });
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// List pinned notes for a project
+/// Capture a note in the inbox, without a project
 #[tauri::command]
-pub async fn list_pinned_notes(project_id: String) -> AppResult<Vec<Note>> {
-    NoteService::list_pinned_notes(project_id).await
+pub async fn create_inbox_note<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    title: String,
+    content: String,
+    tags: Option<Vec<String>>,
+) -> AppResult<Note> {
+    let args = json!({ "title": &title, "tags": &tags });
+    let result =
+        AuditService::track(&state, "create_inbox_note", args, NoteService::create_inbox_note(&state, title, content, tags)).await;
+    ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Created)])
+}
+
+/// List inbox notes, most recently updated first
+#[tauri::command]
+pub async fn list_inbox_notes(state: State<'_, AppState>) -> AppResult<Vec<Note>> {
+    logging::timed("list_inbox_notes", NoteService::list_inbox_notes(&state)).await
+}
+
+/// File a note into a project
+#[tauri::command]
+pub async fn move_note_to_project<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    note_id: String,
+    project_id: String,
+) -> AppResult<Note> {
+    let args = json!({ "note_id": &note_id, "project_id": &project_id });
+    let result = AuditService::track(
+        &state,
+        "move_note_to_project",
+        args,
+        NoteService::move_note_to_project(&state, note_id, project_id),
+    )
+    .await;
+    let result = ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// List a project's notes, optionally paged and sorted
+#[tauri::command]
+pub async fn list_notes(
+    state: State<'_, AppState>,
+    project_id: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort_by: Option<ListSortField>,
+    sort_order: Option<SortOrder>,
+) -> AppResult<Paginated<Note>> {
+    let options = ListOptions { limit, offset, sort_by, sort_order };
+    logging::timed("list_notes", NoteService::list_notes(&state, project_id, options)).await
+}
+
+/// List notes of a project sorted by title
+#[tauri::command]
+pub async fn list_notes_by_title(
+    state: State<'_, AppState>,
+    project_id: String,
+    collation: Option<TitleCollation>,
+) -> AppResult<Vec<Note>> {
+    logging::timed("list_notes_by_title", NoteService::list_notes_by_title(&state, project_id, collation)).await
+}
+
+/// List pinned notes of a project, most recently updated first or in their manual order
+#[tauri::command]
+pub async fn list_pinned_notes(
+    state: State<'_, AppState>,
+    project_id: String,
+    manual_order: Option<bool>,
+) -> AppResult<Vec<Note>> {
+    logging::timed("list_pinned_notes", NoteService::list_pinned_notes(&state, project_id, manual_order.unwrap_or(false))).await
+}
+
+/// Move a pinned note to a position (0-based) among the project's pinned notes
+#[tauri::command]
+pub async fn reorder_pinned_note(state: State<'_, AppState>, id: String, position: usize) -> AppResult<Vec<Note>> {
+    let args = json!({ "id": &id, "position": position });
+    AuditService::track(&state, "reorder_pinned_note", args, NoteService::reorder_pinned_note(&state, id, position)).await
 }
 
 /// List recent notes
 #[tauri::command]
-pub async fn list_recent_notes(project_id: String, limit: i32) -> AppResult<Vec<Note>> {
-    NoteService::list_recent_notes(project_id, limit).await
+pub async fn list_recent_notes(state: State<'_, AppState>, project_id: String, limit: i32) -> AppResult<Vec<Note>> {
+    logging::timed("list_recent_notes", NoteService::list_recent_notes(&state, project_id, limit)).await
+}
+
+/// List a project's most often opened notes
+#[tauri::command]
+pub async fn list_frequent_notes(state: State<'_, AppState>, project_id: String, limit: i32) -> AppResult<Vec<Note>> {
+    logging::timed("list_frequent_notes", NoteService::list_frequent_notes(&state, project_id, limit)).await
+}
+
+/// Get where a note was left in the editor
+#[tauri::command]
+pub async fn get_note_view_state(state: State<'_, AppState>, note_id: String) -> AppResult<Option<NoteViewState>> {
+    logging::timed("get_note_view_state", NoteService::get_note_view_state(&state, note_id)).await
+}
+
+/// Save where a note was left in the editor. Saved on every scroll, so it is
+/// neither audited nor announced as a change of the note.
+#[tauri::command]
+pub async fn save_note_view_state(
+    state: State<'_, AppState>,
+    note_id: String,
+    data: SaveNoteViewStateDto,
+) -> AppResult<NoteViewState> {
+    logging::timed("save_note_view_state", NoteService::save_note_view_state(&state, note_id, data)).await
 }
 
 /// Get note by ID
 #[tauri::command]
-pub async fn get_note(id: String) -> AppResult<Note> {
-    NoteService::get_note(id).await
+pub async fn get_note(state: State<'_, AppState>, id: String) -> AppResult<Note> {
+    logging::timed("get_note", NoteService::get_note(&state, id)).await
+}
+
+/// Update note (`force` overrides the note lock)
+#[tauri::command]
+pub async fn update_note<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateNoteDto,
+    force: Option<bool>,
+) -> AppResult<Note> {
+    let force = force.unwrap_or(false);
+    let args = json!({ "id": &id, "data": &data, "force": force });
+    let result = AuditService::track(&state, "update_note", args, NoteService::update_note(&state, id, data, force)).await;
+    let result = ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// Update note
+/// Update note like `update_note`, returning it before and after the update for undo
 #[tauri::command]
-pub async fn update_note(id: String, data: UpdateNoteDto) -> AppResult<Note> {
-    NoteService::update_note(id, data).await
+pub async fn update_note_v2<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateNoteDto,
+    force: Option<bool>,
+) -> AppResult<Changed<Note>> {
+    let force = force.unwrap_or(false);
+    let args = json!({ "id": &id, "data": &data, "force": force });
+    let result = AuditService::track(&state, "update_note_v2", args, NoteService::update_note_v2(&state, id, data, force)).await;
+    let result = ChangeEventService::notify(&app, result, |change| vec![ChangeEvent::note(&change.after.id, change.after.project_id.as_deref(), ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// Delete note
+/// Move note to the trash, or delete it for good with `permanent` (`force` overrides the note lock)
 #[tauri::command]
-pub async fn delete_note(id: String) -> AppResult<()> {
-    NoteService::delete_note(id).await
+pub async fn delete_note<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    force: Option<bool>,
+    permanent: Option<bool>,
+    delete_attachments: Option<bool>,
+) -> AppResult<()> {
+    let force = force.unwrap_or(false);
+    let permanent = permanent.unwrap_or(false);
+    let delete_attachments = delete_attachments.unwrap_or(false);
+    let args = json!({ "id": &id, "force": force, "permanent": permanent, "delete_attachments": delete_attachments });
+    let project_id = ChangeEventService::project_before_delete(&state, EntityType::Note, &id).await;
+    let event = ChangeEvent::note(&id, project_id.as_deref(), ChangeAction::Deleted);
+    let result = AuditService::track(
+        &state,
+        "delete_note",
+        args,
+        NoteService::delete_note(&state, id, force, permanent, delete_attachments),
+    )
+    .await;
+    let result = ChangeEventService::notify(&app, result, |_| vec![event]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
 /// Toggle pin status
 #[tauri::command]
-pub async fn toggle_note_pin(id: String) -> AppResult<Note> {
-    NoteService::toggle_pin(id).await
+pub async fn toggle_note_pin<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<Note> {
+    let args = json!({ "id": &id });
+    let result = AuditService::track(&state, "toggle_note_pin", args, NoteService::toggle_pin(&state, id)).await;
+    ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Updated)])
 }
 
-/// Duplicate note
+/// Duplicate a note, optionally into another project
 #[tauri::command]
-pub async fn duplicate_note(id: String, new_title: Option<String>) -> AppResult<Note> {
-    NoteService::duplicate_note(id, new_title).await
+pub async fn duplicate_note<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    new_title: Option<String>,
+    target_project_id: Option<String>,
+) -> AppResult<Note> {
+    let args = json!({ "id": &id, "new_title": &new_title, "target_project_id": &target_project_id });
+    let result = AuditService::track(
+        &state,
+        "duplicate_note",
+        args,
+        NoteService::duplicate_note(&state, id, new_title, target_project_id),
+    )
+    .await;
+    let result = ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Created)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// Search notes
+/// Search a project's notes, optionally including the inbox and only notes updated within a range
 #[tauri::command]
-pub async fn search_notes(project_id: String, query: String) -> AppResult<Vec<Note>> {
-    NoteService::search_notes(project_id, query).await
+pub async fn search_notes(
+    state: State<'_, AppState>,
+    project_id: String,
+    query: String,
+    include_inbox: Option<bool>,
+    range: Option<SearchDateRange>,
+) -> AppResult<Vec<SearchHit<Note>>> {
+    logging::timed(
+        "search_notes",
+        NoteService::search_notes(&state, project_id, query, include_inbox.unwrap_or(false), range),
+    )
+    .await
 }
 
 /// Get all tags for a project
 #[tauri::command]
-pub async fn get_note_tags(project_id: String) -> AppResult<Vec<String>> {
-    NoteService::get_all_tags(project_id).await
+pub async fn get_note_tags(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<String>> {
+    logging::timed("get_note_tags", NoteService::get_all_tags(&state, project_id)).await
+}
+
+/// Get notes carrying all or any (the default) of the given tags, or a tag below
+/// them, and none of the excluded tags
+#[tauri::command]
+pub async fn list_notes_by_tags(
+    state: State<'_, AppState>,
+    project_id: String,
+    tags: Vec<String>,
+    match_mode: Option<TagMatchMode>,
+    exclude_tags: Option<Vec<String>>,
+) -> AppResult<Vec<Note>> {
+    logging::timed(
+        "list_notes_by_tags",
+        NoteService::list_notes_by_tags(&state, project_id, tags, match_mode.unwrap_or_default(), exclude_tags.unwrap_or_default()),
+    )
+    .await
+}
+
+/// Move notes to another project
+#[tauri::command]
+pub async fn move_notes_to_project<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    note_ids: Vec<String>,
+    target_project_id: String,
+) -> AppResult<MoveResult> {
+    let args = json!({ "note_ids": &note_ids, "target_project_id": &target_project_id });
+    let result = AuditService::track(
+        &state,
+        "move_notes_to_project",
+        args,
+        NoteService::move_notes_to_project(&state, note_ids, target_project_id),
+    )
+    .await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Lock note against edits
+#[tauri::command]
+pub async fn lock_note<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<Note> {
+    let args = json!({ "id": &id });
+    let result = AuditService::track(&state, "lock_note", args, NoteService::lock_note(&state, id)).await;
+    ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Updated)])
+}
+
+/// Unlock note
+#[tauri::command]
+pub async fn unlock_note<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<Note> {
+    let args = json!({ "id": &id });
+    let result = AuditService::track(&state, "unlock_note", args, NoteService::unlock_note(&state, id)).await;
+    ChangeEventService::notify(&app, result, |note| vec![ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Updated)])
+}
+
+/// Copy a note as shareable plain text or markdown
+#[tauri::command]
+pub async fn copy_note_for_sharing(
+    state: State<'_, AppState>,
+    note_id: String,
+    format: String,
+    include_metadata: bool,
+) -> AppResult<String> {
+    logging::timed("copy_note_for_sharing", NoteService::copy_note_for_sharing(&state, note_id, format, include_metadata)).await
+}
+
+/// List the notes linking to a note
+#[tauri::command]
+pub async fn get_note_backlinks(state: State<'_, AppState>, note_id: String) -> AppResult<Vec<NoteSummary>> {
+    logging::timed("get_note_backlinks", NoteService::get_note_backlinks(&state, note_id)).await
+}
+
+/// List the wikilinks of a note
+#[tauri::command]
+pub async fn get_note_outgoing_links(state: State<'_, AppState>, note_id: String) -> AppResult<Vec<NoteLink>> {
+    logging::timed("get_note_outgoing_links", NoteService::get_note_outgoing_links(&state, note_id)).await
+}
+
+/// Get the word count, character count, heading count and reading time of a note
+#[tauri::command]
+pub async fn get_note_stats(state: State<'_, AppState>, note_id: String) -> AppResult<NoteStats> {
+    logging::timed("get_note_stats", NoteService::get_note_stats(&state, note_id)).await
+}
+
+/// Get the words written in a project per day between two timestamps
+#[tauri::command]
+pub async fn get_writing_stats(
+    state: State<'_, AppState>,
+    project_id: String,
+    from: i64,
+    to: i64,
+    timezone: Option<String>,
+) -> AppResult<WritingStats> {
+    logging::timed("get_writing_stats", NoteService::get_writing_stats(&state, project_id, from, to, timezone)).await
+}
+
+/// Set or clear a project's word-count goal
+#[tauri::command]
+pub async fn set_writing_goal(state: State<'_, AppState>, project_id: String, target_words: Option<i64>) -> AppResult<WritingProgress> {
+    let args = json!({ "project_id": &project_id, "target_words": target_words });
+    AuditService::track(&state, "set_writing_goal", args, NoteService::set_writing_goal(&state, project_id, target_words)).await
 }
 
-/// Get notes by tags
+/// Words of a project's chapter notes against its goal, with the last days' progress
 #[tauri::command]
-pub async fn list_notes_by_tags(project_id: String, tags: Vec<String>) -> AppResult<Vec<Note>> {
-    NoteService::list_notes_by_tags(project_id, tags).await
+pub async fn get_writing_progress(state: State<'_, AppState>, project_id: String) -> AppResult<WritingProgress> {
+    logging::timed("get_writing_progress", NoteService::get_writing_progress(&state, project_id)).await
 }
@@ -0,0 +1,48 @@
+use crate::error::AppResult;
+use crate::models::{CreateNoteTemplateDto, Note, NoteTemplate};
+use crate::services::{AuditService, JumpIndexService, NoteTemplateService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use std::collections::HashMap;
+use tauri::{AppHandle, Runtime, State};
+
+/// Create a new note template
+#[tauri::command]
+pub async fn create_note_template(state: State<'_, AppState>, data: CreateNoteTemplateDto) -> AppResult<NoteTemplate> {
+    let args = json!({ "data": &data });
+    AuditService::track(&state, "create_note_template", args, NoteTemplateService::create_note_template(&state, data)).await
+}
+
+/// List all note templates
+#[tauri::command]
+pub async fn list_note_templates(state: State<'_, AppState>) -> AppResult<Vec<NoteTemplate>> {
+    logging::timed("list_note_templates", NoteTemplateService::list_note_templates(&state)).await
+}
+
+/// Delete a note template
+#[tauri::command]
+pub async fn delete_note_template(state: State<'_, AppState>, id: String) -> AppResult<()> {
+    let args = json!({ "id": id });
+    AuditService::track(&state, "delete_note_template", args, NoteTemplateService::delete_note_template(&state, id)).await
+}
+
+/// Create a note from a template, filling in its placeholders
+#[tauri::command]
+pub async fn create_note_from_template<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    project_id: String,
+    template_id: String,
+    variables: Option<HashMap<String, String>>,
+) -> AppResult<Note> {
+    let args = json!({ "project_id": project_id, "template_id": template_id, "variables": &variables });
+    let result = AuditService::track(
+        &state,
+        "create_note_from_template",
+        args,
+        NoteTemplateService::create_note_from_template(&state, project_id, template_id, variables.unwrap_or_default()),
+    )
+    .await;
+    JumpIndexService::notify_changed(&app, result)
+}
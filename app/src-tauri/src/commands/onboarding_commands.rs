@@ -0,0 +1,20 @@
+use crate::error::AppResult;
+use crate::models::Project;
+use crate::services::{AuditService, OnboardingService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Whether the app is opened for the first time
+#[tauri::command]
+pub async fn is_first_run(state: State<'_, AppState>) -> AppResult<bool> {
+    logging::timed("is_first_run", OnboardingService::is_first_run(&state)).await
+}
+
+/// Create the sample project in a folder and mark the first run done
+#[tauri::command]
+pub async fn create_sample_project(state: State<'_, AppState>, dest_dir: String) -> AppResult<Project> {
+    let args = json!({ "dest_dir": dest_dir });
+    AuditService::track(&state, "create_sample_project", args, OnboardingService::create_sample_project(&state, dest_dir)).await
+}
@@ -1,42 +1,373 @@
 use crate::error::AppResult;
-use crate::models::{CreateProjectDto, Project, UpdateProjectDto};
-use crate::services::ProjectService;
+use crate::models::{
+    ChangeAction, ChangeEvent, Changed, CleanProjectFilesResult, CreateProjectDto, FileDiff, GitCommit, GitStatus, Project, ProjectDashboard, ProjectFileAction, ProjectFilesReport, ProjectFilterDto, ProjectGraph, ProjectSettings, ProjectSort, ProjectStats, ProjectSummary, ProjectWithCounts, TitleCollation,
+    UpdateProjectDto, UpdateProjectSettingsDto,
+};
+use crate::services::{AuditService, ChangeEventService, JumpIndexService, ProjectFilesService, ProjectService};
 use crate::state::AppState;
-use tauri::State;
+use crate::utils::{logging, redact};
+use serde_json::json;
+use std::collections::HashMap;
+use tauri::{AppHandle, Runtime, State};
 
 /// Create a new project
 #[tauri::command]
-pub async fn create_project(
+pub async fn create_project<R: Runtime>(
+    app: AppHandle<R>,
     state: State<'_, AppState>,
     data: CreateProjectDto,
 ) -> AppResult<Project> {
-    ProjectService::create_project(&state, data).await
+    let args = json!({ "data": &data });
+    let result = AuditService::track(&state, "create_project", args, ProjectService::create_project(&state, data)).await;
+    let result = ChangeEventService::notify(&app, result, |project| vec![ChangeEvent::project(&project.id, ChangeAction::Created)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// List all projects
+/// List projects, including archived ones when asked, optionally with favorites first
 #[tauri::command]
-pub async fn list_projects(state: State<'_, AppState>) -> AppResult<Vec<Project>> {
-    ProjectService::list_projects(&state).await
+pub async fn list_projects(
+    state: State<'_, AppState>,
+    include_archived: Option<bool>,
+    sort: Option<ProjectSort>,
+) -> AppResult<Vec<Project>> {
+    logging::timed("list_projects", ProjectService::list_projects(&state, include_archived, sort)).await
+}
+
+/// List projects with their open, done and overdue task counts and note count
+#[tauri::command]
+pub async fn list_projects_with_counts(
+    state: State<'_, AppState>,
+    include_archived: Option<bool>,
+    sort: Option<ProjectSort>,
+) -> AppResult<Vec<ProjectWithCounts>> {
+    logging::timed(
+        "list_projects_with_counts",
+        ProjectService::list_projects_with_counts(&state, include_archived, sort),
+    )
+    .await
+}
+
+/// List all projects sorted by name
+#[tauri::command]
+pub async fn list_projects_by_name(
+    state: State<'_, AppState>,
+    collation: Option<TitleCollation>,
+) -> AppResult<Vec<Project>> {
+    logging::timed("list_projects_by_name", ProjectService::list_projects_by_name(&state, collation)).await
+}
+
+/// Filter projects by status and tags
+#[tauri::command]
+pub async fn filter_projects(
+    state: State<'_, AppState>,
+    filter: ProjectFilterDto,
+) -> AppResult<Vec<Project>> {
+    logging::timed("filter_projects", ProjectService::filter_projects(&state, filter)).await
 }
 
 /// Get project by ID
 #[tauri::command]
 pub async fn get_project(state: State<'_, AppState>, id: String) -> AppResult<Project> {
-    ProjectService::get_project(&state, id).await
+    logging::timed("get_project", ProjectService::get_project(&state, id)).await
+}
+
+/// Get project with task, note and research question counts
+#[tauri::command]
+pub async fn get_project_summary(state: State<'_, AppState>, id: String) -> AppResult<ProjectSummary> {
+    logging::timed("get_project_summary", ProjectService::get_project_summary(&state, id)).await
+}
+
+/// Get task, note and tag statistics of a project
+#[tauri::command]
+pub async fn get_project_stats(state: State<'_, AppState>, project_id: String) -> AppResult<ProjectStats> {
+    logging::timed("get_project_stats", ProjectService::get_project_stats(&state, project_id)).await
+}
+
+/// Get everything the project dashboard shows in one call
+#[tauri::command]
+pub async fn get_project_dashboard(state: State<'_, AppState>, project_id: String) -> AppResult<ProjectDashboard> {
+    logging::timed("get_project_dashboard", ProjectService::get_project_dashboard(&state, project_id)).await
+}
+
+/// Get the graph of a project's notes, references and optionally tasks; nodes
+/// without edges are left out unless `include_orphans` is set
+#[tauri::command]
+pub async fn get_project_graph(
+    state: State<'_, AppState>,
+    project_id: String,
+    include_tasks: Option<bool>,
+    include_orphans: Option<bool>,
+) -> AppResult<ProjectGraph> {
+    let result = ProjectService::get_project_graph(
+        &state,
+        project_id,
+        include_tasks.unwrap_or(false),
+        include_orphans.unwrap_or(false),
+    );
+    logging::timed("get_project_graph", result).await
+}
+
+/// Get statistics of every project, keyed by project id
+#[tauri::command]
+pub async fn get_all_project_stats(state: State<'_, AppState>) -> AppResult<HashMap<String, ProjectStats>> {
+    logging::timed("get_all_project_stats", ProjectService::get_all_project_stats(&state)).await
+}
+
+/// Get the git working tree status of a project
+#[tauri::command]
+pub async fn get_project_git_status(state: State<'_, AppState>, project_id: String) -> AppResult<GitStatus> {
+    logging::timed("get_project_git_status", ProjectService::get_git_status(&state, project_id)).await
+}
+
+/// Get a page of a project's commit history
+#[tauri::command]
+pub async fn get_project_history(
+    state: State<'_, AppState>,
+    project_id: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> AppResult<Vec<GitCommit>> {
+    logging::timed("get_project_history", ProjectService::get_history(&state, project_id, limit, offset)).await
+}
+
+/// Move a project's directory to a new location
+#[tauri::command]
+pub async fn move_project<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String, new_path: String) -> AppResult<Project> {
+    let args = json!({ "id": &id, "new_path": &new_path });
+    let result = AuditService::track(&state, "move_project", args, ProjectService::move_project(&state, id, new_path)).await;
+    ChangeEventService::notify(&app, result, |project| vec![ChangeEvent::project(&project.id, ChangeAction::Updated)])
+}
+
+/// Point a project at a directory it was already moved to
+#[tauri::command]
+pub async fn relink_project<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String, existing_path: String) -> AppResult<Project> {
+    let args = json!({ "id": &id, "existing_path": &existing_path });
+    let result = AuditService::track(&state, "relink_project", args, ProjectService::relink_project(&state, id, existing_path)).await;
+    ChangeEventService::notify(&app, result, |project| vec![ChangeEvent::project(&project.id, ChangeAction::Updated)])
+}
+
+/// Get the changes of a project file since a commit, HEAD by default
+#[tauri::command]
+pub async fn get_file_diff(
+    state: State<'_, AppState>,
+    project_id: String,
+    relative_path: String,
+    commit: Option<String>,
+) -> AppResult<FileDiff> {
+    logging::timed("get_file_diff", ProjectService::get_file_diff(&state, project_id, relative_path, commit)).await
+}
+
+/// Add a git remote to a project, or change its URL
+#[tauri::command]
+pub async fn set_project_remote(
+    state: State<'_, AppState>,
+    project_id: String,
+    name: String,
+    url: String,
+) -> AppResult<()> {
+    let args = json!({ "project_id": &project_id, "name": &name, "url": redact::strip_url_credentials(&url) });
+    AuditService::track(&state, "set_project_remote", args, ProjectService::set_remote(&state, project_id, name, url)).await
+}
+
+/// Push a project to a git remote
+#[tauri::command]
+pub async fn push_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    remote: Option<String>,
+    branch: Option<String>,
+) -> AppResult<GitStatus> {
+    let args = json!({ "project_id": &project_id, "remote": &remote, "branch": &branch });
+    AuditService::track(&state, "push_project", args, ProjectService::push(&state, project_id, remote, branch)).await
+}
+
+/// Pull a project from a git remote, optionally stashing uncommitted changes
+#[tauri::command]
+pub async fn pull_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    remote: Option<String>,
+    branch: Option<String>,
+    stash: Option<bool>,
+) -> AppResult<GitStatus> {
+    let args = json!({ "project_id": &project_id, "remote": &remote, "branch": &branch, "stash": stash });
+    AuditService::track(
+        &state,
+        "pull_project",
+        args,
+        ProjectService::pull(&state, project_id, remote, branch, stash.unwrap_or(false)),
+    )
+    .await
+}
+
+/// Rewrite the managed block of a project's .gitignore, adding extra patterns
+#[tauri::command]
+pub async fn regenerate_gitignore(
+    state: State<'_, AppState>,
+    project_id: String,
+    extra_patterns: Option<Vec<String>>,
+) -> AppResult<String> {
+    let args = json!({ "project_id": &project_id, "extra_patterns": &extra_patterns });
+    AuditService::track(
+        &state,
+        "regenerate_gitignore",
+        args,
+        ProjectService::regenerate_gitignore(&state, project_id, extra_patterns.unwrap_or_default()),
+    )
+    .await
+}
+
+/// Create the missing subdirectories of a project's layout, returning those created
+#[tauri::command]
+pub async fn apply_layout_to_project(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<String>> {
+    let args = json!({ "project_id": &project_id });
+    AuditService::track(&state, "apply_layout_to_project", args, ProjectService::apply_layout_to_project(&state, project_id)).await
 }
 
 /// Update project
 #[tauri::command]
-pub async fn update_project(
+pub async fn update_project<R: Runtime>(
+    app: AppHandle<R>,
     state: State<'_, AppState>,
     id: String,
     data: UpdateProjectDto,
 ) -> AppResult<Project> {
-    ProjectService::update_project(&state, id, data).await
+    let args = json!({ "id": &id, "data": &data });
+    let result = AuditService::track(&state, "update_project", args, ProjectService::update_project(&state, id, data)).await;
+    let result = ChangeEventService::notify(&app, result, |project| vec![ChangeEvent::project(&project.id, ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Update project like `update_project`, returning it before and after the update for undo
+#[tauri::command]
+pub async fn update_project_v2<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateProjectDto,
+) -> AppResult<Changed<Project>> {
+    let args = json!({ "id": &id, "data": &data });
+    let result = AuditService::track(&state, "update_project_v2", args, ProjectService::update_project_v2(&state, id, data)).await;
+    let result = ChangeEventService::notify(&app, result, |change| vec![ChangeEvent::project(&change.after.id, ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
 /// Delete project
 #[tauri::command]
-pub async fn delete_project(state: State<'_, AppState>, id: String) -> AppResult<()> {
-    ProjectService::delete_project(&state, id).await
+pub async fn delete_project<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<()> {
+    let args = json!({ "id": &id });
+    let event = ChangeEvent::project(&id, ChangeAction::Deleted);
+    let result = AuditService::track(&state, "delete_project", args, ProjectService::delete_project(&state, id)).await;
+    let result = ChangeEventService::notify(&app, result, |_| vec![event]);
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Restore an archived project
+#[tauri::command]
+pub async fn restore_project<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<Project> {
+    let args = json!({ "id": &id });
+    let result = AuditService::track(&state, "restore_project", args, ProjectService::restore_project(&state, id)).await;
+    let result = ChangeEventService::notify(&app, result, |project| vec![ChangeEvent::project(&project.id, ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Make a project a favorite or stop it being one
+#[tauri::command]
+pub async fn toggle_project_favorite<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<Project> {
+    let args = json!({ "id": &id });
+    let result = AuditService::track(&state, "toggle_project_favorite", args, ProjectService::toggle_favorite(&state, id)).await;
+    ChangeEventService::notify(&app, result, |project| vec![ChangeEvent::project(&project.id, ChangeAction::Updated)])
+}
+
+/// Move a favorite project within the favorites
+#[tauri::command]
+pub async fn reorder_favorite(state: State<'_, AppState>, id: String, position: usize) -> AppResult<Vec<Project>> {
+    let args = json!({ "id": &id, "position": position });
+    AuditService::track(&state, "reorder_favorite", args, ProjectService::reorder_favorite(&state, id, position)).await
+}
+
+/// Permanently delete a project, optionally with its directory
+#[tauri::command]
+pub async fn purge_project<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    delete_files: bool,
+) -> AppResult<()> {
+    let args = json!({ "id": &id, "delete_files": delete_files });
+    let event = ChangeEvent::project(&id, ChangeAction::Deleted);
+    let result = AuditService::track(&state, "purge_project", args, ProjectService::purge_project(&state, id, delete_files)).await;
+    let result = ChangeEventService::notify(&app, result, |_| vec![event]);
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Get the task statuses allowed in a project
+#[tauri::command]
+pub async fn get_project_statuses(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<String>> {
+    logging::timed("get_project_statuses", ProjectService::get_project_statuses(&state, project_id)).await
+}
+
+/// Replace the task statuses allowed in a project, remapping tasks on removed statuses
+#[tauri::command]
+pub async fn set_project_statuses(
+    state: State<'_, AppState>,
+    project_id: String,
+    statuses: Vec<String>,
+    mapping: Option<HashMap<String, String>>,
+) -> AppResult<Vec<String>> {
+    let args = json!({ "project_id": &project_id, "statuses": &statuses, "mapping": &mapping });
+    AuditService::track(
+        &state,
+        "set_project_statuses",
+        args,
+        ProjectService::set_project_statuses(&state, project_id, statuses, mapping),
+    )
+    .await
+}
+
+/// Read the settings from a project's research.json
+#[tauri::command]
+pub async fn get_project_settings(state: State<'_, AppState>, project_id: String) -> AppResult<ProjectSettings> {
+    logging::timed("get_project_settings", ProjectService::get_project_settings(&state, project_id)).await
+}
+
+/// Change settings in a project's research.json
+#[tauri::command]
+pub async fn update_project_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+    settings: UpdateProjectSettingsDto,
+) -> AppResult<ProjectSettings> {
+    let args = json!({ "project_id": &project_id, "settings": &settings });
+    AuditService::track(
+        &state,
+        "update_project_settings",
+        args,
+        ProjectService::update_project_settings(&state, project_id, settings),
+    )
+    .await
+}
+
+/// Regenerate a missing or corrupt research.json from the project's database row
+#[tauri::command]
+pub async fn repair_project_metadata(state: State<'_, AppState>, project_id: String) -> AppResult<ProjectSettings> {
+    let args = json!({ "project_id": &project_id });
+    AuditService::track(&state, "repair_project_metadata", args, ProjectService::repair_project_metadata(&state, project_id)).await
+}
+
+/// Report orphaned, missing and stale files in a project's notes/ and attachment folders
+#[tauri::command]
+pub async fn analyze_project_files(state: State<'_, AppState>, project_id: String) -> AppResult<ProjectFilesReport> {
+    logging::timed("analyze_project_files", ProjectFilesService::analyze_project_files(&state, project_id)).await
+}
+
+/// Delete orphaned files and re-export stale notes selected from `analyze_project_files`
+#[tauri::command]
+pub async fn clean_project_files(
+    state: State<'_, AppState>,
+    project_id: String,
+    actions: Vec<ProjectFileAction>,
+) -> AppResult<CleanProjectFilesResult> {
+    let args = json!({ "project_id": &project_id, "actions": &actions });
+    AuditService::track(&state, "clean_project_files", args, ProjectFilesService::clean_project_files(&state, project_id, actions)).await
 }
@@ -0,0 +1,38 @@
+use crate::error::AppResult;
+use crate::models::{ProjectTemplate, SaveProjectTemplateOptions};
+use crate::services::{AuditService, ProjectTemplateService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Save a project's task tree and chosen notes as a template for new projects
+#[tauri::command]
+pub async fn save_project_as_template(
+    state: State<'_, AppState>,
+    project_id: String,
+    name: String,
+    options: Option<SaveProjectTemplateOptions>,
+) -> AppResult<ProjectTemplate> {
+    let args = json!({ "project_id": &project_id, "name": &name, "options": &options });
+    AuditService::track(
+        &state,
+        "save_project_as_template",
+        args,
+        ProjectTemplateService::save_project_as_template(&state, project_id, name, options.unwrap_or_default()),
+    )
+    .await
+}
+
+/// List all project templates
+#[tauri::command]
+pub async fn list_project_templates(state: State<'_, AppState>) -> AppResult<Vec<ProjectTemplate>> {
+    logging::timed("list_project_templates", ProjectTemplateService::list_project_templates(&state)).await
+}
+
+/// Delete a project template
+#[tauri::command]
+pub async fn delete_project_template(state: State<'_, AppState>, id: String) -> AppResult<()> {
+    let args = json!({ "id": &id });
+    AuditService::track(&state, "delete_project_template", args, ProjectTemplateService::delete_project_template(&state, id)).await
+}
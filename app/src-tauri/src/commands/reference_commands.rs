@@ -0,0 +1,115 @@
+use crate::error::AppResult;
+use crate::models::{CreateReferenceDto, DuplicateAction, Reference, ReferenceDuplicateGroup, ReferenceImportReport, UpdateReferenceDto};
+use crate::services::{AuditService, ReferenceService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Create a reference in a project's bibliography
+#[tauri::command]
+pub async fn create_reference(state: State<'_, AppState>, data: CreateReferenceDto) -> AppResult<Reference> {
+    let args = json!({ "data": &data });
+    AuditService::track(&state, "create_reference", args, ReferenceService::create_reference(&state, data)).await
+}
+
+/// List the references of a project
+#[tauri::command]
+pub async fn list_references(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<Reference>> {
+    logging::timed("list_references", ReferenceService::list_references(&state, project_id)).await
+}
+
+/// Get reference by ID
+#[tauri::command]
+pub async fn get_reference(state: State<'_, AppState>, id: String) -> AppResult<Reference> {
+    logging::timed("get_reference", ReferenceService::get_reference(&state, id)).await
+}
+
+/// Update reference
+#[tauri::command]
+pub async fn update_reference(
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateReferenceDto,
+) -> AppResult<Reference> {
+    let args = json!({ "id": &id, "data": &data });
+    AuditService::track(&state, "update_reference", args, ReferenceService::update_reference(&state, id, data)).await
+}
+
+/// Delete reference
+#[tauri::command]
+pub async fn delete_reference(state: State<'_, AppState>, id: String) -> AppResult<()> {
+    let args = json!({ "id": &id });
+    AuditService::track(&state, "delete_reference", args, ReferenceService::delete_reference(&state, id)).await
+}
+
+/// Group the references of a project that share a DOI or look alike
+#[tauri::command]
+pub async fn find_duplicate_references(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<ReferenceDuplicateGroup>> {
+    logging::timed("find_duplicate_references", ReferenceService::find_duplicate_references(&state, project_id)).await
+}
+
+/// Fold duplicate references into one, moving their citations to it
+#[tauri::command]
+pub async fn merge_references(state: State<'_, AppState>, keep_id: String, remove_ids: Vec<String>) -> AppResult<Reference> {
+    let args = json!({ "keep_id": &keep_id, "remove_ids": &remove_ids });
+    AuditService::track(&state, "merge_references", args, ReferenceService::merge_references(&state, keep_id, remove_ids)).await
+}
+
+/// Import BibTeX text or a .bib file into a project; duplicates are skipped unless told to update
+#[tauri::command]
+pub async fn import_references_bibtex(
+    state: State<'_, AppState>,
+    project_id: String,
+    bibtex_text_or_path: String,
+    on_duplicate: Option<DuplicateAction>,
+) -> AppResult<ReferenceImportReport> {
+    let on_duplicate = on_duplicate.unwrap_or_default();
+    // The text itself can be large; the audit log only notes how much was given
+    let args = json!({
+        "project_id": &project_id,
+        "input_len": bibtex_text_or_path.len(),
+        "on_duplicate": on_duplicate,
+    });
+    AuditService::track(
+        &state,
+        "import_references_bibtex",
+        args,
+        ReferenceService::import_bibtex(&state, project_id, bibtex_text_or_path, on_duplicate),
+    )
+    .await
+}
+
+/// BibTeX text of a project's references
+#[tauri::command]
+pub async fn export_references_bibtex(state: State<'_, AppState>, project_id: String) -> AppResult<String> {
+    logging::timed("export_references_bibtex", ReferenceService::export_bibtex(&state, project_id)).await
+}
+
+/// Record that a note cites a reference
+#[tauri::command]
+pub async fn cite_in_note(
+    state: State<'_, AppState>,
+    note_id: String,
+    reference_id: String,
+) -> AppResult<Vec<Reference>> {
+    let args = json!({ "note_id": &note_id, "reference_id": &reference_id });
+    AuditService::track(&state, "cite_in_note", args, ReferenceService::cite_in_note(&state, note_id, reference_id)).await
+}
+
+/// Remove a citation from a note
+#[tauri::command]
+pub async fn uncite_in_note(
+    state: State<'_, AppState>,
+    note_id: String,
+    reference_id: String,
+) -> AppResult<Vec<Reference>> {
+    let args = json!({ "note_id": &note_id, "reference_id": &reference_id });
+    AuditService::track(&state, "uncite_in_note", args, ReferenceService::uncite_in_note(&state, note_id, reference_id)).await
+}
+
+/// List the references a note cites
+#[tauri::command]
+pub async fn list_note_references(state: State<'_, AppState>, note_id: String) -> AppResult<Vec<Reference>> {
+    logging::timed("list_note_references", ReferenceService::list_note_references(&state, note_id)).await
+}
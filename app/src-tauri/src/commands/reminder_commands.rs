@@ -0,0 +1,21 @@
+use crate::error::AppResult;
+use crate::models::{ChangeAction, ChangeEvent, Task, TaskWithProject};
+use crate::services::{AuditService, ChangeEventService, ReminderService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// List task reminders not delivered yet, soonest first
+#[tauri::command]
+pub async fn list_pending_reminders(state: State<'_, AppState>) -> AppResult<Vec<TaskWithProject>> {
+    logging::timed("list_pending_reminders", ReminderService::list_pending_reminders(&state)).await
+}
+
+/// Deliver a task's reminder again after a number of minutes
+#[tauri::command]
+pub async fn snooze_reminder<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, task_id: String, minutes: i64) -> AppResult<Task> {
+    let args = json!({ "task_id": &task_id, "minutes": minutes });
+    let result = AuditService::track(&state, "snooze_reminder", args, ReminderService::snooze_reminder(&state, task_id, minutes)).await;
+    ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated)])
+}
@@ -0,0 +1,34 @@
+use crate::error::AppResult;
+use crate::models::{ProgressReportExport, ReportFormat, WeeklyDigest};
+use crate::services::{AuditService, DigestService, ReportService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Report what happened in a project between two instants, as Markdown by
+/// default, optionally committing it to the project's docs/reports/ folder
+#[tauri::command]
+pub async fn generate_progress_report(
+    state: State<'_, AppState>,
+    project_id: String,
+    from: i64,
+    to: i64,
+    format: Option<ReportFormat>,
+    auto_commit: Option<bool>,
+) -> AppResult<ProgressReportExport> {
+    let format = format.unwrap_or_default();
+    let auto_commit = auto_commit.unwrap_or(false);
+    logging::timed(
+        "generate_progress_report",
+        ReportService::generate_progress_report(&state, project_id, from, to, format, auto_commit),
+    ).await
+}
+
+/// Summarize what changed in the workspace since the last digest, saving it as
+/// a note of `save_to_project_id` or of the weekly_digest_project_id setting
+#[tauri::command]
+pub async fn generate_weekly_digest(state: State<'_, AppState>, save_to_project_id: Option<String>) -> AppResult<WeeklyDigest> {
+    let args = json!({ "save_to_project_id": &save_to_project_id });
+    AuditService::track(&state, "generate_weekly_digest", args, DigestService::generate_weekly_digest(&state, save_to_project_id)).await
+}
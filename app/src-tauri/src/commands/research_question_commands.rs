@@ -0,0 +1,97 @@
+use crate::error::AppResult;
+use crate::models::{CreateResearchQuestionDto, ResearchQuestion, ResearchQuestionLinks, UpdateResearchQuestionDto};
+use crate::services::{AuditService, ResearchQuestionService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Create a new research question
+#[tauri::command]
+pub async fn create_research_question(
+    state: State<'_, AppState>,
+    data: CreateResearchQuestionDto,
+) -> AppResult<ResearchQuestion> {
+    let args = json!({ "data": &data });
+    AuditService::track(&state, "create_research_question", args, ResearchQuestionService::create_question(&state, data)).await
+}
+
+/// List research questions of a project
+#[tauri::command]
+pub async fn list_research_questions(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<ResearchQuestion>> {
+    logging::timed("list_research_questions", ResearchQuestionService::list_questions(&state, project_id)).await
+}
+
+/// Get research question by ID
+#[tauri::command]
+pub async fn get_research_question(state: State<'_, AppState>, id: String) -> AppResult<ResearchQuestion> {
+    logging::timed("get_research_question", ResearchQuestionService::get_question(&state, id)).await
+}
+
+/// Update research question
+#[tauri::command]
+pub async fn update_research_question(
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateResearchQuestionDto,
+) -> AppResult<ResearchQuestion> {
+    let args = json!({ "id": &id, "data": &data });
+    AuditService::track(&state, "update_research_question", args, ResearchQuestionService::update_question(&state, id, data)).await
+}
+
+/// Delete research question
+#[tauri::command]
+pub async fn delete_research_question(state: State<'_, AppState>, id: String) -> AppResult<()> {
+    let args = json!({ "id": &id });
+    AuditService::track(&state, "delete_research_question", args, ResearchQuestionService::delete_question(&state, id)).await
+}
+
+/// Link a note to a research question
+#[tauri::command]
+pub async fn link_question_note(
+    state: State<'_, AppState>,
+    question_id: String,
+    note_id: String,
+) -> AppResult<ResearchQuestionLinks> {
+    let args = json!({ "question_id": &question_id, "note_id": &note_id });
+    AuditService::track(&state, "link_question_note", args, ResearchQuestionService::link_note(&state, question_id, note_id)).await
+}
+
+/// Unlink a note from a research question
+#[tauri::command]
+pub async fn unlink_question_note(
+    state: State<'_, AppState>,
+    question_id: String,
+    note_id: String,
+) -> AppResult<ResearchQuestionLinks> {
+    let args = json!({ "question_id": &question_id, "note_id": &note_id });
+    AuditService::track(&state, "unlink_question_note", args, ResearchQuestionService::unlink_note(&state, question_id, note_id)).await
+}
+
+/// Link a task to a research question
+#[tauri::command]
+pub async fn link_question_task(
+    state: State<'_, AppState>,
+    question_id: String,
+    task_id: String,
+) -> AppResult<ResearchQuestionLinks> {
+    let args = json!({ "question_id": &question_id, "task_id": &task_id });
+    AuditService::track(&state, "link_question_task", args, ResearchQuestionService::link_task(&state, question_id, task_id)).await
+}
+
+/// Unlink a task from a research question
+#[tauri::command]
+pub async fn unlink_question_task(
+    state: State<'_, AppState>,
+    question_id: String,
+    task_id: String,
+) -> AppResult<ResearchQuestionLinks> {
+    let args = json!({ "question_id": &question_id, "task_id": &task_id });
+    AuditService::track(&state, "unlink_question_task", args, ResearchQuestionService::unlink_task(&state, question_id, task_id)).await
+}
+
+/// List notes and tasks linked to a research question
+#[tauri::command]
+pub async fn list_question_links(state: State<'_, AppState>, question_id: String) -> AppResult<ResearchQuestionLinks> {
+    logging::timed("list_question_links", ResearchQuestionService::list_links(&state, question_id)).await
+}
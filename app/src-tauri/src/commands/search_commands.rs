@@ -0,0 +1,18 @@
+use crate::error::AppResult;
+use crate::models::{GlobalSearchResult, SearchDateRange};
+use crate::services::SearchService;
+use crate::state::AppState;
+use crate::utils::logging;
+use tauri::State;
+
+/// Search projects, tasks and notes across all non-archived projects, optionally
+/// only what was updated within a range such as this week or March
+#[tauri::command]
+pub async fn global_search(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+    range: Option<SearchDateRange>,
+) -> AppResult<Vec<GlobalSearchResult>> {
+    logging::timed("global_search", SearchService::global_search(&state, query, limit, range)).await
+}
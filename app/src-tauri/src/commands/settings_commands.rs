@@ -0,0 +1,25 @@
+use crate::error::AppResult;
+use crate::services::{AuditService, SettingsService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::{json, Map, Value};
+use tauri::State;
+
+/// Get an application setting, or its default when it was never set
+#[tauri::command]
+pub async fn get_setting(state: State<'_, AppState>, key: String) -> AppResult<Value> {
+    logging::timed("get_setting", SettingsService::get_setting(&state, key)).await
+}
+
+/// Change an application setting
+#[tauri::command]
+pub async fn set_setting(state: State<'_, AppState>, key: String, value: Value) -> AppResult<Value> {
+    let args = json!({ "key": &key, "value": &value });
+    AuditService::track(&state, "set_setting", args, SettingsService::set_setting(&state, key, value)).await
+}
+
+/// Get every application setting with its current or default value
+#[tauri::command]
+pub async fn get_all_settings(state: State<'_, AppState>) -> AppResult<Map<String, Value>> {
+    logging::timed("get_all_settings", SettingsService::get_all_settings(&state)).await
+}
@@ -0,0 +1,12 @@
+use crate::error::AppResult;
+use crate::models::StartupScanReport;
+use crate::services::StartupScanService;
+use crate::state::AppState;
+use crate::utils::logging;
+use tauri::State;
+
+/// Report of the last startup scan, None before the first one
+#[tauri::command]
+pub async fn get_startup_scan_report(state: State<'_, AppState>) -> AppResult<Option<StartupScanReport>> {
+    logging::timed("get_startup_scan_report", StartupScanService::get_startup_scan_report(&state)).await
+}
@@ -0,0 +1,41 @@
+use crate::error::AppResult;
+use crate::models::{Tag, TagNode};
+use crate::services::{AuditService, TagService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// List tags with usage counts as a tree of "/"-separated levels, optionally for one project
+#[tauri::command]
+pub async fn list_tags(state: State<'_, AppState>, project_id: Option<String>) -> AppResult<Vec<TagNode>> {
+    logging::timed("list_tags", TagService::list_tags(&state, project_id)).await
+}
+
+/// Rename a tag and the tags below it, merging into existing tags with the same names
+#[tauri::command]
+pub async fn rename_tag(state: State<'_, AppState>, id: String, new_name: String) -> AppResult<Tag> {
+    let args = json!({ "id": &id, "new_name": &new_name });
+    AuditService::track(&state, "rename_tag", args, TagService::rename_tag(&state, id, new_name)).await
+}
+
+/// Rename a level of the tag tree, including one that only exists through its children
+#[tauri::command]
+pub async fn rename_tag_path(state: State<'_, AppState>, path: String, new_path: String) -> AppResult<Vec<TagNode>> {
+    let args = json!({ "path": &path, "new_path": &new_path });
+    AuditService::track(&state, "rename_tag_path", args, TagService::rename_tag_path(&state, path, new_path)).await
+}
+
+/// Set or clear the color of a tag
+#[tauri::command]
+pub async fn set_tag_color(state: State<'_, AppState>, id: String, color: Option<String>) -> AppResult<Tag> {
+    let args = json!({ "id": &id, "color": &color });
+    AuditService::track(&state, "set_tag_color", args, TagService::set_tag_color(&state, id, color)).await
+}
+
+/// Delete a tag
+#[tauri::command]
+pub async fn delete_tag(state: State<'_, AppState>, id: String) -> AppResult<()> {
+    let args = json!({ "id": &id });
+    AuditService::track(&state, "delete_tag", args, TagService::delete_tag(&state, id)).await
+}
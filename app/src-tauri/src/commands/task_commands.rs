@@ -1,75 +1,349 @@
 use crate::error::AppResult;
-use crate::models::{CreateTaskDto, Task, UpdateTaskDto, TaskWithChildren};
-use crate::services::TaskService;
+use crate::models::{
+    ChangeAction, ChangeEvent, Changed, CreateTaskDto, EntityType, KanbanColumn, ListOptions, ListSortField, MoveResult, Paginated, RankTasksResult, SearchDateRange, SearchHit, SortOrder, TagMatchMode, Task, TaskFilterDto, TaskProgress, TitleCollation, UpdateTaskDto, TaskWithChildren,
+    TaskWithProject,
+};
+use crate::services::{AuditService, ChangeEventService, JumpIndexService, TaskService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
 
 /// Create a new task
 #[tauri::command]
-pub async fn create_task(data: CreateTaskDto) -> AppResult<Task> {
-    TaskService::create_task(data).await
+pub async fn create_task<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, data: CreateTaskDto) -> AppResult<Task> {
+    let args = json!({ "data": &data });
+    let result = AuditService::track(&state, "create_task", args, TaskService::create_task(&state, data)).await;
+    let result = ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Created)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// List all tasks for a project
+/// List a project's tasks, optionally paged and sorted
 #[tauri::command]
-pub async fn list_tasks(project_id: String) -> AppResult<Vec<Task>> {
-    TaskService::list_tasks(project_id).await
+pub async fn list_tasks(
+    state: State<'_, AppState>,
+    project_id: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort_by: Option<ListSortField>,
+    sort_order: Option<SortOrder>,
+) -> AppResult<Paginated<Task>> {
+    let options = ListOptions { limit, offset, sort_by, sort_order };
+    logging::timed("list_tasks", TaskService::list_tasks(&state, project_id, options)).await
 }
 
 /// List root tasks (no parent) for a project
 #[tauri::command]
-pub async fn list_root_tasks(project_id: String) -> AppResult<Vec<Task>> {
-    TaskService::list_root_tasks(project_id).await
+pub async fn list_root_tasks(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<Task>> {
+    logging::timed("list_root_tasks", TaskService::list_root_tasks(&state, project_id)).await
 }
 
 /// List subtasks for a parent task
 #[tauri::command]
-pub async fn list_subtasks(parent_id: String) -> AppResult<Vec<Task>> {
-    TaskService::list_subtasks(parent_id).await
+pub async fn list_subtasks(state: State<'_, AppState>, parent_id: String) -> AppResult<Vec<Task>> {
+    logging::timed("list_subtasks", TaskService::list_subtasks(&state, parent_id)).await
 }
 
 /// Get task by ID
 #[tauri::command]
-pub async fn get_task(id: String) -> AppResult<Task> {
-    TaskService::get_task(id).await
+pub async fn get_task(state: State<'_, AppState>, id: String) -> AppResult<Task> {
+    logging::timed("get_task", TaskService::get_task(&state, id)).await
 }
 
 /// Get task with all descendants (hierarchy)
 #[tauri::command]
-pub async fn get_task_hierarchy(id: String) -> AppResult<TaskWithChildren> {
-    TaskService::get_task_hierarchy(id).await
+pub async fn get_task_hierarchy(state: State<'_, AppState>, id: String) -> AppResult<TaskWithChildren> {
+    logging::timed("get_task_hierarchy", TaskService::get_task_hierarchy(&state, id)).await
 }
 
-/// Update task
+/// Update task; with `cascade`, completing it also completes its subtasks
 #[tauri::command]
-pub async fn update_task(id: String, data: UpdateTaskDto) -> AppResult<Task> {
-    TaskService::update_task(id, data).await
+pub async fn update_task<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateTaskDto,
+    cascade: Option<bool>,
+) -> AppResult<Task> {
+    let cascade = cascade.unwrap_or(false);
+    let args = json!({ "id": &id, "data": &data, "cascade": cascade });
+    let result = AuditService::track(&state, "update_task", args, TaskService::update_task(&state, id, data, cascade)).await;
+    let result = ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
-/// Delete task and all subtasks
+/// Update task like `update_task`, returning it before and after the update for undo
 #[tauri::command]
-pub async fn delete_task(id: String) -> AppResult<()> {
-    TaskService::delete_task(id).await
+pub async fn update_task_v2<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    data: UpdateTaskDto,
+    cascade: Option<bool>,
+) -> AppResult<Changed<Task>> {
+    let cascade = cascade.unwrap_or(false);
+    let args = json!({ "id": &id, "data": &data, "cascade": cascade });
+    let result = AuditService::track(&state, "update_task_v2", args, TaskService::update_task_v2(&state, id, data, cascade)).await;
+    let result = ChangeEventService::notify(&app, result, |change| vec![ChangeEvent::task(&change.after.id, &change.after.project_id, ChangeAction::Updated)]);
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Get completed and total descendant counts of a task
+#[tauri::command]
+pub async fn get_task_progress(state: State<'_, AppState>, id: String) -> AppResult<TaskProgress> {
+    logging::timed("get_task_progress", TaskService::get_task_progress(&state, id)).await
+}
+
+/// Move task and all subtasks to the trash, or delete them for good with `permanent`,
+/// returning how many tasks were deleted
+#[tauri::command]
+pub async fn delete_task<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    permanent: Option<bool>,
+) -> AppResult<usize> {
+    let permanent = permanent.unwrap_or(false);
+    let args = json!({ "id": &id, "permanent": permanent });
+    let project_id = ChangeEventService::project_before_delete(&state, EntityType::Task, &id).await;
+    let event = ChangeEvent::new(EntityType::Task, &id, project_id.as_deref(), ChangeAction::Deleted);
+    let result = AuditService::track(&state, "delete_task", args, TaskService::delete_task(&state, id, permanent)).await;
+    let result = ChangeEventService::notify(&app, result, |_| vec![event]);
+    JumpIndexService::notify_changed(&app, result)
 }
 
 /// Move task to a different parent
 #[tauri::command]
-pub async fn move_task(id: String, new_parent_id: Option<String>) -> AppResult<Task> {
-    TaskService::move_task(id, new_parent_id).await
+pub async fn move_task<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    new_parent_id: Option<String>,
+) -> AppResult<Task> {
+    let args = json!({ "id": &id, "new_parent_id": &new_parent_id });
+    let result = AuditService::track(&state, "move_task", args, TaskService::move_task(&state, id, new_parent_id)).await;
+    ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated)])
 }
 
 /// Reorder task
 #[tauri::command]
-pub async fn reorder_task(id: String, new_order: i32) -> AppResult<Task> {
-    TaskService::reorder_task(id, new_order).await
+pub async fn reorder_task<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String, new_order: i32) -> AppResult<Task> {
+    let args = json!({ "id": &id, "new_order": new_order });
+    let result = AuditService::track(&state, "reorder_task", args, TaskService::reorder_task(&state, id, new_order)).await;
+    ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated)])
 }
 
 /// List tasks by status
 #[tauri::command]
-pub async fn list_tasks_by_status(project_id: String, status: String) -> AppResult<Vec<Task>> {
-    TaskService::list_tasks_by_status(project_id, status).await
+pub async fn list_tasks_by_status(
+    state: State<'_, AppState>,
+    project_id: String,
+    status: String,
+) -> AppResult<Vec<Task>> {
+    logging::timed("list_tasks_by_status", TaskService::list_tasks_by_status(&state, project_id, status)).await
+}
+
+/// List unarchived tasks carrying all or any (the default) of the given tags, or
+/// a tag below them, and none of the excluded tags
+#[tauri::command]
+pub async fn list_tasks_by_tags(
+    state: State<'_, AppState>,
+    project_id: String,
+    tags: Vec<String>,
+    match_mode: Option<TagMatchMode>,
+    exclude_tags: Option<Vec<String>>,
+) -> AppResult<Vec<Task>> {
+    logging::timed(
+        "list_tasks_by_tags",
+        TaskService::list_tasks_by_tags(&state, project_id, tags, match_mode.unwrap_or_default(), exclude_tags.unwrap_or_default()),
+    )
+    .await
+}
+
+/// List tasks of a project matching a filter, optionally paged and sorted
+#[tauri::command]
+pub async fn filter_tasks(
+    state: State<'_, AppState>,
+    project_id: String,
+    filter: TaskFilterDto,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort_by: Option<ListSortField>,
+    sort_order: Option<SortOrder>,
+) -> AppResult<Paginated<Task>> {
+    let options = ListOptions { limit, offset, sort_by, sort_order };
+    logging::timed("filter_tasks", TaskService::filter_tasks(&state, project_id, filter, options)).await
+}
+
+/// List tasks of a project sorted by title
+#[tauri::command]
+pub async fn list_tasks_by_title(
+    state: State<'_, AppState>,
+    project_id: String,
+    collation: Option<TitleCollation>,
+) -> AppResult<Vec<Task>> {
+    logging::timed("list_tasks_by_title", TaskService::list_tasks_by_title(&state, project_id, collation)).await
+}
+
+/// Get a task by its human-readable key
+#[tauri::command]
+pub async fn get_task_by_key(
+    state: State<'_, AppState>,
+    project_id: String,
+    key: String,
+) -> AppResult<Task> {
+    logging::timed("get_task_by_key", TaskService::get_task_by_key(&state, project_id, key)).await
+}
+
+/// Search tasks, optionally within one status and only tasks updated within a range
+#[tauri::command]
+pub async fn search_tasks(
+    state: State<'_, AppState>,
+    project_id: String,
+    query: String,
+    status: Option<String>,
+    range: Option<SearchDateRange>,
+) -> AppResult<Vec<SearchHit<Task>>> {
+    logging::timed("search_tasks", TaskService::search_tasks(&state, project_id, query, status, range)).await
+}
+
+/// List open tasks across projects due within `days` days
+/// (`now` lets the caller pass a local day boundary)
+#[tauri::command]
+pub async fn list_upcoming_tasks(
+    state: State<'_, AppState>,
+    days: i64,
+    now: Option<i64>,
+) -> AppResult<Vec<TaskWithProject>> {
+    logging::timed("list_upcoming_tasks", TaskService::list_upcoming_tasks(&state, days, now)).await
+}
+
+/// List open tasks across projects that are past their due date
+#[tauri::command]
+pub async fn list_overdue_tasks(state: State<'_, AppState>, now: Option<i64>) -> AppResult<Vec<TaskWithProject>> {
+    logging::timed("list_overdue_tasks", TaskService::list_overdue_tasks(&state, now)).await
+}
+
+/// Move tasks to another project
+#[tauri::command]
+pub async fn move_tasks_to_project<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    task_ids: Vec<String>,
+    target_project_id: String,
+    keep_hierarchy: bool,
+) -> AppResult<MoveResult> {
+    let args = json!({
+        "task_ids": &task_ids,
+        "target_project_id": &target_project_id,
+        "keep_hierarchy": keep_hierarchy,
+    });
+    let result = AuditService::track(
+        &state,
+        "move_tasks_to_project",
+        args,
+        TaskService::move_tasks_to_project(&state, task_ids, target_project_id, keep_hierarchy),
+    )
+    .await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Stack-rank tasks of a project
+#[tauri::command]
+pub async fn rank_tasks(
+    state: State<'_, AppState>,
+    project_id: String,
+    ranked_ids: Vec<String>,
+) -> AppResult<RankTasksResult> {
+    let args = json!({ "project_id": &project_id, "ranked_ids": &ranked_ids });
+    AuditService::track(
+        &state,
+        "rank_tasks",
+        args,
+        TaskService::rank_tasks(&state, project_id, ranked_ids),
+    )
+    .await
+}
+
+/// List tasks of a project in stack-rank order
+#[tauri::command]
+pub async fn list_ranked_tasks(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<Task>> {
+    logging::timed("list_ranked_tasks", TaskService::list_ranked_tasks(&state, project_id)).await
+}
+
+/// Get a project's tasks grouped into Kanban columns by status
+#[tauri::command]
+pub async fn get_kanban_board(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<KanbanColumn>> {
+    logging::timed("get_kanban_board", TaskService::get_kanban_board(&state, project_id)).await
+}
+
+/// Move a task to a position in a Kanban column, changing its status
+#[tauri::command]
+pub async fn move_task_on_board<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    new_status: String,
+    new_position: usize,
+) -> AppResult<Task> {
+    let args = json!({ "id": &id, "new_status": &new_status, "new_position": new_position });
+    let result = AuditService::track(
+        &state,
+        "move_task_on_board",
+        args,
+        TaskService::move_task_on_board(&state, id, new_status, new_position),
+    )
+    .await;
+    ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated)])
+}
+
+/// Archive a project's done tasks completed before a time, returning how many were archived
+#[tauri::command]
+pub async fn archive_completed_tasks(
+    state: State<'_, AppState>,
+    project_id: String,
+    completed_before: i64,
+) -> AppResult<usize> {
+    let args = json!({ "project_id": &project_id, "completed_before": completed_before });
+    AuditService::track(
+        &state,
+        "archive_completed_tasks",
+        args,
+        TaskService::archive_completed_tasks(&state, project_id, completed_before),
+    )
+    .await
+}
+
+/// List a project's archived tasks, optionally paged
+#[tauri::command]
+pub async fn list_archived_tasks(
+    state: State<'_, AppState>,
+    project_id: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> AppResult<Paginated<Task>> {
+    let options = ListOptions { limit, offset, ..Default::default() };
+    logging::timed("list_archived_tasks", TaskService::list_archived_tasks(&state, project_id, options)).await
+}
+
+/// Bring an archived task back with its subtree
+#[tauri::command]
+pub async fn unarchive_task<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<Task> {
+    let args = json!({ "id": &id });
+    let result = AuditService::track(&state, "unarchive_task", args, TaskService::unarchive_task(&state, id)).await;
+    ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated)])
+}
+
+/// List a project's open repeating tasks, soonest due first
+#[tauri::command]
+pub async fn list_recurring_tasks(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<Task>> {
+    logging::timed("list_recurring_tasks", TaskService::list_recurring_tasks(&state, project_id)).await
 }
 
-/// Search tasks
+/// Skip the current occurrence of a repeating task, moving it to the next date of its rule
 #[tauri::command]
-pub async fn search_tasks(project_id: String, query: String) -> AppResult<Vec<Task>> {
-    TaskService::search_tasks(project_id, query).await
+pub async fn skip_next_occurrence<R: Runtime>(app: AppHandle<R>, state: State<'_, AppState>, id: String) -> AppResult<Task> {
+    let args = json!({ "id": &id });
+    let result = AuditService::track(&state, "skip_next_occurrence", args, TaskService::skip_next_occurrence(&state, id)).await;
+    ChangeEventService::notify(&app, result, |task| vec![ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated)])
 }
@@ -0,0 +1,62 @@
+use crate::error::AppResult;
+use crate::models::{TimeEntry, TimeSummary};
+use crate::services::{AuditService, TimeTrackingService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::State;
+
+/// Start a timer on a task; fails while another timer is running
+#[tauri::command]
+pub async fn start_timer(state: State<'_, AppState>, task_id: String) -> AppResult<TimeEntry> {
+    let args = json!({ "task_id": task_id });
+    AuditService::track(&state, "start_timer", args, TimeTrackingService::start_timer(&state, task_id)).await
+}
+
+/// Stop the running timer
+#[tauri::command]
+pub async fn stop_timer(state: State<'_, AppState>) -> AppResult<TimeEntry> {
+    AuditService::track(&state, "stop_timer", json!({}), TimeTrackingService::stop_timer(&state)).await
+}
+
+/// Get the running timer, if any
+#[tauri::command]
+pub async fn get_running_timer(state: State<'_, AppState>) -> AppResult<Option<TimeEntry>> {
+    logging::timed("get_running_timer", TimeTrackingService::get_running_timer(&state)).await
+}
+
+/// Record time spent on a task
+#[tauri::command]
+pub async fn add_manual_time_entry(
+    state: State<'_, AppState>,
+    task_id: String,
+    started_at: i64,
+    ended_at: i64,
+    note: Option<String>,
+) -> AppResult<TimeEntry> {
+    let args = json!({ "task_id": task_id, "started_at": started_at, "ended_at": ended_at });
+    AuditService::track(
+        &state,
+        "add_manual_time_entry",
+        args,
+        TimeTrackingService::add_manual_time_entry(&state, task_id, started_at, ended_at, note),
+    )
+    .await
+}
+
+/// List the time entries of a task, most recent first
+#[tauri::command]
+pub async fn list_time_entries(state: State<'_, AppState>, task_id: String) -> AppResult<Vec<TimeEntry>> {
+    logging::timed("list_time_entries", TimeTrackingService::list_time_entries(&state, task_id)).await
+}
+
+/// Get the time tracked on a project per task and per tag
+#[tauri::command]
+pub async fn get_time_summary(
+    state: State<'_, AppState>,
+    project_id: String,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> AppResult<TimeSummary> {
+    logging::timed("get_time_summary", TimeTrackingService::get_time_summary(&state, project_id, from, to)).await
+}
@@ -0,0 +1,41 @@
+use crate::error::AppResult;
+use crate::models::{ChangeAction, ChangeEvent, EntityType, TrashEntry};
+use crate::services::{AuditService, ChangeEventService, JumpIndexService, TrashService};
+use crate::state::AppState;
+use crate::utils::logging;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// List a project's trashed tasks and notes
+#[tauri::command]
+pub async fn list_trash(state: State<'_, AppState>, project_id: String) -> AppResult<Vec<TrashEntry>> {
+    logging::timed("list_trash", TrashService::list_trash(&state, project_id)).await
+}
+
+/// Restore a trashed task or note; a task whose parent is gone comes back as a root task
+#[tauri::command]
+pub async fn restore_from_trash<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    entity_type: EntityType,
+    id: String,
+) -> AppResult<TrashEntry> {
+    let args = json!({ "entity_type": entity_type, "id": &id });
+    let result = AuditService::track(&state, "restore_from_trash", args, TrashService::restore_from_trash(&state, entity_type, id)).await;
+    // A restored item reappears in lists just as a new one would
+    let result = ChangeEventService::notify(&app, result, |entry| {
+        vec![ChangeEvent::new(entry.entity_type, &entry.id, Some(&entry.project_id), ChangeAction::Created)]
+    });
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Permanently remove a project's trashed items, optionally only those older than some days
+#[tauri::command]
+pub async fn empty_trash(
+    state: State<'_, AppState>,
+    project_id: String,
+    older_than_days: Option<u32>,
+) -> AppResult<usize> {
+    let args = json!({ "project_id": &project_id, "older_than_days": older_than_days });
+    AuditService::track(&state, "empty_trash", args, TrashService::empty_trash(&state, project_id, older_than_days)).await
+}
@@ -0,0 +1,29 @@
+use crate::error::AppResult;
+use crate::models::{ChangeAction, ChangeEvent, EntityType, RevertChangeDto, RevertedEntity};
+use crate::services::{AuditService, ChangeEventService, JumpIndexService, UndoService};
+use crate::state::AppState;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// Undo an update made through a `*_v2` update command. Returns Conflict
+/// when the entity was changed again after that update.
+#[tauri::command]
+pub async fn revert_change<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    entity_type: EntityType,
+    id: String,
+    before_payload: RevertChangeDto,
+) -> AppResult<RevertedEntity> {
+    let args = json!({ "entity_type": entity_type, "id": &id, "updated_at": before_payload.updated_at });
+    let result = AuditService::track(&state, "revert_change", args, UndoService::revert_change(&state, entity_type, id, before_payload)).await;
+    let result = ChangeEventService::notify(&app, result, |entity| {
+        let event = match entity {
+            RevertedEntity::Project(project) => ChangeEvent::project(&project.id, ChangeAction::Updated),
+            RevertedEntity::Task(task) => ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Updated),
+            RevertedEntity::Note(note) => ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Updated),
+        };
+        vec![event]
+    });
+    JumpIndexService::notify_changed(&app, result)
+}
@@ -0,0 +1,32 @@
+use crate::error::AppResult;
+use crate::models::{RecentVault, VaultInfo};
+use crate::services::{AuditService, JumpIndexService, VaultService};
+use crate::state::AppState;
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+/// The folder and database file of the open vault
+#[tauri::command]
+pub async fn get_vault_path(state: State<'_, AppState>) -> AppResult<VaultInfo> {
+    VaultService::get_vault_path(&state).await
+}
+
+/// Switch to the vault in a folder, creating its database when `create` is set
+#[tauri::command]
+pub async fn open_vault<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    path: String,
+    create: Option<bool>,
+) -> AppResult<VaultInfo> {
+    let create = create.unwrap_or(false);
+    let args = json!({ "path": &path, "create": create });
+    let result = AuditService::track(&state, "open_vault", args, VaultService::open_vault(&app, &state, path, create)).await;
+    JumpIndexService::notify_changed(&app, result)
+}
+
+/// Vaults opened before on this machine, most recent first
+#[tauri::command]
+pub async fn list_recent_vaults() -> AppResult<Vec<RecentVault>> {
+    VaultService::list_recent_vaults().await
+}
@@ -1,20 +1,32 @@
+use serde::ser::SerializeStruct;
 use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::utils::{logging, sanitize};
 
 /// Application error types
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
-    /// Database operation failed
-    #[error("Database error: {0}")]
-    Database(String),
+    /// Database operation failed, with SQLite's extended result code when known
+    #[error("Database error: {message}")]
+    Database { message: String, extended_code: Option<i32> },
 
     /// File system operation failed
     #[error("File system error: {0}")]
-    FileSystem(#[from] std::io::Error),
+    FileSystem(#[source] std::io::Error),
 
     /// Git operation failed
     #[error("Git error: {0}")]
     Git(String),
 
+    /// Directory is not inside a git repository
+    #[error("Not a git repository: {0}")]
+    NotAGitRepository(String),
+
+    /// The git executable could not be started
+    #[error("Git is not installed or not on PATH")]
+    GitNotFound,
+
     /// Resource not found
     #[error("{0} not found: {1}")]
     NotFound(&'static str, String),
@@ -23,8 +35,7 @@ pub enum AppError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
-    /// Permission denied (reserved for future permission handling)
-    #[allow(dead_code)]
+    /// Permission denied
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
@@ -32,6 +43,10 @@ pub enum AppError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// Update made against an outdated version; `current` is the stored entity
+    #[error("Conflict: {entity} '{id}' was changed by another edit")]
+    EditConflict { entity: &'static str, id: String, current: Value },
+
     /// Internal server error
     #[error("Internal error: {0}")]
     Internal(String),
@@ -43,31 +58,222 @@ pub enum AppError {
     /// System error
     #[error("System error: {0}")]
     System(String),
+
+    /// Database stayed locked by another connection after retrying
+    #[error("Database is busy: {0}")]
+    Busy(String),
+
+    /// A project's research.json is missing or cannot be read
+    #[error("Project metadata is missing or invalid: {0}")]
+    InvalidProjectMetadata(String),
+
+    /// A network request failed or is not allowed in offline mode
+    #[error("Network error: {0}")]
+    Network(String),
+
+    /// Database was created by a newer version of the app
+    #[error("Database schema version {found} is newer than this app supports ({supported}); update the app to open it")]
+    SchemaTooNew { found: i64, supported: i64 },
+
+    /// Schema migrations are still running after startup
+    #[error("Database is upgrading (step {current_step} of {total_steps}); try again when it is done")]
+    Upgrading { current_step: usize, total_steps: usize },
+
+    /// Database file is damaged; `problems` lists what the integrity check found, when it ran
+    #[error("Database file is damaged; restore it from a backup")]
+    DatabaseCorrupt { problems: Vec<String> },
+}
+
+impl AppError {
+    /// Stable machine-readable code for the error variant
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database { .. } => "DATABASE_ERROR",
+            AppError::FileSystem(_) => "FILE_SYSTEM_ERROR",
+            AppError::Git(_) => "GIT_ERROR",
+            AppError::NotAGitRepository(_) => "NOT_A_GIT_REPOSITORY",
+            AppError::GitNotFound => "GIT_NOT_FOUND",
+            AppError::NotFound(_, _) => "NOT_FOUND",
+            AppError::InvalidInput(_) => "INVALID_INPUT",
+            AppError::PermissionDenied(_) => "PERMISSION_DENIED",
+            AppError::Conflict(_) | AppError::EditConflict { .. } => "CONFLICT",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+            AppError::Serialization(_) => "SERIALIZATION_ERROR",
+            AppError::System(_) => "SYSTEM_ERROR",
+            AppError::Busy(_) => "DATABASE_BUSY",
+            AppError::InvalidProjectMetadata(_) => "INVALID_PROJECT_METADATA",
+            AppError::Network(_) => "NETWORK_ERROR",
+            AppError::SchemaTooNew { .. } => "SCHEMA_TOO_NEW",
+            AppError::Upgrading { .. } => "DATABASE_UPGRADING",
+            AppError::DatabaseCorrupt { .. } => "DATABASE_CORRUPT",
+        }
+    }
+
+    /// Structured context for the frontend, for variants that have any
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            AppError::NotFound(entity, id) => Some(json!({
+                "entity": entity,
+                "id": sanitize::sanitize_message(id),
+            })),
+            AppError::Database { extended_code: Some(code), .. } => Some(json!({
+                "sqlite_code": code,
+            })),
+            AppError::EditConflict { entity, id, current } => Some(json!({
+                "entity": entity,
+                "id": id,
+                "current": current,
+            })),
+            AppError::SchemaTooNew { found, supported } => Some(json!({
+                "found": found,
+                "supported": supported,
+            })),
+            AppError::Upgrading { current_step, total_steps } => Some(json!({
+                "current_step": current_step,
+                "total_steps": total_steps,
+            })),
+            AppError::DatabaseCorrupt { problems } if !problems.is_empty() => Some(json!({
+                "problems": problems,
+            })),
+            _ => None,
+        }
+    }
+
+    /// Conflict for an update made against an outdated version, carrying the stored one
+    pub fn edit_conflict<T: Serialize>(entity: &'static str, id: &str, current: &T) -> Self {
+        AppError::EditConflict {
+            entity,
+            id: id.to_string(),
+            current: serde_json::to_value(current).unwrap_or(Value::Null),
+        }
+    }
+
+    /// Whether the failure means the connection itself is unusable (the file
+    /// was moved, replaced or could not be read) rather than the statement,
+    /// so reopening the database may fix it
+    pub fn is_connection_lost(&self) -> bool {
+        let AppError::Database { extended_code: Some(code), .. } = self else {
+            return false;
+        };
+        matches!(code & 0xff, SQLITE_IOERR | SQLITE_CANTOPEN) || *code == SQLITE_READONLY_DBMOVED
+    }
+
+    /// Message safe to show in the UI: no absolute paths outside the app data dir, length-capped
+    pub fn user_message(&self) -> String {
+        sanitize::sanitize_message(&self.to_string())
+    }
+}
+
+/// SQLite result codes of failures that leave a connection unusable
+const SQLITE_IOERR: i32 = 10;
+const SQLITE_CANTOPEN: i32 = 14;
+const SQLITE_READONLY_DBMOVED: i32 = 1032;
+
+/// Friendly messages for unique constraints, keyed by the constrained columns
+const UNIQUE_CONSTRAINT_MESSAGES: &[(&str, &str)] = &[
+    ("projects.path", "A project with this path already exists"),
+    ("tasks.project_id, tasks.task_key", "A task with this key already exists in the project"),
+    ("project_statuses.project_id, project_statuses.name", "This status already exists in the project"),
+    ("index 'idx_time_entries_running'", "A timer is already running"),
+    ("note_templates.name", "A template with this name already exists"),
+    ("references.project_id, references.citation_key", "A reference with this citation key already exists in the project"),
+];
+
+/// Map a SQLite failure to a message that does not leak SQL, parameters or paths
+fn friendly_database_message(err: &rusqlite::Error) -> String {
+    use rusqlite::ErrorCode;
+
+    let rusqlite::Error::SqliteFailure(failure, detail) = err else {
+        return "A database error occurred".into();
+    };
+
+    let message = match failure.code {
+        ErrorCode::ConstraintViolation => {
+            let detail = detail.as_deref().unwrap_or_default();
+            if let Some(columns) = detail.strip_prefix("UNIQUE constraint failed: ") {
+                UNIQUE_CONSTRAINT_MESSAGES
+                    .iter()
+                    .find(|(key, _)| *key == columns)
+                    .map_or("An item with the same value already exists", |(_, message)| message)
+            } else if detail.starts_with("FOREIGN KEY constraint failed") {
+                "The referenced item does not exist"
+            } else if detail.starts_with("NOT NULL constraint failed") {
+                "A required field is missing"
+            } else {
+                "The change conflicts with existing data"
+            }
+        }
+        ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => "The database is busy, please try again",
+        ErrorCode::ReadOnly => "The database is read-only",
+        ErrorCode::DiskFull => "The disk is full",
+        ErrorCode::CannotOpen => "The database could not be opened",
+        ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => "The database file is damaged",
+        _ => "A database error occurred",
+    };
+
+    message.to_string()
 }
 
 /// Result type alias for application operations
 pub type AppResult<T> = Result<T, AppError>;
 
-/// Serialization for sending errors to frontend
+/// Serialization for sending errors to frontend as `{ code, message, details? }`
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let details = self.details();
+        let mut error = serializer.serialize_struct("AppError", 2 + usize::from(details.is_some()))?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.user_message())?;
+        match details {
+            Some(details) => error.serialize_field("details", &details)?,
+            None => error.skip_field("details")?,
+        }
+        error.end()
     }
 }
 
 /// Convert from rusqlite error
 impl From<rusqlite::Error> for AppError {
     fn from(err: rusqlite::Error) -> Self {
-        AppError::Database(err.to_string())
+        // The raw message can contain SQL, bound values and paths: log it, don't send it
+        tracing::error!("Database error: {}", logging::error_chain(&err));
+
+        let message = friendly_database_message(&err);
+        match &err {
+            rusqlite::Error::SqliteFailure(failure, _)
+                if matches!(failure.code, rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) =>
+            {
+                AppError::Busy(message)
+            }
+            rusqlite::Error::SqliteFailure(failure, _)
+                if matches!(failure.code, rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase) =>
+            {
+                AppError::DatabaseCorrupt { problems: Vec::new() }
+            }
+            rusqlite::Error::SqliteFailure(failure, _) => AppError::Database {
+                message,
+                extended_code: Some(failure.extended_code),
+            },
+            _ => AppError::Database { message, extended_code: None },
+        }
+    }
+}
+
+/// Convert from io error
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        tracing::warn!("File system error: {}", logging::error_chain(&err));
+        AppError::FileSystem(err)
     }
 }
 
 /// Convert from serde_json error
 impl From<serde_json::Error> for AppError {
     fn from(err: serde_json::Error) -> Self {
+        tracing::warn!("Serialization error: {}", logging::error_chain(&err));
         AppError::Serialization(err.to_string())
     }
 }
@@ -75,6 +281,145 @@ impl From<serde_json::Error> for AppError {
 /// Convert from anyhow error
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
+        tracing::error!("Internal error: {:#}", err);
         AppError::Internal(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_json(error: AppError) -> Value {
+        serde_json::to_value(error).unwrap()
+    }
+
+    #[test]
+    fn every_variant_serializes_to_code_message_and_details() {
+        let io = || std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let cases = vec![
+            (
+                AppError::Database { message: "A database error occurred".into(), extended_code: None },
+                json!({ "code": "DATABASE_ERROR", "message": "Database error: A database error occurred" }),
+            ),
+            (
+                AppError::Database { message: "The disk is full".into(), extended_code: Some(13) },
+                json!({ "code": "DATABASE_ERROR", "message": "Database error: The disk is full", "details": { "sqlite_code": 13 } }),
+            ),
+            (AppError::FileSystem(io()), json!({ "code": "FILE_SYSTEM_ERROR", "message": "File system error: no such file" })),
+            (AppError::Git("merge failed".into()), json!({ "code": "GIT_ERROR", "message": "Git error: merge failed" })),
+            (
+                AppError::NotAGitRepository("thesis".into()),
+                json!({ "code": "NOT_A_GIT_REPOSITORY", "message": "Not a git repository: thesis" }),
+            ),
+            (AppError::GitNotFound, json!({ "code": "GIT_NOT_FOUND", "message": "Git is not installed or not on PATH" })),
+            (
+                AppError::NotFound("Task", "t-1".into()),
+                json!({ "code": "NOT_FOUND", "message": "Task not found: t-1", "details": { "entity": "Task", "id": "t-1" } }),
+            ),
+            (AppError::InvalidInput("Title is empty".into()), json!({ "code": "INVALID_INPUT", "message": "Invalid input: Title is empty" })),
+            (
+                AppError::PermissionDenied("Note is locked".into()),
+                json!({ "code": "PERMISSION_DENIED", "message": "Permission denied: Note is locked" }),
+            ),
+            (AppError::Conflict("Name taken".into()), json!({ "code": "CONFLICT", "message": "Conflict: Name taken" })),
+            (
+                AppError::EditConflict { entity: "Note", id: "n-1".into(), current: json!({ "title": "Newer" }) },
+                json!({
+                    "code": "CONFLICT",
+                    "message": "Conflict: Note 'n-1' was changed by another edit",
+                    "details": { "entity": "Note", "id": "n-1", "current": { "title": "Newer" } },
+                }),
+            ),
+            (AppError::Internal("oops".into()), json!({ "code": "INTERNAL_ERROR", "message": "Internal error: oops" })),
+            (AppError::Serialization("bad JSON".into()), json!({ "code": "SERIALIZATION_ERROR", "message": "Serialization error: bad JSON" })),
+            (AppError::System("no tray".into()), json!({ "code": "SYSTEM_ERROR", "message": "System error: no tray" })),
+            (AppError::Busy("locked".into()), json!({ "code": "DATABASE_BUSY", "message": "Database is busy: locked" })),
+            (
+                AppError::InvalidProjectMetadata("missing name".into()),
+                json!({ "code": "INVALID_PROJECT_METADATA", "message": "Project metadata is missing or invalid: missing name" }),
+            ),
+            (AppError::Network("offline".into()), json!({ "code": "NETWORK_ERROR", "message": "Network error: offline" })),
+            (
+                AppError::SchemaTooNew { found: 30, supported: 25 },
+                json!({
+                    "code": "SCHEMA_TOO_NEW",
+                    "message": "Database schema version 30 is newer than this app supports (25); update the app to open it",
+                    "details": { "found": 30, "supported": 25 },
+                }),
+            ),
+            (
+                AppError::Upgrading { current_step: 2, total_steps: 5 },
+                json!({
+                    "code": "DATABASE_UPGRADING",
+                    "message": "Database is upgrading (step 2 of 5); try again when it is done",
+                    "details": { "current_step": 2, "total_steps": 5 },
+                }),
+            ),
+            (
+                AppError::DatabaseCorrupt { problems: Vec::new() },
+                json!({ "code": "DATABASE_CORRUPT", "message": "Database file is damaged; restore it from a backup" }),
+            ),
+            (
+                AppError::DatabaseCorrupt { problems: vec!["page 4 is never used".into()] },
+                json!({
+                    "code": "DATABASE_CORRUPT",
+                    "message": "Database file is damaged; restore it from a backup",
+                    "details": { "problems": ["page 4 is never used"] },
+                }),
+            ),
+        ];
+
+        for (error, expected) in cases {
+            let display = error.to_string();
+            assert_eq!(to_json(error), expected, "serializing {}", display);
+        }
+    }
+
+    #[test]
+    fn sqlite_failures_carry_friendly_messages_and_extended_codes() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE projects (path TEXT UNIQUE); INSERT INTO projects VALUES ('/a');").unwrap();
+        let error: AppError = conn.execute("INSERT INTO projects VALUES ('/a')", []).unwrap_err().into();
+        assert_eq!(
+            to_json(error),
+            json!({
+                "code": "DATABASE_ERROR",
+                "message": "Database error: A project with this path already exists",
+                // SQLITE_CONSTRAINT_UNIQUE
+                "details": { "sqlite_code": 2067 },
+            })
+        );
+
+        let error: AppError = rusqlite::Error::QueryReturnedNoRows.into();
+        assert_eq!(to_json(error), json!({ "code": "DATABASE_ERROR", "message": "Database error: A database error occurred" }));
+    }
+
+    #[test]
+    fn paths_outside_the_app_data_dir_are_not_sent() {
+        let error = AppError::NotFound("Project", "/home/someone/private/thesis".into());
+        let json = to_json(error);
+        assert!(!json["message"].as_str().unwrap().contains("/home/someone"));
+        assert!(!json["details"]["id"].as_str().unwrap().contains("/home/someone"));
+        // Display keeps the full text for the log
+        assert!(AppError::NotFound("Project", "/home/someone/private".into()).to_string().contains("/home/someone/private"));
+    }
+
+    #[test]
+    fn only_failures_of_the_connection_count_as_lost() {
+        let database = |code| AppError::Database { message: "failed".into(), extended_code: code };
+        // SQLITE_IOERR, SQLITE_IOERR_READ, SQLITE_CANTOPEN, SQLITE_READONLY_DBMOVED
+        for lost in [10, 266, 14, 1032] {
+            assert!(database(Some(lost)).is_connection_lost(), "code {}", lost);
+        }
+        // SQLITE_BUSY, SQLITE_LOCKED, SQLITE_READONLY, SQLITE_CONSTRAINT_UNIQUE
+        for kept in [5, 6, 8, 2067] {
+            assert!(!database(Some(kept)).is_connection_lost(), "code {}", kept);
+        }
+        assert!(!database(None).is_connection_lost());
+        assert!(!AppError::System("Database not initialized".into()).is_connection_lost());
+
+        let missing = rusqlite::Connection::open("/nonexistent-dir/research.db").unwrap_err();
+        assert!(AppError::from(missing).is_connection_lost());
+    }
+}
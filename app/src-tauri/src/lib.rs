@@ -2,6 +2,7 @@
 //! 
 //! Core library for the Research Management desktop application.
 
+pub mod cli;
 pub mod commands;
 pub mod error;
 pub mod models;
@@ -10,3 +11,275 @@ pub mod state;
 pub mod utils;
 
 pub use error::{AppError, AppResult};
+
+/// Declares the command list once, for the app and for the command tests
+macro_rules! app_commands {
+    ($($command:ident),* $(,)?) => {
+        /// Name of every command the frontend can invoke
+        pub const COMMAND_NAMES: &[&str] = &[$(stringify!($command)),*];
+
+        /// Register every command on `builder`, whatever runtime it targets
+        pub fn register_commands<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::Builder<R> {
+            use commands::*;
+            builder.invoke_handler(tauri::generate_handler![$($command),*])
+        }
+    };
+}
+
+app_commands![
+    // Bootstrap commands
+    bootstrap,
+    // Project commands
+    create_project,
+    list_projects,
+    filter_projects,
+    list_projects_by_name,
+    list_projects_with_counts,
+    get_project,
+    get_project_summary,
+    get_project_stats,
+    get_project_dashboard,
+    get_project_graph,
+    get_all_project_stats,
+    get_project_git_status,
+    get_project_history,
+    move_project,
+    relink_project,
+    get_file_diff,
+    set_project_remote,
+    push_project,
+    pull_project,
+    regenerate_gitignore,
+    apply_layout_to_project,
+    update_project,
+    update_project_v2,
+    delete_project,
+    restore_project,
+    toggle_project_favorite,
+    reorder_favorite,
+    purge_project,
+    get_project_statuses,
+    set_project_statuses,
+    get_project_settings,
+    update_project_settings,
+    repair_project_metadata,
+    analyze_project_files,
+    clean_project_files,
+    // Task commands
+    create_task,
+    list_tasks,
+    get_task,
+    update_task,
+    update_task_v2,
+    get_task_progress,
+    delete_task,
+    list_root_tasks,
+    list_subtasks,
+    get_task_hierarchy,
+    move_task,
+    reorder_task,
+    list_tasks_by_status,
+    list_tasks_by_tags,
+    filter_tasks,
+    list_tasks_by_title,
+    get_task_by_key,
+    search_tasks,
+    move_tasks_to_project,
+    rank_tasks,
+    list_ranked_tasks,
+    list_upcoming_tasks,
+    list_overdue_tasks,
+    get_kanban_board,
+    move_task_on_board,
+    archive_completed_tasks,
+    list_archived_tasks,
+    unarchive_task,
+    list_recurring_tasks,
+    skip_next_occurrence,
+    // Note commands
+    create_note,
+    list_notes,
+    get_note,
+    update_note,
+    update_note_v2,
+    delete_note,
+    list_notes_by_title,
+    list_pinned_notes,
+    reorder_pinned_note,
+    list_recent_notes,
+    list_frequent_notes,
+    get_note_view_state,
+    save_note_view_state,
+    toggle_note_pin,
+    duplicate_note,
+    search_notes,
+    get_note_tags,
+    list_notes_by_tags,
+    move_notes_to_project,
+    lock_note,
+    unlock_note,
+    copy_note_for_sharing,
+    get_note_backlinks,
+    get_note_outgoing_links,
+    get_note_stats,
+    get_writing_stats,
+    set_writing_goal,
+    get_writing_progress,
+    create_inbox_note,
+    list_inbox_notes,
+    move_note_to_project,
+    create_notes_bulk,
+    // Audit commands
+    list_audit_log,
+    export_audit_log_csv,
+    // Automation commands
+    start_automation_server,
+    stop_automation_server,
+    get_automation_status,
+    get_automation_token,
+    get_startup_scan_report,
+    // Backup commands
+    run_backup_now,
+    create_portable_backup,
+    restore_portable_backup,
+    // Deadline commands
+    create_deadline,
+    list_deadlines,
+    list_upcoming_deadlines,
+    get_today_view,
+    get_deadline,
+    update_deadline,
+    delete_deadline,
+    import_deadlines_feed,
+    // Context commands
+    export_project_context,
+    // Metadata commands
+    get_entity_metadata,
+    set_entity_metadata,
+    is_first_run,
+    create_sample_project,
+    // Jump index commands
+    get_jump_index,
+    // Health commands
+    list_orphaned_entities,
+    adopt_orphans,
+    purge_orphans,
+    repair_database,
+    get_db_info,
+    get_schema_version,
+    get_migration_status,
+    reconnect_database,
+    checkpoint_database,
+    check_database_health,
+    optimize_database,
+    get_recent_logs,
+    set_log_level,
+    // Activity commands
+    get_activity_heatmap,
+    list_activity,
+    clear_activity,
+    // Research question commands
+    create_research_question,
+    list_research_questions,
+    get_research_question,
+    update_research_question,
+    delete_research_question,
+    link_question_note,
+    unlink_question_note,
+    link_question_task,
+    unlink_question_task,
+    list_question_links,
+    // Reference commands
+    create_reference,
+    list_references,
+    get_reference,
+    update_reference,
+    delete_reference,
+    find_duplicate_references,
+    merge_references,
+    import_references_bibtex,
+    export_references_bibtex,
+    cite_in_note,
+    uncite_in_note,
+    list_note_references,
+    // Reminder commands
+    list_pending_reminders,
+    snooze_reminder,
+    // Tag commands
+    list_tags,
+    rename_tag,
+    rename_tag_path,
+    set_tag_color,
+    delete_tag,
+    // File index commands
+    index_project_files,
+    list_project_files,
+    set_file_ignored,
+    get_ignore_patterns,
+    set_ignore_patterns,
+    search_project_files,
+    list_files,
+    get_file_info,
+    get_largest_files,
+    get_changed_files,
+    find_duplicate_files,
+    replace_with_symlink,
+    // Export commands
+    export_project,
+    import_project,
+    export_notes_markdown,
+    import_notes_markdown,
+    export_tasks_ical,
+    export_all_tasks_ical,
+    export_tasks_csv,
+    import_tasks_csv,
+    export_note_html,
+    export_project_html,
+    export_references_csv,
+    export_project_site,
+    clear_render_cache,
+    export_note_bundle,
+    import_note_bundle,
+    verify_export,
+    // Report commands
+    generate_progress_report,
+    generate_weekly_digest,
+    // Trash commands
+    list_trash,
+    restore_from_trash,
+    empty_trash,
+    // Undo commands
+    revert_change,
+    // Search commands
+    global_search,
+    // Settings commands
+    get_setting,
+    set_setting,
+    get_all_settings,
+    // Vault commands
+    get_vault_path,
+    open_vault,
+    list_recent_vaults,
+    // Time tracking commands
+    start_timer,
+    stop_timer,
+    get_running_timer,
+    add_manual_time_entry,
+    list_time_entries,
+    get_time_summary,
+    // Note template commands
+    create_note_template,
+    list_note_templates,
+    delete_note_template,
+    create_note_from_template,
+    // Project template commands
+    save_project_as_template,
+    list_project_templates,
+    delete_project_template,
+    // Note attachment commands
+    attach_file_to_note,
+    list_note_attachments,
+    remove_attachment,
+    open_attachment,
+    get_attachment_store_stats,
+];
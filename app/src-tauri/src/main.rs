@@ -1,29 +1,16 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod commands;
-mod error;
-mod models;
-mod services;
-mod state;
-mod utils;
-
-use commands::{
-    // Project commands
-    create_project, list_projects, get_project, update_project, delete_project,
-    // Task commands
-    create_task, list_tasks, get_task, update_task, delete_task,
-    list_root_tasks, list_subtasks, get_task_hierarchy,
-    move_task, reorder_task, list_tasks_by_status, search_tasks,
-    // Note commands
-    create_note, list_notes, get_note, update_note, delete_note,
-    list_pinned_notes, list_recent_notes, toggle_note_pin, duplicate_note,
-    search_notes, get_note_tags, list_notes_by_tags,
-};
-use state::AppState;
+use research_management_lib::{cli, register_commands, services, state::AppState, utils};
 
 fn main() {
-    tauri::Builder::default()
+    // Started from a terminal with a command: run it and exit without a window
+    let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    if cli::is_cli_invocation(&args) {
+        std::process::exit(cli::run(args));
+    }
+
+    register_commands(tauri::Builder::default())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_sql::Builder::new().build())
         .plugin(tauri_plugin_fs::init())
@@ -41,50 +28,35 @@ fn main() {
                 std::fs::create_dir_all(&app_data_dir).unwrap();
             }
             
-            let db_path = app_data_dir.join("research.db");
-            let state = app_handle.state::<AppState>();
-            
-            if let Err(e) = state.init_db(db_path.to_str().unwrap()) {
-                eprintln!("Failed to initialize database: {}", e);
-                return Err(e.into());
+            utils::sanitize::set_app_data_dir(&app_data_dir.to_string_lossy());
+
+            if let Err(e) = utils::logging::init(Some(&app_data_dir.join("logs")), utils::logging::LogLevel::Info) {
+                eprintln!("Failed to open the log directory: {}", e);
             }
+
+            let db_path = services::VaultService::startup_vault(&app_data_dir).join(services::VAULT_DB_FILE);
+
+            // Upgrades of large databases can take a while; the window shows their progress
+            let ready = app_handle.clone();
+            services::HealthService::open_database_in_background(app_handle, db_path.to_string_lossy().into_owned(), move || {
+                let app_handle = &ready;
+                tauri::async_runtime::spawn(services::BackupService::run_scheduler(app_handle.clone()));
+                tauri::async_runtime::spawn(services::ReminderService::run_scheduler(app_handle.clone()));
+                tauri::async_runtime::spawn(services::AuditService::run_pruner(app_handle.clone()));
+                tauri::async_runtime::spawn(services::DigestService::run_scheduler(app_handle.clone()));
+                services::AutomationService::start_if_enabled(app_handle);
+                services::StartupScanService::start_if_enabled(app_handle);
+                services::NoteAttachmentService::start_store_pass(app_handle);
+            });
             
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            // Project commands
-            create_project,
-            list_projects,
-            get_project,
-            update_project,
-            delete_project,
-            // Task commands
-            create_task,
-            list_tasks,
-            get_task,
-            update_task,
-            delete_task,
-            list_root_tasks,
-            list_subtasks,
-            get_task_hierarchy,
-            move_task,
-            reorder_task,
-            list_tasks_by_status,
-            search_tasks,
-            // Note commands
-            create_note,
-            list_notes,
-            get_note,
-            update_note,
-            delete_note,
-            list_pinned_notes,
-            list_recent_notes,
-            toggle_note_pin,
-            duplicate_note,
-            search_notes,
-            get_note_tags,
-            list_notes_by_tags,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                use tauri::Manager;
+                services::AutomationService::shut_down(&app_handle.state::<AppState>());
+            }
+        });
 }
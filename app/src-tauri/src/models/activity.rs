@@ -0,0 +1,66 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use super::EntityType;
+
+/// Activity counts of one calendar day
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityDay {
+    /// Local date, YYYY-MM-DD
+    pub date: String,
+    pub tasks_completed: i64,
+    pub notes_edited: i64,
+    pub commits: i64,
+}
+
+/// Kind of change recorded in the activity log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityAction {
+    Created,
+    Updated,
+    Completed,
+    Deleted,
+}
+
+impl ActivityAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ActivityAction::Created => "created",
+            ActivityAction::Updated => "updated",
+            ActivityAction::Completed => "completed",
+            ActivityAction::Deleted => "deleted",
+        }
+    }
+}
+
+impl ToSql for ActivityAction {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for ActivityAction {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "created" => Ok(ActivityAction::Created),
+            "updated" => Ok(ActivityAction::Updated),
+            "completed" => Ok(ActivityAction::Completed),
+            "deleted" => Ok(ActivityAction::Deleted),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// One change in the recent activity feed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub id: i64,
+    pub project_id: String,
+    pub entity_type: EntityType,
+    pub entity_id: String,
+    pub action: ActivityAction,
+    /// Title of the project, task or note at the time of the change
+    pub summary: String,
+    pub timestamp: i64,
+}
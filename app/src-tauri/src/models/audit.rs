@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Audit log entry recorded for a mutating command
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub command: String,
+    pub args: Option<serde_json::Value>,
+    pub outcome: String,
+    pub timestamp: i64,
+}
+
+/// Filter for listing the audit log
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuditLogFilter {
+    pub command: Option<String>,
+    pub outcome: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// State of the local automation API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationStatus {
+    /// Whether the API is turned on in the settings
+    pub enabled: bool,
+    /// Whether the listener is running
+    pub running: bool,
+    /// Port the listener is bound to on 127.0.0.1, while it runs
+    pub port: Option<u16>,
+}
+
+/// Body of POST /notes/append
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendToNoteDto {
+    pub note_id: String,
+    pub text: String,
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+/// A database backup that was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupResult {
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: i64,
+    /// Older backups removed by the retention settings
+    pub pruned: usize,
+}
+
+/// Format version written to the manifest of portable backups
+pub const PORTABLE_BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// manifest.json of a portable backup archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub schema_version: i64,
+    pub created_at: i64,
+    pub projects: Vec<PortableBackupProject>,
+}
+
+/// A project as it was when a portable backup was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBackupProject {
+    pub id: String,
+    pub name: String,
+    /// Project directory on the machine the backup was written on
+    pub path: String,
+    /// Files archived from the project's attachments directory
+    pub attachment_files: usize,
+}
+
+/// A portable backup that was written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBackupResult {
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: i64,
+    pub projects: usize,
+    pub attachment_files: usize,
+}
+
+/// Where a restored project now lives
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoredProjectPath {
+    pub project_id: String,
+    pub name: String,
+    pub old_path: String,
+    pub new_path: String,
+}
+
+/// A portable backup that was restored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableRestoreResult {
+    pub schema_version: i64,
+    pub projects: Vec<RestoredProjectPath>,
+    pub attachment_files: usize,
+}
+
+/// Sent while a portable backup is written or restored, after every file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBackupProgress {
+    /// "create" or "restore"
+    pub operation: String,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
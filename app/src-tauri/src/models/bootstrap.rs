@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{ActivityEntry, Project, ProjectWithCounts, TaskWithProject};
+
+/// What the first screen needs, loaded in one call when the app starts
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BootstrapData {
+    /// Projects that are not archived, with their counts, most recently modified first
+    pub projects: Vec<ProjectWithCounts>,
+    /// Every setting with its current or default value
+    pub settings: Map<String, Value>,
+    /// Favorite projects in their manual order
+    pub favorite_projects: Vec<Project>,
+    /// Task reminders not delivered yet, soonest first
+    pub pending_reminders: Vec<TaskWithProject>,
+    /// Latest changes across the vault, newest first
+    pub recent_activity: Vec<ActivityEntry>,
+}
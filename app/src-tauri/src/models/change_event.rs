@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use super::EntityType;
+
+/// Kind of change announced to the frontend windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl ChangeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeAction::Created => "created",
+            ChangeAction::Updated => "updated",
+            ChangeAction::Deleted => "deleted",
+        }
+    }
+}
+
+/// Payload of the "<entity>:<action>" events, such as "task:created", sent after
+/// a project, task or note changed so every window can refresh its copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub entity_type: EntityType,
+    pub entity_id: String,
+    /// Project the entity belongs to; the project itself for project events and
+    /// None for inbox notes
+    pub project_id: Option<String>,
+    pub action: ChangeAction,
+}
+
+impl ChangeEvent {
+    pub fn new(entity_type: EntityType, entity_id: &str, project_id: Option<&str>, action: ChangeAction) -> Self {
+        ChangeEvent {
+            entity_type,
+            entity_id: entity_id.to_string(),
+            project_id: project_id.map(str::to_string),
+            action,
+        }
+    }
+
+    pub fn project(id: &str, action: ChangeAction) -> Self {
+        Self::new(EntityType::Project, id, Some(id), action)
+    }
+
+    pub fn task(id: &str, project_id: &str, action: ChangeAction) -> Self {
+        Self::new(EntityType::Task, id, Some(project_id), action)
+    }
+
+    pub fn note(id: &str, project_id: Option<&str>, action: ChangeAction) -> Self {
+        Self::new(EntityType::Note, id, project_id, action)
+    }
+
+    /// Event name, "<entity>:<action>"
+    pub fn name(&self) -> String {
+        let entity = match self.entity_type {
+            EntityType::Project => "project",
+            EntityType::Task => "task",
+            EntityType::Note => "note",
+        };
+        format!("{}:{}", entity, self.action.as_str())
+    }
+}
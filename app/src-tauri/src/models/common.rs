@@ -0,0 +1,186 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::collation::UNICODE_COLLATION;
+
+/// Item skipped by a bulk operation, with the reason it was left untouched
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedItem {
+    pub id: String,
+    pub reason: String,
+}
+
+/// Result of moving entities between projects
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MoveResult {
+    pub moved: usize,
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// Collation used when sorting by title or name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleCollation {
+    /// Case- and accent-insensitive comparison
+    #[default]
+    Unicode,
+    /// Byte-wise comparison (SQLite default)
+    Binary,
+}
+
+impl TitleCollation {
+    /// Name of the SQLite collation
+    pub fn sql_name(self) -> &'static str {
+        match self {
+            TitleCollation::Unicode => UNICODE_COLLATION,
+            TitleCollation::Binary => "BINARY",
+        }
+    }
+}
+
+/// One page of a listing, with the size of the whole listing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub total_count: i64,
+}
+
+/// Field a task or note listing can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSortField {
+    UpdatedAt,
+    CreatedAt,
+    Title,
+}
+
+/// Direction of a sorted listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    /// SQL keyword of the direction
+    pub fn sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// How the tags of a listing by tags combine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatchMode {
+    /// Carrying every one of the tags
+    All,
+    /// Carrying at least one of the tags
+    #[default]
+    Any,
+}
+
+/// Paging and sorting of a listing; unset fields keep the listing's defaults
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListOptions {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    pub sort_by: Option<ListSortField>,
+    pub sort_order: Option<SortOrder>,
+}
+
+impl ListOptions {
+    /// ORDER BY terms, falling back to `default_column` in `default_order` when no field is chosen.
+    /// Ties are broken by id so pages never overlap.
+    pub fn order_clause(&self, default_column: &str, default_order: SortOrder) -> String {
+        let (column, order) = match self.sort_by {
+            None => (default_column, default_order),
+            Some(ListSortField::UpdatedAt) => ("updated_at", SortOrder::Desc),
+            Some(ListSortField::CreatedAt) => ("created_at", SortOrder::Desc),
+            Some(ListSortField::Title) => ("title COLLATE NOCASE", SortOrder::Asc),
+        };
+        format!("{} {}, id ASC", column, self.sort_order.unwrap_or(order).sql())
+    }
+
+    /// LIMIT value, with -1 meaning no limit as SQLite expects
+    pub fn sql_limit(&self) -> i64 {
+        self.limit.map_or(-1, i64::from)
+    }
+
+    /// OFFSET value
+    pub fn sql_offset(&self) -> i64 {
+        self.offset.map_or(0, i64::from)
+    }
+}
+
+/// Entity kinds that carry tags and a free-form metadata object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityType {
+    Project,
+    Task,
+    Note,
+}
+
+impl EntityType {
+    /// Table holding the entity
+    pub fn table(self) -> &'static str {
+        match self {
+            EntityType::Project => "projects",
+            EntityType::Task => "tasks",
+            EntityType::Note => "notes",
+        }
+    }
+
+    /// Junction table linking the entity to its tags
+    pub fn tag_table(self) -> &'static str {
+        match self {
+            EntityType::Project => "project_tags",
+            EntityType::Task => "task_tags",
+            EntityType::Note => "note_tags",
+        }
+    }
+
+    /// Column of the tag junction table referencing the entity
+    pub fn tag_key(self) -> &'static str {
+        match self {
+            EntityType::Project => "project_id",
+            EntityType::Task => "task_id",
+            EntityType::Note => "note_id",
+        }
+    }
+
+    /// Name used in error messages
+    pub fn label(self) -> &'static str {
+        match self {
+            EntityType::Project => "Project",
+            EntityType::Task => "Task",
+            EntityType::Note => "Note",
+        }
+    }
+}
+
+impl ToSql for EntityType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let name = match self {
+            EntityType::Project => "project",
+            EntityType::Task => "task",
+            EntityType::Note => "note",
+        };
+        Ok(ToSqlOutput::from(name))
+    }
+}
+
+impl FromSql for EntityType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "project" => Ok(EntityType::Project),
+            "task" => Ok(EntityType::Task),
+            "note" => Ok(EntityType::Note),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
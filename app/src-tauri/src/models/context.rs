@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use super::{ProjectStatus, TaskPriority};
+
+/// Options for exporting a project's context for assistants
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ContextExportOptions {
+    /// "json" (default) or "markdown"
+    pub format: Option<String>,
+    /// Number of most recently updated notes to include
+    pub max_notes: Option<usize>,
+    /// Maximum characters kept from a single note's content
+    pub note_char_budget: Option<usize>,
+    /// Maximum characters of the whole export
+    pub total_char_budget: Option<usize>,
+    /// Fraction of the total budget reserved for the task list (0.0 - 1.0)
+    pub task_share: Option<f64>,
+    /// Number of references to include, in citation key order
+    pub max_references: Option<usize>,
+    /// Fraction of the total budget the references may use (0.0 - 1.0)
+    pub reference_share: Option<f64>,
+    /// Include note content at all, or titles only
+    pub include_note_content: Option<bool>,
+}
+
+/// Project metadata section of a context export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextProject {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub status: ProjectStatus,
+    pub tags: Vec<String>,
+}
+
+/// Open task with its open subtasks
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContextTask {
+    pub title: String,
+    pub status: String,
+    pub priority: TaskPriority,
+    pub due_date: Option<i64>,
+    pub children: Vec<ContextTask>,
+}
+
+/// Recently updated note, content possibly truncated
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContextNote {
+    pub title: String,
+    pub updated_at: i64,
+    pub tags: Vec<String>,
+    pub content: Option<String>,
+    pub truncated: bool,
+}
+
+/// Reference of the project, cited by its key
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContextReference {
+    pub citation_key: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub year: Option<i64>,
+    pub venue: Option<String>,
+}
+
+/// Structured project context bundle
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectContext {
+    pub project: ContextProject,
+    pub tasks: Vec<ContextTask>,
+    pub references: Vec<ContextReference>,
+    pub notes: Vec<ContextNote>,
+    pub truncated: bool,
+}
+
+/// Rendered project context
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectContextExport {
+    pub format: String,
+    pub content: String,
+    pub char_count: usize,
+    pub truncated: bool,
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use super::{SkippedItem, TaskWithProject};
+
+/// Deadline data transfer object for creation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateDeadlineDto {
+    pub project_id: Option<String>,
+    pub name: String,
+    pub date: i64,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Deadline data transfer object for updates
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateDeadlineDto {
+    pub name: Option<String>,
+    pub date: Option<i64>,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Deadline model for non-task dates such as conference or grant deadlines.
+/// A missing project_id marks a global deadline.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Deadline {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub name: String,
+    pub date: i64,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: i64,
+}
+
+/// Result of importing a deadline feed. In a dry run nothing is written and
+/// `deadlines` lists what would have been created.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadlineImportReport {
+    pub dry_run: bool,
+    pub deadlines: Vec<Deadline>,
+    pub duplicates: Vec<String>,
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// What needs attention today: open tasks that are overdue or due before the
+/// day ends, and the deadlines of today and the coming days, soonest first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodayView {
+    pub overdue_tasks: Vec<TaskWithProject>,
+    pub tasks_due_today: Vec<TaskWithProject>,
+    pub deadlines_today: Vec<Deadline>,
+    pub upcoming_deadlines: Vec<Deadline>,
+}
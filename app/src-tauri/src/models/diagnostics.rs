@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// Effective database settings and size, for the diagnostics screen
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DbInfo {
+    pub path: String,
+    pub file_size_bytes: u64,
+    /// Size of the write-ahead log next to the database, 0 when absent
+    pub wal_size_bytes: u64,
+    pub journal_mode: String,
+    /// "off", "normal", "full" or "extra"
+    pub synchronous: String,
+    pub busy_timeout_ms: i64,
+    pub foreign_keys: bool,
+    /// Negative values are KiB, positive values are pages
+    pub cache_size: i64,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+}
+
+/// Outcome of a WAL checkpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckpointResult {
+    /// Whether another connection prevented a complete checkpoint
+    pub busy: bool,
+    /// Frames in the log before the checkpoint
+    pub log_frames: i64,
+    /// Frames copied back into the database
+    pub checkpointed_frames: i64,
+}
+
+/// Result of the integrity and foreign key checks
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseHealthReport {
+    /// No problems and no orphaned rows were found
+    pub ok: bool,
+    /// Damaged pages or indexes and broken foreign keys, one line each
+    pub problems: Vec<String>,
+    /// Tasks whose project no longer exists
+    pub orphaned_tasks: usize,
+    /// Notes whose project no longer exists
+    pub orphaned_notes: usize,
+    /// Deadlines whose project no longer exists
+    pub orphaned_deadlines: usize,
+}
+
+/// Database size before and after VACUUM and ANALYZE
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OptimizeResult {
+    pub size_before_bytes: i64,
+    pub size_after_bytes: i64,
+}
+
+/// Schema version of the open database
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    /// Version recorded in the database file
+    pub current: i64,
+    /// Latest version this app can migrate to
+    pub latest: i64,
+}
+
+/// Progress of the schema migrations run while the database is opened
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    pub running: bool,
+    /// Migrations applied so far in this run
+    pub current_step: usize,
+    /// Migrations this run applies
+    pub total_steps: usize,
+    pub percent: u32,
+    /// Why the database could not be opened, when it failed
+    pub error: Option<String>,
+}
+
+impl MigrationStatus {
+    /// Status after `current_step` of `total_steps` migrations
+    pub fn progress(current_step: usize, total_steps: usize) -> Self {
+        let percent = (current_step * 100).checked_div(total_steps).map_or(100, |percent| percent as u32);
+        Self {
+            running: current_step < total_steps,
+            current_step,
+            total_steps,
+            percent,
+            error: None,
+        }
+    }
+}
+
+/// Kind of inconsistency found by repair_database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairKind {
+    /// Task whose parent no longer exists; moved to the root of its project
+    OrphanedSubtask,
+    /// Junction row pointing at a missing row on either side; deleted
+    DanglingJunctionRow,
+    /// Indexed file whose project no longer exists; deleted
+    OrphanedFileMetadata,
+    /// completed_at disagreeing with the status; status wins
+    CompletionMismatch,
+}
+
+/// One inconsistent row and what repair_database does about it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepairFinding {
+    pub kind: RepairKind,
+    pub table: String,
+    /// ID of the row, or its key columns joined by ":" for junction rows
+    pub row: String,
+    /// What is wrong with the row
+    pub problem: String,
+    /// What the repair does, or would do in a dry run
+    pub action: String,
+}
+
+/// Result of repair_database. In a dry run nothing is written and
+/// `repaired` is 0.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepairReport {
+    pub dry_run: bool,
+    pub findings: Vec<RepairFinding>,
+    /// Rows changed or deleted
+    pub repaired: usize,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// What changed in the workspace between two digests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyDigest {
+    /// Start of the period: the previous digest, or a week before `generated_at`
+    pub since: i64,
+    pub generated_at: i64,
+    pub markdown: String,
+    /// Note the digest was saved as, when a journal project is set
+    pub note_id: Option<String>,
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Note, NoteAttachment, Project, Tag, Task};
+
+/// Self-contained JSON backup of one project
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectArchive {
+    /// Format version, bumped whenever the layout changes incompatibly
+    pub schema_version: u32,
+    pub exported_at: i64,
+    pub project: Project,
+    /// Ordered task statuses of the project
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    /// Every task of the project; the hierarchy is kept through parent_id
+    pub tasks: Vec<Task>,
+    pub notes: Vec<Note>,
+    /// Tags used by the project, its tasks or its notes
+    pub tags: Vec<Tag>,
+}
+
+/// Result of exporting a project archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub project_id: String,
+    pub path: String,
+    pub task_count: usize,
+    pub note_count: usize,
+    pub tag_count: usize,
+    pub bytes_written: u64,
+}
+
+/// Result of exporting notes as one self-contained HTML file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HtmlExportSummary {
+    pub path: String,
+    pub note_count: usize,
+    /// Images written into the file as data URIs
+    pub images_embedded: usize,
+    /// Images left as placeholders: missing, too large, not an image or not local
+    pub images_skipped: usize,
+    pub bytes_written: u64,
+    /// Notes rendered anew; the others came from the render cache
+    pub notes_rendered: usize,
+}
+
+/// Result of exporting a project as a static site
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SiteExportSummary {
+    pub dest_dir: String,
+    /// HTML pages written: the index, the note pages and the reference list
+    pub pages_written: usize,
+    /// Attachments and images copied next to the pages
+    pub files_copied: usize,
+    /// Files of an earlier export of the site that are no longer generated
+    pub files_removed: usize,
+    /// Notes rendered anew; the others came from the render cache
+    pub notes_rendered: usize,
+}
+
+/// metadata.json of a note bundle: a zip holding note.md, this file and the
+/// note's attachments under attachments/
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteBundleMetadata {
+    /// Format version, bumped whenever the layout changes incompatibly
+    pub format_version: u32,
+    pub exported_at: i64,
+    pub note_id: String,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(default)]
+    pub is_pinned: bool,
+    /// Targets of the note's wikilinks, resolved again by title where the bundle is imported
+    #[serde(default)]
+    pub links: Vec<String>,
+    #[serde(default)]
+    pub attachments: Vec<NoteBundleAttachment>,
+}
+
+/// Attachment carried by a note bundle
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteBundleAttachment {
+    /// Entry of the file in the bundle, such as "attachments/figure.png"
+    pub path: String,
+    pub original_name: String,
+    pub size_bytes: i64,
+}
+
+/// Result of exporting a note bundle
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteBundleExportSummary {
+    pub path: String,
+    pub attachment_count: usize,
+    /// Original names of attachments left out because their file is gone
+    pub missing_attachments: Vec<String>,
+    pub bytes_written: u64,
+}
+
+/// Result of importing a note bundle
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteBundleImportSummary {
+    pub note: Note,
+    pub attachments: Vec<NoteAttachment>,
+}
+
+/// Result of importing a project archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub project: Project,
+    pub task_count: usize,
+    pub note_count: usize,
+    pub tag_count: usize,
+}
+
+/// What happened to one file of a Markdown import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownImportStatus {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+/// Outcome of importing one Markdown file as a note
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarkdownImportResult {
+    /// Path relative to the imported folder, with "/" separators
+    pub path: String,
+    pub status: MarkdownImportStatus,
+    pub note_id: Option<String>,
+    /// Why the file was skipped or failed, or a warning about an imported file
+    pub reason: Option<String>,
+}
+
+/// Calendar component written for each task of an iCalendar export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IcalComponent {
+    /// VTODO: a to-do due at the task's due date, with its completion status
+    #[default]
+    Todo,
+    /// VEVENT: an event starting at the task's due date
+    Event,
+}
+
+/// Row of a CSV import that could not be imported
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsvRowError {
+    /// 1-based line the row starts on
+    pub line: usize,
+    pub message: String,
+}
+
+/// Result of importing tasks from CSV
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CsvImportResult {
+    pub imported: usize,
+    pub errors: Vec<CsvRowError>,
+}
+
+/// Kind of export checked by verify_export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportKind {
+    ProjectArchive,
+    NoteBundle,
+}
+
+/// Entity of an export or of the database, as listed in a verification report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedEntity {
+    /// "project", "status", "task", "note", "tag" or "attachment"
+    pub entity_type: String,
+    pub id: String,
+    /// Title or name, for showing the entity
+    pub label: String,
+}
+
+/// Field whose exported value is not the one in the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub exported: Value,
+    pub live: Value,
+}
+
+/// Entity whose exported content differs from the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityDifference {
+    pub entity: VerifiedEntity,
+    /// The differing fields; only filled in for the first differences of a report
+    pub fields: Vec<FieldDiff>,
+}
+
+/// Result of comparing an export with the live database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub path: String,
+    pub kind: ExportKind,
+    pub exported_at: i64,
+    /// Entities in the export
+    pub checked: usize,
+    /// Entities of the export whose content matches the database
+    pub matching: usize,
+    /// In the export but no longer in the database
+    pub missing: Vec<VerifiedEntity>,
+    /// In the database but not in the export
+    pub extra: Vec<VerifiedEntity>,
+    pub differing: Vec<EntityDifference>,
+}
+
+impl VerificationReport {
+    /// Whether the export matches the database exactly
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.differing.is_empty()
+    }
+}
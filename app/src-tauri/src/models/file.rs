@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+/// Indexed file of a project directory
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileMetadata {
+    pub id: String,
+    pub project_id: String,
+    /// Path relative to the project root, always with "/" separators
+    pub relative_path: String,
+    pub file_name: String,
+    pub file_extension: Option<String>,
+    pub file_size: Option<i64>,
+    pub mime_type: Option<String>,
+    pub created_at: i64,
+    pub modified_at: i64,
+    pub last_indexed_at: Option<i64>,
+    pub is_deleted: bool,
+    pub is_ignored: bool,
+    /// SHA-256 of the content in hex; None for ignored files, files too large
+    /// to hash and files not re-read since hashing was introduced
+    pub content_hash: Option<String>,
+    /// When the indexer last saw the content change, or the file appear
+    pub content_changed_at: Option<i64>,
+}
+
+/// Indexed file compared with the disk
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileInfo {
+    #[serde(flatten)]
+    pub file: FileMetadata,
+    /// Whether the file changed on disk or disappeared since it was indexed
+    pub stale: bool,
+}
+
+/// Stored state of an indexed path, used to decide what a re-index rewrites
+#[derive(Debug, Clone)]
+pub struct FileIndexEntry {
+    pub id: String,
+    pub modified_at: i64,
+    pub file_size: Option<i64>,
+    /// None until the file has been indexed with its content
+    pub last_indexed_at: Option<i64>,
+    pub is_ignored: bool,
+    pub content_hash: Option<String>,
+    pub content_changed_at: Option<i64>,
+    /// Manual choice from set_file_ignored; None defers to ignore patterns
+    pub ignore_override: Option<bool>,
+}
+
+/// Scanned file together with its text content, if it was read
+#[derive(Debug)]
+pub struct ScannedFile {
+    pub metadata: FileMetadata,
+    pub content: Option<String>,
+}
+
+/// Match of a full-text search over file contents
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileSearchResult {
+    pub file_id: String,
+    pub relative_path: String,
+    pub file_name: String,
+    pub snippet: String,
+    /// 1-based line of the first line containing a search term
+    pub line_number: Option<usize>,
+}
+
+/// Indexed file whose content is the same as that of other files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateFile {
+    pub file_id: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub relative_path: String,
+    /// Absolute path on disk
+    pub path: String,
+    pub modified_at: i64,
+}
+
+/// Files of any project with identical content, sorted by project and path
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// SHA-256 of the shared content in hex
+    pub content_hash: String,
+    pub file_size: i64,
+    pub files: Vec<DuplicateFile>,
+    /// Bytes taken by all copies but one
+    pub wasted_bytes: i64,
+}
+
+/// Result of a duplicate file search, the most wasteful groups first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub wasted_bytes: i64,
+    /// Files whose content had to be read because no current hash was stored
+    pub hashed: usize,
+    pub errors: Vec<FileIndexError>,
+}
+
+/// Progress of a duplicate file search, sent while files are compared
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DuplicateScanProgress {
+    pub checked_files: usize,
+    pub total_files: usize,
+    pub checked_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// File that could not be indexed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileIndexError {
+    pub path: String,
+    pub error: String,
+}
+
+/// Result of indexing a project directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FileIndexSummary {
+    pub indexed: usize,
+    /// Files left as they were because their modification time and size, or
+    /// their content hash, did not change
+    pub unchanged: usize,
+    /// Ignored files, symlinks to directories and broken symlinks
+    pub skipped: usize,
+    pub failed: usize,
+    /// Previously indexed files that no longer exist
+    pub deleted: usize,
+    pub errors: Vec<FileIndexError>,
+}
+
+/// Indexed files that appeared, changed or disappeared since a point in time,
+/// each list sorted by path
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChangedFiles {
+    pub since: i64,
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+}
+
+impl ChangedFiles {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+
+    /// One-line description of the changes for a commit message, such as
+    /// "Updated 3 files in docs/" or "Added 1 file, deleted 2 files". Files
+    /// sharing a directory name it. None when nothing changed.
+    pub fn summary(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut parts = Vec::new();
+        for (verb, paths) in [("added", &self.added), ("updated", &self.modified), ("deleted", &self.deleted)] {
+            if !paths.is_empty() {
+                let noun = if paths.len() == 1 { "file" } else { "files" };
+                parts.push(format!("{} {} {}", verb, paths.len(), noun));
+            }
+        }
+        let mut summary = parts.join(", ");
+        if let Some(first) = summary.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+
+        let paths = self.added.iter().chain(&self.modified).chain(&self.deleted);
+        let dir = paths
+            .map(|path| path.rsplit_once('/').map_or("", |(dir, _)| dir))
+            .reduce(|common, dir| common_dir(common, dir));
+        if let Some(dir) = dir.filter(|dir| !dir.is_empty()) {
+            summary.push_str(&format!(" in {}/", dir));
+        }
+        Some(summary)
+    }
+}
+
+/// Longest directory path both `a` and `b` lie in, "" for the root
+fn common_dir<'a>(a: &'a str, b: &str) -> &'a str {
+    let mut end = 0;
+    let mut start = 0;
+    for (x, y) in a.split('/').zip(b.split('/')) {
+        if x != y {
+            break;
+        }
+        end = start + x.len();
+        start = end + 1;
+    }
+    &a[..end]
+}
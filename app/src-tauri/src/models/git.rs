@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+/// Working tree status of a project repository
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GitStatus {
+    /// Current branch, None when HEAD is detached
+    pub branch: Option<String>,
+    /// Upstream branch, if one is configured
+    pub upstream: Option<String>,
+    /// Commits ahead of the upstream
+    pub ahead: u32,
+    /// Commits behind the upstream
+    pub behind: u32,
+    /// Paths with changes in the index
+    pub staged: Vec<String>,
+    /// Paths with unstaged changes in the working tree (including conflicts)
+    pub modified: Vec<String>,
+    pub untracked: Vec<String>,
+}
+
+impl GitStatus {
+    /// Whether there is nothing to commit
+    pub fn is_clean(&self) -> bool {
+        self.staged.is_empty() && self.modified.is_empty() && self.untracked.is_empty()
+    }
+}
+
+/// One commit of a project's history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub email: String,
+    /// Author time, unix seconds
+    pub timestamp: i64,
+    pub subject: String,
+}
+
+/// How a diff line relates the two versions of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    Added,
+    Removed,
+    Context,
+}
+
+/// One line of a diff hunk
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// Line text without the leading marker or line ending
+    pub content: String,
+    /// Line number in the old version; None for added lines
+    pub old_line: Option<u32>,
+    /// Line number in the new version; None for removed lines
+    pub new_line: Option<u32>,
+}
+
+/// A changed region of a file with its surrounding context
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// Text after the closing `@@`, usually the enclosing function or heading
+    pub section: Option<String>,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Changes of one file between two revisions, or a revision and the working copy
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileDiff {
+    pub path: String,
+    /// Path in the old version when the file was renamed
+    pub old_path: Option<String>,
+    /// Binary files are reported without hunks
+    pub is_binary: bool,
+    /// Empty when the file did not change
+    pub hunks: Vec<DiffHunk>,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// Note, reference or task drawn in the project graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    /// "note", "reference" or "task"
+    pub kind: String,
+    pub title: String,
+}
+
+/// Directed connection between two graph nodes
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    /// "link" (note wikilink), "cites" (note to reference), "question" (note and
+    /// task under the same research question) or "subtask" (task to its parent)
+    pub kind: String,
+}
+
+/// Notes, references and optionally tasks of a project and how they connect
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectGraph {
+    pub nodes: Vec<GraphNode>,
+    /// Only edges whose both ends are in `nodes`
+    pub edges: Vec<GraphEdge>,
+    /// Whether the least connected nodes were left out to stay under the size cap
+    pub truncated: bool,
+}
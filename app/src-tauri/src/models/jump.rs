@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Title-only entry of the command palette index
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JumpIndexEntry {
+    pub id: String,
+    /// "project", "task" or "note"
+    pub kind: String,
+    pub project_id: String,
+    pub title: String,
+    pub updated_at: i64,
+}
+
+/// Command palette index across non-archived projects, most recently updated first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JumpIndex {
+    pub entries: Vec<JumpIndexEntry>,
+    /// Whether older entries were left out to stay under the size cap
+    pub truncated: bool,
+}
@@ -1,8 +1,70 @@
+pub mod activity;
+pub mod audit;
+pub mod automation;
+pub mod backup;
+pub mod bootstrap;
+pub mod change_event;
+pub mod common;
+pub mod context;
+pub mod deadline;
+pub mod diagnostics;
+pub mod digest;
+pub mod export;
+pub mod file;
+pub mod git;
+pub mod graph;
+pub mod jump;
 pub mod project;
+pub mod project_template;
+pub mod reference;
+pub mod report;
 pub mod task;
 pub mod note;
+pub mod note_attachment;
+pub mod note_template;
+pub mod orphan;
+pub mod research_question;
+pub mod search;
+pub mod settings;
+pub mod startup_scan;
+pub mod tag;
+pub mod time_entry;
+pub mod trash;
+pub mod undo;
+pub mod vault;
 
+pub use activity::*;
+pub use audit::*;
+pub use automation::*;
+pub use backup::*;
+pub use bootstrap::*;
+pub use change_event::*;
+pub use common::*;
+pub use context::*;
+pub use deadline::*;
+pub use diagnostics::*;
+pub use digest::*;
+pub use export::*;
+pub use file::*;
+pub use git::*;
+pub use graph::*;
+pub use jump::*;
 pub use project::*;
+pub use project_template::*;
+pub use reference::*;
+pub use report::*;
 pub use task::*;
 pub use note::*;
+pub use note_attachment::*;
+pub use note_template::*;
+pub use orphan::*;
+pub use research_question::*;
+pub use search::*;
+pub use settings::*;
+pub use startup_scan::*;
+pub use tag::*;
+pub use time_entry::*;
+pub use trash::*;
+pub use undo::*;
+pub use vault::*;
 
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Note data transfer object for creation
 #[derive(Debug, Serialize, Deserialize)]
@@ -10,6 +11,22 @@ pub struct CreateNoteDto {
     pub is_pinned: Option<bool>,
 }
 
+/// Note of a bulk create request that failed validation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkItemError {
+    /// Position of the note in the request
+    pub index: usize,
+    pub reason: String,
+}
+
+/// Result of creating notes in bulk
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BulkCreateResult {
+    /// IDs of the created notes, in request order
+    pub created_ids: Vec<String>,
+    pub errors: Vec<BulkItemError>,
+}
+
 /// Note data transfer object for updates
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateNoteDto {
@@ -17,17 +34,130 @@ pub struct UpdateNoteDto {
     pub content: Option<String>,
     pub tags: Option<Vec<String>>,
     pub is_pinned: Option<bool>,
+    /// When set, the update only applies if the note's updated_at still equals it
+    pub expected_updated_at: Option<i64>,
 }
 
 /// Note model
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Note {
     pub id: String,
-    pub project_id: String,
+    /// None for inbox notes not yet filed into a project
+    pub project_id: Option<String>,
     pub title: String,
     pub content: String,
     pub created_at: i64,
     pub updated_at: i64,
     pub tags: Option<Vec<String>>,
     pub is_pinned: bool,
+    pub is_locked: bool,
+    /// Free-form metadata object, only loaded for single-note reads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// Lightweight note reference used in link lists
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteSummary {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub title: String,
+    pub updated_at: i64,
+    pub tags: Option<Vec<String>>,
+    pub is_pinned: bool,
+    pub is_locked: bool,
+}
+
+/// Outgoing `[[wikilink]]` of a note
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteLink {
+    /// Link target as written, without alias or heading
+    pub target_title: String,
+    /// Note the link resolves to in the same project; None while unresolved
+    pub note: Option<NoteSummary>,
+}
+
+/// Size of one note, computed from its content
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteStats {
+    pub note_id: String,
+    /// Words, with every CJK character counted as one
+    pub word_count: i64,
+    /// Characters other than whitespace
+    pub character_count: i64,
+    pub heading_count: i64,
+    /// Minutes to read the prose, leaving out code and URLs
+    pub reading_time_minutes: i64,
+}
+
+/// Writing of one local day
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WritingDay {
+    /// Local date, YYYY-MM-DD
+    pub date: String,
+    /// Current words of the notes last edited that day
+    pub words: i64,
+    pub notes_edited: i64,
+}
+
+/// Writing in a project over a time range
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WritingStats {
+    pub project_id: String,
+    /// Words in all the project's notes now
+    pub total_words: i64,
+    /// Words in the notes edited within the range
+    pub words_in_range: i64,
+    /// Days with edits, oldest first
+    pub days: Vec<WritingDay>,
+}
+
+/// Change in the words counted towards a writing goal over one local day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordDelta {
+    /// Local date, YYYY-MM-DD
+    pub date: String,
+    /// Words added that day; negative when more were cut than written
+    pub words: i64,
+}
+
+/// How far a project is towards its word-count goal
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WritingProgress {
+    pub project_id: String,
+    /// None while the project has no goal
+    pub target_words: Option<i64>,
+    /// Tag of the counted notes; empty when every note counts
+    pub tag: String,
+    /// Words in the counted notes now
+    pub words: i64,
+    pub notes: i64,
+    /// Share of the goal reached, 0-100 and beyond; None without a goal
+    pub percent: Option<f64>,
+    /// Words added on each of the last days, oldest first
+    pub days: Vec<WordDelta>,
+}
+
+/// Where a note was left in the editor and how often it is opened. Kept apart
+/// from the note so that reading it never changes its updated_at.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteViewState {
+    pub note_id: String,
+    /// Scroll position of the editor, in the unit the UI saved it in
+    pub scroll_position: f64,
+    /// Character offset of the cursor in the content
+    pub cursor_offset: i64,
+    /// None until the note is first saved as opened
+    pub last_opened_at: Option<i64>,
+    pub open_count: i64,
+}
+
+/// View state of a note to save
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveNoteViewStateDto {
+    pub scroll_position: f64,
+    pub cursor_offset: i64,
+    /// Whether the note was just opened: counts an open and stamps last_opened_at
+    #[serde(default)]
+    pub opened: bool,
 }
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// A file attached to a note, copied into the project's docs/attachments directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteAttachment {
+    pub id: String,
+    pub note_id: String,
+    /// Path of the copy, relative to the project directory
+    pub relative_path: String,
+    /// File name the attachment had where it was copied from
+    pub original_name: String,
+    pub size_bytes: i64,
+    pub created_at: i64,
+}
+
+/// Disk use of the attachment store, where identical attachment files share one copy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentStoreStats {
+    /// Distinct files in the store
+    pub blobs: i64,
+    /// Attachments pointing at a stored file
+    pub references: i64,
+    /// Size of the stored files
+    pub blob_bytes: i64,
+    /// Size of every stored attachment counted on its own
+    pub logical_bytes: i64,
+    /// Disk space the sharing saves; attachments on a drive that cannot link
+    /// to the store hold their own copy and save nothing
+    pub bytes_saved: i64,
+    /// Attachments not in the store yet
+    pub unstored: i64,
+}
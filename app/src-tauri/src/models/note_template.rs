@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Reusable skeleton for new notes. The title pattern and content may hold
+/// `{{placeholder}}` values filled in when a note is created from it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteTemplate {
+    pub id: String,
+    pub name: String,
+    pub title_pattern: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    pub created_at: i64,
+}
+
+/// Note template data transfer object for creation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateNoteTemplateDto {
+    pub name: String,
+    pub title_pattern: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use super::SkippedItem;
+
+/// Row whose project_id points at a project that no longer exists
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrphanedEntity {
+    pub id: String,
+    /// "task", "note" or "deadline"
+    pub kind: String,
+    pub project_id: String,
+    pub title: String,
+}
+
+/// Orphaned rows grouped by kind
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OrphanReport {
+    pub tasks: Vec<OrphanedEntity>,
+    pub notes: Vec<OrphanedEntity>,
+    pub deadlines: Vec<OrphanedEntity>,
+}
+
+impl OrphanReport {
+    /// Total number of orphaned rows
+    pub fn total(&self) -> usize {
+        self.tasks.len() + self.notes.len() + self.deadlines.len()
+    }
+}
+
+/// How a file of a project's notes/ or attachment folder disagrees with the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectFileIssueKind {
+    /// On disk, with nothing in the database pointing at it
+    Orphan,
+    /// Attachment row whose file is gone
+    Missing,
+    /// Exported note file older than the note's last update
+    Stale,
+}
+
+/// A file of a project that does not match the database
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectFileIssue {
+    pub kind: ProjectFileIssueKind,
+    /// Path relative to the project directory, with forward slashes
+    pub relative_path: String,
+    /// Note the file was exported from or attached to, when known
+    pub note_id: Option<String>,
+}
+
+/// Result of comparing a project's notes/ and attachment folder with the database
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectFilesReport {
+    pub orphans: Vec<ProjectFileIssue>,
+    pub missing: Vec<ProjectFileIssue>,
+    pub stale: Vec<ProjectFileIssue>,
+}
+
+/// What to do with a file of a `ProjectFilesReport`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectFileActionKind {
+    /// Delete an orphaned file
+    Delete,
+    /// Write a stale exported note again
+    Reexport,
+}
+
+/// One action the user selected from a `ProjectFilesReport`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectFileAction {
+    pub action: ProjectFileActionKind,
+    pub relative_path: String,
+}
+
+/// Result of cleaning up a project's files
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CleanProjectFilesResult {
+    pub deleted: Vec<String>,
+    pub reexported: Vec<String>,
+    /// Selected actions not carried out, by relative path
+    pub skipped: Vec<SkippedItem>,
+}
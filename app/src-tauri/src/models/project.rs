@@ -1,4 +1,78 @@
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{Note, TagCount, Task};
+
+/// Project lifecycle status, stored as its snake_case name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectStatus {
+    #[default]
+    Active,
+    Archived,
+    Completed,
+}
+
+impl ProjectStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProjectStatus::Active => "active",
+            ProjectStatus::Archived => "archived",
+            ProjectStatus::Completed => "completed",
+        }
+    }
+}
+
+impl fmt::Display for ProjectStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql for ProjectStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+/// Unknown values written by older versions read as active
+impl FromSql for ProjectStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Ok(match value.as_str()? {
+            "active" => ProjectStatus::Active,
+            "archived" => ProjectStatus::Archived,
+            "completed" => ProjectStatus::Completed,
+            other => {
+                tracing::warn!("Unknown project status {:?}, reading it as active", other);
+                ProjectStatus::Active
+            }
+        })
+    }
+}
+
+/// Order of the project list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSort {
+    /// Most recently modified first
+    #[default]
+    Recent,
+    /// Favorites first in their custom order, then the rest most recently modified first
+    FavoritesFirst,
+}
+
+impl ProjectSort {
+    /// ORDER BY clause of the project list
+    pub fn sql_order(self) -> &'static str {
+        match self {
+            ProjectSort::Recent => "last_modified_at DESC",
+            ProjectSort::FavoritesFirst => "is_favorite DESC, favorite_order ASC, last_modified_at DESC",
+        }
+    }
+}
 
 /// Project data transfer object for creation
 #[derive(Debug, Serialize, Deserialize)]
@@ -7,6 +81,10 @@ pub struct CreateProjectDto {
     pub path: String,
     pub description: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Subdirectories to create instead of the project_layout setting
+    pub layout: Option<Vec<String>>,
+    /// Project template whose tasks and notes the project starts with
+    pub template_id: Option<String>,
 }
 
 /// Project data transfer object for updates
@@ -14,8 +92,24 @@ pub struct CreateProjectDto {
 pub struct UpdateProjectDto {
     pub name: Option<String>,
     pub description: Option<String>,
-    pub status: Option<String>,
+    pub status: Option<ProjectStatus>,
     pub tags: Option<Vec<String>>,
+    /// Changing the prefix rewrites the keys of existing tasks
+    pub key_prefix: Option<String>,
+    /// Archived projects cannot be made favorites
+    pub is_favorite: Option<bool>,
+    /// When set, the update only applies if the project's last_modified_at still equals it
+    pub expected_updated_at: Option<i64>,
+}
+
+/// Project list filter. Tag matching is case-insensitive:
+/// `tags_any` needs one of the tags, `tags_all` every tag, and `tags_none` excludes any of them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectFilterDto {
+    pub status: Option<ProjectStatus>,
+    pub tags_any: Option<Vec<String>>,
+    pub tags_all: Option<Vec<String>>,
+    pub tags_none: Option<Vec<String>>,
 }
 
 /// Project model
@@ -25,8 +119,95 @@ pub struct Project {
     pub name: String,
     pub path: String,
     pub description: Option<String>,
-    pub status: String,
+    pub status: ProjectStatus,
     pub created_at: i64,
     pub last_modified_at: i64,
     pub tags: Option<Vec<String>>,
+    /// Prefix of the project's task keys, e.g. "NLP" in "NLP-142"
+    pub key_prefix: Option<String>,
+    /// Favorites can be listed first, in their own order
+    #[serde(default)]
+    pub is_favorite: bool,
+    /// Free-form metadata object, only loaded for single-project reads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// Settings stored in a project's research.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    #[serde(default)]
+    pub auto_commit: bool,
+    #[serde(default)]
+    pub backup_enabled: bool,
+    /// Other settings, such as the file index options, passed through as they are
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Project settings changes; unset fields are left as they are
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateProjectSettingsDto {
+    pub auto_commit: Option<bool>,
+    pub backup_enabled: Option<bool>,
+}
+
+/// Project with aggregate counts for its overview page
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectSummary {
+    #[serde(flatten)]
+    pub project: Project,
+    /// Number of tasks per status
+    pub task_counts: HashMap<String, i64>,
+    pub note_count: i64,
+    /// Number of research questions per status
+    pub question_counts: HashMap<String, i64>,
+}
+
+/// Everything the project dashboard shows, read in one go
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectDashboard {
+    pub project: Project,
+    /// Pinned notes in their pin order
+    pub pinned_notes: Vec<Note>,
+    /// Most recently updated notes, newest first
+    pub recent_notes: Vec<Note>,
+    /// Open tasks due in the next 7 days, soonest first
+    pub due_soon: Vec<Task>,
+    /// Tasks with status "in_progress"
+    pub in_progress: Vec<Task>,
+    pub stats: ProjectStats,
+}
+
+/// Project with the counts shown as badges in the project list
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectWithCounts {
+    #[serde(flatten)]
+    pub project: Project,
+    /// Tasks not done
+    pub open_task_count: i64,
+    pub done_task_count: i64,
+    pub note_count: i64,
+    /// Tasks not done whose due date has passed
+    pub overdue_count: i64,
+}
+
+/// Progress figures of a project for dashboards
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub project_id: String,
+    /// Number of tasks per status
+    pub task_counts: HashMap<String, i64>,
+    /// Number of tasks per priority
+    pub priority_counts: HashMap<String, i64>,
+    /// Tasks not done whose due date has passed
+    pub overdue_tasks: i64,
+    pub note_count: i64,
+    pub pinned_note_count: i64,
+    /// Most used tags across the project's tasks and notes
+    pub top_tags: Vec<TagCount>,
+    /// Latest update of any task or note of the project
+    pub last_activity_at: Option<i64>,
+    /// Share of tasks with status "done", from 0 to 100
+    pub completion_percentage: f64,
 }
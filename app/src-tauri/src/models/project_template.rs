@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::TaskPriority;
+
+/// Tasks and notes a new project can start with, saved from an existing project
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Root tasks in board order, each with its subtasks
+    pub tasks: Vec<TemplateTask>,
+    pub notes: Vec<TemplateNote>,
+    pub created_at: i64,
+}
+
+/// Task of a project template. New tasks start in the first status of the project.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateTask {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: TaskPriority,
+    pub tags: Option<Vec<String>>,
+    /// Seconds from the creation of the project to the due date
+    pub due_offset_secs: Option<i64>,
+    #[serde(default)]
+    pub subtasks: Vec<TemplateTask>,
+}
+
+/// Note of a project template
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateNote {
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub is_pinned: bool,
+}
+
+/// What save_project_as_template copies from the project
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SaveProjectTemplateOptions {
+    pub description: Option<String>,
+    /// Notes of the project to include; none when empty
+    #[serde(default)]
+    pub note_ids: Vec<String>,
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use super::SkippedItem;
+
+/// Allowed reading statuses of a reference
+pub const READING_STATUSES: [&str; 3] = ["unread", "reading", "read"];
+
+/// Reference data transfer object for creation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReferenceDto {
+    pub project_id: String,
+    /// Generated from the first author, year and title when missing
+    pub citation_key: Option<String>,
+    /// BibTeX entry type; "misc" when missing
+    pub entry_type: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub year: Option<i64>,
+    pub venue: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub pdf_path: Option<String>,
+    /// "unread" when missing
+    pub reading_status: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Reference data transfer object for updates
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateReferenceDto {
+    pub citation_key: Option<String>,
+    pub entry_type: Option<String>,
+    pub title: Option<String>,
+    pub authors: Option<Vec<String>>,
+    pub year: Option<i64>,
+    pub venue: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub pdf_path: Option<String>,
+    pub reading_status: Option<String>, // unread, reading, read
+    pub tags: Option<Vec<String>>,
+}
+
+/// Paper, book or other work a project cites
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reference {
+    pub id: String,
+    pub project_id: String,
+    /// BibTeX key, unique within the project
+    pub citation_key: String,
+    /// Lowercase BibTeX entry type, e.g. "article"
+    pub entry_type: String,
+    pub title: String,
+    /// Author names as written, e.g. "Lovelace, Ada"
+    pub authors: Vec<String>,
+    pub year: Option<i64>,
+    /// Journal, conference, publisher or school
+    pub venue: Option<String>,
+    /// Lowercase DOI without a resolver prefix
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub pdf_path: Option<String>,
+    pub created_at: i64,
+    /// One of `READING_STATUSES`
+    #[serde(default)]
+    pub reading_status: String,
+    pub tags: Option<Vec<String>>,
+}
+
+/// What a BibTeX import does with an entry whose DOI or citation key is
+/// already in the project
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateAction {
+    /// Leave the existing reference untouched
+    #[default]
+    Skip,
+    /// Overwrite the existing reference with the imported fields, keeping its ID
+    Update,
+}
+
+/// Result of importing BibTeX entries into a project
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReferenceImportReport {
+    pub imported: Vec<Reference>,
+    /// Existing references overwritten by a duplicate entry
+    pub updated: Vec<Reference>,
+    /// Citation keys of duplicate entries that were left out
+    pub duplicates: Vec<String>,
+    /// Entries that could not be parsed or are unusable, by citation key or line
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// What made references look like the same work
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateMatch {
+    /// Same DOI
+    Doi,
+    /// No DOI, the same year and first author and a similar title
+    Title,
+}
+
+/// References of a project that look like the same work, oldest first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReferenceDuplicateGroup {
+    pub matched_by: DuplicateMatch,
+    pub references: Vec<Reference>,
+}
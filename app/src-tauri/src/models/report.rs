@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use super::TaskTime;
+
+/// Rendering of a progress report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl ReportFormat {
+    /// Extension of the file a report in this format is written to
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// Task listed in a progress report
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportTask {
+    pub id: String,
+    pub task_key: Option<String>,
+    pub title: String,
+    pub status: String,
+    pub due_date: Option<i64>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+}
+
+/// Note listed in a progress report, by title only
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportNote {
+    pub id: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Time tracked on a project within a report window
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportTime {
+    pub total_seconds: i64,
+    /// Most tracked first
+    pub by_task: Vec<TaskTime>,
+}
+
+/// What happened in a project between `from` (inclusive) and `to` (exclusive).
+/// Every list has a stable order so a rendered report only changes with its data.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProgressReport {
+    pub project_id: String,
+    pub project_name: String,
+    pub from: i64,
+    pub to: i64,
+    /// Oldest completion first
+    pub tasks_completed: Vec<ReportTask>,
+    /// Oldest first
+    pub tasks_created: Vec<ReportTask>,
+    /// Oldest first
+    pub notes_added: Vec<ReportNote>,
+    /// Notes created before the window and changed within it, oldest change first
+    pub notes_edited: Vec<ReportNote>,
+    /// Tasks due before the end of the window and not completed by then, earliest due first
+    pub overdue: Vec<ReportTask>,
+    /// None when no time was tracked in the window
+    pub time: Option<ReportTime>,
+}
+
+/// Rendered progress report
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProgressReportExport {
+    pub format: ReportFormat,
+    pub content: String,
+    /// Where the report was written, when it was committed to the project
+    pub path: Option<String>,
+    /// Whether a commit was made; false when the report was already committed unchanged
+    pub committed: bool,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Allowed research question statuses
+pub const RESEARCH_QUESTION_STATUSES: [&str; 3] = ["open", "answered", "abandoned"];
+
+/// Research question data transfer object for creation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateResearchQuestionDto {
+    pub project_id: String,
+    pub question: String,
+}
+
+/// Research question data transfer object for updates
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateResearchQuestionDto {
+    pub question: Option<String>,
+    pub status: Option<String>, // open, answered, abandoned
+    pub answer_summary: Option<String>,
+}
+
+/// Research question model
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResearchQuestion {
+    pub id: String,
+    pub project_id: String,
+    pub question: String,
+    pub status: String,
+    pub answer_summary: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Notes and tasks linked to a research question
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResearchQuestionLinks {
+    pub note_ids: Vec<String>,
+    pub task_ids: Vec<String>,
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use super::EntityType;
+
+/// Project, task or note matching a cross-project search
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GlobalSearchResult {
+    pub entity_type: EntityType,
+    pub id: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub title: String,
+    /// Excerpt of the description or content around the first match
+    pub snippet: Option<String>,
+    /// Higher is more relevant; title matches count more than body matches
+    pub score: i64,
+    pub updated_at: i64,
+}
+
+/// How a task or note matched a project search, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    /// The title starts with the query
+    Prefix,
+    /// Every term appears verbatim in the title or body
+    Exact,
+    /// The title is only similar to the query, e.g. misspelled
+    Fuzzy,
+}
+
+/// A task or note found by a project search
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHit<T> {
+    #[serde(flatten)]
+    pub item: T,
+    pub match_kind: MatchKind,
+    /// Similarity of the title to the query, from 0 to 1
+    pub score: f64,
+}
+
+/// Named window of time a search can be limited to, in the timezone setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchPeriod {
+    Today,
+    /// Since Monday
+    ThisWeek,
+    ThisMonth,
+    /// The latest month with this number (1 for January) that has begun,
+    /// e.g. last year's March when it is February
+    Month(u32),
+}
+
+/// Limit of a search to what was last updated in a window: from `updated_after`
+/// up to, not including, `updated_before`, or within `period`, which replaces both
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchDateRange {
+    pub updated_after: Option<i64>,
+    pub updated_before: Option<i64>,
+    pub period: Option<SearchPeriod>,
+}
@@ -0,0 +1,155 @@
+/// Folder offered first when creating or importing a project; empty for none
+pub const SETTING_DEFAULT_PROJECT_DIR: &str = "default_project_dir";
+
+/// Whether new projects commit changes automatically
+pub const SETTING_AUTO_COMMIT_DEFAULT: &str = "auto_commit_default";
+
+/// Whether scheduled backups run
+pub const SETTING_BACKUP_ENABLED: &str = "backup_enabled";
+
+/// Days a backup is kept before it is removed
+pub const SETTING_BACKUP_RETENTION_DAYS: &str = "backup_retention_days";
+
+/// Hours between scheduled backups
+pub const SETTING_BACKUP_INTERVAL_HOURS: &str = "backup_interval_hours";
+
+/// Most backups kept; older ones are removed after each backup
+pub const SETTING_BACKUP_RETENTION_COUNT: &str = "backup_retention_count";
+
+/// Folder backups are written to; empty for "backups" in the app data folder
+pub const SETTING_BACKUP_DIR: &str = "backup_dir";
+
+/// When the last backup finished, as a Unix timestamp; 0 for never
+pub const SETTING_LAST_BACKUP_AT: &str = "last_backup_at";
+
+/// Days a trashed task or note is kept before the trash is emptied
+pub const SETTING_TRASH_RETENTION_DAYS: &str = "trash_retention_days";
+
+/// Days an audit log entry is kept; 0 to keep entries of any age
+pub const SETTING_AUDIT_RETENTION_DAYS: &str = "audit_retention_days";
+
+/// Most audit log entries kept, dropping the oldest first; 0 for no limit
+pub const SETTING_AUDIT_MAX_ENTRIES: &str = "audit_max_entries";
+
+/// Whether features that reach the network, such as fetching deadline feeds, are turned off
+pub const SETTING_OFFLINE_MODE: &str = "offline_mode";
+
+/// Timezone that days, weeks and months are counted in, such as "UTC+2" or
+/// "CET"; empty for the computer's own
+pub const SETTING_TIMEZONE: &str = "timezone";
+
+/// Whether notes exported as Markdown end with a "Linked from" section
+pub const SETTING_EXPORT_INCLUDE_BACKLINKS: &str = "export_include_backlinks";
+
+/// Whether a digest of the week's changes is generated on `SETTING_WEEKLY_DIGEST_WEEKDAY`
+pub const SETTING_WEEKLY_DIGEST_ENABLED: &str = "weekly_digest_enabled";
+
+/// Day the weekly digest is generated on, 1 for Monday to 7 for Sunday
+pub const SETTING_WEEKLY_DIGEST_WEEKDAY: &str = "weekly_digest_weekday";
+
+/// Project the weekly digest is saved to as a note; empty to not save it
+pub const SETTING_WEEKLY_DIGEST_PROJECT_ID: &str = "weekly_digest_project_id";
+
+/// When the last weekly digest was generated, as a Unix timestamp; 0 for never
+pub const SETTING_LAST_DIGEST_AT: &str = "last_digest_at";
+
+/// Tag of the notes counted towards a project's writing goal, with its
+/// subtags; empty to count every note of the project
+pub const SETTING_WRITING_GOAL_TAG: &str = "writing_goal_tag";
+
+/// Whether the first-run onboarding was completed or skipped
+pub const SETTING_FIRST_RUN_DONE: &str = "first_run_done";
+
+/// Whether projects are re-scanned for changes made while the app was closed
+pub const SETTING_STARTUP_SCAN_ENABLED: &str = "startup_scan_enabled";
+
+/// Whether the local automation API listens on 127.0.0.1
+pub const SETTING_AUTOMATION_ENABLED: &str = "automation_enabled";
+
+/// Port of the local automation API
+pub const SETTING_AUTOMATION_PORT: &str = "automation_port";
+
+/// Appearance of the app: "system", "light" or "dark"
+pub const SETTING_THEME: &str = "theme";
+
+/// Patterns written to the .gitignore of new projects; empty for the built-in ones
+pub const SETTING_GITIGNORE_TEMPLATE: &str = "gitignore_template";
+
+/// Largest file, in bytes, that can be attached to a note
+pub const SETTING_ATTACHMENT_MAX_BYTES: &str = "attachment_max_bytes";
+
+/// Largest note or template content, in bytes; 0 for no limit
+pub const SETTING_NOTE_MAX_BYTES: &str = "note_max_bytes";
+
+/// Lowest title similarity, in percent, at which search reports a fuzzy match
+pub const SETTING_FUZZY_MATCH_THRESHOLD: &str = "fuzzy_match_threshold";
+
+/// Whether completing an overdue repeating task schedules the next occurrence
+/// from its original due date instead of from the day it was completed
+pub const SETTING_RECURRENCE_KEEP_SCHEDULE: &str = "recurrence_keep_schedule";
+
+/// Subdirectories created in new projects, as a JSON list of relative paths
+pub const SETTING_PROJECT_LAYOUT: &str = "project_layout";
+
+/// Settings written to the research.json of new projects over the built-in
+/// ones, as a JSON object; auto_commit comes from auto_commit_default
+pub const SETTING_PROJECT_SETTINGS_DEFAULTS: &str = "project_settings_defaults";
+
+/// Type of value a setting holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    String,
+    Bool,
+    Integer,
+    Json,
+}
+
+/// A known application setting and the value it has until it is first set
+#[derive(Debug, Clone, Copy)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub kind: SettingKind,
+    /// Default value as JSON text
+    pub default: &'static str,
+}
+
+/// Every application setting with its default. The backend and the frontend both
+/// read settings through these, so a default is only ever declared here.
+pub const SETTING_DEFINITIONS: &[SettingDefinition] = &[
+    SettingDefinition { key: SETTING_DEFAULT_PROJECT_DIR, kind: SettingKind::String, default: "\"\"" },
+    SettingDefinition { key: SETTING_AUTO_COMMIT_DEFAULT, kind: SettingKind::Bool, default: "true" },
+    SettingDefinition { key: SETTING_BACKUP_ENABLED, kind: SettingKind::Bool, default: "true" },
+    SettingDefinition { key: SETTING_BACKUP_RETENTION_DAYS, kind: SettingKind::Integer, default: "30" },
+    SettingDefinition { key: SETTING_BACKUP_INTERVAL_HOURS, kind: SettingKind::Integer, default: "24" },
+    SettingDefinition { key: SETTING_BACKUP_RETENTION_COUNT, kind: SettingKind::Integer, default: "10" },
+    SettingDefinition { key: SETTING_BACKUP_DIR, kind: SettingKind::String, default: "\"\"" },
+    SettingDefinition { key: SETTING_LAST_BACKUP_AT, kind: SettingKind::Integer, default: "0" },
+    SettingDefinition { key: SETTING_TRASH_RETENTION_DAYS, kind: SettingKind::Integer, default: "30" },
+    SettingDefinition { key: SETTING_AUDIT_RETENTION_DAYS, kind: SettingKind::Integer, default: "90" },
+    SettingDefinition { key: SETTING_AUDIT_MAX_ENTRIES, kind: SettingKind::Integer, default: "10000" },
+    SettingDefinition { key: SETTING_OFFLINE_MODE, kind: SettingKind::Bool, default: "false" },
+    SettingDefinition { key: SETTING_TIMEZONE, kind: SettingKind::String, default: "\"\"" },
+    SettingDefinition { key: SETTING_EXPORT_INCLUDE_BACKLINKS, kind: SettingKind::Bool, default: "true" },
+    SettingDefinition { key: SETTING_WEEKLY_DIGEST_ENABLED, kind: SettingKind::Bool, default: "false" },
+    SettingDefinition { key: SETTING_WEEKLY_DIGEST_WEEKDAY, kind: SettingKind::Integer, default: "1" },
+    SettingDefinition { key: SETTING_WEEKLY_DIGEST_PROJECT_ID, kind: SettingKind::String, default: "\"\"" },
+    SettingDefinition { key: SETTING_LAST_DIGEST_AT, kind: SettingKind::Integer, default: "0" },
+    SettingDefinition { key: SETTING_WRITING_GOAL_TAG, kind: SettingKind::String, default: "\"chapter\"" },
+    SettingDefinition { key: SETTING_FIRST_RUN_DONE, kind: SettingKind::Bool, default: "false" },
+    SettingDefinition { key: SETTING_STARTUP_SCAN_ENABLED, kind: SettingKind::Bool, default: "false" },
+    SettingDefinition { key: SETTING_AUTOMATION_ENABLED, kind: SettingKind::Bool, default: "false" },
+    SettingDefinition { key: SETTING_AUTOMATION_PORT, kind: SettingKind::Integer, default: "27182" },
+    SettingDefinition { key: SETTING_THEME, kind: SettingKind::String, default: "\"system\"" },
+    SettingDefinition { key: SETTING_GITIGNORE_TEMPLATE, kind: SettingKind::String, default: "\"\"" },
+    SettingDefinition { key: SETTING_ATTACHMENT_MAX_BYTES, kind: SettingKind::Integer, default: "52428800" },
+    SettingDefinition { key: SETTING_NOTE_MAX_BYTES, kind: SettingKind::Integer, default: "5242880" },
+    SettingDefinition { key: SETTING_FUZZY_MATCH_THRESHOLD, kind: SettingKind::Integer, default: "50" },
+    SettingDefinition { key: SETTING_RECURRENCE_KEEP_SCHEDULE, kind: SettingKind::Bool, default: "false" },
+    SettingDefinition { key: SETTING_PROJECT_LAYOUT, kind: SettingKind::Json, default: "[\"docs\", \"data\", \"notes\"]" },
+    SettingDefinition { key: SETTING_PROJECT_SETTINGS_DEFAULTS, kind: SettingKind::Json, default: "{}" },
+];
+
+/// Definition of a known setting
+pub fn setting_definition(key: &str) -> Option<&'static SettingDefinition> {
+    SETTING_DEFINITIONS.iter().find(|definition| definition.key == key)
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Indexed files of a project that appeared, changed or disappeared while the app was closed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartupScanFiles {
+    pub added: usize,
+    pub modified: usize,
+    pub deleted: usize,
+}
+
+/// What the startup scan found in one project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectScanSummary {
+    pub project_id: String,
+    pub name: String,
+    /// False when the time budget ran out before the project's turn
+    pub scanned: bool,
+    /// File index changes, None for projects that were never indexed
+    pub files: Option<StartupScanFiles>,
+    /// Paths with staged, unstaged or untracked changes, None without a git repository
+    pub git_dirty: Option<usize>,
+    /// Fields of research.json that no longer match the database, or why it could not be read
+    pub metadata_drift: Vec<String>,
+    /// Why the project could not be scanned
+    pub error: Option<String>,
+}
+
+/// Result of the last startup scan, sent with the `startup-scan-complete` event
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupScanReport {
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub projects: Vec<ProjectScanSummary>,
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Tag model
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    /// Hex color code for the UI (e.g. "#3b82f6")
+    pub color: Option<String>,
+    pub created_at: i64,
+}
+
+/// Tag with how often it is used
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagUsage {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub note_count: i64,
+    pub task_count: i64,
+}
+
+/// Tag name with how often it is used in some scope
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagCount {
+    pub name: String,
+    pub count: i64,
+}
+
+/// Level of the tag hierarchy, derived from the "/"-separated tag names.
+/// A parent that only exists through its children's names has no tag.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagNode {
+    /// Last segment of the path, e.g. "bayesian"
+    pub name: String,
+    /// Full tag name, e.g. "method/bayesian"
+    pub path: String,
+    pub tag: Option<TagUsage>,
+    pub children: Vec<TagNode>,
+}
@@ -1,4 +1,59 @@
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use super::SkippedItem;
+
+/// Built-in task workflow used when a project has no custom statuses
+pub const DEFAULT_TASK_STATUSES: [&str; 3] = ["todo", "in_progress", "done"];
+
+/// Task priority, stored as its snake_case name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl TaskPriority {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql for TaskPriority {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+/// Unknown values written by older versions read as the default priority
+impl FromSql for TaskPriority {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Ok(match value.as_str()? {
+            "low" => TaskPriority::Low,
+            "medium" => TaskPriority::Medium,
+            "high" => TaskPriority::High,
+            other => {
+                tracing::warn!("Unknown task priority {:?}, reading it as medium", other);
+                TaskPriority::Medium
+            }
+        })
+    }
+}
 
 /// Task data transfer object for creation
 #[derive(Debug, Serialize, Deserialize)]
@@ -8,10 +63,16 @@ pub struct CreateTaskDto {
     pub title: String,
     pub description: Option<String>,
     pub status: Option<String>,  // todo, in_progress, done
-    pub priority: Option<String>, // low, medium, high
+    pub priority: Option<TaskPriority>,
     pub due_date: Option<i64>,
     pub order: Option<i32>,
     pub tags: Option<Vec<String>>,
+    /// Recurrence rule such as "FREQ=WEEKLY;BYDAY=MO"; needs a due date
+    pub recurrence: Option<String>,
+    /// When to be reminded of the task
+    pub remind_at: Option<i64>,
+    /// Minutes before the due date to be reminded, instead of `remind_at`
+    pub remind_before: Option<i64>,
 }
 
 /// Task data transfer object for updates
@@ -20,11 +81,54 @@ pub struct UpdateTaskDto {
     pub title: Option<String>,
     pub description: Option<String>,
     pub status: Option<String>,
-    pub priority: Option<String>,
+    pub priority: Option<TaskPriority>,
     pub due_date: Option<i64>,
     pub parent_id: Option<String>,
     pub order: Option<i32>,
     pub tags: Option<Vec<String>>,
+    /// New recurrence rule; an empty string stops the task repeating
+    pub recurrence: Option<String>,
+    /// New reminder time; 0 removes the reminder. Setting it delivers the reminder again.
+    pub remind_at: Option<i64>,
+    /// Minutes before the (new) due date to be reminded, instead of `remind_at`
+    pub remind_before: Option<i64>,
+    /// When set, the update only applies if the task's updated_at still equals it
+    pub expected_updated_at: Option<i64>,
+}
+
+/// Task list filter; unset fields do not restrict the list, so an empty
+/// filter lists every task of the project. Tag matching is case-insensitive
+/// and a parent tag such as "method" also matches "method/bayesian".
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskFilterDto {
+    pub statuses: Option<Vec<String>>,
+    pub priorities: Option<Vec<TaskPriority>>,
+    pub tags: Option<Vec<String>>,
+    /// Require every tag instead of any of them
+    #[serde(default)]
+    pub match_all_tags: bool,
+    /// Due strictly before this timestamp
+    pub due_before: Option<i64>,
+    /// Due at or after this timestamp
+    pub due_after: Option<i64>,
+    pub has_due_date: Option<bool>,
+    pub parent: Option<TaskParentFilter>,
+    /// Whitespace-separated terms, each matching title, key or description
+    pub query: Option<String>,
+    /// Whether tasks with status "done" are listed; defaults to true
+    pub include_completed: Option<bool>,
+    /// Whether archived tasks are listed; defaults to false
+    pub include_archived: Option<bool>,
+}
+
+/// Position in the task tree a filter is limited to
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskParentFilter {
+    /// Only tasks without a parent
+    Root,
+    /// Only direct subtasks of the given task
+    ParentId(String),
 }
 
 /// Task model
@@ -36,13 +140,38 @@ pub struct Task {
     pub title: String,
     pub description: Option<String>,
     pub status: String,
-    pub priority: String,
+    pub priority: TaskPriority,
     pub due_date: Option<i64>,
     pub completed_at: Option<i64>,
     pub created_at: i64,
     pub updated_at: i64,
     pub order: i32,
     pub tags: Option<Vec<String>>,
+    /// Short human-readable key, unique within the project (e.g. "NLP-142")
+    pub task_key: Option<String>,
+    /// Explicit stack rank within the project (1 = top), if ranked
+    pub rank: Option<i64>,
+    /// Recurrence rule in canonical RRULE form; completing the task creates the next occurrence
+    pub recurrence: Option<String>,
+    /// Occurrence this one was created from when the previous one was completed
+    pub recurrence_parent_id: Option<String>,
+    /// When a reminder of the task is due
+    pub remind_at: Option<i64>,
+    /// When the reminder was delivered; None while it is pending
+    pub reminded_at: Option<i64>,
+    /// Free-form metadata object, only loaded for single-task reads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// Completion of a task's descendants, e.g. "7/12" on a parent
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub task_id: String,
+    /// Descendants with status "done"
+    pub completed: i64,
+    /// All descendants, excluding the task itself
+    pub total: i64,
 }
 
 /// Hierarchical task with children
@@ -52,3 +181,28 @@ pub struct TaskWithChildren {
     pub task: Task,
     pub children: Vec<TaskWithChildren>,
 }
+
+/// Task with the name of its project, for cross-project lists
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskWithProject {
+    #[serde(flatten)]
+    pub task: Task,
+    pub project_name: String,
+}
+
+/// One status column of a project's Kanban board
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KanbanColumn {
+    pub status: String,
+    /// Tasks in their board order, top first
+    pub tasks: Vec<Task>,
+}
+
+/// Result of stack-ranking tasks
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RankTasksResult {
+    pub ranked: usize,
+    /// Tasks of the project that were not in the ranked list
+    pub unranked: Vec<String>,
+    pub skipped: Vec<SkippedItem>,
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// Time spent on a task. The running timer is the one entry without an end.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub id: String,
+    pub task_id: String,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    pub note: Option<String>,
+}
+
+/// Seconds tracked on one task within a summary window
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskTime {
+    pub task_id: String,
+    pub title: String,
+    pub seconds: i64,
+}
+
+/// Seconds tracked on tasks carrying one tag within a summary window
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagTime {
+    pub tag: String,
+    pub seconds: i64,
+}
+
+/// Tracked time of a project, with entries clipped to the window.
+/// A task with several tags counts towards each of them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeSummary {
+    pub project_id: String,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub total_seconds: i64,
+    /// Most tracked first
+    pub by_task: Vec<TaskTime>,
+    /// Most tracked first
+    pub by_tag: Vec<TagTime>,
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use super::EntityType;
+
+/// Deleted task or note that can still be restored
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub entity_type: EntityType,
+    pub id: String,
+    pub project_id: String,
+    pub title: String,
+    /// Parent of a trashed task at the time it was deleted
+    pub parent_id: Option<String>,
+    pub deleted_at: i64,
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Note, Project, Task};
+
+/// Timestamps every update moves, left out of `changed_fields`
+const UPDATE_TIMESTAMP_FIELDS: [&str; 2] = ["updated_at", "last_modified_at"];
+
+/// An entity as it was before an update and as the update left it.
+/// Both are read in the update's own transaction.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Changed<T> {
+    pub before: T,
+    pub after: T,
+    /// Serialized fields whose value differs, sorted by name
+    pub changed_fields: Vec<String>,
+}
+
+impl<T: Serialize> Changed<T> {
+    pub fn new(before: T, after: T) -> Self {
+        let changed_fields = match (serde_json::to_value(&before), serde_json::to_value(&after)) {
+            (Ok(Value::Object(old)), Ok(Value::Object(new))) => new
+                .iter()
+                .filter(|(field, value)| {
+                    !UPDATE_TIMESTAMP_FIELDS.contains(&field.as_str()) && old.get(field.as_str()) != Some(*value)
+                })
+                .map(|(field, _)| field.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        Changed { before, after, changed_fields }
+    }
+}
+
+/// Undo payload of an update, built from its `Changed` result
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevertChangeDto {
+    /// The `before` value of the change
+    pub before: Value,
+    /// Update time the change left the entity with (`updated_at`, or
+    /// `last_modified_at` for projects). The undo is refused once it moved on.
+    pub updated_at: i64,
+}
+
+/// Entity written back by an undo, serialized as the entity itself
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RevertedEntity {
+    Project(Project),
+    Task(Task),
+    Note(Note),
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// The open vault: a folder holding the research database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultInfo {
+    pub path: String,
+    pub db_path: String,
+}
+
+/// Vault opened before on this machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentVault {
+    pub path: String,
+    pub last_opened_at: i64,
+    /// Whether the vault's database is currently reachable, e.g. its synced folder is mounted
+    #[serde(default)]
+    pub available: bool,
+}
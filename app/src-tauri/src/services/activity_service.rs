@@ -0,0 +1,164 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{ActivityDay, ActivityEntry, ProjectSort};
+use crate::services::{DbService, GitService};
+use crate::state::AppState;
+use crate::utils::timezone;
+use chrono::{DateTime, Duration, Local, Offset};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tracing::instrument;
+
+/// Longest window the heatmap covers
+const MAX_HEATMAP_DAYS: i32 = 366;
+
+/// Entries returned by list_activity when no limit is given
+const DEFAULT_ACTIVITY_LIMIT: i64 = 50;
+
+/// Most entries returned by one list_activity page
+const MAX_ACTIVITY_LIMIT: i64 = 500;
+
+/// How long commit timestamps read from git stay cached
+const GIT_CACHE_TTL_SECS: u64 = 3600;
+
+/// Cached commit timestamps per project path: when they were read, the window start, the timestamps
+type GitCache = HashMap<String, (Instant, i64, Vec<i64>)>;
+
+fn git_cache() -> &'static Mutex<GitCache> {
+    static CACHE: OnceLock<Mutex<GitCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Activity statistics across projects
+pub struct ActivityService;
+
+impl ActivityService {
+    /// Daily counts of completed tasks, edited notes and git commits for the last `days` days
+    /// (today included), oldest first. Days are bucketed in `timezone` when given, otherwise
+    /// in the system's local timezone, and every day of the window is present.
+    #[instrument(level = "debug", skip_all, fields(project_id = ?project_id))]
+    pub async fn get_activity_heatmap(
+        state: &AppState,
+        days: i32,
+        project_id: Option<String>,
+        timezone: Option<String>,
+    ) -> AppResult<Vec<ActivityDay>> {
+        state.blocking(move |state| {
+            if !(1..=MAX_HEATMAP_DAYS).contains(&days) {
+                return Err(AppError::InvalidInput(format!(
+                    "Days must be between 1 and {}",
+                    MAX_HEATMAP_DAYS
+                )));
+            }
+
+            let offset_seconds = match timezone.as_deref() {
+                Some(tz) => timezone::parse_utc_offset(tz)
+                    .ok_or_else(|| AppError::InvalidInput(format!("Unknown timezone '{}'", tz)))?,
+                None => Local::now().offset().fix().local_minus_utc(),
+            };
+
+            let now = chrono::Utc::now().timestamp();
+            let today = DateTime::from_timestamp(now + offset_seconds as i64, 0)
+                .ok_or_else(|| AppError::Internal("Current time out of range".into()))?
+                .date_naive();
+            let first_day = today - Duration::days(days as i64 - 1);
+            let from = first_day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp() - offset_seconds as i64;
+
+            let (tasks, notes, project_paths) = {
+                let conn = &state.conn()?;
+
+                let project_paths: Vec<String> = match project_id.as_deref() {
+                    Some(id) => {
+                        let project = DbService::get_project_by_id(conn, id)?
+                            .ok_or_else(|| AppError::NotFound("Project", id.to_string()))?;
+                        vec![project.path]
+                    }
+                    None => DbService::get_all_projects(conn, false, ProjectSort::Recent)?
+                        .into_iter()
+                        .map(|p| p.path)
+                        .collect(),
+                };
+
+                (
+                    DbService::count_completed_tasks_by_day(conn, project_id.as_deref(), from, offset_seconds)?,
+                    DbService::count_edited_notes_by_day(conn, project_id.as_deref(), from, offset_seconds)?,
+                    project_paths,
+                )
+            };
+
+            // Git log walks run without holding the database lock
+            let mut commits: HashMap<String, i64> = HashMap::new();
+            for path in &project_paths {
+                for timestamp in Self::commit_timestamps(path, from) {
+                    if let Some(day) = DateTime::from_timestamp(timestamp + offset_seconds as i64, 0) {
+                        *commits.entry(day.date_naive().to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+
+            Ok(first_day
+                .iter_days()
+                .take(days as usize)
+                .map(|day| {
+                    let date = day.format("%Y-%m-%d").to_string();
+                    ActivityDay {
+                        tasks_completed: tasks.get(&date).copied().unwrap_or(0),
+                        notes_edited: notes.get(&date).copied().unwrap_or(0),
+                        commits: commits.get(&date).copied().unwrap_or(0),
+                        date,
+                    }
+                })
+                .collect())
+        }).await
+    }
+
+    /// A page of the recent activity feed, newest first, optionally for one project.
+    /// Pass the timestamp and id of the last entry seen to get the next page.
+    #[instrument(level = "debug", skip_all, fields(project_id = ?project_id, before_id = ?before_id))]
+    pub async fn list_activity(
+        state: &AppState,
+        project_id: Option<String>,
+        limit: Option<i64>,
+        before_timestamp: Option<i64>,
+        before_id: Option<i64>,
+    ) -> AppResult<Vec<ActivityEntry>> {
+        state.run(move |conn| {
+            let limit = limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT);
+            if !(1..=MAX_ACTIVITY_LIMIT).contains(&limit) {
+                return Err(AppError::InvalidInput(format!("Limit must be between 1 and {}", MAX_ACTIVITY_LIMIT)));
+            }
+
+            DbService::list_activity(conn, project_id.as_deref(), limit, before_timestamp, before_id)
+        }).await
+    }
+
+    /// Remove activity entries, only those older than `older_than_days` when given.
+    /// Returns how many were removed.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn clear_activity(state: &AppState, older_than_days: Option<u32>) -> AppResult<usize> {
+        state.run(move |conn| {
+            let older_than = older_than_days
+                .map(|days| chrono::Utc::now().timestamp() - i64::from(days) * 86_400);
+
+            DbService::with_busy_retry(|| DbService::clear_activity(conn, older_than))
+        }).await
+    }
+
+    /// Commit timestamps of a project since `from`, served from the hourly cache when it covers the window.
+    /// Projects that are not git repositories (or git being unavailable) count as no commits.
+    fn commit_timestamps(path: &str, from: i64) -> Vec<i64> {
+        if let Ok(cache) = git_cache().lock() {
+            if let Some((read_at, since, timestamps)) = cache.get(path) {
+                if read_at.elapsed().as_secs() < GIT_CACHE_TTL_SECS && *since <= from {
+                    return timestamps.iter().copied().filter(|t| *t >= from).collect();
+                }
+            }
+        }
+
+        let timestamps = GitService::commit_timestamps_since(path, from).unwrap_or_default();
+        if let Ok(mut cache) = git_cache().lock() {
+            cache.insert(path.to_string(), (Instant::now(), from, timestamps.clone()));
+        }
+        timestamps
+    }
+}
@@ -0,0 +1,193 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{AuditEntry, AuditLogFilter, SETTING_AUDIT_MAX_ENTRIES, SETTING_AUDIT_RETENTION_DAYS};
+use crate::services::{DbService, SettingsService};
+use crate::state::AppState;
+use crate::utils::{csv, logging, redact};
+use rusqlite::Connection;
+use serde_json::Value;
+use std::fs;
+use std::future::Future;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::instrument;
+
+/// Time between two prunings of the audit log
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default number of entries returned by list_audit_log
+const DEFAULT_AUDIT_LIMIT: i64 = 200;
+
+/// Audit service recording mutating commands for lab-notebook compliance
+pub struct AuditService;
+
+impl AuditService {
+    /// Run a command and record its name, redacted arguments and outcome
+    #[instrument(level = "debug", skip_all)]
+    pub async fn track<T, F>(state: &AppState, command: &str, args: Value, fut: F) -> AppResult<T>
+    where
+        F: Future<Output = AppResult<T>>,
+    {
+        let result = logging::timed(command, fut).await;
+        let outcome = match &result {
+            Ok(_) => "ok",
+            Err(e) => e.code(),
+        };
+
+        // Auditing must never fail the command itself
+        if let Err(e) = Self::record(state, command, &args, outcome) {
+            tracing::warn!("Failed to record audit entry for {}: {}", command, e);
+        }
+
+        result
+    }
+
+    /// Record a single audit entry
+    pub fn record(state: &AppState, command: &str, args: &Value, outcome: &str) -> AppResult<()> {
+        let args_json = serde_json::to_string(&redact::redact_args(args))?;
+        let now = chrono::Utc::now().timestamp();
+
+        let conn = &state.conn()?;
+        DbService::with_busy_retry(|| DbService::insert_audit_entry(conn, command, &args_json, outcome, now))
+    }
+
+    /// Prune the audit log at startup and once a day after that for as long as
+    /// the app runs. The retention settings are read again on every run.
+    pub async fn run_pruner<R: Runtime>(app: AppHandle<R>) {
+        loop {
+            let state = app.state::<AppState>().inner().clone();
+            match state.run_with_retry(Self::prune).await {
+                Ok(removed) if removed > 0 => tracing::info!("Pruned {} audit log entries", removed),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to prune the audit log: {}", e),
+            }
+            tokio::time::sleep(PRUNE_INTERVAL).await;
+        }
+    }
+
+    /// Drop the audit entries the retention settings no longer keep, returning how many were dropped
+    pub fn prune(conn: &Connection) -> AppResult<usize> {
+        let retention_days = SettingsService::get_i64(conn, SETTING_AUDIT_RETENTION_DAYS)?;
+        let max_entries = SettingsService::get_i64(conn, SETTING_AUDIT_MAX_ENTRIES)?;
+
+        let cutoff = if retention_days > 0 {
+            chrono::Utc::now().timestamp() - retention_days * 86_400
+        } else {
+            i64::MIN
+        };
+        // A negative LIMIT keeps every entry
+        let max_entries = if max_entries > 0 { max_entries } else { -1 };
+        DbService::prune_audit_log(conn, cutoff, max_entries)
+    }
+
+    /// List audit entries, newest first
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_audit_log(state: &AppState, filter: Option<AuditLogFilter>, limit: Option<i64>) -> AppResult<Vec<AuditEntry>> {
+        state.run(move |conn| {
+            let limit = limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+            if limit <= 0 {
+                return Err(AppError::InvalidInput("Limit must be greater than 0".into()));
+            }
+
+            DbService::list_audit_entries(conn, &filter.unwrap_or_default(), limit)
+        }).await
+    }
+
+    /// Export audit entries in a time window to a CSV file, returning the number of rows written
+    #[instrument(level = "debug", skip_all)]
+    pub async fn export_audit_log_csv(state: &AppState, path: String, from: Option<i64>, to: Option<i64>) -> AppResult<usize> {
+        state.blocking(move |state| {
+            if path.is_empty() {
+                return Err(AppError::InvalidInput("Export path cannot be empty".into()));
+            }
+
+            let filter = AuditLogFilter {
+                from,
+                to,
+                ..Default::default()
+            };
+
+            let entries = {
+                let conn = &state.conn()?;
+                DbService::list_audit_entries(conn, &filter, -1)?
+            };
+
+            let mut output = csv::format_row(&["timestamp", "command", "outcome", "args"]);
+            for entry in entries.iter().rev() {
+                let timestamp = chrono::DateTime::from_timestamp(entry.timestamp, 0)
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default();
+                let args = entry.args.as_ref().map(|a| a.to_string()).unwrap_or_default();
+                output.push_str(&csv::format_row(&[
+                    timestamp.as_str(),
+                    entry.command.as_str(),
+                    entry.outcome.as_str(),
+                    args.as_str(),
+                ]));
+            }
+
+            fs::write(&path, output)?;
+            Ok(entries.len())
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use rusqlite::params;
+
+    fn insert(conn: &Connection, command: &str, timestamp: i64) {
+        DbService::insert_audit_entry(conn, command, "{}", "ok", timestamp).unwrap();
+    }
+
+    fn commands(conn: &Connection) -> Vec<String> {
+        DbService::list_audit_entries(conn, &AuditLogFilter::default(), -1)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.command)
+            .collect()
+    }
+
+    #[test]
+    fn pruning_follows_the_retention_settings() {
+        let conn = &test_support::open_db();
+        let now = chrono::Utc::now().timestamp();
+        insert(conn, "ancient", now - 100 * 86_400);
+        insert(conn, "old", now - 10 * 86_400);
+        insert(conn, "recent", now - 60);
+        insert(conn, "newest", now);
+
+        // Defaults: 90 days, 10 000 entries
+        assert_eq!(AuditService::prune(conn).unwrap(), 1);
+        assert_eq!(commands(conn), vec!["newest", "recent", "old"]);
+
+        SettingsService::set_i64(conn, SETTING_AUDIT_RETENTION_DAYS, 0).unwrap();
+        SettingsService::set_i64(conn, SETTING_AUDIT_MAX_ENTRIES, 2).unwrap();
+        assert_eq!(AuditService::prune(conn).unwrap(), 1);
+        assert_eq!(commands(conn), vec!["newest", "recent"]);
+
+        SettingsService::set_i64(conn, SETTING_AUDIT_RETENTION_DAYS, 1).unwrap();
+        SettingsService::set_i64(conn, SETTING_AUDIT_MAX_ENTRIES, 0).unwrap();
+        conn.execute("UPDATE audit_log SET timestamp = ?1 WHERE command = 'recent'", params![now - 2 * 86_400]).unwrap();
+        assert_eq!(AuditService::prune(conn).unwrap(), 1);
+        assert_eq!(commands(conn), vec!["newest"]);
+    }
+
+    #[tokio::test]
+    async fn recording_does_not_prune() {
+        let state = test_support::open_state();
+        {
+            let conn = &state.conn().unwrap();
+            SettingsService::set_i64(conn, SETTING_AUDIT_MAX_ENTRIES, 1).unwrap();
+        }
+        let args = serde_json::json!({ "content": "private" });
+        AuditService::track(&state, "first", args.clone(), async { Ok(()) }).await.unwrap();
+        AuditService::track(&state, "second", args, async { Ok(()) }).await.unwrap();
+
+        let conn = &state.conn().unwrap();
+        let entries = DbService::list_audit_entries(conn, &AuditLogFilter::default(), -1).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].args, Some(serde_json::json!({ "content": "[redacted 7 chars]" })));
+    }
+}
@@ -0,0 +1,440 @@
+use std::io::Read;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tracing::instrument;
+
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Runtime};
+use tiny_http::{Header, Method, Request, Response, Server};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AppendToNoteDto, AutomationStatus, ChangeAction, ChangeEvent, CreateTaskDto, UpdateNoteDto, SETTING_AUTOMATION_ENABLED,
+    SETTING_AUTOMATION_PORT,
+};
+use crate::services::{
+    AuditService, ChangeEventService, DbService, JumpIndexService, NoteService, SearchService, SettingsService, TaskService,
+};
+use crate::state::AppState;
+use crate::utils::logging;
+
+/// App setting holding the bearer token of the automation API. It is not a
+/// registered setting, so `set_setting` cannot replace it with a weak one.
+const AUTOMATION_TOKEN_KEY: &str = "automation_token";
+
+/// Largest request body read
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// Called with the change a request made, to tell the windows
+type ChangeListener = Arc<dyn Fn(ChangeEvent) + Send + Sync>;
+
+/// A running automation listener
+pub struct AutomationServer {
+    server: Arc<Server>,
+    thread: JoinHandle<()>,
+    port: u16,
+}
+
+impl AutomationServer {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+/// Local HTTP API for scripts and launchers: add tasks, append to notes and
+/// search while the app runs. It listens on 127.0.0.1 only, is off by default
+/// and every request but GET /health needs the bearer token. Requests go
+/// through the same services as the commands, so the same validation applies.
+///
+/// - `GET /health`
+/// - `POST /tasks` with a `CreateTaskDto`
+/// - `POST /notes/append` with `{"note_id", "text"}`
+/// - `GET /search?q=...&limit=...`
+pub struct AutomationService;
+
+impl AutomationService {
+    /// Turn the API on: remember it for the next start, create the token when
+    /// there is none yet and start listening
+    #[instrument(level = "debug", skip_all)]
+    pub async fn start<R: Runtime>(app: &AppHandle<R>, state: &AppState) -> AppResult<AutomationStatus> {
+        let app = app.clone();
+        state.blocking(move |state| {
+            {
+                let conn = &state.conn()?;
+                DbService::with_busy_retry(|| SettingsService::set_bool(conn, SETTING_AUTOMATION_ENABLED, true))?;
+                Self::token(conn)?;
+            }
+            Self::listen(&app, state)?;
+            Self::status(state)
+        }).await
+    }
+
+    /// Turn the API off and stop listening
+    #[instrument(level = "debug", skip_all)]
+    pub async fn stop(state: &AppState) -> AppResult<AutomationStatus> {
+        state.blocking(move |state| {
+            {
+                let conn = &state.conn()?;
+                DbService::with_busy_retry(|| SettingsService::set_bool(conn, SETTING_AUTOMATION_ENABLED, false))?;
+            }
+            Self::shut_down(state);
+            Self::status(state)
+        }).await
+    }
+
+    /// Whether the API is on and where it listens
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_status(state: &AppState) -> AppResult<AutomationStatus> {
+        state.blocking(Self::status).await
+    }
+
+    /// The bearer token scripts send as `Authorization: Bearer <token>`,
+    /// created when the API was first turned on
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_token(state: &AppState) -> AppResult<String> {
+        state.run(|conn| {
+            Self::stored_token(conn)?
+                .ok_or_else(|| AppError::InvalidInput("Turn on the automation API first; its token is created then".into()))
+        }).await
+    }
+
+    /// Start listening at app start when the API was left on
+    pub fn start_if_enabled<R: Runtime>(app: &AppHandle<R>) {
+        let state = app.state::<AppState>().inner().clone();
+        let enabled = state.conn().and_then(|conn| SettingsService::get_bool(&conn, SETTING_AUTOMATION_ENABLED));
+        match enabled {
+            Ok(true) => {
+                if let Err(e) = Self::listen(app, &state) {
+                    tracing::warn!("Failed to start the automation API: {}", e);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to read the automation settings: {}", e),
+        }
+    }
+
+    /// Stop the listener, if one runs, and wait for its thread to finish the
+    /// request it is on. Called when turning the API off and when the app exits.
+    pub fn shut_down(state: &AppState) {
+        if let Some(running) = state.take_automation() {
+            running.server.unblock();
+            if running.thread.join().is_err() {
+                tracing::warn!("The automation API thread panicked");
+            }
+            tracing::info!("Automation API on port {} stopped", running.port);
+        }
+    }
+
+    /// Bind the configured port and answer requests on a thread of their own,
+    /// replacing a listener that already runs
+    fn listen<R: Runtime>(app: &AppHandle<R>, state: &AppState) -> AppResult<()> {
+        let port = SettingsService::get_i64(&*state.conn()?, SETTING_AUTOMATION_PORT)?;
+        let port = u16::try_from(port).map_err(|_| AppError::InvalidInput(format!("{} is not a port", port)))?;
+        Self::shut_down(state);
+        let app = app.clone();
+        let on_change: ChangeListener = Arc::new(move |event| {
+            ChangeEventService::emit(&app, &event);
+            let _ = JumpIndexService::notify_changed(&app, Ok(()));
+        });
+        let running = Self::bind(state, port, on_change)?;
+        tracing::info!("Automation API listening on 127.0.0.1:{}", running.port);
+        state.set_automation(running);
+        Ok(())
+    }
+
+    fn bind(state: &AppState, port: u16, on_change: ChangeListener) -> AppResult<AutomationServer> {
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|e| AppError::System(format!("Cannot listen on 127.0.0.1:{}: {}", port, e)))?;
+        let port = server.server_addr().to_ip().map(|addr| addr.port()).unwrap_or(port);
+        let server = Arc::new(server);
+
+        let (listener, state) = (Arc::clone(&server), state.clone());
+        let thread = std::thread::Builder::new()
+            .name("automation-api".into())
+            .spawn(move || {
+                // Ends when shut_down unblocks the listener
+                for request in listener.incoming_requests() {
+                    Self::respond(&state, on_change.as_ref(), request);
+                }
+            })?;
+        Ok(AutomationServer { server, thread, port })
+    }
+
+    fn status(state: &AppState) -> AppResult<AutomationStatus> {
+        let port = state.automation_port();
+        Ok(AutomationStatus {
+            enabled: SettingsService::get_bool(&*state.conn()?, SETTING_AUTOMATION_ENABLED)?,
+            running: port.is_some(),
+            port,
+        })
+    }
+
+    fn respond(state: &AppState, on_change: &(dyn Fn(ChangeEvent) + Send + Sync), mut request: Request) {
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .map(|header| header.value.as_str().to_string());
+        let mut body = String::new();
+        let read = request.as_reader().take(MAX_BODY_BYTES + 1).read_to_string(&mut body);
+        let (status, value, change) = match read {
+            Ok(_) if body.len() as u64 > MAX_BODY_BYTES => (413, json!({ "error": "Request body too large" }), None),
+            Ok(_) => Self::handle(state, request.method(), request.url(), authorization.as_deref(), &body),
+            Err(_) => (400, json!({ "error": "The request body is not UTF-8 text" }), None),
+        };
+        if let Some(change) = change {
+            on_change(change);
+        }
+
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("valid header"));
+        if let Err(e) = request.respond(response) {
+            tracing::warn!("Failed to answer an automation request: {}", e);
+        }
+    }
+
+    /// Route one request and give its status code, its JSON body and the
+    /// change it made, if any
+    fn handle(state: &AppState, method: &Method, url: &str, authorization: Option<&str>, body: &str) -> (u16, Value, Option<ChangeEvent>) {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        if (method, path) == (&Method::Get, "/health") {
+            return (200, json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }), None);
+        }
+
+        let token = state.conn().and_then(|conn| Self::stored_token(&conn));
+        let authorized = match (&token, authorization.and_then(|value| value.strip_prefix("Bearer "))) {
+            (Ok(Some(token)), Some(given)) => constant_time_eq(token.as_bytes(), given.trim().as_bytes()),
+            _ => false,
+        };
+        if !authorized {
+            return (401, json!({ "error": "Missing or wrong bearer token" }), None);
+        }
+
+        let result = match (method, path) {
+            (Method::Post, "/tasks") => Self::create_task(state, body),
+            (Method::Post, "/notes/append") => Self::append_to_note(state, body),
+            (Method::Get, "/search") => Self::search(state, query).map(|value| (value, None)),
+            (_, "/tasks" | "/notes/append" | "/search") => return (405, json!({ "error": "Method not allowed" }), None),
+            _ => return (404, json!({ "error": format!("No route for {}", path) }), None),
+        };
+        match result {
+            Ok((value, change)) => (200, value, change),
+            Err(e) => (Self::status_code(&e), json!({ "error": e }), None),
+        }
+    }
+
+    fn create_task(state: &AppState, body: &str) -> AppResult<(Value, Option<ChangeEvent>)> {
+        let data: CreateTaskDto = parse_body(body)?;
+        let args = json!({ "data": &data, "source": "automation" });
+        let task = tauri::async_runtime::block_on(AuditService::track(state, "create_task", args, TaskService::create_task(state, data)))?;
+        let change = ChangeEvent::task(&task.id, &task.project_id, ChangeAction::Created);
+        Ok((serde_json::to_value(task)?, Some(change)))
+    }
+
+    /// Add text to the end of a note, on a new paragraph. The update only
+    /// applies when nobody saved the note in between.
+    fn append_to_note(state: &AppState, body: &str) -> AppResult<(Value, Option<ChangeEvent>)> {
+        let data: AppendToNoteDto = parse_body(body)?;
+        if data.text.trim().is_empty() {
+            return Err(AppError::InvalidInput("Text to append cannot be empty".into()));
+        }
+        let args = json!({ "note_id": &data.note_id, "source": "automation" });
+        let append = async {
+            let note = NoteService::get_note(state, data.note_id.clone()).await?;
+            let separator = match note.content.trim_end_matches(' ') {
+                "" => "",
+                content if content.ends_with("\n\n") => "",
+                content if content.ends_with('\n') => "\n",
+                _ => "\n\n",
+            };
+            let update = UpdateNoteDto {
+                title: None,
+                content: Some(format!("{}{}{}", note.content, separator, data.text)),
+                tags: None,
+                is_pinned: None,
+                expected_updated_at: Some(note.updated_at),
+            };
+            NoteService::update_note(state, note.id, update, false).await
+        };
+        let note = tauri::async_runtime::block_on(AuditService::track(state, "append_to_note", args, append))?;
+        let change = ChangeEvent::note(&note.id, note.project_id.as_deref(), ChangeAction::Updated);
+        Ok((serde_json::to_value(note)?, Some(change)))
+    }
+
+    fn search(state: &AppState, query: &str) -> AppResult<Value> {
+        let mut text = None;
+        let mut limit = None;
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            match key {
+                "q" => text = Some(percent_decode(value)),
+                "limit" => {
+                    limit = Some(value.parse().map_err(|_| AppError::InvalidInput(format!("'{}' is not a limit", value)))?)
+                }
+                _ => {}
+            }
+        }
+        let text = text.ok_or_else(|| AppError::InvalidInput("Missing the q parameter".into()))?;
+        let results = tauri::async_runtime::block_on(logging::timed("global_search", SearchService::global_search(state, text, limit, None)))?;
+        Ok(serde_json::to_value(results)?)
+    }
+
+    fn status_code(error: &AppError) -> u16 {
+        match error {
+            AppError::InvalidInput(_) => 400,
+            AppError::PermissionDenied(_) => 403,
+            AppError::NotFound(..) => 404,
+            AppError::Conflict(_) | AppError::EditConflict { .. } => 409,
+            AppError::Busy(_) => 503,
+            _ => 500,
+        }
+    }
+
+    fn token(conn: &Connection) -> AppResult<String> {
+        if let Some(token) = Self::stored_token(conn)? {
+            return Ok(token);
+        }
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        DbService::with_busy_retry(|| DbService::set_app_setting(conn, AUTOMATION_TOKEN_KEY, &Value::from(token.as_str()).to_string()))?;
+        Ok(token)
+    }
+
+    fn stored_token(conn: &Connection) -> AppResult<Option<String>> {
+        Ok(DbService::get_app_setting(conn, AUTOMATION_TOKEN_KEY)?
+            .and_then(|raw| serde_json::from_str::<String>(&raw).ok())
+            .filter(|token| !token.is_empty()))
+    }
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &str) -> AppResult<T> {
+    serde_json::from_str(body).map_err(|e| AppError::InvalidInput(format!("Invalid request body: {}", e)))
+}
+
+/// Compare secrets without stopping at the first difference
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Decode a query string value: `+` is a space and `%XX` a byte
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |at: usize| (bytes[at] as char).to_digit(16);
+                match (hex(i + 1), hex(i + 2)) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    fn setup() -> (AppState, String, String) {
+        let state = test_support::open_state();
+        let (token, project_id) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Scripts");
+            (AutomationService::token(conn).unwrap(), project.id)
+        };
+        (state, format!("Bearer {}", token), project_id)
+    }
+
+    #[test]
+    fn requests_need_the_token_except_health() {
+        let (state, auth, _) = setup();
+        let status = |method: Method, url: &str, auth: Option<&str>| AutomationService::handle(&state, &method, url, auth, "").0;
+        assert_eq!(status(Method::Get, "/health", None), 200);
+        assert_eq!(status(Method::Get, "/search?q=x", None), 401);
+        assert_eq!(status(Method::Get, "/search?q=x", Some("Bearer nope")), 401);
+        assert_eq!(status(Method::Get, "/search?q=x", Some(&auth)), 200);
+        assert_eq!(status(Method::Get, "/tasks", Some(&auth)), 405);
+        assert_eq!(status(Method::Get, "/nowhere", Some(&auth)), 404);
+        // Turning the API on again keeps the token scripts already use
+        assert_eq!(format!("Bearer {}", AutomationService::token(&state.conn().unwrap()).unwrap()), auth);
+    }
+
+    #[test]
+    fn routes_go_through_the_services() {
+        let (state, auth, project_id) = setup();
+        let call = |method: Method, url: &str, body: Value| {
+            let (status, value, _) = AutomationService::handle(&state, &method, url, Some(&auth), &body.to_string());
+            (status, value)
+        };
+
+        let (status, task) = call(Method::Post, "/tasks", json!({ "project_id": project_id, "title": "Rerun ablation" }));
+        assert_eq!(status, 200);
+        assert_eq!(task["title"], "Rerun ablation");
+        let (status, error) = call(Method::Post, "/tasks", json!({ "project_id": project_id, "title": "  " }));
+        assert_eq!((status, error["error"]["code"].as_str()), (400, Some("INVALID_INPUT")));
+        assert_eq!(call(Method::Post, "/tasks", json!({ "title": "No project" })).0, 400);
+
+        let note = test_support::note(&state.conn().unwrap(), &project_id, "Log", "Started the run\n");
+        let (status, appended) = call(Method::Post, "/notes/append", json!({ "note_id": note.id, "text": "Loss diverged" }));
+        assert_eq!(status, 200);
+        assert_eq!(appended["content"], "Started the run\n\nLoss diverged");
+        let (status, _) = call(Method::Post, "/notes/append", json!({ "note_id": "missing", "text": "x" }));
+        assert_eq!(status, 404);
+
+        let (status, results) = call(Method::Get, "/search?q=ablation&limit=5", Value::Null);
+        assert_eq!(status, 200);
+        assert_eq!(results.as_array().map(Vec::len), Some(1));
+        let (status, results) = call(Method::Get, "/search?q=loss+diverged", Value::Null);
+        assert_eq!((status, results[0]["title"].as_str()), (200, Some("Log")));
+        assert_eq!(call(Method::Get, "/search", Value::Null).0, 400);
+    }
+
+    #[test]
+    fn listener_answers_on_loopback_and_stops() {
+        let (state, auth, project_id) = setup();
+        let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&changes);
+        let running = AutomationService::bind(&state, 0, Arc::new(move |event| seen.lock().unwrap().push(event))).unwrap();
+        let base = format!("http://127.0.0.1:{}", running.port());
+        state.set_automation(running);
+
+        let read = |response: ureq::Response| serde_json::from_str::<Value>(&response.into_string().unwrap()).unwrap();
+        let health = read(ureq::get(&format!("{}/health", base)).call().unwrap());
+        assert_eq!(health["status"], "ok");
+        match ureq::get(&format!("{}/search?q=x", base)).call() {
+            Err(ureq::Error::Status(code, _)) => assert_eq!(code, 401),
+            other => panic!("expected 401, got {:?}", other.map(|r| r.status())),
+        }
+        let created = read(
+            ureq::post(&format!("{}/tasks", base))
+                .set("Authorization", &auth)
+                .send_string(&json!({ "project_id": project_id, "title": "From a script" }).to_string())
+                .unwrap(),
+        );
+        assert_eq!(created["title"], "From a script");
+        assert_eq!(changes.lock().unwrap().len(), 1);
+
+        AutomationService::shut_down(&state);
+        assert_eq!(state.automation_port(), None);
+        assert!(ureq::get(&format!("{}/health", base)).call().is_err());
+    }
+
+    #[test]
+    fn query_values_are_decoded() {
+        assert_eq!(percent_decode("deep+learning%3A%20caf%C3%A9"), "deep learning: café");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
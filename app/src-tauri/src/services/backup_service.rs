@@ -0,0 +1,610 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::instrument;
+
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    BackupResult, PortableBackupManifest, PortableBackupProgress, PortableBackupProject, PortableBackupResult,
+    PortableRestoreResult, ProjectSort, RestoredProjectPath, PORTABLE_BACKUP_FORMAT_VERSION, SETTING_BACKUP_DIR, SETTING_BACKUP_ENABLED, SETTING_BACKUP_INTERVAL_HOURS,
+    SETTING_BACKUP_RETENTION_COUNT, SETTING_BACKUP_RETENTION_DAYS, SETTING_LAST_BACKUP_AT,
+};
+use crate::services::note_attachment_service::ATTACHMENTS_DIR;
+use crate::services::{DbService, NoteAttachmentService, SettingsService};
+use crate::state::AppState;
+use crate::utils::archive::{self, ArchiveReader, ArchiveWriter};
+use crate::utils::sanitize;
+
+/// Event sent after a backup was written, with its `BackupResult`
+pub const BACKUP_COMPLETED_EVENT: &str = "backup:completed";
+
+/// Event sent after a backup failed, with the error
+pub const BACKUP_FAILED_EVENT: &str = "backup:failed";
+
+/// Event sent while a portable backup is written or restored, with its `PortableBackupProgress`
+pub const PORTABLE_BACKUP_PROGRESS_EVENT: &str = "backup:portable-progress";
+
+/// Entries of a portable backup: the manifest, the database and, under
+/// `projects/<project id>/`, each project's attachments at their relative paths
+const PORTABLE_MANIFEST_ENTRY: &str = "manifest.json";
+const PORTABLE_DATABASE_ENTRY: &str = "research.db";
+const PORTABLE_PROJECTS_DIR: &str = "projects";
+
+/// Largest manifest read from a portable backup
+const MAX_MANIFEST_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Longest the scheduler sleeps before reading the backup settings again
+const SCHEDULER_POLL: Duration = Duration::from_secs(15 * 60);
+
+/// Folder in the app data directory used when no backup folder is set
+const DEFAULT_BACKUP_SUBDIR: &str = "backups";
+
+/// Backup file names are `research-<UTC timestamp>.db`, so they sort by age
+const BACKUP_PREFIX: &str = "research-";
+const BACKUP_EXTENSION: &str = ".db";
+
+/// Copies of the database written on a schedule and on demand
+pub struct BackupService;
+
+impl BackupService {
+    /// Back up the database now, then tell the windows how it went. The scheduler
+    /// and the `run_backup_now` command both go through here.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn run_backup<R: Runtime>(app: &AppHandle<R>, state: &AppState) -> AppResult<BackupResult> {
+        let result = state.blocking(Self::backup).await;
+        match &result {
+            Ok(backup) => {
+                tracing::info!("Backup written to {} ({} bytes)", backup.path, backup.size_bytes);
+                if let Err(e) = app.emit(BACKUP_COMPLETED_EVENT, backup) {
+                    tracing::warn!("Failed to emit {}: {}", BACKUP_COMPLETED_EVENT, e);
+                }
+            }
+            Err(error) => {
+                tracing::error!("Backup failed: {}", error);
+                if let Err(e) = app.emit(BACKUP_FAILED_EVENT, error) {
+                    tracing::warn!("Failed to emit {}: {}", BACKUP_FAILED_EVENT, e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Write the database and every project's attachments directory to one
+    /// archive at `path`, with a manifest of the app and schema versions and the
+    /// project paths. Progress is sent as `PORTABLE_BACKUP_PROGRESS_EVENT` after
+    /// every file.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_portable_backup<R: Runtime>(app: &AppHandle<R>, state: &AppState, path: String) -> AppResult<PortableBackupResult> {
+        let app = app.clone();
+        state.blocking(move |state| Self::write_portable(state, &path, &|progress| Self::emit_progress(&app, progress))).await
+    }
+
+    /// Replace the open database with the one in a portable backup and unpack
+    /// the attachments. Every project gets a folder under `restore_projects_to`,
+    /// named after its old one, and its path is updated to match. The database
+    /// is unpacked and checked beside the current one, then swapped in with a
+    /// single rename, so an interrupted restore leaves the current database in
+    /// place. The current database is backed up first, as a scheduled backup.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn restore_portable_backup<R: Runtime>(
+        app: &AppHandle<R>,
+        state: &AppState,
+        path: String,
+        restore_projects_to: String,
+    ) -> AppResult<PortableRestoreResult> {
+        let app = app.clone();
+        state.blocking(move |state| {
+            Self::restore_portable(state, &path, &restore_projects_to, &|progress| Self::emit_progress(&app, progress))
+        }).await
+    }
+
+    /// Run scheduled backups for as long as the app runs. Settings are read again
+    /// on every wake-up, so changes apply without a restart. A failed backup is
+    /// retried on the next wake-up and never stops the loop.
+    pub async fn run_scheduler<R: Runtime>(app: AppHandle<R>) {
+        loop {
+            let state = app.state::<AppState>().inner().clone();
+            let wait = match state.run_with_retry(Self::time_until_due).await {
+                Ok(Some(wait)) if wait.is_zero() => {
+                    let _ = Self::run_backup(&app, &state).await;
+                    SCHEDULER_POLL
+                }
+                Ok(Some(wait)) => wait.min(SCHEDULER_POLL),
+                Ok(None) => SCHEDULER_POLL,
+                Err(e) => {
+                    tracing::warn!("Failed to read the backup schedule: {}", e);
+                    SCHEDULER_POLL
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Time left until the next scheduled backup, zero when one is due;
+    /// None when scheduled backups are off
+    fn time_until_due(conn: &Connection) -> AppResult<Option<Duration>> {
+        if !SettingsService::get_bool(conn, SETTING_BACKUP_ENABLED)? {
+            return Ok(None);
+        }
+
+        let interval_secs = SettingsService::get_i64(conn, SETTING_BACKUP_INTERVAL_HOURS)?.max(1) * 3600;
+        let last_backup_at = SettingsService::get_i64(conn, SETTING_LAST_BACKUP_AT)?;
+        let elapsed = chrono::Utc::now().timestamp() - last_backup_at;
+
+        Ok(Some(Duration::from_secs((interval_secs - elapsed).max(0) as u64)))
+    }
+
+    /// Write a backup, record when it finished and prune old ones.
+    /// Holds the maintenance lock throughout, so it never overlaps a restore.
+    fn backup(state: &AppState) -> AppResult<BackupResult> {
+        let _maintenance = state.begin_maintenance()?;
+        let conn = &state.conn()?;
+
+        let dir = Self::backup_dir(conn)?;
+        fs::create_dir_all(&dir)?;
+
+        let now = chrono::Utc::now();
+        let name = format!("{}{}{}", BACKUP_PREFIX, now.format("%Y%m%d-%H%M%S"), BACKUP_EXTENSION);
+        let path = dir.join(&name);
+        if path.exists() {
+            return Err(AppError::Conflict(format!("Backup '{}' already exists", name)));
+        }
+
+        // Written under a temporary name so a half-written file is never taken for a backup
+        let partial = dir.join(format!("{}.partial", name));
+        let _ = fs::remove_file(&partial);
+        let written = DbService::with_busy_retry(|| DbService::backup_into(conn, &partial.to_string_lossy()))
+            .and_then(|()| Ok(fs::rename(&partial, &path)?));
+        if let Err(e) = written {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+
+        DbService::with_busy_retry(|| SettingsService::set_i64(conn, SETTING_LAST_BACKUP_AT, now.timestamp()))?;
+
+        let keep = SettingsService::get_i64(conn, SETTING_BACKUP_RETENTION_COUNT)?.max(1) as usize;
+        let max_age_days = SettingsService::get_i64(conn, SETTING_BACKUP_RETENTION_DAYS)?;
+
+        Ok(BackupResult {
+            size_bytes: fs::metadata(&path)?.len(),
+            path: path.to_string_lossy().into_owned(),
+            created_at: now.timestamp(),
+            pruned: Self::prune(&dir, &name, keep, max_age_days),
+        })
+    }
+
+    fn write_portable(state: &AppState, path: &str, progress: &dyn Fn(&PortableBackupProgress)) -> AppResult<PortableBackupResult> {
+        let dest = Path::new(path.trim());
+        if !dest.is_absolute() {
+            return Err(AppError::InvalidInput("The backup file must be an absolute path".into()));
+        }
+        if dest.is_dir() {
+            return Err(AppError::InvalidInput(format!("'{}' is a folder", dest.display())));
+        }
+        let _maintenance = state.begin_maintenance()?;
+
+        let partial = PathBuf::from(format!("{}.partial", dest.display()));
+        let snapshot = PathBuf::from(format!("{}.db.partial", dest.display()));
+        let _ = fs::remove_file(&snapshot);
+        let now = chrono::Utc::now().timestamp();
+        let (projects, schema_version) = {
+            let conn = &state.conn()?;
+            DbService::with_busy_retry(|| DbService::backup_into(conn, &snapshot.to_string_lossy()))?;
+            (
+                DbService::get_all_projects(conn, true, ProjectSort::default())?,
+                DbService::get_schema_version(conn)?.current,
+            )
+        };
+
+        let written = (|| -> AppResult<PortableBackupResult> {
+            let mut files = Vec::new();
+            let mut manifest = PortableBackupManifest {
+                format_version: PORTABLE_BACKUP_FORMAT_VERSION,
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                schema_version,
+                created_at: now,
+                projects: Vec::with_capacity(projects.len()),
+            };
+            for project in &projects {
+                let attachments = Self::attachment_files(&project.path);
+                manifest.projects.push(PortableBackupProject {
+                    id: project.id.clone(),
+                    name: project.name.clone(),
+                    path: project.path.clone(),
+                    attachment_files: attachments.len(),
+                });
+                files.extend(attachments.into_iter().map(|(relative_path, file, size)| {
+                    (format!("{}/{}/{}", PORTABLE_PROJECTS_DIR, project.id, relative_path), file, size)
+                }));
+            }
+            let attachment_files = files.len();
+            files.insert(0, (PORTABLE_DATABASE_ENTRY.to_string(), snapshot.clone(), fs::metadata(&snapshot)?.len()));
+
+            let mut status = PortableBackupProgress {
+                operation: "create".to_string(),
+                files_done: 0,
+                files_total: files.len(),
+                bytes_done: 0,
+                bytes_total: files.iter().map(|(_, _, size)| size).sum(),
+            };
+            let mut archive = ArchiveWriter::new(BufWriter::new(File::create(&partial)?), now);
+            archive.add_bytes(PORTABLE_MANIFEST_ENTRY, &serde_json::to_vec_pretty(&manifest)?)?;
+            for (name, file, size) in &files {
+                archive.add_file(name, file)?;
+                status.files_done += 1;
+                status.bytes_done += size;
+                progress(&status);
+            }
+            archive.finish()?;
+            fs::rename(&partial, dest)?;
+
+            Ok(PortableBackupResult {
+                path: dest.to_string_lossy().into_owned(),
+                size_bytes: fs::metadata(dest)?.len(),
+                created_at: now,
+                projects: projects.len(),
+                attachment_files,
+            })
+        })();
+        let _ = fs::remove_file(&snapshot);
+        if written.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+        written
+    }
+
+    fn restore_portable(
+        state: &AppState,
+        path: &str,
+        restore_projects_to: &str,
+        progress: &dyn Fn(&PortableBackupProgress),
+    ) -> AppResult<PortableRestoreResult> {
+        let root = Path::new(restore_projects_to.trim());
+        if !root.is_absolute() {
+            return Err(AppError::InvalidInput("The folder to restore projects to must be an absolute path".into()));
+        }
+        let db_path = state.db_path().ok_or_else(|| AppError::System("Database not initialized".into()))?;
+
+        let archive_error = |e: io::Error| match e.kind() {
+            io::ErrorKind::NotFound => AppError::NotFound("File", path.to_string()),
+            io::ErrorKind::InvalidData => AppError::InvalidInput(format!("'{}' is not a valid portable backup: {}", path, e)),
+            _ => AppError::FileSystem(e),
+        };
+        let mut archive = ArchiveReader::open(Path::new(path)).map_err(archive_error)?;
+        let entries = archive.entries().to_vec();
+        if let Some(entry) = entries.iter().find(|entry| !entry.enclosed) {
+            return Err(AppError::InvalidInput(format!(
+                "The backup entry '{}' points outside the backup; refusing to restore it",
+                entry.name
+            )));
+        }
+        let find_entry = |name: &str| entries.iter().find(|entry| entry.name == name);
+
+        let manifest_entry = find_entry(PORTABLE_MANIFEST_ENTRY)
+            .ok_or_else(|| AppError::InvalidInput(format!("The backup has no {}", PORTABLE_MANIFEST_ENTRY)))?;
+        let manifest: PortableBackupManifest =
+            serde_json::from_str(&archive.read_to_string(manifest_entry, MAX_MANIFEST_BYTES).map_err(archive_error)?)
+                .map_err(|e| AppError::InvalidInput(format!("The backup's {} is invalid: {}", PORTABLE_MANIFEST_ENTRY, e)))?;
+        if manifest.format_version > PORTABLE_BACKUP_FORMAT_VERSION {
+            return Err(AppError::InvalidInput(format!(
+                "Portable backup format {} is newer than this app supports ({})",
+                manifest.format_version, PORTABLE_BACKUP_FORMAT_VERSION
+            )));
+        }
+        let supported = DbService::get_schema_version(&*state.conn()?)?.latest;
+        if manifest.schema_version > supported {
+            return Err(AppError::SchemaTooNew { found: manifest.schema_version, supported });
+        }
+        let database_entry = find_entry(PORTABLE_DATABASE_ENTRY)
+            .ok_or_else(|| AppError::InvalidInput(format!("The backup has no {}", PORTABLE_DATABASE_ENTRY)))?;
+
+        let mut status = PortableBackupProgress {
+            operation: "restore".to_string(),
+            files_done: 0,
+            files_total: entries.len() - 1,
+            bytes_done: 0,
+            bytes_total: entries.iter().filter(|entry| entry.name != PORTABLE_MANIFEST_ENTRY).map(|entry| entry.size).sum(),
+        };
+
+        // Unpacked beside the open database, so swapping it in is one rename
+        let prepared = format!("{}.restore-partial", db_path);
+        let _ = fs::remove_file(&prepared);
+        let restored = (|| -> AppResult<PortableRestoreResult> {
+            archive.extract(database_entry, &mut BufWriter::new(File::create(&prepared)?)).map_err(archive_error)?;
+            status.files_done += 1;
+            status.bytes_done += database_entry.size;
+            progress(&status);
+
+            let projects = Self::restored_project_paths(&manifest, root)?;
+            {
+                let conn = rusqlite::Connection::open(&prepared)?;
+                DbService::configure(&conn)?;
+                let problems = DbService::integrity_check(&conn, true)?;
+                if !problems.is_empty() {
+                    return Err(AppError::InvalidInput(format!("The backup's database is damaged: {}", problems.join("; "))));
+                }
+                DbService::init(&conn)?;
+                for project in &projects {
+                    DbService::update_project_path(&conn, &project.project_id, &project.new_path)?;
+                }
+            }
+
+            let mut attachment_files = 0;
+            for entry in &entries {
+                let Some((project_id, relative_path)) = entry
+                    .name
+                    .strip_prefix(&format!("{}/", PORTABLE_PROJECTS_DIR))
+                    .and_then(|rest| rest.split_once('/'))
+                else {
+                    continue;
+                };
+                let Some(project) = projects.iter().find(|project| project.project_id == project_id) else {
+                    tracing::warn!("Skipping '{}': its project is not in the backup's manifest", entry.name);
+                    continue;
+                };
+                let target = Path::new(&project.new_path).join(relative_path);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                archive.extract(entry, &mut BufWriter::new(File::create(&target)?)).map_err(archive_error)?;
+                attachment_files += 1;
+                status.files_done += 1;
+                status.bytes_done += entry.size;
+                progress(&status);
+            }
+
+            Ok(PortableRestoreResult {
+                schema_version: manifest.schema_version,
+                projects,
+                attachment_files,
+            })
+        })();
+        let restored = restored.and_then(|result| {
+            Self::backup(state)?;
+            state.replace_db_file(&prepared)?;
+            Ok(result)
+        });
+        if restored.is_err() {
+            let _ = fs::remove_file(&prepared);
+        }
+        restored
+    }
+
+    /// A new folder under `root` for every project of a backup, named after the
+    /// last part of its old path and numbered when that name is taken
+    fn restored_project_paths(manifest: &PortableBackupManifest, root: &Path) -> AppResult<Vec<RestoredProjectPath>> {
+        fs::create_dir_all(root)?;
+        let mut restored: Vec<RestoredProjectPath> = Vec::with_capacity(manifest.projects.len());
+        for project in &manifest.projects {
+            let old_name = project.path.trim_end_matches(['/', '\\']).rsplit(['/', '\\']).next().unwrap_or_default();
+            let base = NoteAttachmentService::safe_file_name(if old_name.is_empty() { &project.name } else { old_name });
+            let free = (1..)
+                .map(|n| if n == 1 { root.join(&base) } else { root.join(format!("{} ({})", base, n)) })
+                .find(|candidate| {
+                    let taken = restored.iter().any(|other| Path::new(&other.new_path) == candidate);
+                    let in_use = fs::read_dir(candidate).map(|mut entries| entries.next().is_some()).unwrap_or(candidate.exists());
+                    !taken && !in_use
+                })
+                .unwrap_or_else(|| root.join(&base));
+            fs::create_dir_all(&free)?;
+            restored.push(RestoredProjectPath {
+                project_id: project.id.clone(),
+                name: project.name.clone(),
+                old_path: project.path.clone(),
+                new_path: free.to_string_lossy().into_owned(),
+            });
+        }
+        Ok(restored)
+    }
+
+    /// Files under a project's attachments directory, with their paths relative
+    /// to the project directory and sizes. Links are not followed; a missing
+    /// directory has none.
+    fn attachment_files(project_path: &str) -> Vec<(String, PathBuf, u64)> {
+        let mut files = Vec::new();
+        let mut pending = vec![(ATTACHMENTS_DIR.to_string(), Path::new(project_path).join(ATTACHMENTS_DIR))];
+        while let Some((relative_dir, dir)) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let (Ok(file_type), Some(name)) = (entry.file_type(), entry.file_name().to_str().map(str::to_string)) else {
+                    continue;
+                };
+                let relative_path = format!("{}/{}", relative_dir, name);
+                if file_type.is_dir() {
+                    pending.push((relative_path, entry.path()));
+                } else if file_type.is_file() {
+                    if !archive::is_safe_entry_name(&relative_path) {
+                        tracing::warn!("Skipping attachment '{}' of {}: its name cannot be archived", relative_path, project_path);
+                        continue;
+                    }
+                    let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+                    files.push((relative_path, entry.path(), size));
+                }
+            }
+        }
+        files.sort_unstable();
+        files
+    }
+
+    fn emit_progress<R: Runtime>(app: &AppHandle<R>, progress: &PortableBackupProgress) {
+        if let Err(e) = app.emit(PORTABLE_BACKUP_PROGRESS_EVENT, progress) {
+            tracing::warn!("Failed to emit {}: {}", PORTABLE_BACKUP_PROGRESS_EVENT, e);
+        }
+    }
+
+    /// The configured backup folder, or "backups" in the app data folder
+    fn backup_dir(conn: &Connection) -> AppResult<PathBuf> {
+        let configured = SettingsService::get_string(conn, SETTING_BACKUP_DIR)?;
+        let configured = configured.trim();
+        if !configured.is_empty() {
+            let dir = PathBuf::from(configured);
+            if !dir.is_absolute() {
+                return Err(AppError::InvalidInput("The backup folder must be an absolute path".into()));
+            }
+            return Ok(dir);
+        }
+
+        sanitize::app_data_dir()
+            .map(|dir| Path::new(dir).join(DEFAULT_BACKUP_SUBDIR))
+            .ok_or_else(|| AppError::System("App data folder is not known yet".into()))
+    }
+
+    /// Remove backups beyond the newest `keep`, and any older than `max_age_days`
+    /// when that is positive. The backup just written is always kept. Returns how
+    /// many were removed; a file that cannot be removed is logged and skipped.
+    fn prune(dir: &Path, newest: &str, keep: usize, max_age_days: i64) -> usize {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Failed to list backups in {}: {}", dir.display(), e);
+                return 0;
+            }
+        };
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_EXTENSION))
+            .collect();
+        names.sort_unstable_by(|a, b| b.cmp(a));
+
+        let cutoff = (max_age_days > 0).then(|| chrono::Utc::now() - chrono::Duration::days(max_age_days));
+        let mut pruned = 0;
+        for (index, name) in names.iter().enumerate() {
+            if name == newest {
+                continue;
+            }
+            let path = dir.join(name);
+            let expired = cutoff.is_some_and(|cutoff| {
+                fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified) < cutoff)
+                    .unwrap_or(false)
+            });
+            if index < keep && !expired {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => pruned += 1,
+                Err(e) => tracing::warn!("Failed to remove old backup {}: {}", path.display(), e),
+            }
+        }
+        pruned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use std::sync::Mutex;
+
+    fn count(state: &AppState, sql: &str) -> i64 {
+        state.conn().unwrap().query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn portable_backup_round_trips_database_and_attachments() {
+        let state = test_support::open_state();
+        let work = test_support::temp_dir();
+        let project = {
+            let conn = &state.conn().unwrap();
+            SettingsService::set_string(conn, SETTING_BACKUP_DIR, &work.join("backups").to_string_lossy()).unwrap();
+            let project = test_support::project(conn, "Thesis");
+            test_support::note(conn, &project.id, "Chapter 1", "Draft");
+            project
+        };
+        let attachments = Path::new(&project.path).join(ATTACHMENTS_DIR);
+        fs::create_dir_all(attachments.join("figures")).unwrap();
+        fs::write(attachments.join("paper.pdf"), b"%PDF-1.7").unwrap();
+        fs::write(attachments.join("figures/plot.png"), b"png").unwrap();
+
+        let archive_path = work.join("vault.zip");
+        let seen = Mutex::new(Vec::new());
+        let record = |progress: &PortableBackupProgress| seen.lock().unwrap().push(progress.clone());
+        let written = BackupService::write_portable(&state, &archive_path.to_string_lossy(), &record).unwrap();
+        assert_eq!((written.projects, written.attachment_files), (1, 2));
+        let last = seen.lock().unwrap().last().cloned().unwrap();
+        assert_eq!((last.files_done, last.files_total), (3, 3));
+        assert_eq!(last.bytes_done, last.bytes_total);
+
+        let names: Vec<String> = ArchiveReader::open(&archive_path).unwrap().entries().iter().map(|e| e.name.clone()).collect();
+        assert!(names.contains(&PORTABLE_MANIFEST_ENTRY.to_string()));
+        assert!(names.contains(&PORTABLE_DATABASE_ENTRY.to_string()));
+        assert!(names.contains(&format!("projects/{}/docs/attachments/figures/plot.png", project.id)));
+
+        // Changes made after the backup are undone by the restore
+        {
+            let conn = &state.conn().unwrap();
+            conn.execute("DELETE FROM notes", []).unwrap();
+            test_support::project(conn, "Later");
+        }
+        let root = work.join("restored");
+        seen.lock().unwrap().clear();
+        let restored =
+            BackupService::restore_portable(&state, &archive_path.to_string_lossy(), &root.to_string_lossy(), &record).unwrap();
+        assert_eq!(restored.attachment_files, 2);
+        let old_dir = Path::new(&project.path).file_name().unwrap();
+        let new_path = root.join(old_dir);
+        assert_eq!(restored.projects[0].new_path, new_path.to_string_lossy());
+        assert_eq!(fs::read(new_path.join(ATTACHMENTS_DIR).join("figures/plot.png")).unwrap(), b"png");
+
+        assert_eq!(count(&state, "SELECT COUNT(*) FROM notes"), 1);
+        assert_eq!(count(&state, "SELECT COUNT(*) FROM projects"), 1);
+        let path: String = state.conn().unwrap().query_row("SELECT path FROM projects", [], |row| row.get(0)).unwrap();
+        assert_eq!(path, new_path.to_string_lossy());
+        assert!(!Path::new(&format!("{}.restore-partial", state.db_path().unwrap())).exists());
+        // The database that was replaced is kept as a backup
+        assert_eq!(fs::read_dir(work.join("backups")).unwrap().count(), 1);
+
+        // A second restore to the same folder does not mix into the first one's files
+        fs::remove_dir_all(work.join("backups")).unwrap();
+        let again =
+            BackupService::restore_portable(&state, &archive_path.to_string_lossy(), &root.to_string_lossy(), &|_| {}).unwrap();
+        assert_ne!(again.projects[0].new_path, restored.projects[0].new_path);
+        let _ = fs::remove_dir_all(&work);
+    }
+
+    #[test]
+    fn invalid_portable_backups_leave_the_database_alone() {
+        let state = test_support::open_state();
+        let work = test_support::temp_dir();
+        test_support::project(&state.conn().unwrap(), "Kept");
+        let root = work.join("restored").to_string_lossy().into_owned();
+        let write = |name: &str, manifest: Option<serde_json::Value>| {
+            let path = work.join(name);
+            let mut archive = ArchiveWriter::new(File::create(&path).unwrap(), 0);
+            if let Some(manifest) = manifest {
+                archive.add_bytes(PORTABLE_MANIFEST_ENTRY, manifest.to_string().as_bytes()).unwrap();
+            }
+            archive.add_bytes(PORTABLE_DATABASE_ENTRY, b"not a database").unwrap();
+            archive.finish().unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        let manifest = |schema_version: i64| {
+            serde_json::json!({
+                "format_version": 1,
+                "app_version": "0.1.0",
+                "schema_version": schema_version,
+                "created_at": 0,
+                "projects": [],
+            })
+        };
+
+        let no_manifest = BackupService::restore_portable(&state, &write("a.zip", None), &root, &|_| {});
+        assert!(matches!(no_manifest, Err(AppError::InvalidInput(_))));
+        let newer = BackupService::restore_portable(&state, &write("b.zip", Some(manifest(10_000))), &root, &|_| {});
+        assert!(matches!(newer, Err(AppError::SchemaTooNew { found: 10_000, .. })));
+        let damaged = BackupService::restore_portable(&state, &write("c.zip", Some(manifest(1))), &root, &|_| {});
+        assert!(damaged.is_err());
+        assert!(!Path::new(&format!("{}.restore-partial", state.db_path().unwrap())).exists());
+        assert_eq!(count(&state, "SELECT COUNT(*) FROM projects"), 1);
+        let _ = fs::remove_dir_all(&work);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::error::AppResult;
+use crate::models::{BootstrapData, ProjectSort};
+use crate::services::{DbService, SettingsService};
+use crate::state::AppState;
+use tracing::instrument;
+
+/// Activity entries sent with the startup data, as many as the feed shows at first
+const BOOTSTRAP_ACTIVITY_LIMIT: i64 = 50;
+
+/// Data the app loads once at startup
+pub struct BootstrapService;
+
+impl BootstrapService {
+    /// Projects with counts, settings, favorites, pending reminders and recent
+    /// activity, read on one connection inside one read transaction so they
+    /// agree with each other
+    #[instrument(level = "debug", skip_all)]
+    pub async fn bootstrap(state: &AppState) -> AppResult<BootstrapData> {
+        state.run_with_retry(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            let data = BootstrapData {
+                projects: DbService::get_all_projects_with_counts(
+                    &tx,
+                    false,
+                    ProjectSort::default(),
+                    chrono::Utc::now().timestamp(),
+                )?,
+                settings: SettingsService::all_settings(&tx)?,
+                favorite_projects: DbService::get_favorite_projects(&tx)?,
+                pending_reminders: DbService::get_pending_reminders(&tx, None)?,
+                recent_activity: DbService::list_activity(&tx, None, BOOTSTRAP_ACTIVITY_LIMIT, None, None)?,
+            };
+            tx.commit()?;
+            Ok(data)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProjectStatus, SETTING_TIMEZONE};
+    use crate::services::{test_support, ActivityService, ProjectService, ReminderService};
+    use serde_json::{json, Value};
+
+    fn to_json<T: serde::Serialize>(value: &T) -> Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn bootstrap_returns_what_the_individual_queries_return() {
+        let state = test_support::open_state();
+        let now = chrono::Utc::now().timestamp();
+        let day = 86_400;
+        let favorite = {
+            let conn = &state.conn().unwrap();
+            let thesis = test_support::project(conn, "Thesis");
+            let paper = test_support::project(conn, "Paper");
+            let old = test_support::project(conn, "Old");
+            conn.execute(
+                "UPDATE projects SET status = ?1 WHERE id = ?2",
+                rusqlite::params![ProjectStatus::Archived.as_str(), old.id],
+            )
+            .unwrap();
+            DbService::set_project_favorite(conn, &paper.id, true).unwrap();
+
+            // Enough activity that the feed is cut at its limit
+            for i in 0..60 {
+                let mut task = test_support::new_task(&thesis.id, &format!("Task {}", i));
+                task.due_date = Some(now + (i - 10) * day);
+                if i % 3 == 0 {
+                    task.status = "done".into();
+                }
+                if i % 7 == 0 {
+                    task.remind_at = Some(now + (i + 1) * day);
+                }
+                DbService::insert_task_with_key(conn, &mut task).unwrap();
+            }
+            test_support::task(conn, &old.id, "Archived project task");
+            test_support::note(conn, &thesis.id, "Idea", "body");
+            test_support::note(conn, &paper.id, "Draft", "body");
+            paper
+        };
+        SettingsService::set_setting(&state, SETTING_TIMEZONE.into(), json!("UTC+2")).await.unwrap();
+
+        let data = BootstrapService::bootstrap(&state).await.unwrap();
+
+        let projects = ProjectService::list_projects_with_counts(&state, None, None).await.unwrap();
+        let settings = SettingsService::get_all_settings(&state).await.unwrap();
+        let favorites = DbService::get_favorite_projects(&state.conn().unwrap()).unwrap();
+        let reminders = ReminderService::list_pending_reminders(&state).await.unwrap();
+        let activity = ActivityService::list_activity(&state, None, Some(BOOTSTRAP_ACTIVITY_LIMIT), None, None).await.unwrap();
+
+        assert_eq!(to_json(&data.projects), to_json(&projects));
+        assert_eq!(to_json(&data.settings), to_json(&settings));
+        assert_eq!(to_json(&data.favorite_projects), to_json(&favorites));
+        assert_eq!(to_json(&data.pending_reminders), to_json(&reminders));
+        assert_eq!(to_json(&data.recent_activity), to_json(&activity));
+
+        // The parts are the expected ones, not just equal to each other
+        let names: Vec<&str> = data.projects.iter().map(|p| p.project.name.as_str()).collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"Thesis") && names.contains(&"Paper"));
+        let thesis = data.projects.iter().find(|p| p.project.name == "Thesis").unwrap();
+        assert_eq!((thesis.open_task_count, thesis.done_task_count, thesis.note_count), (40, 20, 1));
+        assert_eq!(thesis.overdue_count, 6);
+        assert_eq!(data.settings[SETTING_TIMEZONE], "UTC+2");
+        assert_eq!(data.favorite_projects.len(), 1);
+        assert_eq!(data.favorite_projects[0].id, favorite.id);
+        // Reminders of done tasks are not pending
+        assert_eq!(data.pending_reminders.len(), 6);
+        assert_eq!(data.recent_activity.len() as i64, BOOTSTRAP_ACTIVITY_LIMIT);
+    }
+
+    #[tokio::test]
+    async fn bootstrap_of_an_empty_vault_has_only_settings() {
+        let state = test_support::open_state();
+        let data = BootstrapService::bootstrap(&state).await.unwrap();
+        assert!(data.projects.is_empty());
+        assert!(data.favorite_projects.is_empty());
+        assert!(data.pending_reminders.is_empty());
+        assert!(data.recent_activity.is_empty());
+        assert_eq!(to_json(&data.settings), to_json(&SettingsService::get_all_settings(&state).await.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn bootstrap_reports_an_unopened_database() {
+        let state = AppState::new();
+        assert!(BootstrapService::bootstrap(&state).await.is_err());
+    }
+}
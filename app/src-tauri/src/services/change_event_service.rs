@@ -0,0 +1,83 @@
+use crate::error::AppResult;
+use crate::models::{ChangeEvent, EntityType};
+use crate::services::DbService;
+use crate::state::AppState;
+use tauri::{AppHandle, Emitter, Runtime};
+use tracing::instrument;
+
+/// Change events that keep several open windows in sync
+pub struct ChangeEventService;
+
+impl ChangeEventService {
+    /// Send a change event to every window. A failure is logged, never returned:
+    /// the change itself has already been committed.
+    pub fn emit<R: Runtime>(app: &AppHandle<R>, event: &ChangeEvent) {
+        let name = event.name();
+        if let Err(e) = app.emit(&name, event) {
+            tracing::warn!("Failed to emit {}: {}", name, e);
+        }
+    }
+
+    /// Emit the events `events` builds from the value of a command that succeeded.
+    /// Commands call this once their service call has returned, so the events
+    /// always follow the commit.
+    pub fn notify<R: Runtime, T>(app: &AppHandle<R>, result: AppResult<T>, events: impl FnOnce(&T) -> Vec<ChangeEvent>) -> AppResult<T> {
+        if let Ok(value) = &result {
+            for event in events(value) {
+                Self::emit(app, &event);
+            }
+        }
+        result
+    }
+
+    /// Project of a task or note about to be deleted, looked up beforehand so its
+    /// deletion event can carry it. None when it cannot be found.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn project_before_delete(state: &AppState, entity: EntityType, id: &str) -> Option<String> {
+        let lookup_id = id.to_string();
+        let project_id = state
+            .run(move |conn| DbService::get_entity_project_id(conn, entity, &lookup_id))
+            .await;
+        project_id.unwrap_or_else(|e| {
+            tracing::warn!("Could not look up the project of {} {}: {}", entity.label(), id, e);
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ChangeAction;
+    use crate::services::test_support;
+
+    #[test]
+    fn events_are_named_and_shaped_for_the_frontend() {
+        let event = ChangeEvent::task("t1", "p1", ChangeAction::Deleted);
+        assert_eq!(event.name(), "task:deleted");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "entity_type": "task", "entity_id": "t1", "project_id": "p1", "action": "deleted" })
+        );
+        assert_eq!(ChangeEvent::project("p1", ChangeAction::Created).project_id.as_deref(), Some("p1"));
+        assert_eq!(ChangeEvent::note("n1", None, ChangeAction::Updated).name(), "note:updated");
+    }
+
+    #[tokio::test]
+    async fn deletions_carry_the_project_looked_up_beforehand() {
+        let state = test_support::open_state();
+        let (project, task, inbox) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let task = test_support::task(conn, &project.id, "Write");
+            let inbox = test_support::new_note(None, "Idea", "");
+            DbService::insert_note(conn, &inbox).unwrap();
+            (project, task, inbox)
+        };
+
+        let found = ChangeEventService::project_before_delete(&state, EntityType::Task, &task.id).await;
+        assert_eq!(found.as_deref(), Some(project.id.as_str()));
+        assert_eq!(ChangeEventService::project_before_delete(&state, EntityType::Note, &inbox.id).await, None);
+        assert_eq!(ChangeEventService::project_before_delete(&state, EntityType::Task, "missing").await, None);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ContextExportOptions, ContextNote, ContextProject, ContextReference, ContextTask, ProjectContext, ProjectContextExport,
+    Reference, Task,
+};
+use crate::services::DbService;
+use crate::state::AppState;
+use crate::utils::text;
+use std::collections::{HashMap, HashSet};
+use tracing::instrument;
+
+/// Number of recent notes included by default
+const DEFAULT_MAX_NOTES: usize = 10;
+
+/// Default maximum characters kept from one note
+const DEFAULT_NOTE_CHAR_BUDGET: usize = 4_000;
+
+/// Default maximum characters of the whole export
+const DEFAULT_TOTAL_CHAR_BUDGET: usize = 60_000;
+
+/// Default share of the total budget reserved for the task list
+const DEFAULT_TASK_SHARE: f64 = 0.25;
+
+/// Number of references included by default
+const DEFAULT_MAX_REFERENCES: usize = 50;
+
+/// Default share of the total budget the references may use
+const DEFAULT_REFERENCE_SHARE: f64 = 0.15;
+
+/// Approximate per-item overhead (labels, punctuation, dates) charged against the budget
+const ITEM_OVERHEAD: usize = 32;
+
+/// Builds assistant-friendly context bundles for a project
+pub struct ContextService;
+
+impl ContextService {
+    /// Export project metadata, open tasks, references and recent notes as one bundle.
+    ///
+    /// The task list may use up to `task_share` of the total budget and the
+    /// references up to `reference_share`; whatever they leave goes to notes,
+    /// which are filled newest first. Each note gets an even share of
+    /// what remains (capped by the per-note budget), so short notes leave room for the
+    /// ones after them. Long notes keep their head and tail around a truncation marker.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_project_context(
+        state: &AppState,
+        project_id: String,
+        options: Option<ContextExportOptions>,
+    ) -> AppResult<ProjectContextExport> {
+        state.blocking(move |state| {
+            let options = options.unwrap_or_default();
+            let format = options.format.clone().unwrap_or_else(|| "json".to_string());
+            if format != "json" && format != "markdown" {
+                return Err(AppError::InvalidInput(format!(
+                    "Invalid format '{}'. Must be one of: json, markdown",
+                    format
+                )));
+            }
+
+            let task_share = options.task_share.unwrap_or(DEFAULT_TASK_SHARE);
+            if !(0.0..=1.0).contains(&task_share) {
+                return Err(AppError::InvalidInput("Task share must be between 0 and 1".into()));
+            }
+            let reference_share = options.reference_share.unwrap_or(DEFAULT_REFERENCE_SHARE);
+            if !(0.0..=1.0).contains(&reference_share) || task_share + reference_share > 1.0 {
+                return Err(AppError::InvalidInput(
+                    "Reference share must be between 0 and 1 and leave room for the task share".into(),
+                ));
+            }
+
+            let max_notes = options.max_notes.unwrap_or(DEFAULT_MAX_NOTES);
+            let max_references = options.max_references.unwrap_or(DEFAULT_MAX_REFERENCES);
+            let note_budget = options.note_char_budget.unwrap_or(DEFAULT_NOTE_CHAR_BUDGET);
+            let total_budget = options.total_char_budget.unwrap_or(DEFAULT_TOTAL_CHAR_BUDGET);
+            let include_content = options.include_note_content.unwrap_or(true);
+
+            let (project, tasks, references, mut notes, done) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                let tasks = DbService::get_tasks_by_project(conn, &project_id)?;
+                let references = DbService::get_references_by_project(conn, &project_id)?;
+                let notes = DbService::get_notes_by_project(conn, &project_id)?;
+                let done = DbService::get_done_status(conn, &project_id)?;
+                (project, tasks, references, notes, done)
+            };
+
+            let context_project = ContextProject {
+                id: project.id,
+                name: project.name,
+                description: project.description,
+                status: project.status,
+                tags: project.tags.unwrap_or_default(),
+            };
+            // The frame every export has: project section, headings and the truncation note
+            let frame = ProjectContext {
+                project: context_project.clone(),
+                tasks: Vec::new(),
+                references: Vec::new(),
+                notes: Vec::new(),
+                truncated: true,
+            };
+            let header_cost = Self::render(&frame, &format)?.chars().count();
+
+            let task_budget = (total_budget as f64 * task_share) as usize;
+            let (context_tasks, tasks_truncated, task_chars) = Self::build_task_tree(tasks, &done, task_budget);
+            let reference_budget = (total_budget as f64 * reference_share) as usize;
+            let (context_references, references_truncated, reference_chars) =
+                Self::take_references(references, max_references, reference_budget);
+
+            // Newest first, id as a tie-breaker so exports are deterministic
+            notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then_with(|| a.id.cmp(&b.id)));
+            let notes_truncated_by_count = notes.len() > max_notes;
+            notes.truncate(max_notes);
+
+            let mut remaining = total_budget.saturating_sub(header_cost + task_chars + reference_chars);
+            let note_count = notes.len();
+            let mut context_notes = Vec::with_capacity(note_count);
+            let mut notes_truncated = notes_truncated_by_count;
+
+            for (index, note) in notes.into_iter().enumerate() {
+                let cost = note.title.chars().count() + ITEM_OVERHEAD;
+                if cost > remaining {
+                    notes_truncated = true;
+                    break;
+                }
+                remaining -= cost;
+
+                let (content, truncated) = if include_content {
+                    let share = (remaining / (note_count - index)).min(note_budget);
+                    let (content, truncated) = text::truncate_middle(&note.content, share);
+                    remaining = remaining.saturating_sub(content.chars().count());
+                    (Some(content), truncated)
+                } else {
+                    (None, false)
+                };
+
+                context_notes.push(ContextNote {
+                    title: note.title,
+                    updated_at: note.updated_at,
+                    tags: note.tags.unwrap_or_default(),
+                    content,
+                    truncated,
+                });
+            }
+
+            let mut context = ProjectContext {
+                project: context_project,
+                tasks: context_tasks,
+                references: context_references,
+                notes: context_notes,
+                truncated: tasks_truncated || references_truncated || notes_truncated,
+            };
+
+            // Enforce the hard cap on the rendered output, dropping the oldest notes
+            // first, then the last references, then the last tasks
+            let mut content = Self::render(&context, &format)?;
+            while content.chars().count() > total_budget
+                && !(context.notes.is_empty() && context.references.is_empty() && context.tasks.is_empty())
+            {
+                if context.notes.pop().is_none() && context.references.pop().is_none() {
+                    context.tasks.pop();
+                }
+                context.truncated = true;
+                content = Self::render(&context, &format)?;
+            }
+
+            Ok(ProjectContextExport {
+                char_count: content.chars().count(),
+                truncated: context.truncated,
+                format,
+                content,
+            })
+        }).await
+    }
+
+    /// Build the open-task forest within a character budget.
+    /// Returns the tasks, whether any were dropped, and the characters used.
+    fn build_task_tree(mut tasks: Vec<Task>, done: &str, budget: usize) -> (Vec<ContextTask>, bool, usize) {
+        tasks.retain(|t| t.status != done);
+        tasks.sort_by(|a, b| {
+            a.order
+                .cmp(&b.order)
+                .then(a.created_at.cmp(&b.created_at))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let open_ids: HashSet<String> = tasks.iter().map(|t| t.id.clone()).collect();
+        let mut children: HashMap<String, Vec<Task>> = HashMap::new();
+        let mut roots = Vec::new();
+
+        for task in tasks {
+            let open_parent = task.parent_id.clone().filter(|p| open_ids.contains(p));
+            match open_parent {
+                Some(parent_id) => children.entry(parent_id).or_default().push(task),
+                None => roots.push(task),
+            }
+        }
+
+        let mut used = 0;
+        let mut truncated = false;
+        let tree = Self::take_tasks(roots, &mut children, budget, &mut used, &mut truncated);
+        (tree, truncated, used)
+    }
+
+    /// The first `max` references in citation key order that fit the budget.
+    /// Returns them, whether any were left out, and the characters used.
+    fn take_references(references: Vec<Reference>, max: usize, budget: usize) -> (Vec<ContextReference>, bool, usize) {
+        let total = references.len();
+        let mut used = 0;
+        let mut taken = Vec::new();
+        for reference in references.into_iter().take(max) {
+            let cost = reference.citation_key.chars().count()
+                + reference.title.chars().count()
+                + reference.authors.iter().map(|a| a.chars().count() + 2).sum::<usize>()
+                + reference.venue.as_deref().map_or(0, |v| v.chars().count())
+                + ITEM_OVERHEAD;
+            if used + cost > budget {
+                break;
+            }
+            used += cost;
+            taken.push(ContextReference {
+                citation_key: reference.citation_key,
+                title: reference.title,
+                authors: reference.authors,
+                year: reference.year,
+                venue: reference.venue,
+            });
+        }
+        let truncated = taken.len() < total;
+        (taken, truncated, used)
+    }
+
+    fn take_tasks(
+        tasks: Vec<Task>,
+        children: &mut HashMap<String, Vec<Task>>,
+        budget: usize,
+        used: &mut usize,
+        truncated: &mut bool,
+    ) -> Vec<ContextTask> {
+        let mut result = Vec::new();
+
+        for task in tasks {
+            let cost = task.title.chars().count() + ITEM_OVERHEAD;
+            if *truncated || *used + cost > budget {
+                *truncated = true;
+                break;
+            }
+            *used += cost;
+
+            let subtasks = children.remove(&task.id).unwrap_or_default();
+            let subtasks = Self::take_tasks(subtasks, children, budget, used, truncated);
+
+            result.push(ContextTask {
+                title: task.title,
+                status: task.status,
+                priority: task.priority,
+                due_date: task.due_date,
+                children: subtasks,
+            });
+        }
+
+        result
+    }
+
+    fn render(context: &ProjectContext, format: &str) -> AppResult<String> {
+        if format == "markdown" {
+            Ok(Self::render_markdown(context))
+        } else {
+            Ok(serde_json::to_string_pretty(context)?)
+        }
+    }
+
+    fn render_markdown(context: &ProjectContext) -> String {
+        let project = &context.project;
+        let mut out = format!("# {}\n\n", project.name);
+
+        if let Some(description) = project.description.as_deref().filter(|d| !d.is_empty()) {
+            out.push_str(description);
+            out.push_str("\n\n");
+        }
+        out.push_str(&format!("Status: {}\n", project.status));
+        if !project.tags.is_empty() {
+            out.push_str(&format!("Tags: {}\n", project.tags.join(", ")));
+        }
+
+        out.push_str("\n## Open tasks\n\n");
+        if context.tasks.is_empty() {
+            out.push_str("_None_\n");
+        }
+        for task in &context.tasks {
+            Self::render_task(&mut out, task, 0);
+        }
+
+        if !context.references.is_empty() {
+            out.push_str("\n## References\n\n");
+            for reference in &context.references {
+                out.push_str(&format!("- [{}] ", reference.citation_key));
+                if !reference.authors.is_empty() {
+                    out.push_str(&reference.authors.join("; "));
+                    out.push(' ');
+                }
+                if let Some(year) = reference.year {
+                    out.push_str(&format!("({}) ", year));
+                }
+                out.push_str(&reference.title);
+                if let Some(venue) = reference.venue.as_deref().filter(|v| !v.is_empty()) {
+                    out.push_str(&format!(". {}", venue));
+                }
+                out.push('\n');
+            }
+        }
+
+        out.push_str("\n## Recent notes\n");
+        for note in &context.notes {
+            out.push_str(&format!("\n### {}\n\nUpdated: {}", note.title, Self::format_date(note.updated_at)));
+            if !note.tags.is_empty() {
+                out.push_str(&format!(" · Tags: {}", note.tags.join(", ")));
+            }
+            out.push('\n');
+            if let Some(content) = &note.content {
+                out.push('\n');
+                out.push_str(content.trim_end());
+                out.push('\n');
+            }
+        }
+
+        if context.truncated {
+            out.push_str("\n_Context truncated to fit the size budget._\n");
+        }
+
+        out
+    }
+
+    fn render_task(out: &mut String, task: &ContextTask, depth: usize) {
+        let due = task
+            .due_date
+            .map(|d| format!(", due {}", Self::format_date(d)))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{}- [{}] {} ({} priority{})\n",
+            "  ".repeat(depth),
+            task.status,
+            task.title,
+            task.priority,
+            due
+        ));
+        for child in &task.children {
+            Self::render_task(out, child, depth + 1);
+        }
+    }
+
+    fn format_date(timestamp: i64) -> String {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use rusqlite::params;
+    use serde_json::Value;
+
+    fn options(format: &str) -> ContextExportOptions {
+        ContextExportOptions { format: Some(format.to_string()), ..Default::default() }
+    }
+
+    /// A project with an open task and its subtask, a done task, two references
+    /// and three notes updated a day apart, the oldest long
+    fn seed(state: &AppState) -> String {
+        let conn = &state.conn().unwrap();
+        let project = test_support::project(conn, "Survey");
+        let parent = test_support::task(conn, &project.id, "Collect papers");
+        let mut child = test_support::new_task(&project.id, "Screen abstracts");
+        child.parent_id = Some(parent.id.clone());
+        DbService::insert_task_with_key(conn, &mut child).unwrap();
+        let done = test_support::task(conn, &project.id, "Pick a topic");
+        conn.execute("UPDATE tasks SET status = 'done' WHERE id = ?1", params![done.id]).unwrap();
+        test_support::reference(conn, &project.id, "vaswani2017", "Attention Is All You Need");
+        test_support::reference(conn, &project.id, "devlin2019", "BERT");
+
+        let long = format!("BEGIN {} END", "x".repeat(20_000));
+        for (age, title, content) in [(3, "Oldest", long.as_str()), (2, "Middle", "Short"), (1, "Newest", "Shorter")] {
+            let note = test_support::note(conn, &project.id, title, content);
+            conn.execute("UPDATE notes SET updated_at = ?1 WHERE id = ?2", params![1_700_000_000 - age * 86_400, note.id]).unwrap();
+        }
+        project.id
+    }
+
+    #[tokio::test]
+    async fn export_is_ordered_and_deterministic() {
+        let state = test_support::open_state();
+        let project_id = seed(&state);
+
+        let first = ContextService::export_project_context(&state, project_id.clone(), None).await.unwrap();
+        let second = ContextService::export_project_context(&state, project_id.clone(), None).await.unwrap();
+        assert_eq!(first.content, second.content);
+        assert_eq!(first.char_count, first.content.chars().count());
+
+        let context: Value = serde_json::from_str(&first.content).unwrap();
+        let tasks = context["tasks"].as_array().unwrap();
+        assert_eq!(tasks.len(), 1, "done tasks are left out");
+        assert_eq!(tasks[0]["title"], "Collect papers");
+        assert_eq!(tasks[0]["children"][0]["title"], "Screen abstracts");
+        let keys: Vec<&str> = context["references"].as_array().unwrap().iter().map(|r| r["citation_key"].as_str().unwrap()).collect();
+        assert_eq!(keys, ["devlin2019", "vaswani2017"]);
+        let titles: Vec<&str> = context["notes"].as_array().unwrap().iter().map(|n| n["title"].as_str().unwrap()).collect();
+        assert_eq!(titles, ["Newest", "Middle", "Oldest"]);
+
+        let limited = ContextExportOptions { max_notes: Some(1), max_references: Some(1), ..options("json") };
+        let limited = ContextService::export_project_context(&state, project_id, Some(limited)).await.unwrap();
+        let context: Value = serde_json::from_str(&limited.content).unwrap();
+        assert_eq!(context["notes"].as_array().unwrap().len(), 1);
+        assert_eq!(context["references"].as_array().unwrap().len(), 1);
+        assert!(limited.truncated);
+    }
+
+    #[tokio::test]
+    async fn long_notes_keep_head_and_tail_within_their_budget() {
+        let state = test_support::open_state();
+        let project_id = seed(&state);
+        let per_note = ContextExportOptions { note_char_budget: Some(500), ..options("json") };
+        let export = ContextService::export_project_context(&state, project_id, Some(per_note)).await.unwrap();
+        let context: Value = serde_json::from_str(&export.content).unwrap();
+
+        let oldest = &context["notes"][2];
+        let content = oldest["content"].as_str().unwrap();
+        assert_eq!(oldest["truncated"], true);
+        assert_eq!(content.chars().count(), 500);
+        assert!(content.starts_with("BEGIN") && content.ends_with("END"));
+        assert!(content.contains(text::TRUNCATION_MARKER));
+        assert_eq!(context["notes"][0]["content"], "Shorter");
+        assert_eq!(context["notes"][0]["truncated"], false);
+    }
+
+    #[tokio::test]
+    async fn short_notes_leave_their_share_to_later_ones_and_the_total_is_capped() {
+        let state = test_support::open_state();
+        let project_id = seed(&state);
+        let budget = 6_000;
+        let notes_only = ContextExportOptions {
+            total_char_budget: Some(budget),
+            note_char_budget: Some(budget),
+            task_share: Some(0.0),
+            reference_share: Some(0.0),
+            ..options("markdown")
+        };
+        let export = ContextService::export_project_context(&state, project_id.clone(), Some(notes_only)).await.unwrap();
+        assert!(export.char_count <= budget);
+        assert!(export.content.contains("### Oldest"));
+        // Three notes share the budget, but the two short ones only take what they need
+        let oldest = export.content.split("### Oldest").nth(1).unwrap();
+        assert!(oldest.chars().count() > budget * 2 / 3, "the long note got {} chars", oldest.chars().count());
+        assert!(!export.content.contains("## Open tasks\n\n- ["));
+
+        for format in ["json", "markdown"] {
+            let tiny = ContextExportOptions { total_char_budget: Some(400), ..options(format) };
+            let export = ContextService::export_project_context(&state, project_id.clone(), Some(tiny)).await.unwrap();
+            assert!(export.char_count <= 400, "{}: {}", format, export.char_count);
+            assert!(export.truncated);
+        }
+    }
+
+    #[tokio::test]
+    async fn options_are_checked() {
+        let state = test_support::open_state();
+        let project_id = seed(&state);
+        for bad in [
+            options("xml"),
+            ContextExportOptions { task_share: Some(1.5), ..Default::default() },
+            ContextExportOptions { task_share: Some(0.7), reference_share: Some(0.5), ..Default::default() },
+        ] {
+            let result = ContextService::export_project_context(&state, project_id.clone(), Some(bad)).await;
+            assert!(matches!(result, Err(AppError::InvalidInput(_))));
+        }
+        let missing = ContextService::export_project_context(&state, "missing".into(), None).await;
+        assert!(matches!(missing, Err(AppError::NotFound(..))));
+    }
+}
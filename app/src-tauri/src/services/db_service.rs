@@ -1,13 +1,107 @@
 //! Database service for SQLite operations
-//! 
-//! Note: Task and Note operations are scaffolded for future Tauri command integration.
-//! They will be wired up when the frontend requires backend persistence for these entities.
 
 #![allow(dead_code)]
 
-use rusqlite::{params, Connection, Row};
-use crate::error::AppResult;
-use crate::models::{Project, Task, Note};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, ToSql};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::error::{AppError, AppResult};
+use crate::utils::{collation, html, markdown, tag_path, text, word_count};
+use crate::models::{
+    ActivityAction, ActivityEntry, AttachmentStoreStats, AuditEntry, AuditLogFilter, ChangedFiles, CheckpointResult, DbInfo, Deadline, FileIndexEntry, FileMetadata, FileSearchResult, GlobalSearchResult, GraphEdge, GraphNode, MoveResult, Note, NoteAttachment, NoteLink, NoteSummary, NoteTemplate, NoteViewState, SaveNoteViewStateDto, Project, ProjectArchive, ProjectFilterDto, ProjectSort, ProjectStatus, ProjectTemplate, ProjectWithCounts, RankTasksResult,
+    EntityType, JumpIndexEntry, ListOptions, Paginated, SortOrder, OrphanReport, OrphanedEntity, Reference, RepairFinding, RepairKind, RepairReport, ResearchQuestion, ResearchQuestionLinks,
+    ProjectStats, ScannedFile, SchemaVersion, SkippedItem, Tag, TagCount, TagMatchMode, TagUsage, Task, TaskFilterDto, TaskParentFilter, TaskProgress, TaskWithProject, TagTime, TaskTime, TimeEntry, TitleCollation, TrashEntry, UpdateNoteDto, UpdateTaskDto, WritingDay,
+    DEFAULT_TASK_STATUSES,
+};
+
+/// How long SQLite itself waits on a locked database before reporting SQLITE_BUSY
+pub const BUSY_TIMEOUT_MS: u64 = 5_000;
+
+/// Page cache per connection; negative values are KiB
+const CACHE_SIZE_KIB: i64 = -16_000;
+
+/// Schema migrations in order; step `i` moves the schema from version `i` to `i + 1`.
+/// Append new steps at the end and never change released ones.
+const MIGRATIONS: &[fn(&Connection) -> AppResult<()>] = &[
+    DbService::migrate_baseline,
+    DbService::migrate_pin_order,
+    DbService::migrate_task_parent_fk,
+    DbService::migrate_trash,
+    DbService::migrate_app_settings,
+    DbService::migrate_activity_log,
+    DbService::migrate_project_favorites,
+    DbService::migrate_time_entries,
+    DbService::migrate_note_templates,
+    DbService::migrate_inbox_notes,
+    DbService::migrate_note_attachments,
+    DbService::migrate_board_order,
+    DbService::migrate_archived_tasks,
+    DbService::migrate_note_word_count,
+    DbService::migrate_references,
+    DbService::migrate_task_recurrence,
+    DbService::migrate_task_reminders,
+    DbService::migrate_file_content_hash,
+    DbService::migrate_startup_indexes,
+    DbService::migrate_project_templates,
+    DbService::migrate_note_view_state,
+    DbService::migrate_note_render_cache,
+    DbService::migrate_reference_reading_status,
+    DbService::migrate_attachment_store,
+    DbService::migrate_writing_goals,
+];
+
+/// Activity entries kept; older ones are pruned as new ones come in
+const ACTIVITY_MAX_ENTRIES: i64 = 20_000;
+
+/// The activity log is pruned after every this many entries
+const ACTIVITY_PRUNE_INTERVAL: i64 = 500;
+
+/// Templates seeded on first run: name, title pattern, content and tags
+const BUILTIN_NOTE_TEMPLATES: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "Experiment log",
+        "Experiment {{date}}: {{experiment}}",
+        "# {{experiment}}\n\nProject: {{project_name}}\nDate: {{date}}\n\n## Hypothesis\n\n\n## Setup\n\n\n## Results\n\n\n## Next steps\n",
+        &["experiment"],
+    ),
+    (
+        "Meeting notes",
+        "Meeting {{date}}",
+        "# Meeting {{date}}\n\nProject: {{project_name}}\nAttendees:\n\n## Agenda\n\n\n## Notes\n\n\n## Action items\n\n- [ ] \n",
+        &["meeting"],
+    ),
+];
+
+/// Attempts made by `with_busy_retry` before giving up
+const BUSY_RETRY_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry; doubled after every attempt
+const BUSY_RETRY_BASE_DELAY_MS: u64 = 25;
+
+/// Columns selected for project rows
+const PROJECT_COLUMNS: &str =
+    "id, name, path, description, status, created_at, last_modified_at, tags, key_prefix, is_favorite";
+
+/// Columns selected for task rows, in the order row_to_task reads them
+const TASK_COLUMNS: &str = r#"id, project_id, parent_id, title, description, status, priority,
+    due_date, completed_at, created_at, updated_at, "order", tags, task_key, rank, recurrence, recurrence_parent_id,
+    remind_at, reminded_at"#;
+
+/// Columns of a reference row as mapped by `row_to_reference`, on the alias `r`
+const REFERENCE_COLUMNS: &str =
+    "r.id, r.project_id, r.citation_key, r.entry_type, r.title, r.authors, r.year, r.venue, r.doi, r.url, r.abstract, r.pdf_path, r.created_at, r.reading_status, r.tags";
+
+/// SQL expression for the done status of the project whose id `project_id` evaluates
+/// to: the last status of its workflow, or "done" for the built-in one
+fn done_status_sql(project_id: &str) -> String {
+    format!(
+        "COALESCE((SELECT ps.name FROM project_statuses ps WHERE ps.project_id = {} ORDER BY ps.position DESC LIMIT 1), 'done')",
+        project_id
+    )
+}
 
 /// Database service for SQLite operations
 pub struct DbService;
@@ -19,12 +113,20 @@ impl DbService {
 
     /// Insert a project into the database
     pub fn insert_project(conn: &Connection, project: &Project) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            Self::insert_project_row(tx, project)?;
+            Self::record_activity(tx, EntityType::Project, &project.id, ActivityAction::Created)
+        })
+    }
+
+    /// Insert a project and its tag links; callers own the transaction
+    fn insert_project_row(conn: &Connection, project: &Project) -> AppResult<()> {
         let tags_json = project.tags.as_ref()
             .map(|t| serde_json::to_string(t).unwrap_or_default());
-        
+
         conn.execute(
-            "INSERT INTO projects (id, name, path, description, status, created_at, last_modified_at, tags)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO projects (id, name, path, description, status, created_at, last_modified_at, tags, key_prefix)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 project.id,
                 project.name,
@@ -34,19 +136,24 @@ impl DbService {
                 project.created_at,
                 project.last_modified_at,
                 tags_json,
+                project.key_prefix,
             ],
         )?;
+        if let Some(tags) = &project.tags {
+            Self::set_entity_tags(conn, EntityType::Project, &project.id, tags)?;
+        }
         Ok(())
     }
 
-    /// Get all projects
-    pub fn get_all_projects(conn: &Connection) -> AppResult<Vec<Project>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, path, description, status, created_at, last_modified_at, tags 
-             FROM projects ORDER BY last_modified_at DESC"
-        )?;
+    /// Get all projects, leaving out archived ones unless `include_archived` is set
+    pub fn get_all_projects(conn: &Connection, include_archived: bool, sort: ProjectSort) -> AppResult<Vec<Project>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM projects WHERE ?1 OR status != 'archived' ORDER BY {}, id ASC",
+            PROJECT_COLUMNS,
+            sort.sql_order()
+        ))?;
         
-        let projects = stmt.query_map([], |row| {
+        let projects = stmt.query_map(params![include_archived], |row| {
             Ok(Self::row_to_project(row))
         })?
         .filter_map(|r| r.ok())
@@ -55,29 +162,185 @@ impl DbService {
         Ok(projects)
     }
 
+    /// Get all projects like get_all_projects, each with its open, done and overdue
+    /// task counts and its note count. Archived tasks are not counted; trashed
+    /// tasks and notes are no longer in their tables.
+    pub fn get_all_projects_with_counts(
+        conn: &Connection,
+        include_archived: bool,
+        sort: ProjectSort,
+        now: i64,
+    ) -> AppResult<Vec<ProjectWithCounts>> {
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {},
+                COALESCE(t.open_task_count, 0) AS open_task_count,
+                COALESCE(t.done_task_count, 0) AS done_task_count,
+                COALESCE(t.overdue_count, 0) AS overdue_count,
+                COALESCE(n.note_count, 0) AS note_count
+             FROM projects
+             LEFT JOIN (
+                SELECT project_id,
+                    SUM(NOT is_done) AS open_task_count,
+                    SUM(is_done) AS done_task_count,
+                    SUM(NOT is_done AND due_date IS NOT NULL AND due_date < ?2) AS overdue_count
+                FROM (
+                    SELECT project_id, due_date, status = {} AS is_done
+                    FROM tasks WHERE NOT is_archived
+                ) GROUP BY project_id
+             ) t ON t.project_id = projects.id
+             LEFT JOIN (
+                SELECT project_id, COUNT(*) AS note_count
+                FROM notes WHERE project_id IS NOT NULL GROUP BY project_id
+             ) n ON n.project_id = projects.id
+             WHERE ?1 OR status != 'archived'
+             ORDER BY {}, id ASC",
+            PROJECT_COLUMNS,
+            done_status_sql("tasks.project_id"),
+            sort.sql_order()
+        ))?;
+
+        let projects = stmt.query_map(params![include_archived, now], |row| {
+            Ok(ProjectWithCounts {
+                project: Self::row_to_project(row),
+                open_task_count: row.get("open_task_count")?,
+                done_task_count: row.get("done_task_count")?,
+                note_count: row.get("note_count")?,
+                overdue_count: row.get("overdue_count")?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(projects)
+    }
+
     /// Get project by ID
     pub fn get_project_by_id(conn: &Connection, id: &str) -> AppResult<Option<Project>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, path, description, status, created_at, last_modified_at, tags 
-             FROM projects WHERE id = ?1"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, metadata FROM projects WHERE id = ?1",
+            PROJECT_COLUMNS
+        ))?;
         
         let mut rows = stmt.query(params![id])?;
         
         if let Some(row) = rows.next()? {
-            Ok(Some(Self::row_to_project(row)))
+            let mut project = Self::row_to_project(row);
+            project.metadata = Self::row_metadata(row);
+            Ok(Some(project))
         } else {
             Ok(None)
         }
     }
 
+    /// Get all projects sorted by name
+    pub fn get_projects_by_name(conn: &Connection, collation: TitleCollation) -> AppResult<Vec<Project>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM projects ORDER BY name COLLATE {} ASC, id ASC",
+            PROJECT_COLUMNS,
+            collation.sql_name()
+        ))?;
+
+        let projects = stmt.query_map([], |row| {
+            Ok(Self::row_to_project(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(projects)
+    }
+
+    /// Filter projects by status and tags
+    pub fn filter_projects(conn: &Connection, filter: &ProjectFilterDto) -> AppResult<Vec<Project>> {
+        // Rows with malformed tag JSON are treated as untagged instead of failing the query
+        const PROJECT_TAGS: &str =
+            "json_each(CASE WHEN json_valid(projects.tags) THEN projects.tags ELSE '[]' END)";
+
+        fn normalized(tags: &Option<Vec<String>>) -> Vec<String> {
+            tags.iter()
+                .flatten()
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect()
+        }
+
+        fn placeholders(count: usize) -> String {
+            vec!["?"; count].join(", ")
+        }
+
+        let mut clauses: Vec<String> = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(status) = &filter.status {
+            clauses.push("status = ?".to_string());
+            values.push(Box::new(*status));
+        }
+
+        let tags_any = normalized(&filter.tags_any);
+        if !tags_any.is_empty() {
+            clauses.push(format!(
+                "EXISTS (SELECT 1 FROM {} WHERE lower(value) IN ({}))",
+                PROJECT_TAGS,
+                placeholders(tags_any.len())
+            ));
+            values.extend(tags_any.into_iter().map(|t| Box::new(t) as Box<dyn ToSql>));
+        }
+
+        for tag in normalized(&filter.tags_all) {
+            clauses.push(format!(
+                "EXISTS (SELECT 1 FROM {} WHERE lower(value) = ?)",
+                PROJECT_TAGS
+            ));
+            values.push(Box::new(tag));
+        }
+
+        let tags_none = normalized(&filter.tags_none);
+        if !tags_none.is_empty() {
+            clauses.push(format!(
+                "NOT EXISTS (SELECT 1 FROM {} WHERE lower(value) IN ({}))",
+                PROJECT_TAGS,
+                placeholders(tags_none.len())
+            ));
+            values.extend(tags_none.into_iter().map(|t| Box::new(t) as Box<dyn ToSql>));
+        }
+
+        let where_clause = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+
+        let query = format!(
+            "SELECT {} FROM projects {} ORDER BY last_modified_at DESC",
+            PROJECT_COLUMNS, where_clause
+        );
+
+        let mut stmt = conn.prepare(&query)?;
+        let projects = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(Self::row_to_project(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(projects)
+    }
+
     /// Update project
-    pub fn update_project(conn: &Connection, id: &str, name: Option<&str>, description: Option<&str>, status: Option<&str>, tags: Option<&Vec<String>>) -> AppResult<()> {
+    /// Update the given fields of a project, returning false when it does not exist or,
+    /// with `expected_modified_at`, when its last_modified_at no longer equals that
+    pub fn update_project(
+        conn: &Connection,
+        id: &str,
+        name: Option<&str>,
+        description: Option<&str>,
+        status: Option<ProjectStatus>,
+        tags: Option<&Vec<String>>,
+        expected_modified_at: Option<i64>,
+    ) -> AppResult<bool> {
         let now = chrono::Utc::now().timestamp();
         
-        // Build dynamic update query
-        let mut updates = vec!["last_modified_at = ?1"];
-        let _params_idx = 2;
+        // Build dynamic update query; last_modified_at always moves forward, so
+        // two updates within a second still differ in version
+        let mut updates = vec!["last_modified_at = MAX(?1, last_modified_at + 1)"];
         
         if name.is_some() { updates.push("name = ?2"); }
         if description.is_some() { updates.push("description = ?3"); }
@@ -85,34 +348,214 @@ impl DbService {
         if tags.is_some() { updates.push("tags = ?5"); }
         
         let query = format!(
-            "UPDATE projects SET {} WHERE id = ?6",
+            "UPDATE projects SET {} WHERE id = ?6 AND (?7 IS NULL OR last_modified_at = ?7)",
             updates.join(", ")
         );
         
         let tags_json = tags.map(|t| serde_json::to_string(t).unwrap_or_default());
         
-        conn.execute(
-            &query,
-            params![
-                now,
-                name.unwrap_or(""),
-                description.unwrap_or(""),
-                status.unwrap_or(""),
-                tags_json.unwrap_or_default(),
-                id,
-            ],
+        Self::with_tx(conn, |tx| {
+            let affected = tx.execute(
+                &query,
+                params![
+                    now,
+                    name.unwrap_or(""),
+                    description.unwrap_or(""),
+                    status.unwrap_or_default(),
+                    tags_json.unwrap_or_default(),
+                    id,
+                    expected_modified_at,
+                ],
+            )?;
+            if affected == 0 {
+                return Ok(false);
+            }
+            if let Some(tags) = tags {
+                Self::set_entity_tags(tx, EntityType::Project, id, tags)?;
+            }
+            if status == Some(ProjectStatus::Archived) {
+                Self::clear_project_favorite(tx, id)?;
+            }
+            Self::record_activity(tx, EntityType::Project, id, ActivityAction::Updated)?;
+            Ok(true)
+        })
+    }
+
+    /// Write back the editable fields of an earlier version of a project, as an undo does.
+    /// A changed key prefix rewrites the task keys again. Returns false when the project does not exist.
+    pub fn restore_project_version(conn: &Connection, project: &Project) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        Self::with_tx(conn, |tx| {
+            let current_prefix: Option<Option<String>> = tx
+                .query_row("SELECT key_prefix FROM projects WHERE id = ?1", params![project.id], |row| row.get(0))
+                .optional()?;
+            let Some(current_prefix) = current_prefix else {
+                return Ok(false);
+            };
+            if let Some(prefix) = project.key_prefix.as_deref() {
+                if current_prefix.as_deref() != Some(prefix) {
+                    Self::set_project_key_prefix(tx, &project.id, prefix)?;
+                }
+            }
+
+            tx.execute(
+                "UPDATE projects SET name = ?1, description = ?2, status = ?3, last_modified_at = MAX(?4, last_modified_at + 1) WHERE id = ?5",
+                params![project.name, project.description, project.status, now, project.id],
+            )?;
+            Self::set_entity_tags(tx, EntityType::Project, &project.id, project.tags.as_deref().unwrap_or_default())?;
+            Self::set_project_favorite(tx, &project.id, project.is_favorite && project.status != ProjectStatus::Archived)?;
+            Self::record_activity(tx, EntityType::Project, &project.id, ActivityAction::Updated)?;
+            Ok(true)
+        })
+    }
+
+    /// Point a project at a new directory, returning false when it does not exist.
+    /// Indexed files are stored relative to the project root and need no change.
+    pub fn update_project_path(conn: &Connection, id: &str, path: &str) -> AppResult<bool> {
+        let tx = conn.unchecked_transaction()?;
+        let affected = tx.execute(
+            "UPDATE projects SET path = ?1, last_modified_at = MAX(?2, last_modified_at + 1) WHERE id = ?3",
+            params![path, chrono::Utc::now().timestamp(), id],
         )?;
-        
-        Ok(())
+        if affected > 0 {
+            Self::record_activity(&tx, EntityType::Project, id, ActivityAction::Updated)?;
+        }
+        tx.commit()?;
+        Ok(affected > 0)
     }
 
     /// Delete project (soft delete)
     pub fn delete_project(conn: &Connection, id: &str) -> AppResult<()> {
         let now = chrono::Utc::now().timestamp();
-        conn.execute(
-            "UPDATE projects SET status = 'archived', last_modified_at = ?1 WHERE id = ?2",
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE projects SET status = 'archived', last_modified_at = MAX(?1, last_modified_at + 1) WHERE id = ?2",
+            params![now, id],
+        )?;
+        Self::clear_project_favorite(&tx, id)?;
+        Self::record_activity(&tx, EntityType::Project, id, ActivityAction::Deleted)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Set an archived project back to active; returns whether a project was restored
+    pub fn restore_project(conn: &Connection, id: &str) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        let changed = conn.execute(
+            "UPDATE projects SET status = 'active', last_modified_at = MAX(?1, last_modified_at + 1) WHERE id = ?2 AND status = 'archived'",
             params![now, id],
         )?;
+        Ok(changed > 0)
+    }
+
+    /// Remove a project row; tasks, notes, deadlines and file metadata go with it through cascading foreign keys
+    pub fn purge_project(conn: &Connection, id: &str) -> AppResult<bool> {
+        let deleted = conn.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+
+    /// Make a project a favorite, placed after the existing ones, or stop it being one.
+    /// Returns false when the project does not exist.
+    pub fn set_project_favorite(conn: &Connection, id: &str, favorite: bool) -> AppResult<bool> {
+        Self::with_tx(conn, |tx| {
+            let affected = tx.execute(
+                "UPDATE projects SET
+                    favorite_order = CASE
+                        WHEN NOT ?1 THEN NULL
+                        WHEN is_favorite THEN favorite_order
+                        ELSE (SELECT COALESCE(MAX(p.favorite_order), 0) + 1 FROM projects p WHERE p.is_favorite)
+                    END,
+                    is_favorite = ?1
+                 WHERE id = ?2",
+                params![favorite, id],
+            )?;
+            if affected > 0 {
+                Self::renumber_favorite_projects(tx)?;
+            }
+            Ok(affected > 0)
+        })
+    }
+
+    /// Get favorite projects in their user-defined order
+    pub fn get_favorite_projects(conn: &Connection) -> AppResult<Vec<Project>> {
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM projects WHERE is_favorite
+             ORDER BY favorite_order IS NULL, favorite_order ASC, last_modified_at DESC, id ASC",
+            PROJECT_COLUMNS
+        ))?;
+
+        let projects = stmt.query_map([], |row| {
+            Ok(Self::row_to_project(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(projects)
+    }
+
+    /// Move a favorite project to `position` (0-based) among the favorites.
+    /// Returns None when the project does not exist and Some(false) when it is not a favorite.
+    pub fn reorder_favorite_project(conn: &Connection, id: &str, position: usize) -> AppResult<Option<bool>> {
+        let tx = conn.unchecked_transaction()?;
+
+        let is_favorite: Option<bool> = tx
+            .query_row("SELECT is_favorite FROM projects WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        let Some(is_favorite) = is_favorite else {
+            return Ok(None);
+        };
+        if !is_favorite {
+            return Ok(Some(false));
+        }
+
+        let mut ids = Self::favorite_project_ids(&tx)?;
+        ids.retain(|favorite| favorite != id);
+        ids.insert(position.min(ids.len()), id.to_string());
+        Self::write_favorite_order(&tx, &ids)?;
+
+        tx.commit()?;
+        Ok(Some(true))
+    }
+
+    /// Stop an archived project being a favorite; callers own the transaction
+    fn clear_project_favorite(conn: &Connection, id: &str) -> AppResult<()> {
+        let changed = conn.execute(
+            "UPDATE projects SET is_favorite = 0, favorite_order = NULL WHERE id = ?1 AND is_favorite",
+            params![id],
+        )?;
+        if changed > 0 {
+            Self::renumber_favorite_projects(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Number the favorite projects 1..n in their current order and clear the
+    /// order of the others; callers own the transaction
+    fn renumber_favorite_projects(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "UPDATE projects SET favorite_order = NULL WHERE NOT is_favorite AND favorite_order IS NOT NULL",
+            [],
+        )?;
+        let ids = Self::favorite_project_ids(conn)?;
+        Self::write_favorite_order(conn, &ids)
+    }
+
+    fn favorite_project_ids(conn: &Connection) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM projects WHERE is_favorite
+             ORDER BY favorite_order IS NULL, favorite_order ASC, last_modified_at DESC, id ASC",
+        )?;
+        let ids = stmt.query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ids)
+    }
+
+    fn write_favorite_order(conn: &Connection, ids: &[String]) -> AppResult<()> {
+        let mut stmt = conn.prepare("UPDATE projects SET favorite_order = ?1 WHERE id = ?2 AND favorite_order IS NOT ?1")?;
+        for (index, id) in ids.iter().enumerate() {
+            stmt.execute(params![index as i64 + 1, id])?;
+        }
         Ok(())
     }
 
@@ -120,15 +563,15 @@ impl DbService {
     // Task Operations
     // ==========================================
 
-    /// Insert a task
+    /// Insert a task; callers run it inside a transaction (see insert_task_with_key)
     pub fn insert_task(conn: &Connection, task: &Task) -> AppResult<()> {
         let tags_json = task.tags.as_ref()
             .map(|t| serde_json::to_string(t).unwrap_or_default());
         
         conn.execute(
             r#"INSERT INTO tasks (id, project_id, parent_id, title, description, status, priority, 
-                due_date, completed_at, created_at, updated_at, "order", tags)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+                due_date, completed_at, created_at, updated_at, "order", tags, task_key, recurrence, recurrence_parent_id, remind_at, reminded_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)"#,
             params![
                 task.id,
                 task.project_id,
@@ -143,18 +586,25 @@ impl DbService {
                 task.updated_at,
                 task.order,
                 tags_json,
+                task.task_key,
+                task.recurrence,
+                task.recurrence_parent_id,
+                task.remind_at,
+                task.reminded_at,
             ],
         )?;
+        if let Some(tags) = &task.tags {
+            Self::set_entity_tags(conn, EntityType::Task, &task.id, tags)?;
+        }
         Ok(())
     }
 
     /// Get tasks by project ID
     pub fn get_tasks_by_project(conn: &Connection, project_id: &str) -> AppResult<Vec<Task>> {
-        let mut stmt = conn.prepare(
-            r#"SELECT id, project_id, parent_id, title, description, status, priority,
-                due_date, completed_at, created_at, updated_at, "order", tags
-               FROM tasks WHERE project_id = ?1 ORDER BY "order" ASC"#
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM tasks WHERE project_id = ?1 ORDER BY "order" ASC"#,
+            TASK_COLUMNS
+        ))?;
         
         let tasks = stmt.query_map(params![project_id], |row| {
             Ok(Self::row_to_task(row))
@@ -165,166 +615,6064 @@ impl DbService {
         Ok(tasks)
     }
 
+    /// Get one page of a project's tasks, by default in board order
+    pub fn get_tasks_page(conn: &Connection, project_id: &str, options: &ListOptions) -> AppResult<Paginated<Task>> {
+        let total_count = conn.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE project_id = ?1 AND NOT is_archived",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE project_id = ?1 AND NOT is_archived ORDER BY {} LIMIT ?2 OFFSET ?3",
+            TASK_COLUMNS,
+            options.order_clause(r#""order""#, SortOrder::Asc)
+        ))?;
+
+        let items = stmt.query_map(params![project_id, options.sql_limit(), options.sql_offset()], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(Paginated { items, total_count })
+    }
+
     /// Get task by ID
     pub fn get_task_by_id(conn: &Connection, id: &str) -> AppResult<Option<Task>> {
-        let mut stmt = conn.prepare(
-            r#"SELECT id, project_id, parent_id, title, description, status, priority,
-                due_date, completed_at, created_at, updated_at, "order", tags
-               FROM tasks WHERE id = ?1"#
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, metadata FROM tasks WHERE id = ?1",
+            TASK_COLUMNS
+        ))?;
         
         let mut rows = stmt.query(params![id])?;
         
         if let Some(row) = rows.next()? {
-            Ok(Some(Self::row_to_task(row)))
+            let mut task = Self::row_to_task(row);
+            task.metadata = Self::row_metadata(row);
+            Ok(Some(task))
         } else {
             Ok(None)
         }
     }
 
-    /// Delete task
-    pub fn delete_task(conn: &Connection, id: &str) -> AppResult<()> {
-        conn.execute("DELETE FROM tasks WHERE id = ?1", params![id])?;
-        Ok(())
+    /// Allocate the task's key and insert it in one transaction
+    pub fn insert_task_with_key(conn: &Connection, task: &mut Task) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            task.task_key = Some(Self::allocate_task_key(tx, &task.project_id)?);
+            Self::insert_task(tx, task)?;
+            Self::record_activity(tx, EntityType::Task, &task.id, ActivityAction::Created)
+        })
     }
 
-    // ==========================================
-    // Note Operations  
-    // ==========================================
-
-    /// Insert a note
-    pub fn insert_note(conn: &Connection, note: &Note) -> AppResult<()> {
-        let tags_json = note.tags.as_ref()
-            .map(|t| serde_json::to_string(t).unwrap_or_default());
-        
-        conn.execute(
-            "INSERT INTO notes (id, project_id, title, content, created_at, updated_at, tags, is_pinned)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                note.id,
-                note.project_id,
-                note.title,
-                note.content,
-                note.created_at,
-                note.updated_at,
-                tags_json,
-                note.is_pinned,
-            ],
-        )?;
+    /// Insert tasks in one transaction, each with a fresh key and placed after the
+    /// siblings already present. Parents must come before their subtasks.
+    pub fn insert_imported_tasks(conn: &Connection, tasks: &mut [Task]) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        for task in tasks.iter_mut() {
+            task.order = Self::sibling_task_ids(&tx, &task.project_id, task.parent_id.as_deref())?.len() as i32;
+            task.task_key = Some(Self::allocate_task_key(&tx, &task.project_id)?);
+            Self::insert_task(&tx, task)?;
+            Self::record_activity(&tx, EntityType::Task, &task.id, ActivityAction::Created)?;
+        }
+        tx.commit()?;
         Ok(())
     }
 
-    /// Get notes by project ID
-    pub fn get_notes_by_project(conn: &Connection, project_id: &str) -> AppResult<Vec<Note>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned
-             FROM notes WHERE project_id = ?1 ORDER BY updated_at DESC"
-        )?;
-        
-        let notes = stmt.query_map(params![project_id], |row| {
-            Ok(Self::row_to_note(row))
+    /// Get root tasks (no parent) of a project
+    pub fn get_root_tasks(conn: &Connection, project_id: &str) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM tasks WHERE project_id = ?1 AND parent_id IS NULL AND NOT is_archived ORDER BY "order" ASC"#,
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_task(row))
         })?
         .filter_map(|r| r.ok())
         .collect();
-        
-        Ok(notes)
+
+        Ok(tasks)
     }
 
-    /// Get note by ID
-    pub fn get_note_by_id(conn: &Connection, id: &str) -> AppResult<Option<Note>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned
-             FROM notes WHERE id = ?1"
-        )?;
-        
-        let mut rows = stmt.query(params![id])?;
-        
-        if let Some(row) = rows.next()? {
-            Ok(Some(Self::row_to_note(row)))
-        } else {
-            Ok(None)
-        }
+    /// Get direct subtasks of a task
+    pub fn get_subtasks(conn: &Connection, parent_id: &str) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM tasks WHERE parent_id = ?1 AND NOT is_archived ORDER BY "order" ASC"#,
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map(params![parent_id], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
     }
 
-    /// Delete note
-    pub fn delete_note(conn: &Connection, id: &str) -> AppResult<()> {
-        conn.execute("DELETE FROM notes WHERE id = ?1", params![id])?;
-        Ok(())
+    /// Update task fields that are provided and bump updated_at, a second past the stored
+    /// value when the clock has not moved on, so it works as the version edits are checked against.
+    /// Moving into the project's done status stamps completed_at; moving to any other status clears it.
+    /// Returns false when the task does not exist or its updated_at no longer equals `expected_updated_at`.
+    pub fn update_task(conn: &Connection, id: &str, data: &UpdateTaskDto, cascade: bool) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        Self::with_tx(conn, |tx| {
+            let current = tx
+                .query_row(
+                    "SELECT project_id, status FROM tasks WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )
+                .optional()?;
+            let Some((project_id, old_status)) = current else {
+                return Ok(false);
+            };
+            let done = Self::get_done_status(tx, &project_id)?;
+            let completes = old_status != done && data.status.as_deref() == Some(done.as_str());
+
+            let affected = tx.execute(
+                r#"UPDATE tasks SET
+                    title = COALESCE(?1, title),
+                    description = COALESCE(?2, description),
+                    status = COALESCE(?3, status),
+                    priority = COALESCE(?4, priority),
+                    due_date = COALESCE(?5, due_date),
+                    parent_id = COALESCE(?6, parent_id),
+                    "order" = COALESCE(?7, "order"),
+                    board_order = CASE WHEN ?3 IS NULL OR ?3 = status THEN board_order ELSE NULL END,
+                    completed_at = CASE
+                        WHEN ?3 IS NULL THEN completed_at
+                        WHEN ?3 = ?13 THEN COALESCE(completed_at, ?8)
+                        ELSE NULL
+                    END,
+                    recurrence = CASE WHEN ?11 IS NULL THEN recurrence ELSE NULLIF(?11, '') END,
+                    remind_at = CASE WHEN ?12 IS NULL THEN remind_at ELSE NULLIF(?12, 0) END,
+                    reminded_at = CASE WHEN ?12 IS NULL THEN reminded_at ELSE NULL END,
+                    updated_at = MAX(?8, updated_at + 1)
+                 WHERE id = ?9 AND (?10 IS NULL OR updated_at = ?10)"#,
+                params![
+                    data.title,
+                    data.description,
+                    data.status,
+                    data.priority,
+                    data.due_date,
+                    data.parent_id,
+                    data.order,
+                    now,
+                    id,
+                    data.expected_updated_at,
+                    data.recurrence,
+                    data.remind_at,
+                    done,
+                ],
+            )?;
+            if affected > 0 {
+                if let Some(tags) = &data.tags {
+                    Self::set_entity_tags(tx, EntityType::Task, id, tags)?;
+                }
+                if cascade && data.status.as_deref() == Some(done.as_str()) {
+                    Self::complete_descendants(tx, id, &done, now)?;
+                }
+                let action = if completes {
+                    ActivityAction::Completed
+                } else {
+                    ActivityAction::Updated
+                };
+                Self::record_activity(tx, EntityType::Task, id, action)?;
+            }
+            Ok(affected > 0)
+        })
     }
-    /// Initialize the database
-    pub fn init(conn: &Connection) -> AppResult<()> {
-        // Create projects table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS projects (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                path TEXT NOT NULL UNIQUE,
-                description TEXT,
-                status TEXT NOT NULL DEFAULT 'active',
-                created_at INTEGER NOT NULL,
-                last_modified_at INTEGER NOT NULL,
-                tags TEXT
-            )",
-            [],
-        )?;
 
-        // Create tasks table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                project_id TEXT NOT NULL,
-                parent_id TEXT,
-                title TEXT NOT NULL,
-                description TEXT,
-                status TEXT NOT NULL,
-                priority TEXT NOT NULL,
-                due_date INTEGER,
-                completed_at INTEGER,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL,
-                \"order\" INTEGER NOT NULL DEFAULT 0,
-                tags TEXT,
-                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
-            )",
-            [],
-        )?;
+    /// Write back the editable fields of an earlier version of a task, as an undo does.
+    /// Subtasks a cascade completed keep their status. Returns false when the task does not exist.
+    pub fn restore_task(conn: &Connection, task: &Task) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        Self::with_tx(conn, |tx| {
+            let affected = tx.execute(
+                r#"UPDATE tasks SET
+                    title = ?1,
+                    description = ?2,
+                    status = ?3,
+                    priority = ?4,
+                    due_date = ?5,
+                    parent_id = ?6,
+                    "order" = ?7,
+                    board_order = CASE WHEN ?3 = status THEN board_order ELSE NULL END,
+                    completed_at = ?8,
+                    recurrence = ?11,
+                    reminded_at = CASE WHEN remind_at IS ?12 THEN reminded_at ELSE NULL END,
+                    remind_at = ?12,
+                    updated_at = MAX(?9, updated_at + 1)
+                 WHERE id = ?10"#,
+                params![
+                    task.title,
+                    task.description,
+                    task.status,
+                    task.priority,
+                    task.due_date,
+                    task.parent_id,
+                    task.order,
+                    task.completed_at,
+                    now,
+                    task.id,
+                    task.recurrence,
+                    task.remind_at,
+                ],
+            )?;
+            if affected == 0 {
+                return Ok(false);
+            }
+            Self::set_entity_tags(tx, EntityType::Task, &task.id, task.tags.as_deref().unwrap_or_default())?;
+            Self::record_activity(tx, EntityType::Task, &task.id, ActivityAction::Updated)?;
+            Ok(true)
+        })
+    }
 
-        // Create notes table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS notes (
-                id TEXT PRIMARY KEY,
-                project_id TEXT NOT NULL,
-                title TEXT NOT NULL,
-                content TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL,
-                is_pinned BOOLEAN DEFAULT 0,
-                tags TEXT,
-                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
-            )",
-            [],
+    /// Move every open descendant of a task to the `done` status in one statement
+    fn complete_descendants(conn: &Connection, id: &str, done: &str, now: i64) -> AppResult<usize> {
+        let affected = conn.execute(
+            "WITH RECURSIVE descendants(id) AS (
+                SELECT id FROM tasks WHERE parent_id = ?1
+                UNION
+                SELECT t.id FROM tasks t JOIN descendants d ON t.parent_id = d.id
+             )
+             UPDATE tasks SET status = ?3, completed_at = COALESCE(completed_at, ?2), board_order = NULL, updated_at = MAX(?2, updated_at + 1)
+             WHERE id IN (SELECT id FROM descendants) AND status != ?3",
+            params![id, now, done],
         )?;
+        Ok(affected)
+    }
 
-        Ok(())
+    /// Count a task's descendants and how many of them are done; None when the task does not exist
+    pub fn get_task_progress(conn: &Connection, id: &str) -> AppResult<Option<TaskProgress>> {
+        let progress = conn
+            .query_row(
+                &format!(
+                    "WITH RECURSIVE descendants(id) AS (
+                        SELECT id FROM tasks WHERE parent_id = ?1
+                        UNION
+                        SELECT t.id FROM tasks t JOIN descendants d ON t.parent_id = d.id
+                     )
+                     SELECT
+                        (SELECT COUNT(*) FROM tasks WHERE id IN (SELECT id FROM descendants) AND status = {}),
+                        (SELECT COUNT(*) FROM descendants)
+                     FROM tasks WHERE id = ?1",
+                    done_status_sql("tasks.project_id")
+                ),
+                params![id],
+                |row| {
+                    Ok(TaskProgress {
+                        task_id: id.to_string(),
+                        completed: row.get(0)?,
+                        total: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(progress)
     }
 
-    // ==========================================
-    // Helper Functions
-    // ==========================================
+    /// Open descendants of a task that repeat, as they are before a cascade completes them
+    pub fn get_open_recurring_descendants(conn: &Connection, id: &str) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE descendants(id) AS (
+                SELECT id FROM tasks WHERE parent_id = ?1
+                UNION
+                SELECT t.id FROM tasks t JOIN descendants d ON t.parent_id = d.id
+             )
+             SELECT {} FROM tasks
+             WHERE id IN (SELECT id FROM descendants) AND status != {} AND recurrence IS NOT NULL",
+            TASK_COLUMNS,
+            done_status_sql("tasks.project_id")
+        ))?;
+        let tasks = stmt.query_map(params![id], |row| Ok(Self::row_to_task(row)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(tasks)
+    }
 
-    fn row_to_project(row: &Row) -> Project {
-        let tags_str: Option<String> = row.get("tags").unwrap_or(None);
-        let tags = tags_str.and_then(|t| serde_json::from_str(&t).ok());
+    /// Open, unarchived tasks of a project that repeat, soonest due first
+    pub fn get_recurring_tasks(conn: &Connection, project_id: &str) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks
+             WHERE project_id = ?1 AND recurrence IS NOT NULL AND status != {} AND NOT is_archived
+             ORDER BY due_date IS NULL, due_date ASC, id ASC",
+            TASK_COLUMNS,
+            done_status_sql("?1")
+        ))?;
+        let tasks = stmt.query_map(params![project_id], |row| Ok(Self::row_to_task(row)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(tasks)
+    }
+
+    /// Move a repeating task to its next due date with the rule that goes with it.
+    /// Returns false when the task does not exist.
+    pub fn reschedule_task(conn: &Connection, id: &str, due_date: i64, recurrence: &str) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        Self::with_tx(conn, |tx| {
+            let affected = tx.execute(
+                "UPDATE tasks SET due_date = ?1, recurrence = ?2, updated_at = MAX(?3, updated_at + 1) WHERE id = ?4",
+                params![due_date, recurrence, now, id],
+            )?;
+            if affected > 0 {
+                Self::record_activity(tx, EntityType::Task, id, ActivityAction::Updated)?;
+            }
+            Ok(affected > 0)
+        })
+    }
+
+    /// Open tasks of non-archived projects whose reminder has not been delivered
+    /// yet, soonest first; with `due_by` only those whose reminder time has come
+    pub fn get_pending_reminders(conn: &Connection, due_by: Option<i64>) -> AppResult<Vec<TaskWithProject>> {
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {}, project_name FROM (
+                SELECT t.*, p.name AS project_name FROM tasks t
+                JOIN projects p ON p.id = t.project_id
+                WHERE t.remind_at IS NOT NULL AND t.reminded_at IS NULL
+                  AND (?1 IS NULL OR t.remind_at <= ?1)
+                  AND t.status != {} AND NOT t.is_archived AND p.status != 'archived'
+             )
+             ORDER BY remind_at ASC, id ASC",
+            TASK_COLUMNS,
+            done_status_sql("t.project_id")
+        ))?;
+
+        let tasks = stmt.query_map(params![due_by], |row| {
+            Ok(TaskWithProject {
+                task: Self::row_to_task(row),
+                project_name: row.get("project_name").unwrap_or_default(),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Mark the reminders whose time came by `now` as delivered and return their
+    /// tasks. A reminder is claimed once: a second caller finds nothing left.
+    pub fn claim_due_reminders(conn: &Connection, now: i64) -> AppResult<Vec<TaskWithProject>> {
+        Self::with_tx(conn, |tx| {
+            let due = Self::get_pending_reminders(tx, Some(now))?;
+            let mut stmt = tx.prepare("UPDATE tasks SET reminded_at = ?1 WHERE id = ?2 AND reminded_at IS NULL")?;
+            let mut claimed = Vec::with_capacity(due.len());
+            for mut entry in due {
+                if stmt.execute(params![now, entry.task.id])? > 0 {
+                    entry.task.reminded_at = Some(now);
+                    claimed.push(entry);
+                }
+            }
+            Ok(claimed)
+        })
+    }
+
+    /// Move a task's reminder to `remind_at` and deliver it again then.
+    /// Returns false when the task does not exist.
+    pub fn set_task_reminder(conn: &Connection, id: &str, remind_at: i64) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        let affected = conn.execute(
+            "UPDATE tasks SET remind_at = ?1, reminded_at = NULL, updated_at = MAX(?2, updated_at + 1) WHERE id = ?3",
+            params![remind_at, now, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Delete a task and all its descendants, returning how many rows were removed.
+    /// With `to_trash` the rows are copied to the trash first so they can be restored.
+    /// The remaining siblings are renumbered to close the gap.
+    pub fn delete_task(conn: &Connection, id: &str, to_trash: bool) -> AppResult<usize> {
+        let tx = conn.unchecked_transaction()?;
+        let position = Self::task_position(&tx, id)?;
+        if to_trash {
+            Self::trash_task_subtree(&tx, id)?;
+        }
+        // Recorded first, while the title can still be read
+        Self::record_activity(&tx, EntityType::Task, id, ActivityAction::Deleted)?;
+        let affected = tx.execute(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1
+                UNION
+                SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+             )
+             DELETE FROM tasks WHERE id IN (SELECT id FROM subtree)",
+            params![id],
+        )?;
+        if let Some((project_id, parent_id)) = position {
+            let siblings = Self::sibling_task_ids(&tx, &project_id, parent_id.as_deref())?;
+            Self::write_task_order(&tx, &siblings)?;
+        }
+        tx.commit()?;
+        Ok(affected)
+    }
+
+    /// Put a task at `position` (0-based, clamped to the sibling list) among the
+    /// tasks sharing its parent, numbering all of them contiguously.
+    /// Returns false when the task does not exist.
+    pub fn reorder_task(conn: &Connection, id: &str, position: i32) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        let tx = conn.unchecked_transaction()?;
+
+        let Some((project_id, parent_id)) = Self::task_position(&tx, id)? else {
+            return Ok(false);
+        };
+
+        let mut siblings = Self::sibling_task_ids(&tx, &project_id, parent_id.as_deref())?;
+        siblings.retain(|sibling| sibling != id);
+        let index = usize::try_from(position.max(0)).unwrap_or(0).min(siblings.len());
+        siblings.insert(index, id.to_string());
+        Self::write_task_order(&tx, &siblings)?;
+        tx.execute("UPDATE tasks SET updated_at = MAX(?1, updated_at + 1) WHERE id = ?2", params![now, id])?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Project and parent of a task
+    fn task_position(conn: &Connection, id: &str) -> AppResult<Option<(String, Option<String>)>> {
+        let position = conn
+            .query_row(
+                "SELECT project_id, parent_id FROM tasks WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(position)
+    }
+
+    /// Ids of the tasks under one parent (None for root tasks) in their current order
+    fn sibling_task_ids(conn: &Connection, project_id: &str, parent_id: Option<&str>) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            r#"SELECT id FROM tasks WHERE project_id = ?1 AND parent_id IS ?2
+               ORDER BY "order" ASC, created_at ASC, id ASC"#,
+        )?;
+        let ids = stmt.query_map(params![project_id, parent_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ids)
+    }
+
+    /// Number tasks 0..n in the given order, touching only rows whose order changes
+    fn write_task_order(conn: &Connection, ids: &[String]) -> AppResult<()> {
+        let mut stmt = conn.prepare(r#"UPDATE tasks SET "order" = ?1 WHERE id = ?2 AND "order" IS NOT ?1"#)?;
+        for (index, id) in ids.iter().enumerate() {
+            stmt.execute(params![index as i64, id])?;
+        }
+        Ok(())
+    }
+
+    /// Give a task a new parent (None makes it a root task) and append it to its new
+    /// siblings. Rejects parents from another project and parents inside the task's
+    /// own subtree. Returns false when the task does not exist.
+    pub fn move_task(conn: &Connection, id: &str, new_parent_id: Option<&str>) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        let tx = conn.unchecked_transaction()?;
+
+        let Some((project_id, old_parent_id)) = Self::task_position(&tx, id)? else {
+            return Ok(false);
+        };
+
+        if let Some(parent_id) = new_parent_id {
+            let parent_project: String = tx
+                .query_row("SELECT project_id FROM tasks WHERE id = ?1", params![parent_id], |row| row.get(0))
+                .optional()?
+                .ok_or_else(|| AppError::NotFound("Task", parent_id.to_string()))?;
+            if parent_project != project_id {
+                return Err(AppError::Conflict(format!(
+                    "Parent task '{}' belongs to a different project",
+                    parent_id
+                )));
+            }
+            if Self::is_in_subtree(&tx, id, parent_id)? {
+                return Err(AppError::Conflict(
+                    "A task cannot be moved under itself or one of its subtasks".into(),
+                ));
+            }
+        }
+
+        tx.execute(
+            r#"UPDATE tasks SET
+                parent_id = ?1,
+                "order" = (SELECT COALESCE(MAX(s."order"), -1) + 1 FROM tasks s
+                           WHERE s.project_id = ?2 AND s.parent_id IS ?1 AND s.id != ?3),
+                updated_at = MAX(?4, updated_at + 1)
+             WHERE id = ?3"#,
+            params![new_parent_id, project_id, id, now],
+        )?;
+        if old_parent_id.as_deref() != new_parent_id {
+            let siblings = Self::sibling_task_ids(&tx, &project_id, old_parent_id.as_deref())?;
+            Self::write_task_order(&tx, &siblings)?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Whether `candidate_id` is the task `root_id` or one of its descendants
+    pub fn is_in_subtree(conn: &Connection, root_id: &str, candidate_id: &str) -> AppResult<bool> {
+        let found = conn.query_row(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1
+                UNION
+                SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+             )
+             SELECT EXISTS(SELECT 1 FROM subtree WHERE id = ?2)",
+            params![root_id, candidate_id],
+            |row| row.get(0),
+        )?;
+        Ok(found)
+    }
+
+    /// Reserve the next sequential task key (e.g. "NLP-142") of a project
+    pub fn allocate_task_key(conn: &Connection, project_id: &str) -> AppResult<String> {
+        let allocated: Option<(String, i64)> = conn
+            .query_row(
+                "UPDATE projects SET next_task_number = next_task_number + 1 WHERE id = ?1
+                 RETURNING COALESCE(key_prefix, 'TASK'), next_task_number - 1",
+                params![project_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        let (prefix, number) = allocated.ok_or_else(|| AppError::NotFound("Project", project_id.to_string()))?;
+        Ok(format!("{}-{}", prefix, number))
+    }
+
+    /// Get task by its human-readable key within a project
+    pub fn get_task_by_key(conn: &Connection, project_id: &str, key: &str) -> AppResult<Option<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE project_id = ?1 AND task_key = upper(?2)",
+            TASK_COLUMNS
+        ))?;
+
+        let mut rows = stmt.query(params![project_id, key.trim()])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Self::row_to_task(row)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get one page of a project's tasks matching every set field of `filter`
+    pub fn filter_tasks(
+        conn: &Connection,
+        project_id: &str,
+        filter: &TaskFilterDto,
+        options: &ListOptions,
+    ) -> AppResult<Paginated<Task>> {
+        const TASK_TAGS: &str =
+            "json_each(CASE WHEN json_valid(tasks.tags) THEN tasks.tags ELSE '[]' END)";
+
+        fn placeholders(count: usize) -> String {
+            vec!["?"; count].join(", ")
+        }
+
+        let mut clauses = vec!["project_id = ?".to_string()];
+        let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(project_id.to_string())];
+
+        if let Some(statuses) = filter.statuses.as_ref().filter(|s| !s.is_empty()) {
+            clauses.push(format!("status IN ({})", placeholders(statuses.len())));
+            values.extend(statuses.iter().map(|s| Box::new(s.clone()) as Box<dyn ToSql>));
+        }
+
+        if let Some(priorities) = filter.priorities.as_ref().filter(|p| !p.is_empty()) {
+            clauses.push(format!("priority IN ({})", placeholders(priorities.len())));
+            values.extend(priorities.iter().map(|p| Box::new(*p) as Box<dyn ToSql>));
+        }
+
+        let tags: Vec<String> = filter.tags.iter()
+            .flatten()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        // A parent tag also matches its descendants
+        if filter.match_all_tags {
+            for tag in &tags {
+                clauses.push(format!("EXISTS (SELECT 1 FROM {} WHERE {})", TASK_TAGS, tag_path::sql_match("value")));
+                values.extend(tag_path::sql_match_values(tag).map(|v| Box::new(v) as Box<dyn ToSql>));
+            }
+        } else if !tags.is_empty() {
+            clauses.push(format!(
+                "EXISTS (SELECT 1 FROM {} WHERE {})",
+                TASK_TAGS,
+                vec![tag_path::sql_match("value"); tags.len()].join(" OR ")
+            ));
+            for tag in &tags {
+                values.extend(tag_path::sql_match_values(tag).map(|v| Box::new(v) as Box<dyn ToSql>));
+            }
+        }
+
+        if let Some(before) = filter.due_before {
+            clauses.push("due_date < ?".to_string());
+            values.push(Box::new(before));
+        }
+        if let Some(after) = filter.due_after {
+            clauses.push("due_date >= ?".to_string());
+            values.push(Box::new(after));
+        }
+        match filter.has_due_date {
+            Some(true) => clauses.push("due_date IS NOT NULL".to_string()),
+            Some(false) => clauses.push("due_date IS NULL".to_string()),
+            None => {}
+        }
+
+        match &filter.parent {
+            Some(TaskParentFilter::Root) => clauses.push("parent_id IS NULL".to_string()),
+            Some(TaskParentFilter::ParentId(parent_id)) => {
+                clauses.push("parent_id = ?".to_string());
+                values.push(Box::new(parent_id.clone()));
+            }
+            None => {}
+        }
+
+        for term in filter.query.iter().flat_map(|q| q.split_whitespace()) {
+            clauses.push(format!(
+                "(title LIKE ? ESCAPE '{e}' OR task_key LIKE ? ESCAPE '{e}' OR description LIKE ? ESCAPE '{e}')",
+                e = text::LIKE_ESCAPE
+            ));
+            let pattern = text::like_contains(term);
+            values.extend((0..3).map(|_| Box::new(pattern.clone()) as Box<dyn ToSql>));
+        }
+
+        if filter.include_completed == Some(false) {
+            clauses.push(format!("status != {}", done_status_sql("tasks.project_id")));
+        }
+        if filter.include_archived != Some(true) {
+            clauses.push("NOT is_archived".to_string());
+        }
+
+        let where_clause = clauses.join(" AND ");
+        let total_count = conn.query_row(
+            &format!("SELECT COUNT(*) FROM tasks WHERE {}", where_clause),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        values.push(Box::new(options.sql_limit()));
+        values.push(Box::new(options.sql_offset()));
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE {} ORDER BY {} LIMIT ? OFFSET ?",
+            TASK_COLUMNS,
+            where_clause,
+            options.order_clause(r#""order""#, SortOrder::Asc)
+        ))?;
+        let items = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(Paginated { items, total_count })
+    }
+
+    /// Search tasks of a project. Every whitespace-separated term must match the
+    /// title, key, description or a tag (case-insensitive); results are ranked by
+    /// where the terms matched: title or key, then tags, then description. Only
+    /// tasks updated from `updated_after` up to `updated_before` are searched.
+    pub fn search_tasks(
+        conn: &Connection,
+        project_id: &str,
+        query: &str,
+        status: Option<&str>,
+        updated_after: Option<i64>,
+        updated_before: Option<i64>,
+    ) -> AppResult<Vec<Task>> {
+        const TASK_TAGS: &str =
+            "json_each(CASE WHEN json_valid(tasks.tags) THEN tasks.tags ELSE '[]' END)";
+
+        let terms: Vec<String> = query.split_whitespace().map(text::like_contains).collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(project_id.to_string())];
+        let mut clauses = vec!["project_id = ?1".to_string(), "NOT is_archived".to_string()];
+        let mut scores = Vec::new();
+
+        if let Some(status) = status {
+            values.push(Box::new(status.to_string()));
+            clauses.push(format!("status = ?{}", values.len()));
+        }
+        Self::push_updated_between(&mut clauses, &mut values, updated_after, updated_before);
+
+        for term in terms {
+            values.push(Box::new(term));
+            let p = format!("?{} ESCAPE '{}'", values.len(), text::LIKE_ESCAPE);
+            clauses.push(format!(
+                "(title LIKE {p} OR task_key LIKE {p} OR description LIKE {p}
+                  OR EXISTS (SELECT 1 FROM {tags} WHERE value LIKE {p}))",
+                p = p,
+                tags = TASK_TAGS
+            ));
+            scores.push(format!(
+                "CASE WHEN title LIKE {p} OR task_key LIKE {p} THEN 3
+                      WHEN EXISTS (SELECT 1 FROM {tags} WHERE value LIKE {p}) THEN 2
+                      ELSE 1 END",
+                p = p,
+                tags = TASK_TAGS
+            ));
+        }
+
+        let query = format!(
+            "SELECT {}, ({}) AS score FROM tasks WHERE {} ORDER BY score DESC, updated_at DESC",
+            TASK_COLUMNS,
+            scores.join(" + "),
+            clauses.join(" AND ")
+        );
+
+        let mut stmt = conn.prepare(&query)?;
+        let tasks = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// IDs and titles of a project's unarchived tasks whose title contains any of
+    /// `trigrams`, the candidates for fuzzy title matching
+    pub fn get_task_title_candidates(
+        conn: &Connection,
+        project_id: &str,
+        status: Option<&str>,
+        trigrams: &[String],
+    ) -> AppResult<Vec<(String, String)>> {
+        if trigrams.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(project_id.to_string()), Box::new(status.map(str::to_string))];
+        let mut likes = Vec::new();
+        for trigram in trigrams {
+            values.push(Box::new(text::like_contains(trigram)));
+            likes.push(format!("title LIKE ?{} ESCAPE '{}'", values.len(), text::LIKE_ESCAPE));
+        }
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, title FROM tasks
+             WHERE project_id = ?1 AND NOT is_archived AND (?2 IS NULL OR status = ?2) AND ({})",
+            likes.join(" OR ")
+        ))?;
+        let candidates = stmt.query_map(params_from_iter(values.iter()), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(candidates)
+    }
+
+    /// Get the tasks with the given IDs, in no particular order
+    pub fn get_tasks_by_ids(conn: &Connection, ids: &[String]) -> AppResult<Vec<Task>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE id IN ({})",
+            TASK_COLUMNS,
+            vec!["?"; ids.len()].join(", ")
+        ))?;
+        let tasks = stmt.query_map(params_from_iter(ids.iter()), |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Open tasks (not in their project's done status) of non-archived projects due in
+    /// `[from, to)`, soonest first. A missing `from` includes everything due before `to`.
+    pub fn get_due_tasks(conn: &Connection, from: Option<i64>, to: i64) -> AppResult<Vec<TaskWithProject>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, project_name FROM (
+                SELECT t.*, p.name AS project_name FROM tasks t
+                JOIN projects p ON p.id = t.project_id
+                WHERE p.status != 'archived' AND t.status != {}
+                  AND t.due_date IS NOT NULL
+                  AND (?1 IS NULL OR t.due_date >= ?1) AND t.due_date < ?2
+             )
+             ORDER BY due_date ASC, project_name COLLATE NOCASE ASC, id ASC",
+            TASK_COLUMNS,
+            done_status_sql("t.project_id")
+        ))?;
+
+        let tasks = stmt.query_map(params![from, to], |row| {
+            Ok(TaskWithProject {
+                task: Self::row_to_task(row),
+                project_name: row.get("project_name").unwrap_or_default(),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Tasks with a due date, done or not, soonest first: those of one project,
+    /// or with None those of every non-archived project
+    pub fn get_dated_tasks(conn: &Connection, project_id: Option<&str>) -> AppResult<Vec<TaskWithProject>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, project_name FROM (
+                SELECT t.*, p.name AS project_name FROM tasks t
+                JOIN projects p ON p.id = t.project_id
+                WHERE t.due_date IS NOT NULL
+                  AND (t.project_id = ?1 OR (?1 IS NULL AND p.status != 'archived'))
+             )
+             ORDER BY due_date ASC, id ASC",
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id], |row| {
+            Ok(TaskWithProject {
+                task: Self::row_to_task(row),
+                project_name: row.get("project_name").unwrap_or_default(),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Get tasks of a project sorted by title
+    pub fn get_tasks_by_title(conn: &Connection, project_id: &str, collation: TitleCollation) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE project_id = ?1 AND NOT is_archived ORDER BY title COLLATE {} ASC, id ASC",
+            TASK_COLUMNS,
+            collation.sql_name()
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Get tasks of a project in stack-rank order; unranked tasks come last
+    pub fn get_tasks_by_rank(conn: &Connection, project_id: &str) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM tasks WHERE project_id = ?1 AND NOT is_archived
+               ORDER BY rank IS NULL, rank ASC, "order" ASC"#,
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Store an explicit stack rank (1 = top) for the listed tasks of a project.
+    /// Tasks of the project missing from the list keep their rank and are reported as unranked.
+    pub fn rank_tasks(conn: &Connection, project_id: &str, ranked_ids: &[String]) -> AppResult<RankTasksResult> {
+        let tx = conn.unchecked_transaction()?;
+        let now = chrono::Utc::now().timestamp();
+        let mut result = RankTasksResult::default();
+
+        let project_tasks: HashSet<String> = {
+            let mut stmt = tx.prepare("SELECT id FROM tasks WHERE project_id = ?1")?;
+            let ids = stmt.query_map(params![project_id], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+
+        let mut seen: HashSet<&str> = HashSet::new();
+        for id in ranked_ids {
+            if !seen.insert(id.as_str()) {
+                result.skipped.push(SkippedItem {
+                    id: id.clone(),
+                    reason: "Task is listed more than once".into(),
+                });
+                continue;
+            }
+            if !project_tasks.contains(id) {
+                result.skipped.push(SkippedItem {
+                    id: id.clone(),
+                    reason: "Task not found in project".into(),
+                });
+                continue;
+            }
+
+            result.ranked += 1;
+            tx.execute(
+                "UPDATE tasks SET rank = ?1, updated_at = MAX(?2, updated_at + 1) WHERE id = ?3",
+                params![result.ranked as i64, now, id],
+            )?;
+        }
+
+        result.unranked = project_tasks
+            .iter()
+            .filter(|id| !seen.contains(id.as_str()))
+            .cloned()
+            .collect();
+        result.unranked.sort();
+
+        tx.commit()?;
+        Ok(result)
+    }
+
+    /// Get tasks of a project in Kanban board order within each status; tasks never
+    /// placed on the board come after the placed ones, in their tree order
+    pub fn get_board_tasks(conn: &Connection, project_id: &str) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM tasks WHERE project_id = ?1 AND NOT is_archived
+               ORDER BY status, board_order IS NULL, board_order ASC, "order" ASC, created_at ASC, id ASC"#,
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Move a task to `position` (0 = top) of the board column for `status`, setting
+    /// or clearing completed_at as update_task does. The source and destination
+    /// columns are renumbered; returns false when the task does not exist.
+    pub fn move_task_on_board(conn: &Connection, id: &str, status: &str, position: usize) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        Self::with_tx(conn, |tx| {
+            let current = tx
+                .query_row(
+                    "SELECT project_id, status FROM tasks WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )
+                .optional()?;
+            let Some((project_id, old_status)) = current else {
+                return Ok(false);
+            };
+
+            let mut column: Vec<String> = Self::board_column_ids(tx, &project_id, status)?
+                .into_iter()
+                .filter(|task_id| task_id != id)
+                .collect();
+            column.insert(position.min(column.len()), id.to_string());
+
+            let done = Self::get_done_status(tx, &project_id)?;
+            tx.execute(
+                "UPDATE tasks SET
+                    status = ?1,
+                    completed_at = CASE
+                        WHEN ?1 = ?4 THEN COALESCE(completed_at, ?2)
+                        ELSE NULL
+                    END,
+                    updated_at = MAX(?2, updated_at + 1)
+                 WHERE id = ?3",
+                params![status, now, id, done],
+            )?;
+            Self::write_board_order(tx, &column)?;
+            if old_status != status {
+                let source = Self::board_column_ids(tx, &project_id, &old_status)?;
+                Self::write_board_order(tx, &source)?;
+            }
+
+            let action = if old_status != done && status == done {
+                ActivityAction::Completed
+            } else {
+                ActivityAction::Updated
+            };
+            Self::record_activity(tx, EntityType::Task, id, action)?;
+            Ok(true)
+        })
+    }
+
+    /// IDs of a board column in its current order
+    fn board_column_ids(conn: &Connection, project_id: &str, status: &str) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            r#"SELECT id FROM tasks WHERE project_id = ?1 AND status = ?2 AND NOT is_archived
+               ORDER BY board_order IS NULL, board_order ASC, "order" ASC, created_at ASC, id ASC"#,
+        )?;
+        let ids = stmt.query_map(params![project_id, status], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ids)
+    }
+
+    fn write_board_order(conn: &Connection, ids: &[String]) -> AppResult<()> {
+        let mut stmt = conn.prepare("UPDATE tasks SET board_order = ?1 WHERE id = ?2 AND board_order IS NOT ?1")?;
+        for (index, id) in ids.iter().enumerate() {
+            stmt.execute(params![index as i64 + 1, id])?;
+        }
+        Ok(())
+    }
+
+    /// Archive a project's done tasks completed before `completed_before`, each only
+    /// when every task below it qualifies too, so a subtree is archived whole or not
+    /// at all. Returns how many tasks were archived.
+    pub fn archive_completed_tasks(conn: &Connection, project_id: &str, completed_before: i64) -> AppResult<usize> {
+        let now = chrono::Utc::now().timestamp();
+        let done = Self::get_done_status(conn, project_id)?;
+        let archived = conn.execute(
+            "WITH RECURSIVE subtree(root, id) AS (
+                SELECT id, id FROM tasks WHERE project_id = ?1
+                UNION
+                SELECT s.root, t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+             )
+             UPDATE tasks SET is_archived = 1, archived_at = ?3
+             WHERE project_id = ?1 AND NOT is_archived AND id IN (
+                SELECT s.root FROM subtree s JOIN tasks t ON t.id = s.id
+                GROUP BY s.root
+                HAVING SUM(t.status != ?4 OR t.completed_at IS NULL OR t.completed_at >= ?2) = 0
+             )",
+            params![project_id, completed_before, now, done],
+        )?;
+        Ok(archived)
+    }
+
+    /// Get one page of a project's archived tasks, most recently completed first
+    pub fn get_archived_tasks_page(conn: &Connection, project_id: &str, options: &ListOptions) -> AppResult<Paginated<Task>> {
+        let total_count = conn.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE project_id = ?1 AND is_archived",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE project_id = ?1 AND is_archived ORDER BY {} LIMIT ?2 OFFSET ?3",
+            TASK_COLUMNS,
+            options.order_clause("completed_at", SortOrder::Desc)
+        ))?;
+
+        let items = stmt.query_map(params![project_id, options.sql_limit(), options.sql_offset()], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(Paginated { items, total_count })
+    }
+
+    /// Bring an archived task back with the archived tasks below it, and its
+    /// archived ancestors so it is reachable in the tree again. Returns how many
+    /// tasks were unarchived.
+    pub fn unarchive_task(conn: &Connection, id: &str) -> AppResult<usize> {
+        let unarchived = conn.execute(
+            "WITH RECURSIVE
+                below(id) AS (
+                    SELECT id FROM tasks WHERE id = ?1
+                    UNION
+                    SELECT t.id FROM tasks t JOIN below b ON t.parent_id = b.id
+                ),
+                above(id, parent_id) AS (
+                    SELECT id, parent_id FROM tasks WHERE id = ?1
+                    UNION
+                    SELECT t.id, t.parent_id FROM tasks t JOIN above a ON t.id = a.parent_id
+                )
+             UPDATE tasks SET is_archived = 0, archived_at = NULL
+             WHERE is_archived AND (id IN (SELECT id FROM below) OR id IN (SELECT id FROM above))",
+            params![id],
+        )?;
+        Ok(unarchived)
+    }
+
+    /// Change a project's key prefix and rewrite its existing task keys to match
+    pub fn set_project_key_prefix(conn: &Connection, project_id: &str, prefix: &str) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            tx.execute(
+                "UPDATE projects SET key_prefix = ?1 WHERE id = ?2",
+                params![prefix, project_id],
+            )?;
+            tx.execute(
+                "UPDATE tasks SET task_key = ?1 || substr(task_key, instr(task_key, '-'))
+                 WHERE project_id = ?2 AND task_key IS NOT NULL",
+                params![prefix, project_id],
+            )?;
+            Ok(())
+        })
+    }
+
+    // ==========================================
+    // Note Operations  
+    // ==========================================
+
+    /// Insert a note
+    pub fn insert_note(conn: &Connection, note: &Note) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        Self::insert_note_row(&tx, note)?;
+        Self::record_activity(&tx, EntityType::Note, &note.id, ActivityAction::Created)?;
+        if let Some(project_id) = note.project_id.as_deref() {
+            Self::refresh_note_links(&tx, project_id)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Insert a note together with its attachment rows in one transaction
+    pub fn insert_note_with_attachments(conn: &Connection, note: &Note, attachments: &[NoteAttachment]) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        Self::insert_note_row(&tx, note)?;
+        for attachment in attachments {
+            Self::insert_note_attachment(&tx, attachment)?;
+        }
+        Self::record_activity(&tx, EntityType::Note, &note.id, ActivityAction::Created)?;
+        if let Some(project_id) = note.project_id.as_deref() {
+            Self::refresh_note_links(&tx, project_id)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Insert several notes in one transaction
+    pub fn insert_notes(conn: &Connection, notes: &[Note]) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        for note in notes {
+            Self::insert_note_row(&tx, note)?;
+            Self::record_activity(&tx, EntityType::Note, &note.id, ActivityAction::Created)?;
+        }
+        let project_ids: HashSet<&str> = notes.iter().filter_map(|note| note.project_id.as_deref()).collect();
+        for project_id in project_ids {
+            Self::refresh_note_links(&tx, project_id)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Insert a note with its tag and wikilink rows; callers own the transaction
+    /// and refresh the project's link targets afterwards
+    fn insert_note_row(conn: &Connection, note: &Note) -> AppResult<()> {
+        let tags_json = note.tags.as_ref()
+            .map(|t| serde_json::to_string(t).unwrap_or_default());
+
+        // Cached statements keep bulk inserts from re-preparing every row
+        conn.prepare_cached(
+            "INSERT INTO notes (id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked, word_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?
+        .execute(params![
+            note.id,
+            note.project_id,
+            note.title,
+            note.content,
+            note.created_at,
+            note.updated_at,
+            tags_json,
+            note.is_pinned,
+            note.is_locked,
+            word_count::word_count(&note.content),
+        ])?;
+        if let Some(tags) = &note.tags {
+            Self::set_entity_tags(conn, EntityType::Note, &note.id, tags)?;
+        }
+        Self::write_note_links(conn, &note.id, &note.content)?;
+        Ok(())
+    }
+
+    /// Get notes by project ID
+    pub fn get_notes_by_project(conn: &Connection, project_id: &str) -> AppResult<Vec<Note>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes WHERE project_id = ?1 ORDER BY updated_at DESC"
+        )?;
+        
+        let notes = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+        
+        Ok(notes)
+    }
+
+    /// Get one page of a project's notes, by default most recently updated first
+    pub fn get_notes_page(conn: &Connection, project_id: &str, options: &ListOptions) -> AppResult<Paginated<Note>> {
+        let total_count = conn.query_row(
+            "SELECT COUNT(*) FROM notes WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes WHERE project_id = ?1 ORDER BY {} LIMIT ?2 OFFSET ?3",
+            options.order_clause("updated_at", SortOrder::Desc)
+        ))?;
+
+        let items = stmt.query_map(params![project_id, options.sql_limit(), options.sql_offset()], |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(Paginated { items, total_count })
+    }
+
+    /// Get notes of a project sorted by title
+    pub fn get_notes_by_title(conn: &Connection, project_id: &str, collation: TitleCollation) -> AppResult<Vec<Note>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes WHERE project_id = ?1 ORDER BY title COLLATE {} ASC, id ASC",
+            collation.sql_name()
+        ))?;
+
+        let notes = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Get inbox notes, which belong to no project yet, most recently updated first
+    pub fn get_inbox_notes(conn: &Connection) -> AppResult<Vec<Note>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes WHERE project_id IS NULL ORDER BY updated_at DESC, id ASC"
+        )?;
+
+        let notes = stmt.query_map([], |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Search notes of a project, and optionally the inbox. Every whitespace-separated
+    /// term must match the title or content (case-insensitive); title matches rank first.
+    /// Only notes updated from `updated_after` up to `updated_before` are searched.
+    pub fn search_notes(
+        conn: &Connection,
+        project_id: &str,
+        query: &str,
+        include_inbox: bool,
+        updated_after: Option<i64>,
+        updated_before: Option<i64>,
+    ) -> AppResult<Vec<Note>> {
+        let terms: Vec<String> = query.split_whitespace().map(text::like_contains).collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(project_id.to_string()), Box::new(include_inbox)];
+        let mut clauses = vec!["(project_id = ?1 OR (?2 AND project_id IS NULL))".to_string()];
+        let mut scores = Vec::new();
+        Self::push_updated_between(&mut clauses, &mut values, updated_after, updated_before);
+
+        for term in terms {
+            values.push(Box::new(term));
+            let p = format!("?{} ESCAPE '{}'", values.len(), text::LIKE_ESCAPE);
+            clauses.push(format!("(title LIKE {p} OR content LIKE {p})", p = p));
+            scores.push(format!("CASE WHEN title LIKE {p} THEN 2 ELSE 1 END", p = p));
+        }
+
+        let query = format!(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked,
+                    ({}) AS score
+             FROM notes WHERE {} ORDER BY score DESC, updated_at DESC",
+            scores.join(" + "),
+            clauses.join(" AND ")
+        );
+
+        let mut stmt = conn.prepare(&query)?;
+        let notes = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// IDs and titles of notes whose title contains any of `trigrams`, the
+    /// candidates for fuzzy title matching
+    pub fn get_note_title_candidates(
+        conn: &Connection,
+        project_id: &str,
+        include_inbox: bool,
+        trigrams: &[String],
+    ) -> AppResult<Vec<(String, String)>> {
+        if trigrams.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(project_id.to_string()), Box::new(include_inbox)];
+        let mut likes = Vec::new();
+        for trigram in trigrams {
+            values.push(Box::new(text::like_contains(trigram)));
+            likes.push(format!("title LIKE ?{} ESCAPE '{}'", values.len(), text::LIKE_ESCAPE));
+        }
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, title FROM notes
+             WHERE (project_id = ?1 OR (?2 AND project_id IS NULL)) AND ({})",
+            likes.join(" OR ")
+        ))?;
+        let candidates = stmt.query_map(params_from_iter(values.iter()), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(candidates)
+    }
+
+    /// Get the notes with the given IDs, in no particular order
+    pub fn get_notes_by_ids(conn: &Connection, ids: &[String]) -> AppResult<Vec<Note>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes WHERE id IN ({})",
+            vec!["?"; ids.len()].join(", ")
+        ))?;
+        let notes = stmt.query_map(params_from_iter(ids.iter()), |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Get note by ID
+    pub fn get_note_by_id(conn: &Connection, id: &str) -> AppResult<Option<Note>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked, metadata
+             FROM notes WHERE id = ?1"
+        )?;
+        
+        let mut rows = stmt.query(params![id])?;
+        
+        if let Some(row) = rows.next()? {
+            let mut note = Self::row_to_note(row);
+            note.metadata = Self::row_metadata(row);
+            Ok(Some(note))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Update note fields that are provided and bump updated_at, by at least a second so
+    /// it works as a version. Returns false when the note does not exist or its
+    /// updated_at no longer equals `expected_updated_at`.
+    pub fn update_note(conn: &Connection, id: &str, data: &UpdateNoteDto) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        Self::with_tx(conn, |tx| {
+            let project_id: Option<Option<String>> = tx
+                .query_row(
+                    "UPDATE notes SET
+                        title = COALESCE(?1, title),
+                        content = COALESCE(?2, content),
+                        is_pinned = COALESCE(?3, is_pinned),
+                        updated_at = MAX(?4, updated_at + 1),
+                        word_count = COALESCE(?6, word_count)
+                     WHERE id = ?5 AND (?7 IS NULL OR updated_at = ?7)
+                     RETURNING project_id",
+                    params![
+                        data.title,
+                        data.content,
+                        data.is_pinned,
+                        now,
+                        id,
+                        data.content.as_deref().map(word_count::word_count),
+                        data.expected_updated_at,
+                    ],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(project_id) = project_id else {
+                return Ok(false);
+            };
+
+            if let Some(tags) = &data.tags {
+                Self::set_entity_tags(tx, EntityType::Note, id, tags)?;
+            }
+            if let Some(content) = &data.content {
+                Self::write_note_links(tx, id, content)?;
+            }
+            if let Some(project_id) = project_id.as_deref() {
+                if data.title.is_some() || data.content.is_some() {
+                    Self::refresh_note_links(tx, project_id)?;
+                }
+                if data.is_pinned.is_some() {
+                    Self::renumber_pinned_notes(tx, project_id)?;
+                }
+            }
+            Self::record_activity(tx, EntityType::Note, id, ActivityAction::Updated)?;
+            Ok(true)
+        })
+    }
+
+    /// Write back the editable fields of an earlier version of a note, as an undo does.
+    /// Returns false when the note does not exist.
+    pub fn restore_note(conn: &Connection, note: &Note) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        Self::with_tx(conn, |tx| {
+            let project_id: Option<Option<String>> = tx
+                .query_row(
+                    "UPDATE notes SET
+                        title = ?1,
+                        content = ?2,
+                        is_pinned = ?3,
+                        updated_at = MAX(?4, updated_at + 1),
+                        word_count = ?5
+                     WHERE id = ?6
+                     RETURNING project_id",
+                    params![
+                        note.title,
+                        note.content,
+                        note.is_pinned,
+                        now,
+                        word_count::word_count(&note.content),
+                        note.id,
+                    ],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(project_id) = project_id else {
+                return Ok(false);
+            };
+
+            Self::set_entity_tags(tx, EntityType::Note, &note.id, note.tags.as_deref().unwrap_or_default())?;
+            Self::write_note_links(tx, &note.id, &note.content)?;
+            if let Some(project_id) = project_id.as_deref() {
+                Self::refresh_note_links(tx, project_id)?;
+                Self::renumber_pinned_notes(tx, project_id)?;
+            }
+            Self::record_activity(tx, EntityType::Note, &note.id, ActivityAction::Updated)?;
+            Ok(true)
+        })
+    }
+
+    /// Flip a note's pin in one statement, so rapid toggles cannot both read the old state.
+    /// A newly pinned note goes last; unpinning closes the gap. Returns false when the note does not exist.
+    pub fn toggle_note_pin(conn: &Connection, id: &str) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        let tx = conn.unchecked_transaction()?;
+
+        let project_id: Option<Option<String>> = tx
+            .query_row(
+                "UPDATE notes SET
+                    is_pinned = NOT is_pinned,
+                    pin_order = CASE WHEN is_pinned THEN NULL ELSE
+                        (SELECT COALESCE(MAX(p.pin_order), 0) + 1 FROM notes p
+                         WHERE p.project_id = notes.project_id AND p.is_pinned)
+                    END,
+                    updated_at = MAX(?1, updated_at + 1)
+                 WHERE id = ?2
+                 RETURNING project_id",
+                params![now, id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(project_id) = project_id else {
+            return Ok(false);
+        };
+
+        if let Some(project_id) = project_id {
+            Self::renumber_pinned_notes(&tx, &project_id)?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Get pinned notes of a project, in their user-defined order or most recently updated first
+    pub fn get_pinned_notes(conn: &Connection, project_id: &str, manual_order: bool) -> AppResult<Vec<Note>> {
+        let order = if manual_order {
+            "pin_order IS NULL, pin_order ASC, updated_at DESC, id ASC"
+        } else {
+            "updated_at DESC, id ASC"
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes WHERE project_id = ?1 AND is_pinned = 1 ORDER BY {}",
+            order
+        ))?;
+
+        let notes = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// The `limit` most recently updated notes of a project
+    pub fn get_recent_notes(conn: &Connection, project_id: &str, limit: i64) -> AppResult<Vec<Note>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes WHERE project_id = ?1 ORDER BY updated_at DESC, id ASC LIMIT ?2",
+        )?;
+
+        let notes = stmt.query_map(params![project_id, limit], |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Notes of a project that were opened, most often opened first and the most
+    /// recently opened first among equals, at most `limit`
+    pub fn get_frequent_notes(conn: &Connection, project_id: &str, limit: i64) -> AppResult<Vec<Note>> {
+        let mut stmt = conn.prepare(
+            "SELECT n.id, n.project_id, n.title, n.content, n.created_at, n.updated_at, n.tags, n.is_pinned, n.is_locked
+             FROM notes n JOIN note_view_state v ON v.note_id = n.id
+             WHERE n.project_id = ?1 AND v.open_count > 0
+             ORDER BY v.open_count DESC, v.last_opened_at DESC, n.id ASC LIMIT ?2",
+        )?;
+
+        let notes = stmt.query_map(params![project_id, limit], |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Get the view state of a note; None when it was never saved
+    pub fn get_note_view_state(conn: &Connection, note_id: &str) -> AppResult<Option<NoteViewState>> {
+        let state = conn.query_row(
+            "SELECT note_id, scroll_position, cursor_offset, last_opened_at, open_count
+             FROM note_view_state WHERE note_id = ?1",
+            params![note_id],
+            Self::row_to_note_view_state,
+        ).optional()?;
+        Ok(state)
+    }
+
+    /// Cached rendering of a note, when it was made from content with this hash by
+    /// this renderer version. A row that does not match or cannot be read is ignored.
+    pub fn get_note_render(conn: &Connection, note_id: &str, content_hash: &str) -> AppResult<Option<html::Prerendered>> {
+        let rendered: Option<String> = conn
+            .prepare_cached(
+                "SELECT rendered FROM note_render_cache
+                 WHERE note_id = ?1 AND content_hash = ?2 AND renderer_version = ?3",
+            )?
+            .query_row(params![note_id, content_hash, html::RENDERER_VERSION], |row| row.get(0))
+            .optional()?;
+        Ok(rendered.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Cache the rendering of a note's content, replacing the one kept for earlier content
+    pub fn save_note_render(conn: &Connection, note_id: &str, content_hash: &str, rendered: &html::Prerendered) -> AppResult<()> {
+        let json = serde_json::to_string(rendered)?;
+        conn.prepare_cached(
+            "INSERT INTO note_render_cache (note_id, content_hash, renderer_version, rendered)
+             SELECT id, ?2, ?3, ?4 FROM notes WHERE id = ?1
+             ON CONFLICT(note_id) DO UPDATE SET
+                content_hash = excluded.content_hash,
+                renderer_version = excluded.renderer_version,
+                rendered = excluded.rendered",
+        )?
+        .execute(params![note_id, content_hash, html::RENDERER_VERSION, json])?;
+        Ok(())
+    }
+
+    /// Drop every cached note rendering. Returns how many were dropped.
+    pub fn clear_note_renders(conn: &Connection) -> AppResult<usize> {
+        Ok(conn.execute("DELETE FROM note_render_cache", [])?)
+    }
+
+    /// Save the view state of a note in one upsert, counting an open when
+    /// `opened`. The note row is not touched. None when the note does not exist.
+    pub fn save_note_view_state(
+        conn: &Connection,
+        note_id: &str,
+        data: &SaveNoteViewStateDto,
+        now: i64,
+    ) -> AppResult<Option<NoteViewState>> {
+        let state = conn.prepare_cached(
+            "INSERT INTO note_view_state (note_id, scroll_position, cursor_offset, last_opened_at, open_count)
+             SELECT id, ?2, ?3, CASE WHEN ?4 THEN ?5 END, ?4 FROM notes WHERE id = ?1
+             ON CONFLICT(note_id) DO UPDATE SET
+                scroll_position = excluded.scroll_position,
+                cursor_offset = excluded.cursor_offset,
+                last_opened_at = CASE WHEN ?4 THEN ?5 ELSE last_opened_at END,
+                open_count = open_count + ?4
+             RETURNING note_id, scroll_position, cursor_offset, last_opened_at, open_count",
+        )?
+        .query_row(
+            params![note_id, data.scroll_position, data.cursor_offset, data.opened, now],
+            Self::row_to_note_view_state,
+        )
+        .optional()?;
+        Ok(state)
+    }
+
+    /// Move a pinned note to `position` (0-based) among its project's pinned notes.
+    /// Returns None when the note does not exist and Some(false) when it is not
+    /// pinned or is an inbox note, which has no project to order it in.
+    pub fn reorder_pinned_note(conn: &Connection, id: &str, position: usize) -> AppResult<Option<bool>> {
+        let tx = conn.unchecked_transaction()?;
+
+        let note: Option<(Option<String>, bool)> = tx
+            .query_row(
+                "SELECT project_id, is_pinned FROM notes WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((project_id, is_pinned)) = note else {
+            return Ok(None);
+        };
+        let (Some(project_id), true) = (project_id, is_pinned) else {
+            return Ok(Some(false));
+        };
+
+        let mut ids = Self::pinned_note_ids(&tx, &project_id)?;
+        ids.retain(|pinned| pinned != id);
+        ids.insert(position.min(ids.len()), id.to_string());
+        Self::write_pin_order(&tx, &ids)?;
+
+        tx.commit()?;
+        Ok(Some(true))
+    }
+
+    /// Number a project's pinned notes 1..n in their current order and clear the
+    /// order of unpinned ones; callers own the transaction
+    fn renumber_pinned_notes(conn: &Connection, project_id: &str) -> AppResult<()> {
+        conn.execute(
+            "UPDATE notes SET pin_order = NULL WHERE project_id = ?1 AND NOT is_pinned AND pin_order IS NOT NULL",
+            params![project_id],
+        )?;
+        let ids = Self::pinned_note_ids(conn, project_id)?;
+        Self::write_pin_order(conn, &ids)
+    }
+
+    fn pinned_note_ids(conn: &Connection, project_id: &str) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM notes WHERE project_id = ?1 AND is_pinned
+             ORDER BY pin_order IS NULL, pin_order ASC, updated_at DESC, id ASC",
+        )?;
+        let ids = stmt.query_map(params![project_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ids)
+    }
+
+    fn write_pin_order(conn: &Connection, ids: &[String]) -> AppResult<()> {
+        let mut stmt = conn.prepare("UPDATE notes SET pin_order = ?1 WHERE id = ?2 AND pin_order IS NOT ?1")?;
+        for (index, id) in ids.iter().enumerate() {
+            stmt.execute(params![index as i64 + 1, id])?;
+        }
+        Ok(())
+    }
+
+    /// Delete note, returning false when it does not exist.
+    /// With `to_trash` the note is copied to the trash first so it can be restored;
+    /// the trash belongs to a project, so inbox notes are always deleted for good.
+    pub fn delete_note(conn: &Connection, id: &str, to_trash: bool) -> AppResult<bool> {
+        let tx = conn.unchecked_transaction()?;
+        let Some(note) = Self::get_note_by_id(&tx, id)? else {
+            return Ok(false);
+        };
+        match note.project_id.as_deref() {
+            Some(project_id) if to_trash => {
+                // Attachment rows go with the note; the trash keeps them for a restore
+                let mut payload = serde_json::to_value(&note)?;
+                payload["attachments"] = serde_json::to_value(Self::get_note_attachments(&tx, id)?)?;
+                let payload = payload.to_string();
+                Self::insert_trash_entry(&tx, EntityType::Note, &note.id, project_id, &note.title, None, &payload)?;
+            }
+            _ => {}
+        }
+        Self::record_activity(&tx, EntityType::Note, id, ActivityAction::Deleted)?;
+
+        tx.execute("DELETE FROM notes WHERE id = ?1", params![id])?;
+        // Another note with the same title may now be the link target
+        if let Some(project_id) = note.project_id.as_deref() {
+            Self::refresh_note_links(&tx, project_id)?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Set the locked flag of a note, returning false when the note does not exist
+    pub fn set_note_locked(conn: &Connection, id: &str, locked: bool) -> AppResult<bool> {
+        let affected = conn.execute(
+            "UPDATE notes SET is_locked = ?1 WHERE id = ?2",
+            params![locked, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Check whether a note is locked against edits
+    pub fn is_note_locked(conn: &Connection, id: &str) -> AppResult<Option<bool>> {
+        let locked = conn
+            .query_row("SELECT is_locked FROM notes WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        Ok(locked)
+    }
+
+    /// Open, unarchived tasks of a project due in `[from, to)`, soonest first
+    pub fn get_project_due_tasks(conn: &Connection, project_id: &str, from: i64, to: i64) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks
+             WHERE project_id = ?1 AND status != {} AND NOT is_archived
+               AND due_date >= ?2 AND due_date < ?3
+             ORDER BY due_date ASC, id ASC",
+            TASK_COLUMNS,
+            done_status_sql("?1")
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id, from, to], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Get tasks by project ID and status
+    pub fn get_tasks_by_status(conn: &Connection, project_id: &str, status: &str) -> AppResult<Vec<Task>> {
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM tasks WHERE project_id = ?1 AND status = ?2 AND NOT is_archived ORDER BY "order" ASC"#,
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map(params![project_id, status], |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    // ==========================================
+    // Tag Operations
+    // ==========================================
+
+    /// Replace the tags of an entity in its junction table and mirror them into
+    /// the entity's JSON tags column. Tags are matched case-insensitively and
+    /// created on demand; returns the stored spellings in input order.
+    pub fn set_entity_tags(conn: &Connection, entity: EntityType, id: &str, tags: &[String]) -> AppResult<Vec<String>> {
+        let now = chrono::Utc::now().timestamp();
+        let resolved = Self::ensure_tags(conn, tags)?;
+
+        conn.prepare_cached(&format!("DELETE FROM {} WHERE {} = ?1", entity.tag_table(), entity.tag_key()))?
+            .execute(params![id])?;
+        let mut insert = conn.prepare_cached(&format!(
+            "INSERT OR IGNORE INTO {} ({}, tag_id, created_at) VALUES (?1, ?2, ?3)",
+            entity.tag_table(),
+            entity.tag_key()
+        ))?;
+        for (tag_id, _) in &resolved {
+            insert.execute(params![id, tag_id, now])?;
+        }
+
+        let names: Vec<String> = resolved.into_iter().map(|(_, name)| name).collect();
+        conn.prepare_cached(&format!("UPDATE {} SET tags = ?1 WHERE id = ?2", entity.table()))?
+            .execute(params![serde_json::to_string(&names).unwrap_or_default(), id])?;
+
+        Ok(names)
+    }
+
+    /// Get the distinct tags used by notes of a project
+    pub fn get_note_tags(conn: &Connection, project_id: &str) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT t.name FROM tags t
+             JOIN note_tags nt ON nt.tag_id = t.id
+             JOIN notes n ON n.id = nt.note_id
+             WHERE n.project_id = ?1
+             ORDER BY t.name COLLATE NOCASE ASC"
+        )?;
+
+        let tags = stmt.query_map(params![project_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tags)
+    }
+
+    /// Get notes of a project by tag, most recently updated first. A note matches
+    /// a tag when it carries the tag or one below it (case-insensitive); with
+    /// `mode` All it must match every tag, with Any at least one, and it must
+    /// match none of `exclude`. Unknown tags match no note.
+    pub fn get_notes_by_tags(
+        conn: &Connection,
+        project_id: &str,
+        tags: &[String],
+        mode: TagMatchMode,
+        exclude: &[String],
+    ) -> AppResult<Vec<Note>> {
+        let Some((included, mut values)) = Self::tagged_ids_query("note_tags", "note_id", tags, mode) else {
+            return Ok(Vec::new());
+        };
+        let mut query = format!(
+            "SELECT id, project_id, title, content, created_at, updated_at, tags, is_pinned, is_locked
+             FROM notes n WHERE n.project_id = ? AND n.id IN ({})",
+            included
+        );
+        if let Some((excluded, excluded_values)) = Self::tagged_ids_query("note_tags", "note_id", exclude, TagMatchMode::Any) {
+            query.push_str(&format!(" AND n.id NOT IN ({})", excluded));
+            values.extend(excluded_values);
+        }
+        query.push_str(" ORDER BY n.updated_at DESC");
+
+        let mut params: Vec<&dyn ToSql> = vec![&project_id];
+        params.extend(values.iter().map(|v| v as &dyn ToSql));
+
+        let mut stmt = conn.prepare(&query)?;
+        let notes = stmt.query_map(params_from_iter(params), |row| {
+            Ok(Self::row_to_note(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Get unarchived tasks of a project by tag in board order, matched like
+    /// `get_notes_by_tags`
+    pub fn get_tasks_by_tags(
+        conn: &Connection,
+        project_id: &str,
+        tags: &[String],
+        mode: TagMatchMode,
+        exclude: &[String],
+    ) -> AppResult<Vec<Task>> {
+        let Some((included, mut values)) = Self::tagged_ids_query("task_tags", "task_id", tags, mode) else {
+            return Ok(Vec::new());
+        };
+        let mut query = format!(
+            "SELECT {} FROM tasks WHERE project_id = ? AND NOT is_archived AND id IN ({})",
+            TASK_COLUMNS, included
+        );
+        if let Some((excluded, excluded_values)) = Self::tagged_ids_query("task_tags", "task_id", exclude, TagMatchMode::Any) {
+            query.push_str(&format!(" AND id NOT IN ({})", excluded));
+            values.extend(excluded_values);
+        }
+        query.push_str(r#" ORDER BY "order" ASC"#);
+
+        let mut params: Vec<&dyn ToSql> = vec![&project_id];
+        params.extend(values.iter().map(|v| v as &dyn ToSql));
+
+        let mut stmt = conn.prepare(&query)?;
+        let tasks = stmt.query_map(params_from_iter(params), |row| {
+            Ok(Self::row_to_task(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tasks)
+    }
+
+    /// Query of the ids in `owner_column` of the tag link table `link_table`
+    /// whose tags match every one (All) or any (Any) of `tags`, with its bound
+    /// values. The tags are joined as rows numbered by position and the ids
+    /// grouped with HAVING COUNT over those numbers, so a tag that falls under
+    /// two of the given ones counts for both. None when no tag is left after
+    /// trimming.
+    fn tagged_ids_query(link_table: &str, owner_column: &str, tags: &[String], mode: TagMatchMode) -> Option<(String, Vec<String>)> {
+        let mut seen = HashSet::new();
+        let tags: Vec<String> = tags.iter()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+            .collect();
+        if tags.is_empty() {
+            return None;
+        }
+
+        let wanted: Vec<String> = (0..tags.len())
+            .map(|i| format!("SELECT {} AS k, ? AS name, ? AS pattern", i))
+            .collect();
+        let required = match mode {
+            TagMatchMode::All => tags.len(),
+            TagMatchMode::Any => 1,
+        };
+        let query = format!(
+            "SELECT l.{owner} FROM {link} l
+             JOIN tags t ON t.id = l.tag_id
+             JOIN ({wanted}) w ON {matches}
+             GROUP BY l.{owner}
+             HAVING COUNT(DISTINCT w.k) >= {required}",
+            owner = owner_column,
+            link = link_table,
+            wanted = wanted.join(" UNION ALL "),
+            matches = tag_path::sql_match_row("t.name", "w.name", "w.pattern"),
+            required = required
+        );
+        let values = tags.iter().flat_map(|t| tag_path::sql_match_values(t)).collect();
+        Some((query, values))
+    }
+
+    /// Get tag by ID
+    pub fn get_tag_by_id(conn: &Connection, id: &str) -> AppResult<Option<Tag>> {
+        let tag = conn
+            .query_row(
+                "SELECT id, name, color, created_at FROM tags WHERE id = ?1",
+                params![id],
+                |row| Ok(Self::row_to_tag(row)),
+            )
+            .optional()?;
+        Ok(tag)
+    }
+
+    /// Get tag by name (case-insensitive)
+    pub fn get_tag_by_name(conn: &Connection, name: &str) -> AppResult<Option<Tag>> {
+        let tag = conn
+            .query_row(
+                "SELECT id, name, color, created_at FROM tags
+                 WHERE name = ?1 COLLATE NOCASE ORDER BY created_at ASC LIMIT 1",
+                params![name],
+                |row| Ok(Self::row_to_tag(row)),
+            )
+            .optional()?;
+        Ok(tag)
+    }
+
+    /// List tags with note and task usage counts. With a project, only tags used
+    /// by that project, its notes or its tasks are listed and counts are scoped to it.
+    pub fn get_tags_with_counts(conn: &Connection, project_id: Option<&str>) -> AppResult<Vec<TagUsage>> {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM (
+                SELECT t.id, t.name, t.color, t.created_at,
+                    (SELECT COUNT(*) FROM note_tags nt JOIN notes n ON n.id = nt.note_id
+                     WHERE nt.tag_id = t.id AND (?1 IS NULL OR n.project_id = ?1)) AS note_count,
+                    (SELECT COUNT(*) FROM task_tags tt JOIN tasks k ON k.id = tt.task_id
+                     WHERE tt.tag_id = t.id AND (?1 IS NULL OR k.project_id = ?1)) AS task_count
+                FROM tags t
+             ) u
+             WHERE ?1 IS NULL OR u.note_count > 0 OR u.task_count > 0
+                OR EXISTS (SELECT 1 FROM project_tags pt WHERE pt.tag_id = u.id AND pt.project_id = ?1)
+             ORDER BY u.name COLLATE NOCASE ASC"
+        )?;
+
+        let tags = stmt.query_map(params![project_id], |row| {
+            Ok(TagUsage {
+                tag: Self::row_to_tag(row),
+                note_count: row.get(4).unwrap_or_default(),
+                task_count: row.get(5).unwrap_or_default(),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(tags)
+    }
+
+    /// Tags named `ancestor` or below it, shortest name first
+    pub fn get_tags_within(conn: &Connection, ancestor: &str) -> AppResult<Vec<Tag>> {
+        let mut stmt = conn.prepare("SELECT id, name, color, created_at FROM tags ORDER BY length(name), name")?;
+        let tags = stmt.query_map([], |row| Ok(Self::row_to_tag(row)))?
+            .filter_map(|r| r.ok())
+            .filter(|tag| tag_path::is_within(&tag.name, ancestor))
+            .collect();
+        Ok(tags)
+    }
+
+    /// Rename a tag and refresh the tags of everything carrying it
+    pub fn rename_tag(conn: &Connection, id: &str, name: &str) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            let tagged = Self::get_tagged_entities(tx, id)?;
+            tx.execute("UPDATE tags SET name = ?1 WHERE id = ?2", params![name, id])?;
+            for (entity, entity_id) in tagged {
+                Self::sync_tags_column(tx, entity, &entity_id)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Fold one tag into another: move its links (skipping ones the target already
+    /// has), delete it and refresh the tags of everything that carried it
+    pub fn merge_tags(conn: &Connection, source_id: &str, target_id: &str) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            let tagged = Self::get_tagged_entities(tx, source_id)?;
+
+            for entity in [EntityType::Project, EntityType::Task, EntityType::Note] {
+                tx.execute(
+                    &format!(
+                        "INSERT OR IGNORE INTO {junction} ({key}, tag_id, created_at)
+                         SELECT {key}, ?1, created_at FROM {junction} WHERE tag_id = ?2",
+                        junction = entity.tag_table(),
+                        key = entity.tag_key()
+                    ),
+                    params![target_id, source_id],
+                )?;
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE tag_id = ?1", entity.tag_table()),
+                    params![source_id],
+                )?;
+            }
+            tx.execute("DELETE FROM tags WHERE id = ?1", params![source_id])?;
+
+            for (entity, entity_id) in tagged {
+                Self::sync_tags_column(tx, entity, &entity_id)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Set or clear the color of a tag, returning false when it does not exist
+    pub fn set_tag_color(conn: &Connection, id: &str, color: Option<&str>) -> AppResult<bool> {
+        let affected = conn.execute(
+            "UPDATE tags SET color = ?1 WHERE id = ?2",
+            params![color, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Delete a tag and its links, returning false when it does not exist
+    pub fn delete_tag(conn: &Connection, id: &str) -> AppResult<bool> {
+        let tx = conn.unchecked_transaction()?;
+        let tagged = Self::get_tagged_entities(&tx, id)?;
+
+        // Clear links explicitly in case foreign keys are off for this connection
+        for entity in [EntityType::Project, EntityType::Task, EntityType::Note] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE tag_id = ?1", entity.tag_table()),
+                params![id],
+            )?;
+        }
+        let affected = tx.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+
+        for (entity, entity_id) in tagged {
+            Self::sync_tags_column(&tx, entity, &entity_id)?;
+        }
+        tx.commit()?;
+        Ok(affected > 0)
+    }
+
+    /// Projects, tasks and notes carrying a tag
+    fn get_tagged_entities(conn: &Connection, tag_id: &str) -> AppResult<Vec<(EntityType, String)>> {
+        let mut tagged = Vec::new();
+        for entity in [EntityType::Project, EntityType::Task, EntityType::Note] {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM {} WHERE tag_id = ?1",
+                entity.tag_key(),
+                entity.tag_table()
+            ))?;
+            let ids: Vec<String> = stmt.query_map(params![tag_id], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            tagged.extend(ids.into_iter().map(|id| (entity, id)));
+        }
+        Ok(tagged)
+    }
+
+    /// Rewrite an entity's JSON tags column from its junction rows
+    fn sync_tags_column(conn: &Connection, entity: EntityType, id: &str) -> AppResult<()> {
+        let names: Vec<String> = {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT t.name FROM {junction} j JOIN tags t ON t.id = j.tag_id
+                 WHERE j.{key} = ?1 ORDER BY j.rowid ASC",
+                junction = entity.tag_table(),
+                key = entity.tag_key()
+            ))?;
+            let names = stmt.query_map(params![id], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            names
+        };
+
+        conn.execute(
+            &format!("UPDATE {} SET tags = ?1 WHERE id = ?2", entity.table()),
+            params![serde_json::to_string(&names).unwrap_or_default(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Resolve tag names to (id, stored name), creating missing tags.
+    /// Blank names and case-insensitive repeats are dropped. A new tag must be a
+    /// valid hierarchical name; existing tags are matched as stored.
+    fn ensure_tags(conn: &Connection, names: &[String]) -> AppResult<Vec<(String, String)>> {
+        let now = chrono::Utc::now().timestamp();
+        let mut seen = HashSet::new();
+        let mut resolved = Vec::new();
+
+        for name in names {
+            let name = name.trim();
+            let normalized = tag_path::normalize(name);
+            let name = normalized.as_deref().unwrap_or(name);
+            if name.is_empty() || !seen.insert(name.to_lowercase()) {
+                continue;
+            }
+
+            let existing: Option<(String, String)> = conn
+                .prepare_cached("SELECT id, name FROM tags WHERE name = ?1 COLLATE NOCASE ORDER BY created_at ASC LIMIT 1")?
+                .query_row(params![name], |row| Ok((row.get(0)?, row.get(1)?)))
+                .optional()?;
+
+            let tag = match existing {
+                Some(tag) => tag,
+                None => {
+                    if let Err(reason) = &normalized {
+                        return Err(AppError::InvalidInput(format!("Invalid tag '{}': {}", name, reason)));
+                    }
+                    let id = Uuid::new_v4().to_string();
+                    conn.execute(
+                        "INSERT INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)",
+                        params![id, name, now],
+                    )?;
+                    (id, name.to_string())
+                }
+            };
+            resolved.push(tag);
+        }
+
+        Ok(resolved)
+    }
+
+    /// Copy JSON tag columns into the junction tables for rows that have tags
+    /// but no junction rows yet (data written before tags were normalized)
+    fn backfill_tag_links(conn: &Connection) -> AppResult<()> {
+        for entity in [EntityType::Project, EntityType::Task, EntityType::Note] {
+            let rows: Vec<(String, String)> = {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT id, tags FROM {table} e
+                     WHERE json_valid(e.tags) AND json_array_length(e.tags) > 0
+                     AND NOT EXISTS (SELECT 1 FROM {junction} j WHERE j.{key} = e.id)",
+                    table = entity.table(),
+                    junction = entity.tag_table(),
+                    key = entity.tag_key()
+                ))?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+
+            for (id, tags_json) in rows {
+                let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+                let tags: Vec<String> = tags.iter().map(|t| tag_path::repair(t)).collect();
+                Self::set_entity_tags(conn, entity, &id, &tags)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Note Link Operations
+    // ==========================================
+
+    /// Notes of the same project whose wikilinks resolve to the given note
+    pub fn get_note_backlinks(conn: &Connection, note_id: &str) -> AppResult<Vec<NoteSummary>> {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT n.id, n.project_id, n.title, n.updated_at, n.tags, n.is_pinned, n.is_locked
+             FROM note_links l
+             JOIN notes n ON n.id = l.source_note_id
+             WHERE l.target_note_id = ?1 AND l.source_note_id != ?1
+             ORDER BY n.title COLLATE NOCASE ASC, n.id ASC"
+        )?;
+
+        let notes = stmt.query_map(params![note_id], |row| {
+            Ok(Self::row_to_note_summary(row, 0))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Wikilinks of a note in the order they appear, with the notes they resolve to
+    pub fn get_note_outgoing_links(conn: &Connection, note_id: &str) -> AppResult<Vec<NoteLink>> {
+        let mut stmt = conn.prepare(
+            "SELECT l.target_title, n.id, n.project_id, n.title, n.updated_at, n.tags, n.is_pinned, n.is_locked
+             FROM note_links l
+             LEFT JOIN notes n ON n.id = l.target_note_id
+             WHERE l.source_note_id = ?1
+             ORDER BY l.position ASC"
+        )?;
+
+        let links = stmt.query_map(params![note_id], |row| {
+            let target_id: Option<String> = row.get(1)?;
+            Ok(NoteLink {
+                target_title: row.get(0)?,
+                note: target_id.map(|_| Self::row_to_note_summary(row, 1)),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(links)
+    }
+
+    /// Replace the wikilink rows of a note with the targets found in its content.
+    /// Targets stay unresolved until the project's links are refreshed.
+    fn write_note_links(conn: &Connection, note_id: &str, content: &str) -> AppResult<()> {
+        conn.prepare_cached("DELETE FROM note_links WHERE source_note_id = ?1")?
+            .execute(params![note_id])?;
+        let mut insert = conn.prepare_cached(
+            "INSERT INTO note_links (source_note_id, target_title, target_note_id, position)
+             VALUES (?1, ?2, NULL, ?3)",
+        )?;
+        for (position, target) in markdown::wikilink_targets(content).iter().enumerate() {
+            insert.execute(params![note_id, target, position as i64])?;
+        }
+        Ok(())
+    }
+
+    /// Resolve every wikilink of a project's notes against the current note
+    /// titles (case-insensitive; the oldest note wins when titles repeat)
+    fn refresh_note_links(conn: &Connection, project_id: &str) -> AppResult<()> {
+        conn.execute(
+            "UPDATE note_links SET target_note_id = (
+                SELECT n.id FROM notes n
+                WHERE n.project_id = ?1 AND n.title = note_links.target_title COLLATE NOCASE
+                ORDER BY n.created_at ASC, n.id ASC LIMIT 1
+             )
+             WHERE source_note_id IN (SELECT id FROM notes WHERE project_id = ?1)",
+            params![project_id],
+        )?;
+        Ok(())
+    }
+
+    /// Notes and references of a project, and its non-archived tasks when
+    /// `include_tasks` is set, with every edge between them: resolved wikilinks,
+    /// citations, notes and tasks sharing a research question, and subtasks.
+    /// Edges are distinct; a note linking to itself has no edge.
+    pub fn get_project_graph(conn: &Connection, project_id: &str, include_tasks: bool) -> AppResult<(Vec<GraphNode>, Vec<GraphEdge>)> {
+        let mut node_queries = vec![
+            ("note", "SELECT id, title FROM notes WHERE project_id = ?1 ORDER BY title COLLATE NOCASE ASC, id ASC"),
+            ("reference", "SELECT id, title FROM \"references\" WHERE project_id = ?1 ORDER BY citation_key ASC"),
+        ];
+        let mut edge_queries = vec![
+            (
+                "link",
+                "SELECT DISTINCT l.source_note_id, l.target_note_id FROM note_links l
+                 JOIN notes n ON n.id = l.source_note_id
+                 WHERE n.project_id = ?1 AND l.target_note_id IS NOT NULL AND l.target_note_id != l.source_note_id",
+            ),
+            (
+                "cites",
+                "SELECT nr.note_id, nr.reference_id FROM note_references nr
+                 JOIN notes n ON n.id = nr.note_id
+                 WHERE n.project_id = ?1",
+            ),
+        ];
+        if include_tasks {
+            node_queries.push((
+                "task",
+                "SELECT id, title FROM tasks WHERE project_id = ?1 AND NOT is_archived ORDER BY \"order\" ASC, id ASC",
+            ));
+            edge_queries.push((
+                "question",
+                "SELECT DISTINCT qn.note_id, qt.task_id FROM research_questions q
+                 JOIN research_question_notes qn ON qn.question_id = q.id
+                 JOIN research_question_tasks qt ON qt.question_id = q.id
+                 JOIN tasks t ON t.id = qt.task_id
+                 WHERE q.project_id = ?1 AND NOT t.is_archived",
+            ));
+            edge_queries.push((
+                "subtask",
+                "SELECT t.id, t.parent_id FROM tasks t
+                 JOIN tasks p ON p.id = t.parent_id
+                 WHERE t.project_id = ?1 AND NOT t.is_archived AND NOT p.is_archived",
+            ));
+        }
+
+        let mut nodes = Vec::new();
+        for (kind, query) in node_queries {
+            let mut stmt = conn.prepare(query)?;
+            let rows = stmt.query_map(params![project_id], |row| {
+                Ok(GraphNode { id: row.get(0)?, kind: kind.to_string(), title: row.get(1)? })
+            })?;
+            nodes.extend(rows.filter_map(|r| r.ok()));
+        }
+
+        let mut edges = Vec::new();
+        for (kind, query) in edge_queries {
+            let mut stmt = conn.prepare(query)?;
+            let rows = stmt.query_map(params![project_id], |row| {
+                Ok(GraphEdge { from: row.get(0)?, to: row.get(1)?, kind: kind.to_string() })
+            })?;
+            edges.extend(rows.filter_map(|r| r.ok()));
+        }
+
+        Ok((nodes, edges))
+    }
+
+    /// Parse the wikilinks of every note (notes written before links were tracked)
+    fn backfill_note_links(conn: &Connection) -> AppResult<()> {
+        let notes: Vec<(String, String, String)> = {
+            let mut stmt = conn.prepare("SELECT id, project_id, content FROM notes")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        let mut project_ids = HashSet::new();
+        for (id, project_id, content) in notes {
+            Self::write_note_links(conn, &id, &content)?;
+            project_ids.insert(project_id);
+        }
+        for project_id in &project_ids {
+            Self::refresh_note_links(conn, project_id)?;
+        }
+
+        Ok(())
+    }
+
+    // ==========================================
+    // Project Status Operations
+    // ==========================================
+
+    /// Get the ordered task statuses for a project, falling back to the built-in workflow
+    pub fn get_project_statuses(conn: &Connection, project_id: &str) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            "SELECT name FROM project_statuses WHERE project_id = ?1 ORDER BY position ASC"
+        )?;
+
+        let statuses: Vec<String> = stmt.query_map(params![project_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        if statuses.is_empty() {
+            Ok(DEFAULT_TASK_STATUSES.iter().map(|s| s.to_string()).collect())
+        } else {
+            Ok(statuses)
+        }
+    }
+
+    /// The status that marks a task of the project complete: the last of its workflow
+    pub fn get_done_status(conn: &Connection, project_id: &str) -> AppResult<String> {
+        let done = Self::get_project_statuses(conn, project_id)?.pop();
+        Ok(done.unwrap_or_else(|| "done".to_string()))
+    }
+
+    /// Replace the task statuses of a project.
+    ///
+    /// Tasks using a status that is no longer in the list are moved to the status given
+    /// in `mapping`; the remap and the new list are applied in the same transaction.
+    /// completed_at then follows the last status of the new list, which marks tasks done.
+    pub fn set_project_statuses(conn: &Connection, project_id: &str, statuses: &[String], mapping: &HashMap<String, String>) -> AppResult<()> {
+        let Some(done) = statuses.last() else {
+            return Err(AppError::InvalidInput("At least one status is required".into()));
+        };
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = statuses.iter().find(|s| !seen.insert(s.as_str())) {
+            return Err(AppError::InvalidInput(format!("Duplicate status '{}'", duplicate)));
+        }
+
+        Self::with_tx(conn, |tx| Self::write_project_statuses(tx, project_id, statuses, done, mapping))
+    }
+
+    fn write_project_statuses(
+        tx: &Connection,
+        project_id: &str,
+        statuses: &[String],
+        done: &str,
+        mapping: &HashMap<String, String>,
+    ) -> AppResult<()> {
+        let now = chrono::Utc::now().timestamp();
+
+        let mut stmt = tx.prepare(
+            "SELECT status, COUNT(*) FROM tasks WHERE project_id = ?1 GROUP BY status"
+        )?;
+        let in_use: Vec<(String, i64)> = stmt.query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        drop(stmt);
+
+        for (status, count) in in_use {
+            if statuses.contains(&status) {
+                continue;
+            }
+
+            let target = mapping.get(&status).ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "Status '{}' is used by {} task(s); provide a mapping to one of the new statuses",
+                    status, count
+                ))
+            })?;
+
+            if !statuses.contains(target) {
+                return Err(AppError::InvalidInput(format!(
+                    "Mapping target '{}' is not one of the new statuses",
+                    target
+                )));
+            }
+
+            tx.execute(
+                "UPDATE tasks SET status = ?1, updated_at = MAX(?2, updated_at + 1) WHERE project_id = ?3 AND status = ?4",
+                params![target, now, project_id, status],
+            )?;
+        }
+
+        tx.execute("DELETE FROM project_statuses WHERE project_id = ?1", params![project_id])?;
+        for (position, name) in statuses.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO project_statuses (project_id, name, position) VALUES (?1, ?2, ?3)",
+                params![project_id, name, position as i64],
+            )?;
+        }
+
+        tx.execute(
+            "UPDATE tasks SET
+                completed_at = CASE WHEN status = ?2 THEN COALESCE(completed_at, ?3) ELSE NULL END,
+                updated_at = MAX(?3, updated_at + 1)
+             WHERE project_id = ?1 AND (status = ?2) != (completed_at IS NOT NULL)",
+            params![project_id, done, now],
+        )?;
+        Ok(())
+    }
+
+    // ==========================================
+    // Bulk Move Operations
+    // ==========================================
+
+    /// Move notes to another project in a single transaction. Locked notes are skipped.
+    pub fn move_notes_to_project(conn: &Connection, note_ids: &[String], target_project_id: &str) -> AppResult<MoveResult> {
+        Self::with_tx(conn, |tx| Self::move_notes_in_tx(tx, note_ids, target_project_id))
+    }
+
+    fn move_notes_in_tx(tx: &Connection, note_ids: &[String], target_project_id: &str) -> AppResult<MoveResult> {
+        let now = chrono::Utc::now().timestamp();
+        let mut result = MoveResult::default();
+        let mut source_projects = HashSet::new();
+
+        for id in note_ids {
+            // The inner None is an inbox note
+            let current: Option<(Option<String>, bool)> = tx
+                .query_row("SELECT project_id, is_locked FROM notes WHERE id = ?1", params![id], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .optional()?;
+
+            match current {
+                None => result.skipped.push(SkippedItem {
+                    id: id.clone(),
+                    reason: "Note not found".into(),
+                }),
+                Some((_, true)) => result.skipped.push(SkippedItem {
+                    id: id.clone(),
+                    reason: "Note is locked".into(),
+                }),
+                Some((Some(project_id), _)) if project_id == target_project_id => result.skipped.push(SkippedItem {
+                    id: id.clone(),
+                    reason: "Note is already in the target project".into(),
+                }),
+                Some((project_id, _)) => {
+                    tx.execute(
+                        "UPDATE notes SET project_id = ?1, updated_at = MAX(?2, updated_at + 1) WHERE id = ?3",
+                        params![target_project_id, now, id],
+                    )?;
+                    source_projects.extend(project_id);
+                    result.moved += 1;
+                }
+            }
+        }
+
+        // Links resolve within a project: re-resolve on both sides of the move
+        if result.moved > 0 {
+            for project_id in &source_projects {
+                Self::refresh_note_links(tx, project_id)?;
+            }
+            Self::refresh_note_links(tx, target_project_id)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Point the attachments of a note at their new files after they were copied
+    /// to another project. `moves` maps old relative paths to new ones; images,
+    /// links and embeds in the note's content that name a renamed file, by path
+    /// or by file name alone, are rewritten to match.
+    pub fn relocate_note_attachments(conn: &Connection, note_id: &str, moves: &HashMap<String, String>) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            let attachments = Self::get_note_attachments(tx, note_id)?;
+            let mut update = tx.prepare_cached("UPDATE note_attachments SET relative_path = ?1 WHERE id = ?2")?;
+            // The copies are files of their own until they are linked to the store again
+            let mut unlink = tx.prepare_cached("UPDATE attachment_links SET is_linked = 0 WHERE attachment_id = ?1")?;
+            for attachment in &attachments {
+                if let Some(new_path) = moves.get(&attachment.relative_path) {
+                    update.execute(params![new_path, attachment.id])?;
+                    unlink.execute(params![attachment.id])?;
+                }
+            }
+
+            let content: Option<String> = tx
+                .query_row("SELECT content FROM notes WHERE id = ?1", params![note_id], |row| row.get(0))
+                .optional()?;
+            let Some(content) = content else {
+                return Ok(());
+            };
+            let rewritten = markdown::rewrite_file_references(&content, |reference| {
+                let reference = reference.trim_start_matches("./");
+                moves.iter().filter(|(old, new)| old != new).find_map(|(old, new)| {
+                    let old_name = old.rsplit('/').next().unwrap_or(old);
+                    let new_name = new.rsplit('/').next().unwrap_or(new);
+                    let target = if reference.eq_ignore_ascii_case(old) {
+                        new.as_str()
+                    } else if reference.eq_ignore_ascii_case(old_name) {
+                        new_name
+                    } else {
+                        return None;
+                    };
+                    // A space would end the Markdown link target early
+                    Some(target.replace(' ', "%20"))
+                })
+            });
+            if rewritten != content {
+                tx.execute(
+                    "UPDATE notes SET content = ?1, word_count = ?2 WHERE id = ?3",
+                    params![rewritten, word_count::word_count(&rewritten), note_id],
+                )?;
+                Self::write_note_links(tx, note_id, &rewritten)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Move tasks to another project in a single transaction.
+    ///
+    /// With `keep_hierarchy`, a task whose parent is not part of the move is skipped;
+    /// otherwise it becomes a root task in the target project. Subtasks left behind
+    /// by a moved parent become root tasks in their original project.
+    pub fn move_tasks_to_project(conn: &Connection, task_ids: &[String], target_project_id: &str, keep_hierarchy: bool) -> AppResult<MoveResult> {
+        let tx = conn.unchecked_transaction()?;
+        let now = chrono::Utc::now().timestamp();
+        let mut result = MoveResult::default();
+
+        // Resolve current project and parent of every requested task
+        let mut parents: HashMap<String, Option<String>> = HashMap::new();
+        let mut in_target: HashSet<String> = HashSet::new();
+        for id in task_ids {
+            if parents.contains_key(id) {
+                continue;
+            }
+
+            let current: Option<(String, Option<String>)> = tx
+                .query_row(
+                    "SELECT project_id, parent_id FROM tasks WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+
+            match current {
+                None => result.skipped.push(SkippedItem {
+                    id: id.clone(),
+                    reason: "Task not found".into(),
+                }),
+                Some((project_id, _)) if project_id == target_project_id => {
+                    in_target.insert(id.clone());
+                    result.skipped.push(SkippedItem {
+                        id: id.clone(),
+                        reason: "Task is already in the target project".into(),
+                    });
+                }
+                Some((_, parent_id)) => {
+                    parents.insert(id.clone(), parent_id);
+                }
+            }
+        }
+
+        // When keeping the hierarchy, drop tasks whose parent is not moving with them.
+        // Repeat until stable since skipping a parent also strands its children.
+        if keep_hierarchy {
+            loop {
+                let stranded: Vec<String> = parents
+                    .iter()
+                    .filter(|(_, parent_id)| {
+                        matches!(parent_id, Some(p) if !parents.contains_key(p) && !in_target.contains(p))
+                    })
+                    .map(|(id, _)| id.clone())
+                    .collect();
+
+                if stranded.is_empty() {
+                    break;
+                }
+
+                for id in stranded {
+                    parents.remove(&id);
+                    result.skipped.push(SkippedItem {
+                        id,
+                        reason: "Parent task is not included in the move".into(),
+                    });
+                }
+            }
+        }
+
+        let moving: HashSet<&String> = parents.keys().collect();
+
+        for (id, parent_id) in &parents {
+            // Moved tasks take the next keys of the target project
+            let task_key = Self::allocate_task_key(&tx, target_project_id)?;
+            let keeps_parent = matches!(parent_id, Some(p) if moving.contains(p) || in_target.contains(p));
+            if keeps_parent {
+                tx.execute(
+                    "UPDATE tasks SET project_id = ?1, task_key = ?2, rank = NULL, board_order = NULL, updated_at = MAX(?3, updated_at + 1) WHERE id = ?4",
+                    params![target_project_id, task_key, now, id],
+                )?;
+            } else {
+                tx.execute(
+                    "UPDATE tasks SET project_id = ?1, task_key = ?2, rank = NULL, board_order = NULL, parent_id = NULL, updated_at = MAX(?3, updated_at + 1) WHERE id = ?4",
+                    params![target_project_id, task_key, now, id],
+                )?;
+            }
+            result.moved += 1;
+        }
+
+        // Re-root subtasks that stayed behind in the source project
+        for id in &moving {
+            tx.execute(
+                "UPDATE tasks SET parent_id = NULL, updated_at = MAX(?1, updated_at + 1) WHERE parent_id = ?2 AND project_id != ?3",
+                params![now, id, target_project_id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(result)
+    }
+
+    // ==========================================
+    // Activity Operations
+    // ==========================================
+
+    /// Count tasks completed per local day since `from`, optionally for one project.
+    /// `offset_seconds` shifts timestamps to local time before bucketing.
+    pub fn count_completed_tasks_by_day(conn: &Connection, project_id: Option<&str>, from: i64, offset_seconds: i32) -> AppResult<HashMap<String, i64>> {
+        Self::count_by_day(conn, "tasks", "completed_at", project_id, from, offset_seconds)
+    }
+
+    /// Count notes last edited per local day since `from`, optionally for one project
+    pub fn count_edited_notes_by_day(conn: &Connection, project_id: Option<&str>, from: i64, offset_seconds: i32) -> AppResult<HashMap<String, i64>> {
+        Self::count_by_day(conn, "notes", "updated_at", project_id, from, offset_seconds)
+    }
+
+    fn count_by_day(
+        conn: &Connection,
+        table: &str,
+        column: &str,
+        project_id: Option<&str>,
+        from: i64,
+        offset_seconds: i32,
+    ) -> AppResult<HashMap<String, i64>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT date({column} + ?1, 'unixepoch') AS day, COUNT(*) FROM {table}
+             WHERE {column} IS NOT NULL AND {column} >= ?2 AND (?3 IS NULL OR project_id = ?3)
+             GROUP BY day",
+            column = column,
+            table = table
+        ))?;
+
+        let counts = stmt.query_map(params![offset_seconds, from, project_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(counts)
+    }
+
+    /// Words of a project's notes grouped by the local day each was last edited,
+    /// for edits in `from..to`, oldest day first
+    pub fn get_note_words_by_day(conn: &Connection, project_id: &str, from: i64, to: i64, offset_seconds: i32) -> AppResult<Vec<WritingDay>> {
+        let mut stmt = conn.prepare(
+            "SELECT date(updated_at + ?1, 'unixepoch') AS day, COALESCE(SUM(word_count), 0), COUNT(*) FROM notes
+             WHERE project_id = ?2 AND updated_at >= ?3 AND updated_at < ?4
+             GROUP BY day
+             ORDER BY day",
+        )?;
+
+        let days = stmt.query_map(params![offset_seconds, project_id, from, to], |row| {
+            Ok(WritingDay {
+                date: row.get(0)?,
+                words: row.get(1)?,
+                notes_edited: row.get(2)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(days)
+    }
+
+    /// Words in all of a project's notes
+    pub fn get_project_word_total(conn: &Connection, project_id: &str) -> AppResult<i64> {
+        let total = conn.query_row(
+            "SELECT COALESCE(SUM(word_count), 0) FROM notes WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+        Ok(total)
+    }
+
+    /// Set a project's word-count goal, or clear it with None
+    pub fn set_writing_goal(conn: &Connection, project_id: &str, target_words: Option<i64>) -> AppResult<()> {
+        match target_words {
+            Some(target_words) => conn.execute(
+                "INSERT INTO project_writing_goals (project_id, target_words, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(project_id) DO UPDATE SET target_words = excluded.target_words, updated_at = excluded.updated_at",
+                params![project_id, target_words, chrono::Utc::now().timestamp()],
+            )?,
+            None => conn.execute("DELETE FROM project_writing_goals WHERE project_id = ?1", params![project_id])?,
+        };
+        Ok(())
+    }
+
+    /// A project's word-count goal
+    pub fn get_writing_goal(conn: &Connection, project_id: &str) -> AppResult<Option<i64>> {
+        Ok(conn
+            .query_row(
+                "SELECT target_words FROM project_writing_goals WHERE project_id = ?1",
+                params![project_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Condition on the note aliased `n` being tagged `tag` or below it; binds
+    /// `tag_path::sql_match_values`. Empty for every note.
+    fn goal_note_filter(tag: &str) -> (String, Vec<String>) {
+        if tag.trim().is_empty() {
+            return (String::new(), Vec::new());
+        }
+        (
+            format!(
+                " AND EXISTS (SELECT 1 FROM note_tags nt JOIN tags t ON t.id = nt.tag_id WHERE nt.note_id = n.id AND {})",
+                tag_path::sql_match("t.name")
+            ),
+            tag_path::sql_match_values(tag).to_vec(),
+        )
+    }
+
+    /// Words and number of a project's notes tagged `tag` or below it, or of
+    /// all its notes when `tag` is empty
+    pub fn get_goal_note_words(conn: &Connection, project_id: &str, tag: &str) -> AppResult<(i64, i64)> {
+        let (filter, values) = Self::goal_note_filter(tag);
+        let mut params: Vec<&dyn ToSql> = vec![&project_id];
+        params.extend(values.iter().map(|v| v as &dyn ToSql));
+        Ok(conn.query_row(
+            &format!("SELECT COALESCE(SUM(n.word_count), 0), COUNT(*) FROM notes n WHERE n.project_id = ?{}", filter),
+            params.as_slice(),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?)
+    }
+
+    /// Recorded word counts of the notes counted by `get_goal_note_words`, as
+    /// (note id, 15-minute bucket, words): every record from `since` on and the
+    /// last one before, oldest first
+    pub fn get_goal_word_history(conn: &Connection, project_id: &str, tag: &str, since: i64) -> AppResult<Vec<(String, i64, i64)>> {
+        let (filter, values) = Self::goal_note_filter(tag);
+        let mut params: Vec<&dyn ToSql> = vec![&project_id, &since];
+        params.extend(values.iter().map(|v| v as &dyn ToSql));
+        let mut stmt = conn.prepare(&format!(
+            "SELECT h.note_id, h.bucket, h.words FROM note_word_history h
+             JOIN notes n ON n.id = h.note_id
+             WHERE n.project_id = ?1
+             AND (h.bucket >= ?2 OR h.bucket = (
+                SELECT MAX(p.bucket) FROM note_word_history p WHERE p.note_id = h.note_id AND p.bucket < ?2
+             )){}
+             ORDER BY h.bucket",
+            filter
+        ))?;
+        let history = stmt
+            .query_map(params.as_slice(), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(history)
+    }
+
+    /// Log a change to a project, task or note with a snapshot of its current title.
+    /// Runs inside the caller's transaction so the entry commits or rolls back with
+    /// the change; does nothing when the entity does not exist or is an inbox note.
+    fn record_activity(conn: &Connection, entity: EntityType, id: &str, action: ActivityAction) -> AppResult<()> {
+        let (project_column, title_column) = match entity {
+            EntityType::Project => ("id", "name"),
+            EntityType::Task | EntityType::Note => ("project_id", "title"),
+        };
+        let inserted = conn
+            .prepare_cached(&format!(
+                "INSERT INTO activity_log (project_id, entity_type, entity_id, action, summary, timestamp)
+                 SELECT {project}, ?1, id, ?2, {title}, ?3 FROM {table} WHERE id = ?4 AND {project} IS NOT NULL",
+                project = project_column,
+                title = title_column,
+                table = entity.table()
+            ))?
+            .execute(params![entity, action, chrono::Utc::now().timestamp(), id])?;
+
+        if inserted > 0 && conn.last_insert_rowid() % ACTIVITY_PRUNE_INTERVAL == 0 {
+            Self::prune_activity_log(conn, ACTIVITY_MAX_ENTRIES)?;
+        }
+        Ok(())
+    }
+
+    /// Drop activity entries beyond the newest `max_entries`
+    fn prune_activity_log(conn: &Connection, max_entries: i64) -> AppResult<usize> {
+        let removed = conn.execute(
+            "DELETE FROM activity_log WHERE id NOT IN (SELECT id FROM activity_log ORDER BY id DESC LIMIT ?1)",
+            params![max_entries],
+        )?;
+        Ok(removed)
+    }
+
+    /// List activity entries newest first, optionally for one project. Pages continue
+    /// strictly before the (`before_timestamp`, `before_id`) of the last entry seen;
+    /// `before_id` only breaks ties between entries of the same second.
+    pub fn list_activity(
+        conn: &Connection,
+        project_id: Option<&str>,
+        limit: i64,
+        before_timestamp: Option<i64>,
+        before_id: Option<i64>,
+    ) -> AppResult<Vec<ActivityEntry>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, project_id, entity_type, entity_id, action, summary, timestamp FROM activity_log
+             WHERE (?1 IS NULL OR project_id = ?1)
+               AND (?2 IS NULL OR timestamp < ?2 OR (timestamp = ?2 AND ?3 IS NOT NULL AND id < ?3))
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?4"
+        )?;
+
+        let entries = stmt.query_map(params![project_id, before_timestamp, before_id, limit], |row| {
+            Ok(ActivityEntry {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                entity_type: row.get(2)?,
+                entity_id: row.get(3)?,
+                action: row.get(4)?,
+                summary: row.get(5)?,
+                timestamp: row.get(6)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(entries)
+    }
+
+    /// Remove activity entries, only those older than `older_than` when given.
+    /// Returns how many were removed.
+    pub fn clear_activity(conn: &Connection, older_than: Option<i64>) -> AppResult<usize> {
+        let removed = conn.execute(
+            "DELETE FROM activity_log WHERE ?1 IS NULL OR timestamp < ?1",
+            params![older_than],
+        )?;
+        Ok(removed)
+    }
+
+    // ==========================================
+    // Jump Index Operations
+    // ==========================================
+
+    /// Get id, title and update time of the most recently updated projects, tasks and notes
+    /// of non-archived projects, at most `limit_per_kind` of each
+    pub fn get_jump_index_entries(conn: &Connection, limit_per_kind: usize) -> AppResult<Vec<JumpIndexEntry>> {
+        let queries = [
+            (
+                "project",
+                "SELECT id, id, name, last_modified_at FROM projects
+                 WHERE status != 'archived' ORDER BY last_modified_at DESC LIMIT ?1",
+            ),
+            (
+                "task",
+                "SELECT t.id, t.project_id, t.title, t.updated_at FROM tasks t
+                 JOIN projects p ON p.id = t.project_id
+                 WHERE p.status != 'archived' AND NOT t.is_archived ORDER BY t.updated_at DESC LIMIT ?1",
+            ),
+            (
+                "note",
+                "SELECT n.id, n.project_id, n.title, n.updated_at FROM notes n
+                 JOIN projects p ON p.id = n.project_id
+                 WHERE p.status != 'archived' ORDER BY n.updated_at DESC LIMIT ?1",
+            ),
+        ];
+
+        let mut entries = Vec::new();
+        for (kind, query) in queries {
+            let mut stmt = conn.prepare(query)?;
+            let rows = stmt.query_map(params![limit_per_kind as i64], |row| {
+                Ok(JumpIndexEntry {
+                    id: row.get(0)?,
+                    kind: kind.to_string(),
+                    project_id: row.get(1)?,
+                    title: row.get(2)?,
+                    updated_at: row.get(3)?,
+                })
+            })?;
+            entries.extend(rows.filter_map(|r| r.ok()));
+        }
+
+        Ok(entries)
+    }
+
+    // ==========================================
+    // Global Search Operations
+    // ==========================================
+
+    /// Search project names and descriptions, task titles and descriptions and note
+    /// titles and content of non-archived projects in one query. Every term must match
+    /// the title or body; each term scores 3 on a title match and 1 on a body match.
+    /// Only what was updated from `updated_after` up to `updated_before` is searched.
+    pub fn global_search(
+        conn: &Connection,
+        query: &str,
+        limit: usize,
+        snippet_chars: usize,
+        updated_after: Option<i64>,
+        updated_before: Option<i64>,
+    ) -> AppResult<Vec<GlobalSearchResult>> {
+        let terms: Vec<String> = query.split_whitespace().map(text::like_contains).collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut clauses = Vec::new();
+        let mut scores = Vec::new();
+        for index in 1..=terms.len() {
+            let p = format!("?{} ESCAPE '{}'", index, text::LIKE_ESCAPE);
+            clauses.push(format!("(title LIKE {p} OR body LIKE {p})", p = p));
+            scores.push(format!("CASE WHEN title LIKE {p} THEN 3 ELSE 1 END", p = p));
+        }
+
+        let sql = format!(
+            "SELECT entity_type, id, project_id, project_name, title, body, updated_at, ({scores}) AS score
+             FROM (
+                SELECT 'project' AS entity_type, id, id AS project_id, name AS project_name,
+                       name AS title, description AS body, last_modified_at AS updated_at
+                FROM projects WHERE status != 'archived'
+                UNION ALL
+                SELECT 'task', t.id, t.project_id, p.name, t.title, t.description, t.updated_at
+                FROM tasks t JOIN projects p ON p.id = t.project_id WHERE p.status != 'archived' AND NOT t.is_archived
+                UNION ALL
+                SELECT 'note', n.id, n.project_id, p.name, n.title, n.content, n.updated_at
+                FROM notes n JOIN projects p ON p.id = n.project_id WHERE p.status != 'archived'
+             )
+             WHERE {clauses}
+               AND (?{after} IS NULL OR updated_at >= ?{after})
+               AND (?{before} IS NULL OR updated_at < ?{before})
+             ORDER BY score DESC, updated_at DESC, id ASC
+             LIMIT ?{limit}",
+            scores = scores.join(" + "),
+            clauses = clauses.join(" AND "),
+            after = terms.len() + 1,
+            before = terms.len() + 2,
+            limit = terms.len() + 3
+        );
+
+        let mut values: Vec<Box<dyn ToSql>> = terms.into_iter().map(|t| Box::new(t) as Box<dyn ToSql>).collect();
+        values.push(Box::new(updated_after));
+        values.push(Box::new(updated_before));
+        values.push(Box::new(limit as i64));
+
+        let mut stmt = conn.prepare(&sql)?;
+        let results = stmt.query_map(params_from_iter(values.iter()), |row| {
+            let body: Option<String> = row.get(5)?;
+            Ok(GlobalSearchResult {
+                entity_type: row.get(0)?,
+                id: row.get(1)?,
+                project_id: row.get(2)?,
+                project_name: row.get(3)?,
+                title: row.get(4)?,
+                snippet: body.and_then(|body| text::snippet(&body, query, snippet_chars)),
+                score: row.get(7)?,
+                updated_at: row.get(6)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(results)
+    }
+
+    // ==========================================
+    // Research Question Operations
+    // ==========================================
+
+    /// Insert a research question
+    pub fn insert_research_question(conn: &Connection, question: &ResearchQuestion) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO research_questions (id, project_id, question, status, answer_summary, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                question.id,
+                question.project_id,
+                question.question,
+                question.status,
+                question.answer_summary,
+                question.created_at,
+                question.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get research question by ID
+    pub fn get_research_question_by_id(conn: &Connection, id: &str) -> AppResult<Option<ResearchQuestion>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, question, status, answer_summary, created_at, updated_at
+             FROM research_questions WHERE id = ?1"
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Self::row_to_research_question(row)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get research questions of a project, oldest first
+    pub fn get_research_questions_by_project(conn: &Connection, project_id: &str) -> AppResult<Vec<ResearchQuestion>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, question, status, answer_summary, created_at, updated_at
+             FROM research_questions WHERE project_id = ?1 ORDER BY created_at ASC"
+        )?;
+
+        let questions = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_research_question(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(questions)
+    }
+
+    /// Update research question fields that are provided
+    pub fn update_research_question(
+        conn: &Connection,
+        id: &str,
+        question: Option<&str>,
+        status: Option<&str>,
+        answer_summary: Option<&str>,
+    ) -> AppResult<bool> {
+        let now = chrono::Utc::now().timestamp();
+        let affected = conn.execute(
+            "UPDATE research_questions SET
+                question = COALESCE(?1, question),
+                status = COALESCE(?2, status),
+                answer_summary = COALESCE(?3, answer_summary),
+                updated_at = ?4
+             WHERE id = ?5",
+            params![question, status, answer_summary, now, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Delete research question, returning false when it does not exist
+    pub fn delete_research_question(conn: &Connection, id: &str) -> AppResult<bool> {
+        let tx = conn.unchecked_transaction()?;
+        // Clear links explicitly in case foreign keys are off for this connection
+        tx.execute("DELETE FROM research_question_notes WHERE question_id = ?1", params![id])?;
+        tx.execute("DELETE FROM research_question_tasks WHERE question_id = ?1", params![id])?;
+        let affected = tx.execute("DELETE FROM research_questions WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(affected > 0)
+    }
+
+    /// Link a note to a research question (no-op when already linked)
+    pub fn link_question_note(conn: &Connection, question_id: &str, note_id: &str) -> AppResult<()> {
+        conn.execute(
+            "INSERT OR IGNORE INTO research_question_notes (question_id, note_id) VALUES (?1, ?2)",
+            params![question_id, note_id],
+        )?;
+        Ok(())
+    }
+
+    /// Unlink a note from a research question, returning false when it was not linked
+    pub fn unlink_question_note(conn: &Connection, question_id: &str, note_id: &str) -> AppResult<bool> {
+        let affected = conn.execute(
+            "DELETE FROM research_question_notes WHERE question_id = ?1 AND note_id = ?2",
+            params![question_id, note_id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Link a task to a research question (no-op when already linked)
+    pub fn link_question_task(conn: &Connection, question_id: &str, task_id: &str) -> AppResult<()> {
+        conn.execute(
+            "INSERT OR IGNORE INTO research_question_tasks (question_id, task_id) VALUES (?1, ?2)",
+            params![question_id, task_id],
+        )?;
+        Ok(())
+    }
+
+    /// Unlink a task from a research question, returning false when it was not linked
+    pub fn unlink_question_task(conn: &Connection, question_id: &str, task_id: &str) -> AppResult<bool> {
+        let affected = conn.execute(
+            "DELETE FROM research_question_tasks WHERE question_id = ?1 AND task_id = ?2",
+            params![question_id, task_id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Get the IDs of notes and tasks linked to a research question
+    pub fn get_question_links(conn: &Connection, question_id: &str) -> AppResult<ResearchQuestionLinks> {
+        let mut note_stmt = conn.prepare(
+            "SELECT note_id FROM research_question_notes WHERE question_id = ?1 ORDER BY note_id"
+        )?;
+        let note_ids = note_stmt.query_map(params![question_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut task_stmt = conn.prepare(
+            "SELECT task_id FROM research_question_tasks WHERE question_id = ?1 ORDER BY task_id"
+        )?;
+        let task_ids = task_stmt.query_map(params![question_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(ResearchQuestionLinks { note_ids, task_ids })
+    }
+
+    /// Count research questions of a project per status
+    pub fn count_research_questions_by_status(conn: &Connection, project_id: &str) -> AppResult<HashMap<String, i64>> {
+        Self::count_by_status(conn, "research_questions", project_id)
+    }
+
+    /// Count tasks of a project per status
+    pub fn count_tasks_by_status(conn: &Connection, project_id: &str) -> AppResult<HashMap<String, i64>> {
+        Self::count_by_status(conn, "tasks", project_id)
+    }
+
+    /// Count notes of a project
+    pub fn count_notes(conn: &Connection, project_id: &str) -> AppResult<i64> {
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM notes WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    fn count_by_status(conn: &Connection, table: &str, project_id: &str) -> AppResult<HashMap<String, i64>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT status, COUNT(*) FROM {} WHERE project_id = ?1 GROUP BY status",
+            table
+        ))?;
+
+        let counts = stmt.query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(counts)
+    }
+
+    /// Compute statistics for one project, or for every project when `project_id` is None.
+    /// Each figure is a single grouped query over all requested projects.
+    pub fn get_project_stats(
+        conn: &Connection,
+        project_id: Option<&str>,
+        now: i64,
+        top_tag_limit: i64,
+    ) -> AppResult<HashMap<String, ProjectStats>> {
+        let mut stats: HashMap<String, ProjectStats> = HashMap::new();
+
+        let mut stmt = conn.prepare("SELECT id FROM projects WHERE ?1 IS NULL OR id = ?1")?;
+        let ids: Vec<String> = stmt.query_map(params![project_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for id in ids {
+            stats.insert(id.clone(), ProjectStats { project_id: id, ..Default::default() });
+        }
+
+        // Tasks in the last status of their project's workflow, for the completion percentage
+        let mut done_counts: HashMap<String, i64> = HashMap::new();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT project_id, status, COUNT(*), MAX(updated_at), status = {} FROM tasks
+             WHERE (?1 IS NULL OR project_id = ?1) AND NOT is_archived
+             GROUP BY project_id, status",
+            done_status_sql("tasks.project_id")
+        ))?;
+        let rows: Vec<(String, String, i64, i64, bool)> = stmt
+            .query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for (id, status, count, updated_at, is_done) in rows {
+            if let Some(entry) = stats.get_mut(&id) {
+                entry.task_counts.insert(status, count);
+                entry.last_activity_at = entry.last_activity_at.max(Some(updated_at));
+                if is_done {
+                    *done_counts.entry(id).or_default() += count;
+                }
+            }
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT project_id, priority, COUNT(*) FROM tasks
+             WHERE (?1 IS NULL OR project_id = ?1) AND NOT is_archived
+             GROUP BY project_id, priority",
+        )?;
+        let rows: Vec<(String, String, i64)> = stmt
+            .query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for (id, priority, count) in rows {
+            if let Some(entry) = stats.get_mut(&id) {
+                entry.priority_counts.insert(priority, count);
+            }
+        }
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT project_id, COUNT(*) FROM tasks
+             WHERE (?1 IS NULL OR project_id = ?1)
+               AND status != {} AND due_date IS NOT NULL AND due_date < ?2
+             GROUP BY project_id",
+            done_status_sql("tasks.project_id")
+        ))?;
+        let rows: Vec<(String, i64)> = stmt
+            .query_map(params![project_id, now], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for (id, count) in rows {
+            if let Some(entry) = stats.get_mut(&id) {
+                entry.overdue_tasks = count;
+            }
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT project_id, COUNT(*), COALESCE(SUM(is_pinned), 0), MAX(updated_at) FROM notes
+             WHERE ?1 IS NULL OR project_id = ?1
+             GROUP BY project_id",
+        )?;
+        let rows: Vec<(String, i64, i64, i64)> = stmt
+            .query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for (id, count, pinned, updated_at) in rows {
+            if let Some(entry) = stats.get_mut(&id) {
+                entry.note_count = count;
+                entry.pinned_note_count = pinned;
+                entry.last_activity_at = entry.last_activity_at.max(Some(updated_at));
+            }
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT project_id, name, uses FROM (
+                SELECT u.project_id, t.name, COUNT(*) AS uses,
+                       ROW_NUMBER() OVER (
+                           PARTITION BY u.project_id ORDER BY COUNT(*) DESC, t.name COLLATE NOCASE
+                       ) AS position
+                FROM (
+                    SELECT k.project_id, tt.tag_id FROM task_tags tt JOIN tasks k ON k.id = tt.task_id
+                    UNION ALL
+                    SELECT n.project_id, nt.tag_id FROM note_tags nt JOIN notes n ON n.id = nt.note_id
+                ) u
+                JOIN tags t ON t.id = u.tag_id
+                WHERE ?1 IS NULL OR u.project_id = ?1
+                GROUP BY u.project_id, t.id
+             )
+             WHERE position <= ?2
+             ORDER BY project_id, position",
+        )?;
+        let rows: Vec<(String, String, i64)> = stmt
+            .query_map(params![project_id, top_tag_limit], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for (id, name, count) in rows {
+            if let Some(entry) = stats.get_mut(&id) {
+                entry.top_tags.push(TagCount { name, count });
+            }
+        }
+
+        for (id, entry) in stats.iter_mut() {
+            let total: i64 = entry.task_counts.values().sum();
+            let done = done_counts.get(id).copied().unwrap_or(0);
+            if total > 0 {
+                entry.completion_percentage = done as f64 * 100.0 / total as f64;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    // ==========================================
+    // Reference Operations
+    // ==========================================
+
+    /// Insert a reference, or overwrite every field of the one with the same ID
+    pub fn upsert_reference(conn: &Connection, reference: &Reference) -> AppResult<()> {
+        conn.prepare_cached(
+            "INSERT INTO \"references\" (id, project_id, citation_key, entry_type, title, authors, year,
+                venue, doi, url, abstract, pdf_path, created_at, reading_status, tags)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+             ON CONFLICT(id) DO UPDATE SET
+                citation_key = excluded.citation_key,
+                entry_type = excluded.entry_type,
+                title = excluded.title,
+                authors = excluded.authors,
+                year = excluded.year,
+                venue = excluded.venue,
+                doi = excluded.doi,
+                url = excluded.url,
+                abstract = excluded.abstract,
+                pdf_path = excluded.pdf_path,
+                reading_status = excluded.reading_status,
+                tags = excluded.tags"
+        )?
+        .execute(params![
+            reference.id,
+            reference.project_id,
+            reference.citation_key,
+            reference.entry_type,
+            reference.title,
+            serde_json::to_string(&reference.authors).unwrap_or_default(),
+            reference.year,
+            reference.venue,
+            reference.doi,
+            reference.url,
+            reference.abstract_text,
+            reference.pdf_path,
+            reference.created_at,
+            reference.reading_status,
+            reference.tags.as_ref().map(|tags| serde_json::to_string(tags).unwrap_or_default()),
+        ])?;
+        Ok(())
+    }
+
+    /// Get reference by ID
+    pub fn get_reference_by_id(conn: &Connection, id: &str) -> AppResult<Option<Reference>> {
+        let reference = conn.query_row(
+            &format!("SELECT {} FROM \"references\" r WHERE r.id = ?1", REFERENCE_COLUMNS),
+            params![id],
+            |row| Ok(Self::row_to_reference(row)),
+        ).optional()?;
+        Ok(reference)
+    }
+
+    /// Get the references of a project, ordered by citation key
+    pub fn get_references_by_project(conn: &Connection, project_id: &str) -> AppResult<Vec<Reference>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM \"references\" r WHERE r.project_id = ?1 ORDER BY r.citation_key COLLATE NOCASE",
+            REFERENCE_COLUMNS
+        ))?;
+        let references = stmt.query_map(params![project_id], |row| Ok(Self::row_to_reference(row)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(references)
+    }
+
+    /// Find a reference of a project by DOI or, failing that, by citation key
+    pub fn find_duplicate_reference(
+        conn: &Connection,
+        project_id: &str,
+        doi: Option<&str>,
+        citation_key: &str,
+    ) -> AppResult<Option<Reference>> {
+        let reference = conn.prepare_cached(&format!(
+            "SELECT {} FROM \"references\" r
+             WHERE r.project_id = ?1 AND ((?2 IS NOT NULL AND r.doi = ?2) OR r.citation_key = ?3)
+             ORDER BY r.doi = ?2 DESC
+             LIMIT 1",
+            REFERENCE_COLUMNS
+        ))?
+        .query_row(params![project_id, doi, citation_key], |row| Ok(Self::row_to_reference(row)))
+        .optional()?;
+        Ok(reference)
+    }
+
+    /// Whether a citation key is taken in a project
+    pub fn citation_key_exists(conn: &Connection, project_id: &str, citation_key: &str) -> AppResult<bool> {
+        let exists = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM \"references\" WHERE project_id = ?1 AND citation_key = ?2)",
+            params![project_id, citation_key],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// Delete a reference and its citations, returning false when it does not exist
+    pub fn delete_reference(conn: &Connection, id: &str) -> AppResult<bool> {
+        Self::with_tx(conn, |conn| {
+            // Clear citations explicitly in case foreign keys are off for this connection
+            conn.execute("DELETE FROM note_references WHERE reference_id = ?1", params![id])?;
+            let affected = conn.execute("DELETE FROM \"references\" WHERE id = ?1", params![id])?;
+            Ok(affected > 0)
+        })
+    }
+
+    /// Save `keep` and fold the references of `remove_ids` into it in one
+    /// transaction: notes citing them cite `keep` instead and they are deleted
+    pub fn merge_references(conn: &Connection, keep: &Reference, remove_ids: &[String]) -> AppResult<()> {
+        Self::with_tx(conn, |conn| {
+            for id in remove_ids {
+                conn.execute(
+                    "INSERT OR IGNORE INTO note_references (note_id, reference_id, created_at)
+                     SELECT note_id, ?1, created_at FROM note_references WHERE reference_id = ?2",
+                    params![keep.id, id],
+                )?;
+                conn.execute("DELETE FROM note_references WHERE reference_id = ?1", params![id])?;
+                conn.execute("DELETE FROM \"references\" WHERE id = ?1", params![id])?;
+            }
+            Self::upsert_reference(conn, keep)
+        })
+    }
+
+    /// Record that a note cites a reference (no-op when it already does)
+    pub fn cite_reference(conn: &Connection, note_id: &str, reference_id: &str) -> AppResult<()> {
+        conn.execute(
+            "INSERT OR IGNORE INTO note_references (note_id, reference_id, created_at) VALUES (?1, ?2, ?3)",
+            params![note_id, reference_id, chrono::Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a citation, returning false when the note did not cite the reference
+    pub fn uncite_reference(conn: &Connection, note_id: &str, reference_id: &str) -> AppResult<bool> {
+        let affected = conn.execute(
+            "DELETE FROM note_references WHERE note_id = ?1 AND reference_id = ?2",
+            params![note_id, reference_id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Get the references a note cites, in the order they were cited
+    pub fn get_note_references(conn: &Connection, note_id: &str) -> AppResult<Vec<Reference>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM note_references nr
+             JOIN \"references\" r ON r.id = nr.reference_id
+             WHERE nr.note_id = ?1
+             ORDER BY nr.created_at, r.citation_key",
+            REFERENCE_COLUMNS
+        ))?;
+        let references = stmt.query_map(params![note_id], |row| Ok(Self::row_to_reference(row)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(references)
+    }
+
+    // ==========================================
+    // Diagnostics Operations
+    // ==========================================
+
+    /// Report effective pragmas, file sizes and page statistics of the main database
+    pub fn get_db_info(conn: &Connection) -> AppResult<DbInfo> {
+        fn pragma<T: rusqlite::types::FromSql>(conn: &Connection, name: &str) -> AppResult<T> {
+            Ok(conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))?)
+        }
+
+        let path: String = conn.query_row(
+            "SELECT file FROM pragma_database_list WHERE name = 'main'",
+            [],
+            |row| row.get(0),
+        )?;
+        let file_size = |p: &str| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+
+        let synchronous = match pragma::<i64>(conn, "synchronous")? {
+            0 => "off",
+            1 => "normal",
+            2 => "full",
+            _ => "extra",
+        };
+
+        Ok(DbInfo {
+            file_size_bytes: file_size(&path),
+            wal_size_bytes: file_size(&format!("{}-wal", path)),
+            path,
+            journal_mode: pragma(conn, "journal_mode")?,
+            synchronous: synchronous.to_string(),
+            busy_timeout_ms: pragma(conn, "busy_timeout")?,
+            foreign_keys: pragma::<i64>(conn, "foreign_keys")? != 0,
+            cache_size: pragma(conn, "cache_size")?,
+            page_size: pragma(conn, "page_size")?,
+            page_count: pragma(conn, "page_count")?,
+            freelist_count: pragma(conn, "freelist_count")?,
+        })
+    }
+
+    /// Run `PRAGMA integrity_check`, or the faster `quick_check` that skips
+    /// matching indexes against their tables. Returns the problems found; empty means ok.
+    pub fn integrity_check(conn: &Connection, quick: bool) -> AppResult<Vec<String>> {
+        let pragma = if quick { "PRAGMA quick_check" } else { "PRAGMA integrity_check" };
+        let mut stmt = conn.prepare(pragma)?;
+        let problems: Vec<String> = stmt.query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .filter(|line: &String| line != "ok")
+            .collect();
+        Ok(problems)
+    }
+
+    /// Run `PRAGMA foreign_key_check`, describing each row that references a missing parent
+    pub fn foreign_key_check(conn: &Connection) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+        let problems = stmt.query_map([], |row| {
+            let table: String = row.get(0)?;
+            let rowid: Option<i64> = row.get(1)?;
+            let parent: String = row.get(2)?;
+            Ok(match rowid {
+                Some(rowid) => format!("{} row {} references a missing row in {}", table, rowid, parent),
+                None => format!("A {} row references a missing row in {}", table, parent),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+        Ok(problems)
+    }
+
+    /// Size of the database in bytes as page count times page size
+    pub fn database_size(conn: &Connection) -> AppResult<i64> {
+        let size = conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(size)
+    }
+
+    /// Rebuild the database file without free pages, then refresh the query planner statistics
+    pub fn vacuum_and_analyze(conn: &Connection) -> AppResult<()> {
+        conn.execute_batch("VACUUM; ANALYZE;")?;
+        Ok(())
+    }
+
+    /// Write a consistent copy of the main database to `path`, which must not exist yet.
+    /// Other connections can keep reading and writing while it runs.
+    pub fn backup_into(conn: &Connection, path: &str) -> AppResult<()> {
+        conn.execute("VACUUM INTO ?1", params![path])?;
+        Ok(())
+    }
+
+    /// Copy the write-ahead log back into the database and truncate it
+    pub fn checkpoint(conn: &Connection) -> AppResult<CheckpointResult> {
+        let result = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+            Ok(CheckpointResult {
+                busy: row.get::<_, i64>(0)? != 0,
+                log_frames: row.get(1)?,
+                checkpointed_frames: row.get(2)?,
+            })
+        })?;
+        Ok(result)
+    }
+
+    // ==========================================
+    // Orphan Operations
+    // ==========================================
+
+    /// Find tasks, notes and deadlines whose project no longer exists.
+    /// Such rows survive when a project is deleted with foreign keys disabled.
+    pub fn find_orphans(conn: &Connection) -> AppResult<OrphanReport> {
+        fn orphans(conn: &Connection, kind: &str, query: &str) -> AppResult<Vec<OrphanedEntity>> {
+            let mut stmt = conn.prepare(query)?;
+            let rows = stmt.query_map([], |row| {
+                Ok(OrphanedEntity {
+                    id: row.get(0)?,
+                    kind: kind.to_string(),
+                    project_id: row.get(1)?,
+                    title: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+            Ok(rows)
+        }
+
+        Ok(OrphanReport {
+            tasks: orphans(
+                conn,
+                "task",
+                "SELECT id, project_id, title FROM tasks t
+                 WHERE NOT EXISTS (SELECT 1 FROM projects p WHERE p.id = t.project_id)",
+            )?,
+            notes: orphans(
+                conn,
+                "note",
+                "SELECT id, project_id, title FROM notes n
+                 WHERE n.project_id IS NOT NULL
+                   AND NOT EXISTS (SELECT 1 FROM projects p WHERE p.id = n.project_id)",
+            )?,
+            deadlines: orphans(
+                conn,
+                "deadline",
+                "SELECT id, project_id, name FROM deadlines d
+                 WHERE d.project_id IS NOT NULL
+                   AND NOT EXISTS (SELECT 1 FROM projects p WHERE p.id = d.project_id)",
+            )?,
+        })
+    }
+
+    /// Move every orphaned row into the target project. Adopted tasks get new keys.
+    pub fn adopt_orphans(conn: &Connection, target_project_id: &str) -> AppResult<usize> {
+        let report = Self::find_orphans(conn)?;
+        let tx = conn.unchecked_transaction()?;
+        let now = chrono::Utc::now().timestamp();
+
+        for task in &report.tasks {
+            let task_key = Self::allocate_task_key(&tx, target_project_id)?;
+            tx.execute(
+                "UPDATE tasks SET project_id = ?1, task_key = ?2, rank = NULL, board_order = NULL, updated_at = MAX(?3, updated_at + 1) WHERE id = ?4",
+                params![target_project_id, task_key, now, task.id],
+            )?;
+        }
+        for note in &report.notes {
+            tx.execute(
+                "UPDATE notes SET project_id = ?1, updated_at = MAX(?2, updated_at + 1) WHERE id = ?3",
+                params![target_project_id, now, note.id],
+            )?;
+        }
+        if !report.notes.is_empty() {
+            Self::refresh_note_links(&tx, target_project_id)?;
+        }
+        for deadline in &report.deadlines {
+            tx.execute(
+                "UPDATE deadlines SET project_id = ?1 WHERE id = ?2",
+                params![target_project_id, deadline.id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(report.total())
+    }
+
+    /// Delete every orphaned row
+    pub fn purge_orphans(conn: &Connection) -> AppResult<usize> {
+        let tx = conn.unchecked_transaction()?;
+        let mut purged = 0;
+        purged += tx.execute(
+            "DELETE FROM tasks WHERE NOT EXISTS (SELECT 1 FROM projects p WHERE p.id = tasks.project_id)",
+            [],
+        )?;
+        purged += tx.execute(
+            "DELETE FROM notes WHERE project_id IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM projects p WHERE p.id = notes.project_id)",
+            [],
+        )?;
+        purged += tx.execute(
+            "DELETE FROM deadlines WHERE project_id IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM projects p WHERE p.id = deadlines.project_id)",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(purged)
+    }
+
+    /// Find rows left inconsistent by edits made outside the app and, unless
+    /// `dry_run`, repair them: subtasks of missing parents move to the root,
+    /// junction rows and indexed files pointing at missing rows are deleted, and
+    /// completed_at is made to agree with the status. Every repair runs in one
+    /// transaction, so a failing step leaves the database untouched.
+    pub fn repair_database(conn: &Connection, dry_run: bool) -> AppResult<RepairReport> {
+        let tx = conn.unchecked_transaction()?;
+        let now = chrono::Utc::now().timestamp();
+        let mut findings = Vec::new();
+        let mut repaired = 0;
+
+        let orphaned_subtasks: Vec<(String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, parent_id FROM tasks t
+                 WHERE parent_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM tasks p WHERE p.id = t.parent_id)
+                 ORDER BY id",
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+        for (id, parent_id) in orphaned_subtasks {
+            if !dry_run {
+                repaired += tx.execute(
+                    "UPDATE tasks SET parent_id = NULL, updated_at = MAX(?1, updated_at + 1) WHERE id = ?2",
+                    params![now, id],
+                )?;
+            }
+            findings.push(RepairFinding {
+                kind: RepairKind::OrphanedSubtask,
+                table: "tasks".into(),
+                row: id,
+                problem: format!("Parent task {} does not exist", parent_id),
+                action: "Move the task to the root of its project".into(),
+            });
+        }
+
+        // Each junction table with its two key columns and the tables they reference
+        let mut junctions: Vec<(&str, [(&str, &str); 2])> = [EntityType::Project, EntityType::Task, EntityType::Note]
+            .into_iter()
+            .map(|entity| (entity.tag_table(), [(entity.tag_key(), entity.table()), ("tag_id", "tags")]))
+            .collect();
+        junctions.extend([
+            ("research_question_notes", [("question_id", "research_questions"), ("note_id", "notes")]),
+            ("research_question_tasks", [("question_id", "research_questions"), ("task_id", "tasks")]),
+            ("note_references", [("note_id", "notes"), ("reference_id", "\"references\"")]),
+        ]);
+        for (junction, [(first, first_table), (second, second_table)]) in junctions {
+            let dangling: Vec<(String, String, bool, bool)> = {
+                let mut stmt = tx.prepare(&format!(
+                    "SELECT * FROM (
+                        SELECT j.{first}, j.{second},
+                               EXISTS(SELECT 1 FROM {first_table} a WHERE a.id = j.{first}) AS has_first,
+                               EXISTS(SELECT 1 FROM {second_table} b WHERE b.id = j.{second}) AS has_second
+                        FROM {junction} j
+                     ) WHERE NOT has_first OR NOT has_second
+                     ORDER BY 1, 2",
+                ))?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+                rows.filter_map(|r| r.ok()).collect()
+            };
+            for (first_id, second_id, has_first, has_second) in dangling {
+                if !dry_run {
+                    repaired += tx.execute(
+                        &format!("DELETE FROM {} WHERE {} = ?1 AND {} = ?2", junction, first, second),
+                        params![first_id, second_id],
+                    )?;
+                }
+                let missing: Vec<String> = [(has_first, first, &first_id), (has_second, second, &second_id)]
+                    .into_iter()
+                    .filter(|(exists, _, _)| !exists)
+                    .map(|(_, column, id)| format!("{} {}", column, id))
+                    .collect();
+                findings.push(RepairFinding {
+                    kind: RepairKind::DanglingJunctionRow,
+                    table: junction.to_string(),
+                    row: format!("{}:{}", first_id, second_id),
+                    problem: format!("Points at missing {}", missing.join(" and ")),
+                    action: "Delete the row".into(),
+                });
+            }
+        }
+
+        let orphaned_files: Vec<(String, String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, project_id, relative_path FROM file_metadata f
+                 WHERE NOT EXISTS (SELECT 1 FROM projects p WHERE p.id = f.project_id)
+                 ORDER BY id",
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+        for (id, project_id, relative_path) in orphaned_files {
+            if !dry_run {
+                repaired += tx.execute("DELETE FROM file_metadata WHERE id = ?1", params![id])?;
+            }
+            findings.push(RepairFinding {
+                kind: RepairKind::OrphanedFileMetadata,
+                table: "file_metadata".into(),
+                row: id,
+                problem: format!("Project {} of {} does not exist", project_id, relative_path),
+                action: "Delete the row".into(),
+            });
+        }
+
+        // Status is what the user sees, so completed_at follows it: cleared on
+        // open tasks, and taken from the last update on done tasks missing it
+        let mismatched: Vec<(String, String, Option<i64>)> = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT id, status, completed_at FROM (
+                    SELECT id, status, completed_at, status = {} AS is_done FROM tasks
+                 )
+                 WHERE (NOT is_done AND completed_at IS NOT NULL) OR (is_done AND completed_at IS NULL)
+                 ORDER BY id",
+                done_status_sql("tasks.project_id")
+            ))?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+        for (id, status, completed_at) in mismatched {
+            let (problem, action) = if completed_at.is_some() {
+                if !dry_run {
+                    repaired += tx.execute(
+                        "UPDATE tasks SET completed_at = NULL, updated_at = MAX(?1, updated_at + 1) WHERE id = ?2",
+                        params![now, id],
+                    )?;
+                }
+                (format!("Has completed_at but status is {}", status), "Clear completed_at")
+            } else {
+                if !dry_run {
+                    repaired += tx.execute(
+                        "UPDATE tasks SET completed_at = updated_at, updated_at = MAX(?1, updated_at + 1) WHERE id = ?2",
+                        params![now, id],
+                    )?;
+                }
+                ("Is done but has no completed_at".to_string(), "Set completed_at to the task's last update")
+            };
+            findings.push(RepairFinding {
+                kind: RepairKind::CompletionMismatch,
+                table: "tasks".into(),
+                row: id,
+                problem,
+                action: action.into(),
+            });
+        }
+
+        if !dry_run {
+            tx.commit()?;
+        }
+        Ok(RepairReport { dry_run, findings, repaired })
+    }
+
+    // ==========================================
+    // Metadata Operations
+    // ==========================================
+
+    /// Get the raw metadata JSON of an entity. Returns None when the entity does not exist.
+    pub fn get_entity_metadata(conn: &Connection, entity: EntityType, id: &str) -> AppResult<Option<Option<String>>> {
+        let metadata = conn
+            .query_row(
+                &format!("SELECT metadata FROM {} WHERE id = ?1", entity.table()),
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(metadata)
+    }
+
+    /// Parsed metadata of every task or note of a project, by entity ID. Entities
+    /// without metadata are left out.
+    pub fn get_project_entity_metadata(conn: &Connection, entity: EntityType, project_id: &str) -> AppResult<HashMap<String, Value>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, metadata FROM {} WHERE project_id = ?1 AND metadata IS NOT NULL",
+            entity.table()
+        ))?;
+
+        let metadata = stmt.query_map(params![project_id], |row| {
+            Ok((row.get::<_, String>(0)?, Self::row_metadata(row)))
+        })?
+        .filter_map(|r| r.ok())
+        .filter_map(|(id, metadata)| metadata.map(|metadata| (id, metadata)))
+        .collect();
+
+        Ok(metadata)
+    }
+
+    /// Store the metadata JSON of an entity, returning false when the entity does not exist
+    pub fn set_entity_metadata(conn: &Connection, entity: EntityType, id: &str, metadata: &str) -> AppResult<bool> {
+        let updated = conn.execute(
+            &format!("UPDATE {} SET metadata = ?1 WHERE id = ?2", entity.table()),
+            params![metadata, id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Project an entity belongs to: the project's own ID for projects and None
+    /// for inbox notes or entities that do not exist
+    pub fn get_entity_project_id(conn: &Connection, entity: EntityType, id: &str) -> AppResult<Option<String>> {
+        let column = match entity {
+            EntityType::Project => "id",
+            EntityType::Task | EntityType::Note => "project_id",
+        };
+        let project_id = conn
+            .query_row(
+                &format!("SELECT {} FROM {} WHERE id = ?1", column, entity.table()),
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(project_id.flatten())
+    }
+
+    // ==========================================
+    // Archive Operations
+    // ==========================================
+
+    /// Check whether a project is registered at the given path
+    pub fn project_path_exists(conn: &Connection, path: &str) -> AppResult<bool> {
+        let exists = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM projects WHERE path = ?1)",
+            params![path],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// Insert an archived project with its statuses, tasks, notes, tags and
+    /// metadata in one transaction. IDs must already be fresh and every task
+    /// must carry a key; `next_task_number` continues the project's key sequence.
+    pub fn insert_project_archive(conn: &Connection, archive: &ProjectArchive, next_task_number: i64) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        let project = &archive.project;
+
+        Self::insert_project_row(&tx, project)?;
+        Self::record_activity(&tx, EntityType::Project, &project.id, ActivityAction::Created)?;
+        tx.execute(
+            "UPDATE projects SET next_task_number = ?1 WHERE id = ?2",
+            params![next_task_number, project.id],
+        )?;
+        if let Some(metadata) = &project.metadata {
+            Self::set_entity_metadata(&tx, EntityType::Project, &project.id, &metadata.to_string())?;
+        }
+
+        for (position, name) in archive.statuses.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO project_statuses (project_id, name, position) VALUES (?1, ?2, ?3)",
+                params![project.id, name, position as i64],
+            )?;
+        }
+
+        for task in &archive.tasks {
+            Self::insert_task(&tx, task)?;
+            if task.rank.is_some() {
+                tx.execute("UPDATE tasks SET rank = ?1 WHERE id = ?2", params![task.rank, task.id])?;
+            }
+            if let Some(metadata) = &task.metadata {
+                Self::set_entity_metadata(&tx, EntityType::Task, &task.id, &metadata.to_string())?;
+            }
+        }
+
+        for note in &archive.notes {
+            Self::insert_note_row(&tx, note)?;
+            if let Some(metadata) = &note.metadata {
+                Self::set_entity_metadata(&tx, EntityType::Note, &note.id, &metadata.to_string())?;
+            }
+        }
+        Self::refresh_note_links(&tx, &project.id)?;
+
+        // Tags were created on demand above; keep archived colors unless one is already set
+        for tag in &archive.tags {
+            if let Some(color) = &tag.color {
+                tx.execute(
+                    "UPDATE tags SET color = ?1 WHERE name = ?2 COLLATE NOCASE AND color IS NULL",
+                    params![color, tag.name],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ==========================================
+    // Trash Operations
+    // ==========================================
+
+    /// Copy a task and its descendants to the trash; callers own the transaction
+    fn trash_task_subtree(conn: &Connection, id: &str) -> AppResult<()> {
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1
+                UNION
+                SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+             )
+             SELECT {}, metadata FROM tasks WHERE id IN (SELECT id FROM subtree)",
+            TASK_COLUMNS
+        ))?;
+        let tasks: Vec<Task> = stmt.query_map(params![id], |row| {
+            let mut task = Self::row_to_task(row);
+            task.metadata = Self::row_metadata(row);
+            Ok(task)
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        for task in &tasks {
+            let payload = serde_json::to_string(task)?;
+            Self::insert_trash_entry(
+                conn,
+                EntityType::Task,
+                &task.id,
+                &task.project_id,
+                &task.title,
+                task.parent_id.as_deref(),
+                &payload,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn insert_trash_entry(
+        conn: &Connection,
+        entity: EntityType,
+        id: &str,
+        project_id: &str,
+        title: &str,
+        parent_id: Option<&str>,
+        payload: &str,
+    ) -> AppResult<()> {
+        let now = chrono::Utc::now().timestamp();
+        conn.execute(
+            "INSERT OR REPLACE INTO trash (entity_type, id, project_id, title, parent_id, payload, deleted_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![entity, id, project_id, title, parent_id, payload, now],
+        )?;
+        Ok(())
+    }
+
+    /// List a project's trashed tasks and notes, most recently deleted first
+    pub fn list_trash(conn: &Connection, project_id: &str) -> AppResult<Vec<TrashEntry>> {
+        let mut stmt = conn.prepare(
+            "SELECT entity_type, id, project_id, title, parent_id, deleted_at FROM trash
+             WHERE project_id = ?1 ORDER BY deleted_at DESC, title ASC",
+        )?;
+        let entries = stmt.query_map(params![project_id], Self::row_to_trash_entry)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(entries)
+    }
+
+    /// Put a trashed task or note back, returning None when it is not in the trash.
+    /// A task whose parent is gone is restored as a root task, as the last of its
+    /// siblings; it keeps its key unless another task took it in the meantime.
+    pub fn restore_from_trash(conn: &Connection, entity: EntityType, id: &str) -> AppResult<Option<TrashEntry>> {
+        let tx = conn.unchecked_transaction()?;
+        let trashed = tx
+            .query_row(
+                "SELECT entity_type, id, project_id, title, parent_id, deleted_at, payload FROM trash
+                 WHERE entity_type = ?1 AND id = ?2",
+                params![entity, id],
+                |row| Ok((Self::row_to_trash_entry(row)?, row.get::<_, String>(6)?)),
+            )
+            .optional()?;
+        let Some((entry, payload)) = trashed else {
+            return Ok(None);
+        };
+
+        let exists: bool = tx.query_row(
+            &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?1)", entity.table()),
+            params![id],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(AppError::Conflict(format!("{} {} already exists", entity.label(), id)));
+        }
+
+        match entity {
+            EntityType::Task => {
+                let mut task: Task = serde_json::from_str(&payload)?;
+                Self::restore_task_row(&tx, &mut task)?;
+            }
+            EntityType::Note => {
+                let mut payload: Value = serde_json::from_str(&payload)?;
+                let attachments: Vec<NoteAttachment> = match payload.get_mut("attachments").map(Value::take) {
+                    Some(attachments) => serde_json::from_value(attachments)?,
+                    None => Vec::new(),
+                };
+                let note: Note = serde_json::from_value(payload)?;
+                Self::insert_note_row(&tx, &note)?;
+                if let Some(metadata) = &note.metadata {
+                    Self::set_entity_metadata(&tx, EntityType::Note, &note.id, &metadata.to_string())?;
+                }
+                for attachment in &attachments {
+                    Self::insert_note_attachment(&tx, attachment)?;
+                }
+                if let Some(project_id) = note.project_id.as_deref() {
+                    if note.is_pinned {
+                        Self::renumber_pinned_notes(&tx, project_id)?;
+                    }
+                    Self::refresh_note_links(&tx, project_id)?;
+                }
+            }
+            EntityType::Project => return Ok(None),
+        }
+
+        tx.execute("DELETE FROM trash WHERE entity_type = ?1 AND id = ?2", params![entity, id])?;
+        tx.commit()?;
+        Ok(Some(entry))
+    }
+
+    /// Insert a task taken out of the trash; callers own the transaction
+    fn restore_task_row(conn: &Connection, task: &mut Task) -> AppResult<()> {
+        if let Some(parent_id) = task.parent_id.as_deref() {
+            let parent_exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM tasks WHERE id = ?1 AND project_id = ?2)",
+                params![parent_id, task.project_id],
+                |row| row.get(0),
+            )?;
+            if !parent_exists {
+                task.parent_id = None;
+            }
+        }
+
+        let siblings = Self::sibling_task_ids(conn, &task.project_id, task.parent_id.as_deref())?;
+        task.order = siblings.len() as i32;
+
+        let key_taken: bool = match &task.task_key {
+            Some(key) => conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM tasks WHERE project_id = ?1 AND task_key = ?2)",
+                params![task.project_id, key],
+                |row| row.get(0),
+            )?,
+            None => true,
+        };
+        if key_taken {
+            task.task_key = Some(Self::allocate_task_key(conn, &task.project_id)?);
+        }
+
+        // The stack ranking moved on while the task was away; it comes back unranked
+        task.rank = None;
+        Self::insert_task(conn, task)?;
+        if let Some(metadata) = &task.metadata {
+            Self::set_entity_metadata(conn, EntityType::Task, &task.id, &metadata.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Permanently remove a project's trashed items, only those deleted before
+    /// `deleted_before` when given. Returns how many were removed.
+    pub fn empty_trash(conn: &Connection, project_id: &str, deleted_before: Option<i64>) -> AppResult<usize> {
+        let removed = conn.execute(
+            "DELETE FROM trash WHERE project_id = ?1 AND (?2 IS NULL OR deleted_at < ?2)",
+            params![project_id, deleted_before],
+        )?;
+        Ok(removed)
+    }
+
+    // ==========================================
+    // App Settings Operations
+    // ==========================================
+
+    /// Get the stored JSON value of an application setting
+    pub fn get_app_setting(conn: &Connection, key: &str) -> AppResult<Option<String>> {
+        let value = conn.query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(value)
+    }
+
+    /// Get every stored application setting as (key, JSON value) pairs
+    pub fn get_app_settings(conn: &Connection) -> AppResult<Vec<(String, String)>> {
+        let mut stmt = conn.prepare_cached("SELECT key, value FROM app_settings ORDER BY key")?;
+        let settings = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(settings)
+    }
+
+    /// Store the JSON value of an application setting, replacing any previous one
+    pub fn set_app_setting(conn: &Connection, key: &str, value: &str) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO app_settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![key, value, chrono::Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    // ==========================================
+    // File Index Operations
+    // ==========================================
+
+    /// Indexed paths of a project that are not flagged deleted, with their stored state
+    pub fn get_file_index(conn: &Connection, project_id: &str) -> AppResult<HashMap<String, FileIndexEntry>> {
+        let mut stmt = conn.prepare(
+            "SELECT relative_path, id, modified_at, file_size, last_indexed_at, is_ignored, ignore_override,
+                content_hash, content_changed_at
+             FROM file_metadata WHERE project_id = ?1 AND is_deleted = 0"
+        )?;
+
+        let index = stmt.query_map(params![project_id], |row| {
+            Ok((
+                row.get(0)?,
+                FileIndexEntry {
+                    id: row.get(1)?,
+                    modified_at: row.get(2)?,
+                    file_size: row.get(3)?,
+                    last_indexed_at: row.get(4)?,
+                    is_ignored: row.get(5)?,
+                    ignore_override: row.get(6)?,
+                    content_hash: row.get(7)?,
+                    content_changed_at: row.get(8)?,
+                },
+            ))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(index)
+    }
+
+    /// Upsert scanned files by (project_id, relative_path), store the new
+    /// modification times of `touched` files, given as (id, modified_at), whose
+    /// content hash did not change, and flag the given rows as deleted or
+    /// ignored, in one transaction. Content changes reach the full-text table
+    /// through triggers. A file that was flagged deleted counts as new again.
+    pub fn save_file_index(
+        conn: &Connection,
+        files: &[ScannedFile],
+        touched: &[(String, i64)],
+        deleted_ids: &[String],
+        ignored_ids: &[String],
+    ) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        let now = chrono::Utc::now().timestamp();
+
+        for ScannedFile { metadata: file, content } in files {
+            tx.execute(
+                "INSERT INTO file_metadata (id, project_id, relative_path, file_name, file_extension,
+                    file_size, mime_type, created_at, modified_at, last_indexed_at, is_deleted, is_ignored, content,
+                    content_hash, content_changed_at, first_indexed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 0, ?11, ?12, ?13, ?14, ?10)
+                 ON CONFLICT(project_id, relative_path) DO UPDATE SET
+                    file_name = excluded.file_name,
+                    file_extension = excluded.file_extension,
+                    file_size = excluded.file_size,
+                    mime_type = excluded.mime_type,
+                    modified_at = excluded.modified_at,
+                    last_indexed_at = excluded.last_indexed_at,
+                    first_indexed_at = CASE WHEN file_metadata.is_deleted
+                        THEN excluded.first_indexed_at ELSE file_metadata.first_indexed_at END,
+                    deleted_at = NULL,
+                    is_deleted = 0,
+                    is_ignored = excluded.is_ignored,
+                    content = excluded.content,
+                    content_hash = excluded.content_hash,
+                    content_changed_at = excluded.content_changed_at",
+                params![
+                    file.id,
+                    file.project_id,
+                    file.relative_path,
+                    file.file_name,
+                    file.file_extension,
+                    file.file_size,
+                    file.mime_type,
+                    file.created_at,
+                    file.modified_at,
+                    file.last_indexed_at,
+                    file.is_ignored,
+                    content,
+                    file.content_hash,
+                    file.content_changed_at,
+                ],
+            )?;
+        }
+
+        for (id, modified_at) in touched {
+            tx.execute("UPDATE file_metadata SET modified_at = ?1 WHERE id = ?2", params![modified_at, id])?;
+        }
+
+        for id in deleted_ids {
+            tx.execute(
+                "UPDATE file_metadata SET is_deleted = 1, deleted_at = ?1, content = NULL WHERE id = ?2",
+                params![now, id],
+            )?;
+        }
+
+        for id in ignored_ids {
+            tx.execute("UPDATE file_metadata SET is_ignored = 1, content = NULL WHERE id = ?1", params![id])?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get an indexed file by ID
+    pub fn get_file_by_id(conn: &Connection, id: &str) -> AppResult<Option<FileMetadata>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, relative_path, file_name, file_extension, file_size, mime_type,
+                created_at, modified_at, last_indexed_at, is_deleted, is_ignored, content_hash, content_changed_at
+             FROM file_metadata WHERE id = ?1"
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Self::row_to_file_metadata(row)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Flag a file ignored or not and remember the choice over ignore patterns.
+    /// Ignoring drops the stored content; un-ignoring marks the file for a
+    /// fresh read on the next index run.
+    pub fn set_file_ignored(conn: &Connection, id: &str, ignored: bool) -> AppResult<bool> {
+        let affected = conn.execute(
+            "UPDATE file_metadata SET
+                is_ignored = ?1,
+                ignore_override = ?1,
+                content = CASE WHEN ?1 THEN NULL ELSE content END,
+                last_indexed_at = CASE WHEN ?1 THEN last_indexed_at ELSE NULL END
+             WHERE id = ?2",
+            params![ignored, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Full-text search over the stored contents of a project's files. Every
+    /// term must match as a word prefix; results are ranked by bm25.
+    pub fn search_file_contents(conn: &Connection, project_id: &str, query: &str, limit: i64) -> AppResult<Vec<FileSearchResult>> {
+        let Some(fts_query) = text::fts_prefix_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.relative_path, f.file_name, f.content,
+                snippet(file_content_fts, 1, '', '', '…', 16)
+             FROM file_content_fts
+             JOIN file_metadata f ON f.id = file_content_fts.file_id
+             WHERE file_content_fts MATCH ?1
+               AND f.project_id = ?2 AND f.is_deleted = 0 AND f.is_ignored = 0
+             ORDER BY bm25(file_content_fts)
+             LIMIT ?3"
+        )?;
+
+        let results = stmt.query_map(params![fts_query, project_id, limit], |row| {
+            let content: Option<String> = row.get(3)?;
+            Ok(FileSearchResult {
+                file_id: row.get(0)?,
+                relative_path: row.get(1)?,
+                file_name: row.get(2)?,
+                snippet: row.get(4)?,
+                line_number: content.and_then(|content| text::first_matching_line(&content, query)),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(results)
+    }
+
+    /// Get the indexed files of a project that still exist, by path
+    pub fn get_project_files(conn: &Connection, project_id: &str) -> AppResult<Vec<FileMetadata>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, relative_path, file_name, file_extension, file_size, mime_type,
+                created_at, modified_at, last_indexed_at, is_deleted, is_ignored, content_hash, content_changed_at
+             FROM file_metadata WHERE project_id = ?1 AND is_deleted = 0
+             ORDER BY relative_path ASC"
+        )?;
+
+        let files = stmt.query_map(params![project_id], |row| {
+            Ok(Self::row_to_file_metadata(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(files)
+    }
+
+    /// Get the existing indexed files of a project below `dir` ("" for the root), by
+    /// path. Without `recursive` only the files directly in `dir` are returned.
+    pub fn get_files_in_dir(conn: &Connection, project_id: &str, dir: &str, recursive: bool) -> AppResult<Vec<FileMetadata>> {
+        let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, project_id, relative_path, file_name, file_extension, file_size, mime_type,
+                created_at, modified_at, last_indexed_at, is_deleted, is_ignored, content_hash, content_changed_at
+             FROM file_metadata
+             WHERE project_id = ?1 AND is_deleted = 0 AND relative_path LIKE ?2 ESCAPE '{}'
+             ORDER BY relative_path ASC",
+            text::LIKE_ESCAPE
+        ))?;
+
+        let files = stmt.query_map(params![project_id, text::like_prefix(&prefix)], |row| {
+            Ok(Self::row_to_file_metadata(row))
+        })?
+        .filter_map(|r| r.ok())
+        // LIKE ignores ASCII case, paths do not
+        .filter(|file: &FileMetadata| match file.relative_path.strip_prefix(&prefix) {
+            Some(rest) => recursive || !rest.contains('/'),
+            None => false,
+        })
+        .collect();
+
+        Ok(files)
+    }
+
+    /// Get an existing indexed file by its path relative to the project root
+    pub fn get_file_by_path(conn: &Connection, project_id: &str, relative_path: &str) -> AppResult<Option<FileMetadata>> {
+        let file = conn
+            .query_row(
+                "SELECT id, project_id, relative_path, file_name, file_extension, file_size, mime_type,
+                    created_at, modified_at, last_indexed_at, is_deleted, is_ignored, content_hash, content_changed_at
+                 FROM file_metadata WHERE project_id = ?1 AND relative_path = ?2 AND is_deleted = 0",
+                params![project_id, relative_path],
+                |row| Ok(Self::row_to_file_metadata(row)),
+            )
+            .optional()?;
+        Ok(file)
+    }
+
+    /// Get the largest existing indexed files of a project, biggest first
+    pub fn get_largest_files(conn: &Connection, project_id: &str, limit: i64) -> AppResult<Vec<FileMetadata>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, relative_path, file_name, file_extension, file_size, mime_type,
+                created_at, modified_at, last_indexed_at, is_deleted, is_ignored, content_hash, content_changed_at
+             FROM file_metadata
+             WHERE project_id = ?1 AND is_deleted = 0 AND file_size IS NOT NULL
+             ORDER BY file_size DESC, relative_path ASC
+             LIMIT ?2"
+        )?;
+
+        let files = stmt.query_map(params![project_id, limit], |row| {
+            Ok(Self::row_to_file_metadata(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(files)
+    }
+
+    /// Store re-read sizes and modification times of indexed files, given as
+    /// (id, size, modified_at), and flag vanished files deleted. Changed files
+    /// lose last_indexed_at so the next index run reads their content again.
+    pub fn refresh_file_stats(conn: &Connection, changed: &[(String, Option<i64>, i64)], deleted_ids: &[String]) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+
+        for (id, size, modified_at) in changed {
+            tx.execute(
+                "UPDATE file_metadata SET file_size = ?1, modified_at = ?2, last_indexed_at = NULL WHERE id = ?3",
+                params![size, modified_at, id],
+            )?;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        for id in deleted_ids {
+            tx.execute(
+                "UPDATE file_metadata SET is_deleted = 1, deleted_at = ?1, content = NULL WHERE id = ?2",
+                params![now, id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Existing indexed files of every project, ignored ones left out, that
+    /// are at least `min_size` bytes and share their size with another such
+    /// file, as (file, project name, project path), largest first
+    pub fn get_same_size_files(conn: &Connection, min_size: i64) -> AppResult<Vec<(FileMetadata, String, String)>> {
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.project_id, f.relative_path, f.file_name, f.file_extension, f.file_size, f.mime_type,
+                f.created_at, f.modified_at, f.last_indexed_at, f.is_deleted, f.is_ignored, f.content_hash,
+                f.content_changed_at, p.name, p.path
+             FROM file_metadata f
+             JOIN projects p ON p.id = f.project_id
+             WHERE f.is_deleted = 0 AND f.is_ignored = 0 AND f.file_size >= ?1
+                AND f.file_size IN (
+                    SELECT file_size FROM file_metadata
+                    WHERE is_deleted = 0 AND is_ignored = 0 AND file_size >= ?1
+                    GROUP BY file_size HAVING COUNT(*) > 1
+                )
+             ORDER BY f.file_size DESC, p.name ASC, f.relative_path ASC"
+        )?;
+
+        let files = stmt.query_map(params![min_size], |row| {
+            Ok((Self::row_to_file_metadata(row), row.get(14)?, row.get(15)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(files)
+    }
+
+    /// Store content hashes computed outside an index run, given as (id, hash,
+    /// modified_at, size). A hash is only kept while the row still has the
+    /// size and time it was computed for, so a re-index in between wins.
+    pub fn store_file_hashes(conn: &Connection, hashes: &[(String, String, i64, i64)]) -> AppResult<()> {
+        let tx = conn.unchecked_transaction()?;
+        for (id, hash, modified_at, size) in hashes {
+            tx.execute(
+                "UPDATE file_metadata SET content_hash = ?1
+                 WHERE id = ?2 AND modified_at = ?3 AND file_size = ?4 AND is_deleted = 0",
+                params![hash, id, modified_at, size],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Paths of a project's files, ignored ones left out, that were first
+    /// indexed, had their content change or were flagged deleted at or after
+    /// `since`. A file both added and changed counts as added; one added and
+    /// deleted again is not listed.
+    pub fn get_changed_files(conn: &Connection, project_id: &str, since: i64) -> AppResult<ChangedFiles> {
+        let mut stmt = conn.prepare(
+            "SELECT relative_path,
+                CASE
+                    WHEN is_deleted THEN 'deleted'
+                    WHEN first_indexed_at >= ?2 THEN 'added'
+                    ELSE 'modified'
+                END
+             FROM file_metadata
+             WHERE project_id = ?1 AND is_ignored = 0
+                AND CASE
+                    WHEN is_deleted THEN deleted_at >= ?2 AND first_indexed_at < ?2
+                    ELSE first_indexed_at >= ?2 OR content_changed_at >= ?2
+                END
+             ORDER BY relative_path ASC"
+        )?;
+
+        let mut changes = ChangedFiles { since, ..ChangedFiles::default() };
+        let rows = stmt.query_map(params![project_id, since], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (path, kind) = row?;
+            match kind.as_str() {
+                "added" => changes.added.push(path),
+                "deleted" => changes.deleted.push(path),
+                _ => changes.modified.push(path),
+            }
+        }
+
+        Ok(changes)
+    }
+
+    // ==========================================
+    // Deadline Operations
+    // ==========================================
+
+    /// Insert a deadline
+    pub fn insert_deadline(conn: &Connection, deadline: &Deadline) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO deadlines (id, project_id, name, date, url, notes, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                deadline.id,
+                deadline.project_id,
+                deadline.name,
+                deadline.date,
+                deadline.url,
+                deadline.notes,
+                deadline.created_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get deadline by ID
+    pub fn get_deadline_by_id(conn: &Connection, id: &str) -> AppResult<Option<Deadline>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, name, date, url, notes, created_at FROM deadlines WHERE id = ?1"
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Self::row_to_deadline(row)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// List deadlines ordered by date. With a project ID, global deadlines are included too.
+    pub fn get_deadlines(conn: &Connection, project_id: Option<&str>, from: Option<i64>, to: Option<i64>) -> AppResult<Vec<Deadline>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, name, date, url, notes, created_at FROM deadlines
+             WHERE (?1 IS NULL OR project_id = ?1 OR project_id IS NULL)
+               AND (?2 IS NULL OR date >= ?2)
+               AND (?3 IS NULL OR date <= ?3)
+             ORDER BY date ASC"
+        )?;
+
+        let deadlines = stmt.query_map(params![project_id, from, to], |row| {
+            Ok(Self::row_to_deadline(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(deadlines)
+    }
+
+    /// Update deadline fields that are provided
+    pub fn update_deadline(conn: &Connection, id: &str, name: Option<&str>, date: Option<i64>, url: Option<&str>, notes: Option<&str>) -> AppResult<bool> {
+        let affected = conn.execute(
+            "UPDATE deadlines SET
+                name = COALESCE(?1, name),
+                date = COALESCE(?2, date),
+                url = COALESCE(?3, url),
+                notes = COALESCE(?4, notes)
+             WHERE id = ?5",
+            params![name, date, url, notes, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Check whether a deadline with the given name exists (case-insensitive)
+    pub fn deadline_name_exists(conn: &Connection, name: &str) -> AppResult<bool> {
+        let exists = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM deadlines WHERE lower(name) = lower(?1))",
+            params![name],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// Delete deadline
+    pub fn delete_deadline(conn: &Connection, id: &str) -> AppResult<bool> {
+        let affected = conn.execute("DELETE FROM deadlines WHERE id = ?1", params![id])?;
+        Ok(affected > 0)
+    }
+
+    // ==========================================
+    // Note Template Operations
+    // ==========================================
+
+    /// Insert a note template
+    pub fn insert_note_template(conn: &Connection, template: &NoteTemplate) -> AppResult<()> {
+        let tags_json = template.tags.as_ref()
+            .map(|t| serde_json::to_string(t).unwrap_or_default());
+
+        conn.execute(
+            "INSERT INTO note_templates (id, name, title_pattern, content, tags, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![template.id, template.name, template.title_pattern, template.content, tags_json, template.created_at],
+        )?;
+        Ok(())
+    }
+
+    /// Get all note templates by name
+    pub fn get_note_templates(conn: &Connection) -> AppResult<Vec<NoteTemplate>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, title_pattern, content, tags, created_at FROM note_templates
+             ORDER BY name COLLATE NOCASE ASC"
+        )?;
+
+        let templates = stmt.query_map([], Self::row_to_note_template)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(templates)
+    }
+
+    /// Get a note template by ID
+    pub fn get_note_template_by_id(conn: &Connection, id: &str) -> AppResult<Option<NoteTemplate>> {
+        let template = conn.query_row(
+            "SELECT id, name, title_pattern, content, tags, created_at FROM note_templates WHERE id = ?1",
+            params![id],
+            Self::row_to_note_template,
+        ).optional()?;
+        Ok(template)
+    }
+
+    /// Delete a note template; false when it does not exist
+    pub fn delete_note_template(conn: &Connection, id: &str) -> AppResult<bool> {
+        let affected = conn.execute("DELETE FROM note_templates WHERE id = ?1", params![id])?;
+        Ok(affected > 0)
+    }
+
+    // ==========================================
+    // Project Template Operations
+    // ==========================================
+
+    /// Insert a project template
+    pub fn insert_project_template(conn: &Connection, template: &ProjectTemplate) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO project_templates (id, name, description, tasks, notes, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                template.id,
+                template.name,
+                template.description,
+                serde_json::to_string(&template.tasks)?,
+                serde_json::to_string(&template.notes)?,
+                template.created_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get all project templates by name
+    pub fn get_project_templates(conn: &Connection) -> AppResult<Vec<ProjectTemplate>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, description, tasks, notes, created_at FROM project_templates
+             ORDER BY name COLLATE NOCASE ASC"
+        )?;
+
+        let templates = stmt.query_map([], Self::row_to_project_template)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(templates)
+    }
+
+    /// Get a project template by ID
+    pub fn get_project_template_by_id(conn: &Connection, id: &str) -> AppResult<Option<ProjectTemplate>> {
+        let template = conn.query_row(
+            "SELECT id, name, description, tasks, notes, created_at FROM project_templates WHERE id = ?1",
+            params![id],
+            Self::row_to_project_template,
+        ).optional()?;
+        Ok(template)
+    }
+
+    /// Whether a project template has this name (case-insensitive)
+    pub fn project_template_name_exists(conn: &Connection, name: &str) -> AppResult<bool> {
+        let exists = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM project_templates WHERE name = ?1 COLLATE NOCASE)",
+            params![name],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// Delete a project template; false when it does not exist
+    pub fn delete_project_template(conn: &Connection, id: &str) -> AppResult<bool> {
+        let affected = conn.execute("DELETE FROM project_templates WHERE id = ?1", params![id])?;
+        Ok(affected > 0)
+    }
+
+    /// Insert a new project with the tasks and notes made from a template in one
+    /// transaction, so a failure leaves no part of it behind. Parents must come
+    /// before their subtasks; every task gets a fresh key.
+    pub fn insert_project_with_content(conn: &Connection, project: &Project, tasks: &mut [Task], notes: &[Note]) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            Self::insert_project(tx, project)?;
+            for task in tasks.iter_mut() {
+                task.task_key = Some(Self::allocate_task_key(tx, &task.project_id)?);
+                Self::insert_task(tx, task)?;
+                Self::record_activity(tx, EntityType::Task, &task.id, ActivityAction::Created)?;
+            }
+            for note in notes {
+                Self::insert_note_row(tx, note)?;
+                Self::record_activity(tx, EntityType::Note, &note.id, ActivityAction::Created)?;
+            }
+            Self::refresh_note_links(tx, &project.id)
+        })
+    }
+
+    // ==========================================
+    // Time Tracking Operations
+    // ==========================================
+
+    /// Insert a time entry
+    pub fn insert_time_entry(conn: &Connection, entry: &TimeEntry) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO time_entries (id, task_id, started_at, ended_at, note) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![entry.id, entry.task_id, entry.started_at, entry.ended_at, entry.note],
+        )?;
+        Ok(())
+    }
+
+    /// Get the running timer, if any
+    pub fn get_running_time_entry(conn: &Connection) -> AppResult<Option<TimeEntry>> {
+        let entry = conn.query_row(
+            "SELECT id, task_id, started_at, ended_at, note FROM time_entries WHERE ended_at IS NULL",
+            [],
+            Self::row_to_time_entry,
+        ).optional()?;
+        Ok(entry)
+    }
+
+    /// End the running timer at `now`, returning it; None when no timer is running
+    pub fn stop_running_time_entry(conn: &Connection, now: i64) -> AppResult<Option<TimeEntry>> {
+        let entry = conn.query_row(
+            "UPDATE time_entries SET ended_at = MAX(started_at, ?1) WHERE ended_at IS NULL
+             RETURNING id, task_id, started_at, ended_at, note",
+            params![now],
+            Self::row_to_time_entry,
+        ).optional()?;
+        Ok(entry)
+    }
+
+    /// Get the time entries of a task, most recent first
+    pub fn get_time_entries_by_task(conn: &Connection, task_id: &str) -> AppResult<Vec<TimeEntry>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, task_id, started_at, ended_at, note FROM time_entries
+             WHERE task_id = ?1 ORDER BY started_at DESC, id ASC"
+        )?;
+
+        let entries = stmt.query_map(params![task_id], Self::row_to_time_entry)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Seconds tracked per task of a project between `from` and `to`, most first.
+    /// Entries are clipped to the window and the running timer counts up to `now`.
+    pub fn get_time_by_task(conn: &Connection, project_id: &str, from: i64, to: i64, now: i64) -> AppResult<Vec<TaskTime>> {
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.title,
+                    SUM(MAX(0, MIN(COALESCE(e.ended_at, ?4), ?3) - MAX(e.started_at, ?2))) AS seconds
+             FROM time_entries e JOIN tasks t ON t.id = e.task_id
+             WHERE t.project_id = ?1 AND e.started_at < ?3 AND COALESCE(e.ended_at, ?4) > ?2
+             GROUP BY t.id
+             ORDER BY seconds DESC, t.title ASC"
+        )?;
+
+        let times = stmt.query_map(params![project_id, from, to, now], |row| {
+            Ok(TaskTime {
+                task_id: row.get(0)?,
+                title: row.get(1)?,
+                seconds: row.get(2)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(times)
+    }
+
+    /// Seconds tracked per tag on a project's tasks between `from` and `to`, most first
+    pub fn get_time_by_tag(conn: &Connection, project_id: &str, from: i64, to: i64, now: i64) -> AppResult<Vec<TagTime>> {
+        let mut stmt = conn.prepare(
+            "SELECT g.name,
+                    SUM(MAX(0, MIN(COALESCE(e.ended_at, ?4), ?3) - MAX(e.started_at, ?2))) AS seconds
+             FROM time_entries e
+             JOIN tasks t ON t.id = e.task_id
+             JOIN task_tags tt ON tt.task_id = t.id
+             JOIN tags g ON g.id = tt.tag_id
+             WHERE t.project_id = ?1 AND e.started_at < ?3 AND COALESCE(e.ended_at, ?4) > ?2
+             GROUP BY g.id
+             ORDER BY seconds DESC, g.name ASC"
+        )?;
+
+        let times = stmt.query_map(params![project_id, from, to, now], |row| {
+            Ok(TagTime {
+                tag: row.get(0)?,
+                seconds: row.get(1)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(times)
+    }
+
+    // ==========================================
+    // Note Attachment Operations
+    // ==========================================
+
+    /// Insert a note attachment
+    pub fn insert_note_attachment(conn: &Connection, attachment: &NoteAttachment) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO note_attachments (id, note_id, relative_path, original_name, size_bytes, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                attachment.id,
+                attachment.note_id,
+                attachment.relative_path,
+                attachment.original_name,
+                attachment.size_bytes,
+                attachment.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the attachments of a note, oldest first
+    pub fn get_note_attachments(conn: &Connection, note_id: &str) -> AppResult<Vec<NoteAttachment>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, note_id, relative_path, original_name, size_bytes, created_at FROM note_attachments
+             WHERE note_id = ?1 ORDER BY created_at ASC, original_name ASC"
+        )?;
+
+        let attachments = stmt.query_map(params![note_id], Self::row_to_note_attachment)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(attachments)
+    }
+
+    /// Get the attachments of every note of a project
+    pub fn get_project_attachments(conn: &Connection, project_id: &str) -> AppResult<Vec<NoteAttachment>> {
+        let mut stmt = conn.prepare(
+            "SELECT a.id, a.note_id, a.relative_path, a.original_name, a.size_bytes, a.created_at
+             FROM note_attachments a JOIN notes n ON n.id = a.note_id
+             WHERE n.project_id = ?1 ORDER BY a.relative_path ASC"
+        )?;
+
+        let attachments = stmt.query_map(params![project_id], Self::row_to_note_attachment)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(attachments)
+    }
+
+    /// Get a note attachment by ID
+    pub fn get_note_attachment_by_id(conn: &Connection, id: &str) -> AppResult<Option<NoteAttachment>> {
+        let attachment = conn.query_row(
+            "SELECT id, note_id, relative_path, original_name, size_bytes, created_at FROM note_attachments WHERE id = ?1",
+            params![id],
+            Self::row_to_note_attachment,
+        ).optional()?;
+        Ok(attachment)
+    }
+
+    /// Delete a note attachment; false when it does not exist
+    pub fn delete_note_attachment(conn: &Connection, id: &str) -> AppResult<bool> {
+        let affected = conn.execute("DELETE FROM note_attachments WHERE id = ?1", params![id])?;
+        Ok(affected > 0)
+    }
+
+    /// Whether any attachment of a note in the project still points at `relative_path`
+    pub fn is_attachment_path_used(conn: &Connection, project_id: &str, relative_path: &str) -> AppResult<bool> {
+        let used = conn.query_row(
+            "SELECT EXISTS(
+                SELECT 1 FROM note_attachments a JOIN notes n ON n.id = a.note_id
+                WHERE n.project_id = ?1 AND a.relative_path = ?2
+            )",
+            params![project_id, relative_path],
+            |row| row.get(0),
+        )?;
+        Ok(used)
+    }
+
+    /// Record that an attachment's file is the blob `hash` of `size_bytes`;
+    /// `is_linked` when the project file is a hard link to the stored blob
+    pub fn insert_attachment_link(conn: &Connection, attachment_id: &str, hash: &str, size_bytes: i64, is_linked: bool) -> AppResult<()> {
+        Self::with_tx(conn, |tx| {
+            tx.execute(
+                "INSERT OR IGNORE INTO attachment_blobs (hash, size_bytes, created_at) VALUES (?1, ?2, ?3)",
+                params![hash, size_bytes, chrono::Utc::now().timestamp()],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO attachment_links (attachment_id, hash, is_linked) VALUES (?1, ?2, ?3)",
+                params![attachment_id, hash, is_linked],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Attachments of project notes whose file is not a link to the store yet,
+    /// with their project directory
+    pub fn get_unstored_attachments(conn: &Connection) -> AppResult<Vec<(NoteAttachment, String)>> {
+        let mut stmt = conn.prepare(
+            "SELECT a.id, a.note_id, a.relative_path, a.original_name, a.size_bytes, a.created_at, p.path
+             FROM note_attachments a
+             JOIN notes n ON n.id = a.note_id
+             JOIN projects p ON p.id = n.project_id
+             LEFT JOIN attachment_links l ON l.attachment_id = a.id
+             WHERE l.attachment_id IS NULL OR l.is_linked = 0
+             ORDER BY a.created_at, a.id"
+        )?;
+        let attachments = stmt.query_map([], |row| Ok((Self::row_to_note_attachment(row)?, row.get(6)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(attachments)
+    }
+
+    /// Delete the blobs no attachment points at any more and return their hashes,
+    /// for the caller to remove their files
+    pub fn take_unused_blobs(conn: &Connection) -> AppResult<Vec<String>> {
+        let unused = "SELECT hash FROM attachment_blobs b
+             WHERE NOT EXISTS (SELECT 1 FROM attachment_links l WHERE l.hash = b.hash)";
+        Self::with_tx(conn, |tx| {
+            let hashes: Vec<String> = tx.prepare(unused)?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            tx.execute(&format!("DELETE FROM attachment_blobs WHERE hash IN ({})", unused), [])?;
+            Ok(hashes)
+        })
+    }
+
+    /// Counts and sizes of the attachment store
+    pub fn get_attachment_store_stats(conn: &Connection) -> AppResult<AttachmentStoreStats> {
+        let (blobs, blob_bytes): (i64, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(size_bytes), 0) FROM attachment_blobs",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let (references, logical_bytes, unlinked_bytes): (i64, i64, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(b.size_bytes), 0),
+                COALESCE(SUM(CASE WHEN l.is_linked THEN 0 ELSE b.size_bytes END), 0)
+             FROM attachment_links l JOIN attachment_blobs b ON b.hash = l.hash",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let unstored: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM note_attachments a
+             WHERE NOT EXISTS (SELECT 1 FROM attachment_links l WHERE l.attachment_id = a.id)",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(AttachmentStoreStats {
+            blobs,
+            references,
+            blob_bytes,
+            logical_bytes,
+            bytes_saved: (logical_bytes - blob_bytes - unlinked_bytes).max(0),
+            unstored,
+        })
+    }
+
+    // ==========================================
+    // Audit Log Operations
+    // ==========================================
+
+    /// Insert an audit log entry
+    pub fn insert_audit_entry(conn: &Connection, command: &str, args: &str, outcome: &str, timestamp: i64) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO audit_log (command, args, outcome, timestamp) VALUES (?1, ?2, ?3, ?4)",
+            params![command, args, outcome, timestamp],
+        )?;
+        Ok(())
+    }
+
+    /// Drop audit entries older than the cutoff or beyond the newest `max_entries`
+    pub fn prune_audit_log(conn: &Connection, older_than: i64, max_entries: i64) -> AppResult<usize> {
+        let mut removed = conn.execute("DELETE FROM audit_log WHERE timestamp < ?1", params![older_than])?;
+        removed += conn.execute(
+            "DELETE FROM audit_log WHERE id NOT IN (SELECT id FROM audit_log ORDER BY id DESC LIMIT ?1)",
+            params![max_entries],
+        )?;
+        Ok(removed)
+    }
+
+    /// List audit entries, newest first. A negative limit returns every match.
+    pub fn list_audit_entries(conn: &Connection, filter: &AuditLogFilter, limit: i64) -> AppResult<Vec<AuditEntry>> {
+        let mut clauses: Vec<&str> = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(command) = &filter.command {
+            clauses.push("command = ?");
+            values.push(Box::new(command.clone()));
+        }
+        if let Some(outcome) = &filter.outcome {
+            clauses.push("outcome = ?");
+            values.push(Box::new(outcome.clone()));
+        }
+        if let Some(from) = filter.from {
+            clauses.push("timestamp >= ?");
+            values.push(Box::new(from));
+        }
+        if let Some(to) = filter.to {
+            clauses.push("timestamp <= ?");
+            values.push(Box::new(to));
+        }
+        values.push(Box::new(limit));
+
+        let where_clause = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+
+        let query = format!(
+            "SELECT id, command, args, outcome, timestamp FROM audit_log {} ORDER BY timestamp DESC, id DESC LIMIT ?",
+            where_clause
+        );
+
+        let mut stmt = conn.prepare(&query)?;
+        let entries = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(Self::row_to_audit_entry(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(entries)
+    }
+
+    /// Apply the pragmas and collations every query relies on. They live on
+    /// the connection, so each new connection needs this before its first query.
+    pub fn configure(conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute_batch(&format!(
+            "PRAGMA foreign_keys = ON;
+             PRAGMA synchronous = NORMAL;
+             PRAGMA temp_store = MEMORY;
+             PRAGMA cache_size = {};",
+            CACHE_SIZE_KIB
+        ))?;
+        // WAL lets readers run alongside a writer; the mode is stored in the file
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        conn.busy_timeout(Duration::from_millis(BUSY_TIMEOUT_MS))?;
+        conn.create_collation(collation::UNICODE_COLLATION, collation::unicode_compare)
+    }
+
+    /// Bring the schema up to the latest version, one migration per transaction.
+    /// Fails without touching the database when it was written by a newer app.
+    pub fn init(conn: &Connection) -> AppResult<()> {
+        Self::init_with_progress(conn, &mut |_, _| {})
+    }
+
+    /// Like `init`, calling `on_step` with the migrations applied so far and
+    /// the number to apply: once before the first and after each one. Every
+    /// step commits with its version, so an interrupted upgrade resumes at the
+    /// step that did not finish.
+    pub fn init_with_progress(conn: &Connection, on_step: &mut dyn FnMut(usize, usize)) -> AppResult<()> {
+        let current = Self::current_schema_version(conn)?;
+        let latest = MIGRATIONS.len() as i64;
+        if current > latest {
+            return Err(AppError::SchemaTooNew { found: current, supported: latest });
+        }
+        let total = (latest - current) as usize;
+        on_step(0, total);
+
+        // Rebuilding a table drops the old one, which must not cascade into the rows
+        // referencing it; the pragma only takes effect outside a transaction
+        conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        let result = MIGRATIONS.iter().enumerate().skip(current as usize).try_for_each(|(index, migrate)| {
+            let tx = conn.unchecked_transaction()?;
+            migrate(&tx)?;
+            tx.pragma_update(None, "user_version", index as i64 + 1)?;
+            tx.commit()?;
+            on_step(index + 1 - current as usize, total);
+            Ok(())
+        });
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        result
+    }
+
+    /// Schema version recorded in the database and the latest one this app knows
+    pub fn get_schema_version(conn: &Connection) -> AppResult<SchemaVersion> {
+        Ok(SchemaVersion {
+            current: Self::current_schema_version(conn)?,
+            latest: MIGRATIONS.len() as i64,
+        })
+    }
+
+    fn current_schema_version(conn: &Connection) -> AppResult<i64> {
+        Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
+    /// Version 1: the schema as it stood when migrations were introduced.
+    /// Databases from before then carry user_version 0 and any subset of it,
+    /// so every statement here tolerates existing tables and columns.
+    fn migrate_baseline(conn: &Connection) -> AppResult<()> {
+        // Create projects table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                path TEXT NOT NULL UNIQUE,
+                description TEXT,
+                status TEXT NOT NULL DEFAULT 'active',
+                created_at INTEGER NOT NULL,
+                last_modified_at INTEGER NOT NULL,
+                tags TEXT
+            )",
+            [],
+        )?;
+
+        // Create tasks table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                parent_id TEXT,
+                title TEXT NOT NULL,
+                description TEXT,
+                status TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                due_date INTEGER,
+                completed_at INTEGER,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                \"order\" INTEGER NOT NULL DEFAULT 0,
+                tags TEXT,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Create notes table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS notes (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                is_pinned BOOLEAN DEFAULT 0,
+                tags TEXT,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Columns added after the initial schema
+        Self::ensure_column(conn, "notes", "is_locked", "BOOLEAN NOT NULL DEFAULT 0")?;
+        Self::ensure_column(conn, "projects", "key_prefix", "TEXT")?;
+        Self::ensure_column(conn, "projects", "next_task_number", "INTEGER NOT NULL DEFAULT 1")?;
+        Self::ensure_column(conn, "tasks", "task_key", "TEXT")?;
+        Self::ensure_column(conn, "tasks", "rank", "INTEGER")?;
+        Self::ensure_column(conn, "projects", "metadata", "TEXT")?;
+        Self::ensure_column(conn, "tasks", "metadata", "TEXT")?;
+        Self::ensure_column(conn, "notes", "metadata", "TEXT")?;
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_tasks_project_key ON tasks(project_id, task_key)",
+            [],
+        )?;
+        Self::backfill_task_keys(conn)?;
+
+        // Create deadlines table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS deadlines (
+                id TEXT PRIMARY KEY,
+                project_id TEXT,
+                name TEXT NOT NULL,
+                date INTEGER NOT NULL,
+                url TEXT,
+                notes TEXT,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_deadlines_date ON deadlines(date)",
+            [],
+        )?;
+
+        // Create audit log table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command TEXT NOT NULL,
+                args TEXT,
+                outcome TEXT NOT NULL,
+                timestamp INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp)",
+            [],
+        )?;
+
+        // Create tag tables (shared with the frontend schema)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tags (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                color TEXT,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        for entity in [EntityType::Project, EntityType::Task, EntityType::Note] {
+            conn.execute(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {junction} (
+                        {key} TEXT NOT NULL,
+                        tag_id TEXT NOT NULL,
+                        created_at INTEGER NOT NULL,
+                        PRIMARY KEY({key}, tag_id),
+                        FOREIGN KEY({key}) REFERENCES {table}(id) ON DELETE CASCADE,
+                        FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+                    )",
+                    junction = entity.tag_table(),
+                    key = entity.tag_key(),
+                    table = entity.table()
+                ),
+                [],
+            )?;
+        }
+        // Older databases may hold names differing only in case; lookups stay case-insensitive regardless
+        if let Err(e) = conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_name_nocase ON tags(name COLLATE NOCASE)",
+            [],
+        ) {
+            tracing::warn!("Skipping case-insensitive tag index: {}", e);
+        }
+        Self::backfill_tag_links(conn)?;
+
+        // Create note link table; target_note_id stays NULL while a link is unresolved
+        let links_exist: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'note_links')",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_links (
+                source_note_id TEXT NOT NULL,
+                target_title TEXT NOT NULL,
+                target_note_id TEXT,
+                position INTEGER NOT NULL,
+                PRIMARY KEY(source_note_id, target_title),
+                FOREIGN KEY(source_note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                FOREIGN KEY(target_note_id) REFERENCES notes(id) ON DELETE SET NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_note_links_target ON note_links(target_note_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_notes_project_title ON notes(project_id, title COLLATE NOCASE)",
+            [],
+        )?;
+        if !links_exist {
+            Self::backfill_note_links(conn)?;
+        }
+
+        // Create file index table (shared with the frontend schema)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS file_metadata (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                relative_path TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                file_extension TEXT,
+                file_size INTEGER,
+                mime_type TEXT,
+                git_hash TEXT,
+                last_commit_hash TEXT,
+                created_at INTEGER NOT NULL,
+                modified_at INTEGER NOT NULL,
+                last_indexed_at INTEGER,
+                content TEXT,
+                metadata TEXT,
+                is_deleted INTEGER NOT NULL DEFAULT 0,
+                is_ignored INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_file_metadata_project_path
+             ON file_metadata(project_id, relative_path)",
+            [],
+        )?;
+        // Manual ignore choice from set_file_ignored; NULL defers to the ignore patterns
+        Self::ensure_column(conn, "file_metadata", "ignore_override", "INTEGER")?;
+
+        // Full-text index over file contents, kept in step with file_metadata.content
+        let fts_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'file_content_fts')",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS file_content_fts USING fts5(file_id UNINDEXED, content)",
+            [],
+        )?;
+        if !fts_exists {
+            // Files indexed before content search existed were never read: force a re-read
+            conn.execute("UPDATE file_metadata SET last_indexed_at = NULL", [])?;
+        }
+        conn.execute(
+            "CREATE TRIGGER IF NOT EXISTS file_metadata_fts_insert AFTER INSERT ON file_metadata
+             WHEN new.content IS NOT NULL BEGIN
+                INSERT INTO file_content_fts (file_id, content) VALUES (new.id, new.content);
+             END",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TRIGGER IF NOT EXISTS file_metadata_fts_update AFTER UPDATE OF content ON file_metadata
+             WHEN old.content IS NOT new.content BEGIN
+                DELETE FROM file_content_fts WHERE old.content IS NOT NULL AND file_id = old.id;
+                INSERT INTO file_content_fts (file_id, content)
+                    SELECT new.id, new.content WHERE new.content IS NOT NULL;
+             END",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TRIGGER IF NOT EXISTS file_metadata_fts_delete AFTER DELETE ON file_metadata
+             WHEN old.content IS NOT NULL BEGIN
+                DELETE FROM file_content_fts WHERE file_id = old.id;
+             END",
+            [],
+        )?;
+
+        // Create project statuses table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS project_statuses (
+                project_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                PRIMARY KEY(project_id, name),
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Create research question tables
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS research_questions (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                question TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'open',
+                answer_summary TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS research_question_notes (
+                question_id TEXT NOT NULL,
+                note_id TEXT NOT NULL,
+                PRIMARY KEY(question_id, note_id),
+                FOREIGN KEY(question_id) REFERENCES research_questions(id) ON DELETE CASCADE,
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS research_question_tasks (
+                question_id TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                PRIMARY KEY(question_id, task_id),
+                FOREIGN KEY(question_id) REFERENCES research_questions(id) ON DELETE CASCADE,
+                FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Version 2: user-defined order of pinned notes, seeded from most recently updated
+    fn migrate_pin_order(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "notes", "pin_order", "INTEGER")?;
+        let project_ids: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT DISTINCT project_id FROM notes WHERE is_pinned")?;
+            let rows = stmt.query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        for project_id in project_ids {
+            Self::renumber_pinned_notes(conn, &project_id)?;
+        }
+        Ok(())
+    }
+
+    /// Version 3: deleting a task deletes its subtasks. SQLite cannot add a foreign key
+    /// to an existing table, so the table is rebuilt and its indexes and triggers restored.
+    fn migrate_task_parent_fk(conn: &Connection) -> AppResult<()> {
+        const COLUMNS: &str = r#"id, project_id, parent_id, title, description, status, priority,
+            due_date, completed_at, created_at, updated_at, "order", tags, task_key, rank, metadata"#;
+
+        // Children of long-deleted parents would violate the new key; keep them as root tasks
+        conn.execute(
+            "UPDATE tasks SET parent_id = NULL
+             WHERE parent_id IS NOT NULL AND parent_id NOT IN (SELECT id FROM tasks)",
+            [],
+        )?;
+
+        let dependents: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT sql FROM sqlite_master
+                 WHERE tbl_name = 'tasks' AND type IN ('index', 'trigger') AND sql IS NOT NULL",
+            )?;
+            let rows = stmt.query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        conn.execute(
+            "CREATE TABLE tasks_new (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                parent_id TEXT,
+                title TEXT NOT NULL,
+                description TEXT,
+                status TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                due_date INTEGER,
+                completed_at INTEGER,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                \"order\" INTEGER NOT NULL DEFAULT 0,
+                tags TEXT,
+                task_key TEXT,
+                rank INTEGER,
+                metadata TEXT,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE,
+                FOREIGN KEY(parent_id) REFERENCES tasks(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            &format!("INSERT INTO tasks_new ({cols}) SELECT {cols} FROM tasks", cols = COLUMNS),
+            [],
+        )?;
+        conn.execute("DROP TABLE tasks", [])?;
+        conn.execute("ALTER TABLE tasks_new RENAME TO tasks", [])?;
+
+        for sql in dependents {
+            conn.execute(&sql, [])?;
+        }
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_tasks_parent ON tasks(parent_id)", [])?;
+        Ok(())
+    }
+
+    /// Version 4: deleted tasks and notes are kept, serialized, in a trash table
+    /// until they are restored or the trash is emptied
+    fn migrate_trash(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS trash (
+                entity_type TEXT NOT NULL,
+                id TEXT NOT NULL,
+                project_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                parent_id TEXT,
+                payload TEXT NOT NULL,
+                deleted_at INTEGER NOT NULL,
+                PRIMARY KEY(entity_type, id),
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_trash_project ON trash(project_id, deleted_at)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 5: application-wide settings as JSON values keyed by name
+    fn migrate_app_settings(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS app_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 6: a feed of recent creates, updates, completions and deletes
+    fn migrate_activity_log(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS activity_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id TEXT NOT NULL,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_activity_log_timestamp ON activity_log(timestamp, id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_activity_log_project ON activity_log(project_id, timestamp, id)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 7: favorite projects and their user-defined order
+    fn migrate_project_favorites(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "projects", "is_favorite", "BOOLEAN NOT NULL DEFAULT 0")?;
+        Self::ensure_column(conn, "projects", "favorite_order", "INTEGER")?;
+        Ok(())
+    }
+
+    /// Version 8: time tracked on tasks. The partial unique index allows only one
+    /// entry without an end, so at most one timer runs at a time.
+    fn migrate_time_entries(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS time_entries (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER,
+                note TEXT,
+                FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_time_entries_task ON time_entries(task_id, started_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_time_entries_running
+             ON time_entries((ended_at IS NULL)) WHERE ended_at IS NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 9: note templates, seeded with the built-in ones. Seeding only
+    /// happens here, so built-in templates the user deletes stay deleted.
+    fn migrate_note_templates(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_templates (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                title_pattern TEXT NOT NULL,
+                content TEXT NOT NULL,
+                tags TEXT,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        let now = chrono::Utc::now().timestamp();
+        for (name, title_pattern, content, tags) in BUILTIN_NOTE_TEMPLATES {
+            let tags_json = serde_json::to_string(tags).unwrap_or_default();
+            conn.execute(
+                "INSERT OR IGNORE INTO note_templates (id, name, title_pattern, content, tags, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![Uuid::new_v4().to_string(), name, title_pattern, content, tags_json, now],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Version 10: inbox notes, captured before they belong to a project. SQLite
+    /// cannot drop NOT NULL from a column, so the table is rebuilt and its indexes
+    /// and triggers restored.
+    fn migrate_inbox_notes(conn: &Connection) -> AppResult<()> {
+        const COLUMNS: &str =
+            "id, project_id, title, content, created_at, updated_at, is_pinned, tags, is_locked, metadata, pin_order";
+
+        let dependents: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT sql FROM sqlite_master
+                 WHERE tbl_name = 'notes' AND type IN ('index', 'trigger') AND sql IS NOT NULL",
+            )?;
+            let rows = stmt.query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        conn.execute(
+            "CREATE TABLE notes_new (
+                id TEXT PRIMARY KEY,
+                project_id TEXT,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                is_pinned BOOLEAN DEFAULT 0,
+                tags TEXT,
+                is_locked BOOLEAN NOT NULL DEFAULT 0,
+                metadata TEXT,
+                pin_order INTEGER,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            &format!("INSERT INTO notes_new ({cols}) SELECT {cols} FROM notes", cols = COLUMNS),
+            [],
+        )?;
+        conn.execute("DROP TABLE notes", [])?;
+        conn.execute("ALTER TABLE notes_new RENAME TO notes", [])?;
+
+        for sql in dependents {
+            conn.execute(&sql, [])?;
+        }
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_notes_inbox ON notes(updated_at) WHERE project_id IS NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 11: files attached to notes. Rows go with their note; the
+    /// copied files stay in the project until removed explicitly.
+    fn migrate_note_attachments(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_attachments (
+                id TEXT PRIMARY KEY,
+                note_id TEXT NOT NULL,
+                relative_path TEXT NOT NULL,
+                original_name TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_note_attachments_note ON note_attachments(note_id, created_at)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 12: per-column task order for the Kanban board
+    fn migrate_board_order(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "tasks", "board_order", "INTEGER")?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_board ON tasks(project_id, status, board_order)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 13: archived tasks, kept out of listings, statistics and search
+    fn migrate_archived_tasks(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "tasks", "is_archived", "BOOLEAN NOT NULL DEFAULT 0")?;
+        Self::ensure_column(conn, "tasks", "archived_at", "INTEGER")?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_archived ON tasks(project_id, is_archived)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 14: cached word count of every note, for writing statistics
+    fn migrate_note_word_count(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "notes", "word_count", "INTEGER")?;
+
+        let notes: Vec<(String, String)> = {
+            let mut stmt = conn.prepare("SELECT id, content FROM notes WHERE word_count IS NULL")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+        let mut update = conn.prepare("UPDATE notes SET word_count = ?1 WHERE id = ?2")?;
+        for (id, content) in &notes {
+            update.execute(params![word_count::word_count(content), id])?;
+        }
+        Ok(())
+    }
+
+    /// Version 15: bibliography references of a project and the notes citing them.
+    /// "references" is an SQL keyword, so the table name is always quoted.
+    fn migrate_references(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS \"references\" (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                citation_key TEXT NOT NULL,
+                entry_type TEXT NOT NULL DEFAULT 'misc',
+                title TEXT NOT NULL,
+                authors TEXT NOT NULL DEFAULT '[]',
+                year INTEGER,
+                venue TEXT,
+                doi TEXT,
+                url TEXT,
+                abstract TEXT,
+                pdf_path TEXT,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_references_key ON \"references\"(project_id, citation_key)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_references_doi ON \"references\"(project_id, doi) WHERE doi IS NOT NULL",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_references (
+                note_id TEXT NOT NULL,
+                reference_id TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY(note_id, reference_id),
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                FOREIGN KEY(reference_id) REFERENCES \"references\"(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_note_references_reference ON note_references(reference_id)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 16: repeating tasks. Completing an occurrence creates the next one,
+    /// which points back at it through recurrence_parent_id.
+    fn migrate_task_recurrence(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "tasks", "recurrence", "TEXT")?;
+        Self::ensure_column(conn, "tasks", "recurrence_parent_id", "TEXT")?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_recurring ON tasks(project_id, due_date) WHERE recurrence IS NOT NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 17: a reminder time per task, and when it was last delivered
+    fn migrate_task_reminders(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "tasks", "remind_at", "INTEGER")?;
+        Self::ensure_column(conn, "tasks", "reminded_at", "INTEGER")?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_pending_reminders ON tasks(remind_at)
+             WHERE remind_at IS NOT NULL AND reminded_at IS NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 18: content hashes of indexed files and when each file appeared,
+    /// last changed or disappeared, so changes can be listed since a point in time.
+    /// Existing files count as present since they were last indexed, and are
+    /// re-read on the next index run to get their hash.
+    fn migrate_file_content_hash(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "file_metadata", "content_hash", "TEXT")?;
+        Self::ensure_column(conn, "file_metadata", "content_changed_at", "INTEGER")?;
+        Self::ensure_column(conn, "file_metadata", "first_indexed_at", "INTEGER")?;
+        Self::ensure_column(conn, "file_metadata", "deleted_at", "INTEGER")?;
+        conn.execute(
+            "UPDATE file_metadata SET first_indexed_at = COALESCE(last_indexed_at, created_at), last_indexed_at = NULL
+             WHERE first_indexed_at IS NULL",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_file_metadata_changes
+             ON file_metadata(project_id, content_changed_at)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 19: indexes for the queries behind the first screens. Task counts
+    /// per project read only the (project_id, status, due_date, is_archived)
+    /// index instead of every task row; pinned notes of a project come from
+    /// (project_id, is_pinned, updated_at) already in order; due tasks across
+    /// projects are found by due_date.
+    fn migrate_startup_indexes(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_project_status ON tasks(project_id, status, due_date, is_archived)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_notes_project_pinned ON notes(project_id, is_pinned, updated_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_due_date ON tasks(due_date) WHERE due_date IS NOT NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 20: project templates, with their task tree and notes as JSON
+    fn migrate_project_templates(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS project_templates (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                description TEXT,
+                tasks TEXT NOT NULL,
+                notes TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 21: editor position and open counts per note, removed with the note
+    fn migrate_note_view_state(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_view_state (
+                note_id TEXT PRIMARY KEY,
+                scroll_position REAL NOT NULL DEFAULT 0,
+                cursor_offset INTEGER NOT NULL DEFAULT 0,
+                last_opened_at INTEGER,
+                open_count INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 22: prerendered HTML of notes for exports, removed with the note
+    fn migrate_note_render_cache(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_render_cache (
+                note_id TEXT PRIMARY KEY,
+                content_hash TEXT NOT NULL,
+                renderer_version INTEGER NOT NULL,
+                rendered TEXT NOT NULL,
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Version 23: how far the user got with reading each reference, and reference tags
+    fn migrate_reference_reading_status(conn: &Connection) -> AppResult<()> {
+        Self::ensure_column(conn, "\"references\"", "reading_status", "TEXT NOT NULL DEFAULT 'unread'")?;
+        Self::ensure_column(conn, "\"references\"", "tags", "TEXT")
+    }
+
+    /// Version 24: content-addressed attachment store. Each distinct attachment
+    /// file is one blob; link rows tie attachments to their blob and go with them.
+    fn migrate_attachment_store(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS attachment_blobs (
+                hash TEXT PRIMARY KEY,
+                size_bytes INTEGER NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS attachment_links (
+                attachment_id TEXT PRIMARY KEY,
+                hash TEXT NOT NULL,
+                is_linked INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY(attachment_id) REFERENCES note_attachments(id) ON DELETE CASCADE,
+                FOREIGN KEY(hash) REFERENCES attachment_blobs(hash)
+            )",
+            [],
+        )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_attachment_links_hash ON attachment_links(hash)", [])?;
+        Ok(())
+    }
+
+    /// Version 25: per-project word-count goals, and a history of every note's
+    /// word count kept by triggers in 15-minute buckets for daily deltas. Only
+    /// the last month is kept, plus the newest count before it.
+    fn migrate_writing_goals(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS project_writing_goals (
+                project_id TEXT PRIMARY KEY,
+                target_words INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_word_history (
+                note_id TEXT NOT NULL,
+                bucket INTEGER NOT NULL,
+                words INTEGER NOT NULL,
+                PRIMARY KEY(note_id, bucket),
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        let record = "INSERT OR REPLACE INTO note_word_history (note_id, bucket, words)
+                VALUES (NEW.id, CAST(strftime('%s', 'now') AS INTEGER) / 900 * 900, NEW.word_count);
+            DELETE FROM note_word_history
+                WHERE note_id = NEW.id
+                AND bucket < (
+                    SELECT MAX(bucket) FROM note_word_history
+                    WHERE note_id = NEW.id AND bucket < CAST(strftime('%s', 'now') AS INTEGER) - 2592000
+                );";
+        conn.execute_batch(&format!(
+            "CREATE TRIGGER IF NOT EXISTS note_word_history_insert AFTER INSERT ON notes
+                WHEN NEW.word_count IS NOT NULL
+            BEGIN
+                {record}
+            END;
+            CREATE TRIGGER IF NOT EXISTS note_word_history_update AFTER UPDATE OF word_count ON notes
+                WHEN NEW.word_count IS NOT NULL AND NEW.word_count IS NOT OLD.word_count
+            BEGIN
+                {record}
+            END;",
+            record = record
+        ))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO note_word_history (note_id, bucket, words)
+             SELECT id, updated_at / 900 * 900, word_count FROM notes WHERE word_count IS NOT NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    // ==========================================
+    // Helper Functions
+    // ==========================================
+
+    /// Run `op` in a transaction, committed when it returns Ok and rolled back when
+    /// it fails. Inside another transaction it runs in a savepoint instead, so
+    /// operations built on it compose and the outermost caller commits.
+    pub fn with_tx<T, F>(conn: &Connection, op: F) -> AppResult<T>
+    where
+        F: FnOnce(&Connection) -> AppResult<T>,
+    {
+        if conn.is_autocommit() {
+            let tx = conn.unchecked_transaction()?;
+            let result = op(&tx)?;
+            tx.commit()?;
+            return Ok(result);
+        }
+
+        conn.execute_batch("SAVEPOINT with_tx")?;
+        match op(conn) {
+            Ok(result) => {
+                conn.execute_batch("RELEASE with_tx")?;
+                Ok(result)
+            }
+            Err(e) => {
+                if let Err(rollback) = conn.execute_batch("ROLLBACK TO with_tx; RELEASE with_tx") {
+                    tracing::warn!("Rolling back a savepoint failed: {}", rollback);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Run a write operation, retrying with exponential backoff while another
+    /// connection holds the database lock. Gives up with `AppError::Busy`.
+    pub fn with_busy_retry<T, F>(mut op: F) -> AppResult<T>
+    where
+        F: FnMut() -> AppResult<T>,
+    {
+        let mut delay = Duration::from_millis(BUSY_RETRY_BASE_DELAY_MS);
+        let mut attempt = 1;
+        loop {
+            match op() {
+                Err(AppError::Busy(_)) if attempt < BUSY_RETRY_ATTEMPTS => {
+                    thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Give projects a key prefix and tasks a key, in creation order, where missing
+    fn backfill_task_keys(conn: &Connection) -> AppResult<()> {
+        let projects: Vec<(String, String)> = {
+            let mut stmt = conn.prepare("SELECT id, name FROM projects WHERE key_prefix IS NULL")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        for (id, name) in projects {
+            conn.execute(
+                "UPDATE projects SET key_prefix = ?1 WHERE id = ?2",
+                params![text::key_prefix_from_name(&name), id],
+            )?;
+        }
+
+        let tasks: Vec<(String, String)> = {
+            let mut stmt = conn.prepare(
+                "SELECT id, project_id FROM tasks
+                 WHERE task_key IS NULL AND project_id IN (SELECT id FROM projects)
+                 ORDER BY created_at ASC, rowid ASC"
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        for (id, project_id) in tasks {
+            let key = Self::allocate_task_key(conn, &project_id)?;
+            conn.execute("UPDATE tasks SET task_key = ?1 WHERE id = ?2", params![key, id])?;
+        }
+
+        Ok(())
+    }
+
+    /// Add the conditions of a search on `updated_at` for the bounds that are given
+    fn push_updated_between(
+        clauses: &mut Vec<String>,
+        values: &mut Vec<Box<dyn ToSql>>,
+        updated_after: Option<i64>,
+        updated_before: Option<i64>,
+    ) {
+        if let Some(after) = updated_after {
+            values.push(Box::new(after));
+            clauses.push(format!("updated_at >= ?{}", values.len()));
+        }
+        if let Some(before) = updated_before {
+            values.push(Box::new(before));
+            clauses.push(format!("updated_at < ?{}", values.len()));
+        }
+    }
+
+    /// Add a column to an existing table if it is missing
+    fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> AppResult<()> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+
+        if !exists {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn row_to_project(row: &Row) -> Project {
+        let tags_str: Option<String> = row.get("tags").unwrap_or(None);
+        let tags = tags_str.and_then(|t| serde_json::from_str(&t).ok());
 
         Project {
             id: row.get("id").unwrap_or_default(),
             name: row.get("name").unwrap_or_default(),
             path: row.get("path").unwrap_or_default(),
             description: row.get("description").unwrap_or(None),
-            status: row.get("status").unwrap_or_else(|_| "active".to_string()),
+            status: row.get("status").unwrap_or_default(),
             created_at: row.get("created_at").unwrap_or_default(),
             last_modified_at: row.get("last_modified_at").unwrap_or_default(),
             tags,
+            key_prefix: row.get("key_prefix").unwrap_or(None),
+            is_favorite: row.get("is_favorite").unwrap_or(false),
+            metadata: None,
         }
     }
 
@@ -346,9 +6694,26 @@ impl DbService {
             updated_at: row.get(10).unwrap_or_default(),
             order: row.get(11).unwrap_or_default(),
             tags,
+            task_key: row.get(13).unwrap_or(None),
+            rank: row.get(14).unwrap_or(None),
+            recurrence: row.get(15).unwrap_or(None),
+            recurrence_parent_id: row.get(16).unwrap_or(None),
+            remind_at: row.get(17).unwrap_or(None),
+            reminded_at: row.get(18).unwrap_or(None),
+            metadata: None,
         }
     }
 
+    fn row_to_note_view_state(row: &Row) -> rusqlite::Result<NoteViewState> {
+        Ok(NoteViewState {
+            note_id: row.get(0)?,
+            scroll_position: row.get(1)?,
+            cursor_offset: row.get(2)?,
+            last_opened_at: row.get(3)?,
+            open_count: row.get(4)?,
+        })
+    }
+
     fn row_to_note(row: &Row) -> Note {
         let tags_str: Option<String> = row.get(6).ok();
         let tags = tags_str.and_then(|s| serde_json::from_str(&s).ok());
@@ -362,6 +6727,896 @@ impl DbService {
             updated_at: row.get(5).unwrap_or_default(),
             tags,
             is_pinned: row.get(7).unwrap_or_default(),
+            is_locked: row.get(8).unwrap_or_default(),
+            metadata: None,
+        }
+    }
+
+    /// Parse the metadata column of a single-entity row
+    fn row_metadata(row: &Row) -> Option<Value> {
+        let metadata: Option<String> = row.get("metadata").unwrap_or(None);
+        metadata.and_then(|m| serde_json::from_str(&m).ok())
+    }
+
+    fn row_to_note_template(row: &Row) -> rusqlite::Result<NoteTemplate> {
+        let tags_str: Option<String> = row.get(4)?;
+        Ok(NoteTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            title_pattern: row.get(2)?,
+            content: row.get(3)?,
+            tags: tags_str.and_then(|t| serde_json::from_str(&t).ok()),
+            created_at: row.get(5)?,
+        })
+    }
+
+    /// A task tree or note list that no longer parses reads as empty
+    fn row_to_project_template(row: &Row) -> rusqlite::Result<ProjectTemplate> {
+        let tasks: String = row.get(3)?;
+        let notes: String = row.get(4)?;
+        Ok(ProjectTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            tasks: serde_json::from_str(&tasks).unwrap_or_default(),
+            notes: serde_json::from_str(&notes).unwrap_or_default(),
+            created_at: row.get(5)?,
+        })
+    }
+
+    fn row_to_time_entry(row: &Row) -> rusqlite::Result<TimeEntry> {
+        Ok(TimeEntry {
+            id: row.get(0)?,
+            task_id: row.get(1)?,
+            started_at: row.get(2)?,
+            ended_at: row.get(3)?,
+            note: row.get(4)?,
+        })
+    }
+
+    fn row_to_note_attachment(row: &Row) -> rusqlite::Result<NoteAttachment> {
+        Ok(NoteAttachment {
+            id: row.get(0)?,
+            note_id: row.get(1)?,
+            relative_path: row.get(2)?,
+            original_name: row.get(3)?,
+            size_bytes: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    }
+
+    fn row_to_trash_entry(row: &Row) -> rusqlite::Result<TrashEntry> {
+        Ok(TrashEntry {
+            entity_type: row.get(0)?,
+            id: row.get(1)?,
+            project_id: row.get(2)?,
+            title: row.get(3)?,
+            parent_id: row.get(4)?,
+            deleted_at: row.get(5)?,
+        })
+    }
+
+    fn row_to_reference(row: &Row) -> Reference {
+        let authors: String = row.get(5).unwrap_or_default();
+        let tags: Option<String> = row.get(14).unwrap_or(None);
+        Reference {
+            id: row.get(0).unwrap_or_default(),
+            project_id: row.get(1).unwrap_or_default(),
+            citation_key: row.get(2).unwrap_or_default(),
+            entry_type: row.get(3).unwrap_or_default(),
+            title: row.get(4).unwrap_or_default(),
+            authors: serde_json::from_str(&authors).unwrap_or_default(),
+            year: row.get(6).unwrap_or(None),
+            venue: row.get(7).unwrap_or(None),
+            doi: row.get(8).unwrap_or(None),
+            url: row.get(9).unwrap_or(None),
+            abstract_text: row.get(10).unwrap_or(None),
+            pdf_path: row.get(11).unwrap_or(None),
+            created_at: row.get(12).unwrap_or_default(),
+            reading_status: row.get(13).unwrap_or_default(),
+            tags: tags.and_then(|tags| serde_json::from_str(&tags).ok()),
+        }
+    }
+
+    fn row_to_research_question(row: &Row) -> ResearchQuestion {
+        ResearchQuestion {
+            id: row.get(0).unwrap_or_default(),
+            project_id: row.get(1).unwrap_or_default(),
+            question: row.get(2).unwrap_or_default(),
+            status: row.get(3).unwrap_or_default(),
+            answer_summary: row.get(4).unwrap_or(None),
+            created_at: row.get(5).unwrap_or_default(),
+            updated_at: row.get(6).unwrap_or_default(),
+        }
+    }
+
+    /// Map note summary columns starting at `offset`
+    fn row_to_note_summary(row: &Row, offset: usize) -> NoteSummary {
+        let tags_str: Option<String> = row.get(offset + 4).unwrap_or(None);
+        NoteSummary {
+            id: row.get(offset).unwrap_or_default(),
+            project_id: row.get(offset + 1).unwrap_or_default(),
+            title: row.get(offset + 2).unwrap_or_default(),
+            updated_at: row.get(offset + 3).unwrap_or_default(),
+            tags: tags_str.and_then(|t| serde_json::from_str(&t).ok()),
+            is_pinned: row.get(offset + 5).unwrap_or_default(),
+            is_locked: row.get(offset + 6).unwrap_or_default(),
+        }
+    }
+
+    fn row_to_file_metadata(row: &Row) -> FileMetadata {
+        FileMetadata {
+            id: row.get(0).unwrap_or_default(),
+            project_id: row.get(1).unwrap_or_default(),
+            relative_path: row.get(2).unwrap_or_default(),
+            file_name: row.get(3).unwrap_or_default(),
+            file_extension: row.get(4).unwrap_or(None),
+            file_size: row.get(5).unwrap_or(None),
+            mime_type: row.get(6).unwrap_or(None),
+            created_at: row.get(7).unwrap_or_default(),
+            modified_at: row.get(8).unwrap_or_default(),
+            last_indexed_at: row.get(9).unwrap_or(None),
+            is_deleted: row.get(10).unwrap_or_default(),
+            is_ignored: row.get(11).unwrap_or_default(),
+            content_hash: row.get(12).unwrap_or(None),
+            content_changed_at: row.get(13).unwrap_or(None),
         }
     }
+
+    fn row_to_tag(row: &Row) -> Tag {
+        Tag {
+            id: row.get(0).unwrap_or_default(),
+            name: row.get(1).unwrap_or_default(),
+            color: row.get(2).unwrap_or(None),
+            created_at: row.get(3).unwrap_or_default(),
+        }
+    }
+
+    fn row_to_deadline(row: &Row) -> Deadline {
+        Deadline {
+            id: row.get(0).unwrap_or_default(),
+            project_id: row.get(1).unwrap_or(None),
+            name: row.get(2).unwrap_or_default(),
+            date: row.get(3).unwrap_or_default(),
+            url: row.get(4).unwrap_or(None),
+            notes: row.get(5).unwrap_or(None),
+            created_at: row.get(6).unwrap_or_default(),
+        }
+    }
+
+    fn row_to_audit_entry(row: &Row) -> AuditEntry {
+        let args_str: Option<String> = row.get(2).unwrap_or(None);
+        let args = args_str.and_then(|a| serde_json::from_str(&a).ok());
+
+        AuditEntry {
+            id: row.get(0).unwrap_or_default(),
+            command: row.get(1).unwrap_or_default(),
+            args,
+            outcome: row.get(3).unwrap_or_default(),
+            timestamp: row.get(4).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ListSortField, TaskPriority};
+    use crate::services::test_support::{self, note, project, reference, task};
+
+    fn edge(from: &str, to: &str, kind: &str) -> GraphEdge {
+        GraphEdge { from: from.to_string(), to: to.to_string(), kind: kind.to_string() }
+    }
+
+    #[test]
+    fn project_graph_has_links_citations_and_subtasks() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "graph");
+        let a = note(&conn, &p.id, "Alpha", "See [[Beta]] and [[Alpha]] and [[Missing]]");
+        let b = note(&conn, &p.id, "Beta", "");
+        let r = reference(&conn, &p.id, "knuth1984", "Literate Programming");
+        DbService::cite_reference(&conn, &b.id, &r.id).unwrap();
+        let parent = task(&conn, &p.id, "Parent");
+        let mut child = test_support::new_task(&p.id, "Child");
+        child.parent_id = Some(parent.id.clone());
+        DbService::insert_task_with_key(&conn, &mut child).unwrap();
+
+        let (nodes, edges) = DbService::get_project_graph(&conn, &p.id, false).unwrap();
+        let kinds: Vec<&str> = nodes.iter().map(|n| n.kind.as_str()).collect();
+        assert_eq!(kinds, ["note", "note", "reference"]);
+        assert_eq!(edges, [edge(&a.id, &b.id, "link"), edge(&b.id, &r.id, "cites")]);
+
+        let (nodes, edges) = DbService::get_project_graph(&conn, &p.id, true).unwrap();
+        assert_eq!(nodes.len(), 5);
+        assert!(edges.contains(&edge(&child.id, &parent.id, "subtask")));
+    }
+
+    #[test]
+    fn moving_notes_skips_locked_and_missing_ones() {
+        let conn = test_support::open_db();
+        let from = project(&conn, "from");
+        let to = project(&conn, "to");
+        let open = note(&conn, &from.id, "Open", "");
+        let locked = note(&conn, &from.id, "Locked", "");
+        conn.execute("UPDATE notes SET is_locked = 1 WHERE id = ?1", params![locked.id]).unwrap();
+
+        let ids = [open.id.clone(), locked.id.clone(), "missing".to_string()];
+        let result = DbService::move_notes_to_project(&conn, &ids, &to.id).unwrap();
+        assert_eq!(result.moved, 1);
+        let reasons: Vec<&str> = result.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert_eq!(reasons, ["Note is locked", "Note not found"]);
+        let moved = DbService::get_note_by_id(&conn, &open.id).unwrap().unwrap();
+        assert_eq!(moved.project_id.as_deref(), Some(to.id.as_str()));
+    }
+
+    #[test]
+    fn relocating_attachments_rewrites_paths_and_links() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "relocate");
+        let content = "![plot](docs/attachments/plot.png)\n[data](./docs/attachments/data.csv)\n![[plot.png]]\n`docs/attachments/plot.png`";
+        let n = note(&conn, &p.id, "Results", content);
+        for name in ["plot.png", "data.csv"] {
+            let attachment = NoteAttachment {
+                id: Uuid::new_v4().to_string(),
+                note_id: n.id.clone(),
+                relative_path: format!("docs/attachments/{}", name),
+                original_name: name.to_string(),
+                size_bytes: 1,
+                created_at: 0,
+            };
+            DbService::insert_note_attachment(&conn, &attachment).unwrap();
+        }
+
+        let moves = HashMap::from([
+            ("docs/attachments/plot.png".to_string(), "docs/attachments/plot (2).png".to_string()),
+            ("docs/attachments/data.csv".to_string(), "docs/attachments/data.csv".to_string()),
+        ]);
+        DbService::relocate_note_attachments(&conn, &n.id, &moves).unwrap();
+
+        let paths: Vec<String> = DbService::get_note_attachments(&conn, &n.id).unwrap()
+            .into_iter().map(|a| a.relative_path).collect();
+        assert!(paths.contains(&"docs/attachments/plot (2).png".to_string()));
+        assert!(paths.contains(&"docs/attachments/data.csv".to_string()));
+        let note = DbService::get_note_by_id(&conn, &n.id).unwrap().unwrap();
+        assert_eq!(
+            note.content,
+            "![plot](docs/attachments/plot%20(2).png)\n[data](./docs/attachments/data.csv)\n![[plot%20(2).png]]\n`docs/attachments/plot.png`"
+        );
+    }
+
+    #[test]
+    fn project_graph_joins_notes_and_tasks_through_questions() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "questions");
+        let n = note(&conn, &p.id, "Findings", "");
+        let t = task(&conn, &p.id, "Run experiment");
+        let question = ResearchQuestion {
+            id: Uuid::new_v4().to_string(),
+            project_id: p.id.clone(),
+            question: "Does it work?".to_string(),
+            status: "open".to_string(),
+            answer_summary: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+        DbService::insert_research_question(&conn, &question).unwrap();
+        DbService::link_question_note(&conn, &question.id, &n.id).unwrap();
+        DbService::link_question_task(&conn, &question.id, &t.id).unwrap();
+
+        let (_, edges) = DbService::get_project_graph(&conn, &p.id, true).unwrap();
+        assert_eq!(edges, [edge(&n.id, &t.id, "question")]);
+        let (_, edges) = DbService::get_project_graph(&conn, &p.id, false).unwrap();
+        assert!(edges.is_empty());
+    }
+
+    fn statuses(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn status_update(status: &str) -> UpdateTaskDto {
+        UpdateTaskDto {
+            title: None,
+            description: None,
+            status: Some(status.to_string()),
+            priority: None,
+            due_date: None,
+            parent_id: None,
+            order: None,
+            tags: None,
+            recurrence: None,
+            remind_at: None,
+            remind_before: None,
+            expected_updated_at: None,
+        }
+    }
+
+    #[test]
+    fn project_statuses_must_be_a_non_empty_list_of_distinct_names() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "workflow");
+        let none = HashMap::new();
+
+        let empty = DbService::set_project_statuses(&conn, &p.id, &[], &none);
+        assert!(matches!(empty, Err(AppError::InvalidInput(_))));
+        let duplicate = DbService::set_project_statuses(&conn, &p.id, &statuses(&["queued", "read", "queued"]), &none);
+        assert!(matches!(duplicate, Err(AppError::InvalidInput(_))));
+        assert_eq!(DbService::get_project_statuses(&conn, &p.id).unwrap(), DEFAULT_TASK_STATUSES);
+    }
+
+    #[test]
+    fn last_project_status_marks_tasks_done() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "reading");
+        let parent = task(&conn, &p.id, "Survey");
+        let mut child = test_support::new_task(&p.id, "Paper");
+        child.parent_id = Some(parent.id.clone());
+        DbService::insert_task_with_key(&conn, &mut child).unwrap();
+        DbService::update_task(&conn, &parent.id, &status_update("done"), false).unwrap();
+
+        // Renaming the workflow keeps done tasks done under the new last status
+        let mapping = HashMap::from([
+            ("todo".to_string(), "queued".to_string()),
+            ("done".to_string(), "skimmed".to_string()),
+        ]);
+        DbService::set_project_statuses(&conn, &p.id, &statuses(&["queued", "skimmed", "summarized"]), &mapping).unwrap();
+        assert_eq!(DbService::get_done_status(&conn, &p.id).unwrap(), "summarized");
+        let parent_now = DbService::get_task_by_id(&conn, &parent.id).unwrap().unwrap();
+        assert_eq!(parent_now.status, "skimmed");
+        assert_eq!(parent_now.completed_at, None);
+
+        DbService::update_task(&conn, &parent.id, &status_update("summarized"), true).unwrap();
+        let parent_now = DbService::get_task_by_id(&conn, &parent.id).unwrap().unwrap();
+        let child_now = DbService::get_task_by_id(&conn, &child.id).unwrap().unwrap();
+        assert!(parent_now.completed_at.is_some());
+        assert_eq!(child_now.status, "summarized");
+        assert!(child_now.completed_at.is_some());
+        let progress = DbService::get_task_progress(&conn, &parent.id).unwrap().unwrap();
+        assert_eq!((progress.completed, progress.total), (1, 1));
+
+        DbService::move_task_on_board(&conn, &child.id, "queued", 0).unwrap();
+        assert_eq!(DbService::get_task_by_id(&conn, &child.id).unwrap().unwrap().completed_at, None);
+        let report = DbService::repair_database(&conn, true).unwrap();
+        assert!(report.findings.iter().all(|f| !matches!(f.kind, RepairKind::CompletionMismatch)));
+    }
+
+    #[test]
+    fn project_filter_combines_any_all_and_none_tags() {
+        let conn = test_support::open_db();
+        let tagged = |name: &str, tags: &str, status: &str| {
+            let p = project(&conn, name);
+            conn.execute(
+                "UPDATE projects SET tags = ?1, status = ?2 WHERE id = ?3",
+                params![tags, status, p.id],
+            ).unwrap();
+        };
+        tagged("nlp", r#"["NLP", "ml"]"#, "active");
+        tagged("nlp teaching", r#"["nlp", "teaching"]"#, "active");
+        tagged("vision", r#"["vision", "ml"]"#, "active");
+        tagged("old nlp", r#"["nlp"]"#, "archived");
+        tagged("broken", "not json", "active");
+
+        let filter = |status: Option<ProjectStatus>, any: &[&str], all: &[&str], none: &[&str]| {
+            let list = |tags: &[&str]| Some(tags.iter().map(|t| t.to_string()).collect::<Vec<_>>()).filter(|t| !t.is_empty());
+            let dto = ProjectFilterDto { status, tags_any: list(any), tags_all: list(all), tags_none: list(none) };
+            let mut names: Vec<String> = DbService::filter_projects(&conn, &dto).unwrap().into_iter().map(|p| p.name).collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(filter(None, &["nlp"], &[], &[]), ["nlp", "nlp teaching", "old nlp"]);
+        assert_eq!(filter(None, &["vision", " Teaching "], &[], &[]), ["nlp teaching", "vision"]);
+        assert_eq!(filter(None, &[], &["nlp", "ml"], &[]), ["nlp"]);
+        assert_eq!(filter(None, &[], &[], &["nlp", "vision"]), ["broken"]);
+        // Active projects tagged nlp but not teaching
+        assert_eq!(filter(Some(ProjectStatus::Active), &["nlp"], &[], &["teaching"]), ["nlp"]);
+        assert_eq!(filter(None, &["ml"], &["nlp"], &["vision"]), ["nlp"]);
+        assert_eq!(filter(None, &[], &[], &[]).len(), 5);
+        // Values are bound, never spliced into the SQL
+        assert!(filter(None, &["') OR 1=1 --"], &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn task_search_ranks_title_then_tag_then_description_matches() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "search");
+        let insert = |title: &str, description: Option<&str>, tags: &[&str], updated_at: i64| {
+            let mut task = test_support::new_task(&p.id, title);
+            task.description = description.map(str::to_string);
+            task.tags = Some(tags.iter().map(|t| t.to_string()).collect());
+            task.updated_at = updated_at;
+            DbService::insert_task_with_key(&conn, &mut task).unwrap();
+            task
+        };
+        let described = insert("Read papers", Some("Survey of transformer models"), &[], 300);
+        let tagged = insert("Write intro", None, &["Transformer"], 200);
+        let titled = insert("Train the TRANSFORMER", None, &[], 100);
+
+        let titles = |query: &str, status: Option<&str>| -> Vec<String> {
+            DbService::search_tasks(&conn, &p.id, query, status, None, None).unwrap().into_iter().map(|t| t.title).collect()
+        };
+
+        // Case-insensitive, and a title match outranks a newer tag or description match
+        assert_eq!(titles("transformer", None), [titled.title.as_str(), tagged.title.as_str(), described.title.as_str()]);
+        // Every term must match somewhere
+        assert_eq!(titles("transformer survey", None), [described.title.as_str()]);
+        assert!(titles("transformer missing", None).is_empty());
+        // Task keys match like titles
+        let key = titled.task_key.clone().unwrap();
+        assert_eq!(titles(&key.to_lowercase(), None), [titled.title.as_str()]);
+    }
+
+    #[test]
+    fn task_search_filters_by_status_date_and_archive() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "search");
+        let other = project(&conn, "other");
+        let mut done = test_support::new_task(&p.id, "Draft abstract");
+        done.status = "done".to_string();
+        done.completed_at = Some(50);
+        done.updated_at = 50;
+        DbService::insert_task_with_key(&conn, &mut done).unwrap();
+        let mut todo = test_support::new_task(&p.id, "Draft conclusion");
+        todo.updated_at = 150;
+        DbService::insert_task_with_key(&conn, &mut todo).unwrap();
+        task(&conn, &other.id, "Draft elsewhere");
+
+        let ids = |status: Option<&str>, after: Option<i64>, before: Option<i64>| -> Vec<String> {
+            DbService::search_tasks(&conn, &p.id, "draft", status, after, before).unwrap().into_iter().map(|t| t.id).collect()
+        };
+
+        assert_eq!(ids(None, None, None).len(), 2);
+        assert_eq!(ids(Some("todo"), None, None), [todo.id.as_str()]);
+        assert_eq!(ids(Some("done"), None, None), [done.id.as_str()]);
+        assert!(ids(Some("in_progress"), None, None).is_empty());
+        assert_eq!(ids(None, Some(100), None), [todo.id.as_str()]);
+        assert_eq!(ids(None, None, Some(100)), [done.id.as_str()]);
+
+        DbService::archive_completed_tasks(&conn, &p.id, 100).unwrap();
+        assert_eq!(ids(None, None, None), [todo.id.as_str()]);
+    }
+
+    #[test]
+    fn task_search_treats_empty_queries_and_wildcards_literally() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "search");
+        let percent = task(&conn, &p.id, "Raise coverage to 90%");
+        let underscore = task(&conn, &p.id, "Rename load_data");
+        task(&conn, &p.id, "Rename loadXdata");
+        task(&conn, &p.id, "Raise coverage to 90 points");
+
+        let ids = |query: &str| -> Vec<String> {
+            DbService::search_tasks(&conn, &p.id, query, None, None, None).unwrap().into_iter().map(|t| t.id).collect()
+        };
+
+        assert!(ids("").is_empty());
+        assert!(ids("   \t ").is_empty());
+        assert_eq!(ids("90%"), [percent.id.as_str()]);
+        assert_eq!(ids("load_data"), [underscore.id.as_str()]);
+        assert_eq!(ids("%"), [percent.id.as_str()]);
+        assert_eq!(ids("_"), [underscore.id.as_str()]);
+        assert!(ids("\\").is_empty());
+        // Quotes are bound, never spliced into the SQL
+        assert!(ids("' OR 1=1 --").is_empty());
+    }
+
+    #[test]
+    fn note_links_resolve_as_targets_appear_change_and_go() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "links");
+        let other = project(&conn, "other");
+        let target = note(&conn, &p.id, "Methods", "");
+        note(&conn, &other.id, "Later", "");
+        let source = note(&conn, &p.id, "Draft", "See [[methods]] and [[Later]], twice: [[Methods|here]]");
+
+        let targets = |conn: &Connection| -> Vec<(String, Option<String>)> {
+            DbService::get_note_outgoing_links(conn, &source.id)
+                .unwrap()
+                .into_iter()
+                .map(|link| (link.target_title, link.note.map(|n| n.id)))
+                .collect()
+        };
+        let rename = |id: &str, title: &str| {
+            let data = UpdateNoteDto { title: Some(title.to_string()), content: None, tags: None, is_pinned: None, expected_updated_at: None };
+            assert!(DbService::update_note(&conn, id, &data).unwrap());
+        };
+
+        // Case-insensitive within the project; a same-titled note elsewhere does not count
+        assert_eq!(targets(&conn), [("methods".to_string(), Some(target.id.clone())), ("Later".to_string(), None)]);
+        let backlinks: Vec<String> = DbService::get_note_backlinks(&conn, &target.id).unwrap().into_iter().map(|n| n.id).collect();
+        assert_eq!(backlinks, [source.id.as_str()]);
+
+        // Unresolved until a note with the title exists
+        let later = note(&conn, &p.id, "LATER", "");
+        assert_eq!(targets(&conn)[1].1.as_deref(), Some(later.id.as_str()));
+
+        // Renaming the target away unresolves the link; renaming another note to the title resolves to it
+        rename(&target.id, "Method section");
+        assert_eq!(targets(&conn)[0].1, None);
+        assert!(DbService::get_note_backlinks(&conn, &target.id).unwrap().is_empty());
+        let replacement = note(&conn, &p.id, "Scratch", "");
+        rename(&replacement.id, "Methods");
+        assert_eq!(targets(&conn)[0].1.as_deref(), Some(replacement.id.as_str()));
+
+        // Deleting a target clears it, or moves the link to the oldest note left with the title
+        let duplicate = note(&conn, &p.id, "later", "");
+        assert!(DbService::delete_note(&conn, &later.id, true).unwrap());
+        assert_eq!(targets(&conn)[1].1.as_deref(), Some(duplicate.id.as_str()));
+        assert!(DbService::delete_note(&conn, &duplicate.id, false).unwrap());
+        assert_eq!(targets(&conn)[1].1, None);
+
+        // Editing the content replaces the link rows
+        let data = UpdateNoteDto { title: None, content: Some("Only [[Methods]]".to_string()), tags: None, is_pinned: None, expected_updated_at: None };
+        DbService::update_note(&conn, &source.id, &data).unwrap();
+        assert_eq!(targets(&conn), [("Methods".to_string(), Some(replacement.id.clone()))]);
+    }
+
+    #[test]
+    fn stats_count_the_last_workflow_status_as_done() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "custom");
+        let statuses: Vec<String> = ["idea", "drafting", "published"].iter().map(|s| s.to_string()).collect();
+        DbService::set_project_statuses(&conn, &p.id, &statuses, &HashMap::new()).unwrap();
+        let insert = |title: &str, status: &str, due_date: Option<i64>| {
+            let mut task = test_support::new_task(&p.id, title);
+            task.status = status.to_string();
+            task.due_date = due_date;
+            DbService::insert_task_with_key(&conn, &mut task).unwrap();
+        };
+        insert("Shipped late", "published", Some(100));
+        insert("Also shipped", "published", None);
+        insert("Late draft", "drafting", Some(100));
+        // A task named "done" is not done in this workflow
+        insert("Odd one", "done", Some(100));
+
+        let stats = DbService::get_project_stats(&conn, Some(&p.id), 1_000, 10).unwrap().remove(&p.id).unwrap();
+        assert_eq!(stats.overdue_tasks, 2);
+        assert_eq!(stats.completion_percentage, 50.0);
+
+        let counts = DbService::get_all_projects_with_counts(&conn, true, ProjectSort::default(), 1_000).unwrap();
+        let counts = counts.into_iter().find(|c| c.project.id == p.id).unwrap();
+        assert_eq!((counts.open_task_count, counts.done_task_count, counts.overdue_count), (2, 2, 2));
+    }
+
+    fn schema_objects(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM sqlite_master WHERE tbl_name = ?1 AND type IN ('index', 'trigger') ORDER BY name")
+            .unwrap();
+        let names = stmt.query_map(params![table], |row| row.get(0)).unwrap().filter_map(|r| r.ok()).collect();
+        names
+    }
+
+    #[test]
+    fn migrations_upgrade_a_partial_pre_migration_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        DbService::configure(&conn).unwrap();
+        // An early build: only projects and tasks, without the columns added since
+        conn.execute_batch(
+            "CREATE TABLE projects (
+                id TEXT PRIMARY KEY, name TEXT NOT NULL, path TEXT NOT NULL UNIQUE, description TEXT,
+                status TEXT NOT NULL DEFAULT 'active', created_at INTEGER NOT NULL,
+                last_modified_at INTEGER NOT NULL, tags TEXT
+             );
+             CREATE TABLE tasks (
+                id TEXT PRIMARY KEY, project_id TEXT NOT NULL, parent_id TEXT, title TEXT NOT NULL,
+                description TEXT, status TEXT NOT NULL, priority TEXT NOT NULL, due_date INTEGER,
+                completed_at INTEGER, created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL,
+                \"order\" INTEGER NOT NULL DEFAULT 0, tags TEXT
+             );
+             INSERT INTO projects VALUES ('p1', 'Old', '/old', NULL, 'active', 1, 1, NULL);
+             INSERT INTO tasks (id, project_id, parent_id, title, status, priority, created_at, updated_at)
+             VALUES ('t1', 'p1', NULL, 'Parent', 'todo', 'medium', 1, 1),
+                    ('t2', 'p1', 't1', 'Child', 'todo', 'medium', 1, 1),
+                    ('t3', 'p1', 'gone', 'Stray', 'todo', 'medium', 1, 1);",
+        )
+        .unwrap();
+        assert_eq!(DbService::get_schema_version(&conn).unwrap().current, 0);
+
+        let mut steps = Vec::new();
+        DbService::init_with_progress(&conn, &mut |done, total| steps.push((done, total))).unwrap();
+        let latest = MIGRATIONS.len();
+        assert_eq!(steps.first(), Some(&(0, latest)));
+        assert_eq!(steps.last(), Some(&(latest, latest)));
+        let version = DbService::get_schema_version(&conn).unwrap();
+        assert_eq!((version.current, version.latest), (latest as i64, latest as i64));
+
+        // Old rows read through today's queries; a stray parent becomes a root task
+        let project = DbService::get_project_by_id(&conn, "p1").unwrap().unwrap();
+        assert_eq!((project.name.as_str(), project.metadata), ("Old", None));
+        assert_eq!(DbService::get_task_by_id(&conn, "t2").unwrap().unwrap().parent_id.as_deref(), Some("t1"));
+        assert_eq!(DbService::get_task_by_id(&conn, "t3").unwrap().unwrap().parent_id, None);
+        // Tables the early build never had exist now
+        note(&conn, "p1", "New note", "");
+        DbService::insert_note(&conn, &test_support::new_note(None, "Inbox", "")).unwrap();
+
+        // Subtasks go with their parent since version 3
+        conn.execute("DELETE FROM tasks WHERE id = 't1'", []).unwrap();
+        assert!(DbService::get_task_by_id(&conn, "t2").unwrap().is_none());
+
+        // A second run has nothing to do
+        let mut steps = Vec::new();
+        DbService::init_with_progress(&conn, &mut |done, total| steps.push((done, total))).unwrap();
+        assert_eq!(steps, [(0, 0)]);
+    }
+
+    #[test]
+    fn a_database_from_a_newer_app_is_left_untouched() {
+        let conn = test_support::open_db();
+        let newer = MIGRATIONS.len() as i64 + 1;
+        conn.pragma_update(None, "user_version", newer).unwrap();
+
+        let result = DbService::init(&conn);
+        assert!(matches!(
+            result,
+            Err(AppError::SchemaTooNew { found, supported }) if found == newer && supported == MIGRATIONS.len() as i64
+        ));
+        assert_eq!(DbService::get_schema_version(&conn).unwrap().current, newer);
+        // The foreign key pragma is only switched off once migrations run
+        assert!(conn.pragma_query_value(None, "foreign_keys", |row| row.get::<_, bool>(0)).unwrap());
+    }
+
+    #[test]
+    fn table_rebuilds_keep_rows_indexes_and_triggers() {
+        let conn = Connection::open_in_memory().unwrap();
+        DbService::configure(&conn).unwrap();
+        // Stop just before the tasks rebuild of version 3
+        for migrate in &MIGRATIONS[..2] {
+            migrate(&conn).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 2).unwrap();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, path, created_at, last_modified_at) VALUES ('p1', 'Kept', '/kept', 1, 1);
+             INSERT INTO tasks (id, project_id, title, status, priority, created_at, updated_at, task_key)
+             VALUES ('t1', 'p1', 'Parent', 'todo', 'high', 1, 1, 'K-1');
+             INSERT INTO tasks (id, project_id, parent_id, title, status, priority, created_at, updated_at, task_key)
+             VALUES ('t2', 'p1', 't1', 'Child', 'done', 'low', 1, 1, 'K-2');
+             INSERT INTO notes (id, project_id, title, content, created_at, updated_at, is_pinned, pin_order)
+             VALUES ('n1', 'p1', 'Pinned', 'Body', 1, 1, 1, 0);
+             CREATE INDEX idx_tasks_mine ON tasks(due_date);
+             CREATE INDEX idx_notes_mine ON notes(updated_at);
+             CREATE TRIGGER tasks_touch_project AFTER UPDATE OF title ON tasks
+             BEGIN UPDATE projects SET last_modified_at = 42 WHERE id = NEW.project_id; END;
+             CREATE TRIGGER notes_touch_project AFTER UPDATE OF title ON notes
+             BEGIN UPDATE projects SET last_modified_at = 43 WHERE id = NEW.project_id; END;",
+        )
+        .unwrap();
+        let tasks_before = schema_objects(&conn, "tasks");
+        let notes_before = schema_objects(&conn, "notes");
+
+        DbService::init(&conn).unwrap();
+
+        let tasks_after = schema_objects(&conn, "tasks");
+        let notes_after = schema_objects(&conn, "notes");
+        assert!(tasks_before.iter().all(|name| tasks_after.contains(name)), "{:?} lost from {:?}", tasks_before, tasks_after);
+        assert!(notes_before.iter().all(|name| notes_after.contains(name)), "{:?} lost from {:?}", notes_before, notes_after);
+        assert!(tasks_after.iter().any(|name| name == "idx_tasks_parent"));
+        assert!(notes_after.iter().any(|name| name == "idx_notes_inbox"));
+
+        let child = DbService::get_task_by_id(&conn, "t2").unwrap().unwrap();
+        assert_eq!((child.parent_id.as_deref(), child.task_key.as_deref(), child.status.as_str()), (Some("t1"), Some("K-2"), "done"));
+        let pinned = DbService::get_note_by_id(&conn, "n1").unwrap().unwrap();
+        assert!(pinned.is_pinned);
+        assert_eq!(pinned.content, "Body");
+
+        // The restored triggers still fire on the rebuilt tables
+        let touched = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT last_modified_at FROM projects WHERE id = 'p1'", [], |row| row.get(0)).unwrap()
+        };
+        conn.execute("UPDATE tasks SET title = 'Renamed' WHERE id = 't1'", []).unwrap();
+        assert_eq!(touched(&conn), 42);
+        conn.execute("UPDATE notes SET title = 'Renamed' WHERE id = 'n1'", []).unwrap();
+        assert_eq!(touched(&conn), 43);
+        // The rebuilt notes table takes inbox notes
+        DbService::insert_note(&conn, &test_support::new_note(None, "Inbox", "")).unwrap();
+    }
+
+    #[test]
+    fn task_filter_fields_narrow_alone_and_combined() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "filters");
+        let insert = |title: &str, status: &str, priority: TaskPriority, tags: &[&str], due_date: Option<i64>, parent_id: Option<&str>| {
+            let mut task = test_support::new_task(&p.id, title);
+            task.status = status.to_string();
+            task.priority = priority;
+            task.tags = Some(tags.iter().map(|t| t.to_string()).collect());
+            task.due_date = due_date;
+            task.parent_id = parent_id.map(str::to_string);
+            task.completed_at = (status == "done").then_some(1);
+            DbService::insert_task_with_key(&conn, &mut task).unwrap();
+            task
+        };
+        let root = insert("Run experiments", "in_progress", TaskPriority::High, &["method/bayesian"], Some(100), None);
+        insert("Collect data", "done", TaskPriority::Low, &["data"], Some(50), Some(&root.id));
+        insert("Fit 100% of models", "todo", TaskPriority::High, &["Method", "data"], None, Some(&root.id));
+        insert("Write up", "todo", TaskPriority::Medium, &[], Some(200), None);
+        let archived = insert("Old run", "done", TaskPriority::Low, &["data"], None, None);
+        conn.execute("UPDATE tasks SET is_archived = 1 WHERE id = ?1", params![archived.id]).unwrap();
+
+        let titles = |filter: TaskFilterDto| -> Vec<String> {
+            let page = DbService::filter_tasks(&conn, &p.id, &filter, &ListOptions::default()).unwrap();
+            assert_eq!(page.total_count as usize, page.items.len());
+            let mut titles: Vec<String> = page.items.into_iter().map(|t| t.title).collect();
+            titles.sort();
+            titles
+        };
+        let strings = |items: &[&str]| Some(items.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+
+        // An empty filter lists what list_tasks lists
+        let all = DbService::get_tasks_page(&conn, &p.id, &ListOptions::default()).unwrap();
+        let mut listed: Vec<String> = all.items.into_iter().map(|t| t.title).collect();
+        listed.sort();
+        assert_eq!(titles(TaskFilterDto::default()), listed);
+        assert_eq!(listed.len(), 4);
+
+        // Each field alone
+        assert_eq!(titles(TaskFilterDto { statuses: strings(&["todo", "done"]), ..Default::default() }), ["Collect data", "Fit 100% of models", "Write up"]);
+        assert_eq!(titles(TaskFilterDto { priorities: Some(vec![TaskPriority::High]), ..Default::default() }), ["Fit 100% of models", "Run experiments"]);
+        assert_eq!(titles(TaskFilterDto { tags: strings(&["method"]), ..Default::default() }), ["Fit 100% of models", "Run experiments"]);
+        assert_eq!(titles(TaskFilterDto { tags: strings(&["method", "data"]), match_all_tags: true, ..Default::default() }), ["Fit 100% of models"]);
+        assert_eq!(titles(TaskFilterDto { due_before: Some(100), ..Default::default() }), ["Collect data"]);
+        assert_eq!(titles(TaskFilterDto { due_after: Some(100), ..Default::default() }), ["Run experiments", "Write up"]);
+        assert_eq!(titles(TaskFilterDto { has_due_date: Some(false), ..Default::default() }), ["Fit 100% of models"]);
+        assert_eq!(titles(TaskFilterDto { parent: Some(TaskParentFilter::Root), ..Default::default() }), ["Run experiments", "Write up"]);
+        assert_eq!(
+            titles(TaskFilterDto { parent: Some(TaskParentFilter::ParentId(root.id.clone())), ..Default::default() }),
+            ["Collect data", "Fit 100% of models"]
+        );
+        assert_eq!(titles(TaskFilterDto { query: Some("100%".into()), ..Default::default() }), ["Fit 100% of models"]);
+        assert_eq!(titles(TaskFilterDto { include_completed: Some(false), ..Default::default() }).len(), 3);
+        assert_eq!(titles(TaskFilterDto { include_archived: Some(true), ..Default::default() }).len(), 5);
+
+        // Combinations
+        assert_eq!(
+            titles(TaskFilterDto { tags: strings(&["data"]), include_completed: Some(false), ..Default::default() }),
+            ["Fit 100% of models"]
+        );
+        assert_eq!(
+            titles(TaskFilterDto {
+                parent: Some(TaskParentFilter::Root),
+                has_due_date: Some(true),
+                priorities: Some(vec![TaskPriority::Medium, TaskPriority::High]),
+                due_before: Some(150),
+                ..Default::default()
+            }),
+            ["Run experiments"]
+        );
+        assert_eq!(
+            titles(TaskFilterDto { statuses: strings(&["done"]), include_archived: Some(true), tags: strings(&["DATA"]), ..Default::default() }),
+            ["Collect data", "Old run"]
+        );
+        assert!(titles(TaskFilterDto { statuses: strings(&["todo"]), query: Some("experiments".into()), ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn task_filter_pages_and_sorts() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "pages");
+        for i in 0..5 {
+            let mut t = test_support::new_task(&p.id, &format!("Task {}", i));
+            t.due_date = Some(i);
+            DbService::insert_task_with_key(&conn, &mut t).unwrap();
+        }
+        let options = ListOptions { limit: Some(2), offset: Some(1), sort_by: Some(ListSortField::Title), sort_order: Some(SortOrder::Desc) };
+        let filter = TaskFilterDto { has_due_date: Some(true), ..Default::default() };
+        let page = DbService::filter_tasks(&conn, &p.id, &filter, &options).unwrap();
+        assert_eq!(page.total_count, 5);
+        let titles: Vec<&str> = page.items.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["Task 3", "Task 2"]);
+    }
+
+    fn count(conn: &Connection, sql: &str, id: &str) -> i64 {
+        conn.query_row(sql, [id], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn with_tx_rolls_back_when_a_later_statement_fails() {
+        let conn = test_support::open_db();
+        let fresh = Project {
+            id: "fresh".to_string(),
+            tags: Some(vec!["method".to_string()]),
+            ..test_support::new_project("fresh")
+        };
+
+        let result = DbService::with_tx(&conn, |tx| {
+            DbService::insert_project_row(tx, &fresh)?;
+            // Same id as the first insert: the primary key rejects it
+            DbService::insert_project_row(tx, &fresh)
+        });
+        assert!(result.is_err());
+        assert!(DbService::get_project_by_id(&conn, "fresh").unwrap().is_none());
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM project_tags WHERE project_id = ?1", "fresh"), 0);
+        assert!(conn.is_autocommit(), "the transaction is closed");
+
+        DbService::with_tx(&conn, |tx| DbService::insert_project_row(tx, &fresh)).unwrap();
+        assert_eq!(DbService::get_project_by_id(&conn, "fresh").unwrap().unwrap().tags, fresh.tags);
+    }
+
+    #[test]
+    fn nested_with_tx_rolls_back_only_its_own_work() {
+        let conn = test_support::open_db();
+        let with_id = |id: &str| Project { id: id.to_string(), ..test_support::new_project(id) };
+
+        DbService::with_tx(&conn, |tx| {
+            DbService::insert_project(tx, &with_id("outer"))?;
+            let inner = DbService::with_tx(tx, |tx| {
+                DbService::insert_project(tx, &with_id("inner"))?;
+                Err::<(), _>(AppError::Conflict("stop".into()))
+            });
+            assert!(inner.is_err());
+            assert!(!tx.is_autocommit(), "the outer transaction is still open");
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(DbService::get_project_by_id(&conn, "outer").unwrap().is_some());
+        assert!(DbService::get_project_by_id(&conn, "inner").unwrap().is_none());
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM activity_log WHERE entity_id = ?1", "inner"), 0);
+    }
+
+    #[test]
+    fn update_project_applies_all_or_nothing() {
+        let conn = test_support::open_db();
+        let p = project(&conn, "thesis");
+        let tags = vec!["nlp".to_string()];
+
+        assert!(!DbService::update_project(&conn, "missing", Some("x"), None, None, None, None).unwrap());
+        let stale = Some(p.last_modified_at - 1);
+        assert!(!DbService::update_project(&conn, &p.id, Some("stale"), None, None, None, stale).unwrap());
+        assert_eq!(DbService::get_project_by_id(&conn, &p.id).unwrap().unwrap().name, "thesis");
+
+        // The activity entry is the last statement of the update; when it fails, nothing is kept
+        conn.execute_batch(
+            "CREATE TEMP TRIGGER fail_activity BEFORE INSERT ON activity_log BEGIN SELECT RAISE(ABORT, 'disk full'); END;",
+        )
+        .unwrap();
+        assert!(DbService::update_project(&conn, &p.id, Some("renamed"), None, None, Some(&tags), None).is_err());
+        let stored = DbService::get_project_by_id(&conn, &p.id).unwrap().unwrap();
+        assert_eq!((stored.name.as_str(), stored.tags), ("thesis", None));
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM project_tags WHERE project_id = ?1", &p.id), 0);
+
+        conn.execute_batch("DROP TRIGGER fail_activity").unwrap();
+        assert!(DbService::update_project(&conn, &p.id, Some("renamed"), None, None, Some(&tags), None).unwrap());
+        let stored = DbService::get_project_by_id(&conn, &p.id).unwrap().unwrap();
+        assert_eq!((stored.name.as_str(), stored.tags), ("renamed", Some(tags)));
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM project_tags WHERE project_id = ?1", &p.id), 1);
+    }
+
+    #[test]
+    fn version_19_adds_the_startup_indexes_and_queries_use_them() {
+        let startup_indexes = ["idx_notes_project_pinned", "idx_tasks_due_date", "idx_tasks_project_status"];
+        // Stop just before migrate_startup_indexes, the 19th migration
+        let version = 18;
+
+        let conn = Connection::open_in_memory().unwrap();
+        DbService::configure(&conn).unwrap();
+        for migrate in &MIGRATIONS[..version] {
+            migrate(&conn).unwrap();
+        }
+        conn.pragma_update(None, "user_version", version as i64).unwrap();
+        let present = |conn: &Connection| -> Vec<String> {
+            let mut names = schema_objects(conn, "tasks");
+            names.extend(schema_objects(conn, "notes"));
+            names.retain(|name| startup_indexes.contains(&name.as_str()));
+            names.sort();
+            names
+        };
+        assert!(present(&conn).is_empty());
+
+        DbService::init(&conn).unwrap();
+        assert_eq!(present(&conn), startup_indexes);
+        assert!(DbService::current_schema_version(&conn).unwrap() >= 19);
+
+        let plan = |sql: &str| -> String {
+            let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql)).unwrap();
+            let details: Vec<String> = stmt.query_map([], |row| row.get(3)).unwrap().filter_map(|r| r.ok()).collect();
+            details.join("; ")
+        };
+        let pinned = plan("SELECT id FROM notes WHERE project_id = 'p' AND is_pinned = 1 ORDER BY updated_at DESC");
+        assert!(pinned.contains("idx_notes_project_pinned") && !pinned.contains("TEMP B-TREE"), "{}", pinned);
+        let due = plan("SELECT id FROM tasks WHERE due_date < 1000");
+        assert!(due.contains("idx_tasks_due_date"), "{}", due);
+        let counts = plan(
+            "SELECT project_id, SUM(status = 'done') FROM tasks WHERE NOT is_archived AND due_date IS NOT NULL GROUP BY project_id",
+        );
+        assert!(counts.contains("COVERING INDEX idx_tasks_project_status"), "{}", counts);
+    }
 }
@@ -0,0 +1,420 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateDeadlineDto, Deadline, DeadlineImportReport, SkippedItem, TodayView, UpdateDeadlineDto, SETTING_OFFLINE_MODE,
+};
+use crate::services::{DbService, SettingsService};
+use crate::state::AppState;
+use crate::utils::{http, timezone};
+use crate::utils::validate::Validate;
+use chrono::{Local, TimeZone};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Timezone assumed for feed entries that do not state one. AoE is the latest
+/// timezone, so the stored instant is never earlier than the real deadline.
+const DEFAULT_FEED_TIMEZONE: &str = "AoE";
+
+/// Days after today whose deadlines the today view lists by default
+const TODAY_VIEW_DEFAULT_DAYS: i64 = 14;
+
+/// Deadline service for business logic
+pub struct DeadlineService;
+
+impl DeadlineService {
+    /// Create a new deadline
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_deadline(state: &AppState, mut data: CreateDeadlineDto) -> AppResult<Deadline> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            let deadline = Deadline {
+                id: Uuid::new_v4().to_string(),
+                project_id: data.project_id.filter(|id| !id.is_empty()),
+                name: data.name,
+                date: data.date,
+                url: data.url,
+                notes: data.notes,
+                created_at: chrono::Utc::now().timestamp(),
+            };
+
+            if let Some(project_id) = &deadline.project_id {
+                if DbService::get_project_by_id(conn, project_id)?.is_none() {
+                    return Err(AppError::NotFound("Project", project_id.clone()));
+                }
+            }
+            DbService::with_busy_retry(|| DbService::insert_deadline(conn, &deadline))?;
+            Ok(deadline)
+        }).await
+    }
+
+    /// List deadlines for a project (including global ones), or all deadlines
+    #[instrument(level = "debug", skip_all, fields(project_id = ?project_id))]
+    pub async fn list_deadlines(state: &AppState, project_id: Option<String>, include_past: bool) -> AppResult<Vec<Deadline>> {
+        state.run(move |conn| {
+            let from = if include_past { None } else { Some(chrono::Utc::now().timestamp()) };
+
+            DbService::get_deadlines(conn, project_id.as_deref(), from, None)
+        }).await
+    }
+
+    /// List deadlines falling within the next `days` days, soonest first
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_upcoming_deadlines(state: &AppState, days: i64, include_past: bool) -> AppResult<Vec<Deadline>> {
+        state.run(move |conn| {
+            if days <= 0 {
+                return Err(AppError::InvalidInput("Days must be greater than 0".into()));
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            let from = if include_past { None } else { Some(now) };
+            let to = now.saturating_add(days.saturating_mul(86_400));
+
+            DbService::get_deadlines(conn, None, from, Some(to))
+        }).await
+    }
+
+    /// Open tasks overdue or due before the end of today, today's deadlines still
+    /// ahead, and the deadlines of the next `days` days (14 by default). Today
+    /// ends at local midnight; `now` defaults to the current time.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_today_view(state: &AppState, days: Option<i64>, now: Option<i64>) -> AppResult<TodayView> {
+        state.run(move |conn| {
+            let days = days.unwrap_or(TODAY_VIEW_DEFAULT_DAYS);
+            if days <= 0 {
+                return Err(AppError::InvalidInput("Days must be greater than 0".into()));
+            }
+
+            let now = now.unwrap_or_else(|| chrono::Utc::now().timestamp());
+            let end_of_today = Self::end_of_local_day(now);
+            let horizon = now.saturating_add(days.saturating_mul(86_400)).max(end_of_today);
+
+            Ok(TodayView {
+                overdue_tasks: DbService::get_due_tasks(conn, None, now)?,
+                tasks_due_today: DbService::get_due_tasks(conn, Some(now), end_of_today)?,
+                deadlines_today: DbService::get_deadlines(conn, None, Some(now), Some(end_of_today))?,
+                upcoming_deadlines: DbService::get_deadlines(conn, None, Some(end_of_today + 1), Some(horizon))?,
+            })
+        }).await
+    }
+
+    /// Last second of the local day `timestamp` falls on
+    fn end_of_local_day(timestamp: i64) -> i64 {
+        let Some(instant) = chrono::DateTime::from_timestamp(timestamp, 0) else {
+            return timestamp;
+        };
+        let tomorrow = instant.with_timezone(&Local).date_naive().succ_opt().and_then(|day| day.and_hms_opt(0, 0, 0));
+        tomorrow
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+            .map_or(timestamp, |midnight| midnight.timestamp() - 1)
+    }
+
+    /// Get deadline by ID
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_deadline(state: &AppState, id: String) -> AppResult<Deadline> {
+        state.run(move |conn| {
+            let deadline = DbService::get_deadline_by_id(conn, &id)?;
+            deadline.ok_or(AppError::NotFound("Deadline", id))
+        }).await
+    }
+
+    /// Update deadline
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_deadline(state: &AppState, id: String, mut data: UpdateDeadlineDto) -> AppResult<Deadline> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            let updated = DbService::with_busy_retry(|| DbService::update_deadline(
+                conn,
+                &id,
+                data.name.as_deref(),
+                data.date,
+                data.url.as_deref(),
+                data.notes.as_deref(),
+            ))?;
+            if !updated {
+                return Err(AppError::NotFound("Deadline", id));
+            }
+
+            let deadline = DbService::get_deadline_by_id(conn, &id)?;
+            deadline.ok_or(AppError::NotFound("Deadline", id))
+        }).await
+    }
+
+    /// Delete deadline
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_deadline(state: &AppState, id: String) -> AppResult<()> {
+        state.run(move |conn| {
+            if !DbService::with_busy_retry(|| DbService::delete_deadline(conn, &id))? {
+                return Err(AppError::NotFound("Deadline", id));
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Import deadlines from an ai-deadlines style feed: a YAML or JSON list read
+    /// from a file or fetched over http(s), unless offline mode is on.
+    ///
+    /// Each entry provides `title`, `year`, `deadline`, optional `abstract_deadline`,
+    /// `timezone` and `link`. Timezones are AoE, UTC or GMT offsets and common
+    /// abbreviations; entries in a region zone such as "America/New_York" are
+    /// skipped with the reason. Entries whose name (title + year) already exists
+    /// are reported as duplicates. With `dry_run` nothing is written.
+    #[instrument(level = "debug", skip_all, fields(project_id = ?project_id))]
+    pub async fn import_deadlines_feed(
+        state: &AppState,
+        url_or_path: String,
+        project_id: Option<String>,
+        dry_run: bool,
+    ) -> AppResult<DeadlineImportReport> {
+        state.blocking(move |state| {
+            let raw = if http::is_url(&url_or_path) {
+                let offline = SettingsService::get_bool(&*state.conn()?, SETTING_OFFLINE_MODE)?;
+                http::get_text(&url_or_path, offline)?
+            } else {
+                fs::read_to_string(&url_or_path)?
+            };
+            let feed = Self::parse_feed(&raw)?;
+            let entries = feed
+                .as_array()
+                .ok_or_else(|| AppError::InvalidInput("Deadline feed must be a list of entries".into()))?;
+
+            let project_id = project_id.filter(|id| !id.is_empty());
+            let now = chrono::Utc::now().timestamp();
+            let mut candidates = Vec::new();
+            let mut skipped = Vec::new();
+
+            for (index, entry) in entries.iter().enumerate() {
+                match Self::parse_feed_entry(entry) {
+                    Ok(parsed) => {
+                        for (name, date, url) in parsed {
+                            candidates.push(Deadline {
+                                id: Uuid::new_v4().to_string(),
+                                project_id: project_id.clone(),
+                                name,
+                                date,
+                                url,
+                                notes: None,
+                                created_at: now,
+                            });
+                        }
+                    }
+                    Err(reason) => skipped.push(SkippedItem {
+                        id: Self::entry_label(entry, index),
+                        reason,
+                    }),
+                }
+            }
+
+            let conn = &state.conn()?;
+
+            if let Some(project_id) = &project_id {
+                if DbService::get_project_by_id(conn, project_id)?.is_none() {
+                    return Err(AppError::NotFound("Project", project_id.clone()));
+                }
+            }
+
+            let mut seen = HashSet::new();
+            let mut deadlines = Vec::new();
+            let mut duplicates = Vec::new();
+
+            let tx = conn.unchecked_transaction()?;
+            for deadline in candidates {
+                let is_new = seen.insert(deadline.name.to_lowercase());
+                if !is_new || DbService::deadline_name_exists(&tx, &deadline.name)? {
+                    duplicates.push(deadline.name);
+                    continue;
+                }
+                if !dry_run {
+                    DbService::insert_deadline(&tx, &deadline)?;
+                }
+                deadlines.push(deadline);
+            }
+            tx.commit()?;
+
+            Ok(DeadlineImportReport {
+                dry_run,
+                deadlines,
+                duplicates,
+                skipped,
+            })
+        }).await
+    }
+
+    /// Read a feed as JSON, or as YAML when it is not JSON
+    fn parse_feed(raw: &str) -> AppResult<Value> {
+        if let Ok(feed) = serde_json::from_str(raw) {
+            return Ok(feed);
+        }
+        serde_yaml::from_str(raw)
+            .map_err(|e| AppError::InvalidInput(format!("Deadline feed is neither JSON nor YAML: {}", e)))
+    }
+
+    /// Map one feed entry to (name, utc timestamp, link) tuples for its deadlines
+    fn parse_feed_entry(entry: &Value) -> Result<Vec<(String, i64, Option<String>)>, String> {
+        let title = entry
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or("Missing title")?;
+
+        let year = match entry.get("year") {
+            Some(Value::Number(n)) => n.as_i64().map(|y| y.to_string()),
+            Some(Value::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            _ => None,
+        };
+
+        let name = match &year {
+            Some(year) if !title.ends_with(year.as_str()) => format!("{} {}", title, year),
+            _ => title.to_string(),
+        };
+
+        let tz = entry
+            .get("timezone")
+            .and_then(Value::as_str)
+            .unwrap_or(DEFAULT_FEED_TIMEZONE);
+        let offset = timezone::parse_utc_offset(tz)
+            .ok_or_else(|| format!("Unsupported timezone '{}'; use AoE, a UTC offset such as UTC-5 or an abbreviation", tz))?;
+
+        let link = entry
+            .get("link")
+            .and_then(Value::as_str)
+            .map(|l| l.to_string());
+
+        let deadline = entry
+            .get("deadline")
+            .and_then(Value::as_str)
+            .ok_or("Missing deadline")?;
+        let date = timezone::parse_local_datetime(deadline, offset)
+            .ok_or_else(|| format!("Unrecognized deadline '{}'", deadline))?;
+
+        let mut parsed = vec![(name.clone(), date, link.clone())];
+
+        if let Some(abstract_deadline) = entry.get("abstract_deadline").and_then(Value::as_str) {
+            if let Some(date) = timezone::parse_local_datetime(abstract_deadline, offset) {
+                parsed.push((format!("{} (abstract)", name), date, link));
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn entry_label(entry: &Value, index: usize) -> String {
+        entry
+            .get("title")
+            .and_then(Value::as_str)
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| format!("entry {}", index + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use rusqlite::Connection;
+
+    fn deadline(conn: &Connection, project_id: Option<&str>, name: &str, date: i64) {
+        let deadline = Deadline {
+            id: Uuid::new_v4().to_string(),
+            project_id: project_id.map(str::to_string),
+            name: name.to_string(),
+            date,
+            url: None,
+            notes: None,
+            created_at: 0,
+        };
+        DbService::insert_deadline(conn, &deadline).unwrap();
+    }
+
+    fn names(deadlines: &[Deadline]) -> Vec<&str> {
+        deadlines.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn today_view_splits_tasks_and_deadlines_around_today() {
+        let state = test_support::open_state();
+        let noon = Local::now().date_naive().and_hms_opt(12, 0, 0).unwrap();
+        let now = Local.from_local_datetime(&noon).earliest().unwrap().timestamp();
+        let hour = 3600;
+        let day = 86_400;
+        {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            for (title, due) in [("late", now - day), ("tonight", now + 6 * hour), ("next week", now + 7 * day)] {
+                let mut task = test_support::new_task(&project.id, title);
+                task.due_date = Some(due);
+                DbService::insert_task_with_key(conn, &mut task).unwrap();
+            }
+            deadline(conn, Some(&project.id), "this morning", now - 2 * hour);
+            deadline(conn, None, "CHI abstract", now + 11 * hour);
+            deadline(conn, Some(&project.id), "CHI paper", now + 8 * day);
+            deadline(conn, None, "next year", now + 300 * day);
+        }
+
+        let view = DeadlineService::get_today_view(&state, None, Some(now)).await.unwrap();
+        let titles = |tasks: &[crate::models::TaskWithProject]| tasks.iter().map(|t| t.task.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&view.overdue_tasks), vec!["late"]);
+        assert_eq!(titles(&view.tasks_due_today), vec!["tonight"]);
+        assert_eq!(names(&view.deadlines_today), vec!["CHI abstract"]);
+        assert_eq!(names(&view.upcoming_deadlines), vec!["CHI paper"]);
+
+        let view = DeadlineService::get_today_view(&state, Some(7), Some(now)).await.unwrap();
+        assert!(view.upcoming_deadlines.is_empty());
+        assert!(DeadlineService::get_today_view(&state, Some(0), Some(now)).await.is_err());
+    }
+
+    fn feed_file(contents: &str, extension: &str) -> String {
+        let path = test_support::temp_dir().join(format!("feed.{}", extension));
+        fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn yaml_feeds_are_imported_with_aoe_converted_to_utc() {
+        let state = test_support::open_state();
+        let feed = feed_file(
+            "- title: CHI\n  year: 2026\n  deadline: '2025-09-11 23:59'\n  abstract_deadline: '2025-09-04 23:59'\n  timezone: AoE\n  link: https://chi2026.acm.org\n\
+             - title: NeurIPS\n  year: 2025\n  deadline: 2025-05-15 20:00:00\n  timezone: UTC+2\n\
+             - title: ICML\n  year: 2025\n  deadline: '2025-01-30 23:59'\n  timezone: America/New_York\n",
+            "yml",
+        );
+
+        let report = DeadlineService::import_deadlines_feed(&state, feed.clone(), None, true).await.unwrap();
+        let imported: Vec<(&str, i64)> = report.deadlines.iter().map(|d| (d.name.as_str(), d.date)).collect();
+        assert_eq!(imported, vec![
+            // 2025-09-11 23:59 at UTC-12 is 2025-09-12 11:59 UTC
+            ("CHI 2026", 1_757_678_340),
+            ("CHI 2026 (abstract)", 1_757_073_540),
+            ("NeurIPS 2025", 1_747_332_000),
+        ]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].id, "ICML");
+        assert!(report.skipped[0].reason.contains("America/New_York"));
+        assert!(DbService::get_deadlines(&state.conn().unwrap(), None, None, None).unwrap().is_empty());
+
+        let report = DeadlineService::import_deadlines_feed(&state, feed.clone(), None, false).await.unwrap();
+        assert_eq!(report.deadlines.len(), 3);
+        let again = DeadlineService::import_deadlines_feed(&state, feed, None, false).await.unwrap();
+        assert!(again.deadlines.is_empty());
+        assert_eq!(again.duplicates, vec!["CHI 2026", "CHI 2026 (abstract)", "NeurIPS 2025"]);
+    }
+
+    #[tokio::test]
+    async fn json_feeds_still_parse_and_offline_mode_blocks_urls() {
+        let state = test_support::open_state();
+        let feed = feed_file(r#"[{"title": "UIST", "year": "2025", "deadline": "2025-04-08"}]"#, "json");
+        let report = DeadlineService::import_deadlines_feed(&state, feed, None, true).await.unwrap();
+        assert_eq!(report.deadlines[0].name, "UIST 2025");
+
+        let bad = feed_file("title: [unclosed", "yml");
+        assert!(matches!(DeadlineService::import_deadlines_feed(&state, bad, None, true).await, Err(AppError::InvalidInput(_))));
+
+        SettingsService::set_bool(&state.conn().unwrap(), SETTING_OFFLINE_MODE, true).unwrap();
+        let fetched = DeadlineService::import_deadlines_feed(&state, "https://example.com/feed.yml".into(), None, true).await;
+        assert!(matches!(fetched, Err(AppError::Network(_))));
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::instrument;
+
+use chrono::Datelike;
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::error::AppResult;
+use crate::models::{
+    CreateNoteDto, ProjectSort, WeeklyDigest, SETTING_LAST_DIGEST_AT, SETTING_WEEKLY_DIGEST_ENABLED, SETTING_WEEKLY_DIGEST_PROJECT_ID,
+    SETTING_WEEKLY_DIGEST_WEEKDAY,
+};
+use crate::services::{DbService, NoteService, SettingsService};
+use crate::state::AppState;
+use crate::utils::word_count;
+
+/// Event sent with the `WeeklyDigest` when the scheduler has generated one
+pub const WEEKLY_DIGEST_READY_EVENT: &str = "weekly-digest-ready";
+
+/// App setting holding the word count of every note at the last digest, by
+/// note ID, to measure how much notes grew since
+const DIGEST_WORD_COUNTS_KEY: &str = "weekly_digest_word_counts";
+
+/// Time between two checks for whether the digest is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(60 * 60);
+
+/// Period of the first digest, when there is no earlier one
+const DEFAULT_PERIOD_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// How far ahead deadlines are listed
+const UPCOMING_DEADLINE_SECS: i64 = 14 * 24 * 60 * 60;
+
+/// A note grew significantly when it gained more than this share of its words…
+const GROWTH_SHARE: f64 = 0.2;
+
+/// …or more than this many words
+const GROWTH_WORDS: i64 = 500;
+
+/// Weekly summary of what changed across the workspace
+pub struct DigestService;
+
+impl DigestService {
+    /// Generate the digest on the configured weekday for as long as the app
+    /// runs, once per day at most, and send it to the windows
+    pub async fn run_scheduler<R: Runtime>(app: AppHandle<R>) {
+        loop {
+            let state = app.state::<AppState>().inner().clone();
+            let now = chrono::Utc::now().timestamp();
+            match state.run(move |conn| Self::is_due(conn, now)).await {
+                Ok(true) => match Self::generate_weekly_digest(&state, None).await {
+                    Ok(digest) => {
+                        if let Err(e) = app.emit(WEEKLY_DIGEST_READY_EVENT, &digest) {
+                            tracing::warn!("Failed to emit {}: {}", WEEKLY_DIGEST_READY_EVENT, e);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to generate the weekly digest: {}", e),
+                },
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to check whether the weekly digest is due: {}", e),
+            }
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    }
+
+    /// Summarize, as Markdown, what changed since the last digest: per project
+    /// the tasks completed and created, notes that grew by more than 20% or 500
+    /// words and new references, then the deadlines of the next two weeks. The
+    /// digest is saved as a note of `save_to_project_id`, by default the
+    /// weekly_digest_project_id setting, and becomes the start of the next one.
+    #[instrument(level = "debug", skip_all, fields(save_to_project_id = ?save_to_project_id))]
+    pub async fn generate_weekly_digest(state: &AppState, save_to_project_id: Option<String>) -> AppResult<WeeklyDigest> {
+        let (mut digest, project_id, mut counts) = state.run(move |conn| {
+            let now = chrono::Utc::now().timestamp();
+            let last = SettingsService::get_i64(conn, SETTING_LAST_DIGEST_AT)?;
+            let since = if last > 0 { last } else { now - DEFAULT_PERIOD_SECS };
+            let offset = SettingsService::utc_offset(conn, now)?;
+            let project_id = match save_to_project_id {
+                Some(id) => id,
+                None => SettingsService::get_string(conn, SETTING_WEEKLY_DIGEST_PROJECT_ID)?,
+            };
+            let (markdown, counts) = Self::render(conn, since, now, offset)?;
+            let digest = WeeklyDigest { since, generated_at: now, markdown, note_id: None };
+            Ok((digest, project_id.trim().to_string(), counts))
+        }).await?;
+
+        if !project_id.is_empty() {
+            let title = format!("Weekly digest {}", Self::format_date(digest.generated_at, 0));
+            let note = CreateNoteDto { project_id, title, content: digest.markdown.clone(), tags: None, is_pinned: None };
+            let note = NoteService::create_note(state, note).await?;
+            // The digest itself is not news in the next one
+            counts.insert(note.id.clone(), word_count::word_count(&note.content));
+            digest.note_id = Some(note.id);
+        }
+
+        let generated_at = digest.generated_at;
+        let counts = serde_json::to_string(&counts)?;
+        state.run(move |conn| {
+            DbService::with_busy_retry(|| {
+                DbService::with_tx(conn, |conn| {
+                    DbService::set_app_setting(conn, DIGEST_WORD_COUNTS_KEY, &counts)?;
+                    SettingsService::set_i64(conn, SETTING_LAST_DIGEST_AT, generated_at)
+                })
+            })
+        }).await?;
+        Ok(digest)
+    }
+
+    /// Whether the scheduler should generate a digest at `now`: it is turned
+    /// on, today is the configured weekday and none was generated today
+    fn is_due(conn: &Connection, now: i64) -> AppResult<bool> {
+        if !SettingsService::get_bool(conn, SETTING_WEEKLY_DIGEST_ENABLED)? {
+            return Ok(false);
+        }
+        let offset = SettingsService::utc_offset(conn, now)?;
+        let local_date = |timestamp: i64| {
+            chrono::DateTime::from_timestamp(timestamp + offset as i64, 0).unwrap_or_default().date_naive()
+        };
+        let weekday = SettingsService::get_i64(conn, SETTING_WEEKLY_DIGEST_WEEKDAY)?;
+        let last = SettingsService::get_i64(conn, SETTING_LAST_DIGEST_AT)?;
+        let today = local_date(now);
+        Ok(today.weekday().number_from_monday() as i64 == weekday && (last == 0 || local_date(last) < today))
+    }
+
+    /// The digest text and the current word count of every note
+    fn render(conn: &Connection, since: i64, now: i64, offset: i32) -> AppResult<(String, HashMap<String, i64>)> {
+        let previous_counts: HashMap<String, i64> = DbService::get_app_setting(conn, DIGEST_WORD_COUNTS_KEY)?
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        let mut counts = HashMap::new();
+        let mut sections = Vec::new();
+
+        for project in DbService::get_all_projects(conn, false, ProjectSort::Recent)? {
+            let done = DbService::get_done_status(conn, &project.id)?;
+            let tasks = DbService::get_tasks_by_project(conn, &project.id)?;
+            let completed = tasks
+                .iter()
+                .filter(|task| task.status == done && task.completed_at.is_some_and(|at| at >= since))
+                .count();
+            let created = tasks.iter().filter(|task| task.created_at >= since).count();
+
+            let mut grown = Vec::new();
+            for note in DbService::get_notes_by_project(conn, &project.id)? {
+                let words = word_count::word_count(&note.content);
+                counts.insert(note.id.clone(), words);
+                // Without a count from the last digest, only new notes can be measured
+                let before = match previous_counts.get(&note.id) {
+                    Some(before) => *before,
+                    None if note.created_at >= since => 0,
+                    None => continue,
+                };
+                let growth = words - before;
+                if growth > 0 && (growth > GROWTH_WORDS || growth as f64 > before as f64 * GROWTH_SHARE) {
+                    grown.push((note.title, growth));
+                }
+            }
+            grown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+            let mut references: Vec<_> = DbService::get_references_by_project(conn, &project.id)?
+                .into_iter()
+                .filter(|reference| reference.created_at >= since)
+                .collect();
+            references.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.citation_key.cmp(&b.citation_key)));
+
+            if completed == 0 && created == 0 && grown.is_empty() && references.is_empty() {
+                continue;
+            }
+            let mut section = format!("### {}\n\n", project.name);
+            section.push_str(&format!("- Tasks completed: {}\n- Tasks created: {}\n", completed, created));
+            for (title, growth) in &grown {
+                section.push_str(&format!("- Note grew: {} (+{} words)\n", title, growth));
+            }
+            for reference in &references {
+                section.push_str(&format!("- New reference: {} ({})\n", reference.title, reference.citation_key));
+            }
+            sections.push(section);
+        }
+
+        let mut markdown = format!(
+            "# Weekly digest, {} to {}\n\n## Projects\n\n",
+            Self::format_date(since, offset),
+            Self::format_date(now, offset)
+        );
+        if sections.is_empty() {
+            markdown.push_str("Nothing changed since the last digest.\n");
+        } else {
+            markdown.push_str(&sections.join("\n"));
+        }
+
+        let deadlines = DbService::get_deadlines(conn, None, Some(now), Some(now + UPCOMING_DEADLINE_SECS))?;
+        if !deadlines.is_empty() {
+            markdown.push_str("\n## Upcoming deadlines\n\n");
+            for deadline in deadlines {
+                markdown.push_str(&format!("- {}: {}\n", Self::format_date(deadline.date, offset), deadline.name));
+            }
+        }
+
+        Ok((markdown, counts))
+    }
+
+    fn format_date(timestamp: i64, offset: i32) -> String {
+        chrono::DateTime::from_timestamp(timestamp + offset as i64, 0).unwrap_or_default().format("%Y-%m-%d").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Deadline, UpdateNoteDto};
+    use crate::services::test_support;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn digest_lists_changes_since_the_last_one() {
+        let state = test_support::open_state();
+        let (journal, long_note, short_note) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let journal = test_support::project(conn, "Journal");
+            let mut done = test_support::new_task(&project.id, "Draft outline");
+            done.status = "done".to_string();
+            done.completed_at = Some(chrono::Utc::now().timestamp());
+            DbService::insert_task(conn, &done).unwrap();
+            test_support::reference(conn, &project.id, "doe2020", "On drafting");
+            let long_note = test_support::note(conn, &project.id, "Chapter 1", &"word ".repeat(100));
+            let short_note = test_support::note(conn, &project.id, "Ideas", &"word ".repeat(100));
+            let deadline = Deadline {
+                id: Uuid::new_v4().to_string(),
+                project_id: Some(project.id.clone()),
+                name: "Committee meeting".to_string(),
+                date: chrono::Utc::now().timestamp() + 3 * 24 * 60 * 60,
+                url: None,
+                notes: None,
+                created_at: 0,
+            };
+            DbService::insert_deadline(conn, &deadline).unwrap();
+            (journal, long_note, short_note)
+        };
+
+        let first = DigestService::generate_weekly_digest(&state, Some(journal.id.clone())).await.unwrap();
+        assert!(first.markdown.contains("### Thesis\n\n- Tasks completed: 1\n- Tasks created: 1\n"), "{}", first.markdown);
+        assert!(first.markdown.contains("- New reference: On drafting (doe2020)\n"));
+        assert!(first.markdown.contains("- Note grew: Chapter 1 (+100 words)\n"));
+        assert!(first.markdown.contains("## Upcoming deadlines\n\n"));
+        assert!(first.markdown.contains(": Committee meeting\n"));
+        let saved = DbService::get_note_by_id(&state.conn().unwrap(), first.note_id.as_deref().unwrap()).unwrap().unwrap();
+        assert_eq!(saved.project_id.as_deref(), Some(journal.id.as_str()));
+        assert_eq!(saved.content, first.markdown);
+
+        // Growth is measured against the counts of the first digest
+        let edit = |content: String| UpdateNoteDto {
+            title: None,
+            content: Some(content),
+            tags: None,
+            is_pinned: None,
+            expected_updated_at: None,
+        };
+        DbService::update_note(&state.conn().unwrap(), &long_note.id, &edit("word ".repeat(130))).unwrap();
+        DbService::update_note(&state.conn().unwrap(), &short_note.id, &edit("word ".repeat(110))).unwrap();
+        // Digests start at the second the last one was made; start after this
+        // one so the task and reference above are behind it
+        SettingsService::set_i64(&state.conn().unwrap(), SETTING_LAST_DIGEST_AT, chrono::Utc::now().timestamp() + 1).unwrap();
+
+        let second = DigestService::generate_weekly_digest(&state, Some(String::new())).await.unwrap();
+        assert!(second.note_id.is_none());
+        assert!(
+            second.markdown.contains("### Thesis\n\n- Tasks completed: 0\n- Tasks created: 0\n- Note grew: Chapter 1 (+30 words)\n"),
+            "{}",
+            second.markdown
+        );
+        assert!(!second.markdown.contains("Ideas"));
+        assert!(!second.markdown.contains("### Journal"));
+
+        SettingsService::set_i64(&state.conn().unwrap(), SETTING_LAST_DIGEST_AT, chrono::Utc::now().timestamp() + 1).unwrap();
+        let third = DigestService::generate_weekly_digest(&state, None).await.unwrap();
+        assert!(third.markdown.contains("Nothing changed since the last digest.\n"), "{}", third.markdown);
+    }
+
+    #[test]
+    fn digest_is_due_once_on_the_configured_weekday() {
+        let conn = test_support::open_db();
+        // 2026-10-12 12:00 UTC is a Monday
+        let monday = 1_791_806_400;
+        SettingsService::set_string(&conn, crate::models::SETTING_TIMEZONE, "UTC").unwrap();
+        assert!(!DigestService::is_due(&conn, monday).unwrap());
+
+        SettingsService::set_bool(&conn, SETTING_WEEKLY_DIGEST_ENABLED, true).unwrap();
+        assert!(DigestService::is_due(&conn, monday).unwrap());
+        assert!(!DigestService::is_due(&conn, monday + 24 * 60 * 60).unwrap());
+
+        SettingsService::set_i64(&conn, SETTING_LAST_DIGEST_AT, monday - 60).unwrap();
+        assert!(!DigestService::is_due(&conn, monday).unwrap());
+        assert!(DigestService::is_due(&conn, monday + 7 * 24 * 60 * 60).unwrap());
+    }
+}
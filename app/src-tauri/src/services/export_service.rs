@@ -0,0 +1,2790 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateNoteDto, CsvImportResult, CsvRowError, Deadline, EntityDifference, EntityType, ExportKind, ExportSummary, FieldDiff, HtmlExportSummary, IcalComponent, ImportSummary,
+    MarkdownImportResult, MarkdownImportStatus, Note, NoteAttachment, NoteBundleAttachment, NoteBundleExportSummary,
+    NoteBundleImportSummary, NoteBundleMetadata, ProjectArchive, Reference, SiteExportSummary, Task, TaskPriority,
+    TaskWithProject, VerificationReport, VerifiedEntity, SETTING_ATTACHMENT_MAX_BYTES, SETTING_EXPORT_INCLUDE_BACKLINKS,
+};
+use crate::services::note_attachment_service::ATTACHMENTS_DIR;
+use crate::services::{DbService, GitService, NoteAttachmentService, ProjectService, SettingsService};
+use crate::state::AppState;
+use crate::utils::archive::{ArchiveReader, ArchiveWriter};
+use crate::utils::validate::Validate;
+use crate::utils::{base64, csv, frontmatter, hash, html, ical, markdown, mime, path, text};
+use rusqlite::Connection;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Version written to the `schema_version` field of project archives
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Folder of a project that notes are exported to as Markdown by default
+pub(crate) const NOTES_EXPORT_DIR: &str = "notes";
+
+/// Markers around the "Linked from" section of exported notes
+pub(crate) const BACKLINKS_START: &str = "<!-- research-vault:backlinks:start -->";
+pub(crate) const BACKLINKS_END: &str = "<!-- research-vault:backlinks:end -->";
+
+/// Title given to imported Markdown files whose name yields no title
+const UNTITLED_NOTE: &str = "Untitled";
+
+/// Product identifier written to iCalendar exports
+const ICAL_PRODUCT_ID: &str = "-//Research Vault//Tasks//EN";
+
+/// Domain part of the UIDs of exported tasks
+const ICAL_UID_DOMAIN: &str = "research-vault";
+
+/// Version written to the `format_version` field of note bundles
+const NOTE_BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Entry of a note bundle holding the note as Markdown with frontmatter
+const BUNDLE_NOTE_ENTRY: &str = "note.md";
+
+/// Entry of a note bundle holding its `NoteBundleMetadata`
+const BUNDLE_METADATA_ENTRY: &str = "metadata.json";
+
+/// Folder of a note bundle holding the attachments
+const BUNDLE_ATTACHMENTS_DIR: &str = "attachments";
+
+/// Largest note.md or metadata.json read from a note bundle
+const MAX_BUNDLE_TEXT_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Largest total size of the entries of a note bundle that is imported
+const MAX_BUNDLE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Differences of a verification report listed with their fields
+const VERIFY_DIFF_SAMPLE: usize = 20;
+
+/// Entities of one type compared by verify_export, as (id, label, content)
+type ComparedEntities = Vec<(String, String, Value)>;
+
+/// Largest image embedded into an HTML export; bigger ones become placeholders
+const MAX_HTML_IMAGE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Stylesheet inlined into HTML exports
+const HTML_STYLESHEET: &str = r#"body { font-family: -apple-system, "Segoe UI", Roboto, Helvetica, Arial, sans-serif; line-height: 1.6; color: #1f2328; max-width: 46rem; margin: 2rem auto; padding: 0 1rem; }
+h1, h2, h3, h4, h5, h6 { line-height: 1.25; margin: 1.5em 0 0.5em; }
+a { color: #0969da; }
+.meta { color: #656d76; font-size: 0.9em; margin-top: 0; }
+.tag { background: #eef1f4; border-radius: 0.25em; padding: 0 0.35em; margin-right: 0.25em; }
+.missing-link, .attachment { color: #656d76; font-style: italic; }
+article + article { border-top: 1px solid #d0d7de; margin-top: 2.5rem; }
+pre { background: #f6f8fa; padding: 0.75rem 1rem; overflow-x: auto; border-radius: 6px; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 0.9em; }
+:not(pre) > code { background: #f6f8fa; padding: 0.1em 0.3em; border-radius: 4px; }
+blockquote { margin: 0; padding-left: 1rem; border-left: 0.25rem solid #d0d7de; color: #57606a; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.6rem; }
+img { max-width: 100%; }
+li.task { list-style: none; }
+@media print { nav.toc { break-after: page; } article + article { break-before: page; } }
+"#;
+
+/// Page of a static site export; `{{root}}` is the way back to the site's top folder
+const SITE_PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}}</title>
+<link rel="stylesheet" href="{{root}}style.css">
+</head>
+<body>
+<nav class="site"><a href="{{root}}index.html">{{project}}</a></nav>
+{{body}}</body>
+</html>
+"#;
+
+/// Lists the files a static site export wrote, so the next export of the same
+/// folder replaces or removes those and nothing else
+const SITE_MANIFEST: &str = ".research-vault-site.json";
+
+/// Folder of a static site export holding one page per note
+const SITE_NOTES_DIR: &str = "notes";
+
+/// Folder of a static site export holding copies of the project files the notes show
+const SITE_FILES_DIR: &str = "files";
+
+/// Columns of a reference CSV export, in order; a subset can be chosen per export
+const REFERENCE_CSV_COLUMNS: [&str; 9] = [
+    "cite_key", "title", "authors", "year", "venue", "doi", "reading_status", "tags", "added",
+];
+
+/// Columns of a task CSV export, in order
+const TASK_CSV_COLUMNS: [&str; 10] = [
+    "id", "parent_id", "depth", "position", "title", "description", "status", "priority", "due_date", "tags",
+];
+
+/// Exports projects to portable archives and imports them back
+pub struct ExportService;
+
+impl ExportService {
+    /// Write a project with all of its tasks, notes and tags to `dest_path` as
+    /// one pretty-printed JSON document. Archived projects export like any other.
+    /// The file is written next to its destination first and then moved into
+    /// place, so an interrupted export never leaves a truncated archive behind.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_project(state: &AppState, project_id: String, dest_path: String) -> AppResult<ExportSummary> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            if dest_path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Export path cannot be empty".into()));
+            }
+
+            let dest = Path::new(&dest_path);
+            if dest.is_dir() {
+                return Err(AppError::InvalidInput("Export path must be a file, not a directory".into()));
+            }
+
+            let archive = Self::project_archive(&*state.conn()?, &project_id)?;
+
+            let json = serde_json::to_string_pretty(&archive)?;
+
+            let mut staging = dest.as_os_str().to_owned();
+            staging.push(".partial");
+            if let Err(e) = fs::write(&staging, &json).and_then(|_| fs::rename(&staging, dest)) {
+                let _ = fs::remove_file(&staging);
+                return Err(e.into());
+            }
+
+            Ok(ExportSummary {
+                project_id,
+                path: dest_path,
+                task_count: archive.tasks.len(),
+                note_count: archive.notes.len(),
+                tag_count: archive.tags.len(),
+                bytes_written: json.len() as u64,
+            })
+        }).await
+    }
+
+    /// A project with all of its tasks, notes, statuses and tags, as written by
+    /// export_project
+    fn project_archive(conn: &Connection, project_id: &str) -> AppResult<ProjectArchive> {
+        let project = DbService::get_project_by_id(conn, project_id)?
+            .ok_or_else(|| AppError::NotFound("Project", project_id.to_string()))?;
+
+        // List queries leave metadata out; attach it so the archive is complete
+        let mut task_metadata = DbService::get_project_entity_metadata(conn, EntityType::Task, project_id)?;
+        let mut tasks = DbService::get_tasks_by_project(conn, project_id)?;
+        for task in &mut tasks {
+            task.metadata = task_metadata.remove(&task.id);
+        }
+
+        let mut note_metadata = DbService::get_project_entity_metadata(conn, EntityType::Note, project_id)?;
+        let mut notes = DbService::get_notes_by_project(conn, project_id)?;
+        for note in &mut notes {
+            note.metadata = note_metadata.remove(&note.id);
+        }
+
+        let statuses = DbService::get_project_statuses(conn, project_id)?;
+        let tags = DbService::get_tags_with_counts(conn, Some(project_id))?
+            .into_iter()
+            .map(|usage| usage.tag)
+            .collect();
+
+        Ok(ProjectArchive {
+            schema_version: EXPORT_SCHEMA_VERSION,
+            exported_at: chrono::Utc::now().timestamp(),
+            project,
+            statuses,
+            tasks,
+            notes,
+            tags,
+        })
+    }
+
+    /// Create a new project at `new_path` from an archive written by
+    /// export_project. Every entity gets a fresh ID with parent and project
+    /// references remapped, so an archive can be imported next to its source.
+    /// The database rows are inserted in one transaction; if that fails the
+    /// new directory is removed again.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn import_project(state: &AppState, src_path: String, new_path: String) -> AppResult<ImportSummary> {
+        state.blocking(move |state| {
+            if src_path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Archive path cannot be empty".into()));
+            }
+            if new_path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Project path cannot be empty".into()));
+            }
+
+            let raw = fs::read_to_string(&src_path)?;
+            let mut archive: ProjectArchive = serde_json::from_str(&raw)
+                .map_err(|e| AppError::InvalidInput(format!("Not a valid project archive: {}", e)))?;
+            if archive.schema_version == 0 || archive.schema_version > EXPORT_SCHEMA_VERSION {
+                return Err(AppError::InvalidInput(format!(
+                    "Unsupported archive schema version {} (this version reads up to {})",
+                    archive.schema_version, EXPORT_SCHEMA_VERSION
+                )));
+            }
+
+            {
+                let conn = &state.conn()?;
+                if DbService::project_path_exists(conn, &new_path)? {
+                    return Err(AppError::Conflict("A project with this path already exists".into()));
+                }
+            }
+            if fs::metadata(&new_path).is_ok() {
+                return Err(AppError::Conflict("Project path already exists".into()));
+            }
+
+            let next_task_number = Self::assign_fresh_ids(&mut archive, &new_path);
+
+            ProjectService::create_project_directory(state, &new_path, &archive.project.name, archive.project.description.as_deref(), None)?;
+
+            let inserted = state.conn().and_then(|conn| {
+                DbService::with_busy_retry(|| DbService::insert_project_archive(&conn, &archive, next_task_number))
+            });
+            if let Err(e) = inserted {
+                if let Err(cleanup) = fs::remove_dir_all(&new_path) {
+                    tracing::warn!("Failed to remove {} after a failed import: {}", new_path, cleanup);
+                }
+                return Err(e);
+            }
+
+            Ok(ImportSummary {
+                task_count: archive.tasks.len(),
+                note_count: archive.notes.len(),
+                tag_count: archive.tags.len(),
+                project: archive.project,
+            })
+        }).await
+    }
+
+    /// Give the project, its tasks and its notes new IDs, remap parent and
+    /// project references and key tasks that have no key. Parents missing from
+    /// the archive make the task a root task. Returns the next free task number.
+    fn assign_fresh_ids(archive: &mut ProjectArchive, new_path: &str) -> i64 {
+        let project = &mut archive.project;
+        project.id = Uuid::new_v4().to_string();
+        project.path = new_path.to_string();
+        // An imported copy starts out of the favorites
+        project.is_favorite = false;
+        let prefix = project
+            .key_prefix
+            .get_or_insert_with(|| text::key_prefix_from_name(&project.name))
+            .clone();
+
+        let task_ids: HashMap<String, String> = archive
+            .tasks
+            .iter()
+            .map(|task| (task.id.clone(), Uuid::new_v4().to_string()))
+            .collect();
+
+        let mut next_number = archive
+            .tasks
+            .iter()
+            .filter_map(|task| task.task_key.as_deref()?.rsplit_once('-')?.1.parse::<i64>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        for task in &mut archive.tasks {
+            task.id = task_ids[&task.id].clone();
+            task.project_id = archive.project.id.clone();
+            task.parent_id = task.parent_id.as_ref().and_then(|parent| task_ids.get(parent)).cloned();
+            task.recurrence_parent_id = task.recurrence_parent_id.as_ref().and_then(|previous| task_ids.get(previous)).cloned();
+            if task.task_key.is_none() {
+                task.task_key = Some(format!("{}-{}", prefix, next_number));
+                next_number += 1;
+            }
+        }
+
+        for note in &mut archive.notes {
+            note.id = Uuid::new_v4().to_string();
+            note.project_id = Some(archive.project.id.clone());
+        }
+
+        next_number
+    }
+
+    /// Write every note of a project to `dest_dir` as a Markdown file with YAML
+    /// frontmatter, defaulting to the project's notes/ folder. Files are named
+    /// after slugified titles, with "-2", "-3"… when titles collide; existing
+    /// files of the same name are overwritten so re-exports update in place.
+    /// With `include_backlinks` (by default the export_include_backlinks
+    /// setting) a note linked from others ends with a "Linked from" section of
+    /// relative links to their files, between markers the import strips.
+    /// Returns the written paths.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_notes_markdown(
+        state: &AppState,
+        project_id: String,
+        dest_dir: Option<String>,
+        include_backlinks: Option<bool>,
+    ) -> AppResult<Vec<String>> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let (project_path, mut notes, include_backlinks) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                let include_backlinks = match include_backlinks {
+                    Some(include) => include,
+                    None => SettingsService::get_bool(conn, SETTING_EXPORT_INCLUDE_BACKLINKS)?,
+                };
+                (project.path, DbService::get_notes_by_project(conn, &project_id)?, include_backlinks)
+            };
+
+            let dest = match dest_dir.filter(|dir| !dir.trim().is_empty()) {
+                Some(dir) => PathBuf::from(dir),
+                None => Path::new(&project_path).join(NOTES_EXPORT_DIR),
+            };
+            fs::create_dir_all(&dest)?;
+
+            // Oldest first so a note keeps its file name as newer namesakes appear
+            notes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+            let mut used = HashSet::new();
+            let mut file_names = HashMap::with_capacity(notes.len());
+            for note in &notes {
+                let slug = text::slugify(&note.title);
+                let mut file_name = format!("{}.md", slug);
+                let mut suffix = 2;
+                while !used.insert(file_name.clone()) {
+                    file_name = format!("{}-{}.md", slug, suffix);
+                    suffix += 1;
+                }
+                file_names.insert(note.id.clone(), file_name);
+            }
+
+            let mut backlinks = HashMap::new();
+            if include_backlinks {
+                let conn = &state.conn()?;
+                for note in &notes {
+                    let linked_from: Vec<(String, String)> = DbService::get_note_backlinks(conn, &note.id)?
+                        .into_iter()
+                        .filter_map(|source| file_names.get(&source.id).map(|file| (source.title, file.clone())))
+                        .collect();
+                    if !linked_from.is_empty() {
+                        backlinks.insert(note.id.clone(), linked_from);
+                    }
+                }
+            }
+
+            let mut written = Vec::with_capacity(notes.len());
+            for note in &notes {
+                let mut markdown = Self::note_markdown(note);
+                if let Some(linked_from) = backlinks.get(&note.id) {
+                    markdown = Self::append_backlinks(&markdown, linked_from);
+                }
+                let path = dest.join(&file_names[&note.id]);
+                fs::write(&path, markdown)?;
+                written.push(path.to_string_lossy().into_owned());
+            }
+
+            if dest.starts_with(&project_path) && !written.is_empty() {
+                GitService::auto_commit(&project_path, &format!("Export {} notes as Markdown", written.len()));
+            }
+
+            Ok(written)
+        }).await
+    }
+
+    /// Markdown of an exported note with a "Linked from" section of relative
+    /// links, as (title, file name) pairs, between the backlink markers
+    fn append_backlinks(markdown: &str, linked_from: &[(String, String)]) -> String {
+        let mut out = markdown.to_string();
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&format!("\n{}\n## Linked from\n\n", BACKLINKS_START));
+        for (title, file_name) in linked_from {
+            let title = title.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]");
+            out.push_str(&format!("- [{}](./{})\n", title, file_name));
+        }
+        out.push_str(BACKLINKS_END);
+        out.push('\n');
+        out
+    }
+
+    /// Content of an exported note without the "Linked from" section that an
+    /// export appended, so importing it does not copy the section into the note
+    pub(crate) fn strip_backlinks(content: &str) -> &str {
+        let Some(start) = content.rfind(BACKLINKS_START) else {
+            return content;
+        };
+        let Some(end) = content[start..].find(BACKLINKS_END).map(|end| start + end + BACKLINKS_END.len()) else {
+            return content;
+        };
+        if !content[end..].trim().is_empty() {
+            return content;
+        }
+        let before = &content[..start];
+        before.strip_suffix('\n').unwrap_or(before)
+    }
+
+    /// A note as Markdown with the YAML frontmatter of a notes export
+    pub(crate) fn note_markdown(note: &Note) -> String {
+        frontmatter::render(
+            &[
+                ("id", json!(note.id)),
+                ("title", json!(note.title)),
+                ("tags", json!(note.tags.clone().unwrap_or_default())),
+                ("created_at", json!(Self::rfc3339(note.created_at))),
+                ("updated_at", json!(Self::rfc3339(note.updated_at))),
+                ("is_pinned", json!(note.is_pinned)),
+            ],
+            &note.content,
+        )
+    }
+
+    /// Write one note to `dest_path` as a single HTML file with no external
+    /// resources. Wikilinks to the note itself become anchors and other
+    /// wikilinks plain text; attached images up to 2 MB are embedded.
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn export_note_html(state: &AppState, note_id: String, dest_path: String) -> AppResult<HtmlExportSummary> {
+        state.blocking(move |state| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+            Self::check_file_dest(&dest_path)?;
+
+            let (note, project_dir, attachments) = {
+                let conn = &state.conn()?;
+                let note = DbService::get_note_by_id(conn, &note_id)?
+                    .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?;
+                let project_dir = match note.project_id.as_deref() {
+                    Some(project_id) => DbService::get_project_by_id(conn, project_id)?.map(|project| project.path),
+                    None => None,
+                };
+                let attachments = DbService::get_note_attachments(conn, &note.id)?;
+                (note, project_dir, attachments)
+            };
+
+            let notes = [note];
+            let mut assets = HtmlAssets::new(&notes, project_dir.as_deref().map(Path::new));
+            assets.attachments.insert(notes[0].id.clone(), attachments);
+            (assets.prerendered, assets.rendered) = Self::prerender_notes(&*state.conn()?, &notes)?;
+
+            let body = format!("<main>\n{}</main>\n", Self::html_article(&notes[0], 1, &mut assets));
+            let bytes_written = Self::write_html(&dest_path, &notes[0].title, &body)?;
+            Ok(assets.summary(dest_path, notes.len(), bytes_written))
+        }).await
+    }
+
+    /// Write every note of a project to `dest_path` as a single HTML file with
+    /// no external resources, after a table of contents grouping the notes by
+    /// tag. Notes appear under each of their tags, and untagged ones last.
+    /// Wikilinks between the notes become anchors; attached images up to 2 MB
+    /// are embedded.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_project_html(state: &AppState, project_id: String, dest_path: String) -> AppResult<HtmlExportSummary> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            Self::check_file_dest(&dest_path)?;
+
+            let (project, mut notes, attachments) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                let notes = DbService::get_notes_by_project(conn, &project_id)?;
+                let mut attachments = HashMap::with_capacity(notes.len());
+                for note in &notes {
+                    attachments.insert(note.id.clone(), DbService::get_note_attachments(conn, &note.id)?);
+                }
+                (project, notes, attachments)
+            };
+
+            // Oldest first, so the oldest note wins a wikilink when titles repeat
+            notes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+            let mut assets = HtmlAssets::new(&notes, Some(Path::new(&project.path)));
+            assets.attachments = attachments;
+            (assets.prerendered, assets.rendered) = Self::prerender_notes(&*state.conn()?, &notes)?;
+            notes.sort_by_cached_key(|note| note.title.to_lowercase());
+
+            let mut by_tag: BTreeMap<String, Vec<&Note>> = BTreeMap::new();
+            let mut untagged = Vec::new();
+            for note in &notes {
+                match note.tags.as_deref() {
+                    Some(tags) if !tags.is_empty() => {
+                        for tag in tags {
+                            by_tag.entry(tag.clone()).or_default().push(note);
+                        }
+                    }
+                    _ => untagged.push(note),
+                }
+            }
+
+            let mut body = format!("<header>\n<h1>{}</h1>\n", html::escape(&project.name));
+            if let Some(description) = project.description.as_deref().filter(|d| !d.trim().is_empty()) {
+                body.push_str(&format!("<p>{}</p>\n", html::escape(description)));
+            }
+            body.push_str(&format!(
+                "<p class=\"meta\">{} notes · exported {}</p>\n</header>\n",
+                notes.len(),
+                Self::html_date(chrono::Utc::now().timestamp())
+            ));
+
+            body.push_str("<nav class=\"toc\">\n<h2>Contents</h2>\n");
+            let sections = by_tag.iter().map(|(tag, tagged)| (tag.as_str(), tagged));
+            for (heading, section) in sections.chain((!untagged.is_empty()).then_some(("Untagged", &untagged))) {
+                body.push_str(&format!("<h3>{}</h3>\n<ul>\n", html::escape(heading)));
+                for note in section {
+                    body.push_str(&format!(
+                        "<li><a href=\"#{}\">{}</a></li>\n",
+                        HtmlAssets::anchor(&note.id),
+                        html::escape(&note.title)
+                    ));
+                }
+                body.push_str("</ul>\n");
+            }
+            body.push_str("</nav>\n<main>\n");
+            for note in &notes {
+                body.push_str(&Self::html_article(note, 2, &mut assets));
+            }
+            body.push_str("</main>\n");
+
+            let bytes_written = Self::write_html(&dest_path, &project.name, &body)?;
+            Ok(assets.summary(dest_path, notes.len(), bytes_written))
+        }).await
+    }
+
+    /// Write a project to `dest_dir` as a static site for readers without the app:
+    /// index.html with a task summary and the notes, one page per note under
+    /// notes/ with its attachments copied to files/, and references.html when the
+    /// project has references. All links are relative, so the folder can be
+    /// zipped or served from anywhere. Regenerating replaces the files the last
+    /// export listed in its manifest and removes those no longer generated; a
+    /// non-empty folder without a manifest is refused rather than written into.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_project_site(state: &AppState, project_id: String, dest_dir: String) -> AppResult<SiteExportSummary> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            if dest_dir.trim().is_empty() {
+                return Err(AppError::InvalidInput("Export folder cannot be empty".into()));
+            }
+
+            let (project, mut notes, attachments, task_counts, statuses, mut references) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                let notes = DbService::get_notes_by_project(conn, &project_id)?;
+                let mut attachments = HashMap::with_capacity(notes.len());
+                for note in &notes {
+                    attachments.insert(note.id.clone(), DbService::get_note_attachments(conn, &note.id)?);
+                }
+                let task_counts = DbService::count_tasks_by_status(conn, &project_id)?;
+                let statuses = DbService::get_project_statuses(conn, &project_id)?;
+                let references = DbService::get_references_by_project(conn, &project_id)?;
+                (project, notes, attachments, task_counts, statuses, references)
+            };
+
+            let dest = PathBuf::from(&dest_dir);
+            let previous = Self::read_site_manifest(&dest)?;
+            fs::create_dir_all(dest.join(SITE_NOTES_DIR))?;
+
+            // Oldest first, so a note keeps its page as newer namesakes appear and
+            // wins a wikilink when titles repeat
+            notes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+            let mut pages = HashMap::with_capacity(notes.len());
+            let mut used = HashSet::new();
+            for note in &notes {
+                let slug = text::slugify(&note.title);
+                let mut page = format!("{}.html", slug);
+                let mut suffix = 2;
+                while !used.insert(page.clone()) {
+                    page = format!("{}-{}.html", slug, suffix);
+                    suffix += 1;
+                }
+                pages.insert(note.id.clone(), page);
+            }
+
+            let (mut prerendered, notes_rendered) = Self::prerender_notes(&*state.conn()?, &notes)?;
+            let mut site = SiteLinks::new(&notes, &pages, Path::new(&project.path), &dest);
+            let mut written = BTreeSet::new();
+            let write_page = |written: &mut BTreeSet<String>, relative: String, title: &str, root: &str, body: &str| {
+                let values = HashMap::from([
+                    ("title".to_string(), html::escape(title)),
+                    ("root".to_string(), root.to_string()),
+                    ("project".to_string(), html::escape(&project.name)),
+                    ("body".to_string(), body.to_string()),
+                ]);
+                fs::write(dest.join(&relative), text::fill_placeholders(SITE_PAGE_TEMPLATE, &values))?;
+                written.insert(relative);
+                AppResult::Ok(())
+            };
+
+            for note in &notes {
+                let note_attachments = attachments.get(&note.id).map(Vec::as_slice).unwrap_or_default();
+                for attachment in note_attachments {
+                    site.copy(&attachment.relative_path);
+                }
+                site.attachments = note_attachments;
+
+                let mut body = format!(
+                    "<main>\n<article>\n<h1>{}</h1>\n<p class=\"meta\">Updated {}",
+                    html::escape(&note.title),
+                    Self::html_date(note.updated_at)
+                );
+                for tag in note.tags.iter().flatten() {
+                    body.push_str(&format!(" <span class=\"tag\">#{}</span>", html::escape(tag)));
+                }
+                body.push_str("</p>\n");
+                let content = prerendered.remove(&note.id).unwrap_or_else(|| html::prerender(&note.content));
+                body.push_str(&content.resolve(&mut site));
+                body.push_str("</article>\n</main>\n");
+                write_page(&mut written, format!("{}/{}", SITE_NOTES_DIR, pages[&note.id]), &note.title, "../", &body)?;
+            }
+
+            let mut body = format!("<header>\n<h1>{}</h1>\n", html::escape(&project.name));
+            if let Some(description) = project.description.as_deref().filter(|d| !d.trim().is_empty()) {
+                body.push_str(&format!("<p>{}</p>\n", html::escape(description)));
+            }
+            body.push_str(&format!(
+                "<p class=\"meta\">Exported {}</p>\n</header>\n<main>\n",
+                Self::html_date(chrono::Utc::now().timestamp())
+            ));
+
+            if !task_counts.is_empty() {
+                body.push_str("<h2>Tasks</h2>\n<table>\n<tr><th>Status</th><th>Tasks</th></tr>\n");
+                for status in &statuses {
+                    let count = task_counts.get(status).copied().unwrap_or(0);
+                    body.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", html::escape(status), count));
+                }
+                body.push_str("</table>\n");
+            }
+
+            body.push_str(&format!("<h2>Notes</h2>\n<p class=\"meta\">{} notes</p>\n<ul>\n", notes.len()));
+            let mut by_title: Vec<&Note> = notes.iter().collect();
+            by_title.sort_by_cached_key(|note| note.title.to_lowercase());
+            for note in by_title {
+                body.push_str(&format!(
+                    "<li><a href=\"{}/{}\">{}</a></li>\n",
+                    SITE_NOTES_DIR,
+                    site_href(&pages[&note.id]),
+                    html::escape(&note.title)
+                ));
+            }
+            body.push_str("</ul>\n");
+
+            if !references.is_empty() {
+                body.push_str("<p><a href=\"references.html\">References</a></p>\n");
+                references.sort_by(|a, b| a.citation_key.cmp(&b.citation_key));
+                let mut list = "<main>\n<h1>References</h1>\n<ul>\n".to_string();
+                for reference in &references {
+                    list.push_str(&Self::site_reference(reference));
+                }
+                list.push_str("</ul>\n</main>\n");
+                write_page(&mut written, "references.html".to_string(), "References", "", &list)?;
+            }
+            body.push_str("</main>\n");
+            write_page(&mut written, "index.html".to_string(), &project.name, "", &body)?;
+
+            fs::write(dest.join("style.css"), format!("{}nav.site {{ margin-bottom: 1rem; }}\n", HTML_STYLESHEET))?;
+            written.insert("style.css".to_string());
+
+            let pages_written = written.len() - 1;
+            let files_copied = site.copied.len();
+            written.append(&mut site.copied);
+
+            let mut files_removed = 0;
+            for relative in previous.difference(&written) {
+                let file = dest.join(relative);
+                if fs::remove_file(&file).is_ok() {
+                    files_removed += 1;
+                    Self::remove_empty_dirs(&dest, file.parent());
+                }
+            }
+            let manifest = json!({ "files": written });
+            fs::write(dest.join(SITE_MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+
+            Ok(SiteExportSummary { dest_dir, pages_written, files_copied, files_removed, notes_rendered })
+        }).await
+    }
+
+    /// Files listed by the manifest of an earlier site export in `dest`, keeping
+    /// only plain relative paths so a tampered manifest cannot point outside it.
+    /// An empty or missing folder has none; any other folder without a manifest is refused.
+    fn read_site_manifest(dest: &Path) -> AppResult<BTreeSet<String>> {
+        let manifest = dest.join(SITE_MANIFEST);
+        if !manifest.exists() {
+            let is_empty = match fs::read_dir(dest) {
+                Ok(mut entries) => entries.next().is_none(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => true,
+                Err(_) if dest.is_file() => {
+                    return Err(AppError::InvalidInput("Export folder must be a folder, not a file".into()));
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if !is_empty {
+                return Err(AppError::InvalidInput(
+                    "Export folder is not empty and holds no earlier site export".into(),
+                ));
+            }
+            return Ok(BTreeSet::new());
+        }
+
+        let manifest: Value = serde_json::from_str(&fs::read_to_string(&manifest)?)
+            .map_err(|e| AppError::InvalidInput(format!("Unreadable site manifest: {}", e)))?;
+        let files = manifest["files"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter(|relative| {
+                Path::new(relative)
+                    .components()
+                    .all(|component| matches!(component, std::path::Component::Normal(_)))
+            })
+            .map(str::to_string)
+            .collect();
+        Ok(files)
+    }
+
+    /// Remove `dir` and its parents while they are empty, stopping at `root`
+    fn remove_empty_dirs(root: &Path, mut dir: Option<&Path>) {
+        while let Some(current) = dir.filter(|d| *d != root && d.starts_with(root)) {
+            if fs::remove_dir(current).is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+    }
+
+    /// A reference as a list item of references.html, anchored by its citation key
+    fn site_reference(reference: &Reference) -> String {
+        let mut item = format!("<li id=\"{}\">", html::escape(&reference.citation_key));
+        if !reference.authors.is_empty() {
+            item.push_str(&html::escape(&reference.authors.join("; ")));
+            item.push(' ');
+        }
+        if let Some(year) = reference.year {
+            item.push_str(&format!("({}). ", year));
+        }
+        item.push_str(&format!("<em>{}</em>.", html::escape(&reference.title)));
+        if let Some(venue) = reference.venue.as_deref().filter(|v| !v.is_empty()) {
+            item.push_str(&format!(" {}.", html::escape(venue)));
+        }
+        if let Some(doi) = reference.doi.as_deref().filter(|d| !d.is_empty()) {
+            item.push_str(&format!(
+                " <a href=\"https://doi.org/{}\">doi:{}</a>",
+                html::escape(&site_href(doi)),
+                html::escape(doi)
+            ));
+        } else if let Some(url) = reference.url.as_deref().filter(|u| u.starts_with("http://") || u.starts_with("https://")) {
+            item.push_str(&format!(" <a href=\"{}\">{}</a>", html::escape(url), html::escape(url)));
+        }
+        item.push_str("</li>\n");
+        item
+    }
+
+    /// Drop every cached note rendering, as after a change to the renderer.
+    /// Returns how many were dropped.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn clear_render_cache(state: &AppState) -> AppResult<usize> {
+        state.run(|conn| DbService::with_busy_retry(|| DbService::clear_note_renders(conn))).await
+    }
+
+    /// The content of each note ready for its links and images, by note ID, and how
+    /// many notes had to be rendered for it. Notes whose content the render cache
+    /// holds for this renderer version come from there; the others are rendered
+    /// and cached in one transaction.
+    fn prerender_notes(conn: &Connection, notes: &[Note]) -> AppResult<(HashMap<String, html::Prerendered>, usize)> {
+        let mut prerendered = HashMap::with_capacity(notes.len());
+        let mut fresh = Vec::new();
+        for note in notes {
+            let content_hash = hash::sha256_hex(note.content.as_bytes());
+            match DbService::get_note_render(conn, &note.id, &content_hash)? {
+                Some(cached) => {
+                    prerendered.insert(note.id.clone(), cached);
+                }
+                None => {
+                    prerendered.insert(note.id.clone(), html::prerender(&note.content));
+                    fresh.push((note.id.as_str(), content_hash));
+                }
+            }
+        }
+
+        if !fresh.is_empty() {
+            DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                for (note_id, content_hash) in &fresh {
+                    DbService::save_note_render(tx, note_id, content_hash, &prerendered[*note_id])?;
+                }
+                Ok(())
+            }))?;
+        }
+        Ok((prerendered, fresh.len()))
+    }
+
+    fn check_file_dest(dest_path: &str) -> AppResult<()> {
+        if dest_path.trim().is_empty() {
+            return Err(AppError::InvalidInput("Export path cannot be empty".into()));
+        }
+        if Path::new(dest_path).is_dir() {
+            return Err(AppError::InvalidInput("Export path must be a file, not a directory".into()));
+        }
+        Ok(())
+    }
+
+    /// A note as an `<article>` with its title at the given heading level
+    fn html_article(note: &Note, level: u8, assets: &mut HtmlAssets) -> String {
+        let mut out = format!(
+            "<article id=\"{}\">\n<h{level}>{}</h{level}>\n<p class=\"meta\">Updated {}",
+            HtmlAssets::anchor(&note.id),
+            html::escape(&note.title),
+            Self::html_date(note.updated_at),
+            level = level
+        );
+        for tag in note.tags.iter().flatten() {
+            out.push_str(&format!(" <span class=\"tag\">#{}</span>", html::escape(tag)));
+        }
+        out.push_str("</p>\n");
+
+        assets.note_id = note.id.clone();
+        let content = assets.prerendered.remove(&note.id).unwrap_or_else(|| html::prerender(&note.content));
+        out.push_str(&content.resolve(assets));
+        out.push_str("</article>\n");
+        out
+    }
+
+    fn html_date(timestamp: i64) -> String {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+
+    /// Wrap the body in a complete document with the inlined stylesheet and write
+    /// it through a staging file. Returns the size of the document.
+    fn write_html(dest_path: &str, title: &str, body: &str) -> AppResult<u64> {
+        let document = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            html::escape(title),
+            HTML_STYLESHEET,
+            body
+        );
+
+        let dest = Path::new(dest_path);
+        let mut staging = dest.as_os_str().to_owned();
+        staging.push(".partial");
+        if let Err(e) = fs::write(&staging, &document).and_then(|_| fs::rename(&staging, dest)) {
+            let _ = fs::remove_file(&staging);
+            return Err(e.into());
+        }
+        Ok(document.len() as u64)
+    }
+
+    fn rfc3339(timestamp: i64) -> String {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default()
+    }
+
+    /// Write the tasks that have a due date to `dest_path` as an iCalendar file,
+    /// one component per task: the tasks of one project, or with None those of
+    /// every non-archived project. Deadlines of the project, global ones included,
+    /// or with None every deadline, follow as events. Each UID is derived from the
+    /// task or deadline id, so subscribed calendars update earlier copies on
+    /// re-export. Returns how many tasks and deadlines were written.
+    #[instrument(level = "debug", skip_all, fields(project_id = ?project_id))]
+    pub async fn export_tasks_ical(
+        state: &AppState,
+        project_id: Option<String>,
+        dest_path: String,
+        component: IcalComponent,
+    ) -> AppResult<usize> {
+        state.blocking(move |state| {
+            if dest_path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Export path cannot be empty".into()));
+            }
+            if project_id.as_deref().is_some_and(str::is_empty) {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let (calendar_name, tasks, deadlines) = {
+                let conn = &state.conn()?;
+                let calendar_name = match &project_id {
+                    Some(id) => DbService::get_project_by_id(conn, id)?
+                        .ok_or_else(|| AppError::NotFound("Project", id.clone()))?
+                        .name,
+                    None => "Research Vault".to_string(),
+                };
+                let tasks = DbService::get_dated_tasks(conn, project_id.as_deref())?;
+                let deadlines = DbService::get_deadlines(conn, project_id.as_deref(), None, None)?;
+                (calendar_name, tasks, deadlines)
+            };
+
+            let calendar = Self::render_ical(&calendar_name, &tasks, &deadlines, component, chrono::Utc::now().timestamp());
+            fs::write(&dest_path, calendar)?;
+            Ok(tasks.len() + deadlines.len())
+        }).await
+    }
+
+    fn render_ical(
+        calendar_name: &str,
+        tasks: &[TaskWithProject],
+        deadlines: &[Deadline],
+        component: IcalComponent,
+        now: i64,
+    ) -> String {
+        let mut out = String::new();
+        out.push_str(&ical::content_line("BEGIN", "VCALENDAR"));
+        out.push_str(&ical::content_line("VERSION", "2.0"));
+        out.push_str(&ical::content_line("PRODID", ICAL_PRODUCT_ID));
+        out.push_str(&ical::content_line("CALSCALE", "GREGORIAN"));
+        out.push_str(&ical::content_line("X-WR-CALNAME", &ical::escape_text(calendar_name)));
+
+        let name = match component {
+            IcalComponent::Todo => "VTODO",
+            IcalComponent::Event => "VEVENT",
+        };
+
+        for TaskWithProject { task, project_name } in tasks {
+            let Some(due_date) = task.due_date else {
+                continue;
+            };
+            let summary = match &task.task_key {
+                Some(key) => format!("{} {}", key, task.title),
+                None => task.title.clone(),
+            };
+
+            out.push_str(&ical::content_line("BEGIN", name));
+            out.push_str(&ical::content_line("UID", &format!("{}@{}", task.id, ICAL_UID_DOMAIN)));
+            out.push_str(&ical::content_line("DTSTAMP", &ical::format_timestamp(now)));
+            out.push_str(&ical::content_line("CREATED", &ical::format_timestamp(task.created_at)));
+            out.push_str(&ical::content_line("LAST-MODIFIED", &ical::format_timestamp(task.updated_at)));
+            out.push_str(&ical::content_line("SUMMARY", &ical::escape_text(&summary)));
+            if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+                out.push_str(&ical::content_line("DESCRIPTION", &ical::escape_text(description)));
+            }
+
+            match component {
+                IcalComponent::Todo => {
+                    out.push_str(&ical::content_line("DUE", &ical::format_timestamp(due_date)));
+                    // completed_at is set exactly while a task is in its project's done status
+                    let status = match task.status.as_str() {
+                        _ if task.completed_at.is_some() => "COMPLETED",
+                        "in_progress" => "IN-PROCESS",
+                        _ => "NEEDS-ACTION",
+                    };
+                    out.push_str(&ical::content_line("STATUS", status));
+                    if let Some(completed_at) = task.completed_at.filter(|_| status == "COMPLETED") {
+                        out.push_str(&ical::content_line("COMPLETED", &ical::format_timestamp(completed_at)));
+                    }
+                }
+                IcalComponent::Event => {
+                    out.push_str(&ical::content_line("DTSTART", &ical::format_timestamp(due_date)));
+                    out.push_str(&ical::content_line("TRANSP", "TRANSPARENT"));
+                }
+            }
+
+            // RFC 5545: 1 is the highest priority, 9 the lowest
+            let priority = match task.priority {
+                TaskPriority::High => "1",
+                TaskPriority::Medium => "5",
+                TaskPriority::Low => "9",
+            };
+            out.push_str(&ical::content_line("PRIORITY", priority));
+
+            let mut categories = vec![ical::escape_text(project_name)];
+            categories.extend(task.tags.iter().flatten().map(|tag| ical::escape_text(tag)));
+            out.push_str(&ical::content_line("CATEGORIES", &categories.join(",")));
+
+            out.push_str(&ical::content_line("END", name));
+        }
+
+        // Deadlines are dates to keep in view, never to-dos
+        for deadline in deadlines {
+            out.push_str(&ical::content_line("BEGIN", "VEVENT"));
+            out.push_str(&ical::content_line("UID", &format!("deadline-{}@{}", deadline.id, ICAL_UID_DOMAIN)));
+            out.push_str(&ical::content_line("DTSTAMP", &ical::format_timestamp(now)));
+            out.push_str(&ical::content_line("CREATED", &ical::format_timestamp(deadline.created_at)));
+            out.push_str(&ical::content_line("SUMMARY", &ical::escape_text(&deadline.name)));
+            if let Some(notes) = deadline.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+                out.push_str(&ical::content_line("DESCRIPTION", &ical::escape_text(notes)));
+            }
+            if let Some(url) = deadline.url.as_deref().filter(|u| !u.trim().is_empty()) {
+                out.push_str(&ical::content_line("URL", url));
+            }
+            out.push_str(&ical::content_line("DTSTART", &ical::format_timestamp(deadline.date)));
+            out.push_str(&ical::content_line("TRANSP", "TRANSPARENT"));
+            out.push_str(&ical::content_line("CATEGORIES", "Deadline"));
+            out.push_str(&ical::content_line("END", "VEVENT"));
+        }
+
+        out.push_str(&ical::content_line("END", "VCALENDAR"));
+        out
+    }
+
+    /// Write a project's tasks to `dest_path` as CSV in tree order, each task followed
+    /// by its subtasks, with its depth (0 for root tasks) and its 1-based position
+    /// among its siblings. Tags are joined with ";". Returns how many tasks were written.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_tasks_csv(state: &AppState, project_id: String, dest_path: String) -> AppResult<usize> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            if dest_path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Export path cannot be empty".into()));
+            }
+
+            let tasks = {
+                let conn = &state.conn()?;
+                if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                    return Err(AppError::NotFound("Project", project_id));
+                }
+                DbService::get_tasks_by_project(conn, &project_id)?
+            };
+
+            let ids: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+            let mut children: HashMap<Option<&str>, Vec<&Task>> = HashMap::new();
+            for task in &tasks {
+                // A task whose parent is missing is listed as a root task
+                let parent = task.parent_id.as_deref().filter(|id| ids.contains(id));
+                children.entry(parent).or_default().push(task);
+            }
+
+            let mut output = csv::format_row(&TASK_CSV_COLUMNS);
+            let mut written = 0;
+            let mut stack: Vec<(&Task, usize, usize)> = children
+                .get(&None)
+                .map(|roots| roots.iter().enumerate().rev().map(|(i, task)| (*task, 0, i + 1)).collect())
+                .unwrap_or_default();
+
+            while let Some((task, depth, position)) = stack.pop() {
+                let depth_text = depth.to_string();
+                let position_text = position.to_string();
+                let due_date = task.due_date.map(Self::rfc3339).unwrap_or_default();
+                let tags = task.tags.as_ref().map(|tags| tags.join(";")).unwrap_or_default();
+                output.push_str(&csv::format_row(&[
+                    task.id.as_str(),
+                    task.parent_id.as_deref().unwrap_or_default(),
+                    depth_text.as_str(),
+                    position_text.as_str(),
+                    task.title.as_str(),
+                    task.description.as_deref().unwrap_or_default(),
+                    task.status.as_str(),
+                    task.priority.as_str(),
+                    due_date.as_str(),
+                    tags.as_str(),
+                ]));
+                written += 1;
+
+                if let Some(subtasks) = children.get(&Some(task.id.as_str())) {
+                    stack.extend(subtasks.iter().enumerate().rev().map(|(i, subtask)| (*subtask, depth + 1, i + 1)));
+                }
+            }
+
+            fs::write(&dest_path, output)?;
+            Ok(written)
+        }).await
+    }
+
+    /// Write a project's references to `dest_path` as CSV, by citation key.
+    /// `columns` picks and orders a subset of `REFERENCE_CSV_COLUMNS`; all of them
+    /// by default. Only references with `tag` and `reading_status` are written when
+    /// those are given. Authors and tags are joined with "; ". Returns how many
+    /// references were written.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_references_csv(
+        state: &AppState,
+        project_id: String,
+        dest_path: String,
+        columns: Option<Vec<String>>,
+        tag: Option<String>,
+        reading_status: Option<String>,
+    ) -> AppResult<usize> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            if dest_path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Export path cannot be empty".into()));
+            }
+            let columns = match columns {
+                Some(columns) if columns.is_empty() => {
+                    return Err(AppError::InvalidInput("At least one column must be chosen".into()));
+                }
+                Some(columns) => columns,
+                None => REFERENCE_CSV_COLUMNS.iter().map(|column| column.to_string()).collect(),
+            };
+            if let Some(unknown) = columns.iter().find(|column| !REFERENCE_CSV_COLUMNS.contains(&column.as_str())) {
+                return Err(AppError::InvalidInput(format!(
+                    "Unknown column '{}'; expected one of: {}",
+                    unknown,
+                    REFERENCE_CSV_COLUMNS.join(", ")
+                )));
+            }
+
+            let references = {
+                let conn = &state.conn()?;
+                if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                    return Err(AppError::NotFound("Project", project_id));
+                }
+                DbService::get_references_by_project(conn, &project_id)?
+            };
+
+            let mut output = csv::format_row(&columns);
+            let mut written = 0;
+            for reference in &references {
+                let has_tag = tag.as_ref().is_none_or(|tag| {
+                    reference.tags.iter().flatten().any(|t| t.eq_ignore_ascii_case(tag))
+                });
+                let has_status = reading_status.as_ref().is_none_or(|status| reference.reading_status.eq_ignore_ascii_case(status));
+                if !has_tag || !has_status {
+                    continue;
+                }
+
+                let fields: Vec<String> = columns.iter().map(|column| Self::reference_csv_field(reference, column)).collect();
+                output.push_str(&csv::format_row(&fields));
+                written += 1;
+            }
+
+            fs::write(&dest_path, output)?;
+            Ok(written)
+        }).await
+    }
+
+    fn reference_csv_field(reference: &Reference, column: &str) -> String {
+        match column {
+            "cite_key" => reference.citation_key.clone(),
+            "title" => reference.title.clone(),
+            "authors" => reference.authors.join("; "),
+            "year" => reference.year.map(|year| year.to_string()).unwrap_or_default(),
+            "venue" => reference.venue.clone().unwrap_or_default(),
+            "doi" => reference.doi.clone().unwrap_or_default(),
+            "reading_status" => reference.reading_status.clone(),
+            "tags" => reference.tags.as_ref().map(|tags| tags.join("; ")).unwrap_or_default(),
+            "added" => chrono::DateTime::from_timestamp(reference.created_at, 0)
+                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    /// Create tasks from a CSV file with a header row. Only "title" is required;
+    /// id, parent_id, depth (or level), description, status, priority, due_date
+    /// (RFC 3339 or YYYY-MM-DD) and tags (";"-separated) are read when present.
+    /// A row's parent is the row or existing task named in parent_id, otherwise the
+    /// closest earlier row one level shallower. Bad rows are reported with their
+    /// line and skipped, as are rows below them; the rest are inserted in one transaction.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn import_tasks_csv(state: &AppState, project_id: String, src_path: String) -> AppResult<CsvImportResult> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let content = fs::read_to_string(&src_path)?;
+            let records = csv::parse(&content).map_err(|line| {
+                AppError::InvalidInput(format!("Unterminated quoted field starting on line {}", line))
+            })?;
+            let mut records = records.into_iter();
+            let header = records.next().ok_or_else(|| AppError::InvalidInput("The CSV file is empty".into()))?;
+
+            let mut columns: HashMap<String, usize> = HashMap::new();
+            for (index, name) in header.fields.iter().enumerate() {
+                columns.entry(name.trim().to_lowercase()).or_insert(index);
+            }
+            if !columns.contains_key("title") {
+                return Err(AppError::InvalidInput("The CSV file has no title column".into()));
+            }
+            let depth_column = if columns.contains_key("depth") { "depth" } else { "level" };
+
+            let conn = &state.conn()?;
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            let statuses = DbService::get_project_statuses(conn, &project_id)?;
+            let now = chrono::Utc::now().timestamp();
+
+            let mut result = CsvImportResult::default();
+            let mut tasks: Vec<Task> = Vec::new();
+            // CSV row id -> id of the created task, None when the row failed
+            let mut row_ids: HashMap<String, Option<String>> = HashMap::new();
+            // Last row seen at each depth, for parents given by indentation
+            let mut levels: Vec<Option<String>> = Vec::new();
+
+            for record in records {
+                let fields = &record.fields;
+                let row_id = Self::csv_value(&columns, fields, "id");
+                let parent_ref = Self::csv_value(&columns, fields, "parent_id");
+                let depth_text = Self::csv_value(&columns, fields, depth_column);
+                let depth = depth_text.parse::<usize>().ok();
+
+                let parent = if !parent_ref.is_empty() {
+                    match row_ids.get(parent_ref) {
+                        Some(Some(id)) => Ok(Some(id.clone())),
+                        Some(None) => Err(format!("Parent row '{}' was not imported", parent_ref)),
+                        None => match DbService::get_task_by_id(conn, parent_ref)? {
+                            Some(task) if task.project_id == project_id => Ok(Some(task.id)),
+                            _ => Err(format!("Parent '{}' is neither an earlier row nor a task of the project", parent_ref)),
+                        },
+                    }
+                } else if depth_text.is_empty() {
+                    Ok(None)
+                } else {
+                    match depth {
+                        None => Err(format!("Invalid depth '{}'", depth_text)),
+                        Some(0) => Ok(None),
+                        Some(depth) => match levels.get(depth - 1) {
+                            Some(Some(id)) => Ok(Some(id.clone())),
+                            Some(None) => Err("Parent row was not imported".to_string()),
+                            None => Err(format!("No row at depth {} above this one", depth - 1)),
+                        },
+                    }
+                };
+
+                let outcome = parent.and_then(|parent_id| {
+                    Self::task_from_csv_row(&columns, fields, &project_id, &statuses, parent_id, now)
+                });
+                let created_id = match outcome {
+                    Ok(task) => {
+                        let id = task.id.clone();
+                        tasks.push(task);
+                        Some(id)
+                    }
+                    Err(message) => {
+                        result.errors.push(CsvRowError { line: record.line, message });
+                        None
+                    }
+                };
+
+                if !row_id.is_empty() {
+                    row_ids.insert(row_id.to_string(), created_id.clone());
+                }
+                if let Some(depth) = depth {
+                    levels.truncate(depth);
+                    levels.resize(depth, None);
+                    levels.push(created_id);
+                }
+            }
+
+            DbService::with_busy_retry(|| DbService::insert_imported_tasks(conn, &mut tasks))?;
+            result.imported = tasks.len();
+            Ok(result)
+        }).await
+    }
+
+    /// Trimmed value of a named column, empty when the column or field is missing
+    fn csv_value<'a>(columns: &HashMap<String, usize>, fields: &'a [String], name: &str) -> &'a str {
+        columns
+            .get(name)
+            .and_then(|&index| fields.get(index))
+            .map_or("", |value| value.trim())
+    }
+
+    fn task_from_csv_row(
+        columns: &HashMap<String, usize>,
+        fields: &[String],
+        project_id: &str,
+        statuses: &[String],
+        parent_id: Option<String>,
+        now: i64,
+    ) -> Result<Task, String> {
+        let title = Self::csv_value(columns, fields, "title");
+        if title.is_empty() {
+            return Err("Title cannot be empty".into());
+        }
+
+        let status = match Self::csv_value(columns, fields, "status") {
+            "" => statuses.first().cloned().unwrap_or_else(|| "todo".to_string()),
+            status if statuses.iter().any(|s| s == status) => status.to_string(),
+            status => {
+                return Err(format!("Invalid status '{}'. Must be one of: {}", status, statuses.join(", ")));
+            }
+        };
+
+        let priority = match Self::csv_value(columns, fields, "priority").to_lowercase().as_str() {
+            "" | "medium" => TaskPriority::Medium,
+            "low" => TaskPriority::Low,
+            "high" => TaskPriority::High,
+            other => return Err(format!("Invalid priority '{}'. Must be one of: low, medium, high", other)),
+        };
+
+        let due_date = match Self::csv_value(columns, fields, "due_date") {
+            "" => None,
+            value => Some(Self::parse_csv_date(value).ok_or_else(|| format!("Invalid due date '{}'", value))?),
+        };
+
+        let description = Self::csv_value(columns, fields, "description");
+        let tags: Vec<String> = Self::csv_value(columns, fields, "tags")
+            .split(';')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        Ok(Task {
+            id: Uuid::new_v4().to_string(),
+            project_id: project_id.to_string(),
+            parent_id,
+            title: title.to_string(),
+            description: (!description.is_empty()).then(|| description.to_string()),
+            completed_at: (statuses.last() == Some(&status)).then_some(now),
+            status,
+            priority,
+            due_date,
+            created_at: now,
+            updated_at: now,
+            order: 0,
+            tags: (!tags.is_empty()).then_some(tags),
+            task_key: None,
+            rank: None,
+            recurrence: None,
+            recurrence_parent_id: None,
+            remind_at: None,
+            reminded_at: None,
+            metadata: None,
+        })
+    }
+
+    /// Parse an RFC 3339 timestamp or a plain YYYY-MM-DD date (midnight UTC)
+    fn parse_csv_date(value: &str) -> Option<i64> {
+        if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(value) {
+            return Some(date_time.timestamp());
+        }
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date_time| date_time.and_utc().timestamp())
+    }
+
+    /// Import every .md file below `src_dir` as a note of the project. Titles,
+    /// tags and the pinned flag come from YAML frontmatter when present, the
+    /// title otherwise from the file name. A file whose frontmatter cannot be
+    /// parsed is imported with its raw content. Files whose title and content
+    /// match an existing note (or an earlier file of the batch) are skipped, so
+    /// running the import twice adds nothing. All notes are inserted in one
+    /// transaction; the result lists what happened to every file.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn import_notes_markdown(
+        state: &AppState,
+        project_id: String,
+        src_dir: String,
+    ) -> AppResult<Vec<MarkdownImportResult>> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            let root = PathBuf::from(&src_dir);
+            if !root.is_dir() {
+                return Err(AppError::NotFound("Directory", src_dir));
+            }
+
+            let (project_path, mut known) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                let known: HashSet<u64> = DbService::get_notes_by_project(conn, &project_id)?
+                    .iter()
+                    .map(|note| hash::fnv1a_64(&[note.title.as_str(), note.content.as_str()]))
+                    .collect();
+                (project.path, known)
+            };
+
+            let mut files = Vec::new();
+            Self::find_markdown_files(&root, &mut files);
+            files.sort();
+
+            let now = chrono::Utc::now().timestamp();
+            let mut notes = Vec::new();
+            let mut results = Vec::with_capacity(files.len());
+
+            for path in files {
+                let relative = path
+                    .strip_prefix(&root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let mut result = MarkdownImportResult {
+                    path: relative,
+                    status: MarkdownImportStatus::Failed,
+                    note_id: None,
+                    reason: None,
+                };
+
+                let raw = match fs::read(&path) {
+                    Ok(bytes) => match String::from_utf8(bytes) {
+                        Ok(raw) => raw,
+                        Err(_) => {
+                            result.reason = Some("File is not valid UTF-8".into());
+                            results.push(result);
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        result.reason = Some(e.to_string());
+                        results.push(result);
+                        continue;
+                    }
+                };
+
+                let (fields, content) = match frontmatter::split(&raw) {
+                    Some((header, body)) => match frontmatter::parse(header) {
+                        Ok(fields) => (fields, Self::strip_backlinks(body).to_string()),
+                        Err(e) => {
+                            result.reason = Some(format!("Frontmatter could not be parsed ({}); imported the raw file", e));
+                            (Map::new(), raw.clone())
+                        }
+                    },
+                    None => (Map::new(), raw.clone()),
+                };
+
+                let title = fields
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|title| !title.is_empty())
+                    .map(str::to_string)
+                    .or_else(|| {
+                        path.file_stem()
+                            .map(|stem| stem.to_string_lossy().trim().to_string())
+                            .filter(|stem| !stem.is_empty())
+                    })
+                    .unwrap_or_else(|| UNTITLED_NOTE.to_string());
+
+                if !known.insert(hash::fnv1a_64(&[title.as_str(), content.as_str()])) {
+                    result.status = MarkdownImportStatus::Skipped;
+                    result.reason = Some("A note with the same title and content already exists".into());
+                    results.push(result);
+                    continue;
+                }
+
+                let tags = Self::frontmatter_tags(fields.get("tags"));
+                let is_pinned = fields
+                    .get("pinned")
+                    .or_else(|| fields.get("is_pinned"))
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+
+                let note = Note {
+                    id: Uuid::new_v4().to_string(),
+                    project_id: Some(project_id.clone()),
+                    title,
+                    content,
+                    created_at: now,
+                    updated_at: now,
+                    tags: (!tags.is_empty()).then_some(tags),
+                    is_pinned,
+                    is_locked: false,
+                    metadata: None,
+                };
+                result.status = MarkdownImportStatus::Imported;
+                result.note_id = Some(note.id.clone());
+                results.push(result);
+                notes.push(note);
+            }
+
+            if !notes.is_empty() {
+                {
+                    let conn = &state.conn()?;
+                    DbService::with_busy_retry(|| DbService::insert_notes(conn, &notes))?;
+                }
+                GitService::auto_commit(&project_path, &format!("Import {} notes from Markdown", notes.len()));
+            }
+
+            Ok(results)
+        }).await
+    }
+
+    /// Write one note to `dest_path` as a zip bundle for sharing: note.md with
+    /// frontmatter, metadata.json with its tags, timestamps and links, and its
+    /// attachments under attachments/. References to the attachments in the
+    /// Markdown are rewritten to point into the bundle. Attachments whose file
+    /// is gone are left out and reported.
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn export_note_bundle(state: &AppState, note_id: String, dest_path: String) -> AppResult<NoteBundleExportSummary> {
+        state.blocking(move |state| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+            Self::check_file_dest(&dest_path)?;
+
+            let (note, project_dir, attachments) = {
+                let conn = &state.conn()?;
+                let note = DbService::get_note_by_id(conn, &note_id)?
+                    .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?;
+                let project_dir = match note.project_id.as_deref() {
+                    Some(project_id) => DbService::get_project_by_id(conn, project_id)?.map(|project| project.path),
+                    None => None,
+                };
+                let attachments = DbService::get_note_attachments(conn, &note.id)?;
+                (note, project_dir, attachments)
+            };
+
+            // Each attachment file once, under a name unique in the bundle
+            let mut bundled = Vec::new();
+            let mut entries = HashMap::new();
+            let mut missing_attachments = Vec::new();
+            let mut used = HashSet::new();
+            for attachment in &attachments {
+                let source = project_dir.as_deref().map(|dir| Path::new(dir).join(&attachment.relative_path));
+                let Some(source) = source.filter(|source| source.is_file()) else {
+                    missing_attachments.push(attachment.original_name.clone());
+                    continue;
+                };
+
+                let file_name = attachment.relative_path.rsplit('/').next().unwrap_or(&attachment.original_name);
+                // Spaces would end a Markdown link target early
+                let file_name = NoteAttachmentService::safe_file_name(file_name).replace(' ', "-");
+                let (stem, extension) = match file_name.rfind('.') {
+                    Some(dot) if dot > 0 => (&file_name[..dot], &file_name[dot..]),
+                    _ => (file_name.as_str(), ""),
+                };
+                let mut entry = format!("{}/{}", BUNDLE_ATTACHMENTS_DIR, file_name);
+                let mut suffix = 2;
+                while !used.insert(entry.to_lowercase()) {
+                    entry = format!("{}/{}-{}{}", BUNDLE_ATTACHMENTS_DIR, stem, suffix, extension);
+                    suffix += 1;
+                }
+
+                entries.insert(attachment.id.clone(), entry.clone());
+                bundled.push((
+                    NoteBundleAttachment {
+                        path: entry,
+                        original_name: attachment.original_name.clone(),
+                        size_bytes: attachment.size_bytes,
+                    },
+                    source,
+                ));
+            }
+
+            let content = markdown::rewrite_file_references(&note.content, |reference| {
+                find_attachment(&attachments, reference).and_then(|attachment| entries.get(&attachment.id).cloned())
+            });
+            let (bundle_attachments, sources): (Vec<_>, Vec<_>) = bundled.into_iter().unzip();
+            let tags = note.tags.clone().unwrap_or_default();
+            let document = frontmatter::render(
+                &[
+                    ("id", json!(note.id)),
+                    ("title", json!(note.title)),
+                    ("tags", json!(tags)),
+                    ("created_at", json!(Self::rfc3339(note.created_at))),
+                    ("updated_at", json!(Self::rfc3339(note.updated_at))),
+                    ("is_pinned", json!(note.is_pinned)),
+                ],
+                &content,
+            );
+
+            let now = chrono::Utc::now().timestamp();
+            let metadata = NoteBundleMetadata {
+                format_version: NOTE_BUNDLE_FORMAT_VERSION,
+                exported_at: now,
+                note_id: note.id.clone(),
+                title: note.title.clone(),
+                tags,
+                created_at: note.created_at,
+                updated_at: note.updated_at,
+                is_pinned: note.is_pinned,
+                links: markdown::wikilink_targets(&note.content),
+                attachments: bundle_attachments,
+            };
+
+            // Written through a staging file so a failed export leaves no partial zip
+            let dest = Path::new(&dest_path);
+            let mut staging = dest.as_os_str().to_owned();
+            staging.push(".partial");
+            let written = fs::File::create(&staging).and_then(|file| {
+                let mut bundle = ArchiveWriter::new(BufWriter::new(file), now);
+                bundle.add_bytes(BUNDLE_NOTE_ENTRY, document.as_bytes())?;
+                bundle.add_bytes(BUNDLE_METADATA_ENTRY, &serde_json::to_vec_pretty(&metadata)?)?;
+                for (attachment, source) in metadata.attachments.iter().zip(&sources) {
+                    bundle.add_file(&attachment.path, source)?;
+                }
+                bundle.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()
+            });
+            if let Err(e) = written.and_then(|_| fs::rename(&staging, dest)) {
+                let _ = fs::remove_file(&staging);
+                return Err(e.into());
+            }
+
+            Ok(NoteBundleExportSummary {
+                attachment_count: metadata.attachments.len(),
+                missing_attachments,
+                bytes_written: fs::metadata(dest)?.len(),
+                path: dest_path,
+            })
+        }).await
+    }
+
+    /// Create a note in a project from a bundle written by export_note_bundle.
+    /// The attachments are copied into the project's docs/attachments folder,
+    /// numbered when a name is taken, and the note's references to them are
+    /// rewritten to the copies. Bundles with entries reaching outside the
+    /// bundle, or larger than 1 GB unpacked, are refused.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn import_note_bundle(state: &AppState, project_id: String, src_path: String) -> AppResult<NoteBundleImportSummary> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let (project, limits, max_attachment_bytes) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                (project, SettingsService::limits(conn)?, SettingsService::get_i64(conn, SETTING_ATTACHMENT_MAX_BYTES)?)
+            };
+
+            let bundle_error = |e: io::Error| match e.kind() {
+                io::ErrorKind::NotFound => AppError::NotFound("File", src_path.clone()),
+                io::ErrorKind::InvalidData => AppError::InvalidInput(format!("'{}' is not a valid note bundle: {}", src_path, e)),
+                _ => AppError::FileSystem(e),
+            };
+            let mut bundle = ArchiveReader::open(Path::new(&src_path)).map_err(bundle_error)?;
+            let entries = bundle.entries().to_vec();
+            if let Some(entry) = entries.iter().find(|entry| !entry.enclosed) {
+                return Err(AppError::InvalidInput(format!(
+                    "The bundle entry '{}' points outside the bundle; refusing to import it",
+                    entry.name
+                )));
+            }
+            let total_bytes = entries.iter().fold(0u64, |total, entry| total.saturating_add(entry.size));
+            if total_bytes > MAX_BUNDLE_BYTES {
+                return Err(AppError::InvalidInput(format!(
+                    "The bundle unpacks to {} bytes, more than the {} allowed",
+                    total_bytes, MAX_BUNDLE_BYTES
+                )));
+            }
+            let find_entry = |name: &str| entries.iter().find(|entry| entry.name == name);
+
+            let note_entry = find_entry(BUNDLE_NOTE_ENTRY)
+                .ok_or_else(|| AppError::InvalidInput(format!("The bundle has no {}", BUNDLE_NOTE_ENTRY)))?;
+            let raw = bundle.read_to_string(note_entry, MAX_BUNDLE_TEXT_BYTES).map_err(bundle_error)?;
+            let metadata: Option<NoteBundleMetadata> = match find_entry(BUNDLE_METADATA_ENTRY) {
+                Some(entry) => {
+                    let json = bundle.read_to_string(entry, MAX_BUNDLE_TEXT_BYTES).map_err(bundle_error)?;
+                    Some(serde_json::from_str(&json).map_err(|e| {
+                        AppError::InvalidInput(format!("The bundle's {} is invalid: {}", BUNDLE_METADATA_ENTRY, e))
+                    })?)
+                }
+                None => None,
+            };
+            if let Some(version) = metadata.as_ref().map(|metadata| metadata.format_version) {
+                if version > NOTE_BUNDLE_FORMAT_VERSION {
+                    return Err(AppError::InvalidInput(format!(
+                        "Note bundle format {} is newer than this app supports ({})",
+                        version, NOTE_BUNDLE_FORMAT_VERSION
+                    )));
+                }
+            }
+
+            let (fields, content) = match frontmatter::split(&raw) {
+                Some((header, body)) => (frontmatter::parse(header).unwrap_or_default(), body.to_string()),
+                None => (Map::new(), raw.clone()),
+            };
+            let title = metadata
+                .as_ref()
+                .map(|metadata| metadata.title.clone())
+                .or_else(|| fields.get("title").and_then(Value::as_str).map(str::to_string))
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or_else(|| UNTITLED_NOTE.to_string());
+            let tags = match &metadata {
+                Some(metadata) => metadata.tags.clone(),
+                None => Self::frontmatter_tags(fields.get("tags")),
+            };
+            let is_pinned = match &metadata {
+                Some(metadata) => metadata.is_pinned,
+                None => fields.get("is_pinned").and_then(Value::as_bool).unwrap_or(false),
+            };
+
+            let mut data = CreateNoteDto {
+                project_id: project.id.clone(),
+                title,
+                content,
+                tags: (!tags.is_empty()).then_some(tags),
+                is_pinned: Some(is_pinned),
+            };
+            data.validate(&limits)?;
+
+            // Without metadata.json, every file under attachments/ is taken
+            let listed: Vec<(String, String)> = match &metadata {
+                Some(metadata) => metadata
+                    .attachments
+                    .iter()
+                    .map(|attachment| (attachment.path.clone(), attachment.original_name.clone()))
+                    .collect(),
+                None => entries
+                    .iter()
+                    .filter(|entry| entry.name.starts_with(&format!("{}/", BUNDLE_ATTACHMENTS_DIR)))
+                    .map(|entry| (entry.name.clone(), entry.name.rsplit('/').next().unwrap_or_default().to_string()))
+                    .collect(),
+            };
+            let mut bundled = Vec::with_capacity(listed.len());
+            for (entry_name, original_name) in listed {
+                let entry = find_entry(&entry_name).ok_or_else(|| {
+                    AppError::InvalidInput(format!("The bundle lists the attachment '{}' but does not contain it", entry_name))
+                })?;
+                if entry.size > u64::try_from(max_attachment_bytes).unwrap_or(0) {
+                    return Err(AppError::InvalidInput(format!(
+                        "The attachment '{}' is larger than the {} bytes allowed",
+                        entry_name, max_attachment_bytes
+                    )));
+                }
+                bundled.push((entry.clone(), original_name));
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            let note_id = Uuid::new_v4().to_string();
+            let dir = Path::new(&project.path).join(ATTACHMENTS_DIR);
+            if !bundled.is_empty() {
+                fs::create_dir_all(&dir)?;
+            }
+
+            let mut attachments: Vec<NoteAttachment> = Vec::with_capacity(bundled.len());
+            let mut copies = HashMap::new();
+            let remove_copies = |attachments: &[NoteAttachment]| {
+                for attachment in attachments {
+                    let _ = fs::remove_file(Path::new(&project.path).join(&attachment.relative_path));
+                }
+            };
+            for (entry, original_name) in bundled {
+                let bundle_name = entry.name.rsplit('/').next().unwrap_or_default();
+                let copied = NoteAttachmentService::write_to_free_name(
+                    &dir,
+                    &NoteAttachmentService::safe_file_name(bundle_name),
+                    |file| bundle.extract(&entry, file).map(|_| ()),
+                );
+                let file_name = match copied {
+                    Ok(file_name) => file_name,
+                    Err(e) => {
+                        remove_copies(&attachments);
+                        return Err(e);
+                    }
+                };
+
+                let relative_path = format!("{}/{}", ATTACHMENTS_DIR, file_name);
+                copies.insert(entry.name.clone(), relative_path.clone());
+                attachments.push(NoteAttachment {
+                    id: Uuid::new_v4().to_string(),
+                    note_id: note_id.clone(),
+                    relative_path,
+                    original_name,
+                    size_bytes: i64::try_from(entry.size).unwrap_or(i64::MAX),
+                    created_at: now,
+                });
+            }
+
+            let content = markdown::rewrite_file_references(&data.content, |reference| {
+                let reference = reference.trim_start_matches("./");
+                copies.get(reference).cloned()
+            });
+            let note = Note {
+                id: note_id,
+                project_id: Some(project.id.clone()),
+                title: data.title,
+                content,
+                created_at: metadata.as_ref().map_or(now, |metadata| metadata.created_at.min(now)),
+                updated_at: now,
+                tags: data.tags,
+                is_pinned,
+                is_locked: false,
+                metadata: None,
+            };
+            {
+                let conn = &state.conn()?;
+                if let Err(e) = DbService::with_busy_retry(|| DbService::insert_note_with_attachments(conn, &note, &attachments)) {
+                    remove_copies(&attachments);
+                    return Err(e);
+                }
+            }
+
+            GitService::auto_commit(&project.path, &format!("Import note bundle: {}", note.title));
+            Ok(NoteBundleImportSummary { note, attachments })
+        }).await
+    }
+
+    /// Compare a project archive or a note bundle with the database entity by
+    /// entity, by ID and content hash: what the export has that the database
+    /// lost, what was added since, and what differs, the first differences with
+    /// their fields. An unchanged workspace verifies clean.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn verify_export(state: &AppState, path: String) -> AppResult<VerificationReport> {
+        state.blocking(move |state| {
+            if path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Export path cannot be empty".into()));
+            }
+            let mut magic = Vec::with_capacity(2);
+            fs::File::open(&path)
+                .and_then(|file| file.take(2).read_to_end(&mut magic))
+                .map_err(|e| match e.kind() {
+                    io::ErrorKind::NotFound => AppError::NotFound("File", path.clone()),
+                    _ => AppError::FileSystem(e),
+                })?;
+
+            if magic == b"PK" {
+                Self::verify_note_bundle(state, path)
+            } else {
+                Self::verify_project_archive(state, path)
+            }
+        }).await
+    }
+
+    fn verify_project_archive(state: &AppState, path: String) -> AppResult<VerificationReport> {
+        let raw = fs::read_to_string(&path)?;
+        let archive: ProjectArchive = serde_json::from_str(&raw)
+            .map_err(|e| AppError::InvalidInput(format!("Not a valid project archive: {}", e)))?;
+        let live = {
+            let conn = &state.conn()?;
+            match DbService::get_project_by_id(conn, &archive.project.id)? {
+                Some(_) => Some(Self::project_archive(conn, &archive.project.id)?),
+                None => None,
+            }
+        };
+
+        let entities = |archive: &ProjectArchive| -> AppResult<Vec<(&'static str, ComparedEntities)>> {
+            Ok(vec![
+                ("project", vec![(archive.project.id.clone(), archive.project.name.clone(), serde_json::to_value(&archive.project)?)]),
+                (
+                    "status",
+                    archive.statuses.iter().enumerate().map(|(position, status)| (status.clone(), status.clone(), json!(position))).collect(),
+                ),
+                ("task", archive.tasks.iter().map(|task| Ok((task.id.clone(), task.title.clone(), serde_json::to_value(task)?))).collect::<AppResult<_>>()?),
+                ("note", archive.notes.iter().map(|note| Ok((note.id.clone(), note.title.clone(), serde_json::to_value(note)?))).collect::<AppResult<_>>()?),
+                ("tag", archive.tags.iter().map(|tag| Ok((tag.id.clone(), tag.name.clone(), serde_json::to_value(tag)?))).collect::<AppResult<_>>()?),
+            ])
+        };
+
+        let mut report = Self::empty_report(path, ExportKind::ProjectArchive, archive.exported_at);
+        let mut live_entities = match &live {
+            Some(live) => entities(live)?,
+            None => Vec::new(),
+        };
+        for (entity_type, exported) in entities(&archive)? {
+            let live = live_entities
+                .iter_mut()
+                .find(|(live_type, _)| *live_type == entity_type)
+                .map(|(_, live)| std::mem::take(live))
+                .unwrap_or_default();
+            Self::compare_entities(&mut report, entity_type, exported, live);
+        }
+        Ok(report)
+    }
+
+    /// Compare the note of a bundle, read from its metadata.json and note.md,
+    /// with the note it was exported from. References to attachments are
+    /// compared by the attachment they name, as the bundle points them into itself.
+    fn verify_note_bundle(state: &AppState, path: String) -> AppResult<VerificationReport> {
+        let bundle_error = |e: io::Error| match e.kind() {
+            io::ErrorKind::InvalidData => AppError::InvalidInput(format!("'{}' is not a valid note bundle: {}", path, e)),
+            _ => AppError::FileSystem(e),
+        };
+        let mut bundle = ArchiveReader::open(Path::new(&path)).map_err(bundle_error)?;
+        let entries = bundle.entries().to_vec();
+        let find_entry = |name: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .ok_or_else(|| AppError::InvalidInput(format!("The bundle has no {} to verify", name)))
+        };
+        let metadata: NoteBundleMetadata = serde_json::from_str(
+            &bundle.read_to_string(find_entry(BUNDLE_METADATA_ENTRY)?, MAX_BUNDLE_TEXT_BYTES).map_err(bundle_error)?,
+        )
+        .map_err(|e| AppError::InvalidInput(format!("The bundle's {} is invalid: {}", BUNDLE_METADATA_ENTRY, e)))?;
+        let raw = bundle.read_to_string(find_entry(BUNDLE_NOTE_ENTRY)?, MAX_BUNDLE_TEXT_BYTES).map_err(bundle_error)?;
+        let body = frontmatter::split(&raw).map_or(raw.as_str(), |(_, body)| body);
+
+        let (note, attachments) = {
+            let conn = &state.conn()?;
+            match DbService::get_note_by_id(conn, &metadata.note_id)? {
+                Some(note) => {
+                    let attachments = DbService::get_note_attachments(conn, &note.id)?;
+                    (Some(note), attachments)
+                }
+                None => (None, Vec::new()),
+            }
+        };
+
+        let bundled: HashMap<&str, &str> = metadata
+            .attachments
+            .iter()
+            .map(|attachment| (attachment.path.as_str(), attachment.original_name.as_str()))
+            .collect();
+        let note_value = |title: &str, tags: &[String], created_at: i64, updated_at: i64, is_pinned: bool, content: String| {
+            let mut tags = tags.to_vec();
+            tags.sort();
+            json!({
+                "title": title,
+                "tags": tags,
+                "created_at": created_at,
+                "updated_at": updated_at,
+                "is_pinned": is_pinned,
+                "content": content,
+            })
+        };
+
+        let exported_content = markdown::rewrite_file_references(body, |reference| {
+            bundled.get(reference).map(|name| format!("attachment:{}", name))
+        });
+        let exported = vec![(
+            metadata.note_id.clone(),
+            metadata.title.clone(),
+            note_value(&metadata.title, &metadata.tags, metadata.created_at, metadata.updated_at, metadata.is_pinned, exported_content),
+        )];
+        let exported_attachments = metadata
+            .attachments
+            .iter()
+            .map(|attachment| (attachment.original_name.clone(), attachment.original_name.clone(), json!(attachment.size_bytes)))
+            .collect();
+
+        let (live, live_attachments) = match &note {
+            Some(note) => {
+                // Only the attachments that went into the bundle had their references rewritten
+                let mut content = markdown::rewrite_file_references(&note.content, |reference| {
+                    find_attachment(&attachments, reference)
+                        .filter(|attachment| bundled.values().any(|name| *name == attachment.original_name))
+                        .map(|attachment| format!("attachment:{}", attachment.original_name))
+                });
+                // note.md always ends with a line break
+                if !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                let tags = note.tags.clone().unwrap_or_default();
+                (
+                    vec![(note.id.clone(), note.title.clone(), note_value(&note.title, &tags, note.created_at, note.updated_at, note.is_pinned, content))],
+                    attachments
+                        .iter()
+                        .map(|attachment| (attachment.original_name.clone(), attachment.original_name.clone(), json!(attachment.size_bytes)))
+                        .collect(),
+                )
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let mut report = Self::empty_report(path.clone(), ExportKind::NoteBundle, metadata.exported_at);
+        Self::compare_entities(&mut report, "note", exported, live);
+        Self::compare_entities(&mut report, "attachment", exported_attachments, live_attachments);
+        Ok(report)
+    }
+
+    fn empty_report(path: String, kind: ExportKind, exported_at: i64) -> VerificationReport {
+        VerificationReport {
+            path,
+            kind,
+            exported_at,
+            checked: 0,
+            matching: 0,
+            missing: Vec::new(),
+            extra: Vec::new(),
+            differing: Vec::new(),
+        }
+    }
+
+    /// Match exported entities of one type with the live ones by ID, given as
+    /// (id, label, content), and add the outcome to the report
+    fn compare_entities(
+        report: &mut VerificationReport,
+        entity_type: &str,
+        exported: ComparedEntities,
+        live: ComparedEntities,
+    ) {
+        let entity = |id: String, label: String| VerifiedEntity { entity_type: entity_type.to_string(), id, label };
+        let mut live_by_id: HashMap<String, Value> = HashMap::with_capacity(live.len());
+        let mut live_order = Vec::with_capacity(live.len());
+        for (id, label, value) in live {
+            live_order.push((id.clone(), label));
+            live_by_id.insert(id, value);
+        }
+
+        for (id, label, value) in exported {
+            report.checked += 1;
+            let Some(live_value) = live_by_id.remove(&id) else {
+                report.missing.push(entity(id, label));
+                continue;
+            };
+            if Self::content_hash(&value) == Self::content_hash(&live_value) {
+                report.matching += 1;
+                continue;
+            }
+            let fields = if report.differing.len() < VERIFY_DIFF_SAMPLE {
+                Self::field_diffs(&value, &live_value)
+            } else {
+                Vec::new()
+            };
+            report.differing.push(EntityDifference { entity: entity(id, label), fields });
+        }
+
+        report.extra.extend(
+            live_order
+                .into_iter()
+                .filter(|(id, _)| live_by_id.contains_key(id))
+                .map(|(id, label)| entity(id, label)),
+        );
+    }
+
+    /// SHA-256 of a value's JSON, whose object keys serialize in sorted order
+    fn content_hash(value: &Value) -> String {
+        hash::sha256_hex(value.to_string().as_bytes())
+    }
+
+    /// Top-level fields whose values differ; values that are not objects are
+    /// one field named "value"
+    fn field_diffs(exported: &Value, live: &Value) -> Vec<FieldDiff> {
+        let (Value::Object(exported), Value::Object(live)) = (exported, live) else {
+            return vec![FieldDiff { field: "value".to_string(), exported: exported.clone(), live: live.clone() }];
+        };
+        let fields: BTreeSet<&String> = exported.keys().chain(live.keys()).collect();
+        fields
+            .into_iter()
+            .filter_map(|field| {
+                let (exported, live) = (exported.get(field).unwrap_or(&Value::Null), live.get(field).unwrap_or(&Value::Null));
+                (exported != live).then(|| FieldDiff { field: field.clone(), exported: exported.clone(), live: live.clone() })
+            })
+            .collect()
+    }
+
+    /// Collect .md files below `dir`, skipping hidden entries such as .git or
+    /// .obsidian. Unreadable directories are left out; linked directories are
+    /// not followed.
+    fn find_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            let path = entry.path();
+            if file_type.is_dir() {
+                Self::find_markdown_files(&path, files);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+                && path.is_file()
+            {
+                files.push(path);
+            }
+        }
+    }
+
+    /// Tags from a frontmatter value: a list, or one string separated by commas
+    /// or spaces. Leading "#" marks are dropped.
+    fn frontmatter_tags(value: Option<&Value>) -> Vec<String> {
+        let raw: Vec<String> = match value {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::String(tag) => Some(tag.clone()),
+                    Value::Number(number) => Some(number.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            Some(Value::String(tags)) => tags
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        raw.iter()
+            .map(|tag| tag.trim().trim_start_matches('#').to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+}
+
+/// Resolves the wikilinks and images of the notes in one HTML export
+struct HtmlAssets<'a> {
+    /// Anchor of each note by lowercase title; the first note given wins a repeated title
+    anchors: HashMap<String, String>,
+    project_dir: Option<&'a Path>,
+    /// Attachments by note ID
+    attachments: HashMap<String, Vec<NoteAttachment>>,
+    /// Note being rendered, whose attachments images are looked up in first
+    note_id: String,
+    /// Content of the notes ready for their links and images, by note ID
+    prerendered: HashMap<String, html::Prerendered>,
+    /// Notes the render cache did not hold
+    rendered: usize,
+    embedded: usize,
+    skipped: usize,
+}
+
+impl<'a> HtmlAssets<'a> {
+    fn new(notes: &[Note], project_dir: Option<&'a Path>) -> Self {
+        let mut anchors = HashMap::with_capacity(notes.len());
+        for note in notes {
+            anchors.entry(note.title.to_lowercase()).or_insert_with(|| Self::anchor(&note.id));
+        }
+        HtmlAssets {
+            anchors,
+            project_dir,
+            attachments: HashMap::new(),
+            note_id: String::new(),
+            prerendered: HashMap::new(),
+            rendered: 0,
+            embedded: 0,
+            skipped: 0,
+        }
+    }
+
+    fn anchor(note_id: &str) -> String {
+        format!("note-{}", note_id)
+    }
+
+    fn summary(&self, path: String, note_count: usize, bytes_written: u64) -> HtmlExportSummary {
+        HtmlExportSummary {
+            path,
+            note_count,
+            images_embedded: self.embedded,
+            images_skipped: self.skipped,
+            bytes_written,
+            notes_rendered: self.rendered,
+        }
+    }
+
+    /// Data URI of a local image: an attachment of the current note matched by
+    /// name or path, or a file inside the project. Remote URLs are never fetched.
+    fn embed(&self, reference: &str) -> Option<String> {
+        let reference = reference.trim();
+        if reference.starts_with("data:image/") {
+            return Some(reference.to_string());
+        }
+        if reference.contains("://") {
+            return None;
+        }
+        let project_dir = self.project_dir?;
+
+        let relative = self
+            .attachments
+            .get(&self.note_id)
+            .and_then(|attachments| find_attachment(attachments, reference))
+            .map_or(reference, |attachment| attachment.relative_path.as_str());
+
+        // Only files inside the project are read, whatever the note links to
+        let root = project_dir.canonicalize().ok()?;
+        let file = project_dir.join(relative).canonicalize().ok()?;
+        if !path::is_within(&file, &root) {
+            return None;
+        }
+
+        let extension = file.extension()?.to_str()?.to_ascii_lowercase();
+        let mime = mime::mime_from_extension(&extension).filter(|mime| mime.starts_with("image/"))?;
+        if fs::metadata(&file).ok()?.len() > MAX_HTML_IMAGE_BYTES {
+            return None;
+        }
+        let bytes = fs::read(&file).ok()?;
+        Some(format!("data:{};base64,{}", mime, base64::encode(&bytes)))
+    }
+}
+
+impl html::Resolver for HtmlAssets<'_> {
+    fn note_href(&mut self, target: &str) -> Option<String> {
+        self.anchors.get(&target.to_lowercase()).map(|anchor| format!("#{}", anchor))
+    }
+
+    fn image_source(&mut self, reference: &str) -> Option<String> {
+        let source = self.embed(reference);
+        if source.is_some() {
+            self.embedded += 1;
+        } else {
+            self.skipped += 1;
+        }
+        source
+    }
+}
+
+/// Resolves the links of a static site's note pages: wikilinks to the other note
+/// pages, and images to copies of the project files they show
+struct SiteLinks<'a> {
+    /// Page of each note by lowercase title; the first note given wins a repeated title
+    pages: HashMap<String, &'a str>,
+    project_dir: &'a Path,
+    dest: &'a Path,
+    /// Attachments of the note being rendered
+    attachments: &'a [NoteAttachment],
+    /// Copied files, relative to `dest`
+    copied: BTreeSet<String>,
+}
+
+impl<'a> SiteLinks<'a> {
+    fn new(notes: &[Note], pages: &'a HashMap<String, String>, project_dir: &'a Path, dest: &'a Path) -> Self {
+        let mut by_title = HashMap::with_capacity(notes.len());
+        for note in notes {
+            by_title.entry(note.title.to_lowercase()).or_insert_with(|| pages[&note.id].as_str());
+        }
+        SiteLinks {
+            pages: by_title,
+            project_dir,
+            dest,
+            attachments: &[],
+            copied: BTreeSet::new(),
+        }
+    }
+
+    /// Copy a file of the project into the site's files/ folder, once. Returns its
+    /// path relative to the site, or None when it is missing or outside the project.
+    fn copy(&mut self, relative: &str) -> Option<String> {
+        let root = self.project_dir.canonicalize().ok()?;
+        let file = self.project_dir.join(relative).canonicalize().ok()?;
+        if !path::is_within(&file, &root) || !file.is_file() {
+            return None;
+        }
+
+        let inside = file.strip_prefix(&root).ok()?.to_string_lossy().replace('\\', "/");
+        let target = format!("{}/{}", SITE_FILES_DIR, inside);
+        if !self.copied.contains(&target) {
+            let dest = self.dest.join(&target);
+            fs::create_dir_all(dest.parent()?).ok()?;
+            fs::copy(&file, &dest).ok()?;
+            self.copied.insert(target.clone());
+        }
+        Some(target)
+    }
+}
+
+impl html::Resolver for SiteLinks<'_> {
+    fn note_href(&mut self, target: &str) -> Option<String> {
+        self.pages.get(&target.to_lowercase()).map(|page| site_href(page))
+    }
+
+    fn image_source(&mut self, reference: &str) -> Option<String> {
+        let reference = reference.trim();
+        if reference.starts_with("data:image/") {
+            return Some(reference.to_string());
+        }
+        if reference.contains("://") {
+            return None;
+        }
+        let relative = find_attachment(self.attachments, reference)
+            .map_or(reference, |attachment| attachment.relative_path.as_str())
+            .to_string();
+        self.copy(&relative).map(|copied| format!("../{}", site_href(&copied)))
+    }
+}
+
+/// A relative path as a URL path, percent-encoding everything but unreserved
+/// characters and the separators
+fn site_href(relative: &str) -> String {
+    let mut href = String::with_capacity(relative.len());
+    for byte in relative.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            href.push(byte as char);
+        } else {
+            href.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    href
+}
+
+/// The attachment a note's reference points at: matched by path in the
+/// project, by the copy's file name or by the name it was attached under.
+/// Spaces may be written as "%20", as they are in Markdown link targets.
+fn find_attachment<'a>(attachments: &'a [NoteAttachment], reference: &str) -> Option<&'a NoteAttachment> {
+    let reference = &reference.replace("%20", " ");
+    let name = reference.rsplit(['/', '\\']).next().unwrap_or(reference);
+    attachments.iter().find(|attachment| {
+        attachment.relative_path.eq_ignore_ascii_case(reference)
+            || attachment.original_name.eq_ignore_ascii_case(name)
+            || attachment.relative_path.rsplit('/').next().is_some_and(|file| file.eq_ignore_ascii_case(name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProjectSort, UpdateNoteDto};
+    use crate::services::test_support;
+
+    #[tokio::test]
+    async fn project_site_links_pages_and_copies_attachments() {
+        let state = test_support::open_state();
+        let (project, dest) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Field study");
+            let first = test_support::note(conn, &project.id, "Site visit", "See [[Results 2]] and [[Nowhere]].\n\n![map](map%20one.png)");
+            test_support::note(conn, &project.id, "Results 2", "Back to [[site visit]]");
+            let attachment = NoteAttachment {
+                id: Uuid::new_v4().to_string(),
+                note_id: first.id,
+                relative_path: "docs/attachments/map one.png".to_string(),
+                original_name: "map one.png".to_string(),
+                size_bytes: 3,
+                created_at: 0,
+            };
+            DbService::insert_note_attachment(conn, &attachment).unwrap();
+            test_support::reference(conn, &project.id, "doe2020", "On fields");
+            test_support::task(conn, &project.id, "Write up");
+            (project, test_support::temp_dir().join("site"))
+        };
+        let attachments = Path::new(&project.path).join("docs/attachments");
+        fs::create_dir_all(&attachments).unwrap();
+        fs::write(attachments.join("map one.png"), "png").unwrap();
+
+        let summary = ExportService::export_project_site(&state, project.id.clone(), dest.to_string_lossy().into_owned())
+            .await
+            .unwrap();
+        assert_eq!((summary.pages_written, summary.files_copied, summary.files_removed), (4, 1, 0));
+
+        let index = fs::read_to_string(dest.join("index.html")).unwrap();
+        assert!(index.contains("<a href=\"notes/site-visit.html\">Site visit</a>"));
+        assert!(index.contains("<a href=\"references.html\">References</a>"));
+        assert!(index.contains("<tr><td>todo</td><td>1</td></tr>\n<tr><td>in_progress</td><td>0</td></tr>"));
+        let page = fs::read_to_string(dest.join("notes/site-visit.html")).unwrap();
+        assert!(page.contains("<link rel=\"stylesheet\" href=\"../style.css\">"));
+        assert!(page.contains("<a href=\"results-2.html\">Results 2</a>"));
+        assert!(page.contains("<span class=\"missing-link\">Nowhere</span>"));
+        assert!(page.contains("src=\"../files/docs/attachments/map%20one.png\""));
+        assert_eq!(fs::read_to_string(dest.join("files/docs/attachments/map one.png")).unwrap(), "png");
+        assert!(fs::read_to_string(dest.join("references.html")).unwrap().contains("id=\"doe2020\""));
+    }
+
+    #[tokio::test]
+    async fn regenerating_a_site_removes_only_its_stale_files() {
+        let state = test_support::open_state();
+        let (project, note) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Site");
+            let note = test_support::note(conn, &project.id, "Draft", "");
+            (project, note)
+        };
+        let dest = test_support::temp_dir().join("site");
+        let dest_dir = dest.to_string_lossy().into_owned();
+        ExportService::export_project_site(&state, project.id.clone(), dest_dir.clone()).await.unwrap();
+        assert!(dest.join("notes/draft.html").exists());
+
+        fs::write(dest.join("notes/mine.html"), "kept").unwrap();
+        DbService::delete_note(&state.conn().unwrap(), &note.id, false).unwrap();
+        let summary = ExportService::export_project_site(&state, project.id.clone(), dest_dir).await.unwrap();
+        assert_eq!(summary.files_removed, 1);
+        assert!(!dest.join("notes/draft.html").exists());
+        assert!(dest.join("notes/mine.html").exists());
+
+        // A folder with other content and no manifest is not written into
+        let foreign = test_support::temp_dir();
+        fs::write(foreign.join("notes.txt"), "mine").unwrap();
+        let result = ExportService::export_project_site(&state, project.id, foreign.to_string_lossy().into_owned()).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn exporting_an_unchanged_project_again_renders_nothing() {
+        let state = test_support::open_state();
+        let (project, first) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Big");
+            let notes: Vec<Note> = (0..500)
+                .map(|i| test_support::new_note(Some(&project.id), &format!("Note {}", i), &format!("# Note {}\n\nSee [[Note {}]]", i, i + 1)))
+                .collect();
+            DbService::insert_notes(conn, &notes).unwrap();
+            (project, notes.into_iter().next().unwrap())
+        };
+        let dest = test_support::temp_dir();
+        let html_path = dest.join("project.html").to_string_lossy().into_owned();
+
+        let export = || ExportService::export_project_html(&state, project.id.clone(), html_path.clone());
+        assert_eq!(export().await.unwrap().notes_rendered, 500);
+        let rendered = fs::read_to_string(&html_path).unwrap();
+        assert_eq!(export().await.unwrap().notes_rendered, 0);
+        assert_eq!(fs::read_to_string(&html_path).unwrap(), rendered);
+
+        // The site reuses what the single-file export rendered
+        let site_dir = dest.join("site").to_string_lossy().into_owned();
+        let site = ExportService::export_project_site(&state, project.id.clone(), site_dir).await.unwrap();
+        assert_eq!(site.notes_rendered, 0);
+
+        let edit = UpdateNoteDto {
+            title: None,
+            content: Some("Changed".to_string()),
+            tags: None,
+            is_pinned: None,
+            expected_updated_at: None,
+        };
+        DbService::update_note(&state.conn().unwrap(), &first.id, &edit).unwrap();
+        assert_eq!(export().await.unwrap().notes_rendered, 1);
+
+        assert_eq!(ExportService::clear_render_cache(&state).await.unwrap(), 500);
+        assert_eq!(export().await.unwrap().notes_rendered, 500);
+    }
+
+    #[tokio::test]
+    async fn exported_notes_list_backlinks_that_import_strips() {
+        let state = test_support::open_state();
+        let (project, other) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Linked");
+            let other = test_support::project(conn, "Reimported");
+            let mut target = test_support::new_note(Some(&project.id), "Methods", "How we sampled");
+            target.created_at -= 10;
+            let source = test_support::new_note(Some(&project.id), "Results [draft]", "Following [[methods]]");
+            DbService::insert_notes(conn, &[target, source]).unwrap();
+            (project, other)
+        };
+
+        let dest = test_support::temp_dir().join("notes");
+        let dest_dir = Some(dest.to_string_lossy().into_owned());
+        ExportService::export_notes_markdown(&state, project.id.clone(), dest_dir.clone(), None).await.unwrap();
+        let methods = fs::read_to_string(dest.join("methods.md")).unwrap();
+        assert!(methods.ends_with(&format!(
+            "How we sampled\n\n{}\n## Linked from\n\n- [Results \\[draft\\]](./results-draft.md)\n{}\n",
+            BACKLINKS_START, BACKLINKS_END
+        )));
+        assert!(!fs::read_to_string(dest.join("results-draft.md")).unwrap().contains(BACKLINKS_START));
+
+        // Importing the export gives back the notes as written without the section
+        let imported = ExportService::import_notes_markdown(&state, other.id.clone(), dest.to_string_lossy().into_owned()).await.unwrap();
+        assert!(imported.iter().all(|result| result.status == MarkdownImportStatus::Imported));
+        let contents: Vec<String> = DbService::get_notes_by_project(&state.conn().unwrap(), &other.id)
+            .unwrap()
+            .into_iter()
+            .map(|note| note.content)
+            .collect();
+        assert!(contents.contains(&"How we sampled\n".to_string()));
+        assert!(contents.contains(&"Following [[methods]]\n".to_string()));
+
+        ExportService::export_notes_markdown(&state, project.id.clone(), dest_dir, Some(false)).await.unwrap();
+        assert!(!fs::read_to_string(dest.join("methods.md")).unwrap().contains(BACKLINKS_START));
+    }
+
+    #[tokio::test]
+    async fn verifying_an_archive_flags_exactly_the_changed_note() {
+        let state = test_support::open_state();
+        let (project, changed) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Seeded");
+            test_support::task(conn, &project.id, "Collect samples");
+            test_support::task(conn, &project.id, "Analyse");
+            test_support::note(conn, &project.id, "Kept", "Unchanged");
+            let changed = test_support::note(conn, &project.id, "Changed", "Before");
+            (project, changed)
+        };
+        let dest = test_support::temp_dir().join("seeded.json").to_string_lossy().into_owned();
+        ExportService::export_project(&state, project.id.clone(), dest.clone()).await.unwrap();
+
+        let report = ExportService::verify_export(&state, dest.clone()).await.unwrap();
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(report.kind, ExportKind::ProjectArchive);
+        assert_eq!(report.checked, report.matching);
+
+        let update = UpdateNoteDto {
+            title: None,
+            content: Some("After".to_string()),
+            tags: None,
+            is_pinned: None,
+            expected_updated_at: None,
+        };
+        DbService::update_note(&state.conn().unwrap(), &changed.id, &update).unwrap();
+        let report = ExportService::verify_export(&state, dest.clone()).await.unwrap();
+        assert!(report.missing.is_empty() && report.extra.is_empty());
+        assert_eq!(report.differing.len(), 1);
+        let difference = &report.differing[0];
+        assert_eq!((difference.entity.entity_type.as_str(), difference.entity.id.as_str()), ("note", changed.id.as_str()));
+        let content = difference.fields.iter().find(|field| field.field == "content").unwrap();
+        assert_eq!((content.exported.clone(), content.live.clone()), (json!("Before"), json!("After")));
+
+        // Deleted since the export, and added since
+        DbService::delete_note(&state.conn().unwrap(), &changed.id, false).unwrap();
+        let added = test_support::task(&state.conn().unwrap(), &project.id, "New");
+        let report = ExportService::verify_export(&state, dest).await.unwrap();
+        assert_eq!(report.missing.iter().map(|entity| entity.id.as_str()).collect::<Vec<_>>(), vec![changed.id.as_str()]);
+        assert_eq!(report.extra.iter().map(|entity| entity.id.as_str()).collect::<Vec<_>>(), vec![added.id.as_str()]);
+    }
+
+    #[tokio::test]
+    async fn verifying_a_note_bundle_compares_note_and_attachments() {
+        let state = test_support::open_state();
+        let (project, note) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Bundled");
+            let note = test_support::note(conn, &project.id, "Figure", "![plot](plot.png)");
+            let attachment = NoteAttachment {
+                id: Uuid::new_v4().to_string(),
+                note_id: note.id.clone(),
+                relative_path: "docs/attachments/plot.png".to_string(),
+                original_name: "plot.png".to_string(),
+                size_bytes: 4,
+                created_at: 0,
+            };
+            DbService::insert_note_attachment(conn, &attachment).unwrap();
+            (project, note)
+        };
+        let attachments = Path::new(&project.path).join(ATTACHMENTS_DIR);
+        fs::create_dir_all(&attachments).unwrap();
+        fs::write(attachments.join("plot.png"), "plot").unwrap();
+        let dest = test_support::temp_dir().join("figure.zip").to_string_lossy().into_owned();
+        ExportService::export_note_bundle(&state, note.id.clone(), dest.clone()).await.unwrap();
+
+        let report = ExportService::verify_export(&state, dest.clone()).await.unwrap();
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!((report.kind, report.checked), (ExportKind::NoteBundle, 2));
+
+        let update = UpdateNoteDto {
+            title: Some("Figure 1".to_string()),
+            content: None,
+            tags: None,
+            is_pinned: None,
+            expected_updated_at: None,
+        };
+        DbService::update_note(&state.conn().unwrap(), &note.id, &update).unwrap();
+        let report = ExportService::verify_export(&state, dest).await.unwrap();
+        assert_eq!(report.differing.len(), 1);
+        let fields: Vec<&str> = report.differing[0].fields.iter().map(|field| field.field.as_str()).collect();
+        assert!(fields.contains(&"title"), "{:?}", fields);
+        assert!(!fields.contains(&"content"), "{:?}", fields);
+    }
+
+    #[test]
+    fn stripping_backlinks_keeps_text_after_the_section() {
+        let section = format!("{}\n## Linked from\n\n- [A](./a.md)\n{}\n", BACKLINKS_START, BACKLINKS_END);
+        assert_eq!(ExportService::strip_backlinks(&format!("Body\n\n{}", section)), "Body\n");
+        assert_eq!(ExportService::strip_backlinks("Body"), "Body");
+        // A section the user wrote text after is theirs now
+        let edited = format!("Body\n\n{}More", section);
+        assert_eq!(ExportService::strip_backlinks(&edited), edited);
+        let unterminated = format!("Body\n\n{}\n", BACKLINKS_START);
+        assert_eq!(ExportService::strip_backlinks(&unterminated), unterminated);
+    }
+
+    fn csv_rows(path: &Path) -> Vec<Vec<String>> {
+        csv::parse(&fs::read_to_string(path).unwrap()).unwrap().into_iter().map(|record| record.fields).collect()
+    }
+
+    #[tokio::test]
+    async fn references_csv_escapes_authors_and_filters_rows() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Reading list");
+            let mut quoted = test_support::reference(conn, &project.id, "doe2020", "On \"quoted\" titles");
+            quoted.authors = vec!["Doe, Jane".to_string(), "O\"Brien, Pat".to_string()];
+            quoted.year = Some(2020);
+            quoted.reading_status = "read".to_string();
+            quoted.tags = Some(vec!["Methods".to_string(), "core".to_string()]);
+            DbService::upsert_reference(conn, &quoted).unwrap();
+            let mut other = test_support::reference(conn, &project.id, "roe2021", "Plain");
+            other.tags = Some(vec!["methods".to_string()]);
+            DbService::upsert_reference(conn, &other).unwrap();
+            test_support::reference(conn, &project.id, "zed2022", "Untagged");
+            project
+        };
+        let dest = Path::new(&project.path).join("refs.csv");
+        let dest_path = dest.to_string_lossy().into_owned();
+        let added = chrono::Utc::now().format("%Y-%m-%d").to_string();
+
+        let written = ExportService::export_references_csv(&state, project.id.clone(), dest_path.clone(), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(written, 3);
+        let text = fs::read_to_string(&dest).unwrap();
+        assert!(text.contains("\"Doe, Jane; O\"\"Brien, Pat\""));
+        let rows = csv_rows(&dest);
+        assert_eq!(rows[0], REFERENCE_CSV_COLUMNS.to_vec());
+        assert_eq!(rows[1], vec![
+            "doe2020", "On \"quoted\" titles", "Doe, Jane; O\"Brien, Pat", "2020", "", "", "read", "Methods; core", added.as_str(),
+        ]);
+        assert_eq!(rows[3][6], "unread");
+
+        let columns = Some(vec!["title".to_string(), "cite_key".to_string()]);
+        let written = ExportService::export_references_csv(&state, project.id.clone(), dest_path.clone(), columns, Some("METHODS".into()), None)
+            .await
+            .unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(csv_rows(&dest), vec![
+            vec!["title", "cite_key"],
+            vec!["On \"quoted\" titles", "doe2020"],
+            vec!["Plain", "roe2021"],
+        ]);
+
+        let written = ExportService::export_references_csv(&state, project.id.clone(), dest_path.clone(), None, Some("methods".into()), Some("read".into()))
+            .await
+            .unwrap();
+        assert_eq!(written, 1);
+
+        let unknown = ExportService::export_references_csv(&state, project.id, dest_path, Some(vec!["abstract".into()]), None, None).await;
+        assert!(matches!(unknown, Err(AppError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn ical_export_adds_deadlines_as_events() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let other = test_support::project(conn, "Other");
+            let mut task = test_support::new_task(&project.id, "Draft");
+            task.due_date = Some(1_767_225_600);
+            DbService::insert_task_with_key(conn, &mut task).unwrap();
+            for (id, project_id, name) in [("d1", Some(&project.id), "CHI paper, final"), ("d2", None, "Grant"), ("d3", Some(&other.id), "Elsewhere")] {
+                let deadline = Deadline {
+                    id: id.to_string(),
+                    project_id: project_id.cloned(),
+                    name: name.to_string(),
+                    date: 1_767_268_799,
+                    url: Some("https://chi.acm.org".to_string()),
+                    notes: Some("Camera ready".to_string()),
+                    created_at: 0,
+                };
+                DbService::insert_deadline(conn, &deadline).unwrap();
+            }
+            project
+        };
+        let dest = Path::new(&project.path).join("tasks.ics");
+
+        let written = ExportService::export_tasks_ical(&state, Some(project.id.clone()), dest.to_string_lossy().into_owned(), IcalComponent::Todo)
+            .await
+            .unwrap();
+        assert_eq!(written, 3);
+        let calendar = fs::read_to_string(&dest).unwrap();
+        assert_eq!(calendar.matches("BEGIN:VTODO").count(), 1);
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+        assert!(calendar.contains(
+            "BEGIN:VEVENT\r\nUID:deadline-d1@research-vault\r\nDTSTAMP:"
+        ));
+        assert!(calendar.contains("SUMMARY:CHI paper\\, final\r\nDESCRIPTION:Camera ready\r\nURL:https://chi.acm.org\r\nDTSTART:20260101T115959Z\r\n"));
+        assert!(calendar.contains("UID:deadline-d2@research-vault"));
+        assert!(!calendar.contains("Elsewhere"));
+
+        let written = ExportService::export_tasks_ical(&state, None, dest.to_string_lossy().into_owned(), IcalComponent::Event)
+            .await
+            .unwrap();
+        assert_eq!(written, 4);
+        assert!(fs::read_to_string(&dest).unwrap().contains("UID:deadline-d3@research-vault"));
+    }
+
+    #[tokio::test]
+    async fn aoe_feed_deadlines_reach_the_calendar_in_utc() {
+        let state = test_support::open_state();
+        let feed = test_support::temp_dir().join("feed.yml");
+        fs::write(
+            &feed,
+            "- title: CHI\n  year: 2026\n  deadline: '2025-09-11 23:59'\n  timezone: AoE\n\
+             - title: UIST\n  year: 2026\n  deadline: '2026-04-01 23:59'\n",
+        )
+        .unwrap();
+        crate::services::DeadlineService::import_deadlines_feed(&state, feed.to_string_lossy().into_owned(), None, false)
+            .await
+            .unwrap();
+
+        let dest = test_support::temp_dir().join("deadlines.ics");
+        let written = ExportService::export_tasks_ical(&state, None, dest.to_string_lossy().into_owned(), IcalComponent::Event)
+            .await
+            .unwrap();
+        assert_eq!(written, 2);
+        let calendar = fs::read_to_string(&dest).unwrap();
+        // 23:59 at UTC-12 is 11:59 the next day in UTC; feeds without a timezone are AoE too
+        assert!(calendar.contains("SUMMARY:CHI 2026\r\n"));
+        assert!(calendar.contains("DTSTART:20250912T115900Z\r\n"));
+        assert!(calendar.contains("DTSTART:20260402T115900Z\r\n"));
+        assert!(!calendar.contains("DTSTART:20250911"));
+    }
+
+    /// Tasks as (title, parent title) pairs and notes as (title, content, tags), sorted
+    type Shape = (Vec<(String, Option<String>)>, Vec<(String, String, Option<Vec<String>>)>);
+
+    fn project_shape(conn: &Connection, project_id: &str) -> Shape {
+        let tasks = DbService::get_tasks_by_project(conn, project_id).unwrap();
+        let titles: HashMap<&str, &str> = tasks.iter().map(|t| (t.id.as_str(), t.title.as_str())).collect();
+        let mut tree: Vec<(String, Option<String>)> = tasks
+            .iter()
+            .map(|t| (t.title.clone(), t.parent_id.as_deref().map(|p| titles[p].to_string())))
+            .collect();
+        tree.sort();
+        let mut notes: Vec<_> = DbService::get_notes_by_project(conn, project_id)
+            .unwrap()
+            .into_iter()
+            .map(|n| (n.title, n.content, n.tags))
+            .collect();
+        notes.sort();
+        (tree, notes)
+    }
+
+    #[tokio::test]
+    async fn imported_archive_has_the_same_tree_and_notes_under_new_ids() {
+        let state = test_support::open_state();
+        let (project, source_ids) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Round trip");
+            let root = test_support::task(conn, &project.id, "Chapter 1");
+            let mut child = test_support::new_task(&project.id, "Section 1.1");
+            child.parent_id = Some(root.id.clone());
+            DbService::insert_task_with_key(conn, &mut child).unwrap();
+            let mut grandchild = test_support::new_task(&project.id, "Figure 1.1.a");
+            grandchild.parent_id = Some(child.id.clone());
+            DbService::insert_task_with_key(conn, &mut grandchild).unwrap();
+            test_support::task(conn, &project.id, "Chapter 2");
+            let mut note = test_support::new_note(Some(&project.id), "Outline", "# Plan\n\nSee [[Sources]]");
+            note.tags = Some(vec!["writing".to_string()]);
+            DbService::insert_note(conn, &note).unwrap();
+            let sources = test_support::note(conn, &project.id, "Sources", "Vaswani et al.");
+            let ids: Vec<String> = [root.id, child.id, grandchild.id, note.id, sources.id].into();
+            (project, ids)
+        };
+
+        let archive = test_support::temp_dir().join("project.json");
+        let exported = ExportService::export_project(&state, project.id.clone(), archive.to_string_lossy().into_owned())
+            .await
+            .unwrap();
+        assert_eq!((exported.task_count, exported.note_count), (4, 2));
+
+        let new_path = test_support::temp_dir().join("copy");
+        let summary = ExportService::import_project(
+            &state,
+            archive.to_string_lossy().into_owned(),
+            new_path.to_string_lossy().into_owned(),
+        )
+        .await
+        .unwrap();
+        assert_ne!(summary.project.id, project.id);
+        assert_eq!(summary.project.path, new_path.to_string_lossy());
+        assert!(new_path.join("research.json").is_file());
+
+        let conn = &state.conn().unwrap();
+        assert_eq!(project_shape(conn, &summary.project.id), project_shape(conn, &project.id));
+
+        let tasks = DbService::get_tasks_by_project(conn, &summary.project.id).unwrap();
+        let notes = DbService::get_notes_by_project(conn, &summary.project.id).unwrap();
+        let imported_ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).chain(notes.iter().map(|n| n.id.as_str())).collect();
+        assert!(imported_ids.iter().all(|id| !source_ids.iter().any(|source| source == id)));
+        assert!(tasks.iter().all(|t| t.project_id == summary.project.id));
+        assert!(tasks.iter().all(|t| t.parent_id.as_ref().is_none_or(|p| tasks.iter().any(|parent| &parent.id == p))));
+        // The source project is untouched
+        assert_eq!(DbService::get_tasks_by_project(conn, &project.id).unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn import_rejects_taken_paths_and_newer_archives() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Source");
+            test_support::task(conn, &project.id, "Only task");
+            project
+        };
+        let archive = test_support::temp_dir().join("project.json");
+        let archive_path = archive.to_string_lossy().into_owned();
+        ExportService::export_project(&state, project.id.clone(), archive_path.clone()).await.unwrap();
+        let project_count = || DbService::get_all_projects(&state.conn().unwrap(), true, ProjectSort::default()).unwrap().len();
+
+        // The path of a registered project, and an existing folder
+        let result = ExportService::import_project(&state, archive_path.clone(), project.path.clone()).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let existing = test_support::temp_dir();
+        let result = ExportService::import_project(&state, archive_path.clone(), existing.to_string_lossy().into_owned()).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        let mut json: Value = serde_json::from_str(&fs::read_to_string(&archive).unwrap()).unwrap();
+        json["schema_version"] = json!(EXPORT_SCHEMA_VERSION + 1);
+        fs::write(&archive, json.to_string()).unwrap();
+        let new_path = test_support::temp_dir().join("copy");
+        let result = ExportService::import_project(&state, archive_path, new_path.to_string_lossy().into_owned()).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+        assert!(!new_path.exists());
+        assert_eq!(project_count(), 1);
+    }
+}
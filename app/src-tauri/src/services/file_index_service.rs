@@ -0,0 +1,892 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ChangedFiles, DuplicateFile, DuplicateGroup, DuplicateReport, DuplicateScanProgress, FileIndexEntry, FileIndexError,
+    FileIndexSummary, FileInfo, FileMetadata, FileSearchResult, ScannedFile,
+};
+use crate::services::DbService;
+use crate::state::AppState;
+use crate::utils::ignore::IgnoreRules;
+use crate::utils::{hash, mime, research_json};
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Directories never descended into while indexing
+const SKIPPED_DIRS: [&str; 1] = [".git"];
+
+/// research.json settings key holding extra gitignore-style patterns
+const IGNORE_PATTERNS_SETTING: &str = "ignore_patterns";
+
+/// research.json settings key overriding the largest file whose content is indexed
+const MAX_CONTENT_BYTES_SETTING: &str = "max_indexed_content_bytes";
+
+/// Files larger than this are indexed without their content by default
+const DEFAULT_MAX_CONTENT_BYTES: u64 = 1024 * 1024;
+
+/// research.json settings key overriding the largest file whose content is hashed
+const MAX_HASH_BYTES_SETTING: &str = "max_hashed_file_bytes";
+
+/// Files larger than this are not hashed by default; their changes are told
+/// from their size and modification time alone
+const DEFAULT_MAX_HASH_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Leading bytes checked for NUL when telling text from binary content
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Most results returned by a content search
+const MAX_SEARCH_RESULTS: i64 = 100;
+
+/// Files returned by get_largest_files when no limit is given
+const DEFAULT_LARGEST_FILES: usize = 20;
+
+/// Most files returned by get_largest_files
+const MAX_LARGEST_FILES: usize = 500;
+
+/// Event sent with a `DuplicateScanProgress` while duplicate files are compared
+pub const DUPLICATE_SCAN_PROGRESS_EVENT: &str = "duplicate-scan-progress";
+
+/// Least time between two duplicate search progress events
+const DUPLICATE_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Outcome of walking a project directory
+struct ScanResult {
+    files: Vec<ScannedFile>,
+    /// Files with a new modification time but the same content hash, as (id, modified_at)
+    touched: Vec<(String, i64)>,
+    /// Every relative file path visited, including ignored files
+    seen: HashSet<String>,
+    /// Directories skipped by an ignore pattern
+    ignored_dirs: Vec<String>,
+    summary: FileIndexSummary,
+}
+
+/// Indexes the files of project directories into file_metadata
+pub struct FileIndexService;
+
+impl FileIndexService {
+    /// Walk a project directory and record its files. Paths matching .gitignore
+    /// or the ignore_patterns setting are flagged ignored (ignored directories
+    /// are not descended into), files ignored by hand are left untouched and
+    /// indexed files that disappeared are flagged deleted. The content of small
+    /// text files is stored for search and the content of files up to a size
+    /// threshold is hashed. Files whose modification time and size did not
+    /// change since the last run are not read again; files whose time changed
+    /// but whose hash did not only get the new time.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn index_project_files(state: &AppState, project_id: String) -> AppResult<FileIndexSummary> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let (project_path, known) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                (project.path, DbService::get_file_index(conn, &project_id)?)
+            };
+
+            let root = PathBuf::from(&project_path);
+            if !root.is_dir() {
+                return Err(AppError::NotFound("Project directory", project_path));
+            }
+
+            let settings = research_json::read_settings(&project_path);
+            let mut settings_rules = IgnoreRules::new();
+            for pattern in Self::ignore_patterns(&settings) {
+                settings_rules.add_pattern("", &pattern);
+            }
+            let max_content_bytes = settings
+                .get(MAX_CONTENT_BYTES_SETTING)
+                .and_then(Value::as_u64)
+                .unwrap_or(DEFAULT_MAX_CONTENT_BYTES);
+            let max_hash_bytes = settings
+                .get(MAX_HASH_BYTES_SETTING)
+                .and_then(Value::as_u64)
+                .unwrap_or(DEFAULT_MAX_HASH_BYTES);
+
+            // Walk without holding the database lock
+            let scan = Self::scan(&root, &project_id, &known, &settings_rules, max_content_bytes, max_hash_bytes);
+            let mut summary = scan.summary;
+
+            // Files under an ignored directory were not visited: flag them ignored
+            // unless a manual choice exists, instead of treating them as deleted
+            let mut deleted_ids = Vec::new();
+            let mut ignored_ids = Vec::new();
+            for (path, entry) in &known {
+                if scan.seen.contains(path) || entry.ignore_override == Some(true) {
+                    continue;
+                }
+                if scan.ignored_dirs.iter().any(|dir| Self::is_within(path, dir)) {
+                    if entry.ignore_override.is_none() && !entry.is_ignored {
+                        ignored_ids.push(entry.id.clone());
+                    }
+                } else {
+                    deleted_ids.push(entry.id.clone());
+                }
+            }
+            summary.deleted = deleted_ids.len();
+
+            let conn = &state.conn()?;
+            DbService::with_busy_retry(|| {
+                DbService::save_file_index(conn, &scan.files, &scan.touched, &deleted_ids, &ignored_ids)
+            })?;
+            Ok(summary)
+        }).await
+    }
+
+    /// Files of a project added, modified or deleted at or after `since`, as
+    /// recorded by index_project_files. Changes are told by content hash, so a
+    /// file whose modification time moved without its content changing is not
+    /// listed. `ChangedFiles::summary` turns the result into a commit message.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_changed_files(state: &AppState, project_id: String, since: i64) -> AppResult<ChangedFiles> {
+        if project_id.is_empty() {
+            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+        }
+
+        state.run(move |conn| {
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::get_changed_files(conn, &project_id, since)
+        }).await
+    }
+
+    /// List the indexed files of a project that still exist
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_project_files(state: &AppState, project_id: String) -> AppResult<Vec<FileMetadata>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::get_project_files(conn, &project_id)
+        }).await
+    }
+
+    /// List the indexed files in `subdir` of a project (the root when None), with
+    /// the files of nested directories when `recursive`. Each file is compared
+    /// with the disk; with `refresh` the index is updated from it first. Files
+    /// new on disk are only found by index_project_files.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_files(
+        state: &AppState,
+        project_id: String,
+        subdir: Option<String>,
+        recursive: bool,
+        refresh: bool,
+    ) -> AppResult<Vec<FileInfo>> {
+        state.blocking(move |state| {
+            let dir = Self::normalize_relative_path(subdir.as_deref().unwrap_or(""))?;
+            let project_path = Self::project_path(state, &project_id)?;
+            let files = {
+                let conn = &state.conn()?;
+                DbService::get_files_in_dir(conn, &project_id, &dir, recursive)?
+            };
+            Self::compare_with_disk(state, &project_path, files, refresh)
+        }).await
+    }
+
+    /// Get one indexed file by its path relative to the project root
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_file_info(state: &AppState, project_id: String, relative_path: String, refresh: bool) -> AppResult<FileInfo> {
+        state.blocking(move |state| {
+            let path = Self::normalize_relative_path(&relative_path)?;
+            if path.is_empty() {
+                return Err(AppError::InvalidInput("File path cannot be empty".into()));
+            }
+            let project_path = Self::project_path(state, &project_id)?;
+            let file = {
+                let conn = &state.conn()?;
+                DbService::get_file_by_path(conn, &project_id, &path)?
+                    .ok_or_else(|| AppError::NotFound("File", path.clone()))?
+            };
+
+            // A refresh drops a file that is gone from the disk
+            Self::compare_with_disk(state, &project_path, vec![file], refresh)?
+                .pop()
+                .ok_or(AppError::NotFound("File", path))
+        }).await
+    }
+
+    /// The largest indexed files of a project, for a storage breakdown
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_largest_files(state: &AppState, project_id: String, limit: Option<usize>) -> AppResult<Vec<FileInfo>> {
+        state.blocking(move |state| {
+            let limit = limit.unwrap_or(DEFAULT_LARGEST_FILES).clamp(1, MAX_LARGEST_FILES);
+            let project_path = Self::project_path(state, &project_id)?;
+            let files = {
+                let conn = &state.conn()?;
+                DbService::get_largest_files(conn, &project_id, limit as i64)?
+            };
+            Self::compare_with_disk(state, &project_path, files, false)
+        }).await
+    }
+
+    /// Search the indexed contents of a project's files
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn search_project_files(state: &AppState, project_id: String, query: String) -> AppResult<Vec<FileSearchResult>> {
+        if project_id.is_empty() {
+            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+        }
+
+        state.run(move |conn| {
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::search_file_contents(conn, &project_id, &query, MAX_SEARCH_RESULTS)
+        }).await
+    }
+
+    /// Flag one indexed file ignored or not. The choice overrides ignore
+    /// patterns on later indexing runs.
+    #[instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn set_file_ignored(state: &AppState, file_id: String, ignored: bool) -> AppResult<FileMetadata> {
+        state.run(move |conn| {
+            if file_id.is_empty() {
+                return Err(AppError::InvalidInput("File ID cannot be empty".into()));
+            }
+
+            if !DbService::with_busy_retry(|| DbService::set_file_ignored(conn, &file_id, ignored))? {
+                return Err(AppError::NotFound("File", file_id));
+            }
+            DbService::get_file_by_id(conn, &file_id)?
+                .ok_or(AppError::NotFound("File", file_id))
+        }).await
+    }
+
+    /// Get the ignore patterns stored in the project's research.json
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_ignore_patterns(state: &AppState, project_id: String) -> AppResult<Vec<String>> {
+        state.blocking(move |state| {
+            let project_path = Self::project_path(state, &project_id)?;
+            Ok(Self::ignore_patterns(&research_json::read_settings(&project_path)))
+        }).await
+    }
+
+    /// Replace the ignore patterns stored in the project's research.json.
+    /// Blank entries are dropped; returns the stored patterns.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn set_ignore_patterns(state: &AppState, project_id: String, patterns: Vec<String>) -> AppResult<Vec<String>> {
+        state.blocking(move |state| {
+            let patterns: Vec<String> = patterns
+                .iter()
+                .map(|pattern| pattern.trim())
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_string)
+                .collect();
+            if patterns.iter().any(|pattern| pattern.contains('\n')) {
+                return Err(AppError::InvalidInput("Ignore patterns cannot span multiple lines".into()));
+            }
+
+            let project_path = Self::project_path(state, &project_id)?;
+            research_json::write_setting(&project_path, IGNORE_PATTERNS_SETTING, serde_json::json!(patterns))?;
+            Ok(patterns)
+        }).await
+    }
+
+    /// Find files with identical content across all projects. Only indexed
+    /// files of at least `min_size_bytes` (1 when 0) that share their size
+    /// with another file are compared; the stored content hash is used while
+    /// a file keeps its indexed size and time, otherwise the file is hashed
+    /// and the hash stored for next time. Symlinks and extra hard links are
+    /// left out since they take no space of their own. `on_progress` is
+    /// called as files are checked.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn find_duplicate_files<F>(state: &AppState, min_size_bytes: u64, mut on_progress: F) -> AppResult<DuplicateReport>
+    where
+        F: FnMut(&DuplicateScanProgress) + Send + 'static,
+    {
+        state.blocking(move |state| {
+            let min_size = i64::try_from(min_size_bytes.max(1)).unwrap_or(i64::MAX);
+            let candidates = {
+                let conn = &state.conn()?;
+                DbService::get_same_size_files(conn, min_size)?
+            };
+
+            let mut report = DuplicateReport::default();
+            let mut progress = DuplicateScanProgress {
+                total_files: candidates.len(),
+                total_bytes: candidates.iter().filter_map(|(file, _, _)| file.file_size).map(|size| size as u64).sum(),
+                ..DuplicateScanProgress::default()
+            };
+            on_progress(&progress);
+            let mut last_progress = Instant::now();
+
+            let mut by_hash: HashMap<(String, i64), Vec<DuplicateFile>> = HashMap::new();
+            let mut seen_identities = HashSet::new();
+            let mut new_hashes = Vec::new();
+            for (file, project_name, project_path) in candidates {
+                let path = Path::new(&project_path).join(&file.relative_path);
+                progress.checked_files += 1;
+                progress.checked_bytes += file.file_size.unwrap_or(0) as u64;
+                if last_progress.elapsed() >= DUPLICATE_PROGRESS_INTERVAL {
+                    on_progress(&progress);
+                    last_progress = Instant::now();
+                }
+
+                let metadata = match fs::symlink_metadata(&path) {
+                    Ok(metadata) if metadata.is_file() => metadata,
+                    Ok(_) => continue,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => {
+                        report.errors.push(FileIndexError { path: path.to_string_lossy().into_owned(), error: e.to_string() });
+                        continue;
+                    }
+                };
+                if Self::file_identity(&metadata).is_some_and(|identity| !seen_identities.insert(identity)) {
+                    continue;
+                }
+
+                let size = i64::try_from(metadata.len()).unwrap_or(i64::MAX);
+                let modified_at = metadata.modified().map(Self::unix_seconds).ok();
+                let as_indexed = Some(size) == file.file_size && modified_at == Some(file.modified_at);
+                let content_hash = match &file.content_hash {
+                    Some(content_hash) if as_indexed => content_hash.clone(),
+                    _ => match hash::sha256_file(&path) {
+                        Ok(content_hash) => {
+                            report.hashed += 1;
+                            if as_indexed {
+                                new_hashes.push((file.id.clone(), content_hash.clone(), file.modified_at, size));
+                            }
+                            content_hash
+                        }
+                        Err(e) => {
+                            report.errors.push(FileIndexError { path: path.to_string_lossy().into_owned(), error: e.to_string() });
+                            continue;
+                        }
+                    },
+                };
+
+                by_hash.entry((content_hash, size)).or_default().push(DuplicateFile {
+                    file_id: file.id,
+                    project_id: file.project_id,
+                    project_name,
+                    relative_path: file.relative_path,
+                    path: path.to_string_lossy().into_owned(),
+                    modified_at: modified_at.unwrap_or(file.modified_at),
+                });
+            }
+            on_progress(&progress);
+
+            if !new_hashes.is_empty() {
+                let conn = &state.conn()?;
+                DbService::with_busy_retry(|| DbService::store_file_hashes(conn, &new_hashes))?;
+            }
+
+            report.groups = by_hash
+                .into_iter()
+                .filter(|(_, files)| files.len() > 1)
+                .map(|((content_hash, file_size), mut files)| {
+                    files.sort_by(|a, b| (&a.project_name, &a.relative_path).cmp(&(&b.project_name, &b.relative_path)));
+                    let wasted_bytes = file_size.saturating_mul(files.len() as i64 - 1);
+                    DuplicateGroup { content_hash, file_size, files, wasted_bytes }
+                })
+                .collect();
+            report.groups.sort_by(|a, b| b.wasted_bytes.cmp(&a.wasted_bytes).then_with(|| a.content_hash.cmp(&b.content_hash)));
+            report.wasted_bytes = report.groups.iter().map(|group| group.wasted_bytes).sum();
+            Ok(report)
+        }).await
+    }
+
+    /// Replace a duplicate file with a symbolic link to the file kept. Both
+    /// must be regular files with the same content at the time of the call;
+    /// the link is swapped in with a rename so the duplicate is never missing.
+    /// On Windows this needs Developer Mode or administrator rights.
+    #[instrument(level = "debug", skip_all, fields(keep_file_id = %keep_file_id, duplicate_file_id = %duplicate_file_id))]
+    pub async fn replace_with_symlink(state: &AppState, keep_file_id: String, duplicate_file_id: String) -> AppResult<()> {
+        state.blocking(move |state| {
+            if keep_file_id == duplicate_file_id {
+                return Err(AppError::InvalidInput("A file cannot be replaced with a link to itself".into()));
+            }
+            let (keep, duplicate) = {
+                let conn = &state.conn()?;
+                (Self::indexed_file_path(conn, &keep_file_id)?, Self::indexed_file_path(conn, &duplicate_file_id)?)
+            };
+
+            let keep_metadata = fs::symlink_metadata(&keep)?;
+            let duplicate_metadata = fs::symlink_metadata(&duplicate)?;
+            for (path, metadata) in [(&keep, &keep_metadata), (&duplicate, &duplicate_metadata)] {
+                if !metadata.is_file() {
+                    return Err(AppError::Conflict(format!("'{}' is not a regular file", path.display())));
+                }
+            }
+            if keep_metadata.len() != duplicate_metadata.len() || hash::sha256_file(&keep)? != hash::sha256_file(&duplicate)? {
+                return Err(AppError::Conflict(format!(
+                    "'{}' and '{}' no longer have the same content",
+                    keep.display(),
+                    duplicate.display()
+                )));
+            }
+
+            let name = duplicate.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            let temp = duplicate.with_file_name(format!(".{}.{}.link", name, Uuid::new_v4()));
+            Self::symlink_file(&keep, &temp)?;
+            if let Err(e) = fs::rename(&temp, &duplicate) {
+                let _ = fs::remove_file(&temp);
+                return Err(e.into());
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Absolute path of an indexed file that is not flagged deleted
+    fn indexed_file_path(conn: &Connection, file_id: &str) -> AppResult<PathBuf> {
+        let file = DbService::get_file_by_id(conn, file_id)?
+            .filter(|file| !file.is_deleted)
+            .ok_or_else(|| AppError::NotFound("File", file_id.to_string()))?;
+        let project = DbService::get_project_by_id(conn, &file.project_id)?
+            .ok_or_else(|| AppError::NotFound("Project", file.project_id.clone()))?;
+        Ok(Path::new(&project.path).join(&file.relative_path))
+    }
+
+    #[cfg(unix)]
+    fn symlink_file(target: &Path, link: &Path) -> AppResult<()> {
+        Ok(std::os::unix::fs::symlink(target, link)?)
+    }
+
+    #[cfg(windows)]
+    fn symlink_file(target: &Path, link: &Path) -> AppResult<()> {
+        // ERROR_PRIVILEGE_NOT_HELD
+        const PRIVILEGE_NOT_HELD: i32 = 1314;
+        std::os::windows::fs::symlink_file(target, link).map_err(|e| match e.raw_os_error() {
+            Some(PRIVILEGE_NOT_HELD) => AppError::PermissionDenied(
+                "Creating symbolic links needs Developer Mode or administrator rights on Windows".into(),
+            ),
+            _ => e.into(),
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn symlink_file(_target: &Path, _link: &Path) -> AppResult<()> {
+        Err(AppError::System("Symbolic links are not supported on this platform".into()))
+    }
+
+    /// Device and inode of a file, shared by its hard links; None where the
+    /// platform does not expose them
+    #[cfg(unix)]
+    fn file_identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+        use std::os::unix::fs::MetadataExt;
+        Some((metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(not(unix))]
+    fn file_identity(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+        None
+    }
+
+    fn project_path(state: &AppState, project_id: &str) -> AppResult<String> {
+        if project_id.is_empty() {
+            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+        }
+
+        let conn = &state.conn()?;
+        DbService::get_project_by_id(conn, project_id)?
+            .map(|project| project.path)
+            .ok_or_else(|| AppError::NotFound("Project", project_id.to_string()))
+    }
+
+    /// Check a path given relative to the project root and bring it to index
+    /// form, with "/" separators and no empty or "." parts. Absolute paths and
+    /// ".." parts are rejected so the path stays inside the project directory.
+    fn normalize_relative_path(raw: &str) -> AppResult<String> {
+        let raw = raw.trim();
+        let unified = raw.replace('\\', "/");
+        if unified.starts_with('/') || Path::new(raw).is_absolute() || unified.chars().nth(1) == Some(':') {
+            return Err(AppError::InvalidInput(format!("'{}' must be relative to the project directory", raw)));
+        }
+
+        let mut parts = Vec::new();
+        for part in unified.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    return Err(AppError::InvalidInput(format!("'{}' must stay inside the project directory", raw)));
+                }
+                part => parts.push(part),
+            }
+        }
+        Ok(parts.join("/"))
+    }
+
+    /// Mark the files whose modification time on disk differs from the index,
+    /// or that are gone, as stale. With `refresh` the index takes the disk's
+    /// size and time instead and files that are gone are flagged deleted and
+    /// left out.
+    fn compare_with_disk(state: &AppState, project_path: &str, files: Vec<FileMetadata>, refresh: bool) -> AppResult<Vec<FileInfo>> {
+        let root = Path::new(project_path);
+        let mut infos = Vec::with_capacity(files.len());
+        let mut changed = Vec::new();
+        let mut deleted_ids = Vec::new();
+
+        for mut file in files {
+            let on_disk = fs::metadata(root.join(&file.relative_path)).ok().filter(|metadata| metadata.is_file());
+            let disk_modified = on_disk.as_ref().and_then(|metadata| metadata.modified().ok()).map(Self::unix_seconds);
+            let stale = match &on_disk {
+                Some(_) => disk_modified.is_some_and(|modified_at| modified_at != file.modified_at),
+                None => true,
+            };
+            if !(refresh && stale) {
+                infos.push(FileInfo { file, stale });
+                continue;
+            }
+
+            match on_disk {
+                Some(metadata) => {
+                    file.file_size = i64::try_from(metadata.len()).ok();
+                    file.modified_at = disk_modified.unwrap_or(file.modified_at);
+                    file.last_indexed_at = None;
+                    changed.push((file.id.clone(), file.file_size, file.modified_at));
+                    infos.push(FileInfo { file, stale: false });
+                }
+                None => deleted_ids.push(file.id),
+            }
+        }
+
+        if !changed.is_empty() || !deleted_ids.is_empty() {
+            let conn = &state.conn()?;
+            DbService::with_busy_retry(|| DbService::refresh_file_stats(conn, &changed, &deleted_ids))?;
+        }
+        Ok(infos)
+    }
+
+    fn ignore_patterns(settings: &Map<String, Value>) -> Vec<String> {
+        settings
+            .get(IGNORE_PATTERNS_SETTING)
+            .and_then(|value| value.as_array())
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .filter_map(|pattern| pattern.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Collect file rows under `root`, reading each directory's .gitignore as it
+    /// is entered. Per-file failures are recorded in the summary instead of
+    /// aborting the walk.
+    fn scan(
+        root: &Path,
+        project_id: &str,
+        known: &HashMap<String, FileIndexEntry>,
+        settings_rules: &IgnoreRules,
+        max_content_bytes: u64,
+        max_hash_bytes: u64,
+    ) -> ScanResult {
+        let now = chrono::Utc::now().timestamp();
+        let mut summary = FileIndexSummary::default();
+        let mut files = Vec::new();
+        let mut touched = Vec::new();
+        let mut seen = HashSet::new();
+        let mut ignored_dirs = Vec::new();
+        let mut gitignore_rules = IgnoreRules::new();
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let dir_relative = Self::relative_path(root, &dir);
+            if let Ok(contents) = fs::read_to_string(dir.join(".gitignore")) {
+                gitignore_rules.add_gitignore(&dir_relative, &contents);
+            }
+            let is_ignored = |path: &str, is_dir: bool| {
+                settings_rules.is_ignored(path, is_dir) || gitignore_rules.is_ignored(path, is_dir)
+            };
+
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    Self::record_failure(&mut summary, Self::relative_path(root, &dir), e);
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        Self::record_failure(&mut summary, Self::relative_path(root, &dir), e);
+                        continue;
+                    }
+                };
+
+                let path = entry.path();
+                let relative = Self::relative_path(root, &path);
+                let file_type = match entry.file_type() {
+                    Ok(file_type) => file_type,
+                    Err(e) => {
+                        Self::record_failure(&mut summary, relative, e);
+                        continue;
+                    }
+                };
+
+                if file_type.is_dir() {
+                    let name = entry.file_name();
+                    if SKIPPED_DIRS.iter().any(|skipped| name == *skipped) {
+                        continue;
+                    }
+                    if is_ignored(&relative, true) {
+                        summary.skipped += 1;
+                        ignored_dirs.push(relative);
+                    } else {
+                        pending.push(path);
+                    }
+                    continue;
+                }
+
+                // Symlinks are indexed when they point at a file; directory links
+                // are not followed so that cycles cannot occur
+                let metadata = match fs::metadata(&path) {
+                    Ok(metadata) if metadata.is_file() => metadata,
+                    Ok(_) => {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    Err(_) if file_type.is_symlink() => {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        Self::record_failure(&mut summary, relative, e);
+                        continue;
+                    }
+                };
+
+                seen.insert(relative.clone());
+
+                let existing = known.get(&relative);
+                if existing.is_some_and(|entry| entry.ignore_override == Some(true)) {
+                    summary.skipped += 1;
+                    continue;
+                }
+                let ignored = existing.is_none_or(|entry| entry.ignore_override.is_none())
+                    && is_ignored(&relative, false);
+
+                let modified_at = metadata.modified().map(Self::unix_seconds).unwrap_or(now);
+                let file_size = i64::try_from(metadata.len()).ok();
+                let unchanged = existing.is_some_and(|entry| {
+                    entry.last_indexed_at.is_some()
+                        && entry.modified_at == modified_at
+                        && entry.file_size == file_size
+                        && entry.is_ignored == ignored
+                });
+                if unchanged {
+                    summary.unchanged += 1;
+                    continue;
+                }
+
+                let content_hash = if ignored || metadata.len() > max_hash_bytes {
+                    None
+                } else {
+                    match hash::sha256_file(&path) {
+                        Ok(content_hash) => Some(content_hash),
+                        Err(e) => {
+                            Self::record_failure(&mut summary, relative, e);
+                            continue;
+                        }
+                    }
+                };
+
+                // Touched, checked out or copied over with the same content:
+                // keep the stored row and only take the new time
+                if let Some(entry) = existing.filter(|entry| {
+                    entry.last_indexed_at.is_some()
+                        && entry.is_ignored == ignored
+                        && content_hash.is_some()
+                        && entry.content_hash == content_hash
+                }) {
+                    touched.push((entry.id.clone(), modified_at));
+                    summary.unchanged += 1;
+                    continue;
+                }
+
+                // Without a hash on both sides, fall back to size and time
+                let content_changed = existing.is_none_or(|entry| match (&entry.content_hash, &content_hash) {
+                    (Some(stored), Some(current)) => stored != current,
+                    _ => entry.modified_at != modified_at || entry.file_size != file_size,
+                });
+
+                let extension = path
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_lowercase())
+                    .filter(|ext| !ext.is_empty());
+
+                let content = if ignored {
+                    None
+                } else {
+                    match Self::read_text_content(&path, extension.as_deref(), metadata.len(), max_content_bytes) {
+                        Ok(content) => content,
+                        Err(e) => {
+                            Self::record_failure(&mut summary, relative, e);
+                            continue;
+                        }
+                    }
+                };
+
+                let file = FileMetadata {
+                    id: existing.map_or_else(|| Uuid::new_v4().to_string(), |entry| entry.id.clone()),
+                    project_id: project_id.to_string(),
+                    file_name: entry.file_name().to_string_lossy().into_owned(),
+                    mime_type: extension.as_deref().and_then(mime::mime_from_extension).map(str::to_string),
+                    file_extension: extension,
+                    file_size,
+                    created_at: metadata.created().map(Self::unix_seconds).unwrap_or(modified_at),
+                    modified_at,
+                    last_indexed_at: Some(now),
+                    is_deleted: false,
+                    is_ignored: ignored,
+                    content_hash,
+                    content_changed_at: if content_changed {
+                        Some(now)
+                    } else {
+                        existing.and_then(|entry| entry.content_changed_at)
+                    },
+                    relative_path: relative,
+                };
+                files.push(ScannedFile { metadata: file, content });
+                if ignored {
+                    summary.skipped += 1;
+                } else {
+                    summary.indexed += 1;
+                }
+            }
+        }
+
+        ScanResult {
+            files,
+            touched,
+            seen,
+            ignored_dirs,
+            summary,
+        }
+    }
+
+    /// Read a file's content when it is small enough and looks like text: a
+    /// text-like or missing extension, no NUL in the leading bytes and valid UTF-8
+    fn read_text_content(
+        path: &Path,
+        extension: Option<&str>,
+        size: u64,
+        max_bytes: u64,
+    ) -> std::io::Result<Option<String>> {
+        if size > max_bytes || !extension.is_none_or(mime::is_text_extension) {
+            return Ok(None);
+        }
+
+        let bytes = fs::read(path)?;
+        if bytes.iter().take(BINARY_SNIFF_BYTES).any(|byte| *byte == 0) {
+            return Ok(None);
+        }
+        Ok(String::from_utf8(bytes).ok())
+    }
+
+    /// Whether `path` is `dir` or lies below it
+    fn is_within(path: &str, dir: &str) -> bool {
+        path.strip_prefix(dir)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
+    fn record_failure(summary: &mut FileIndexSummary, path: String, error: std::io::Error) {
+        summary.failed += 1;
+        summary.errors.push(FileIndexError {
+            path,
+            error: error.to_string(),
+        });
+    }
+
+    /// Path relative to the project root with "/" separators on every platform
+    fn relative_path(root: &Path, path: &Path) -> String {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn unix_seconds(time: SystemTime) -> i64 {
+        time.duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn duplicates_are_found_across_projects_and_replaced_with_links() {
+        let state = test_support::open_state();
+        let (first, second) = {
+            let conn = &state.conn().unwrap();
+            (test_support::project(conn, "First"), test_support::project(conn, "Second"))
+        };
+        let dataset = "id,value\n1,42\n2,17\n";
+        fs::write(Path::new(&first.path).join("data.csv"), dataset).unwrap();
+        fs::create_dir_all(Path::new(&second.path).join("raw")).unwrap();
+        fs::write(Path::new(&second.path).join("raw/data-copy.csv"), dataset).unwrap();
+        // Same size, other content
+        fs::write(Path::new(&second.path).join("other.csv"), "id,value\n1,42\n2,18\n").unwrap();
+        fs::write(Path::new(&first.path).join("tiny.txt"), "x").unwrap();
+        fs::write(Path::new(&second.path).join("tiny.txt"), "x").unwrap();
+        FileIndexService::index_project_files(&state, first.id.clone()).await.unwrap();
+        FileIndexService::index_project_files(&state, second.id.clone()).await.unwrap();
+        // Hashes are computed lazily when the index has none
+        state.conn().unwrap().execute("UPDATE file_metadata SET content_hash = NULL", []).unwrap();
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&updates);
+        let report = FileIndexService::find_duplicate_files(&state, 2, move |progress| sink.lock().unwrap().push(progress.clone()))
+            .await
+            .unwrap();
+        assert_eq!(report.groups.len(), 1);
+        let group = &report.groups[0];
+        let paths: Vec<&str> = group.files.iter().map(|file| file.relative_path.as_str()).collect();
+        assert_eq!(paths, ["data.csv", "raw/data-copy.csv"]);
+        assert_eq!(group.wasted_bytes, dataset.len() as i64);
+        assert_eq!(report.wasted_bytes, dataset.len() as i64);
+        assert_eq!(report.hashed, 3);
+        let last = updates.lock().unwrap().last().cloned().unwrap();
+        assert_eq!((last.checked_files, last.total_files), (3, 3));
+
+        // The hashes were stored, so the next search reads nothing
+        let again = FileIndexService::find_duplicate_files(&state, 2, |_| {}).await.unwrap();
+        assert_eq!(again.hashed, 0);
+        assert_eq!(again.groups.len(), 1);
+        let with_tiny = FileIndexService::find_duplicate_files(&state, 0, |_| {}).await.unwrap();
+        assert_eq!(with_tiny.groups.len(), 2);
+
+        let keep = group.files[0].file_id.clone();
+        let duplicate = group.files[1].file_id.clone();
+        FileIndexService::replace_with_symlink(&state, keep.clone(), duplicate.clone()).await.unwrap();
+        let link = Path::new(&second.path).join("raw/data-copy.csv");
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&link).unwrap(), dataset);
+        assert!(FileIndexService::find_duplicate_files(&state, 2, |_| {}).await.unwrap().groups.is_empty());
+
+        // A link is not replaced again, and differing files are refused
+        assert!(matches!(
+            FileIndexService::replace_with_symlink(&state, keep, duplicate).await,
+            Err(AppError::Conflict(_))
+        ));
+        let other = {
+            let conn = &state.conn().unwrap();
+            DbService::get_file_by_path(conn, &second.id, "other.csv").unwrap().unwrap().id
+        };
+        assert!(matches!(
+            FileIndexService::replace_with_symlink(&state, group.files[0].file_id.clone(), other).await,
+            Err(AppError::Conflict(_))
+        ));
+    }
+}
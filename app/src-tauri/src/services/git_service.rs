@@ -1,6 +1,28 @@
-use std::path::Path;
-use std::process::Command;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 use crate::error::{AppError, AppResult};
+use crate::models::{DiffHunk, DiffLine, DiffLineKind, FileDiff, GitCommit, GitStatus};
+use crate::utils::research_json;
+
+/// Identity used for commits when the user has not configured one
+const FALLBACK_AUTHOR_NAME: &str = "Research Vault";
+const FALLBACK_AUTHOR_EMAIL: &str = "research-vault@localhost";
+
+/// `git log` placeholders, in the order parse_commit reads them
+const LOG_FIELDS: [&str; 6] = ["%H", "%h", "%an", "%ae", "%at", "%s"];
+
+/// Longest a push or pull may take before git is killed
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How often a network command is checked for completion
+const NETWORK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Message of the stash saved by a pull over uncommitted changes
+const PULL_STASH_MESSAGE: &str = "Research Vault: uncommitted changes before pull";
 
 pub struct GitService;
 
@@ -20,4 +42,590 @@ impl GitService {
 
         Ok(())
     }
+
+    /// Commit timestamps (unix seconds) of the current branch since the given instant
+    pub fn commit_timestamps_since(path: &str, since: i64) -> AppResult<Vec<i64>> {
+        let output = Command::new("git")
+            .args(["log", "--format=%ct", &format!("--since=@{}", since)])
+            .current_dir(Path::new(path))
+            .output()
+            .map_err(|e| AppError::Git(format!("Failed to execute git log: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::Git(format!("Git log failed: {}", stderr)));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().parse().ok())
+            .filter(|timestamp| *timestamp >= since)
+            .collect())
+    }
+
+    /// Stage every change in the working tree
+    pub fn add_all(path: &str) -> AppResult<()> {
+        Self::run(path, &["add", "--all"], "add")?;
+        Ok(())
+    }
+
+    /// Stage one path, relative to the working tree
+    pub fn add(path: &str, relative_path: &str) -> AppResult<()> {
+        Self::run(path, &["add", "--", relative_path], "add")?;
+        Ok(())
+    }
+
+    /// Commit staged changes. Returns false without committing when nothing is staged.
+    pub fn commit(path: &str, message: &str) -> AppResult<bool> {
+        let staged = Command::new("git")
+            .args(["diff", "--cached", "--quiet"])
+            .current_dir(Path::new(path))
+            .status()
+            .map_err(|e| AppError::Git(format!("Failed to execute git diff: {}", e)))?;
+        if staged.success() {
+            return Ok(false);
+        }
+
+        let name = format!("user.name={}", FALLBACK_AUTHOR_NAME);
+        let email = format!("user.email={}", FALLBACK_AUTHOR_EMAIL);
+        let mut args: Vec<&str> = Vec::new();
+        if !Self::has_identity(path) {
+            args.extend(["-c", name.as_str(), "-c", email.as_str()]);
+        }
+        args.extend(["commit", "--quiet", "-m", message]);
+
+        Self::run(path, &args, "commit")?;
+        Ok(true)
+    }
+
+    /// Stage and commit everything when the project enables auto_commit in
+    /// research.json. Git failures are logged and never fail the caller.
+    pub fn auto_commit(path: &str, message: &str) {
+        if !Self::auto_commit_enabled(path) {
+            return;
+        }
+
+        if let Err(e) = Self::add_all(path).and_then(|_| Self::commit(path, message)) {
+            tracing::warn!("Auto-commit in {} failed: {}", path, e);
+        }
+    }
+
+    /// Whether research.json has `settings.auto_commit` set
+    fn auto_commit_enabled(path: &str) -> bool {
+        research_json::read_settings(path)
+            .get("auto_commit")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Whether git has a committer email for this repository
+    fn has_identity(path: &str) -> bool {
+        Command::new("git")
+            .args(["config", "user.email"])
+            .current_dir(Path::new(path))
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    /// Branch, ahead/behind counts and changed paths of a working tree
+    pub fn status(path: &str) -> AppResult<GitStatus> {
+        if !Path::new(path).is_dir() {
+            return Err(AppError::NotFound("Project directory", path.to_string()));
+        }
+
+        // -z keeps paths with spaces, quotes or non-UTF-8 bytes unescaped
+        let output = Self::run(path, &["status", "--porcelain=v2", "--branch", "-z"], "status")?;
+        Ok(Self::parse_status(&output.stdout))
+    }
+
+    /// Commits of the current branch, newest first, skipping the first `skip`
+    pub fn log(path: &str, limit: u32, skip: u32) -> AppResult<Vec<GitCommit>> {
+        if !Path::new(path).is_dir() {
+            return Err(AppError::NotFound("Project directory", path.to_string()));
+        }
+
+        // Fields are split by the unit separator and records by NUL (-z); the
+        // subject comes last so separators inside it cannot shift other fields
+        let format = format!("--format={}", LOG_FIELDS.join("%x1f"));
+        let max_count = format!("--max-count={}", limit);
+        let skip = format!("--skip={}", skip);
+
+        let output = match Self::run(path, &["log", "-z", &format, &max_count, &skip], "log") {
+            Ok(output) => output,
+            // A fresh repository has no HEAD yet
+            Err(AppError::Git(message)) if message.contains("does not have any commits") => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter_map(Self::parse_commit)
+            .collect())
+    }
+
+    /// Diff one file of the repository between `from_rev` and `to_rev`, or against the
+    /// working copy when `to_rev` is None. Renames are detected, so a renamed file
+    /// reports its old path and only its content changes.
+    pub fn diff_file(path: &str, relative_path: &str, from_rev: &str, to_rev: Option<&str>) -> AppResult<FileDiff> {
+        Self::check_inside(path, relative_path)?;
+        Self::check_ref_arg("Revision", from_rev)?;
+        if let Some(to_rev) = to_rev {
+            Self::check_ref_arg("Revision", to_rev)?;
+        }
+
+        let mut revs = vec![from_rev];
+        revs.extend(to_rev);
+
+        // Rename pairs are only found when both paths are diffed, so look the file up first
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff", "-M", "--name-status", "-z"];
+        args.extend(&revs);
+        let output = Self::run(path, &args, "diff")?;
+        let old_path = Self::renamed_from(&output.stdout, relative_path);
+
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff", "-M", "-U3"];
+        args.extend(&revs);
+        args.push("--");
+        args.extend(old_path.as_deref());
+        args.push(relative_path);
+        let output = Self::run(path, &args, "diff")?;
+
+        let (is_binary, hunks) = Self::parse_diff(&String::from_utf8_lossy(&output.stdout));
+        Ok(FileDiff {
+            path: relative_path.to_string(),
+            old_path,
+            is_binary,
+            hunks,
+        })
+    }
+
+    /// Old path of `relative_path` in `git diff --name-status -z` output when it was renamed
+    fn renamed_from(stdout: &[u8], relative_path: &str) -> Option<String> {
+        let stdout = String::from_utf8_lossy(stdout);
+        let mut fields = stdout.split('\0');
+        while let Some(status) = fields.next() {
+            if status.starts_with('R') || status.starts_with('C') {
+                let (old, new) = (fields.next()?, fields.next()?);
+                if status.starts_with('R') && new == relative_path {
+                    return Some(old.to_string());
+                }
+            } else {
+                fields.next();
+            }
+        }
+        None
+    }
+
+    /// Parse `git diff -U3` output of a single file into whether it is binary and its hunks
+    fn parse_diff(stdout: &str) -> (bool, Vec<DiffHunk>) {
+        let mut hunks: Vec<DiffHunk> = Vec::new();
+        let (mut old_line, mut new_line) = (0, 0);
+
+        for line in stdout.lines() {
+            if let Some(header) = line.strip_prefix("@@ ") {
+                if let Some(hunk) = Self::parse_hunk_header(header) {
+                    old_line = hunk.old_start;
+                    new_line = hunk.new_start;
+                    hunks.push(hunk);
+                }
+                continue;
+            }
+
+            let Some(hunk) = hunks.last_mut() else {
+                // File headers before the first hunk
+                if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                    return (true, Vec::new());
+                }
+                continue;
+            };
+
+            let (kind, content) = match line.chars().next() {
+                Some('+') => (DiffLineKind::Added, &line[1..]),
+                Some('-') => (DiffLineKind::Removed, &line[1..]),
+                Some(' ') => (DiffLineKind::Context, &line[1..]),
+                // A context line that was empty may lose its leading space
+                None => (DiffLineKind::Context, ""),
+                // "\ No newline at end of file" and headers of a following file
+                _ => continue,
+            };
+
+            let (old, new) = match kind {
+                DiffLineKind::Added => (None, Some(new_line)),
+                DiffLineKind::Removed => (Some(old_line), None),
+                DiffLineKind::Context => (Some(old_line), Some(new_line)),
+            };
+            if old.is_some() {
+                old_line += 1;
+            }
+            if new.is_some() {
+                new_line += 1;
+            }
+
+            hunk.lines.push(DiffLine {
+                kind,
+                content: content.to_string(),
+                old_line: old,
+                new_line: new,
+            });
+        }
+
+        (false, hunks)
+    }
+
+    /// Parse the part of a hunk header after "@@ ": "-1,3 +1,4 @@ section"
+    fn parse_hunk_header(header: &str) -> Option<DiffHunk> {
+        let (ranges, section) = header.split_once("@@")?;
+        let mut ranges = ranges.split_whitespace();
+        let (old_start, old_lines) = Self::parse_range(ranges.next()?.strip_prefix('-')?)?;
+        let (new_start, new_lines) = Self::parse_range(ranges.next()?.strip_prefix('+')?)?;
+        let section = section.trim();
+
+        Some(DiffHunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            section: (!section.is_empty()).then(|| section.to_string()),
+            lines: Vec::new(),
+        })
+    }
+
+    /// Parse "start,count" or "start", where a missing count means one line
+    fn parse_range(range: &str) -> Option<(u32, u32)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    }
+
+    /// Reject paths that are absolute, climb out with "..", or resolve through a
+    /// symlink to somewhere outside the repository
+    fn check_inside(path: &str, relative_path: &str) -> AppResult<()> {
+        let relative = Path::new(relative_path);
+        let escapes = relative_path.trim().is_empty()
+            || relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(AppError::InvalidInput(format!("'{}' is not a path inside the project", relative_path)));
+        }
+
+        let root = fs::canonicalize(path)?;
+        if let Ok(resolved) = fs::canonicalize(root.join(relative)) {
+            if !resolved.starts_with(&root) {
+                return Err(AppError::PermissionDenied(format!("'{}' is outside the project", relative_path)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Point the remote `name` at `url`, adding it when it does not exist yet
+    pub fn set_remote(path: &str, name: &str, url: &str) -> AppResult<()> {
+        Self::check_ref_arg("Remote name", name)?;
+        Self::check_ref_arg("Remote URL", url)?;
+
+        if Self::has_remote(path, name)? {
+            Self::run(path, &["remote", "set-url", name, url], "remote set-url")?;
+        } else {
+            Self::run(path, &["remote", "add", name, url], "remote add")?;
+        }
+        Ok(())
+    }
+
+    /// Push `branch` to `remote`, setting it as the upstream
+    pub fn push(path: &str, remote: &str, branch: &str) -> AppResult<()> {
+        Self::check_ref_arg("Remote name", remote)?;
+        Self::check_ref_arg("Branch", branch)?;
+        if !Self::has_remote(path, remote)? {
+            return Err(AppError::Git(format!("No remote named '{}' is configured", remote)));
+        }
+
+        Self::run_network(path, &["push", "--set-upstream", remote, branch], "push", remote)?;
+        Ok(())
+    }
+
+    /// Fast-forward the current branch to `branch` of `remote`. A dirty working tree
+    /// is refused unless `stash` is set, in which case the changes are stashed for
+    /// the pull and reapplied afterwards.
+    pub fn pull(path: &str, remote: &str, branch: &str, stash: bool) -> AppResult<()> {
+        Self::check_ref_arg("Remote name", remote)?;
+        Self::check_ref_arg("Branch", branch)?;
+        if !Self::has_remote(path, remote)? {
+            return Err(AppError::Git(format!("No remote named '{}' is configured", remote)));
+        }
+
+        let stashed = if Self::status(path)?.is_clean() {
+            false
+        } else if stash {
+            Self::run(path, &["stash", "push", "--include-untracked", "-m", PULL_STASH_MESSAGE], "stash")?;
+            true
+        } else {
+            return Err(AppError::Conflict(
+                "The working tree has uncommitted changes; commit them or pull with stash".into(),
+            ));
+        };
+
+        let pulled = Self::run_network(path, &["pull", "--ff-only", "--no-edit", remote, branch], "pull", remote);
+
+        if stashed {
+            if let Err(e) = Self::run(path, &["stash", "pop"], "stash pop") {
+                tracing::warn!("Reapplying stashed changes in {} failed: {}", path, e);
+                return Err(AppError::Git(match pulled {
+                    Ok(_) => "Pulled, but the stashed changes conflict with the pulled ones; they are kept in the stash".into(),
+                    Err(_) => "The pull failed and the stashed changes could not be reapplied; they are kept in the stash".into(),
+                }));
+            }
+        }
+
+        pulled.map(|_| ())
+    }
+
+    /// Whether git ignores `relative_path` (relative to the repository root),
+    /// following every .gitignore, .git/info/exclude and the global excludes file
+    pub fn is_ignored(path: &str, relative_path: &str) -> AppResult<bool> {
+        let output = Command::new("git")
+            .args(["check-ignore", "--quiet", "--", relative_path])
+            .current_dir(Path::new(path))
+            .output()
+            .map_err(|e| AppError::Git(format!("Failed to execute git check-ignore: {}", e)))?;
+
+        // Exit code 1 means not ignored; anything else but 0 is an error
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if stderr.contains("not a git repository") {
+                    return Err(AppError::NotAGitRepository(path.to_string()));
+                }
+                Err(AppError::Git(format!("Git check-ignore failed: {}", stderr)))
+            }
+        }
+    }
+
+    /// Whether a remote with this name exists
+    fn has_remote(path: &str, name: &str) -> AppResult<bool> {
+        let output = Self::run(path, &["remote"], "remote")?;
+        Ok(String::from_utf8_lossy(&output.stdout).lines().any(|remote| remote.trim() == name))
+    }
+
+    /// Reject empty values and values git would read as an option
+    fn check_ref_arg(label: &str, value: &str) -> AppResult<()> {
+        if value.trim().is_empty() {
+            return Err(AppError::InvalidInput(format!("{} cannot be empty", label)));
+        }
+        if value.starts_with('-') {
+            return Err(AppError::InvalidInput(format!("{} cannot start with '-'", label)));
+        }
+        Ok(())
+    }
+
+    fn parse_commit(record: &str) -> Option<GitCommit> {
+        let record = record.trim_start_matches('\n');
+        let mut fields = record.splitn(LOG_FIELDS.len(), '\x1f');
+
+        Some(GitCommit {
+            hash: fields.next().filter(|hash| !hash.is_empty())?.to_string(),
+            short_hash: fields.next()?.to_string(),
+            author: fields.next()?.to_string(),
+            email: fields.next()?.to_string(),
+            timestamp: fields.next()?.parse().ok()?,
+            subject: fields.next()?.to_string(),
+        })
+    }
+
+    /// Parse `git status --porcelain=v2 --branch -z` output
+    fn parse_status(stdout: &[u8]) -> GitStatus {
+        let mut status = GitStatus::default();
+        let mut fields = stdout
+            .split(|b| *b == 0)
+            .filter(|field| !field.is_empty())
+            .map(|field| String::from_utf8_lossy(field).into_owned());
+
+        while let Some(entry) = fields.next() {
+            if let Some(header) = entry.strip_prefix("# ") {
+                match header.split_once(' ') {
+                    Some(("branch.head", head)) if head != "(detached)" => status.branch = Some(head.to_string()),
+                    Some(("branch.upstream", upstream)) => status.upstream = Some(upstream.to_string()),
+                    Some(("branch.ab", counts)) => {
+                        for count in counts.split_whitespace() {
+                            if let Some(ahead) = count.strip_prefix('+') {
+                                status.ahead = ahead.parse().unwrap_or(0);
+                            } else if let Some(behind) = count.strip_prefix('-') {
+                                status.behind = behind.parse().unwrap_or(0);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            // Number of space-separated fields before the path, per entry type
+            let (xy, path) = match entry.chars().next() {
+                Some('1') => Self::split_entry(&entry, 8),
+                Some('2') => {
+                    // Renames and copies are followed by the original path
+                    fields.next();
+                    Self::split_entry(&entry, 9)
+                }
+                Some('u') => Self::split_entry(&entry, 10),
+                Some('?') => {
+                    status.untracked.push(entry[2..].to_string());
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let Some((xy, path)) = xy.zip(path) else {
+                continue;
+            };
+            let mut codes = xy.chars();
+            let staged = codes.next().is_some_and(|c| c != '.');
+            let unstaged = codes.next().is_some_and(|c| c != '.');
+
+            if entry.starts_with('u') {
+                status.modified.push(path);
+                continue;
+            }
+            if staged {
+                status.staged.push(path.clone());
+            }
+            if unstaged {
+                status.modified.push(path);
+            }
+        }
+
+        status
+    }
+
+    /// Split a changed-entry line into its XY code and the path that follows
+    /// `skip` space-separated fields
+    fn split_entry(entry: &str, skip: usize) -> (Option<String>, Option<String>) {
+        let mut parts = entry.splitn(skip + 1, ' ');
+        let xy = parts.nth(1).map(str::to_string);
+        let path = parts.nth(skip - 2).map(str::to_string);
+        (xy, path)
+    }
+
+    /// Run a git command that talks to a remote. Credential prompts are disabled so
+    /// a missing login fails instead of waiting for input, the command is killed
+    /// after NETWORK_TIMEOUT, and failures are reported by cause rather than raw stderr.
+    fn run_network(path: &str, args: &[&str], action: &str, remote: &str) -> AppResult<Output> {
+        let mut command = Command::new("git");
+        command
+            .args(args)
+            .current_dir(Path::new(path))
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+            command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+        }
+
+        let mut child = command.spawn().map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound if Path::new(path).is_dir() => AppError::GitNotFound,
+            _ => AppError::Git(format!("Failed to execute git {}: {}", action, e)),
+        })?;
+
+        // Drain both pipes while waiting so a chatty git cannot block on a full pipe
+        let stdout = child.stdout.take().map(Self::drain);
+        let stderr = child.stderr.take().map(Self::drain);
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|e| AppError::Git(format!("Git {} failed: {}", action, e)))? {
+                break status;
+            }
+            if started.elapsed() >= NETWORK_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(AppError::Git(format!(
+                    "Git {} timed out after {} seconds",
+                    action,
+                    NETWORK_TIMEOUT.as_secs()
+                )));
+            }
+            thread::sleep(NETWORK_POLL_INTERVAL);
+        };
+
+        let output = Output {
+            status,
+            stdout: stdout.and_then(|reader| reader.join().ok()).unwrap_or_default(),
+            stderr: stderr.and_then(|reader| reader.join().ok()).unwrap_or_default(),
+        };
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::Git(Self::describe_remote_failure(&stderr, action, remote)));
+        }
+
+        Ok(output)
+    }
+
+    fn drain<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = pipe.read_to_end(&mut buffer);
+            buffer
+        })
+    }
+
+    /// Explain a failed push or pull by its cause: authentication, diverged
+    /// history, a missing remote or an unreachable network
+    fn describe_remote_failure(stderr: &str, action: &str, remote: &str) -> String {
+        let lower = stderr.to_lowercase();
+        let any = |needles: &[&str]| needles.iter().any(|needle| lower.contains(needle));
+
+        if any(&[
+            "authentication failed",
+            "could not read username",
+            "could not read password",
+            "terminal prompts disabled",
+            "permission denied",
+            "invalid username or password",
+            "returned error: 403",
+        ]) {
+            format!("Authentication with '{}' failed; check your credentials or SSH key", remote)
+        } else if any(&["non-fast-forward", "fetch first", "not possible to fast-forward", "divergent branches"]) {
+            if action == "push" {
+                format!("'{}' has commits that are not in this project; pull before pushing", remote)
+            } else {
+                format!("This project and '{}' have diverged; the pull cannot fast-forward", remote)
+            }
+        } else if any(&["does not appear to be a git repository", "no such remote", "repository not found", "couldn't find remote ref"]) {
+            format!("'{}' does not point at an existing repository or branch", remote)
+        } else if any(&[
+            "could not resolve host",
+            "unable to access",
+            "network is unreachable",
+            "connection timed out",
+            "connection refused",
+            "could not read from remote repository",
+        ]) {
+            format!("'{}' could not be reached; check your network connection", remote)
+        } else {
+            let detail = stderr.lines().map(str::trim).rfind(|line| !line.is_empty()).unwrap_or("unknown error");
+            format!("Git {} failed: {}", action, detail)
+        }
+    }
+
+    fn run(path: &str, args: &[&str], action: &str) -> AppResult<Output> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(Path::new(path))
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound if Path::new(path).is_dir() => AppError::GitNotFound,
+                _ => AppError::Git(format!("Failed to execute git {}: {}", action, e)),
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("not a git repository") {
+                return Err(AppError::NotAGitRepository(path.to_string()));
+            }
+            return Err(AppError::Git(format!("Git {} failed: {}", action, stderr)));
+        }
+
+        Ok(output)
+    }
 }
@@ -0,0 +1,275 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{CheckpointResult, DatabaseHealthReport, DbInfo, MigrationStatus, OptimizeResult, OrphanReport, RepairReport, SchemaVersion};
+use crate::services::DbService;
+use crate::state::AppState;
+use crate::utils::logging::{self, LogLevel};
+use rusqlite::Connection;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tracing::instrument;
+
+/// Event sent with the `MigrationStatus` while the database is upgraded at startup
+pub const MIGRATION_PROGRESS_EVENT: &str = "migration-progress";
+
+/// Most log lines returned at once
+const MAX_LOG_LINES: usize = 5_000;
+
+/// Database consistency checks and their remedies
+pub struct HealthService;
+
+impl HealthService {
+    /// Open the database at `db_path` on a background thread, so the window
+    /// shows while a large upgrade runs, sending the migration progress to the
+    /// windows. `on_ready` runs once commands can use the database, including
+    /// when it is damaged so the frontend can offer a backup to restore.
+    pub fn open_database_in_background<R: Runtime>(app: &AppHandle<R>, db_path: String, on_ready: impl FnOnce() + Send + 'static) {
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let state = app.state::<AppState>();
+            let result = state.init_db_with_progress(&db_path, |status| {
+                if let Err(e) = app.emit(MIGRATION_PROGRESS_EVENT, status) {
+                    tracing::warn!("Failed to emit {}: {}", MIGRATION_PROGRESS_EVENT, e);
+                }
+            });
+            match result {
+                Ok(()) | Err(AppError::DatabaseCorrupt { .. }) => on_ready(),
+                Err(e) => tracing::error!("Failed to initialize database: {}", e),
+            }
+        });
+    }
+
+    /// Progress of the schema migrations run when the database was opened
+    pub fn get_migration_status(state: &AppState) -> MigrationStatus {
+        state.migration_status()
+    }
+
+    /// Run at startup: report rows that lost their project
+    pub fn startup_check(conn: &Connection) {
+        match DbService::find_orphans(conn) {
+            Ok(report) if report.total() > 0 => tracing::warn!(
+                "Found {} orphaned rows ({} tasks, {} notes, {} deadlines) whose project no longer exists",
+                report.total(),
+                report.tasks.len(),
+                report.notes.len(),
+                report.deadlines.len()
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to check for orphaned rows: {}", e),
+        }
+    }
+
+    /// List tasks, notes and deadlines whose project no longer exists
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_orphaned_entities(state: &AppState) -> AppResult<OrphanReport> {
+        state.run(move |conn| {
+            DbService::find_orphans(conn)
+        }).await
+    }
+
+    /// Move all orphaned rows into an existing project, returning how many were adopted
+    #[instrument(level = "debug", skip_all, fields(target_project_id = %target_project_id))]
+    pub async fn adopt_orphans(state: &AppState, target_project_id: String) -> AppResult<usize> {
+        state.run(move |conn| {
+            if target_project_id.is_empty() {
+                return Err(AppError::InvalidInput("Target project ID cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &target_project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", target_project_id));
+            }
+            DbService::with_busy_retry(|| DbService::adopt_orphans(conn, &target_project_id))
+        }).await
+    }
+
+    /// Delete all orphaned rows, returning how many were removed
+    #[instrument(level = "debug", skip_all)]
+    pub async fn purge_orphans(state: &AppState) -> AppResult<usize> {
+        state.run(move |conn| {
+            DbService::with_busy_retry(|| DbService::purge_orphans(conn))
+        }).await
+    }
+
+    /// Find and, unless `dry_run`, repair orphaned subtasks, dangling junction
+    /// rows, indexed files of missing projects and stale completion dates
+    #[instrument(level = "debug", skip_all)]
+    pub async fn repair_database(state: &AppState, dry_run: bool) -> AppResult<RepairReport> {
+        state.run(move |conn| {
+            DbService::with_busy_retry(|| DbService::repair_database(conn, dry_run))
+        }).await
+    }
+
+    /// Report database settings, size and page statistics
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_db_info(state: &AppState) -> AppResult<DbInfo> {
+        state.run_with_retry(move |conn| {
+            DbService::get_db_info(conn)
+        }).await
+    }
+
+    /// Report the schema version of the database
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_schema_version(state: &AppState) -> AppResult<SchemaVersion> {
+        state.run_with_retry(move |conn| {
+            DbService::get_schema_version(conn)
+        }).await
+    }
+
+    /// Close every database connection and open new ones to the same file,
+    /// then report on the reopened database
+    #[instrument(level = "debug", skip_all)]
+    pub async fn reconnect_database(state: &AppState) -> AppResult<DbInfo> {
+        state.blocking(|state| {
+            state.reconnect()?;
+            DbService::get_db_info(&*state.conn()?)
+        }).await
+    }
+
+    /// Fold the write-ahead log back into the database file
+    #[instrument(level = "debug", skip_all)]
+    pub async fn checkpoint_database(state: &AppState) -> AppResult<CheckpointResult> {
+        state.run(DbService::checkpoint).await
+    }
+
+    /// Run the full integrity and foreign key checks and count orphaned rows
+    #[instrument(level = "debug", skip_all)]
+    pub async fn check_database_health(state: &AppState) -> AppResult<DatabaseHealthReport> {
+        state
+            .run(|conn| {
+                let mut problems = DbService::integrity_check(conn, false)?;
+                problems.extend(DbService::foreign_key_check(conn)?);
+                let orphans = DbService::find_orphans(conn)?;
+
+                Ok(DatabaseHealthReport {
+                    ok: problems.is_empty() && orphans.total() == 0,
+                    problems,
+                    orphaned_tasks: orphans.tasks.len(),
+                    orphaned_notes: orphans.notes.len(),
+                    orphaned_deadlines: orphans.deadlines.len(),
+                })
+            })
+            .await
+    }
+
+    /// Reclaim free pages with VACUUM and refresh query statistics with ANALYZE
+    #[instrument(level = "debug", skip_all)]
+    pub async fn optimize_database(state: &AppState) -> AppResult<OptimizeResult> {
+        state
+            .run(|conn| {
+                let size_before_bytes = DbService::database_size(conn)?;
+                DbService::with_busy_retry(|| DbService::vacuum_and_analyze(conn))?;
+                Ok(OptimizeResult {
+                    size_before_bytes,
+                    size_after_bytes: DbService::database_size(conn)?,
+                })
+            })
+            .await
+    }
+
+    /// Last lines of the application log, oldest first
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_recent_logs(lines: usize) -> AppResult<Vec<String>> {
+        if lines == 0 || lines > MAX_LOG_LINES {
+            return Err(AppError::InvalidInput(format!("Lines must be between 1 and {}", MAX_LOG_LINES)));
+        }
+        Ok(logging::recent_lines(lines)?)
+    }
+
+    /// Change how verbose the application log is
+    #[instrument(level = "debug", skip_all)]
+    pub async fn set_log_level(level: LogLevel) -> AppResult<LogLevel> {
+        logging::set_level(level);
+        tracing::info!("Log level set to {:?}", level);
+        Ok(logging::level())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    #[test]
+    fn migrations_report_progress_and_hold_off_commands_until_done() {
+        let state = AppState::new();
+        let path = test_support::temp_dir().join("research.db").to_string_lossy().into_owned();
+        let watcher = state.clone();
+        let mut seen = Vec::new();
+        state
+            .init_db_with_progress(&path, |status| {
+                if status.running {
+                    assert!(matches!(watcher.conn(), Err(AppError::Upgrading { .. })));
+                }
+                seen.push(status.clone());
+            })
+            .unwrap();
+
+        let total = DbService::get_schema_version(&state.conn().unwrap()).unwrap().latest as usize;
+        assert_eq!(seen.len(), total + 1);
+        assert_eq!((seen[0].running, seen[0].percent), (true, 0));
+        assert!(seen.windows(2).all(|pair| pair[0].current_step + 1 == pair[1].current_step));
+        let done = MigrationStatus { running: false, current_step: total, total_steps: total, percent: 100, error: None };
+        assert_eq!(seen.last(), Some(&done));
+        assert_eq!(HealthService::get_migration_status(&state), done);
+
+        // An up-to-date database has nothing to upgrade
+        let mut seen = Vec::new();
+        AppState::new().init_db_with_progress(&path, |status| seen.push(status.clone())).unwrap();
+        assert_eq!(seen, vec![MigrationStatus::progress(0, 0)]);
+        assert!(!seen[0].running);
+    }
+
+    #[test]
+    fn a_failed_upgrade_is_kept_in_the_status() {
+        let path = test_support::temp_dir().join("research.db");
+        Connection::open(&path).unwrap().pragma_update(None, "user_version", 10_000).unwrap();
+
+        let state = AppState::new();
+        let result = state.init_db_with_progress(&path.to_string_lossy(), |_| {});
+        assert!(matches!(result, Err(AppError::SchemaTooNew { found: 10_000, .. })));
+        let status = HealthService::get_migration_status(&state);
+        assert!(!status.running);
+        assert!(status.error.unwrap().contains("newer"));
+        assert!(matches!(state.conn(), Err(AppError::System(message)) if message.contains("newer")));
+    }
+
+    #[tokio::test]
+    async fn connections_open_in_wal_mode_and_write_interleaved() {
+        let state = test_support::open_state();
+        let project = test_support::project(&state.conn().unwrap(), "Concurrent");
+
+        let info = HealthService::get_db_info(&state).await.unwrap();
+        assert_eq!(info.journal_mode, "wal");
+        assert_eq!(info.synchronous, "normal");
+        assert_eq!(info.busy_timeout_ms, crate::services::db_service::BUSY_TIMEOUT_MS as i64);
+        assert!(info.foreign_keys);
+        assert!(info.cache_size < 0);
+        assert!(info.page_count > 0 && info.page_size > 0);
+        assert_eq!(Some(info.path), state.db_path());
+
+        // Two connections take turns holding the write lock
+        let writers: Vec<_> = (0..2)
+            .map(|writer| {
+                let state = state.clone();
+                let project_id = project.id.clone();
+                std::thread::spawn(move || {
+                    let conn = state.conn().unwrap();
+                    for i in 0..25 {
+                        let tx = rusqlite::Transaction::new_unchecked(&conn, rusqlite::TransactionBehavior::Immediate).unwrap();
+                        test_support::task(&tx, &project_id, &format!("Writer {} task {}", writer, i));
+                        std::thread::yield_now();
+                        tx.commit().unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().expect("writer hit a lock error");
+        }
+        assert_eq!(DbService::get_tasks_by_project(&state.conn().unwrap(), &project.id).unwrap().len(), 50);
+
+        assert!(HealthService::get_db_info(&state).await.unwrap().wal_size_bytes > 0);
+        let checkpoint = HealthService::checkpoint_database(&state).await.unwrap();
+        assert!(!checkpoint.busy);
+        assert_eq!(checkpoint.checkpointed_frames, checkpoint.log_frames);
+        assert_eq!(HealthService::get_db_info(&state).await.unwrap().wal_size_bytes, 0);
+    }
+}
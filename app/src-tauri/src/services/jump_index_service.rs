@@ -0,0 +1,42 @@
+use crate::error::AppResult;
+use crate::models::JumpIndex;
+use crate::services::DbService;
+use crate::state::AppState;
+use tauri::{AppHandle, Emitter, Runtime};
+use tracing::instrument;
+
+/// Maximum number of entries in the jump index
+pub const JUMP_INDEX_LIMIT: usize = 20_000;
+
+/// Event telling the frontend to refetch its cached jump index
+pub const JUMP_INDEX_INVALIDATED_EVENT: &str = "jump-index-invalidated";
+
+/// Title index for the "jump to anything" command palette
+pub struct JumpIndexService;
+
+impl JumpIndexService {
+    /// Build the index of project, task and note titles
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_jump_index(state: &AppState) -> AppResult<JumpIndex> {
+        state.run(move |conn| {
+            // One extra row per kind tells whether anything was cut off
+            let mut entries = DbService::get_jump_index_entries(conn, JUMP_INDEX_LIMIT + 1)?;
+            entries.sort_by_key(|e| std::cmp::Reverse(e.updated_at));
+
+            let truncated = entries.len() > JUMP_INDEX_LIMIT;
+            entries.truncate(JUMP_INDEX_LIMIT);
+
+            Ok(JumpIndex { entries, truncated })
+        }).await
+    }
+
+    /// Emit the invalidation event after a command that succeeded and may have changed titles
+    pub fn notify_changed<R: Runtime, T>(app: &AppHandle<R>, result: AppResult<T>) -> AppResult<T> {
+        if result.is_ok() {
+            if let Err(e) = app.emit(JUMP_INDEX_INVALIDATED_EVENT, ()) {
+                tracing::warn!("Failed to emit {}: {}", JUMP_INDEX_INVALIDATED_EVENT, e);
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::{AppError, AppResult};
+use crate::models::EntityType;
+use crate::services::DbService;
+use crate::state::AppState;
+use crate::utils::json_patch;
+use serde_json::{Map, Value};
+use tracing::instrument;
+
+/// Maximum size of the serialized metadata object of one entity
+pub const MAX_METADATA_BYTES: usize = 16 * 1024;
+
+/// Free-form metadata stored alongside projects, tasks and notes
+pub struct MetadataService;
+
+impl MetadataService {
+    /// Get the metadata object of an entity (empty when nothing is stored)
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_entity_metadata(state: &AppState, entity_type: EntityType, id: String) -> AppResult<Value> {
+        state.run(move |conn| {
+            let stored = DbService::get_entity_metadata(conn, entity_type, &id)?
+                .ok_or_else(|| AppError::NotFound(entity_type.label(), id))?;
+
+            Ok(Self::parse(stored.as_deref()))
+        }).await
+    }
+
+    /// Update the metadata object of an entity.
+    ///
+    /// With `merge` the patch is applied as an RFC 7396 merge patch (null removes a key);
+    /// without it the patch replaces the stored object. Returns the stored object.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn set_entity_metadata(
+        state: &AppState,
+        entity_type: EntityType,
+        id: String,
+        patch: Value,
+        merge: bool,
+    ) -> AppResult<Value> {
+        state.run(move |conn| {
+            if !patch.is_object() {
+                return Err(AppError::InvalidInput("Metadata must be a JSON object".into()));
+            }
+
+            let stored = DbService::get_entity_metadata(conn, entity_type, &id)?
+                .ok_or_else(|| AppError::NotFound(entity_type.label(), id.clone()))?;
+
+            let mut metadata = if merge {
+                Self::parse(stored.as_deref())
+            } else {
+                Value::Object(Map::new())
+            };
+            json_patch::merge_patch(&mut metadata, &patch);
+
+            let serialized = serde_json::to_string(&metadata)?;
+            if serialized.len() > MAX_METADATA_BYTES {
+                return Err(AppError::InvalidInput(format!(
+                    "Metadata is {} bytes; the limit is {} bytes",
+                    serialized.len(),
+                    MAX_METADATA_BYTES
+                )));
+            }
+
+            DbService::with_busy_retry(|| DbService::set_entity_metadata(conn, entity_type, &id, &serialized))?;
+            Ok(metadata)
+        }).await
+    }
+
+    /// Stored metadata that is missing or not an object reads as an empty object
+    fn parse(stored: Option<&str>) -> Value {
+        stored
+            .and_then(|m| serde_json::from_str::<Value>(m).ok())
+            .filter(Value::is_object)
+            .unwrap_or_else(|| Value::Object(Map::new()))
+    }
+}
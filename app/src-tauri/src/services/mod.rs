@@ -1,13 +1,77 @@
+pub mod activity_service;
+pub mod audit_service;
+pub mod automation_service;
+pub mod backup_service;
+pub mod bootstrap_service;
+pub mod change_event_service;
+pub mod context_service;
 pub mod db_service;
+pub mod deadline_service;
+pub mod digest_service;
+pub mod export_service;
+pub mod file_index_service;
+pub mod jump_index_service;
+pub mod metadata_service;
+pub mod onboarding_service;
 pub mod project_service;
+pub mod project_files_service;
+pub mod project_template_service;
+pub mod reference_service;
+pub mod reminder_service;
+pub mod report_service;
+pub mod research_question_service;
+pub mod search_service;
+pub mod settings_service;
+pub mod startup_scan_service;
+pub mod tag_service;
+pub mod time_tracking_service;
+pub mod trash_service;
+pub mod undo_service;
+pub mod vault_service;
 pub mod task_service;
+pub mod note_attachment_service;
 pub mod note_service;
+pub mod note_template_service;
 pub mod git_service;
+pub mod health_service;
+#[cfg(test)]
+pub mod test_support;
 
+pub use activity_service::*;
+pub use audit_service::*;
+pub use automation_service::*;
+pub use backup_service::*;
+pub use bootstrap_service::*;
+pub use change_event_service::*;
+pub use context_service::*;
 pub use db_service::*;
+pub use deadline_service::*;
+pub use digest_service::*;
+pub use export_service::*;
+pub use file_index_service::*;
+pub use jump_index_service::*;
+pub use metadata_service::*;
+pub use onboarding_service::*;
 pub use project_service::*;
+pub use project_files_service::*;
+pub use project_template_service::*;
+pub use reference_service::*;
+pub use reminder_service::*;
+pub use report_service::*;
+pub use research_question_service::*;
+pub use search_service::*;
+pub use settings_service::*;
+pub use startup_scan_service::*;
+pub use tag_service::*;
+pub use time_tracking_service::*;
+pub use trash_service::*;
+pub use undo_service::*;
+pub use vault_service::*;
 pub use task_service::*;
+pub use note_attachment_service::*;
 pub use note_service::*;
+pub use note_template_service::*;
 pub use git_service::*;
+pub use health_service::*;
 
 
@@ -0,0 +1,577 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{AttachmentStoreStats, NoteAttachment, SETTING_ATTACHMENT_MAX_BYTES};
+use crate::services::{DbService, GitService, NoteService, SettingsService};
+use crate::state::AppState;
+use crate::utils::{hash, path};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Directory of attachment copies, relative to the project directory
+pub(crate) const ATTACHMENTS_DIR: &str = "docs/attachments";
+
+/// Directory of the attachment store, beside the database file. Every distinct
+/// attachment file is kept once, at `<two hash digits>/<sha256>`, and the
+/// project copies are hard links to it, so identical files attached in several
+/// projects take the disk space of one. Linked copies share their content:
+/// attachments are treated as read-only once attached.
+const STORE_DIR: &str = "attachments";
+
+/// Bytes read at a time while hashing a file into the store
+const STORE_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Numbered names tried before giving up on finding a free one
+const MAX_NAME_ATTEMPTS: u32 = 1000;
+
+/// Files attached to notes
+pub struct NoteAttachmentService;
+
+impl NoteAttachmentService {
+    /// Copy a file into the project's docs/attachments directory and attach it to
+    /// a note. The copy keeps the file's name and extension, numbered when taken.
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn attach_file(state: &AppState, note_id: String, source_path: String) -> AppResult<NoteAttachment> {
+        state.blocking(move |state| {
+            let (note, project, max_bytes) = {
+                let conn = &state.conn()?;
+                NoteService::ensure_unlocked(conn, &note_id, false)?;
+                let note = DbService::get_note_by_id(conn, &note_id)?
+                    .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?;
+                let project = NoteService::note_project(conn, &note)?.ok_or_else(|| {
+                    AppError::InvalidInput("Inbox notes cannot have attachments; move the note into a project first".into())
+                })?;
+                (note, project, SettingsService::get_i64(conn, SETTING_ATTACHMENT_MAX_BYTES)?)
+            };
+
+            let source = Path::new(&source_path);
+            let metadata = fs::metadata(source).map_err(|e| match e.kind() {
+                ErrorKind::NotFound => AppError::NotFound("File", source_path.clone()),
+                _ => AppError::FileSystem(e),
+            })?;
+            if !metadata.is_file() {
+                return Err(AppError::InvalidInput(format!("'{}' is not a file", source_path)));
+            }
+            let size = i64::try_from(metadata.len()).unwrap_or(i64::MAX);
+            if size > max_bytes {
+                return Err(AppError::InvalidInput(format!(
+                    "'{}' is {}; attachments are limited to {}",
+                    source_path,
+                    Self::format_size(size),
+                    Self::format_size(max_bytes)
+                )));
+            }
+            let original_name = source
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .ok_or_else(|| AppError::InvalidInput(format!("'{}' has no file name", source_path)))?;
+
+            let (hash, blob) = Self::store_file(&Self::store_dir(state)?, source)?;
+            let dir = Path::new(&project.path).join(ATTACHMENTS_DIR);
+            fs::create_dir_all(&dir)?;
+            let (file_name, is_linked) = Self::link_to_free_name(&blob, &dir, &Self::safe_file_name(&original_name))?;
+
+            let attachment = NoteAttachment {
+                id: Uuid::new_v4().to_string(),
+                note_id: note.id.clone(),
+                relative_path: format!("{}/{}", ATTACHMENTS_DIR, file_name),
+                original_name,
+                size_bytes: size,
+                created_at: chrono::Utc::now().timestamp(),
+            };
+            {
+                let conn = &state.conn()?;
+                let inserted = DbService::with_busy_retry(|| DbService::insert_note_attachment(conn, &attachment))
+                    .and_then(|_| DbService::with_busy_retry(|| DbService::insert_attachment_link(conn, &attachment.id, &hash, size, is_linked)));
+                if let Err(e) = inserted {
+                    let _ = fs::remove_file(dir.join(&file_name));
+                    let _ = DbService::delete_note_attachment(conn, &attachment.id);
+                    Self::collect_unused_blobs(state)?;
+                    return Err(e);
+                }
+            }
+
+            GitService::auto_commit(&project.path, &format!("Attach {} to note: {}", file_name, note.title));
+            Ok(attachment)
+        }).await
+    }
+
+    /// List the attachments of a note, oldest first
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn list_attachments(state: &AppState, note_id: String) -> AppResult<Vec<NoteAttachment>> {
+        state.run(move |conn| {
+            if DbService::get_note_by_id(conn, &note_id)?.is_none() {
+                return Err(AppError::NotFound("Note", note_id));
+            }
+            DbService::get_note_attachments(conn, &note_id)
+        }).await
+    }
+
+    /// Detach a file from its note. With `delete_file` the copy is removed too,
+    /// unless another attachment in the project still uses it.
+    #[instrument(level = "debug", skip_all, fields(attachment_id = %attachment_id))]
+    pub async fn remove_attachment(state: &AppState, attachment_id: String, delete_file: bool) -> AppResult<()> {
+        state.blocking(move |state| {
+            let (attachment, project, title) = {
+                let conn = &state.conn()?;
+                let attachment = DbService::get_note_attachment_by_id(conn, &attachment_id)?
+                    .ok_or_else(|| AppError::NotFound("Attachment", attachment_id.clone()))?;
+                NoteService::ensure_unlocked(conn, &attachment.note_id, false)?;
+                let note = DbService::get_note_by_id(conn, &attachment.note_id)?
+                    .ok_or_else(|| AppError::NotFound("Note", attachment.note_id.clone()))?;
+                if !DbService::with_busy_retry(|| DbService::delete_note_attachment(conn, &attachment_id))? {
+                    return Err(AppError::NotFound("Attachment", attachment_id));
+                }
+                let project = NoteService::note_project(conn, &note)?;
+                (attachment, project, note.title)
+            };
+
+            if let (true, Some(project)) = (delete_file, project) {
+                if Self::delete_unused_files(state, &project.id, &project.path, &[attachment])? > 0 {
+                    GitService::auto_commit(&project.path, &format!("Remove attachment from note: {}", title));
+                }
+            }
+            Self::collect_unused_blobs(state)?;
+            Ok(())
+        }).await
+    }
+
+    /// Counts and sizes of the attachment store, with the disk space it saves
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_attachment_store_stats(state: &AppState) -> AppResult<AttachmentStoreStats> {
+        state.run(DbService::get_attachment_store_stats).await
+    }
+
+    /// Store the attachments that are not in the store yet, in the background
+    pub fn start_store_pass<R: Runtime>(app: &AppHandle<R>) {
+        let state = app.state::<AppState>().inner().clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = Self::store_existing_attachments(&state).await {
+                tracing::warn!("Failed to move attachments into the attachment store: {}", e);
+            }
+        });
+    }
+
+    /// Bring the attachments added before the store, or copied to another
+    /// project since, into the store: each file is hashed and replaced by a link
+    /// to its stored copy, under the same relative path. Files that are missing
+    /// are skipped; files that cannot be linked stay copies. Returns how many
+    /// attachments were stored.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn store_existing_attachments(state: &AppState) -> AppResult<usize> {
+        state.blocking(|state| {
+            let store = Self::store_dir(state)?;
+            let attachments = DbService::get_unstored_attachments(&*state.conn()?)?;
+            let mut stored = 0;
+            for (attachment, project_path) in attachments {
+                if !Self::is_own_copy(&attachment.relative_path) {
+                    continue;
+                }
+                let file = Path::new(&project_path).join(&attachment.relative_path);
+                if !file.is_file() {
+                    continue;
+                }
+                match Self::adopt_file(&store, &file) {
+                    Ok((hash, size, is_linked)) => {
+                        let conn = &state.conn()?;
+                        DbService::with_busy_retry(|| DbService::insert_attachment_link(conn, &attachment.id, &hash, size, is_linked))?;
+                        stored += 1;
+                    }
+                    Err(e) => tracing::warn!("Could not store attachment {}: {}", file.display(), e),
+                }
+            }
+            Self::collect_unused_blobs(state)?;
+            if stored > 0 {
+                tracing::info!("Moved {} attachments into the attachment store", stored);
+            }
+            Ok(stored)
+        }).await
+    }
+
+    /// Delete the stored files no attachment points at any more. Project copies
+    /// are hard links, so they keep their content.
+    fn collect_unused_blobs(state: &AppState) -> AppResult<()> {
+        let hashes = DbService::with_busy_retry(|| DbService::take_unused_blobs(&*state.conn()?))?;
+        let store = Self::store_dir(state)?;
+        for hash in hashes {
+            match fs::remove_file(Self::blob_path(&store, &hash)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => tracing::warn!("Could not delete stored attachment {}: {}", hash, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// The attachment store of the open vault
+    fn store_dir(state: &AppState) -> AppResult<PathBuf> {
+        let db_path = state.db_path().ok_or_else(|| AppError::System("Database not initialized".into()))?;
+        let vault = Path::new(&db_path).parent().unwrap_or(Path::new("."));
+        Ok(vault.join(STORE_DIR))
+    }
+
+    fn blob_path(store: &Path, hash: &str) -> PathBuf {
+        store.join(&hash[..2]).join(hash)
+    }
+
+    /// Copy `source` into the store, hashing it chunk by chunk on the way, unless
+    /// the same content is stored already. Returns the hash and the stored file.
+    fn store_file(store: &Path, source: &Path) -> AppResult<(String, PathBuf)> {
+        fs::create_dir_all(store)?;
+        let incoming = store.join(format!(".incoming-{}", Uuid::new_v4()));
+        let copied = fs::File::open(source).and_then(|mut input| {
+            let mut output = fs::File::create(&incoming)?;
+            let mut hasher = Sha256::new();
+            let mut buffer = vec![0u8; STORE_CHUNK_BYTES];
+            loop {
+                let read = input.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+                output.write_all(&buffer[..read])?;
+            }
+            output.sync_all()?;
+            Ok(hash::hex(&hasher.finalize()))
+        });
+        let hash = match copied {
+            Ok(hash) => hash,
+            Err(e) => {
+                let _ = fs::remove_file(&incoming);
+                return Err(e.into());
+            }
+        };
+
+        let blob = Self::blob_path(store, &hash);
+        let placed = if blob.is_file() {
+            fs::remove_file(&incoming)
+        } else {
+            fs::create_dir_all(store.join(&hash[..2])).and_then(|_| fs::rename(&incoming, &blob))
+        };
+        if let Err(e) = placed {
+            let _ = fs::remove_file(&incoming);
+            return Err(e.into());
+        }
+        Ok((hash, blob))
+    }
+
+    /// Store an attachment file that is already in a project and put a link to
+    /// the stored copy in its place. Returns the hash, the size and whether the
+    /// file is now a link.
+    fn adopt_file(store: &Path, file: &Path) -> AppResult<(String, i64, bool)> {
+        let size = i64::try_from(fs::metadata(file)?.len()).unwrap_or(i64::MAX);
+        let (hash, blob) = Self::store_file(store, file)?;
+        // Swapped in by rename, so the attachment is never missing
+        let replacement = file.with_file_name(format!(".{}.link", Uuid::new_v4()));
+        let is_linked = match fs::hard_link(&blob, &replacement).and_then(|_| fs::rename(&replacement, file)) {
+            Ok(()) => true,
+            Err(_) => {
+                let _ = fs::remove_file(&replacement);
+                false
+            }
+        };
+        Ok((hash, size, is_linked))
+    }
+
+    /// Absolute path of an attachment's file, for the frontend to open
+    #[instrument(level = "debug", skip_all, fields(attachment_id = %attachment_id))]
+    pub async fn open_attachment(state: &AppState, attachment_id: String) -> AppResult<String> {
+        state.run(move |conn| {
+            let attachment = DbService::get_note_attachment_by_id(conn, &attachment_id)?
+                .ok_or(AppError::NotFound("Attachment", attachment_id))?;
+            let note = DbService::get_note_by_id(conn, &attachment.note_id)?
+                .ok_or_else(|| AppError::NotFound("Note", attachment.note_id.clone()))?;
+            let project = NoteService::note_project(conn, &note)?.ok_or_else(|| {
+                AppError::InvalidInput("The note is in the inbox; its attachments stay with the project they were added in".into())
+            })?;
+
+            let path = Path::new(&project.path).join(&attachment.relative_path);
+            if !path.is_file() {
+                return Err(AppError::NotFound("Attachment file", path.to_string_lossy().into_owned()));
+            }
+            Ok(path.to_string_lossy().into_owned())
+        }).await
+    }
+
+    /// Remove the files of `attachments` from a project's directory, skipping any
+    /// that another attachment in the project still uses. The rows must already
+    /// be gone. Returns how many files were removed.
+    pub(crate) fn delete_unused_files(
+        state: &AppState,
+        project_id: &str,
+        project_path: &str,
+        attachments: &[NoteAttachment],
+    ) -> AppResult<usize> {
+        let unused: Vec<&NoteAttachment> = {
+            let conn = &state.conn()?;
+            let mut unused = Vec::new();
+            for attachment in attachments {
+                if !DbService::is_attachment_path_used(conn, project_id, &attachment.relative_path)? {
+                    unused.push(attachment);
+                }
+            }
+            unused
+        };
+
+        let mut removed = 0;
+        for attachment in unused {
+            // Only copies this service made are ever deleted
+            if !Self::is_own_copy(&attachment.relative_path) {
+                continue;
+            }
+            match fs::remove_file(Path::new(project_path).join(&attachment.relative_path)) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => tracing::warn!("Could not delete attachment {}: {}", attachment.relative_path, e),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Copy the files of `attachments` from one project directory into another's
+    /// attachment directory, for notes about to move there. Returns the new
+    /// relative path of every copied file keyed by its old one; files that are
+    /// missing or were not copied in by this service stay where they are. On
+    /// failure the copies made so far are removed.
+    pub(crate) fn copy_files_to_project(
+        source_path: &str,
+        target_path: &str,
+        attachments: &[NoteAttachment],
+    ) -> AppResult<HashMap<String, String>> {
+        let target_dir = Path::new(target_path).join(ATTACHMENTS_DIR);
+        let mut moves = HashMap::new();
+        for attachment in attachments {
+            let relative_path = &attachment.relative_path;
+            if moves.contains_key(relative_path) || !Self::is_own_copy(relative_path) {
+                continue;
+            }
+            let source = Path::new(source_path).join(relative_path);
+            if !source.is_file() {
+                continue;
+            }
+            let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+            let copied = fs::create_dir_all(&target_dir)
+                .map_err(AppError::from)
+                .and_then(|_| Self::copy_to_free_name(&source, &target_dir, name));
+            match copied {
+                Ok(file_name) => {
+                    moves.insert(relative_path.clone(), format!("{}/{}", ATTACHMENTS_DIR, file_name));
+                }
+                Err(e) => {
+                    Self::remove_copies(target_path, &moves);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(moves)
+    }
+
+    /// Remove the files copied by `copy_files_to_project` after the move failed
+    pub(crate) fn remove_copies(target_path: &str, moves: &HashMap<String, String>) {
+        for new_path in moves.values() {
+            if let Err(e) = fs::remove_file(Path::new(target_path).join(new_path)) {
+                tracing::warn!("Could not remove attachment copy {}: {}", new_path, e);
+            }
+        }
+    }
+
+    /// Whether a relative path names a file this service copied into a project
+    fn is_own_copy(relative_path: &str) -> bool {
+        relative_path.starts_with(ATTACHMENTS_DIR) && !relative_path.contains("..")
+    }
+
+    /// Hard-link the stored file `blob` into `dir` under `name`, or "stem (2).ext"
+    /// and so on when that is taken. Where links are not possible (the project
+    /// is on another drive) the file is copied instead. Returns the name and
+    /// whether it is a link.
+    fn link_to_free_name(blob: &Path, dir: &Path, name: &str) -> AppResult<(String, bool)> {
+        for attempt in 1..=MAX_NAME_ATTEMPTS {
+            let candidate = Self::numbered_name(name, attempt);
+            match fs::hard_link(blob, dir.join(&candidate)) {
+                Ok(()) => return Ok((candidate, true)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(_) => break,
+            }
+        }
+        Ok((Self::copy_to_free_name(blob, dir, name)?, false))
+    }
+
+    /// `name` for the first attempt, then "stem (2).ext" and so on
+    fn numbered_name(name: &str, attempt: u32) -> String {
+        if attempt == 1 {
+            return name.to_string();
+        }
+        match name.rfind('.') {
+            Some(dot) if dot > 0 => format!("{} ({}){}", &name[..dot], attempt, &name[dot..]),
+            _ => format!("{} ({})", name, attempt),
+        }
+    }
+
+    /// Copy `source` into `dir` under `name`, or "stem (2).ext" and so on when
+    /// that is taken
+    fn copy_to_free_name(source: &Path, dir: &Path, name: &str) -> AppResult<String> {
+        Self::write_to_free_name(dir, name, |file| {
+            fs::File::open(source).and_then(|mut input| io::copy(&mut input, file)).map(|_| ())
+        })
+    }
+
+    /// Create a file in `dir` named `name`, or "stem (2).ext" and so on when
+    /// that is taken, and fill it with `write`. Files are created exclusively,
+    /// so a name is never reused; one that could not be filled is removed.
+    pub(crate) fn write_to_free_name(
+        dir: &Path,
+        name: &str,
+        write: impl FnOnce(&mut fs::File) -> io::Result<()>,
+    ) -> AppResult<String> {
+        for attempt in 1..=MAX_NAME_ATTEMPTS {
+            let candidate = Self::numbered_name(name, attempt);
+            let target = dir.join(&candidate);
+            let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(&target) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if let Err(e) = write(&mut file) {
+                drop(file);
+                let _ = fs::remove_file(&target);
+                return Err(e.into());
+            }
+            return Ok(candidate);
+        }
+
+        Err(AppError::Conflict(format!("No free name for '{}' in {}", name, ATTACHMENTS_DIR)))
+    }
+
+    /// A file name that is safe on every platform: separators, reserved
+    /// characters and control characters become underscores
+    pub(crate) fn safe_file_name(name: &str) -> String {
+        let cleaned: String = name
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+        let cleaned = cleaned.trim_matches(|c: char| c == ' ' || c == '.');
+        if cleaned.is_empty() {
+            "attachment".to_string()
+        } else if path::is_reserved_windows_name(cleaned) {
+            format!("_{}", cleaned)
+        } else {
+            cleaned.to_string()
+        }
+    }
+
+    fn format_size(bytes: i64) -> String {
+        const MB: f64 = 1024.0 * 1024.0;
+        if bytes >= 1024 * 1024 {
+            format!("{:.1} MB", bytes as f64 / MB)
+        } else {
+            format!("{} bytes", bytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    fn source_file(content: &str) -> String {
+        let dir = test_support::temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("paper.pdf");
+        fs::write(&file, content).unwrap();
+        file.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn identical_files_are_stored_once_and_removed_with_the_last_reference() {
+        let state = test_support::open_state();
+        let (first, second) = {
+            let conn = &state.conn().unwrap();
+            let (one, two) = (test_support::project(conn, "One"), test_support::project(conn, "Two"));
+            (test_support::note(conn, &one.id, "First", ""), test_support::note(conn, &two.id, "Second", ""))
+        };
+        let source = source_file("same pdf bytes");
+
+        let a = NoteAttachmentService::attach_file(&state, first.id.clone(), source.clone()).await.unwrap();
+        let b = NoteAttachmentService::attach_file(&state, second.id.clone(), source).await.unwrap();
+
+        // The project files keep their relative paths
+        for (attachment, note) in [(&a, &first), (&b, &second)] {
+            let project = DbService::get_project_by_id(&state.conn().unwrap(), note.project_id.as_deref().unwrap()).unwrap().unwrap();
+            let file = Path::new(&project.path).join(&attachment.relative_path);
+            assert_eq!(fs::read_to_string(file).unwrap(), "same pdf bytes");
+        }
+        let stats = NoteAttachmentService::get_attachment_store_stats(&state).await.unwrap();
+        assert_eq!(
+            stats,
+            AttachmentStoreStats { blobs: 1, references: 2, blob_bytes: 14, logical_bytes: 28, bytes_saved: 14, unstored: 0 }
+        );
+
+        let store = NoteAttachmentService::store_dir(&state).unwrap();
+        let hash = hash::sha256_file(Path::new(&NoteAttachmentService::open_attachment(&state, a.id.clone()).await.unwrap())).unwrap();
+        let blob = NoteAttachmentService::blob_path(&store, &hash);
+        assert!(blob.is_file());
+
+        NoteAttachmentService::remove_attachment(&state, a.id, true).await.unwrap();
+        assert!(blob.is_file(), "the other note still uses it");
+        NoteAttachmentService::remove_attachment(&state, b.id.clone(), false).await.unwrap();
+        assert!(!blob.exists());
+        assert_eq!(NoteAttachmentService::get_attachment_store_stats(&state).await.unwrap(), AttachmentStoreStats::default());
+
+        // Kept without delete_file, with its content
+        let project = DbService::get_project_by_id(&state.conn().unwrap(), second.project_id.as_deref().unwrap()).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(Path::new(&project.path).join(&b.relative_path)).unwrap(), "same pdf bytes");
+    }
+
+    #[tokio::test]
+    async fn attachments_from_before_the_store_are_moved_into_it() {
+        let state = test_support::open_state();
+        let (project, note) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Old");
+            let note = test_support::note(conn, &project.id, "Old note", "");
+            (project, note)
+        };
+        let dir = Path::new(&project.path).join(ATTACHMENTS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["a.txt", "b.txt"] {
+            fs::write(dir.join(name), "old bytes").unwrap();
+            let attachment = NoteAttachment {
+                id: Uuid::new_v4().to_string(),
+                note_id: note.id.clone(),
+                relative_path: format!("{}/{}", ATTACHMENTS_DIR, name),
+                original_name: name.to_string(),
+                size_bytes: 9,
+                created_at: 0,
+            };
+            DbService::insert_note_attachment(&state.conn().unwrap(), &attachment).unwrap();
+        }
+        assert_eq!(NoteAttachmentService::get_attachment_store_stats(&state).await.unwrap().unstored, 2);
+
+        assert_eq!(NoteAttachmentService::store_existing_attachments(&state).await.unwrap(), 2);
+        let stats = NoteAttachmentService::get_attachment_store_stats(&state).await.unwrap();
+        assert_eq!((stats.blobs, stats.references, stats.bytes_saved, stats.unstored), (1, 2, 9, 0));
+        for name in ["a.txt", "b.txt"] {
+            assert_eq!(fs::read_to_string(dir.join(name)).unwrap(), "old bytes");
+        }
+
+        // Nothing left to do the next time
+        assert_eq!(NoteAttachmentService::store_existing_attachments(&state).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn numbered_names_keep_the_extension() {
+        assert_eq!(NoteAttachmentService::numbered_name("paper.pdf", 1), "paper.pdf");
+        assert_eq!(NoteAttachmentService::numbered_name("paper.pdf", 3), "paper (3).pdf");
+        assert_eq!(NoteAttachmentService::numbered_name(".env", 2), ".env (2)");
+        assert_eq!(NoteAttachmentService::numbered_name("README", 2), "README (2)");
+    }
+}
@@ -1,154 +1,1352 @@
 use crate::error::{AppError, AppResult};
-use crate::models::{CreateNoteDto, Note, UpdateNoteDto};
+use crate::models::{
+    BulkCreateResult, BulkItemError, Changed, CreateNoteDto, EntityType, ListOptions, MoveResult, Note, NoteAttachment, NoteLink, NoteStats, NoteSummary, NoteViewState, Paginated, Project, RevertChangeDto, SaveNoteViewStateDto, SearchDateRange, SearchHit, TagMatchMode, TitleCollation, UpdateNoteDto,
+    WordDelta, WritingProgress, WritingStats, SETTING_WRITING_GOAL_TAG,
+};
+use crate::services::{DbService, GitService, NoteAttachmentService, SearchService, SettingsService, UndoService};
+use crate::state::AppState;
+use crate::utils::validate::Validate;
+use crate::utils::{markdown, timezone, word_count};
+use chrono::{Local, Offset};
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+use tracing::instrument;
 use uuid::Uuid;
 
+/// Most notes one create_notes_bulk call takes
+const MAX_BULK_NOTES: usize = 10_000;
+
+/// Largest word-count goal of a project
+const MAX_WRITING_GOAL: i64 = 100_000_000;
+
+/// Days of word deltas reported with a project's writing progress
+const WRITING_PROGRESS_DAYS: i64 = 14;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
 /// Note service for business logic
 pub struct NoteService;
 
 impl NoteService {
     /// Create a new note
-    pub async fn create_note(data: CreateNoteDto) -> AppResult<Note> {
-        // Validate input
-        if data.title.is_empty() {
-            return Err(AppError::InvalidInput("Note title cannot be empty".into()));
-        }
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_note(state: &AppState, mut data: CreateNoteDto) -> AppResult<Note> {
+        state.blocking(move |state| {
+            // Validate input
+            if data.project_id.trim().is_empty() {
+                return Err(AppError::InvalidInput(
+                    "Project ID cannot be empty; use create_inbox_note for a note without a project".into(),
+                ));
+            }
+            data.validate(&SettingsService::limits(&*state.conn()?)?)?;
 
-        if data.project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+            let project_id = data.project_id;
+            let now = chrono::Utc::now().timestamp();
+            let note = Note {
+                id: Uuid::new_v4().to_string(),
+                project_id: Some(project_id.clone()),
+                title: data.title,
+                content: data.content,
+                created_at: now,
+                updated_at: now,
+                tags: data.tags,
+                is_pinned: data.is_pinned.unwrap_or(false),
+                is_locked: false,
+                metadata: None,
+            };
 
-        let now = chrono::Utc::now().timestamp();
-        let note = Note {
-            id: Uuid::new_v4().to_string(),
-            project_id: data.project_id,
-            title: data.title,
-            content: data.content,
-            created_at: now,
-            updated_at: now,
-            tags: data.tags,
-            is_pinned: data.is_pinned.unwrap_or(false),
-        };
+            let project_path = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                DbService::with_busy_retry(|| DbService::insert_note(conn, &note))?;
+                project.path
+            };
+
+            GitService::auto_commit(&project_path, &format!("Create note: {}", note.title));
+            Ok(note)
+        }).await
+    }
+
+    /// Create many notes in a project at once, for importers. Every note is
+    /// validated first and the valid ones are inserted in one transaction; the
+    /// invalid ones are reported by position. With `atomic`, a single invalid
+    /// note means nothing is created.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn create_notes_bulk(
+        state: &AppState,
+        project_id: String,
+        items: Vec<CreateNoteDto>,
+        atomic: bool,
+    ) -> AppResult<BulkCreateResult> {
+        state.blocking(move |state| {
+            if project_id.trim().is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            if items.len() > MAX_BULK_NOTES {
+                return Err(AppError::InvalidInput(format!(
+                    "At most {} notes can be created at once, got {}",
+                    MAX_BULK_NOTES,
+                    items.len()
+                )));
+            }
+
+            let (project_path, limits) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                (project.path, SettingsService::limits(conn)?)
+            };
+
+            let now = chrono::Utc::now().timestamp();
+            let mut result = BulkCreateResult::default();
+            let mut notes = Vec::with_capacity(items.len());
+            for (index, mut item) in items.into_iter().enumerate() {
+                let reason = if let Err(e) = item.validate(&limits) {
+                    Some(match e {
+                        AppError::InvalidInput(reason) => reason,
+                        e => e.to_string(),
+                    })
+                } else if !item.project_id.is_empty() && item.project_id != project_id {
+                    Some(format!("Note belongs to project '{}', not '{}'", item.project_id, project_id))
+                } else {
+                    None
+                };
+                if let Some(reason) = reason {
+                    result.errors.push(BulkItemError { index, reason });
+                    continue;
+                }
 
-        // TODO: Save to database via IPC to frontend repository
+                notes.push(Note {
+                    id: Uuid::new_v4().to_string(),
+                    project_id: Some(project_id.clone()),
+                    title: item.title,
+                    content: item.content,
+                    created_at: now,
+                    updated_at: now,
+                    tags: item.tags,
+                    is_pinned: item.is_pinned.unwrap_or(false),
+                    is_locked: false,
+                    metadata: None,
+                });
+            }
+            if notes.is_empty() || (atomic && !result.errors.is_empty()) {
+                return Ok(result);
+            }
 
-        Ok(note)
+            {
+                let conn = &state.conn()?;
+                DbService::with_busy_retry(|| DbService::insert_notes(conn, &notes))?;
+            }
+
+            GitService::auto_commit(&project_path, &format!("Create {} notes", notes.len()));
+            result.created_ids = notes.into_iter().map(|note| note.id).collect();
+            Ok(result)
+        }).await
     }
 
-    /// Get all notes for a project
-    pub async fn list_notes(project_id: String) -> AppResult<Vec<Note>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+    /// Capture a note in the inbox, without a project, to be filed later
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_inbox_note(
+        state: &AppState,
+        title: String,
+        content: String,
+        tags: Option<Vec<String>>,
+    ) -> AppResult<Note> {
+        state.run(move |conn| {
+            let mut data = CreateNoteDto { project_id: String::new(), title, content, tags, is_pinned: None };
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            let now = chrono::Utc::now().timestamp();
+            let note = Note {
+                id: Uuid::new_v4().to_string(),
+                project_id: None,
+                title: data.title,
+                content: data.content,
+                created_at: now,
+                updated_at: now,
+                tags: data.tags,
+                is_pinned: false,
+                is_locked: false,
+                metadata: None,
+            };
 
-        // TODO: Fetch from database
-        Ok(vec![])
+            DbService::with_busy_retry(|| DbService::insert_note(conn, &note))?;
+            Ok(note)
+        }).await
     }
 
-    /// Get pinned notes for a project
-    pub async fn list_pinned_notes(project_id: String) -> AppResult<Vec<Note>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+    /// List inbox notes, most recently updated first
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_inbox_notes(state: &AppState) -> AppResult<Vec<Note>> {
+        state.run(DbService::get_inbox_notes).await
+    }
+
+    /// File a note, usually from the inbox, into a project. Its attachment
+    /// files move along when it comes from another project.
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id, project_id = %project_id))]
+    pub async fn move_note_to_project(state: &AppState, note_id: String, project_id: String) -> AppResult<Note> {
+        state.blocking(move |state| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
 
-        // TODO: Fetch from database
-        Ok(vec![])
+            let project = {
+                let conn = &state.conn()?;
+                Self::ensure_unlocked(conn, &note_id, false)?;
+                DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?
+            };
+
+            let result = Self::move_notes_with_attachments(state, std::slice::from_ref(&note_id), &project)?;
+            let note = {
+                let conn = &state.conn()?;
+                if let Some(skipped) = result.skipped.into_iter().next() {
+                    return Err(match DbService::get_note_by_id(conn, &note_id)? {
+                        None => AppError::NotFound("Note", note_id),
+                        Some(_) => AppError::Conflict(skipped.reason),
+                    });
+                }
+                DbService::get_note_by_id(conn, &note_id)?
+                    .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?
+            };
+
+            GitService::auto_commit(&project.path, &format!("File note: {}", note.title));
+            Ok(note)
+        }).await
     }
 
-    /// Get recent notes
-    pub async fn list_recent_notes(project_id: String, limit: i32) -> AppResult<Vec<Note>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+    /// Get a page of a project's notes
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_notes(state: &AppState, project_id: String, options: ListOptions) -> AppResult<Paginated<Note>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
 
-        if limit <= 0 {
-            return Err(AppError::InvalidInput("Limit must be greater than 0".into()));
-        }
+            DbService::get_notes_page(conn, &project_id, &options)
+        }).await
+    }
+
+    /// Get notes of a project sorted by title
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_notes_by_title(
+        state: &AppState,
+        project_id: String,
+        collation: Option<TitleCollation>,
+    ) -> AppResult<Vec<Note>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
 
-        // TODO: Fetch from database with limit
-        Ok(vec![])
+            DbService::get_notes_by_title(conn, &project_id, collation.unwrap_or_default())
+        }).await
+    }
+
+    /// Get pinned notes of a project, most recently updated first or in their manual order
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_pinned_notes(state: &AppState, project_id: String, manual_order: bool) -> AppResult<Vec<Note>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            DbService::get_pinned_notes(conn, &project_id, manual_order)
+        }).await
+    }
+
+    /// Move a pinned note to a position among its project's pinned notes,
+    /// returning the pinned notes in their new order
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn reorder_pinned_note(state: &AppState, id: String, position: usize) -> AppResult<Vec<Note>> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            match DbService::with_busy_retry(|| DbService::reorder_pinned_note(conn, &id, position))? {
+                None => Err(AppError::NotFound("Note", id)),
+                Some(false) => Err(AppError::Conflict("Only pinned notes can be reordered".into())),
+                Some(true) => {
+                    let note = DbService::get_note_by_id(conn, &id)?
+                        .ok_or_else(|| AppError::NotFound("Note", id.clone()))?;
+                    match note.project_id {
+                        Some(project_id) => DbService::get_pinned_notes(conn, &project_id, true),
+                        None => Ok(Vec::new()),
+                    }
+                }
+            }
+        }).await
+    }
+
+    /// Get a project's most recently updated notes, at most `limit`
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_recent_notes(state: &AppState, project_id: String, limit: i32) -> AppResult<Vec<Note>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if limit <= 0 {
+                return Err(AppError::InvalidInput("Limit must be greater than 0".into()));
+            }
+
+            DbService::get_recent_notes(conn, &project_id, i64::from(limit))
+        }).await
+    }
+
+    /// Get a project's most often opened notes, at most `limit`
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_frequent_notes(state: &AppState, project_id: String, limit: i32) -> AppResult<Vec<Note>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if limit <= 0 {
+                return Err(AppError::InvalidInput("Limit must be greater than 0".into()));
+            }
+
+            DbService::get_frequent_notes(conn, &project_id, i64::from(limit))
+        }).await
+    }
+
+    /// Get where a note was left in the editor; None when it was never saved
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn get_note_view_state(state: &AppState, note_id: String) -> AppResult<Option<NoteViewState>> {
+        state.run(move |conn| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            match DbService::get_note_view_state(conn, &note_id)? {
+                Some(view_state) => Ok(Some(view_state)),
+                None if DbService::get_note_by_id(conn, &note_id)?.is_some() => Ok(None),
+                None => Err(AppError::NotFound("Note", note_id)),
+            }
+        }).await
+    }
+
+    /// Save where a note was left in the editor. The note itself is not
+    /// modified, so this never moves it up in the recently edited notes.
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn save_note_view_state(
+        state: &AppState,
+        note_id: String,
+        data: SaveNoteViewStateDto,
+    ) -> AppResult<NoteViewState> {
+        state.run(move |conn| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+            if !data.scroll_position.is_finite() || data.scroll_position < 0.0 {
+                return Err(AppError::InvalidInput("Scroll position must be a number of at least 0".into()));
+            }
+            if data.cursor_offset < 0 {
+                return Err(AppError::InvalidInput("Cursor offset cannot be negative".into()));
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            DbService::with_busy_retry(|| DbService::save_note_view_state(conn, &note_id, &data, now))?
+                .ok_or(AppError::NotFound("Note", note_id))
+        }).await
     }
 
     /// Get note by ID
-    pub async fn get_note(id: String) -> AppResult<Note> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_note(state: &AppState, id: String) -> AppResult<Note> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            let note = DbService::get_note_by_id(conn, &id)?;
+            note.ok_or(AppError::NotFound("Note", id))
+        }).await
+    }
+
+    /// Update note. Locked notes are rejected unless `force` is set, and
+    /// updated_at only moves when a field actually changes. With
+    /// `expected_updated_at`, an outdated version is rejected with the stored note.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_note(state: &AppState, id: String, data: UpdateNoteDto, force: bool) -> AppResult<Note> {
+        Self::update_note_v2(state, id, data, force).await.map(|change| change.after)
+    }
+
+    /// Update note like `update_note`, returning it as it was before and after the update
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_note_v2(state: &AppState, id: String, mut data: UpdateNoteDto, force: bool) -> AppResult<Changed<Note>> {
+        state.blocking(move |state| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            let (change, project_path) = {
+                let conn = &state.conn()?;
+                data.validate(&SettingsService::limits(conn)?)?;
+
+                Self::ensure_unlocked(conn, &id, force)?;
+                let (change, updated) = DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                    let before = DbService::get_note_by_id(tx, &id)?
+                        .ok_or_else(|| AppError::NotFound("Note", id.clone()))?;
+                    if data.expected_updated_at.is_some_and(|expected| expected != before.updated_at) {
+                        return Err(AppError::edit_conflict("Note", &id, &before));
+                    }
+                    let updated = Self::has_changes(&before, &data);
+                    if updated && !DbService::update_note(tx, &id, &data)? {
+                        return Err(AppError::edit_conflict("Note", &id, &before));
+                    }
+                    let after = DbService::get_note_by_id(tx, &id)?
+                        .ok_or_else(|| AppError::NotFound("Note", id.clone()))?;
+                    Ok((Changed::new(before, after), updated))
+                }))?;
+                if !updated {
+                    return Ok(change);
+                }
+                let project = Self::note_project(conn, &change.after)?;
+                (change, project.map(|p| p.path))
+            };
+
+            if let Some(path) = project_path {
+                GitService::auto_commit(&path, &format!("Update note: {}", change.after.title));
+            }
+            Ok(change)
+        }).await
+    }
+
+    /// Undo an update by writing back the note as it was before, unless it was
+    /// updated again since. Locked notes are rejected.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn revert_note(state: &AppState, id: String, payload: RevertChangeDto) -> AppResult<Note> {
+        state.blocking(move |state| {
+            let before: Note = UndoService::decode(EntityType::Note, &id, payload.before)?;
+            if before.title.is_empty() {
+                return Err(AppError::InvalidInput("Note title cannot be empty".into()));
+            }
+
+            let (note, project_path) = {
+                let conn = &state.conn()?;
+
+                Self::ensure_unlocked(conn, &id, false)?;
+                let note = DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                    let current = DbService::get_note_by_id(tx, &id)?
+                        .ok_or_else(|| AppError::NotFound("Note", id.clone()))?;
+                    UndoService::ensure_unchanged(EntityType::Note, &id, current.updated_at, payload.updated_at)?;
+                    if current.project_id != before.project_id {
+                        return Err(AppError::Conflict(format!("Note '{}' moved to another project", id)));
+                    }
+                    DbService::restore_note(tx, &before)?;
+                    DbService::get_note_by_id(tx, &id)?
+                        .ok_or_else(|| AppError::NotFound("Note", id.clone()))
+                }))?;
+                let project = Self::note_project(conn, &note)?;
+                (note, project.map(|p| p.path))
+            };
+
+            if let Some(path) = project_path {
+                GitService::auto_commit(&path, &format!("Undo note edit: {}", note.title));
+            }
+            Ok(note)
+        }).await
+    }
+
+    /// Delete note. Locked notes are rejected unless `force` is set.
+    /// The note goes to the trash unless `permanent` is set or it is an inbox note.
+    /// Its attachments are detached; with `delete_attachments` a note deleted for
+    /// good also takes the attached files no other note uses.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_note(
+        state: &AppState,
+        id: String,
+        force: bool,
+        permanent: bool,
+        delete_attachments: bool,
+    ) -> AppResult<()> {
+        state.blocking(move |state| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            let (title, project, attachments) = {
+                let conn = &state.conn()?;
+
+                Self::ensure_unlocked(conn, &id, force)?;
+                let note = DbService::get_note_by_id(conn, &id)?
+                    .ok_or_else(|| AppError::NotFound("Note", id.clone()))?;
+                let project = Self::note_project(conn, &note)?;
+                // A trashed note may come back, so its files stay
+                let attachments = if delete_attachments && (permanent || project.is_none()) {
+                    DbService::get_note_attachments(conn, &id)?
+                } else {
+                    Vec::new()
+                };
+                if !DbService::with_busy_retry(|| DbService::delete_note(conn, &id, !permanent))? {
+                    return Err(AppError::NotFound("Note", id));
+                }
+                (note.title, project, attachments)
+            };
+
+            if let Some(project) = project {
+                NoteAttachmentService::delete_unused_files(state, &project.id, &project.path, &attachments)?;
+                GitService::auto_commit(&project.path, &format!("Delete note: {}", title));
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Toggle pin status
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn toggle_pin(state: &AppState, id: String) -> AppResult<Note> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            if !DbService::with_busy_retry(|| DbService::toggle_note_pin(conn, &id))? {
+                return Err(AppError::NotFound("Note", id));
+            }
+            DbService::get_note_by_id(conn, &id)?
+                .ok_or(AppError::NotFound("Note", id))
+        }).await
+    }
+
+    /// Duplicate a note with its content and tags, titled "Copy of ..." unless a
+    /// title is given, optionally into another project; an inbox note is copied into
+    /// the inbox when no project is given. The copy starts unpinned and unlocked.
+    #[instrument(level = "debug", skip_all, fields(id = %id, target_project_id = ?target_project_id))]
+    pub async fn duplicate_note(
+        state: &AppState,
+        id: String,
+        new_title: Option<String>,
+        target_project_id: Option<String>,
+    ) -> AppResult<Note> {
+        state.blocking(move |state| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            if new_title.as_deref().is_some_and(|title| title.trim().is_empty()) {
+                return Err(AppError::InvalidInput("Note title cannot be empty".into()));
+            }
+
+            let (note, project_path) = {
+                let conn = &state.conn()?;
+
+                let source = DbService::get_note_by_id(conn, &id)?
+                    .ok_or_else(|| AppError::NotFound("Note", id.clone()))?;
+                let project_id = target_project_id.or_else(|| source.project_id.clone());
+                let project = match project_id.as_deref() {
+                    Some(project_id) => Some(
+                        DbService::get_project_by_id(conn, project_id)?
+                            .ok_or_else(|| AppError::NotFound("Project", project_id.to_string()))?,
+                    ),
+                    None => None,
+                };
+
+                let now = chrono::Utc::now().timestamp();
+                let copy = Note {
+                    id: Uuid::new_v4().to_string(),
+                    project_id,
+                    title: new_title.unwrap_or_else(|| format!("Copy of {}", source.title)),
+                    content: source.content,
+                    created_at: now,
+                    updated_at: now,
+                    tags: source.tags,
+                    is_pinned: false,
+                    is_locked: false,
+                    metadata: None,
+                };
+                DbService::with_busy_retry(|| DbService::insert_note(conn, &copy))?;
+
+                let note = DbService::get_note_by_id(conn, &copy.id)?
+                    .ok_or_else(|| AppError::NotFound("Note", copy.id.clone()))?;
+                (note, project.map(|p| p.path))
+            };
+
+            if let Some(path) = project_path {
+                GitService::auto_commit(&path, &format!("Duplicate note: {}", note.title));
+            }
+            Ok(note)
+        }).await
+    }
+
+    /// Search the notes of a project by title and content, and the inbox as well
+    /// with `include_inbox`. Notes whose title is only similar to the query, such
+    /// as a misspelling, are added as fuzzy matches.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn search_notes(
+        state: &AppState,
+        project_id: String,
+        query: String,
+        include_inbox: bool,
+        range: Option<SearchDateRange>,
+    ) -> AppResult<Vec<SearchHit<Note>>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let (after, before) = SearchService::updated_bounds(conn, range.as_ref(), chrono::Utc::now().timestamp())?;
+            let in_range = |updated_at: i64| after.is_none_or(|a| updated_at >= a) && before.is_none_or(|b| updated_at < b);
+            let exact = DbService::search_notes(conn, &project_id, &query, include_inbox, after, before)?;
+            SearchService::with_fuzzy_matches(
+                conn,
+                &query,
+                exact,
+                |note| &note.id,
+                |note| &note.title,
+                |trigrams| DbService::get_note_title_candidates(conn, &project_id, include_inbox, trigrams),
+                |ids| Ok(DbService::get_notes_by_ids(conn, ids)?.into_iter().filter(|note| in_range(note.updated_at)).collect()),
+            )
+        }).await
+    }
+
+    /// Get all tags used by notes of a project
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_all_tags(state: &AppState, project_id: String) -> AppResult<Vec<String>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            DbService::get_note_tags(conn, &project_id)
+        }).await
+    }
+
+    /// Get notes carrying all or any of the given tags, or a tag below them, and
+    /// none of the excluded ones
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_notes_by_tags(
+        state: &AppState,
+        project_id: String,
+        tags: Vec<String>,
+        match_mode: TagMatchMode,
+        exclude_tags: Vec<String>,
+    ) -> AppResult<Vec<Note>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if tags.is_empty() {
+                return Err(AppError::InvalidInput("Tags cannot be empty".into()));
+            }
+
+            DbService::get_notes_by_tags(conn, &project_id, &tags, match_mode, &exclude_tags)
+        }).await
+    }
+
+    /// Move notes to another project; locked notes are skipped. Attachment files
+    /// of the moved notes are copied into the target project, the notes' links
+    /// to them rewritten, and the old files removed once nothing uses them.
+    #[instrument(level = "debug", skip_all, fields(target_project_id = %target_project_id))]
+    pub async fn move_notes_to_project(state: &AppState, note_ids: Vec<String>, target_project_id: String) -> AppResult<MoveResult> {
+        state.blocking(move |state| {
+            if target_project_id.is_empty() {
+                return Err(AppError::InvalidInput("Target project ID cannot be empty".into()));
+            }
+
+            let target = DbService::get_project_by_id(&*state.conn()?, &target_project_id)?
+                .ok_or(AppError::NotFound("Project", target_project_id))?;
+            Self::move_notes_with_attachments(state, &note_ids, &target)
+        }).await
+    }
+
+    /// Move notes into `target` together with their attachment files. Files are
+    /// copied first so a failed move leaves the source projects untouched; the
+    /// copies are removed again when the database update fails.
+    fn move_notes_with_attachments(state: &AppState, note_ids: &[String], target: &Project) -> AppResult<MoveResult> {
+        // Attachments of the notes leaving another project, by that project
+        let sources: Vec<(Project, Vec<NoteAttachment>)> = {
+            let conn = &state.conn()?;
+            let mut by_project: HashMap<String, (Project, Vec<NoteAttachment>)> = HashMap::new();
+            for id in note_ids {
+                let Some(note) = DbService::get_note_by_id(conn, id)? else {
+                    continue;
+                };
+                if note.is_locked || note.project_id.as_deref() == Some(target.id.as_str()) {
+                    continue;
+                }
+                let Some(project) = Self::note_project(conn, &note)? else {
+                    continue;
+                };
+                let attachments = DbService::get_note_attachments(conn, id)?;
+                if !attachments.is_empty() {
+                    by_project.entry(project.id.clone()).or_insert_with(|| (project, Vec::new())).1.extend(attachments);
+                }
+            }
+            by_project.into_values().collect()
+        };
+
+        let mut copied: Vec<(Project, Vec<NoteAttachment>, HashMap<String, String>)> = Vec::new();
+        let remove_all = |copied: &[(Project, Vec<NoteAttachment>, HashMap<String, String>)]| {
+            for (_, _, moves) in copied {
+                NoteAttachmentService::remove_copies(&target.path, moves);
+            }
+        };
+        for (project, attachments) in sources {
+            match NoteAttachmentService::copy_files_to_project(&project.path, &target.path, &attachments) {
+                Ok(moves) => copied.push((project, attachments, moves)),
+                Err(e) => {
+                    remove_all(&copied);
+                    return Err(e);
+                }
+            }
+        }
+
+        let moved = {
+            let conn = &state.conn()?;
+            DbService::with_busy_retry(|| {
+                DbService::with_tx(conn, |tx| {
+                    let result = DbService::move_notes_to_project(tx, note_ids, &target.id)?;
+                    for (_, attachments, moves) in &copied {
+                        let owners: HashSet<&str> = attachments.iter().map(|a| a.note_id.as_str()).collect();
+                        for note_id in owners {
+                            DbService::relocate_note_attachments(tx, note_id, moves)?;
+                        }
+                    }
+                    Ok(result)
+                })
+            })
+        };
+        let result = match moved {
+            Ok(result) => result,
+            Err(e) => {
+                remove_all(&copied);
+                return Err(e);
+            }
+        };
+
+        for (project, attachments, moves) in &copied {
+            if NoteAttachmentService::delete_unused_files(state, &project.id, &project.path, attachments)? > 0 {
+                GitService::auto_commit(&project.path, &format!("Move note attachments to {}", target.name));
+            }
+            if !moves.is_empty() {
+                GitService::auto_commit(&target.path, &format!("Move note attachments from {}", project.name));
+            }
         }
+        Ok(result)
+    }
 
-        // TODO: Fetch from database
-        Err(AppError::NotFound("Note", id))
+    /// Lock a note against edits and deletion
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn lock_note(state: &AppState, id: String) -> AppResult<Note> {
+        state.blocking(move |state| {
+            Self::set_locked(state, id, true)
+        }).await
     }
 
-    /// Update note
-    pub async fn update_note(id: String, _data: UpdateNoteDto) -> AppResult<Note> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+    /// Unlock a previously locked note
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn unlock_note(state: &AppState, id: String) -> AppResult<Note> {
+        state.blocking(move |state| {
+            Self::set_locked(state, id, false)
+        }).await
+    }
+
+    /// Project a note belongs to; None for inbox notes and missing projects
+    pub(crate) fn note_project(conn: &Connection, note: &Note) -> AppResult<Option<Project>> {
+        match note.project_id.as_deref() {
+            Some(project_id) => DbService::get_project_by_id(conn, project_id),
+            None => Ok(None),
         }
+    }
 
-        // TODO: Update in database
-        Err(AppError::NotFound("Note", id))
+    /// Reject changes to a locked note unless explicitly overridden
+    pub fn ensure_unlocked(conn: &Connection, id: &str, force: bool) -> AppResult<()> {
+        match DbService::is_note_locked(conn, id)? {
+            None => Err(AppError::NotFound("Note", id.to_string())),
+            Some(true) if !force => Err(AppError::PermissionDenied("Note is locked".into())),
+            Some(_) => Ok(()),
+        }
     }
 
-    /// Delete note
-    pub async fn delete_note(id: String) -> AppResult<()> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+    /// Render a note as a shareable copy with a citation footer.
+    /// `format` is "plain" (markdown stripped) or "markdown" (vault syntax flattened).
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn copy_note_for_sharing(
+        state: &AppState,
+        note_id: String,
+        format: String,
+        include_metadata: bool,
+    ) -> AppResult<String> {
+        state.blocking(move |state| {
+            let plain = match format.as_str() {
+                "plain" => true,
+                "markdown" => false,
+                _ => {
+                    return Err(AppError::InvalidInput(format!(
+                        "Invalid format '{}'. Must be one of: plain, markdown",
+                        format
+                    )))
+                }
+            };
+
+            let (note, project_name) = {
+                let conn = &state.conn()?;
+                let note = DbService::get_note_by_id(conn, &note_id)?
+                    .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?;
+                let project = Self::note_project(conn, &note)?;
+                (note, project.map(|p| p.name))
+            };
+
+            let mut output = String::new();
+
+            if include_metadata {
+                if plain {
+                    output.push_str(&format!("{}\n\n", note.title));
+                } else {
+                    output.push_str(&format!("# {}\n\n", note.title));
+                }
+                if let Some(tags) = note.tags.as_ref().filter(|t| !t.is_empty()) {
+                    output.push_str(&format!("Tags: {}\n\n", tags.join(", ")));
+                }
+            }
+
+            let body = if plain {
+                markdown::strip_markdown(&note.content)
+            } else {
+                markdown::flatten_for_sharing(&note.content)
+            };
+            output.push_str(body.trim_end());
+
+            let date = chrono::Local::now().format("%Y-%m-%d");
+            output.push_str(&format!(
+                "\n\n— exported from {}, {}",
+                project_name.as_deref().unwrap_or("Research Vault"),
+                date
+            ));
+
+            Ok(output)
+        }).await
+    }
+
+    /// List the notes whose `[[wikilinks]]` point at a note
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn get_note_backlinks(state: &AppState, note_id: String) -> AppResult<Vec<NoteSummary>> {
+        state.run(move |conn| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            if DbService::get_note_by_id(conn, &note_id)?.is_none() {
+                return Err(AppError::NotFound("Note", note_id));
+            }
+            DbService::get_note_backlinks(conn, &note_id)
+        }).await
+    }
+
+    /// List the `[[wikilinks]]` of a note, resolved or not
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn get_note_outgoing_links(state: &AppState, note_id: String) -> AppResult<Vec<NoteLink>> {
+        state.run(move |conn| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            if DbService::get_note_by_id(conn, &note_id)?.is_none() {
+                return Err(AppError::NotFound("Note", note_id));
+            }
+            DbService::get_note_outgoing_links(conn, &note_id)
+        }).await
+    }
+
+    /// Word, character and heading counts of a note and its estimated reading time
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn get_note_stats(state: &AppState, note_id: String) -> AppResult<NoteStats> {
+        state.run(move |conn| {
+            if note_id.is_empty() {
+                return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+            }
+
+            let note = DbService::get_note_by_id(conn, &note_id)?
+                .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?;
+            let counts = word_count::count(&note.content);
+            Ok(NoteStats {
+                note_id,
+                word_count: counts.words,
+                character_count: counts.characters,
+                heading_count: counts.headings,
+                reading_time_minutes: counts.reading_minutes,
+            })
+        }).await
+    }
+
+    /// Words written in a project between `from` and `to` (exclusive), by local day.
+    /// Without a revision history a note's words count on the day it was last
+    /// edited. Days follow `timezone` when given, otherwise the system's timezone.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_writing_stats(
+        state: &AppState,
+        project_id: String,
+        from: i64,
+        to: i64,
+        timezone: Option<String>,
+    ) -> AppResult<WritingStats> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            if from >= to {
+                return Err(AppError::InvalidInput("The start of the range must be before its end".into()));
+            }
+            let offset_seconds = match timezone.as_deref() {
+                Some(tz) => timezone::parse_utc_offset(tz)
+                    .ok_or_else(|| AppError::InvalidInput(format!("Unknown timezone '{}'", tz)))?,
+                None => Local::now().offset().fix().local_minus_utc(),
+            };
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+
+            let days = DbService::get_note_words_by_day(conn, &project_id, from, to, offset_seconds)?;
+            Ok(WritingStats {
+                total_words: DbService::get_project_word_total(conn, &project_id)?,
+                words_in_range: days.iter().map(|day| day.words).sum(),
+                days,
+                project_id,
+            })
+        }).await
+    }
+
+    /// Set a project's word-count goal, or clear it with None, and report the
+    /// progress towards it
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn set_writing_goal(state: &AppState, project_id: String, target_words: Option<i64>) -> AppResult<WritingProgress> {
+        if target_words.is_some_and(|words| !(1..=MAX_WRITING_GOAL).contains(&words)) {
+            return Err(AppError::InvalidInput(format!("A writing goal must be between 1 and {} words", MAX_WRITING_GOAL)));
         }
+        state.run(move |conn| {
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::with_busy_retry(|| DbService::set_writing_goal(conn, &project_id, target_words))?;
+            Self::writing_progress(conn, &project_id, chrono::Utc::now().timestamp())
+        }).await
+    }
 
-        // TODO: Delete from database
-        Err(AppError::NotFound("Note", id))
+    /// Words in a project's notes tagged with the writing_goal_tag setting,
+    /// against its goal, with the words added on each of the last 14 days. The
+    /// word counts are the ones cached when notes are saved; the days count
+    /// the notes tagged now.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_writing_progress(state: &AppState, project_id: String) -> AppResult<WritingProgress> {
+        state.run(move |conn| {
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            Self::writing_progress(conn, &project_id, chrono::Utc::now().timestamp())
+        }).await
     }
 
-    /// Toggle pin status
-    pub async fn toggle_pin(id: String) -> AppResult<Note> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
+    fn writing_progress(conn: &Connection, project_id: &str, now: i64) -> AppResult<WritingProgress> {
+        let tag = SettingsService::get_string(conn, SETTING_WRITING_GOAL_TAG)?.trim().to_string();
+        let target_words = DbService::get_writing_goal(conn, project_id)?;
+        let (words, notes) = DbService::get_goal_note_words(conn, project_id, &tag)?;
+
+        let offset = i64::from(SettingsService::utc_offset(conn, now)?);
+        let today = (now + offset).div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY - offset;
+        let first_day = today - (WRITING_PROGRESS_DAYS - 1) * SECONDS_PER_DAY;
+        let mut history = DbService::get_goal_word_history(conn, project_id, &tag, first_day)?.into_iter().peekable();
+
+        // Words of the counted notes as last recorded before `end`
+        let mut latest: HashMap<String, i64> = HashMap::new();
+        let mut words_before = |end: i64| {
+            while let Some((note_id, _, words)) = history.next_if(|(_, bucket, _)| *bucket < end) {
+                latest.insert(note_id, words);
+            }
+            latest.values().sum::<i64>()
+        };
+        let mut previous = words_before(first_day);
+        let mut days = Vec::with_capacity(WRITING_PROGRESS_DAYS as usize);
+        for day in 0..WRITING_PROGRESS_DAYS {
+            let start = first_day + day * SECONDS_PER_DAY;
+            let total = words_before(start + SECONDS_PER_DAY);
+            days.push(WordDelta {
+                date: chrono::DateTime::from_timestamp(start + offset, 0).unwrap_or_default().format("%Y-%m-%d").to_string(),
+                words: total - previous,
+            });
+            previous = total;
         }
 
-        // TODO: Toggle pin in database
-        Err(AppError::NotFound("Note", id))
+        Ok(WritingProgress {
+            project_id: project_id.to_string(),
+            target_words,
+            tag,
+            words,
+            notes,
+            percent: target_words.map(|target| words as f64 * 100.0 / target as f64),
+            days,
+        })
+    }
+
+    /// Whether applying the update would change any stored field
+    fn has_changes(note: &Note, data: &UpdateNoteDto) -> bool {
+        data.title.as_ref().is_some_and(|t| *t != note.title)
+            || data.content.as_ref().is_some_and(|c| *c != note.content)
+            || data.tags.as_ref().is_some_and(|t| Some(t) != note.tags.as_ref())
+            || data.is_pinned.is_some_and(|p| p != note.is_pinned)
     }
 
-    /// Duplicate note
-    pub async fn duplicate_note(id: String, _new_title: Option<String>) -> AppResult<Note> {
+    fn set_locked(state: &AppState, id: String, locked: bool) -> AppResult<Note> {
         if id.is_empty() {
             return Err(AppError::InvalidInput("Note ID cannot be empty".into()));
         }
 
-        // TODO: Duplicate in database
-        Err(AppError::NotFound("Note", id))
+        let conn = &state.conn()?;
+        if !DbService::with_busy_retry(|| DbService::set_note_locked(conn, &id, locked))? {
+            return Err(AppError::NotFound("Note", id));
+        }
+        let note = DbService::get_note_by_id(conn, &id)?;
+        note.ok_or(AppError::NotFound("Note", id))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use std::fs;
+    use std::path::Path;
 
-    /// Search notes
-    pub async fn search_notes(project_id: String, _query: String) -> AppResult<Vec<Note>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+    #[tokio::test]
+    async fn moving_notes_takes_their_attachment_files_along() {
+        let state = test_support::open_state();
+        let (from, to, note) = {
+            let conn = &state.conn().unwrap();
+            let from = test_support::project(conn, "from");
+            let to = test_support::project(conn, "to");
+            let note = test_support::note(conn, &from.id, "Plot", "![](docs/attachments/plot.png)");
+            let attachment = NoteAttachment {
+                id: Uuid::new_v4().to_string(),
+                note_id: note.id.clone(),
+                relative_path: "docs/attachments/plot.png".to_string(),
+                original_name: "plot.png".to_string(),
+                size_bytes: 4,
+                created_at: 0,
+            };
+            DbService::insert_note_attachment(conn, &attachment).unwrap();
+            (from, to, note)
+        };
+        for (project, contents) in [(&from, "from"), (&to, "taken")] {
+            let dir = Path::new(&project.path).join("docs/attachments");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("plot.png"), contents).unwrap();
         }
 
-        // TODO: Search in database
-        Ok(vec![])
+        let result = NoteService::move_notes_to_project(&state, vec![note.id.clone()], to.id.clone()).await.unwrap();
+        assert_eq!(result.moved, 1);
+
+        let conn = &state.conn().unwrap();
+        let attachments = DbService::get_note_attachments(conn, &note.id).unwrap();
+        assert_eq!(attachments[0].relative_path, "docs/attachments/plot (2).png");
+        let moved = DbService::get_note_by_id(conn, &note.id).unwrap().unwrap();
+        assert_eq!(moved.content, "![](docs/attachments/plot%20(2).png)");
+        assert_eq!(fs::read_to_string(Path::new(&to.path).join("docs/attachments/plot (2).png")).unwrap(), "from");
+        assert_eq!(fs::read_to_string(Path::new(&to.path).join("docs/attachments/plot.png")).unwrap(), "taken");
+        assert!(!Path::new(&from.path).join("docs/attachments/plot.png").exists());
     }
 
-    /// Get all tags for a project
-    pub async fn get_all_tags(project_id: String) -> AppResult<Vec<String>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+    #[tokio::test]
+    async fn locked_notes_are_not_filed_elsewhere() {
+        let state = test_support::open_state();
+        let (note, to) = {
+            let conn = &state.conn().unwrap();
+            let from = test_support::project(conn, "from");
+            let to = test_support::project(conn, "to");
+            let note = test_support::note(conn, &from.id, "Locked", "");
+            DbService::set_note_locked(conn, &note.id, true).unwrap();
+            (note, to)
+        };
+
+        let result = NoteService::move_note_to_project(&state, note.id, to.id).await;
+        assert!(matches!(result, Err(AppError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn writing_progress_counts_chapter_notes_and_daily_deltas() {
+        let state = test_support::open_state();
+        let (project, chapter) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let mut chapter = test_support::new_note(Some(&project.id), "Introduction", "one two three four five");
+            chapter.tags = Some(vec!["chapter".to_string()]);
+            DbService::insert_note(conn, &chapter).unwrap();
+            let mut section = test_support::new_note(Some(&project.id), "Methods", "six seven");
+            section.tags = Some(vec!["chapter/two".to_string()]);
+            DbService::insert_note(conn, &section).unwrap();
+            test_support::note(conn, &project.id, "Scratch", "not counted at all");
+            (project, chapter)
+        };
+
+        let progress = NoteService::get_writing_progress(&state, project.id.clone()).await.unwrap();
+        assert_eq!((progress.target_words, progress.percent), (None, None));
+        assert_eq!((progress.tag.as_str(), progress.words, progress.notes), ("chapter", 7, 2));
+        assert_eq!(progress.days.len(), 14);
+        assert_eq!(progress.days[13].words, 7);
+
+        // Two of today's words were already there three days ago
+        let three_days_ago = (chrono::Utc::now().timestamp() - 3 * SECONDS_PER_DAY) / 900 * 900;
+        state.conn().unwrap().execute(
+            "INSERT INTO note_word_history (note_id, bucket, words) VALUES (?1, ?2, 2)",
+            rusqlite::params![chapter.id, three_days_ago],
+        ).unwrap();
+        let progress = NoteService::set_writing_goal(&state, project.id.clone(), Some(20)).await.unwrap();
+        assert_eq!(progress.target_words, Some(20));
+        assert_eq!(progress.percent, Some(35.0));
+        assert_eq!(progress.days.iter().map(|day| day.words).collect::<Vec<_>>(), [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 5]);
+
+        // Cutting words counts against today
+        let update = UpdateNoteDto {
+            title: None,
+            content: Some("one".to_string()),
+            tags: None,
+            is_pinned: None,
+            expected_updated_at: None,
+        };
+        DbService::update_note(&state.conn().unwrap(), &chapter.id, &update).unwrap();
+        let progress = NoteService::get_writing_progress(&state, project.id.clone()).await.unwrap();
+        assert_eq!((progress.words, progress.days[13].words), (3, 1));
+
+        let progress = NoteService::set_writing_goal(&state, project.id.clone(), None).await.unwrap();
+        assert_eq!(progress.target_words, None);
+        let result = NoteService::set_writing_goal(&state, project.id, Some(0)).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn word_counts_are_cached_on_write_and_summed_by_day() {
+        let state = test_support::open_state();
+        let day = 86_400;
+        let (project, draft, ideas) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let draft = test_support::note(conn, &project.id, "Draft", "# Intro\n\none two three");
+            let ideas = test_support::note(conn, &project.id, "Ideas", "研究");
+            conn.execute("UPDATE notes SET updated_at = ?1 WHERE id = ?2", rusqlite::params![10 * day + 60, draft.id]).unwrap();
+            conn.execute("UPDATE notes SET updated_at = ?1 WHERE id = ?2", rusqlite::params![11 * day + 60, ideas.id]).unwrap();
+            (project, draft, ideas)
+        };
+
+        let stats = NoteService::get_note_stats(&state, draft.id.clone()).await.unwrap();
+        assert_eq!((stats.word_count, stats.character_count, stats.heading_count, stats.reading_time_minutes), (4, 17, 1, 1));
+
+        let writing = NoteService::get_writing_stats(&state, project.id.clone(), 10 * day, 12 * day, Some("UTC".into())).await.unwrap();
+        assert_eq!((writing.total_words, writing.words_in_range), (6, 6));
+        let days: Vec<(&str, i64, i64)> = writing.days.iter().map(|d| (d.date.as_str(), d.words, d.notes_edited)).collect();
+        assert_eq!(days, [("1970-01-11", 4, 1), ("1970-01-12", 2, 1)]);
 
-        // TODO: Fetch unique tags from database
-        Ok(vec![])
+        let update = UpdateNoteDto { title: None, content: Some("Just two".into()), tags: None, is_pinned: None, expected_updated_at: None };
+        NoteService::update_note(&state, ideas.id.clone(), update, false).await.unwrap();
+        let cached: i64 = state.conn().unwrap()
+            .query_row("SELECT word_count FROM notes WHERE id = ?1", rusqlite::params![ideas.id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cached, 2);
+
+        let writing = NoteService::get_writing_stats(&state, project.id.clone(), 10 * day, 11 * day, Some("UTC".into())).await.unwrap();
+        assert_eq!((writing.total_words, writing.words_in_range), (6, 4));
+        assert!(NoteService::get_writing_stats(&state, project.id, 11 * day, 11 * day, None).await.is_err());
     }
 
-    /// Get notes by tags
-    pub async fn list_notes_by_tags(project_id: String, tags: Vec<String>) -> AppResult<Vec<Note>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+    fn bulk_item(title: &str, tags: &[&str]) -> CreateNoteDto {
+        CreateNoteDto {
+            project_id: String::new(),
+            title: title.to_string(),
+            content: format!("Annotation on {}", title),
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            is_pinned: None,
         }
+    }
+
+    #[tokio::test]
+    async fn bulk_creation_keeps_the_valid_notes_unless_atomic() {
+        let state = test_support::open_state();
+        let (project, other) = {
+            let conn = &state.conn().unwrap();
+            (test_support::project(conn, "Thesis"), test_support::project(conn, "Other"))
+        };
+        let items = || {
+            let mut foreign = bulk_item("Foreign", &[]);
+            foreign.project_id = other.id.clone();
+            vec![bulk_item("Zotero one", &["zotero"]), bulk_item("  ", &[]), bulk_item("Zotero two", &["zotero", "pdf"]), foreign]
+        };
+
+        let result = NoteService::create_notes_bulk(&state, project.id.clone(), items(), true).await.unwrap();
+        assert!(result.created_ids.is_empty());
+        assert_eq!(result.errors.iter().map(|e| e.index).collect::<Vec<_>>(), [1, 3]);
+        assert!(result.errors[1].reason.contains(&other.id), "{}", result.errors[1].reason);
+
+        let result = NoteService::create_notes_bulk(&state, project.id.clone(), items(), false).await.unwrap();
+        assert_eq!(result.created_ids.len(), 2);
+        assert_eq!(result.errors.len(), 2);
+
+        let conn = &state.conn().unwrap();
+        let titles: Vec<String> = result
+            .created_ids
+            .iter()
+            .map(|id| DbService::get_note_by_id(conn, id).unwrap().unwrap().title)
+            .collect();
+        assert_eq!(titles, ["Zotero one", "Zotero two"]);
+        let tagged = DbService::get_notes_by_tags(conn, &project.id, &["zotero".to_string()], TagMatchMode::Any, &[]).unwrap();
+        assert_eq!(tagged.len(), 2);
+        let found = DbService::search_notes(conn, &project.id, "annotation", false, None, None).unwrap();
+        assert_eq!(found.len(), 2, "both notes are in the full-text index");
+        let logged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM activity_log WHERE entity_type = 'note' AND action = 'created' AND entity_id IN (?1, ?2)",
+                rusqlite::params![result.created_ids[0], result.created_ids[1]],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(logged, 2);
+    }
 
-        if tags.is_empty() {
-            return Err(AppError::InvalidInput("Tags cannot be empty".into()));
+    #[tokio::test]
+    async fn a_thousand_notes_are_created_within_a_second() {
+        let state = test_support::open_state();
+        let project = test_support::project(&state.conn().unwrap(), "Zotero");
+        let items: Vec<CreateNoteDto> = (0..1_000).map(|i| bulk_item(&format!("Annotation {}", i), &["zotero"])).collect();
+
+        let started = std::time::Instant::now();
+        let result = NoteService::create_notes_bulk(&state, project.id.clone(), items, true).await.unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(result.created_ids.len(), 1_000);
+        assert!(result.errors.is_empty());
+        // Unoptimized test builds get more room than the second a release build has to meet
+        let budget = if cfg!(debug_assertions) { 5_000 } else { 1_000 };
+        assert!(elapsed.as_millis() < budget, "creating 1,000 notes took {:?}", elapsed);
+
+        let too_many = (0..=MAX_BULK_NOTES).map(|i| bulk_item(&i.to_string(), &[])).collect();
+        assert!(matches!(
+            NoteService::create_notes_bulk(&state, project.id, too_many, false).await,
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn interleaved_note_edits_conflict_instead_of_overwriting() {
+        let state = test_support::open_state();
+        let note = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let note = test_support::note(conn, &project.id, "Draft", "First paragraph");
+            conn.execute("UPDATE notes SET updated_at = 1000 WHERE id = ?1", [&note.id]).unwrap();
+            note
+        };
+        let update = |fields: serde_json::Value| -> UpdateNoteDto { serde_json::from_value(fields).unwrap() };
+
+        let first = update(serde_json::json!({ "content": "First paragraph\n\nSecond paragraph", "expected_updated_at": 1000 }));
+        let first = NoteService::update_note(&state, note.id.clone(), first, false).await.unwrap();
+
+        let second = update(serde_json::json!({ "content": "First paragraph, edited", "expected_updated_at": 1000 }));
+        match NoteService::update_note(&state, note.id.clone(), second, false).await {
+            Err(error @ AppError::EditConflict { .. }) => {
+                let details = error.details().unwrap();
+                assert_eq!(details["entity"], "Note");
+                assert_eq!(details["current"]["content"], first.content.as_str());
+            }
+            other => panic!("expected an edit conflict, got {:?}", other),
         }
+        let stored = NoteService::get_note(&state, note.id.clone()).await.unwrap();
+        assert_eq!(stored.content, "First paragraph\n\nSecond paragraph", "the second paragraph is kept");
+
+        let unchecked = NoteService::update_note(&state, note.id.clone(), update(serde_json::json!({ "title": "Chapter" })), false).await.unwrap();
+        assert_eq!(unchecked.title, "Chapter");
+    }
+
+    #[tokio::test]
+    async fn a_stale_edit_in_the_same_second_is_rejected() {
+        let state = test_support::open_state();
+        let note = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            test_support::note(conn, &project.id, "Draft", "First paragraph")
+        };
+        let update = |fields: serde_json::Value| -> UpdateNoteDto { serde_json::from_value(fields).unwrap() };
+
+        // Saved right after it was created, well within the same second
+        let first = update(serde_json::json!({ "content": "Edited once", "expected_updated_at": note.updated_at }));
+        let first = NoteService::update_note(&state, note.id.clone(), first, false).await.unwrap();
+        assert!(first.updated_at > note.updated_at);
+
+        let stale = update(serde_json::json!({ "content": "Edited elsewhere", "expected_updated_at": note.updated_at }));
+        let stale = NoteService::update_note(&state, note.id.clone(), stale, false).await;
+        assert!(matches!(stale, Err(AppError::EditConflict { entity: "Note", .. })), "{:?}", stale);
+        assert_eq!(NoteService::get_note(&state, note.id.clone()).await.unwrap().content, "Edited once");
+
+        let second = update(serde_json::json!({ "title": "Chapter", "expected_updated_at": first.updated_at }));
+        let second = NoteService::update_note(&state, note.id.clone(), second, false).await.unwrap();
+        assert!(second.updated_at > first.updated_at);
+    }
+
+    #[tokio::test]
+    async fn listing_notes_by_tags_combines_all_any_and_exclusions() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Stats");
+            let other = test_support::project(conn, "Other");
+            let tagged = [
+                (&project, "Both", &["methods", "stats"][..]),
+                (&project, "Methods", &["methods"]),
+                (&project, "Stats draft", &["stats", "draft"]),
+                (&project, "Both draft", &["methods", "stats", "draft"]),
+                (&project, "Bayes", &["methods/bayes"]),
+                (&project, "Untagged", &[]),
+                (&other, "Elsewhere", &["methods", "stats"]),
+            ];
+            for (project, title, tags) in tagged {
+                let mut note = test_support::new_note(Some(&project.id), title, "");
+                note.tags = Some(tags.iter().map(|t| t.to_string()).collect());
+                DbService::insert_note(conn, &note).unwrap();
+            }
+            project
+        };
+
+        let list = |tags: &[&str], mode, exclude: &[&str]| {
+            let state = state.clone();
+            let project_id = project.id.clone();
+            let tags = tags.iter().map(|t| t.to_string()).collect();
+            let exclude = exclude.iter().map(|t| t.to_string()).collect();
+            async move {
+                let notes = NoteService::list_notes_by_tags(&state, project_id, tags, mode, exclude).await.unwrap();
+                let mut titles: Vec<String> = notes.into_iter().map(|note| note.title).collect();
+                titles.sort();
+                titles
+            }
+        };
+        use TagMatchMode::{All, Any};
+
+        assert_eq!(list(&["methods", "stats"], All, &[]).await, ["Both", "Both draft"]);
+        assert_eq!(
+            list(&["methods", "stats"], Any, &[]).await,
+            ["Bayes", "Both", "Both draft", "Methods", "Stats draft"]
+        );
+        // Carrying an included and an excluded tag leaves the note out
+        assert_eq!(list(&["methods", "stats"], Any, &["draft"]).await, ["Bayes", "Both", "Methods"]);
+        assert_eq!(list(&["methods", "stats"], All, &["draft"]).await, ["Both"]);
+        // Excluding a tag excludes the tags below it too
+        assert_eq!(list(&["stats", "methods/bayes"], Any, &["methods"]).await, ["Stats draft"]);
+
+        // A tag below two of the given ones counts for both
+        assert_eq!(list(&["methods", "methods/bayes"], All, &[]).await, ["Bayes"]);
+        assert_eq!(list(&["methods", "Methods ", " METHODS"], All, &[]).await, list(&["methods"], Any, &[]).await);
+
+        // Unknown tags match nothing instead of failing
+        assert!(list(&["unknown"], Any, &[]).await.is_empty());
+        assert!(list(&["methods", "unknown"], All, &[]).await.is_empty());
+        assert_eq!(list(&["methods", "unknown"], Any, &[]).await, list(&["methods"], Any, &[]).await);
+        assert_eq!(list(&["methods"], Any, &["unknown"]).await, list(&["methods"], Any, &[]).await);
+        assert!(list(&["  "], Any, &[]).await.is_empty());
 
-        // TODO: Fetch from database filtered by tags
-        Ok(vec![])
+        let error = NoteService::list_notes_by_tags(&state, project.id.clone(), Vec::new(), Any, Vec::new()).await.unwrap_err();
+        assert!(matches!(error, AppError::InvalidInput(_)));
+        let error = NoteService::list_notes_by_tags(&state, String::new(), vec!["methods".into()], Any, Vec::new()).await.unwrap_err();
+        assert!(matches!(error, AppError::InvalidInput(_)));
     }
 }
@@ -0,0 +1,143 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateNoteDto, CreateNoteTemplateDto, Note, NoteTemplate};
+use crate::services::{DbService, NoteService, SettingsService};
+use crate::state::AppState;
+use crate::utils::template::{self, TemplateVariables};
+use crate::utils::validate::Validate;
+use chrono::Local;
+use std::collections::HashMap;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Note templates and notes created from them
+pub struct NoteTemplateService;
+
+impl NoteTemplateService {
+    /// Create a new note template
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_note_template(state: &AppState, mut data: CreateNoteTemplateDto) -> AppResult<NoteTemplate> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            let template = NoteTemplate {
+                id: Uuid::new_v4().to_string(),
+                name: data.name,
+                title_pattern: data.title_pattern,
+                content: data.content,
+                tags: data.tags,
+                created_at: chrono::Utc::now().timestamp(),
+            };
+
+            DbService::with_busy_retry(|| DbService::insert_note_template(conn, &template))?;
+            Ok(template)
+        }).await
+    }
+
+    /// List all note templates by name
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_note_templates(state: &AppState) -> AppResult<Vec<NoteTemplate>> {
+        state.run(DbService::get_note_templates).await
+    }
+
+    /// Delete a note template; notes created from it are kept
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_note_template(state: &AppState, id: String) -> AppResult<()> {
+        state.run(move |conn| {
+            if !DbService::with_busy_retry(|| DbService::delete_note_template(conn, &id))? {
+                return Err(AppError::NotFound("Note template", id));
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Create a note from a template. `{{date}}`, `{{time}}`, `{{project_name}}`,
+    /// `{{today}}`, `{{project.name}}`, `{{project.tags}}` and
+    /// `{{ref:KEY.title}}` / `{{ref:KEY.authors}}` for the project's references
+    /// are available, and `variables` adds or overrides plain values.
+    /// Placeholders left without a value are kept verbatim; a reference
+    /// variable whose cite key is not in the project is refused.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id, template_id = %template_id))]
+    pub async fn create_note_from_template(
+        state: &AppState,
+        project_id: String,
+        template_id: String,
+        variables: HashMap<String, String>,
+    ) -> AppResult<Note> {
+        let data = state.run(move |conn| {
+            let template = DbService::get_note_template_by_id(conn, &template_id)?
+                .ok_or(AppError::NotFound("Note template", template_id))?;
+            let project = DbService::get_project_by_id(conn, &project_id)?
+                .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+            let references = DbService::get_references_by_project(conn, &project_id)?;
+
+            let now = Local::now();
+            let today = now.format("%Y-%m-%d").to_string();
+            let mut values = HashMap::from([
+                ("date".to_string(), today.clone()),
+                ("time".to_string(), now.format("%H:%M").to_string()),
+                ("project_name".to_string(), project.name.clone()),
+            ]);
+            values.extend(variables);
+            let variables = TemplateVariables { values: &values, project: &project, today, references: &references };
+            let fill = |text: &str| {
+                template::fill(text, &variables).map_err(|key| {
+                    AppError::InvalidInput(format!("The template uses the cite key '{}', which no reference of this project has", key))
+                })
+            };
+
+            Ok(CreateNoteDto {
+                title: fill(&template.title_pattern)?.trim().to_string(),
+                content: fill(&template.content)?,
+                project_id,
+                tags: template.tags,
+                is_pinned: None,
+            })
+        }).await?;
+
+        NoteService::create_note(state, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    #[tokio::test]
+    async fn literature_notes_fill_reference_variables() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Survey");
+            test_support::reference(conn, &project.id, "doe2020", "On fields");
+            let template = NoteTemplate {
+                id: "literature".to_string(),
+                name: "Literature note".to_string(),
+                title_pattern: "{{ref:doe2020.title}}".to_string(),
+                content: "Project: {{project.name}}\n{{unknown.value}}".to_string(),
+                tags: None,
+                created_at: 0,
+            };
+            DbService::insert_note_template(conn, &template).unwrap();
+            let missing = NoteTemplate {
+                id: "missing".to_string(),
+                name: "Missing".to_string(),
+                title_pattern: "{{ref:roe1999.title}}".to_string(),
+                content: String::new(),
+                tags: None,
+                created_at: 0,
+            };
+            DbService::insert_note_template(conn, &missing).unwrap();
+            project
+        };
+
+        let note = NoteTemplateService::create_note_from_template(&state, project.id.clone(), "literature".into(), HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(note.title, "On fields");
+        assert_eq!(note.content, "Project: Survey\n{{unknown.value}}");
+
+        let result = NoteTemplateService::create_note_from_template(&state, project.id, "missing".into(), HashMap::new()).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(message)) if message.contains("roe1999")));
+    }
+}
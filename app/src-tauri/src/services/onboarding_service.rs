@@ -0,0 +1,179 @@
+use std::path::Path;
+use tracing::instrument;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateNoteDto, CreateProjectDto, CreateReferenceDto, CreateTaskDto, Project, ProjectSort, SETTING_FIRST_RUN_DONE};
+use crate::services::{DbService, NoteService, ProjectService, ReferenceService, SettingsService, TaskService};
+use crate::state::AppState;
+
+/// Name of the sample project and of its folder
+const SAMPLE_PROJECT_NAME: &str = "Welcome to Research Vault";
+
+/// Numbered folder names tried when the sample project's folder is taken
+const MAX_SAMPLE_FOLDER_ATTEMPTS: u32 = 100;
+
+/// Notes of the sample project as (title, content, tags, pinned)
+const SAMPLE_NOTES: [(&str, &str, &[&str], bool); 4] = [
+    ("Start here", include_str!("../../assets/onboarding/start-here.md"), &["getting-started"], true),
+    ("Notes and links", include_str!("../../assets/onboarding/notes-and-links.md"), &["getting-started"], false),
+    ("Tasks and statuses", include_str!("../../assets/onboarding/tasks-and-statuses.md"), &["getting-started"], false),
+    ("References and citations", include_str!("../../assets/onboarding/references-and-citations.md"), &["getting-started"], false),
+];
+
+/// A sample task as (title, status, subtasks with their statuses)
+type SampleTask = (&'static str, &'static str, &'static [(&'static str, &'static str)]);
+
+/// Tasks of the sample project
+const SAMPLE_TASKS: [SampleTask; 2] = [
+    (
+        "Run the pilot study",
+        "in_progress",
+        &[("Write the consent form", "done"), ("Recruit five participants", "in_progress"), ("Analyse the pilot results", "todo")],
+    ),
+    ("Draft the literature review", "todo", &[("Read the papers in References", "todo")]),
+];
+
+/// First-run experience: the sample project offered to new users
+pub struct OnboardingService;
+
+impl OnboardingService {
+    /// Whether the app is opened for the first time: the first run was never
+    /// completed and there are no projects
+    #[instrument(level = "debug", skip_all)]
+    pub async fn is_first_run(state: &AppState) -> AppResult<bool> {
+        state.run(|conn| {
+            if SettingsService::get_bool(conn, SETTING_FIRST_RUN_DONE)? {
+                return Ok(false);
+            }
+            Ok(DbService::get_all_projects(conn, true, ProjectSort::Recent)?.is_empty())
+        }).await
+    }
+
+    /// Create the "Welcome to Research Vault" project in `dest_dir` with notes
+    /// explaining the app, a small task tree, a reference and a pinned "Start
+    /// here" note, all through the regular services, and mark the first run
+    /// done. It is an ordinary project and is purged like any other.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_sample_project(state: &AppState, dest_dir: String) -> AppResult<Project> {
+        let dest = Path::new(dest_dir.trim());
+        if dest_dir.trim().is_empty() || !dest.is_dir() {
+            return Err(AppError::InvalidInput(format!("'{}' is not a folder", dest_dir)));
+        }
+        let path = (1..=MAX_SAMPLE_FOLDER_ATTEMPTS)
+            .map(|attempt| match attempt {
+                1 => dest.join(SAMPLE_PROJECT_NAME),
+                _ => dest.join(format!("{} {}", SAMPLE_PROJECT_NAME, attempt)),
+            })
+            .find(|path| std::fs::symlink_metadata(path).is_err())
+            .ok_or_else(|| AppError::Conflict(format!("No free folder name for the sample project in {}", dest_dir)))?;
+
+        let project = ProjectService::create_project(state, CreateProjectDto {
+            name: SAMPLE_PROJECT_NAME.to_string(),
+            path: path.to_string_lossy().into_owned(),
+            description: Some("A tour of notes, tasks and references. Delete it whenever you like.".to_string()),
+            tags: Some(vec!["sample".to_string()]),
+            layout: None,
+            template_id: None,
+        }).await?;
+
+        let mut notes = Vec::with_capacity(SAMPLE_NOTES.len());
+        for (title, content, tags, is_pinned) in SAMPLE_NOTES {
+            let note = NoteService::create_note(state, CreateNoteDto {
+                project_id: project.id.clone(),
+                title: title.to_string(),
+                content: content.to_string(),
+                tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+                is_pinned: Some(is_pinned),
+            }).await?;
+            notes.push(note);
+        }
+
+        for (title, status, subtasks) in SAMPLE_TASKS {
+            let parent = TaskService::create_task(state, Self::sample_task(&project.id, None, title, status)).await?;
+            for (title, status) in subtasks {
+                TaskService::create_task(state, Self::sample_task(&project.id, Some(&parent.id), title, status)).await?;
+            }
+        }
+
+        let reference = ReferenceService::create_reference(state, CreateReferenceDto {
+            project_id: project.id.clone(),
+            citation_key: Some("vaswani2017".to_string()),
+            entry_type: Some("inproceedings".to_string()),
+            title: "Attention Is All You Need".to_string(),
+            authors: ["Vaswani, Ashish", "Shazeer, Noam", "Parmar, Niki", "Uszkoreit, Jakob", "Jones, Llion", "Gomez, Aidan N.", "Kaiser, Łukasz", "Polosukhin, Illia"]
+                .iter()
+                .map(|author| author.to_string())
+                .collect(),
+            year: Some(2017),
+            venue: Some("Advances in Neural Information Processing Systems".to_string()),
+            doi: None,
+            url: Some("https://arxiv.org/abs/1706.03762".to_string()),
+            abstract_text: None,
+            pdf_path: None,
+            reading_status: None,
+            tags: None,
+        }).await?;
+        if let Some(note) = notes.iter().find(|note| note.title == "References and citations") {
+            ReferenceService::cite_in_note(state, note.id.clone(), reference.id).await?;
+        }
+
+        state.run(|conn| DbService::with_busy_retry(|| SettingsService::set_bool(conn, SETTING_FIRST_RUN_DONE, true))).await?;
+        Ok(project)
+    }
+
+    fn sample_task(project_id: &str, parent_id: Option<&str>, title: &str, status: &str) -> CreateTaskDto {
+        CreateTaskDto {
+            project_id: project_id.to_string(),
+            parent_id: parent_id.map(str::to_string),
+            title: title.to_string(),
+            description: None,
+            status: Some(status.to_string()),
+            priority: None,
+            due_date: None,
+            order: None,
+            tags: None,
+            recurrence: None,
+            remind_at: None,
+            remind_before: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    #[tokio::test]
+    async fn sample_project_is_built_once_and_deleted_like_any_other() {
+        let state = test_support::open_state();
+        assert!(OnboardingService::is_first_run(&state).await.unwrap());
+
+        let dest = test_support::temp_dir().to_string_lossy().into_owned();
+        let project = OnboardingService::create_sample_project(&state, dest.clone()).await.unwrap();
+        assert!(!OnboardingService::is_first_run(&state).await.unwrap());
+        {
+            let conn = &state.conn().unwrap();
+            let notes = DbService::get_notes_by_project(conn, &project.id).unwrap();
+            assert_eq!(notes.len(), SAMPLE_NOTES.len());
+            let pinned: Vec<&str> = notes.iter().filter(|note| note.is_pinned).map(|note| note.title.as_str()).collect();
+            assert_eq!(pinned, ["Start here"]);
+            let tasks = DbService::get_tasks_by_project(conn, &project.id).unwrap();
+            assert_eq!(tasks.len(), 6);
+            assert_eq!(tasks.iter().filter(|task| task.parent_id.is_some()).count(), 4);
+            assert!(tasks.iter().any(|task| task.status == "done"));
+            assert_eq!(DbService::get_references_by_project(conn, &project.id).unwrap().len(), 1);
+        }
+
+        // A second one goes next to the first
+        let second = OnboardingService::create_sample_project(&state, dest).await.unwrap();
+        assert!(second.path.ends_with("Welcome to Research Vault 2"), "{}", second.path);
+
+        ProjectService::purge_project(&state, project.id.clone(), true).await.unwrap();
+        assert!(!Path::new(&project.path).exists());
+        let conn = &state.conn().unwrap();
+        assert!(DbService::get_project_by_id(conn, &project.id).unwrap().is_none());
+        assert!(DbService::get_notes_by_project(conn, &project.id).unwrap().is_empty());
+        assert!(DbService::get_references_by_project(conn, &project.id).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CleanProjectFilesResult, Note, ProjectFileAction, ProjectFileActionKind, ProjectFileIssue, ProjectFileIssueKind,
+    ProjectFilesReport, SkippedItem,
+};
+use crate::services::export_service::NOTES_EXPORT_DIR;
+use crate::services::note_attachment_service::ATTACHMENTS_DIR;
+use crate::services::{DbService, ExportService, GitService};
+use crate::state::AppState;
+use crate::utils::frontmatter;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tracing::instrument;
+
+/// Compares the files a project's notes were exported and attached to with the database
+pub struct ProjectFilesService;
+
+impl ProjectFilesService {
+    /// Compare the project's notes/ and attachment folders with the database,
+    /// without changing anything. Exported notes are recognised by the `id` in
+    /// their frontmatter; Markdown files without one are the user's own and
+    /// never reported.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn analyze_project_files(state: &AppState, project_id: String) -> AppResult<ProjectFilesReport> {
+        state.blocking(move |state| {
+            let (project_path, notes, attachments) = Self::load(state, &project_id)?;
+            Self::analyze(Path::new(&project_path), &notes, &attachments)
+        }).await
+    }
+
+    /// Carry out the selected actions: delete orphaned files and write stale
+    /// exported notes again. Each action is checked against a fresh analysis, so
+    /// only a file that is still an orphan is deleted and only a stale export is
+    /// rewritten; anything else is skipped with the reason.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn clean_project_files(
+        state: &AppState,
+        project_id: String,
+        actions: Vec<ProjectFileAction>,
+    ) -> AppResult<CleanProjectFilesResult> {
+        state.blocking(move |state| {
+            let (project_path, notes, attachments) = Self::load(state, &project_id)?;
+            let project_dir = Path::new(&project_path);
+            let report = Self::analyze(project_dir, &notes, &attachments)?;
+
+            let orphans: HashSet<&str> = report.orphans.iter().map(|f| f.relative_path.as_str()).collect();
+            let stale: HashMap<&str, &str> = report
+                .stale
+                .iter()
+                .filter_map(|f| Some((f.relative_path.as_str(), f.note_id.as_deref()?)))
+                .collect();
+            let notes: HashMap<&str, &Note> = notes.iter().map(|note| (note.id.as_str(), note)).collect();
+
+            let mut result = CleanProjectFilesResult::default();
+            for action in actions {
+                let relative_path = action.relative_path;
+                let outcome = match action.action {
+                    ProjectFileActionKind::Delete if orphans.contains(relative_path.as_str()) => {
+                        fs::remove_file(project_dir.join(&relative_path)).map(|_| &mut result.deleted)
+                    }
+                    ProjectFileActionKind::Reexport => match stale.get(relative_path.as_str()).and_then(|id| notes.get(id)) {
+                        Some(note) => fs::write(project_dir.join(&relative_path), ExportService::note_markdown(note))
+                            .map(|_| &mut result.reexported),
+                        None => {
+                            result.skipped.push(SkippedItem { id: relative_path, reason: "Not a stale export".into() });
+                            continue;
+                        }
+                    },
+                    ProjectFileActionKind::Delete => {
+                        result.skipped.push(SkippedItem { id: relative_path, reason: "Not an orphaned file".into() });
+                        continue;
+                    }
+                };
+                match outcome {
+                    Ok(done) => done.push(relative_path),
+                    Err(e) => result.skipped.push(SkippedItem { id: relative_path, reason: e.to_string() }),
+                }
+            }
+
+            let changed = result.deleted.len() + result.reexported.len();
+            if changed > 0 {
+                GitService::auto_commit(&project_path, &format!("Clean up {} project files", changed));
+            }
+            Ok(result)
+        }).await
+    }
+
+    fn load(state: &AppState, project_id: &str) -> AppResult<(String, Vec<Note>, Vec<crate::models::NoteAttachment>)> {
+        if project_id.is_empty() {
+            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+        }
+        let conn = &state.conn()?;
+        let project = DbService::get_project_by_id(conn, project_id)?
+            .ok_or_else(|| AppError::NotFound("Project", project_id.to_string()))?;
+        let notes = DbService::get_notes_by_project(conn, project_id)?;
+        let attachments = DbService::get_project_attachments(conn, project_id)?;
+        Ok((project.path, notes, attachments))
+    }
+
+    fn analyze(project_dir: &Path, notes: &[Note], attachments: &[crate::models::NoteAttachment]) -> AppResult<ProjectFilesReport> {
+        let mut report = ProjectFilesReport::default();
+        let notes: HashMap<&str, &Note> = notes.iter().map(|note| (note.id.as_str(), note)).collect();
+
+        for (relative_path, file) in Self::list_files(project_dir, NOTES_EXPORT_DIR)? {
+            if !relative_path.ends_with(".md") {
+                continue;
+            }
+            let Some(note_id) = Self::exported_note_id(&file) else {
+                continue;
+            };
+            match notes.get(note_id.as_str()) {
+                None => report.orphans.push(ProjectFileIssue {
+                    kind: ProjectFileIssueKind::Orphan,
+                    relative_path,
+                    note_id: Some(note_id),
+                }),
+                Some(note) if Self::modified_at(&file).is_some_and(|modified| note.updated_at > modified) => {
+                    report.stale.push(ProjectFileIssue {
+                        kind: ProjectFileIssueKind::Stale,
+                        relative_path,
+                        note_id: Some(note_id),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
+        let attached: HashSet<&str> = attachments.iter().map(|a| a.relative_path.as_str()).collect();
+        for (relative_path, _) in Self::list_files(project_dir, ATTACHMENTS_DIR)? {
+            if !attached.contains(relative_path.as_str()) {
+                report.orphans.push(ProjectFileIssue {
+                    kind: ProjectFileIssueKind::Orphan,
+                    relative_path,
+                    note_id: None,
+                });
+            }
+        }
+        for attachment in attachments {
+            if !project_dir.join(&attachment.relative_path).is_file() {
+                report.missing.push(ProjectFileIssue {
+                    kind: ProjectFileIssueKind::Missing,
+                    relative_path: attachment.relative_path.clone(),
+                    note_id: Some(attachment.note_id.clone()),
+                });
+            }
+        }
+
+        report.orphans.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        report.stale.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        Ok(report)
+    }
+
+    /// Files directly inside a folder of the project, as paths relative to the
+    /// project with forward slashes; none when the folder does not exist
+    fn list_files(project_dir: &Path, folder: &str) -> AppResult<Vec<(String, std::path::PathBuf)>> {
+        let entries = match fs::read_dir(project_dir.join(folder)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                files.push((format!("{}/{}", folder, name), entry.path()));
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// `id` of the frontmatter of an exported note
+    fn exported_note_id(file: &Path) -> Option<String> {
+        let raw = fs::read_to_string(file).ok()?;
+        let (header, _) = frontmatter::split(&raw)?;
+        let fields = frontmatter::parse(header).ok()?;
+        fields.get("id")?.as_str().map(str::to_string)
+    }
+
+    fn modified_at(file: &Path) -> Option<i64> {
+        let modified = fs::metadata(file).ok()?.modified().ok()?;
+        Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NoteAttachment;
+    use crate::services::test_support;
+    use rusqlite::params;
+
+    fn attach(conn: &rusqlite::Connection, note_id: &str, relative_path: &str) {
+        let attachment = NoteAttachment {
+            id: uuid::Uuid::new_v4().to_string(),
+            note_id: note_id.to_string(),
+            relative_path: relative_path.to_string(),
+            original_name: relative_path.rsplit('/').next().unwrap().to_string(),
+            size_bytes: 1,
+            created_at: 0,
+        };
+        DbService::insert_note_attachment(conn, &attachment).unwrap();
+    }
+
+    fn paths(issues: &[ProjectFileIssue]) -> Vec<&str> {
+        issues.iter().map(|f| f.relative_path.as_str()).collect()
+    }
+
+    /// A project with an up-to-date export, a stale export, an export of a
+    /// deleted note, a user's own Markdown file, an unreferenced attachment and
+    /// an attachment row whose file is gone
+    fn setup(state: &AppState) -> (crate::models::Project, Note) {
+        let conn = &state.conn().unwrap();
+        let project = test_support::project(conn, "files");
+        let dir = Path::new(&project.path);
+        fs::create_dir_all(dir.join(NOTES_EXPORT_DIR)).unwrap();
+        fs::create_dir_all(dir.join(ATTACHMENTS_DIR)).unwrap();
+
+        let fresh = test_support::note(conn, &project.id, "Fresh", "current");
+        fs::write(dir.join("notes/fresh.md"), ExportService::note_markdown(&fresh)).unwrap();
+        attach(conn, &fresh.id, "docs/attachments/used.png");
+        fs::write(dir.join("docs/attachments/used.png"), "x").unwrap();
+        attach(conn, &fresh.id, "docs/attachments/gone.png");
+
+        let stale = test_support::note(conn, &project.id, "Stale", "old text");
+        fs::write(dir.join("notes/stale.md"), ExportService::note_markdown(&stale)).unwrap();
+        let later = chrono::Utc::now().timestamp() + 3600;
+        conn.execute(
+            "UPDATE notes SET content = 'new text', updated_at = ?1 WHERE id = ?2",
+            params![later, stale.id],
+        ).unwrap();
+
+        let deleted = test_support::new_note(Some(&project.id), "Deleted", "gone");
+        fs::write(dir.join("notes/deleted.md"), ExportService::note_markdown(&deleted)).unwrap();
+        fs::write(dir.join("notes/mine.md"), "# My own notes\n").unwrap();
+        fs::write(dir.join("docs/attachments/unused.png"), "x").unwrap();
+
+        let stale = DbService::get_note_by_id(conn, &stale.id).unwrap().unwrap();
+        (project, stale)
+    }
+
+    #[tokio::test]
+    async fn analysis_reports_orphans_missing_files_and_stale_exports() {
+        let state = test_support::open_state();
+        let (project, stale) = setup(&state);
+
+        let report = ProjectFilesService::analyze_project_files(&state, project.id.clone()).await.unwrap();
+        assert_eq!(paths(&report.orphans), vec!["docs/attachments/unused.png", "notes/deleted.md"]);
+        assert_eq!(paths(&report.missing), vec!["docs/attachments/gone.png"]);
+        assert_eq!(paths(&report.stale), vec!["notes/stale.md"]);
+        assert_eq!(report.stale[0].note_id.as_deref(), Some(stale.id.as_str()));
+
+        // Analysing changes nothing
+        let dir = Path::new(&project.path);
+        assert!(dir.join("notes/deleted.md").exists());
+        assert!(dir.join("docs/attachments/unused.png").exists());
+    }
+
+    #[tokio::test]
+    async fn cleaning_only_carries_out_the_selected_valid_actions() {
+        let state = test_support::open_state();
+        let (project, _) = setup(&state);
+        let dir = Path::new(&project.path);
+        let action = |action, relative_path: &str| ProjectFileAction { action, relative_path: relative_path.to_string() };
+
+        let result = ProjectFilesService::clean_project_files(&state, project.id.clone(), vec![
+            action(ProjectFileActionKind::Delete, "notes/deleted.md"),
+            action(ProjectFileActionKind::Reexport, "notes/stale.md"),
+            action(ProjectFileActionKind::Delete, "notes/mine.md"),
+            action(ProjectFileActionKind::Delete, "docs/attachments/used.png"),
+            action(ProjectFileActionKind::Reexport, "notes/fresh.md"),
+        ]).await.unwrap();
+
+        assert_eq!(result.deleted, vec!["notes/deleted.md"]);
+        assert_eq!(result.reexported, vec!["notes/stale.md"]);
+        let skipped: Vec<&str> = result.skipped.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(skipped, vec!["notes/mine.md", "docs/attachments/used.png", "notes/fresh.md"]);
+
+        assert!(!dir.join("notes/deleted.md").exists());
+        assert!(dir.join("notes/mine.md").exists());
+        assert!(dir.join("docs/attachments/used.png").exists());
+        // The unselected orphan is left alone
+        assert!(dir.join("docs/attachments/unused.png").exists());
+        assert!(fs::read_to_string(dir.join("notes/stale.md")).unwrap().contains("new text"));
+    }
+}
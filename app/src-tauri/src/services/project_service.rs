@@ -1,132 +1,1289 @@
 use crate::error::{AppError, AppResult};
-use crate::models::{CreateProjectDto, Project, UpdateProjectDto};
-use crate::services::{DbService, GitService};
+use crate::models::{
+    Changed, CreateProjectDto, EntityType, FileDiff, GitCommit, GitStatus, GraphEdge, GraphNode, Project, ProjectDashboard, ProjectFilterDto, ProjectGraph, ProjectSettings, ProjectSort, ProjectStats, ProjectStatus, ProjectSummary, ProjectWithCounts, RevertChangeDto, TitleCollation,
+    UpdateProjectDto, UpdateProjectSettingsDto, SETTING_AUTO_COMMIT_DEFAULT, SETTING_GITIGNORE_TEMPLATE, SETTING_PROJECT_SETTINGS_DEFAULTS,
+};
+use crate::services::{DbService, GitService, ProjectTemplateService, SettingsService, UndoService};
 use crate::state::AppState;
+use crate::utils::validate::{self, Validate};
+use crate::utils::{gitignore, path, research_json, sanitize, text};
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use tracing::instrument;
 use uuid::Uuid;
 
+/// Recent notes shown on the project dashboard
+const DASHBOARD_RECENT_NOTES: i64 = 10;
+
+/// How far ahead the dashboard lists due tasks: 7 days
+const DASHBOARD_DUE_WINDOW_SECS: i64 = 7 * 24 * 3600;
+
+/// Commits returned per history page when no limit is given
+const DEFAULT_HISTORY_PAGE: u32 = 50;
+
+/// Largest history page the UI may request
+const MAX_HISTORY_PAGE: u32 = 500;
+
+/// Tags listed per project in its statistics
+const TOP_TAGS_PER_PROJECT: i64 = 10;
+
+/// Most nodes returned by get_project_graph; the least connected are left out beyond it
+const PROJECT_GRAPH_NODE_LIMIT: usize = 3_000;
+
+/// Remote used by push and pull when none is given
+const DEFAULT_REMOTE: &str = "origin";
+
+/// How far the created_at of research.json may be from the project's own
+/// creation time for relink_project to accept the directory
+const RELINK_CREATED_AT_TOLERANCE_SECS: i64 = 60;
+
 /// Project service for business logic
 pub struct ProjectService;
 
 impl ProjectService {
     /// Create a new project
-    pub async fn create_project(state: &AppState, data: CreateProjectDto) -> AppResult<Project> {
-        // Validate input
-        if data.name.is_empty() {
-            return Err(AppError::InvalidInput("Project name cannot be empty".into()));
-        }
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_project(state: &AppState, mut data: CreateProjectDto) -> AppResult<Project> {
+        state.blocking(move |state| {
+            // Validate input
+            data.validate(&SettingsService::limits(&*state.conn()?)?)?;
+
+            let path = Self::normalize_new_project_path(state, &data.path)?;
+
+            // Check if path already exists
+            if fs::symlink_metadata(&path).is_ok() {
+                return Err(AppError::Conflict(format!("'{}' already exists", path)));
+            }
+
+            let template = match data.template_id.take().filter(|id| !id.is_empty()) {
+                Some(id) => Some(
+                    DbService::get_project_template_by_id(&*state.conn()?, &id)?
+                        .ok_or(AppError::NotFound("Project template", id))?,
+                ),
+                None => None,
+            };
+
+            Self::create_project_directory(state, &path, &data.name, data.description.as_deref(), data.layout.take())?;
+
+            // Generate project model
+            let now = chrono::Utc::now().timestamp();
+            let key_prefix = text::key_prefix_from_name(&data.name);
+            let project = Project {
+                id: Uuid::new_v4().to_string(),
+                name: data.name,
+                path,
+                description: data.description,
+                status: ProjectStatus::Active,
+                created_at: now,
+                last_modified_at: now,
+                tags: data.tags,
+                key_prefix: Some(key_prefix),
+                is_favorite: false,
+                metadata: None,
+            };
+
+            // Save to database, with the template's tasks and notes in the same transaction
+            let conn = &state.conn()?;
+            let inserted = match &template {
+                Some(template) => {
+                    let (mut tasks, notes) = ProjectTemplateService::instantiate(template, &project.id, now);
+                    DbService::with_busy_retry(|| DbService::insert_project_with_content(conn, &project, &mut tasks, &notes))
+                }
+                None => DbService::with_busy_retry(|| DbService::insert_project(conn, &project)),
+            };
+            if let Err(e) = inserted {
+                if let Err(cleanup) = fs::remove_dir_all(&project.path) {
+                    tracing::warn!("Failed to remove {} after a failed create: {}", project.path, cleanup);
+                }
+                return Err(e);
+            }
 
-        if data.path.is_empty() {
+            Ok(project)
+        }).await
+    }
+
+    /// Turn the path typed for a new project into the absolute path to store:
+    /// `~` expanded and the parent canonicalized. The parent must be an existing
+    /// writable directory outside the app data directory and outside every
+    /// registered project.
+    fn normalize_new_project_path(state: &AppState, raw: &str) -> AppResult<String> {
+        let raw = raw.trim();
+        if raw.is_empty() {
             return Err(AppError::InvalidInput("Project path cannot be empty".into()));
         }
 
-        // Check if path already exists
-        if fs::metadata(&data.path).is_ok() {
-            return Err(AppError::Conflict("Project path already exists".into()));
+        let expanded = path::expand_home(raw).ok_or_else(|| {
+            AppError::InvalidInput(format!("Cannot expand '~' in '{}': the home directory is unknown", raw))
+        })?;
+        if !expanded.is_absolute() {
+            return Err(AppError::InvalidInput(format!(
+                "Project path '{}' must be a full path, not one relative to the current folder",
+                raw
+            )));
+        }
+
+        let name = match expanded.components().next_back() {
+            Some(Component::Normal(name)) => name.to_string_lossy().into_owned(),
+            _ => {
+                return Err(AppError::InvalidInput(format!(
+                    "Project path '{}' must end in the name of the new project folder",
+                    raw
+                )))
+            }
+        };
+        if cfg!(windows) && path::is_invalid_windows_name(&name) {
+            return Err(AppError::InvalidInput(format!(
+                "'{}' cannot be used as a folder name on Windows",
+                name
+            )));
+        }
+
+        let parent = expanded.parent().unwrap_or(Path::new(""));
+        let parent = match fs::canonicalize(parent) {
+            Ok(parent) => path::display_form(parent),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(AppError::InvalidInput(format!(
+                    "Parent folder '{}' does not exist",
+                    parent.display()
+                )))
+            }
+            Err(e) => {
+                return Err(AppError::InvalidInput(format!(
+                    "Parent folder '{}' cannot be read: {}",
+                    parent.display(),
+                    e
+                )))
+            }
+        };
+        if !parent.is_dir() {
+            return Err(AppError::InvalidInput(format!("'{}' is not a folder", parent.display())));
+        }
+        Self::check_writable(&parent)?;
+
+        let normalized = parent.join(&name);
+        if let Some(app_data) = sanitize::app_data_dir() {
+            let app_data = fs::canonicalize(app_data).map(path::display_form).unwrap_or_else(|_| PathBuf::from(app_data));
+            if path::is_within(&normalized, &app_data) {
+                return Err(AppError::InvalidInput(format!(
+                    "Projects cannot be created inside the app's data folder '{}'",
+                    app_data.display()
+                )));
+            }
+        }
+
+        let conn = &state.conn()?;
+        for project in DbService::get_all_projects(conn, true, ProjectSort::Recent)? {
+            let existing = Path::new(&project.path);
+            let existing = fs::canonicalize(existing).map(path::display_form).unwrap_or_else(|_| existing.to_path_buf());
+            if path::is_within(&normalized, &existing) {
+                return Err(AppError::InvalidInput(format!(
+                    "'{}' is inside the project '{}' at '{}'",
+                    normalized.display(),
+                    project.name,
+                    existing.display()
+                )));
+            }
+        }
+
+        Ok(normalized.to_string_lossy().into_owned())
+    }
+
+    /// Check that files can be created in `dir` by creating and removing one
+    fn check_writable(dir: &Path) -> AppResult<()> {
+        let probe = dir.join(format!(".research-vault-{}.tmp", Uuid::new_v4()));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+            Ok(_) => {
+                let _ = fs::remove_file(&probe);
+                Ok(())
+            }
+            Err(e) => Err(AppError::InvalidInput(format!(
+                "Parent folder '{}' is not writable: {}",
+                dir.display(),
+                e
+            ))),
         }
+    }
+
+    /// Lay out a new project directory: the subdirectories of `layout`, or of the
+    /// project_layout setting when it is None, a git repository, a .gitignore,
+    /// research.json recording the layout and an initial commit
+    pub(crate) fn create_project_directory(
+        state: &AppState,
+        path: &str,
+        name: &str,
+        description: Option<&str>,
+        layout: Option<Vec<String>>,
+    ) -> AppResult<()> {
+        let template = Self::gitignore_template(state)?;
+        let mut metadata = research_json::new_metadata(name, description, &chrono::Utc::now().to_rfc3339());
+        let layout = {
+            let conn = &state.conn()?;
+            Self::apply_settings_defaults(conn, &mut metadata)?;
+            match layout {
+                Some(layout) => layout,
+                None => SettingsService::project_layout(conn)?,
+            }
+        };
 
         // Create project directory
-        fs::create_dir_all(&data.path)
-            .map_err(|e| AppError::FileSystem(e))?;
+        fs::create_dir_all(path)
+            .map_err(AppError::FileSystem)?;
 
-        // Create default subdirectories
-        fs::create_dir_all(format!("{}/docs", data.path))?;
-        fs::create_dir_all(format!("{}/data", data.path))?;
-        fs::create_dir_all(format!("{}/notes", data.path))?;
+        // Create the layout's subdirectories
+        for dir in &layout {
+            fs::create_dir_all(Path::new(path).join(dir))?;
+        }
 
-        // Initialize git repository
-        GitService::init(&data.path)?;
+        // Initialize git repository, keeping data/ and junk files out of it
+        GitService::init(path)?;
+        gitignore::write_managed_block(path, &template, &[])?;
 
         // Create research.json metadata
-        let now = chrono::Utc::now().timestamp();
-        let metadata = serde_json::json!({
-            "version": "1.0.0",
-            "title": data.name,
-            "description": data.description,
-            "created_at": chrono::Utc::now().to_rfc3339(),
-            "updated_at": chrono::Utc::now().to_rfc3339(),
-            "settings": {
-                "auto_commit": true,
-                "backup_enabled": true
-            }
-        });
-
-        let metadata_path = format!("{}/research.json", data.path);
-        fs::write(metadata_path, serde_json::to_string_pretty(&metadata)?)
-            .map_err(|e| AppError::FileSystem(e))?;
-
-        // Generate project model
-        let project = Project {
-            id: Uuid::new_v4().to_string(),
-            name: data.name,
-            path: data.path,
-            description: data.description,
-            status: "active".to_string(),
-            created_at: now,
-            last_modified_at: now,
-            tags: data.tags,
-        };
+        metadata.insert(research_json::LAYOUT_KEY.to_string(), Value::from(layout));
+        research_json::write(path, &metadata)?;
 
-        // Save to database
-        let db = state.db.lock().map_err(|_| AppError::System("Failed to lock database".into()))?;
-        if let Some(conn) = db.as_ref() {
-            DbService::insert_project(conn, &project)?;
-        } else {
-            return Err(AppError::System("Database not initialized".into()));
+        // Leave the repository with a HEAD; a failed commit does not fail the project
+        if let Err(e) = GitService::add_all(path)
+            .and_then(|_| GitService::commit(path, "Initialize research project"))
+        {
+            tracing::warn!("Initial commit in {} failed: {}", path, e);
         }
 
-        Ok(project)
+        Ok(())
     }
 
-    /// Get all projects
-    pub async fn list_projects(state: &AppState) -> AppResult<Vec<Project>> {
-        let db = state.db.lock().map_err(|_| AppError::System("Failed to lock database".into()))?;
-        if let Some(conn) = db.as_ref() {
-            DbService::get_all_projects(conn)
-        } else {
-            Err(AppError::System("Database not initialized".into()))
+    /// Lay the settings of new projects over the built-in ones in `metadata`: the
+    /// project_settings_defaults setting, then auto_commit from auto_commit_default
+    fn apply_settings_defaults(conn: &Connection, metadata: &mut Map<String, Value>) -> AppResult<()> {
+        let mut settings = metadata
+            .get("settings")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        if let Value::Object(defaults) = SettingsService::get_value(conn, SETTING_PROJECT_SETTINGS_DEFAULTS)? {
+            settings.extend(defaults);
         }
+        settings.insert(
+            "auto_commit".to_string(),
+            Value::Bool(SettingsService::get_bool(conn, SETTING_AUTO_COMMIT_DEFAULT)?),
+        );
+        metadata.insert("settings".to_string(), Value::Object(settings));
+        Ok(())
+    }
+
+    /// Create the subdirectories of a project's layout that are missing, leaving
+    /// existing folders and files alone. The layout is the one recorded in its
+    /// research.json, or the project_layout setting, which is then recorded.
+    /// Returns the subdirectories created.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn apply_layout_to_project(state: &AppState, project_id: String) -> AppResult<Vec<String>> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, project_id)?;
+            let root = Path::new(&path);
+            if !root.is_dir() {
+                return Err(AppError::NotFound("Project directory", path));
+            }
+
+            let metadata = match research_json::read(&path) {
+                Ok(metadata) => Some(metadata),
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => return Err(Self::metadata_error(e)),
+            };
+            let recorded = metadata.as_ref().and_then(research_json::layout);
+            let record = recorded.is_none();
+            let mut layout = match recorded {
+                Some(layout) => layout,
+                None => SettingsService::project_layout(&*state.conn()?)?,
+            };
+            validate::layout(research_json::LAYOUT_KEY, &mut layout)?;
+
+            // Refuse before creating anything when a file stands where a folder goes
+            for dir in &layout {
+                let blocked = Path::new(dir)
+                    .ancestors()
+                    .filter(|part| !part.as_os_str().is_empty())
+                    .map(|part| root.join(part))
+                    .find(|target| target.exists() && !target.is_dir());
+                if let Some(target) = blocked {
+                    return Err(AppError::Conflict(format!(
+                        "'{}' is a file, so the folder '{}' cannot be created",
+                        target.display(),
+                        dir
+                    )));
+                }
+            }
+
+            let mut created = Vec::new();
+            for dir in &layout {
+                let target = root.join(dir);
+                if !target.is_dir() {
+                    fs::create_dir_all(&target)?;
+                    created.push(dir.clone());
+                }
+            }
+
+            if let Some(mut metadata) = metadata.filter(|_| record) {
+                metadata.insert(research_json::LAYOUT_KEY.to_string(), Value::from(layout));
+                research_json::write(&path, &metadata)?;
+                GitService::auto_commit(&path, "Record project layout");
+            }
+            Ok(created)
+        }).await
+    }
+
+    /// Rewrite the app-managed block of a project's .gitignore from the current
+    /// template plus `extra_patterns`, keeping everything outside the block and the
+    /// extra patterns of earlier calls. Returns the new file contents.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn regenerate_gitignore(
+        state: &AppState,
+        project_id: String,
+        extra_patterns: Vec<String>,
+    ) -> AppResult<String> {
+        state.blocking(move |state| {
+            if let Some(pattern) = extra_patterns.iter().find(|p| p.contains('\n') || p.contains('\r')) {
+                return Err(AppError::InvalidInput(format!("Pattern '{}' spans several lines", pattern.trim())));
+            }
+
+            let template = Self::gitignore_template(state)?;
+            let path = Self::project_path(state, project_id)?;
+            let contents = gitignore::write_managed_block(&path, &template, &extra_patterns)?;
+            GitService::auto_commit(&path, "Update .gitignore");
+            Ok(contents)
+        }).await
+    }
+
+    /// The gitignore_template setting, or the built-in template when it is empty
+    fn gitignore_template(state: &AppState) -> AppResult<String> {
+        let conn = &state.conn()?;
+        let template = SettingsService::get_string(conn, SETTING_GITIGNORE_TEMPLATE)?;
+        Ok(if template.trim().is_empty() {
+            gitignore::DEFAULT_TEMPLATE.to_string()
+        } else {
+            template
+        })
+    }
+
+    /// Get all projects; archived ones only when `include_archived` is set
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_projects(
+        state: &AppState,
+        include_archived: Option<bool>,
+        sort: Option<ProjectSort>,
+    ) -> AppResult<Vec<Project>> {
+        state.run(move |conn| {
+            DbService::get_all_projects(conn, include_archived.unwrap_or(false), sort.unwrap_or_default())
+        }).await
+    }
+
+    /// Get all projects with their task and note counts, for the project list
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_projects_with_counts(
+        state: &AppState,
+        include_archived: Option<bool>,
+        sort: Option<ProjectSort>,
+    ) -> AppResult<Vec<ProjectWithCounts>> {
+        state.run(move |conn| {
+            DbService::get_all_projects_with_counts(
+                conn,
+                include_archived.unwrap_or(false),
+                sort.unwrap_or_default(),
+                chrono::Utc::now().timestamp(),
+            )
+        }).await
+    }
+
+    /// Get all projects sorted by name
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_projects_by_name(state: &AppState, collation: Option<TitleCollation>) -> AppResult<Vec<Project>> {
+        state.run(move |conn| {
+            DbService::get_projects_by_name(conn, collation.unwrap_or_default())
+        }).await
+    }
+
+    /// Filter projects by status and tags
+    #[instrument(level = "debug", skip_all)]
+    pub async fn filter_projects(state: &AppState, filter: ProjectFilterDto) -> AppResult<Vec<Project>> {
+        state.run(move |conn| {
+            DbService::filter_projects(conn, &filter)
+        }).await
     }
 
     /// Get project by ID
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
     pub async fn get_project(state: &AppState, id: String) -> AppResult<Project> {
-        let db = state.db.lock().map_err(|_| AppError::System("Failed to lock database".into()))?;
-        if let Some(conn) = db.as_ref() {
+        state.run(move |conn| {
             let project = DbService::get_project_by_id(conn, &id)?;
-            project.ok_or_else(|| AppError::NotFound("Project", id))
-        } else {
-            Err(AppError::System("Database not initialized".into()))
-        }
+            project.ok_or(AppError::NotFound("Project", id))
+        }).await
     }
 
-    /// Update project
+    /// Update project. With `expected_updated_at`, an outdated version is
+    /// rejected with the stored project.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
     pub async fn update_project(state: &AppState, id: String, data: UpdateProjectDto) -> AppResult<Project> {
-        let db = state.db.lock().map_err(|_| AppError::System("Failed to lock database".into()))?;
-        if let Some(conn) = db.as_ref() {
-            DbService::update_project(
-                conn, 
-                &id, 
-                data.name.as_deref(), 
-                data.description.as_deref(), 
-                data.status.as_deref(), 
-                data.tags.as_ref()
-            )?;
-            
-            // Return updated project
-            let project = DbService::get_project_by_id(conn, &id)?;
-            project.ok_or_else(|| AppError::NotFound("Project", id))
-        } else {
-            Err(AppError::System("Database not initialized".into()))
-        }
+        Self::update_project_v2(state, id, data).await.map(|change| change.after)
+    }
+
+    /// Update project like `update_project`, returning it as it was before and after the update
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_project_v2(state: &AppState, id: String, mut data: UpdateProjectDto) -> AppResult<Changed<Project>> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            let prefix = match data.key_prefix.as_deref() {
+                Some(prefix) => {
+                    let prefix = prefix.trim().to_ascii_uppercase();
+                    if !text::is_valid_key_prefix(&prefix) {
+                        return Err(AppError::InvalidInput(format!(
+                            "Invalid key prefix '{}': use 1-{} letters or digits, starting with a letter",
+                            prefix,
+                            text::MAX_KEY_PREFIX_LEN
+                        )));
+                    }
+                    Some(prefix)
+                }
+                None => None,
+            };
+
+            // Every change and the returned row come from one transaction, so a
+            // concurrent delete cannot leave the update half done or unreadable
+            DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                let before = DbService::get_project_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Project", id.clone()))?;
+                if data.is_favorite == Some(true) && data.status.unwrap_or(before.status) == ProjectStatus::Archived {
+                    return Err(AppError::Conflict(format!("Archived project '{}' cannot be a favorite", before.name)));
+                }
+
+                if let Some(prefix) = prefix.as_deref() {
+                    DbService::set_project_key_prefix(tx, &id, prefix)?;
+                }
+                if !DbService::update_project(
+                    tx,
+                    &id,
+                    data.name.as_deref(),
+                    data.description.as_deref(),
+                    data.status,
+                    data.tags.as_ref(),
+                    data.expected_updated_at,
+                )? {
+                    return Err(AppError::edit_conflict("Project", &id, &before));
+                }
+                if let Some(favorite) = data.is_favorite {
+                    DbService::set_project_favorite(tx, &id, favorite)?;
+                }
+
+                let after = DbService::get_project_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Project", id.clone()))?;
+                Ok(Changed::new(before, after))
+            }))
+        }).await
+    }
+
+    /// Undo an update by writing back the project as it was before, unless it
+    /// was updated again since. Its path is never changed by an undo.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn revert_project(state: &AppState, id: String, payload: RevertChangeDto) -> AppResult<Project> {
+        state.run(move |conn| {
+            let before: Project = UndoService::decode(EntityType::Project, &id, payload.before)?;
+            if before.name.trim().is_empty() {
+                return Err(AppError::InvalidInput("Project name cannot be empty".into()));
+            }
+            if let Some(prefix) = before.key_prefix.as_deref() {
+                if !text::is_valid_key_prefix(prefix) {
+                    return Err(AppError::InvalidInput(format!("Invalid key prefix '{}'", prefix)));
+                }
+            }
+
+            DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                let current = DbService::get_project_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Project", id.clone()))?;
+                UndoService::ensure_unchanged(EntityType::Project, &id, current.last_modified_at, payload.updated_at)?;
+                DbService::restore_project_version(tx, &before)?;
+                DbService::get_project_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Project", id.clone()))
+            }))
+        }).await
     }
 
     /// Delete project
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
     pub async fn delete_project(state: &AppState, id: String) -> AppResult<()> {
-        let db = state.db.lock().map_err(|_| AppError::System("Failed to lock database".into()))?;
-        if let Some(conn) = db.as_ref() {
-            DbService::delete_project(conn, &id)
-        } else {
-            Err(AppError::System("Database not initialized".into()))
+        state.run(move |conn| {
+            DbService::with_busy_retry(|| DbService::delete_project(conn, &id))
+        }).await
+    }
+
+    /// Make a project a favorite or stop it being one; archived projects cannot be favorites
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn toggle_favorite(state: &AppState, id: String) -> AppResult<Project> {
+        state.run(move |conn| {
+            let project = DbService::get_project_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Project", id.clone()))?;
+            if !project.is_favorite && project.status == ProjectStatus::Archived {
+                return Err(AppError::Conflict(format!("Archived project '{}' cannot be a favorite", project.name)));
+            }
+
+            DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                if !DbService::set_project_favorite(tx, &id, !project.is_favorite)? {
+                    return Err(AppError::NotFound("Project", id.clone()));
+                }
+                DbService::get_project_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Project", id.clone()))
+            }))
+        }).await
+    }
+
+    /// Move a favorite project to `position` among the favorites, returning them in their new order
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn reorder_favorite(state: &AppState, id: String, position: usize) -> AppResult<Vec<Project>> {
+        state.run(move |conn| {
+            match DbService::with_busy_retry(|| DbService::reorder_favorite_project(conn, &id, position))? {
+                None => Err(AppError::NotFound("Project", id)),
+                Some(false) => Err(AppError::Conflict("Only favorite projects can be reordered".into())),
+                Some(true) => DbService::get_favorite_projects(conn),
+            }
+        }).await
+    }
+
+    /// Bring an archived project back to active
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn restore_project(state: &AppState, id: String) -> AppResult<Project> {
+        state.run(move |conn| {
+            let project = DbService::get_project_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Project", id.clone()))?;
+            if project.status != ProjectStatus::Archived {
+                return Err(AppError::Conflict(format!("Project '{}' is not archived", project.name)));
+            }
+
+            DbService::with_busy_retry(|| DbService::restore_project(conn, &id))?;
+            DbService::get_project_by_id(conn, &id)?
+                .ok_or(AppError::NotFound("Project", id))
+        }).await
+    }
+
+    /// Permanently delete a project and everything recorded for it,
+    /// optionally removing its directory from disk as well
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn purge_project(state: &AppState, id: String, delete_files: bool) -> AppResult<()> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, id.clone())?;
+
+            // Check the directory before the row is gone, so a suspicious path fails the whole purge
+            let removable = if delete_files {
+                Self::removable_project_dir(&path)?
+            } else {
+                None
+            };
+
+            {
+                let conn = &state.conn()?;
+                if !DbService::with_busy_retry(|| DbService::purge_project(conn, &id))? {
+                    return Err(AppError::NotFound("Project", id));
+                }
+            }
+
+            if let Some(dir) = removable {
+                fs::remove_dir_all(dir)?;
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Resolve a recorded project path to a directory that is safe to delete.
+    /// Returns None when the directory no longer exists. Refuses symlinks, the
+    /// filesystem root, the home directory and anything without a research.json,
+    /// so a corrupt row can never point the purge at an unrelated tree.
+    fn removable_project_dir(path: &str) -> AppResult<Option<PathBuf>> {
+        if path.trim().is_empty() {
+            return Err(AppError::PermissionDenied("Project has no recorded directory".into()));
+        }
+
+        let recorded = Path::new(path);
+        let link_metadata = match fs::symlink_metadata(recorded) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if link_metadata.file_type().is_symlink() || !link_metadata.is_dir() {
+            return Err(AppError::PermissionDenied(format!("'{}' is not a project directory", path)));
+        }
+
+        let dir = fs::canonicalize(recorded)?;
+        let is_home = std::env::var_os("HOME")
+            .and_then(|home| fs::canonicalize(home).ok())
+            .is_some_and(|home| home == dir);
+        if dir.parent().is_none() || is_home {
+            return Err(AppError::PermissionDenied(format!("Refusing to delete '{}'", dir.display())));
+        }
+        if !dir.join(research_json::FILE_NAME).is_file() {
+            return Err(AppError::PermissionDenied(format!(
+                "'{}' has no {} and does not look like a project directory",
+                dir.display(),
+                research_json::FILE_NAME
+            )));
+        }
+
+        Ok(Some(dir))
+    }
+
+    /// Move a project's directory to `new_path` and record the new location. The
+    /// directory is renamed, or copied and then removed when the rename crosses
+    /// filesystems. When the old directory is already gone this is a relink.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn move_project(state: &AppState, id: String, new_path: String) -> AppResult<Project> {
+        state.blocking(move |state| {
+            let project = Self::relocation_target(state, &id, &new_path)?;
+            if project.path == new_path {
+                return Ok(project);
+            }
+
+            let old = Path::new(&project.path);
+            if !old.is_dir() {
+                Self::check_project_dir(&project, &new_path)?;
+                return Self::save_project_path(state, &id, &new_path);
+            }
+
+            let new = Path::new(&new_path);
+            if fs::symlink_metadata(new).is_ok() {
+                return Err(AppError::Conflict(format!("'{}' already exists", new_path)));
+            }
+            if new.starts_with(old) {
+                return Err(AppError::InvalidInput("A project cannot be moved into its own directory".into()));
+            }
+            if let Some(parent) = new.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let copied = match fs::rename(old, new) {
+                Ok(()) => false,
+                Err(e) => {
+                    tracing::info!("Renaming {} failed ({}); copying instead", project.path, e);
+                    if let Err(e) = Self::copy_dir_all(old, new) {
+                        let _ = fs::remove_dir_all(new);
+                        return Err(e.into());
+                    }
+                    true
+                }
+            };
+
+            let saved = Self::save_project_path(state, &id, &new_path);
+            match (&saved, copied) {
+                (Ok(_), true) => {
+                    if let Err(e) = fs::remove_dir_all(old) {
+                        tracing::warn!("Moved project copied, but removing {} failed: {}", project.path, e);
+                    }
+                }
+                (Err(_), true) => {
+                    let _ = fs::remove_dir_all(new);
+                }
+                (Err(_), false) => {
+                    if let Err(e) = fs::rename(new, old) {
+                        tracing::error!("Moving {} back to {} failed: {}", new_path, project.path, e);
+                    }
+                }
+                (Ok(_), false) => {}
+            }
+            saved
+        }).await
+    }
+
+    /// Point a project at a directory the user already moved it to, after checking
+    /// that the directory holds this project
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn relink_project(state: &AppState, id: String, existing_path: String) -> AppResult<Project> {
+        state.blocking(move |state| {
+            let project = Self::relocation_target(state, &id, &existing_path)?;
+            if project.path == existing_path {
+                return Ok(project);
+            }
+
+            Self::check_project_dir(&project, &existing_path)?;
+            Self::save_project_path(state, &id, &existing_path)
+        }).await
+    }
+
+    /// Validate a new location for a project and load the project. The path must be
+    /// absolute and not registered to another project.
+    fn relocation_target(state: &AppState, id: &str, path: &str) -> AppResult<Project> {
+        if path.trim().is_empty() {
+            return Err(AppError::InvalidInput("Project path cannot be empty".into()));
+        }
+        if !Path::new(path).is_absolute() {
+            return Err(AppError::InvalidInput("Project path must be absolute".into()));
+        }
+
+        let conn = &state.conn()?;
+        let project = DbService::get_project_by_id(conn, id)?
+            .ok_or_else(|| AppError::NotFound("Project", id.to_string()))?;
+        if project.path != path && DbService::project_path_exists(conn, path)? {
+            return Err(AppError::Conflict("A project with this path already exists".into()));
+        }
+        Ok(project)
+    }
+
+    /// Check that `path` is a directory holding `project`: its research.json has the
+    /// project's title or creation time, or, without one, it is a git repository
+    /// with the default project layout
+    fn check_project_dir(project: &Project, path: &str) -> AppResult<()> {
+        let dir = Path::new(path);
+        if !dir.is_dir() {
+            return Err(AppError::NotFound("Project directory", path.to_string()));
+        }
+
+        match research_json::read(path) {
+            Ok(metadata) => {
+                let title = metadata.get("title").and_then(Value::as_str).unwrap_or_default();
+                let created_at = metadata
+                    .get("created_at")
+                    .and_then(Value::as_str)
+                    .and_then(|created| chrono::DateTime::parse_from_rfc3339(created).ok())
+                    .map(|created| created.timestamp());
+                let same_title = title.trim().eq_ignore_ascii_case(project.name.trim());
+                let same_creation = created_at
+                    .is_some_and(|created| (created - project.created_at).abs() <= RELINK_CREATED_AT_TOLERANCE_SECS);
+                if same_title || same_creation {
+                    Ok(())
+                } else {
+                    Err(AppError::Conflict(format!(
+                        "'{}' holds the project '{}', not '{}'",
+                        path, title, project.name
+                    )))
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let looks_like_project = dir.join(".git").exists()
+                    && ["docs", "data", "notes"].iter().any(|sub| dir.join(sub).is_dir());
+                if looks_like_project {
+                    Ok(())
+                } else {
+                    Err(AppError::InvalidInput(format!(
+                        "'{}' has no {} and does not look like a project directory",
+                        path,
+                        research_json::FILE_NAME
+                    )))
+                }
+            }
+            Err(e) => Err(Self::metadata_error(e)),
+        }
+    }
+
+    fn save_project_path(state: &AppState, id: &str, path: &str) -> AppResult<Project> {
+        let conn = &state.conn()?;
+        if !DbService::with_busy_retry(|| DbService::update_project_path(conn, id, path))? {
+            return Err(AppError::NotFound("Project", id.to_string()));
+        }
+        DbService::get_project_by_id(conn, id)?
+            .ok_or_else(|| AppError::NotFound("Project", id.to_string()))
+    }
+
+    /// Copy a directory tree, recreating symlinks instead of following them
+    fn copy_dir_all(from: &Path, to: &Path) -> std::io::Result<()> {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let target = to.join(entry.file_name());
+            if file_type.is_dir() {
+                Self::copy_dir_all(&entry.path(), &target)?;
+            } else if file_type.is_symlink() {
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(fs::read_link(entry.path())?, &target)?;
+                #[cfg(not(unix))]
+                fs::copy(entry.path(), &target).map(|_| ())?;
+            } else {
+                fs::copy(entry.path(), &target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get a project with task, note and research question counts
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_project_summary(state: &AppState, id: String) -> AppResult<ProjectSummary> {
+        state.run(move |conn| {
+            let project = DbService::get_project_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Project", id.clone()))?;
+
+            Ok(ProjectSummary {
+                task_counts: DbService::count_tasks_by_status(conn, &id)?,
+                note_count: DbService::count_notes(conn, &id)?,
+                question_counts: DbService::count_research_questions_by_status(conn, &id)?,
+                project,
+            })
+        }).await
+    }
+
+    /// Get the pinned and recent notes, the tasks due this week and in progress,
+    /// and the statistics of a project. Everything is read in one transaction on
+    /// one connection, so the parts agree with each other.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_project_dashboard(state: &AppState, project_id: String) -> AppResult<ProjectDashboard> {
+        state.run(move |conn| {
+            DbService::with_tx(conn, |tx| {
+                let project = DbService::get_project_by_id(tx, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                let now = chrono::Utc::now().timestamp();
+                let stats = DbService::get_project_stats(tx, Some(&project_id), now, TOP_TAGS_PER_PROJECT)?
+                    .remove(&project_id)
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+
+                Ok(ProjectDashboard {
+                    pinned_notes: DbService::get_pinned_notes(tx, &project_id, true)?,
+                    recent_notes: DbService::get_recent_notes(tx, &project_id, DASHBOARD_RECENT_NOTES)?,
+                    due_soon: DbService::get_project_due_tasks(tx, &project_id, now, now + DASHBOARD_DUE_WINDOW_SECS)?,
+                    in_progress: DbService::get_tasks_by_status(tx, &project_id, "in_progress")?,
+                    stats,
+                    project,
+                })
+            })
+        }).await
+    }
+
+    /// Get the notes, references and optionally tasks of a project with the links,
+    /// citations, shared research questions and subtasks between them. Nodes
+    /// without edges are left out unless `include_orphans` is set.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_project_graph(
+        state: &AppState,
+        project_id: String,
+        include_tasks: bool,
+        include_orphans: bool,
+    ) -> AppResult<ProjectGraph> {
+        state.run(move |conn| {
+            DbService::with_tx(conn, |tx| {
+                if DbService::get_project_by_id(tx, &project_id)?.is_none() {
+                    return Err(AppError::NotFound("Project", project_id.clone()));
+                }
+                let (nodes, edges) = DbService::get_project_graph(tx, &project_id, include_tasks)?;
+                Ok(Self::build_graph(nodes, edges, include_orphans, PROJECT_GRAPH_NODE_LIMIT))
+            })
+        }).await
+    }
+
+    /// Drop edges to nodes that are not listed, then orphans unless they are
+    /// wanted, then the least connected nodes beyond `limit` with their edges
+    fn build_graph(mut nodes: Vec<GraphNode>, mut edges: Vec<GraphEdge>, include_orphans: bool, limit: usize) -> ProjectGraph {
+        let ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+        edges.retain(|e| ids.contains(e.from.as_str()) && ids.contains(e.to.as_str()));
+
+        let mut degree: HashMap<String, usize> = HashMap::new();
+        for edge in &edges {
+            *degree.entry(edge.from.clone()).or_default() += 1;
+            *degree.entry(edge.to.clone()).or_default() += 1;
+        }
+        if !include_orphans {
+            nodes.retain(|n| degree.contains_key(&n.id));
         }
+
+        let truncated = nodes.len() > limit;
+        if truncated {
+            nodes.sort_by_key(|n| std::cmp::Reverse(degree.get(&n.id).copied().unwrap_or(0)));
+            nodes.truncate(limit);
+            let kept: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+            edges.retain(|e| kept.contains(e.from.as_str()) && kept.contains(e.to.as_str()));
+        }
+
+        ProjectGraph { nodes, edges, truncated }
+    }
+
+    /// Get task, note and tag statistics of a project
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_project_stats(state: &AppState, project_id: String) -> AppResult<ProjectStats> {
+        state.run(move |conn| {
+            let now = chrono::Utc::now().timestamp();
+            DbService::get_project_stats(conn, Some(&project_id), now, TOP_TAGS_PER_PROJECT)?
+                .remove(&project_id)
+                .ok_or(AppError::NotFound("Project", project_id))
+        }).await
+    }
+
+    /// Get statistics of every project, keyed by project id
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_all_project_stats(state: &AppState) -> AppResult<HashMap<String, ProjectStats>> {
+        let now = chrono::Utc::now().timestamp();
+        state.run(move |conn| DbService::get_project_stats(conn, None, now, TOP_TAGS_PER_PROJECT)).await
+    }
+
+    /// Get the git working tree status of a project directory
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_git_status(state: &AppState, project_id: String) -> AppResult<GitStatus> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, project_id)?;
+            GitService::status(&path)
+        }).await
+    }
+
+    /// Get a page of a project's commit history, newest first
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_history(
+        state: &AppState,
+        project_id: String,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> AppResult<Vec<GitCommit>> {
+        state.blocking(move |state| {
+            let limit = limit.unwrap_or(DEFAULT_HISTORY_PAGE);
+            if limit == 0 || limit > MAX_HISTORY_PAGE {
+                return Err(AppError::InvalidInput(format!(
+                    "Limit must be between 1 and {}",
+                    MAX_HISTORY_PAGE
+                )));
+            }
+
+            let path = Self::project_path(state, project_id)?;
+            GitService::log(&path, limit, offset.unwrap_or(0))
+        }).await
+    }
+
+    /// Diff a file of a project's working copy against a commit, HEAD by default
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_file_diff(
+        state: &AppState,
+        project_id: String,
+        relative_path: String,
+        commit: Option<String>,
+    ) -> AppResult<FileDiff> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, project_id)?;
+            let commit = commit.filter(|c| !c.trim().is_empty());
+            GitService::diff_file(&path, &relative_path, commit.as_deref().unwrap_or("HEAD"), None)
+        }).await
+    }
+
+    /// Add a git remote to a project repository, or change its URL
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn set_remote(state: &AppState, project_id: String, name: String, url: String) -> AppResult<()> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, project_id)?;
+            GitService::set_remote(&path, name.trim(), url.trim())
+        }).await
+    }
+
+    /// Push a branch of a project repository, by default the current branch to "origin"
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn push(
+        state: &AppState,
+        project_id: String,
+        remote: Option<String>,
+        branch: Option<String>,
+    ) -> AppResult<GitStatus> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, project_id)?;
+            let branch = Self::branch_or_current(&path, branch)?;
+            GitService::push(&path, remote.as_deref().unwrap_or(DEFAULT_REMOTE), &branch)?;
+            GitService::status(&path)
+        }).await
+    }
+
+    /// Fast-forward a project repository from a remote branch, by default the current
+    /// branch from "origin". Uncommitted changes are refused unless `stash` is set.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn pull(
+        state: &AppState,
+        project_id: String,
+        remote: Option<String>,
+        branch: Option<String>,
+        stash: bool,
+    ) -> AppResult<GitStatus> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, project_id)?;
+            let branch = Self::branch_or_current(&path, branch)?;
+            GitService::pull(&path, remote.as_deref().unwrap_or(DEFAULT_REMOTE), &branch, stash)?;
+            GitService::status(&path)
+        }).await
+    }
+
+    /// The given branch, or the checked-out one
+    fn branch_or_current(path: &str, branch: Option<String>) -> AppResult<String> {
+        match branch.filter(|b| !b.trim().is_empty()) {
+            Some(branch) => Ok(branch),
+            None => GitService::status(path)?
+                .branch
+                .ok_or_else(|| AppError::InvalidInput("No branch is checked out; name the branch to use".into())),
+        }
+    }
+
+    /// Look up a project's directory, releasing the database before any git work
+    fn project_path(state: &AppState, project_id: String) -> AppResult<String> {
+        let conn = &state.conn()?;
+        let project = DbService::get_project_by_id(conn, &project_id)?
+            .ok_or(AppError::NotFound("Project", project_id))?;
+        Ok(project.path)
+    }
+
+    /// Read the settings from a project's research.json
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_project_settings(state: &AppState, project_id: String) -> AppResult<ProjectSettings> {
+        state.blocking(move |state| {
+            let path = Self::project_path(state, project_id)?;
+            let metadata = research_json::read(&path).map_err(Self::metadata_error)?;
+            Self::settings_from(&metadata)
+        }).await
+    }
+
+    /// Merge settings changes into a project's research.json and stamp its updated_at
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn update_project_settings(
+        state: &AppState,
+        project_id: String,
+        changes: UpdateProjectSettingsDto,
+    ) -> AppResult<ProjectSettings> {
+        state.blocking(move |state| {
+            let mut values = Map::new();
+            if let Some(auto_commit) = changes.auto_commit {
+                values.insert("auto_commit".to_string(), Value::Bool(auto_commit));
+            }
+            if let Some(backup_enabled) = changes.backup_enabled {
+                values.insert("backup_enabled".to_string(), Value::Bool(backup_enabled));
+            }
+
+            let path = Self::project_path(state, project_id)?;
+            let settings = research_json::update_settings(&path, values).map_err(Self::metadata_error)?;
+            GitService::auto_commit(&path, "Update project settings");
+
+            serde_json::from_value(Value::Object(settings))
+                .map_err(|e| AppError::InvalidProjectMetadata(format!("settings: {}", e)))
+        }).await
+    }
+
+    /// Rewrite a project's research.json from its database row. Settings that can
+    /// still be read are kept; otherwise the defaults are written.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn repair_project_metadata(state: &AppState, project_id: String) -> AppResult<ProjectSettings> {
+        state.blocking(move |state| {
+            let project = {
+                let conn = &state.conn()?;
+                DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or(AppError::NotFound("Project", project_id))?
+            };
+            if !Path::new(&project.path).is_dir() {
+                return Err(AppError::NotFound("Project directory", project.path));
+            }
+
+            let created_at = chrono::DateTime::from_timestamp(project.created_at, 0)
+                .unwrap_or_default()
+                .to_rfc3339();
+            let mut metadata = research_json::new_metadata(&project.name, project.description.as_deref(), &created_at);
+
+            let existing = research_json::read(&project.path).ok();
+            let readable_settings = existing
+                .as_ref()
+                .filter(|existing| Self::settings_from(existing).is_ok())
+                .and_then(|existing| existing.get("settings").cloned());
+            if let Some(settings) = readable_settings {
+                metadata.insert("settings".to_string(), settings);
+            }
+            if let Some(layout) = existing.as_ref().and_then(research_json::layout) {
+                metadata.insert(research_json::LAYOUT_KEY.to_string(), Value::from(layout));
+            }
+
+            research_json::write(&project.path, &metadata)?;
+            GitService::auto_commit(&project.path, "Repair research.json");
+            Self::settings_from(&metadata)
+        }).await
+    }
+
+    fn settings_from(metadata: &Map<String, Value>) -> AppResult<ProjectSettings> {
+        let settings = metadata.get("settings").cloned().unwrap_or_else(|| Value::Object(Map::new()));
+        serde_json::from_value(settings)
+            .map_err(|e| AppError::InvalidProjectMetadata(format!("settings: {}", e)))
+    }
+
+    /// A missing or unreadable research.json becomes a typed error the UI can offer a repair for
+    fn metadata_error(err: std::io::Error) -> AppError {
+        match err.kind() {
+            ErrorKind::NotFound => AppError::InvalidProjectMetadata(format!("{} not found", research_json::FILE_NAME)),
+            ErrorKind::InvalidData => AppError::InvalidProjectMetadata(err.to_string()),
+            _ => AppError::from(err),
+        }
+    }
+
+    /// Get the ordered task statuses allowed in a project
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_project_statuses(state: &AppState, project_id: String) -> AppResult<Vec<String>> {
+        state.run(move |conn| {
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::get_project_statuses(conn, &project_id)
+        }).await
+    }
+
+    /// Replace the task statuses allowed in a project
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn set_project_statuses(
+        state: &AppState,
+        project_id: String,
+        statuses: Vec<String>,
+        mapping: Option<HashMap<String, String>>,
+    ) -> AppResult<Vec<String>> {
+        state.run(move |conn| {
+            let statuses: Vec<String> = statuses.into_iter().map(|s| s.trim().to_string()).collect();
+
+            if statuses.iter().any(|s| s.is_empty()) {
+                return Err(AppError::InvalidInput("Status names cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            let mapping = mapping.unwrap_or_default();
+            DbService::with_busy_retry(|| DbService::set_project_statuses(conn, &project_id, &statuses, &mapping))?;
+            DbService::get_project_statuses(conn, &project_id)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode { id: id.to_string(), kind: "note".to_string(), title: id.to_string() }
+    }
+
+    fn edge(from: &str, to: &str) -> GraphEdge {
+        GraphEdge { from: from.to_string(), to: to.to_string(), kind: "link".to_string() }
+    }
+
+    #[test]
+    fn graph_leaves_out_orphans_unless_asked() {
+        let nodes = || vec![node("a"), node("b"), node("lonely")];
+        let edges = || vec![edge("a", "b"), edge("a", "elsewhere")];
+
+        let graph = ProjectService::build_graph(nodes(), edges(), false, 10);
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(graph.edges, [edge("a", "b")]);
+        assert!(!graph.truncated);
+
+        let graph = ProjectService::build_graph(nodes(), edges(), true, 10);
+        assert_eq!(graph.nodes.len(), 3);
+    }
+
+    #[test]
+    fn graph_over_the_cap_keeps_the_most_connected_nodes() {
+        let nodes = vec![node("leaf"), node("hub"), node("spoke1"), node("spoke2")];
+        let edges = vec![edge("hub", "spoke1"), edge("hub", "spoke2"), edge("leaf", "spoke2")];
+
+        let graph = ProjectService::build_graph(nodes, edges, false, 2);
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["hub", "spoke2"]);
+        assert_eq!(graph.edges, [edge("hub", "spoke2")]);
+        assert!(graph.truncated);
+    }
+
+    fn invalid_input(result: AppResult<String>) -> String {
+        match result {
+            Err(AppError::InvalidInput(message)) => message,
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn new_project_paths_are_made_absolute_and_normalized() {
+        let state = test_support::open_state();
+        let dir = fs::canonicalize(test_support::temp_dir()).unwrap();
+        fs::create_dir(dir.join("papers")).unwrap();
+
+        let raw = format!("  {}  ", dir.join("papers").join("..").join("Thesis").display());
+        let normalized = ProjectService::normalize_new_project_path(&state, &raw).unwrap();
+        assert_eq!(normalized, dir.join("Thesis").to_string_lossy());
+
+        if let Some(home) = path::home_dir().filter(|home| home.is_dir()) {
+            let normalized = ProjectService::normalize_new_project_path(&state, "~/research-vault-test-project").unwrap();
+            assert_eq!(Path::new(&normalized), fs::canonicalize(home).unwrap().join("research-vault-test-project"));
+        }
+    }
+
+    #[test]
+    fn relative_and_unusable_project_paths_are_rejected() {
+        let state = test_support::open_state();
+        let dir = fs::canonicalize(test_support::temp_dir()).unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        assert!(invalid_input(ProjectService::normalize_new_project_path(&state, "  ")).contains("cannot be empty"));
+        for relative in ["Thesis", "papers/Thesis", "../../etc/Thesis", "./Thesis"] {
+            let message = invalid_input(ProjectService::normalize_new_project_path(&state, relative));
+            assert!(message.contains("must be a full path"), "{}: {}", relative, message);
+        }
+
+        let root = dir.join("..").to_string_lossy().into_owned();
+        let message = invalid_input(ProjectService::normalize_new_project_path(&state, &root));
+        assert!(message.contains("must end in the name"), "{}", message);
+
+        let missing = dir.join("missing").join("Thesis").to_string_lossy().into_owned();
+        let message = invalid_input(ProjectService::normalize_new_project_path(&state, &missing));
+        assert!(message.contains("does not exist"), "{}", message);
+
+        let under_file = dir.join("notes.txt").join("Thesis").to_string_lossy().into_owned();
+        invalid_input(ProjectService::normalize_new_project_path(&state, &under_file));
+    }
+
+    #[test]
+    fn new_projects_cannot_nest_inside_registered_ones() {
+        let state = test_support::open_state();
+        let existing = test_support::project(&state.conn().unwrap(), "Thesis");
+        fs::create_dir(Path::new(&existing.path).join("data")).unwrap();
+
+        let nested = Path::new(&existing.path).join("data").join("Side project").to_string_lossy().into_owned();
+        let message = invalid_input(ProjectService::normalize_new_project_path(&state, &nested));
+        assert!(message.contains("inside the project 'Thesis'"), "{}", message);
+
+        let sibling = Path::new(&existing.path).with_extension("old").to_string_lossy().into_owned();
+        assert!(ProjectService::normalize_new_project_path(&state, &sibling).is_ok());
+    }
+
+    #[tokio::test]
+    async fn interleaved_project_edits_conflict_instead_of_overwriting() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            conn.execute("UPDATE projects SET last_modified_at = 1000 WHERE id = ?1", [&project.id]).unwrap();
+            project
+        };
+        let update = |fields: serde_json::Value| -> UpdateProjectDto { serde_json::from_value(fields).unwrap() };
+
+        let first = update(serde_json::json!({ "description": "PhD", "expected_updated_at": 1000 }));
+        let first = ProjectService::update_project(&state, project.id.clone(), first).await.unwrap();
+
+        let second = update(serde_json::json!({ "name": "Dissertation", "expected_updated_at": 1000 }));
+        match ProjectService::update_project(&state, project.id.clone(), second).await {
+            Err(AppError::EditConflict { entity: "Project", current, .. }) => {
+                assert_eq!(current["description"], "PhD");
+                assert_eq!(current["last_modified_at"], first.last_modified_at);
+            }
+            other => panic!("expected an edit conflict, got {:?}", other),
+        }
+        let stored = ProjectService::get_project(&state, project.id.clone()).await.unwrap();
+        assert_eq!(stored.name, "Thesis");
+
+        let retried = update(serde_json::json!({ "name": "Dissertation", "expected_updated_at": first.last_modified_at }));
+        let retried = ProjectService::update_project(&state, project.id, retried).await.unwrap();
+        assert_eq!((retried.name.as_str(), retried.description.as_deref()), ("Dissertation", Some("PhD")));
+    }
+
+    #[tokio::test]
+    async fn a_stale_edit_in_the_same_second_is_rejected() {
+        let state = test_support::open_state();
+        let project = test_support::project(&state.conn().unwrap(), "Thesis");
+        let update = |fields: serde_json::Value| -> UpdateProjectDto { serde_json::from_value(fields).unwrap() };
+
+        let loaded = project.last_modified_at;
+        let first = update(serde_json::json!({ "description": "PhD", "expected_updated_at": loaded }));
+        let first = ProjectService::update_project(&state, project.id.clone(), first).await.unwrap();
+        assert!(first.last_modified_at > loaded);
+
+        let stale = update(serde_json::json!({ "name": "Dissertation", "expected_updated_at": loaded }));
+        let stale = ProjectService::update_project(&state, project.id.clone(), stale).await;
+        assert!(matches!(stale, Err(AppError::EditConflict { entity: "Project", .. })), "{:?}", stale);
+        assert_eq!(ProjectService::get_project(&state, project.id).await.unwrap().name, "Thesis");
     }
 }
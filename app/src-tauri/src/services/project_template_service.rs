@@ -0,0 +1,151 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{Note, ProjectTemplate, SaveProjectTemplateOptions, Task, TemplateNote, TemplateTask, DEFAULT_TASK_STATUSES};
+use crate::services::DbService;
+use crate::state::AppState;
+use crate::utils::limits::{MAX_DESCRIPTION_LEN, MAX_NAME_LEN};
+use crate::utils::validate;
+use rusqlite::Connection;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Project templates and the tasks and notes new projects get from them
+pub struct ProjectTemplateService;
+
+impl ProjectTemplateService {
+    /// Save the task tree of a project and the chosen notes as a template. Due
+    /// dates are kept as offsets from the project's creation; statuses, keys,
+    /// completion, recurrence and reminders are not kept, and archived tasks are
+    /// left out.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn save_project_as_template(
+        state: &AppState,
+        project_id: String,
+        mut name: String,
+        mut options: SaveProjectTemplateOptions,
+    ) -> AppResult<ProjectTemplate> {
+        state.run(move |conn| {
+            validate::required("name", &mut name, MAX_NAME_LEN)?;
+            if let Some(description) = &mut options.description {
+                validate::text("description", description, MAX_DESCRIPTION_LEN)?;
+            }
+
+            let project = DbService::get_project_by_id(conn, &project_id)?
+                .ok_or(AppError::NotFound("Project", project_id))?;
+            if DbService::project_template_name_exists(conn, &name)? {
+                return Err(AppError::Conflict(format!("A project template named '{}' already exists", name)));
+            }
+
+            let tasks = Self::snapshot_tasks(conn, DbService::get_root_tasks(conn, &project.id)?, project.created_at)?;
+            let mut notes = Vec::with_capacity(options.note_ids.len());
+            for note_id in options.note_ids {
+                let note = DbService::get_note_by_id(conn, &note_id)?
+                    .filter(|note| note.project_id.as_deref() == Some(project.id.as_str()))
+                    .ok_or(AppError::NotFound("Note", note_id))?;
+                notes.push(TemplateNote {
+                    title: note.title,
+                    content: note.content,
+                    tags: note.tags,
+                    is_pinned: note.is_pinned,
+                });
+            }
+
+            let template = ProjectTemplate {
+                id: Uuid::new_v4().to_string(),
+                name,
+                description: options.description.filter(|description| !description.is_empty()),
+                tasks,
+                notes,
+                created_at: chrono::Utc::now().timestamp(),
+            };
+            DbService::with_busy_retry(|| DbService::insert_project_template(conn, &template))?;
+            Ok(template)
+        }).await
+    }
+
+    /// List all project templates by name
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_project_templates(state: &AppState) -> AppResult<Vec<ProjectTemplate>> {
+        state.run(DbService::get_project_templates).await
+    }
+
+    /// Delete a project template; projects created from it are kept
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_project_template(state: &AppState, id: String) -> AppResult<()> {
+        state.run(move |conn| {
+            if !DbService::with_busy_retry(|| DbService::delete_project_template(conn, &id))? {
+                return Err(AppError::NotFound("Project template", id));
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Tasks and notes of `template` for a new project created at `created_at`,
+    /// with fresh IDs and due dates resolved from their offsets. Parents come
+    /// before their subtasks and every task starts in the first default status.
+    pub fn instantiate(template: &ProjectTemplate, project_id: &str, created_at: i64) -> (Vec<Task>, Vec<Note>) {
+        let mut tasks = Vec::new();
+        Self::instantiate_tasks(&template.tasks, None, project_id, created_at, &mut tasks);
+
+        let notes = template.notes.iter()
+            .map(|note| Note {
+                id: Uuid::new_v4().to_string(),
+                project_id: Some(project_id.to_string()),
+                title: note.title.clone(),
+                content: note.content.clone(),
+                created_at,
+                updated_at: created_at,
+                tags: note.tags.clone(),
+                is_pinned: note.is_pinned,
+                is_locked: false,
+                metadata: None,
+            })
+            .collect();
+
+        (tasks, notes)
+    }
+
+    fn snapshot_tasks(conn: &Connection, tasks: Vec<Task>, project_created_at: i64) -> AppResult<Vec<TemplateTask>> {
+        tasks.into_iter()
+            .map(|task| {
+                let subtasks = Self::snapshot_tasks(conn, DbService::get_subtasks(conn, &task.id)?, project_created_at)?;
+                Ok(TemplateTask {
+                    title: task.title,
+                    description: task.description,
+                    priority: task.priority,
+                    tags: task.tags,
+                    due_offset_secs: task.due_date.map(|due| due - project_created_at),
+                    subtasks,
+                })
+            })
+            .collect()
+    }
+
+    fn instantiate_tasks(items: &[TemplateTask], parent_id: Option<&str>, project_id: &str, created_at: i64, out: &mut Vec<Task>) {
+        for (order, item) in items.iter().enumerate() {
+            let id = Uuid::new_v4().to_string();
+            out.push(Task {
+                id: id.clone(),
+                project_id: project_id.to_string(),
+                parent_id: parent_id.map(str::to_string),
+                title: item.title.clone(),
+                description: item.description.clone(),
+                status: DEFAULT_TASK_STATUSES[0].to_string(),
+                priority: item.priority,
+                due_date: item.due_offset_secs.map(|offset| created_at.saturating_add(offset)),
+                completed_at: None,
+                created_at,
+                updated_at: created_at,
+                order: order as i32,
+                tags: item.tags.clone(),
+                task_key: None,
+                rank: None,
+                recurrence: None,
+                recurrence_parent_id: None,
+                remind_at: None,
+                reminded_at: None,
+                metadata: None,
+            });
+            Self::instantiate_tasks(&item.subtasks, Some(&id), project_id, created_at, out);
+        }
+    }
+}
@@ -0,0 +1,688 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateReferenceDto, DuplicateAction, DuplicateMatch, Reference, ReferenceDuplicateGroup, ReferenceImportReport, SkippedItem,
+    UpdateReferenceDto, READING_STATUSES,
+};
+use crate::services::{DbService, SettingsService};
+use crate::state::AppState;
+use crate::utils::validate::Validate;
+use crate::utils::{bibtex, fuzzy};
+use rusqlite::Connection;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Entry type of references created without one
+const DEFAULT_ENTRY_TYPE: &str = "misc";
+
+/// Characters BibTeX does not allow in a citation key
+const INVALID_KEY_CHARS: &[char] = &['{', '}', '(', ')', ',', '=', '#', '"', '%', '\'', '~', '\\'];
+
+/// Prefixes stripped from DOIs so the same DOI always compares equal
+const DOI_PREFIXES: [&str; 5] = ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "http://dx.doi.org/", "doi:"];
+
+/// Title similarity, both ways, at which references of the same year and
+/// first author count as duplicates
+const DUPLICATE_TITLE_SIMILARITY: f64 = 0.85;
+
+/// Bibliography of a project and the notes that cite it
+pub struct ReferenceService;
+
+impl ReferenceService {
+    /// Create a reference; its DOI and citation key must be new to the project
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_reference(state: &AppState, mut data: CreateReferenceDto) -> AppResult<Reference> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+            if DbService::get_project_by_id(conn, &data.project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", data.project_id));
+            }
+
+            let authors = Self::clean_authors(data.authors);
+            let year = data.year;
+            let citation_key = match Self::clean(data.citation_key) {
+                Some(key) => Self::validate_key(key)?,
+                None => Self::generate_key(conn, &data.project_id, &authors, year, &data.title)?,
+            };
+            let doi = Self::clean(data.doi).map(|doi| Self::validate_doi(&doi)).transpose()?;
+
+            if let Some(existing) = DbService::find_duplicate_reference(conn, &data.project_id, doi.as_deref(), &citation_key)? {
+                return Err(AppError::Conflict(format!(
+                    "Reference '{}' already has this DOI or citation key",
+                    existing.citation_key
+                )));
+            }
+
+            let reference = Reference {
+                id: Uuid::new_v4().to_string(),
+                project_id: data.project_id,
+                citation_key,
+                entry_type: Self::clean_entry_type(data.entry_type),
+                title: data.title,
+                authors,
+                year,
+                venue: Self::clean(data.venue),
+                doi,
+                url: Self::clean(data.url),
+                abstract_text: Self::clean(data.abstract_text),
+                pdf_path: Self::clean(data.pdf_path),
+                created_at: chrono::Utc::now().timestamp(),
+                reading_status: data.reading_status.unwrap_or_else(|| READING_STATUSES[0].to_string()),
+                tags: data.tags,
+            };
+            DbService::with_busy_retry(|| DbService::upsert_reference(conn, &reference))?;
+            Ok(reference)
+        }).await
+    }
+
+    /// List the references of a project by citation key
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_references(state: &AppState, project_id: String) -> AppResult<Vec<Reference>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            DbService::get_references_by_project(conn, &project_id)
+        }).await
+    }
+
+    /// Get reference by ID
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_reference(state: &AppState, id: String) -> AppResult<Reference> {
+        state.run(move |conn| {
+            Self::require_reference(conn, &id)
+        }).await
+    }
+
+    /// Update the reference fields that are provided
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_reference(state: &AppState, id: String, mut data: UpdateReferenceDto) -> AppResult<Reference> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+            let mut reference = Self::require_reference(conn, &id)?;
+
+            if let Some(title) = data.title {
+                reference.title = title;
+            }
+            if let Some(key) = Self::clean(data.citation_key) {
+                let key = Self::validate_key(key)?;
+                if key != reference.citation_key && DbService::citation_key_exists(conn, &reference.project_id, &key)? {
+                    return Err(AppError::Conflict(format!("Citation key '{}' is already used in the project", key)));
+                }
+                reference.citation_key = key;
+            }
+            if let Some(doi) = Self::clean(data.doi) {
+                let doi = Self::validate_doi(&doi)?;
+                if let Some(existing) = DbService::find_duplicate_reference(conn, &reference.project_id, Some(&doi), &reference.citation_key)? {
+                    if existing.id != reference.id {
+                        return Err(AppError::Conflict(format!("Reference '{}' already has this DOI", existing.citation_key)));
+                    }
+                }
+                reference.doi = Some(doi);
+            }
+            if data.entry_type.is_some() {
+                reference.entry_type = Self::clean_entry_type(data.entry_type);
+            }
+            if let Some(authors) = data.authors {
+                reference.authors = Self::clean_authors(authors);
+            }
+            reference.year = data.year.or(reference.year);
+            reference.venue = Self::clean(data.venue).or(reference.venue);
+            reference.url = Self::clean(data.url).or(reference.url);
+            reference.abstract_text = Self::clean(data.abstract_text).or(reference.abstract_text);
+            reference.pdf_path = Self::clean(data.pdf_path).or(reference.pdf_path);
+            reference.reading_status = data.reading_status.unwrap_or(reference.reading_status);
+            if data.tags.is_some() {
+                reference.tags = data.tags;
+            }
+
+            DbService::with_busy_retry(|| DbService::upsert_reference(conn, &reference))?;
+            Ok(reference)
+        }).await
+    }
+
+    /// Delete a reference; notes citing it lose the citation
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_reference(state: &AppState, id: String) -> AppResult<()> {
+        state.run(move |conn| {
+            if !DbService::with_busy_retry(|| DbService::delete_reference(conn, &id))? {
+                return Err(AppError::NotFound("Reference", id));
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Import BibTeX entries into a project from text or from a file path.
+    /// An entry whose DOI or citation key is already in the project is skipped
+    /// or overwrites the existing reference, per `on_duplicate`; either way it is
+    /// reported. Entries that cannot be parsed are reported and left out.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn import_bibtex(
+        state: &AppState,
+        project_id: String,
+        bibtex_text_or_path: String,
+        on_duplicate: DuplicateAction,
+    ) -> AppResult<ReferenceImportReport> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let text = if Self::looks_like_bibtex(&bibtex_text_or_path) {
+                bibtex_text_or_path
+            } else {
+                fs::read_to_string(bibtex_text_or_path.trim())?
+            };
+            let (entries, errors) = bibtex::parse(&text);
+
+            let mut skipped: Vec<SkippedItem> = errors
+                .into_iter()
+                .map(|error| SkippedItem { id: format!("line {}", error.line), reason: error.message })
+                .collect();
+
+            let conn = &state.conn()?;
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            let mut report = DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                let mut report = ReferenceImportReport::default();
+                for entry in &entries {
+                    let mut reference = match Self::reference_from_entry(entry, &project_id, now) {
+                        Ok(reference) => reference,
+                        Err(reason) => {
+                            report.skipped.push(SkippedItem { id: entry.key.clone(), reason });
+                            continue;
+                        }
+                    };
+
+                    match DbService::find_duplicate_reference(tx, &project_id, reference.doi.as_deref(), &reference.citation_key)? {
+                        None => {
+                            DbService::upsert_reference(tx, &reference)?;
+                            report.imported.push(reference);
+                        }
+                        Some(existing) if on_duplicate == DuplicateAction::Update => {
+                            // Keep what identifies the reference and what BibTeX does not carry
+                            reference.id = existing.id;
+                            reference.citation_key = existing.citation_key;
+                            reference.created_at = existing.created_at;
+                            reference.pdf_path = existing.pdf_path;
+                            reference.reading_status = existing.reading_status;
+                            reference.tags = existing.tags;
+                            DbService::upsert_reference(tx, &reference)?;
+                            report.updated.push(reference);
+                        }
+                        Some(_) => report.duplicates.push(reference.citation_key),
+                    }
+                }
+                Ok(report)
+            }))?;
+
+            skipped.append(&mut report.skipped);
+            report.skipped = skipped;
+            Ok(report)
+        }).await
+    }
+
+    /// Groups of references of a project that look like the same work: those
+    /// sharing a DOI, then among those without one, those with the same year
+    /// and first author surname and similar titles. Only references in the
+    /// same year and surname bucket have their titles compared.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn find_duplicate_references(state: &AppState, project_id: String) -> AppResult<Vec<ReferenceDuplicateGroup>> {
+        state.run(move |conn| {
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            let mut references = DbService::get_references_by_project(conn, &project_id)?;
+            references.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.citation_key.cmp(&b.citation_key)));
+
+            let mut by_doi: BTreeMap<String, Vec<Reference>> = BTreeMap::new();
+            let mut buckets: BTreeMap<(Option<i64>, String), Vec<Reference>> = BTreeMap::new();
+            for reference in references {
+                match reference.doi.as_deref().map(Self::normalize_doi).filter(|doi| !doi.is_empty()) {
+                    Some(doi) => by_doi.entry(doi).or_default().push(reference),
+                    None => {
+                        let surname = reference.authors.first().map(|author| Self::surname(author)).unwrap_or_default();
+                        buckets.entry((reference.year, surname)).or_default().push(reference);
+                    }
+                }
+            }
+
+            let mut groups: Vec<ReferenceDuplicateGroup> = by_doi
+                .into_values()
+                .map(|references| ReferenceDuplicateGroup { matched_by: DuplicateMatch::Doi, references })
+                .collect();
+            for bucket in buckets.into_values() {
+                let mut similar: Vec<Vec<Reference>> = Vec::new();
+                for reference in bucket {
+                    match similar.iter_mut().find(|group| group.iter().any(|other| Self::similar_titles(&other.title, &reference.title))) {
+                        Some(group) => group.push(reference),
+                        None => similar.push(vec![reference]),
+                    }
+                }
+                groups.extend(similar.into_iter().map(|references| ReferenceDuplicateGroup { matched_by: DuplicateMatch::Title, references }));
+            }
+
+            groups.retain(|group| group.references.len() > 1);
+            groups.sort_by(|a, b| a.references[0].citation_key.cmp(&b.references[0].citation_key));
+            Ok(groups)
+        }).await
+    }
+
+    /// Fold duplicates into the reference `keep_id`: it takes the fields it
+    /// lacks from them, the union of their tags and the furthest reading status,
+    /// notes citing them cite it instead, and they are deleted, all in one
+    /// transaction. Returns the merged reference.
+    #[instrument(level = "debug", skip_all, fields(keep_id = %keep_id))]
+    pub async fn merge_references(state: &AppState, keep_id: String, remove_ids: Vec<String>) -> AppResult<Reference> {
+        state.run(move |conn| {
+            let mut seen = HashSet::new();
+            let remove_ids: Vec<String> = remove_ids.into_iter().filter(|id| seen.insert(id.clone())).collect();
+            if remove_ids.is_empty() {
+                return Err(AppError::InvalidInput("No references to merge".into()));
+            }
+            if remove_ids.contains(&keep_id) {
+                return Err(AppError::InvalidInput("A reference cannot be merged into itself".into()));
+            }
+
+            let mut keep = Self::require_reference(conn, &keep_id)?;
+            for id in &remove_ids {
+                let duplicate = Self::require_reference(conn, id)?;
+                if duplicate.project_id != keep.project_id {
+                    return Err(AppError::Conflict(format!("Reference '{}' belongs to a different project", duplicate.citation_key)));
+                }
+                Self::absorb(&mut keep, duplicate);
+            }
+
+            DbService::with_busy_retry(|| DbService::merge_references(conn, &keep, &remove_ids))?;
+            Ok(keep)
+        }).await
+    }
+
+    /// BibTeX text of every reference of a project
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn export_bibtex(state: &AppState, project_id: String) -> AppResult<String> {
+        state.run(move |conn| {
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+
+            let entries: Vec<String> = DbService::get_references_by_project(conn, &project_id)?
+                .iter()
+                .map(Self::format_reference)
+                .collect();
+            Ok(entries.join("\n"))
+        }).await
+    }
+
+    /// Record that a note cites a reference of its project; returns what the note cites
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id, reference_id = %reference_id))]
+    pub async fn cite_in_note(state: &AppState, note_id: String, reference_id: String) -> AppResult<Vec<Reference>> {
+        state.run(move |conn| {
+            let reference = Self::require_reference(conn, &reference_id)?;
+            let note = DbService::get_note_by_id(conn, &note_id)?
+                .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?;
+            if note.project_id.as_deref() != Some(reference.project_id.as_str()) {
+                return Err(AppError::Conflict("Note belongs to a different project than the reference".into()));
+            }
+
+            DbService::with_busy_retry(|| DbService::cite_reference(conn, &note_id, &reference_id))?;
+            DbService::get_note_references(conn, &note_id)
+        }).await
+    }
+
+    /// Remove a citation from a note; returns what the note still cites
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id, reference_id = %reference_id))]
+    pub async fn uncite_in_note(state: &AppState, note_id: String, reference_id: String) -> AppResult<Vec<Reference>> {
+        state.run(move |conn| {
+            if !DbService::with_busy_retry(|| DbService::uncite_reference(conn, &note_id, &reference_id))? {
+                return Err(AppError::NotFound("Citation", reference_id));
+            }
+            DbService::get_note_references(conn, &note_id)
+        }).await
+    }
+
+    /// List the references a note cites
+    #[instrument(level = "debug", skip_all, fields(note_id = %note_id))]
+    pub async fn list_note_references(state: &AppState, note_id: String) -> AppResult<Vec<Reference>> {
+        state.run(move |conn| {
+            if DbService::get_note_by_id(conn, &note_id)?.is_none() {
+                return Err(AppError::NotFound("Note", note_id));
+            }
+            DbService::get_note_references(conn, &note_id)
+        }).await
+    }
+
+    /// Fill the gaps of `keep` from a duplicate about to be deleted
+    fn absorb(keep: &mut Reference, duplicate: Reference) {
+        if keep.entry_type == DEFAULT_ENTRY_TYPE {
+            keep.entry_type = duplicate.entry_type;
+        }
+        if keep.authors.is_empty() {
+            keep.authors = duplicate.authors;
+        }
+        keep.year = keep.year.or(duplicate.year);
+        keep.venue = keep.venue.take().or(duplicate.venue);
+        keep.doi = keep.doi.take().or(duplicate.doi);
+        keep.url = keep.url.take().or(duplicate.url);
+        keep.abstract_text = keep.abstract_text.take().or(duplicate.abstract_text);
+        keep.pdf_path = keep.pdf_path.take().or(duplicate.pdf_path);
+
+        let progress = |status: &str| READING_STATUSES.iter().position(|s| *s == status).unwrap_or(0);
+        if progress(&duplicate.reading_status) > progress(&keep.reading_status) {
+            keep.reading_status = duplicate.reading_status;
+        }
+
+        if let Some(tags) = duplicate.tags {
+            let merged = keep.tags.get_or_insert_with(Vec::new);
+            for tag in tags {
+                if !merged.contains(&tag) {
+                    merged.push(tag);
+                }
+            }
+        }
+    }
+
+    /// Lowercased surname of an author written "Surname, Given" or "Given Surname"
+    fn surname(author: &str) -> String {
+        let surname = match author.split_once(',') {
+            Some((surname, _)) => surname,
+            None => author.split_whitespace().next_back().unwrap_or_default(),
+        };
+        surname.trim().to_lowercase()
+    }
+
+    fn similar_titles(a: &str, b: &str) -> bool {
+        fuzzy::title_similarity(a, b).min(fuzzy::title_similarity(b, a)) >= DUPLICATE_TITLE_SIMILARITY
+    }
+
+    fn require_reference(conn: &Connection, id: &str) -> AppResult<Reference> {
+        DbService::get_reference_by_id(conn, id)?
+            .ok_or_else(|| AppError::NotFound("Reference", id.to_string()))
+    }
+
+    /// Text rather than a path: it holds an entry or spans several lines
+    fn looks_like_bibtex(input: &str) -> bool {
+        let input = input.trim_start();
+        input.starts_with('@') || input.starts_with('%') || input.contains('\n')
+    }
+
+    fn reference_from_entry(entry: &bibtex::Entry, project_id: &str, now: i64) -> Result<Reference, String> {
+        let title = entry.field("title").ok_or("Missing title")?;
+        let key = Self::validate_key(entry.key.clone()).map_err(|e| e.to_string())?;
+        let authors = entry
+            .field("author")
+            .or_else(|| entry.field("editor"))
+            .map(bibtex::split_names)
+            .unwrap_or_default();
+        let year = entry
+            .field("year")
+            .or_else(|| entry.field("date"))
+            .and_then(|value| value.get(..4))
+            .and_then(|year| year.parse().ok());
+        let venue = ["journal", "journaltitle", "booktitle", "publisher", "school", "institution", "howpublished"]
+            .iter()
+            .find_map(|field| entry.field(field))
+            .map(str::to_string);
+
+        Ok(Reference {
+            id: Uuid::new_v4().to_string(),
+            project_id: project_id.to_string(),
+            citation_key: key,
+            entry_type: entry.entry_type.clone(),
+            title: title.to_string(),
+            authors,
+            year,
+            venue,
+            doi: entry.field("doi").map(Self::normalize_doi),
+            url: entry.field("url").map(str::to_string),
+            abstract_text: entry.field("abstract").map(str::to_string),
+            pdf_path: None,
+            created_at: now,
+            reading_status: READING_STATUSES[0].to_string(),
+            tags: None,
+        })
+    }
+
+    fn format_reference(reference: &Reference) -> String {
+        let venue_field = match reference.entry_type.as_str() {
+            "article" => "journal",
+            "inproceedings" | "incollection" | "conference" => "booktitle",
+            "book" | "inbook" => "publisher",
+            "phdthesis" | "mastersthesis" | "thesis" => "school",
+            "techreport" => "institution",
+            _ => "howpublished",
+        };
+
+        let fields = [
+            ("author", reference.authors.join(" and ")),
+            ("title", reference.title.clone()),
+            (venue_field, reference.venue.clone().unwrap_or_default()),
+            ("year", reference.year.map(|y| y.to_string()).unwrap_or_default()),
+            ("doi", reference.doi.clone().unwrap_or_default()),
+            ("url", reference.url.clone().unwrap_or_default()),
+            ("abstract", reference.abstract_text.clone().unwrap_or_default()),
+            ("file", reference.pdf_path.clone().unwrap_or_default()),
+        ];
+        bibtex::format_entry(&reference.entry_type, &reference.citation_key, &fields)
+    }
+
+    /// Key made of the first author's family name, the year and the first long
+    /// title word, e.g. "lovelace1843notes", with a letter added when taken
+    fn generate_key(
+        conn: &Connection,
+        project_id: &str,
+        authors: &[String],
+        year: Option<i64>,
+        title: &str,
+    ) -> AppResult<String> {
+        let ascii = |s: &str| -> String {
+            s.chars().filter(char::is_ascii_alphanumeric).flat_map(|c| c.to_lowercase()).collect()
+        };
+        let author = authors.first().map(|a| ascii(bibtex::family_name(a))).unwrap_or_default();
+        let word = title
+            .split_whitespace()
+            .map(ascii)
+            .find(|word| word.len() > 3)
+            .unwrap_or_default();
+        let mut base = format!("{}{}{}", author, year.map(|y| y.to_string()).unwrap_or_default(), word);
+        if base.is_empty() {
+            base = "ref".to_string();
+        }
+
+        if !DbService::citation_key_exists(conn, project_id, &base)? {
+            return Ok(base);
+        }
+        for suffix in 'a'..='z' {
+            let key = format!("{}{}", base, suffix);
+            if !DbService::citation_key_exists(conn, project_id, &key)? {
+                return Ok(key);
+            }
+        }
+        Ok(format!("{}-{}", base, &Uuid::new_v4().simple().to_string()[..8]))
+    }
+
+    fn validate_key(key: String) -> AppResult<String> {
+        if key.is_empty() || key.contains(char::is_whitespace) || key.contains(INVALID_KEY_CHARS) {
+            return Err(AppError::InvalidInput(format!(
+                "Citation key '{}' must be non-empty without spaces or any of {{}}(),=#\"%'~\\",
+                key
+            )));
+        }
+        Ok(key)
+    }
+
+    fn normalize_doi(doi: &str) -> String {
+        let doi = doi.trim();
+        let stripped = DOI_PREFIXES
+            .iter()
+            .find_map(|prefix| {
+                doi.get(..prefix.len())
+                    .filter(|head| head.eq_ignore_ascii_case(prefix))
+                    .map(|_| &doi[prefix.len()..])
+            })
+            .unwrap_or(doi);
+        stripped.trim().to_lowercase()
+    }
+
+    fn validate_doi(doi: &str) -> AppResult<String> {
+        let doi = Self::normalize_doi(doi);
+        if !doi.starts_with("10.") || !doi.contains('/') {
+            return Err(AppError::InvalidInput(format!("'{}' is not a DOI", doi)));
+        }
+        Ok(doi)
+    }
+
+    fn clean_entry_type(entry_type: Option<String>) -> String {
+        Self::clean(entry_type)
+            .map(|t| t.to_lowercase())
+            .filter(|t| t.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or_else(|| DEFAULT_ENTRY_TYPE.to_string())
+    }
+
+    fn clean_authors(authors: Vec<String>) -> Vec<String> {
+        authors
+            .into_iter()
+            .map(|a| a.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|a| !a.is_empty())
+            .collect()
+    }
+
+    /// Trimmed value, None when blank
+    fn clean(value: Option<String>) -> Option<String> {
+        value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    fn new_reference(project_id: &str, reading_status: Option<&str>) -> CreateReferenceDto {
+        CreateReferenceDto {
+            project_id: project_id.to_string(),
+            citation_key: Some("lovelace1843".to_string()),
+            entry_type: Some("article".to_string()),
+            title: "Notes on the Analytical Engine".to_string(),
+            authors: vec!["Lovelace, Ada".to_string()],
+            year: Some(1843),
+            venue: None,
+            doi: None,
+            url: None,
+            abstract_text: None,
+            pdf_path: None,
+            reading_status: reading_status.map(str::to_string),
+            tags: Some(vec![" engines ".to_string()]),
+        }
+    }
+
+    #[tokio::test]
+    async fn reading_status_is_checked_and_kept_across_bibtex_updates() {
+        let state = test_support::open_state();
+        let project = test_support::project(&state.conn().unwrap(), "History");
+
+        let invalid = ReferenceService::create_reference(&state, new_reference(&project.id, Some("skimmed"))).await;
+        assert!(matches!(invalid, Err(AppError::InvalidInput(_))));
+
+        let created = ReferenceService::create_reference(&state, new_reference(&project.id, None)).await.unwrap();
+        assert_eq!(created.reading_status, "unread");
+        assert_eq!(created.tags, Some(vec!["engines".to_string()]));
+
+        let update = UpdateReferenceDto {
+            citation_key: None,
+            entry_type: None,
+            title: None,
+            authors: None,
+            year: None,
+            venue: None,
+            doi: None,
+            url: None,
+            abstract_text: None,
+            pdf_path: None,
+            reading_status: Some(" Reading ".to_string()),
+            tags: None,
+        };
+        let updated = ReferenceService::update_reference(&state, created.id.clone(), update).await.unwrap();
+        assert_eq!(updated.reading_status, "reading");
+        assert_eq!(updated.tags, created.tags);
+
+        let bibtex = "@article{lovelace1843, title = {Notes by the Translator}, author = {Lovelace, Ada}}".to_string();
+        let report = ReferenceService::import_bibtex(&state, project.id, bibtex, DuplicateAction::Update).await.unwrap();
+        assert_eq!(report.updated.len(), 1);
+        let reimported = ReferenceService::get_reference(&state, created.id).await.unwrap();
+        assert_eq!(reimported.title, "Notes by the Translator");
+        assert_eq!(reimported.reading_status, "reading");
+        assert_eq!(reimported.tags, Some(vec!["engines".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn duplicates_are_found_by_doi_and_by_similar_title() {
+        let state = test_support::open_state();
+        let (project, note) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Survey");
+            let note = test_support::note(conn, &project.id, "Reading", "");
+            (project, note)
+        };
+        let create = |key: &str, title: &str, author: &str, doi: Option<&str>| {
+            let mut data = new_reference(&project.id, None);
+            data.citation_key = Some(key.to_string());
+            data.title = title.to_string();
+            data.authors = vec![author.to_string()];
+            data.doi = doi.map(str::to_string);
+            data.tags = Some(vec![key.to_string()]);
+            data
+        };
+        let engine = ReferenceService::create_reference(&state, create("a", "Notes on the Analytical Engine", "Lovelace, Ada", None)).await.unwrap();
+        let typo = ReferenceService::create_reference(&state, create("b", "Notes on the Analytic Engine", "Ada Lovelace", None)).await.unwrap();
+        // Same title but a different first author is another work
+        ReferenceService::create_reference(&state, create("c", "Notes on the Analytical Engine", "Babbage, Charles", None)).await.unwrap();
+        ReferenceService::create_reference(&state, create("d", "Sketch of the Engine", "Menabrea, Luigi", Some("10.1/x"))).await.unwrap();
+        {
+            // A DOI written with a resolver prefix, as older imports stored it
+            let conn = &state.conn().unwrap();
+            let mut legacy = test_support::reference(conn, &project.id, "e", "Sketch of the Analytical Engine");
+            legacy.doi = Some("https://doi.org/10.1/X".to_string());
+            legacy.abstract_text = Some("Translated".to_string());
+            DbService::upsert_reference(conn, &legacy).unwrap();
+        }
+
+        let groups = ReferenceService::find_duplicate_references(&state, project.id.clone()).await.unwrap();
+        let keys: Vec<(DuplicateMatch, Vec<&str>)> = groups
+            .iter()
+            .map(|group| (group.matched_by, group.references.iter().map(|r| r.citation_key.as_str()).collect()))
+            .collect();
+        assert_eq!(keys, vec![(DuplicateMatch::Title, vec!["a", "b"]), (DuplicateMatch::Doi, vec!["d", "e"])]);
+
+        ReferenceService::cite_in_note(&state, note.id.clone(), typo.id.clone()).await.unwrap();
+        ReferenceService::update_reference(&state, typo.id.clone(), UpdateReferenceDto {
+            citation_key: None,
+            entry_type: None,
+            title: None,
+            authors: None,
+            year: None,
+            venue: Some("Scientific Memoirs".to_string()),
+            doi: None,
+            url: None,
+            abstract_text: None,
+            pdf_path: None,
+            reading_status: Some("read".to_string()),
+            tags: None,
+        }).await.unwrap();
+
+        let merged = ReferenceService::merge_references(&state, engine.id.clone(), vec![typo.id.clone()]).await.unwrap();
+        assert_eq!(merged.venue.as_deref(), Some("Scientific Memoirs"));
+        assert_eq!(merged.reading_status, "read");
+        assert_eq!(merged.tags, Some(vec!["a".to_string(), "b".to_string()]));
+        let cited = ReferenceService::list_note_references(&state, note.id).await.unwrap();
+        assert_eq!(cited.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), vec![engine.id.as_str()]);
+        assert!(matches!(ReferenceService::get_reference(&state, typo.id).await, Err(AppError::NotFound(..))));
+
+        let into_itself = ReferenceService::merge_references(&state, engine.id.clone(), vec![engine.id]).await;
+        assert!(matches!(into_itself, Err(AppError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+use tracing::instrument;
+
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Task, TaskWithProject};
+use crate::services::DbService;
+use crate::state::AppState;
+
+/// Event sent when a task's reminder is due, with the `TaskWithProject`
+pub const TASK_REMINDER_EVENT: &str = "task:reminder";
+
+/// Time between two checks for due reminders
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+/// Longest a reminder can be snoozed: one week, in minutes
+const MAX_SNOOZE_MINUTES: i64 = 7 * 24 * 60;
+
+/// Reminders of tasks and the loop that delivers them
+pub struct ReminderService;
+
+impl ReminderService {
+    /// Deliver due reminders for as long as the app runs. Every tick takes all
+    /// reminders whose time has passed, so those missed while the computer slept
+    /// go out at the first tick after it wakes. The loop runs once per app, not
+    /// per window, and each reminder is marked delivered before its event is
+    /// sent, so no window sees it twice.
+    pub async fn run_scheduler<R: Runtime>(app: AppHandle<R>) {
+        loop {
+            let state = app.state::<AppState>().inner().clone();
+            let now = chrono::Utc::now().timestamp();
+            match state.run(move |conn| DbService::with_busy_retry(|| DbService::claim_due_reminders(conn, now))).await {
+                Ok(due) => {
+                    for reminder in due {
+                        if let Err(e) = app.emit(TASK_REMINDER_EVENT, &reminder) {
+                            tracing::warn!("Failed to emit {}: {}", TASK_REMINDER_EVENT, e);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to check for due reminders: {}", e),
+            }
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    }
+
+    /// Reminders not delivered yet, soonest first, including those whose time
+    /// has passed but that the scheduler has not picked up
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_pending_reminders(state: &AppState) -> AppResult<Vec<TaskWithProject>> {
+        state.run_with_retry(move |conn| DbService::get_pending_reminders(conn, None)).await
+    }
+
+    /// Deliver a task's reminder again `minutes` from now
+    #[instrument(level = "debug", skip_all, fields(task_id = %task_id))]
+    pub async fn snooze_reminder(state: &AppState, task_id: String, minutes: i64) -> AppResult<Task> {
+        state.run(move |conn| {
+            if task_id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
+            if !(1..=MAX_SNOOZE_MINUTES).contains(&minutes) {
+                return Err(AppError::InvalidInput(format!(
+                    "A reminder can be snoozed for 1 to {} minutes",
+                    MAX_SNOOZE_MINUTES
+                )));
+            }
+
+            let task = DbService::get_task_by_id(conn, &task_id)?
+                .ok_or_else(|| AppError::NotFound("Task", task_id.clone()))?;
+            if task.remind_at.is_none() {
+                return Err(AppError::InvalidInput(format!("Task {} has no reminder", task_id)));
+            }
+            if task.status == DbService::get_done_status(conn, &task.project_id)? {
+                return Err(AppError::Conflict(format!("Task {} is already done", task_id)));
+            }
+
+            let remind_at = chrono::Utc::now().timestamp() + minutes * 60;
+            if !DbService::with_busy_retry(|| DbService::set_task_reminder(conn, &task_id, remind_at))? {
+                return Err(AppError::NotFound("Task", task_id));
+            }
+            DbService::get_task_by_id(conn, &task_id)?
+                .ok_or(AppError::NotFound("Task", task_id))
+        }).await
+    }
+}
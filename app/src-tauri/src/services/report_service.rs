@@ -0,0 +1,293 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Note, ProgressReport, ProgressReportExport, Project, ReportFormat, ReportNote, ReportTask, ReportTime, Task,
+};
+use crate::services::{DbService, GitService};
+use crate::state::AppState;
+use std::fs;
+use std::path::Path;
+use tracing::instrument;
+
+/// Folder of a project that committed reports are written to
+const REPORTS_DIR: &str = "docs/reports";
+
+/// Builds progress reports of what happened in a project over a period
+pub struct ReportService;
+
+impl ReportService {
+    /// Report the tasks completed and created, notes added and edited, overdue
+    /// tasks and tracked time of a project between `from` (inclusive) and `to`
+    /// (exclusive). A window in which nothing happened yields a report saying so.
+    ///
+    /// With `auto_commit` the report is written to docs/reports/ in the project
+    /// and committed, whatever the project's own auto-commit setting. Generating
+    /// the same window again overwrites that file and commits only if it changed.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn generate_progress_report(
+        state: &AppState,
+        project_id: String,
+        from: i64,
+        to: i64,
+        format: ReportFormat,
+        auto_commit: bool,
+    ) -> AppResult<ProgressReportExport> {
+        state.blocking(move |state| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            if to < from {
+                return Err(AppError::InvalidInput("The report must end after it starts".into()));
+            }
+
+            let (project, report) = {
+                let conn = &state.conn()?;
+                let project = DbService::get_project_by_id(conn, &project_id)?
+                    .ok_or_else(|| AppError::NotFound("Project", project_id.clone()))?;
+                let tasks = DbService::get_tasks_by_project(conn, &project_id)?;
+                let notes = DbService::get_notes_by_project(conn, &project_id)?;
+                let done = DbService::get_done_status(conn, &project_id)?;
+
+                // A running timer counts up to now, but never past the end of the window
+                let now = chrono::Utc::now().timestamp().min(to);
+                let mut by_task = DbService::get_time_by_task(conn, &project_id, from, to, now)?;
+                by_task.retain(|t| t.seconds > 0);
+                by_task.sort_by(|a, b| {
+                    b.seconds
+                        .cmp(&a.seconds)
+                        .then_with(|| a.title.cmp(&b.title))
+                        .then_with(|| a.task_id.cmp(&b.task_id))
+                });
+                let time = (!by_task.is_empty()).then(|| ReportTime {
+                    total_seconds: by_task.iter().map(|t| t.seconds).sum(),
+                    by_task,
+                });
+
+                let report = Self::build(&project, &done, from, to, tasks, notes, time);
+                (project, report)
+            };
+
+            let content = match format {
+                ReportFormat::Markdown => Self::render_markdown(&report),
+                ReportFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+            };
+
+            let mut path = None;
+            let mut committed = false;
+            if auto_commit {
+                let relative = format!(
+                    "{}/progress-{}-to-{}.{}",
+                    REPORTS_DIR,
+                    Self::format_date(from),
+                    Self::format_date(to),
+                    format.extension()
+                );
+                let dest = Path::new(&project.path).join(&relative);
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&dest, &content)?;
+
+                GitService::add(&project.path, &relative)?;
+                committed = GitService::commit(
+                    &project.path,
+                    &format!("Add progress report {} to {}", Self::format_date(from), Self::format_date(to)),
+                )?;
+                path = Some(dest.to_string_lossy().into_owned());
+            }
+
+            Ok(ProgressReportExport { format, content, path, committed })
+        }).await
+    }
+
+    fn build(
+        project: &Project,
+        done: &str,
+        from: i64,
+        to: i64,
+        tasks: Vec<Task>,
+        notes: Vec<Note>,
+        time: Option<ReportTime>,
+    ) -> ProgressReport {
+        let in_window = |timestamp: i64| timestamp >= from && timestamp < to;
+
+        let mut tasks_completed: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| t.status == done && t.completed_at.is_some_and(in_window))
+            .collect();
+        tasks_completed.sort_by(|a, b| a.completed_at.cmp(&b.completed_at).then_with(|| a.id.cmp(&b.id)));
+
+        let mut tasks_created: Vec<&Task> = tasks.iter().filter(|t| in_window(t.created_at)).collect();
+        tasks_created.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        // Overdue as of the end of the window: a task completed later still counts
+        let mut overdue: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| t.due_date.is_some_and(|due| due < to))
+            .filter(|t| match t.completed_at {
+                Some(completed_at) => completed_at >= to,
+                None => t.status != done,
+            })
+            .collect();
+        overdue.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| a.id.cmp(&b.id)));
+
+        let mut notes_added: Vec<&Note> = notes.iter().filter(|n| in_window(n.created_at)).collect();
+        notes_added.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        let mut notes_edited: Vec<&Note> = notes
+            .iter()
+            .filter(|n| n.created_at < from && in_window(n.updated_at))
+            .collect();
+        notes_edited.sort_by(|a, b| a.updated_at.cmp(&b.updated_at).then_with(|| a.id.cmp(&b.id)));
+
+        ProgressReport {
+            project_id: project.id.clone(),
+            project_name: project.name.clone(),
+            from,
+            to,
+            tasks_completed: tasks_completed.into_iter().map(Self::report_task).collect(),
+            tasks_created: tasks_created.into_iter().map(Self::report_task).collect(),
+            notes_added: notes_added.into_iter().map(Self::report_note).collect(),
+            notes_edited: notes_edited.into_iter().map(Self::report_note).collect(),
+            overdue: overdue.into_iter().map(Self::report_task).collect(),
+            time,
+        }
+    }
+
+    fn report_task(task: &Task) -> ReportTask {
+        ReportTask {
+            id: task.id.clone(),
+            task_key: task.task_key.clone(),
+            title: task.title.clone(),
+            status: task.status.clone(),
+            due_date: task.due_date,
+            created_at: task.created_at,
+            completed_at: task.completed_at,
+        }
+    }
+
+    fn report_note(note: &Note) -> ReportNote {
+        ReportNote {
+            id: note.id.clone(),
+            title: note.title.clone(),
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+        }
+    }
+
+    /// Render a report as Markdown. The output depends only on the report, so
+    /// the same window renders the same file and commits cleanly.
+    fn render_markdown(report: &ProgressReport) -> String {
+        let mut out = format!(
+            "# Progress report: {}\n\n{} to {}\n",
+            report.project_name,
+            Self::format_date(report.from),
+            Self::format_date(report.to)
+        );
+
+        let tracked = report.time.as_ref().map_or(0, |t| t.total_seconds);
+        let quiet = report.tasks_completed.is_empty()
+            && report.tasks_created.is_empty()
+            && report.notes_added.is_empty()
+            && report.notes_edited.is_empty()
+            && report.overdue.is_empty()
+            && tracked == 0;
+        if quiet {
+            out.push_str("\n_Nothing happened in this period._\n");
+            return out;
+        }
+
+        out.push_str("\n## Summary\n\n");
+        out.push_str(&format!("- Tasks completed: {}\n", report.tasks_completed.len()));
+        out.push_str(&format!("- Tasks created: {}\n", report.tasks_created.len()));
+        out.push_str(&format!("- Notes added: {}\n", report.notes_added.len()));
+        out.push_str(&format!("- Notes edited: {}\n", report.notes_edited.len()));
+        out.push_str(&format!("- Overdue tasks: {}\n", report.overdue.len()));
+        if tracked > 0 {
+            out.push_str(&format!("- Time tracked: {}\n", Self::format_duration(tracked)));
+        }
+
+        out.push_str("\n## Completed tasks\n\n");
+        Self::render_list(&mut out, &report.tasks_completed, |task| {
+            format!("{} (completed {})", Self::task_label(task), Self::format_date(task.completed_at.unwrap_or_default()))
+        });
+
+        out.push_str("\n## Created tasks\n\n");
+        Self::render_list(&mut out, &report.tasks_created, |task| {
+            format!("{} ({})", Self::task_label(task), task.status)
+        });
+
+        out.push_str("\n## Notes added\n\n");
+        Self::render_list(&mut out, &report.notes_added, |note| note.title.clone());
+
+        out.push_str("\n## Notes edited\n\n");
+        Self::render_list(&mut out, &report.notes_edited, |note| note.title.clone());
+
+        out.push_str("\n## Overdue tasks\n\n");
+        Self::render_list(&mut out, &report.overdue, |task| {
+            format!("{} (due {})", Self::task_label(task), Self::format_date(task.due_date.unwrap_or_default()))
+        });
+
+        if let Some(time) = &report.time {
+            out.push_str("\n## Time tracked\n\n");
+            Self::render_list(&mut out, &time.by_task, |entry| {
+                format!("{}: {}", entry.title, Self::format_duration(entry.seconds))
+            });
+        }
+
+        out
+    }
+
+    fn render_list<T>(out: &mut String, items: &[T], line: impl Fn(&T) -> String) {
+        if items.is_empty() {
+            out.push_str("_None_\n");
+        }
+        for item in items {
+            out.push_str(&format!("- {}\n", line(item)));
+        }
+    }
+
+    fn task_label(task: &ReportTask) -> String {
+        match &task.task_key {
+            Some(key) => format!("{} {}", key, task.title),
+            None => task.title.clone(),
+        }
+    }
+
+    fn format_duration(seconds: i64) -> String {
+        let minutes = seconds / 60;
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+
+    fn format_date(timestamp: i64) -> String {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+
+    #[test]
+    fn completion_follows_the_last_status_of_the_workflow() {
+        let conn = test_support::open_db();
+        let project = test_support::project(&conn, "reading");
+        let mut summarized = test_support::new_task(&project.id, "Summarized");
+        summarized.status = "summarized".to_string();
+        summarized.due_date = Some(120);
+        summarized.completed_at = Some(150);
+        // "done" is just another step once the workflow ends in "summarized"
+        let mut skimmed = test_support::new_task(&project.id, "Skimmed");
+        skimmed.status = "done".to_string();
+        skimmed.due_date = Some(120);
+        let ids = [summarized.id.clone(), skimmed.id.clone()];
+
+        let report = ReportService::build(&project, "summarized", 100, 200, vec![summarized, skimmed], Vec::new(), None);
+        let completed: Vec<&str> = report.tasks_completed.iter().map(|t| t.id.as_str()).collect();
+        let overdue: Vec<&str> = report.overdue.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(completed, [ids[0].as_str()]);
+        assert_eq!(overdue, [ids[1].as_str()]);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateResearchQuestionDto, ResearchQuestion, ResearchQuestionLinks, UpdateResearchQuestionDto,
+    RESEARCH_QUESTION_STATUSES,
+};
+use crate::services::{DbService, SettingsService};
+use crate::state::AppState;
+use crate::utils::validate::Validate;
+use rusqlite::Connection;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Research question service for business logic
+pub struct ResearchQuestionService;
+
+impl ResearchQuestionService {
+    /// Create a new open research question
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_question(state: &AppState, mut data: CreateResearchQuestionDto) -> AppResult<ResearchQuestion> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            let now = chrono::Utc::now().timestamp();
+            let question = ResearchQuestion {
+                id: Uuid::new_v4().to_string(),
+                project_id: data.project_id,
+                question: data.question,
+                status: "open".to_string(),
+                answer_summary: None,
+                created_at: now,
+                updated_at: now,
+            };
+
+            if DbService::get_project_by_id(conn, &question.project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", question.project_id));
+            }
+            DbService::with_busy_retry(|| DbService::insert_research_question(conn, &question))?;
+            Ok(question)
+        }).await
+    }
+
+    /// List research questions of a project
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_questions(state: &AppState, project_id: String) -> AppResult<Vec<ResearchQuestion>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            DbService::get_research_questions_by_project(conn, &project_id)
+        }).await
+    }
+
+    /// Get research question by ID
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_question(state: &AppState, id: String) -> AppResult<ResearchQuestion> {
+        state.run(move |conn| {
+            let question = DbService::get_research_question_by_id(conn, &id)?;
+            question.ok_or(AppError::NotFound("Research question", id))
+        }).await
+    }
+
+    /// Update a research question.
+    ///
+    /// Marking it answered needs an answer summary and at least one linked note
+    /// as evidence.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_question(
+        state: &AppState,
+        id: String,
+        mut data: UpdateResearchQuestionDto,
+    ) -> AppResult<ResearchQuestion> {
+        state.run(move |conn| {
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            if let Some(status) = &data.status {
+                if !RESEARCH_QUESTION_STATUSES.contains(&status.as_str()) {
+                    return Err(AppError::InvalidInput(format!(
+                        "Invalid status '{}'. Must be one of: {}",
+                        status,
+                        RESEARCH_QUESTION_STATUSES.join(", ")
+                    )));
+                }
+            }
+
+            let current = DbService::get_research_question_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Research question", id.clone()))?;
+
+            if data.status.as_deref() == Some("answered") {
+                let has_summary = data
+                    .answer_summary
+                    .as_deref()
+                    .or(current.answer_summary.as_deref())
+                    .is_some_and(|s| !s.trim().is_empty());
+                if !has_summary {
+                    return Err(AppError::InvalidInput(
+                        "An answered question needs an answer summary".into(),
+                    ));
+                }
+                if DbService::get_question_links(conn, &id)?.note_ids.is_empty() {
+                    return Err(AppError::InvalidInput(
+                        "Link at least one note as evidence before answering the question".into(),
+                    ));
+                }
+            }
+
+            DbService::with_busy_retry(|| DbService::update_research_question(
+                conn,
+                &id,
+                data.question.as_deref(),
+                data.status.as_deref(),
+                data.answer_summary.as_deref(),
+            ))?;
+
+            let question = DbService::get_research_question_by_id(conn, &id)?;
+            question.ok_or(AppError::NotFound("Research question", id))
+        }).await
+    }
+
+    /// Delete a research question and its links
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_question(state: &AppState, id: String) -> AppResult<()> {
+        state.run(move |conn| {
+            if !DbService::with_busy_retry(|| DbService::delete_research_question(conn, &id))? {
+                return Err(AppError::NotFound("Research question", id));
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Link a note of the same project to a research question
+    #[instrument(level = "debug", skip_all, fields(question_id = %question_id, note_id = %note_id))]
+    pub async fn link_note(state: &AppState, question_id: String, note_id: String) -> AppResult<ResearchQuestionLinks> {
+        state.run(move |conn| {
+            let question = Self::require_question(conn, &question_id)?;
+            let note = DbService::get_note_by_id(conn, &note_id)?
+                .ok_or_else(|| AppError::NotFound("Note", note_id.clone()))?;
+            if note.project_id.as_deref() != Some(question.project_id.as_str()) {
+                return Err(AppError::Conflict("Note belongs to a different project than the question".into()));
+            }
+
+            DbService::with_busy_retry(|| DbService::link_question_note(conn, &question_id, &note_id))?;
+            DbService::get_question_links(conn, &question_id)
+        }).await
+    }
+
+    /// Unlink a note from a research question. The last note of an answered question cannot be unlinked.
+    #[instrument(level = "debug", skip_all, fields(question_id = %question_id, note_id = %note_id))]
+    pub async fn unlink_note(state: &AppState, question_id: String, note_id: String) -> AppResult<ResearchQuestionLinks> {
+        state.run(move |conn| {
+            let question = Self::require_question(conn, &question_id)?;
+            let links = DbService::get_question_links(conn, &question_id)?;
+            if !links.note_ids.contains(&note_id) {
+                return Err(AppError::NotFound("Linked note", note_id));
+            }
+            if question.status == "answered" && links.note_ids.len() == 1 {
+                return Err(AppError::InvalidInput(
+                    "Cannot unlink the last evidence note of an answered question".into(),
+                ));
+            }
+
+            DbService::with_busy_retry(|| DbService::unlink_question_note(conn, &question_id, &note_id))?;
+            DbService::get_question_links(conn, &question_id)
+        }).await
+    }
+
+    /// Link a task of the same project to a research question
+    #[instrument(level = "debug", skip_all, fields(question_id = %question_id, task_id = %task_id))]
+    pub async fn link_task(state: &AppState, question_id: String, task_id: String) -> AppResult<ResearchQuestionLinks> {
+        state.run(move |conn| {
+            let question = Self::require_question(conn, &question_id)?;
+            let task = DbService::get_task_by_id(conn, &task_id)?
+                .ok_or_else(|| AppError::NotFound("Task", task_id.clone()))?;
+            if task.project_id != question.project_id {
+                return Err(AppError::Conflict("Task belongs to a different project than the question".into()));
+            }
+
+            DbService::with_busy_retry(|| DbService::link_question_task(conn, &question_id, &task_id))?;
+            DbService::get_question_links(conn, &question_id)
+        }).await
+    }
+
+    /// Unlink a task from a research question
+    #[instrument(level = "debug", skip_all, fields(question_id = %question_id, task_id = %task_id))]
+    pub async fn unlink_task(state: &AppState, question_id: String, task_id: String) -> AppResult<ResearchQuestionLinks> {
+        state.run(move |conn| {
+            Self::require_question(conn, &question_id)?;
+            if !DbService::with_busy_retry(|| DbService::unlink_question_task(conn, &question_id, &task_id))? {
+                return Err(AppError::NotFound("Linked task", task_id));
+            }
+            DbService::get_question_links(conn, &question_id)
+        }).await
+    }
+
+    /// List notes and tasks linked to a research question
+    #[instrument(level = "debug", skip_all, fields(question_id = %question_id))]
+    pub async fn list_links(state: &AppState, question_id: String) -> AppResult<ResearchQuestionLinks> {
+        state.run(move |conn| {
+            Self::require_question(conn, &question_id)?;
+            DbService::get_question_links(conn, &question_id)
+        }).await
+    }
+
+    fn require_question(conn: &Connection, id: &str) -> AppResult<ResearchQuestion> {
+        DbService::get_research_question_by_id(conn, id)?
+            .ok_or_else(|| AppError::NotFound("Research question", id.to_string()))
+    }
+}
@@ -0,0 +1,254 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{GlobalSearchResult, MatchKind, SearchDateRange, SearchHit, SETTING_FUZZY_MATCH_THRESHOLD};
+use crate::services::{DbService, SettingsService};
+use crate::state::AppState;
+use crate::utils::{fuzzy, timezone};
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+use tracing::instrument;
+
+/// Results returned when no limit is given
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Most results returned by one search
+const MAX_SEARCH_LIMIT: usize = 200;
+
+/// Characters of description or content shown around a match
+const SNIPPET_CHARS: usize = 160;
+
+/// Most fuzzy matches added to a project search
+const MAX_FUZZY_RESULTS: usize = 50;
+
+/// Search across all non-archived projects
+pub struct SearchService;
+
+impl SearchService {
+    /// Find projects, tasks and notes matching every term of `query`, most relevant
+    /// first, optionally only those updated within `range`
+    #[instrument(level = "debug", skip_all)]
+    pub async fn global_search(
+        state: &AppState,
+        query: String,
+        limit: Option<usize>,
+        range: Option<SearchDateRange>,
+    ) -> AppResult<Vec<GlobalSearchResult>> {
+        let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+        if limit == 0 || limit > MAX_SEARCH_LIMIT {
+            return Err(AppError::InvalidInput(format!("Limit must be between 1 and {}", MAX_SEARCH_LIMIT)));
+        }
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        state.run(move |conn| {
+            let (after, before) = Self::updated_bounds(conn, range.as_ref(), chrono::Utc::now().timestamp())?;
+            DbService::global_search(conn, &query, limit, SNIPPET_CHARS, after, before)
+        }).await
+    }
+
+    /// Bounds on `updated_at` of a search range: its period worked out in the
+    /// timezone setting as of `now`, or else its explicit bounds
+    pub(crate) fn updated_bounds(
+        conn: &Connection,
+        range: Option<&SearchDateRange>,
+        now: i64,
+    ) -> AppResult<(Option<i64>, Option<i64>)> {
+        let Some(range) = range else {
+            return Ok((None, None));
+        };
+        if let Some(period) = range.period {
+            let offset = SettingsService::utc_offset(conn, now)?;
+            let (start, end) = timezone::period_bounds(period, now, offset)
+                .ok_or_else(|| AppError::InvalidInput("Month must be between 1 and 12".into()))?;
+            return Ok((Some(start), Some(end)));
+        }
+        if let (Some(after), Some(before)) = (range.updated_after, range.updated_before) {
+            if after >= before {
+                return Err(AppError::InvalidInput("updated_after must be earlier than updated_before".into()));
+            }
+        }
+        Ok((range.updated_after, range.updated_before))
+    }
+
+    /// Add fuzzy title matches to the `exact` results of a project search and tag
+    /// every hit with how it matched, best first. `candidates` lists (id, title)
+    /// pairs whose title shares a trigram with the query; `load` reads the
+    /// entities of the candidates similar enough to count.
+    pub(crate) fn with_fuzzy_matches<T>(
+        conn: &Connection,
+        query: &str,
+        exact: Vec<T>,
+        id: impl Fn(&T) -> &str,
+        title: impl Fn(&T) -> &str,
+        candidates: impl FnOnce(&[String]) -> AppResult<Vec<(String, String)>>,
+        load: impl FnOnce(&[String]) -> AppResult<Vec<T>>,
+    ) -> AppResult<Vec<SearchHit<T>>> {
+        let threshold = SettingsService::get_i64(conn, SETTING_FUZZY_MATCH_THRESHOLD)?.clamp(1, 100) as f64 / 100.0;
+        let found: HashSet<String> = exact.iter().map(|item| id(item).to_string()).collect();
+
+        let mut similar: Vec<(String, f64)> = candidates(&fuzzy::search_trigrams(query))?
+            .into_iter()
+            .filter(|(candidate_id, _)| !found.contains(candidate_id))
+            .map(|(candidate_id, candidate_title)| (candidate_id, fuzzy::title_similarity(query, &candidate_title)))
+            .filter(|(_, score)| *score >= threshold)
+            .collect();
+        similar.sort_by(|a, b| b.1.total_cmp(&a.1));
+        similar.truncate(MAX_FUZZY_RESULTS);
+
+        let scores: HashMap<String, f64> = similar.into_iter().collect();
+        let ids: Vec<String> = scores.keys().cloned().collect();
+        let fuzzy_hits = load(&ids)?.into_iter().filter_map(|item| {
+            let score = *scores.get(id(&item))?;
+            Some(SearchHit { item, match_kind: MatchKind::Fuzzy, score })
+        });
+
+        let phrase = query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let mut hits: Vec<SearchHit<T>> = exact
+            .into_iter()
+            .map(|item| {
+                let item_title = title(&item);
+                let match_kind = if item_title.to_lowercase().starts_with(&phrase) {
+                    MatchKind::Prefix
+                } else {
+                    MatchKind::Exact
+                };
+                let score = fuzzy::title_similarity(query, item_title);
+                SearchHit { item, match_kind, score }
+            })
+            .chain(fuzzy_hits)
+            .collect();
+
+        // Stable, so exact hits with equal scores keep the order SQL ranked them in
+        hits.sort_by(|a, b| a.match_kind.cmp(&b.match_kind).then(b.score.total_cmp(&a.score)));
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Note, SearchPeriod, SETTING_TIMEZONE};
+    use crate::services::{test_support, NoteService, TaskService};
+    use rusqlite::params;
+
+    fn range(updated_after: Option<i64>, updated_before: Option<i64>, period: Option<SearchPeriod>) -> Option<SearchDateRange> {
+        Some(SearchDateRange { updated_after, updated_before, period })
+    }
+
+    #[tokio::test]
+    async fn searches_can_be_limited_to_when_items_were_updated() {
+        let state = test_support::open_state();
+        let now = chrono::Utc::now().timestamp();
+        let long_ago = now - 400 * 86_400;
+        let project = {
+            let conn = &state.conn().unwrap();
+            SettingsService::set_string(conn, SETTING_TIMEZONE, "UTC").unwrap();
+            let project = test_support::project(conn, "Corpus");
+            test_support::note(conn, &project.id, "Tokenizer notes", "fresh");
+            let old = test_support::note(conn, &project.id, "Tokenizer draft", "stale");
+            conn.execute("UPDATE notes SET updated_at = ?1 WHERE id = ?2", params![long_ago, old.id]).unwrap();
+            test_support::task(conn, &project.id, "Tokenizer benchmark");
+            let old = test_support::task(conn, &project.id, "Tokenizer bug");
+            conn.execute("UPDATE tasks SET updated_at = ?1 WHERE id = ?2", params![long_ago, old.id]).unwrap();
+            project
+        };
+        let titles = |hits: Vec<SearchHit<crate::models::Note>>| hits.into_iter().map(|h| h.item.title).collect::<Vec<_>>();
+
+        let all = NoteService::search_notes(&state, project.id.clone(), "tokenizer".into(), false, None).await.unwrap();
+        assert_eq!(all.len(), 2);
+        let today = NoteService::search_notes(&state, project.id.clone(), "tokenizer".into(), false, range(None, None, Some(SearchPeriod::Today)))
+            .await
+            .unwrap();
+        assert_eq!(titles(today), vec!["Tokenizer notes"]);
+        let before = NoteService::search_notes(&state, project.id.clone(), "tokenizer".into(), false, range(None, Some(now - 86_400), None))
+            .await
+            .unwrap();
+        assert_eq!(titles(before), vec!["Tokenizer draft"]);
+        // Fuzzy matches respect the range too
+        let fuzzy = NoteService::search_notes(&state, project.id.clone(), "tokeniser draft".into(), false, range(Some(now - 60), None, None))
+            .await
+            .unwrap();
+        assert!(fuzzy.iter().all(|hit| hit.item.title != "Tokenizer draft"));
+
+        let tasks = TaskService::search_tasks(&state, project.id.clone(), "tokenizer".into(), None, range(None, None, Some(SearchPeriod::ThisWeek)))
+            .await
+            .unwrap();
+        assert_eq!(tasks.iter().map(|h| h.item.title.as_str()).collect::<Vec<_>>(), vec!["Tokenizer benchmark"]);
+
+        let global = SearchService::global_search(&state, "tokenizer".into(), None, range(Some(now - 86_400), None, None)).await.unwrap();
+        assert_eq!(global.len(), 2);
+        assert!(global.iter().all(|result| result.updated_at >= now - 86_400));
+        let global = SearchService::global_search(&state, "tokenizer".into(), None, None).await.unwrap();
+        assert_eq!(global.len(), 4);
+
+        let backwards = SearchService::global_search(&state, "tokenizer".into(), None, range(Some(now), Some(now - 1), None)).await;
+        assert!(matches!(backwards, Err(AppError::InvalidInput(_))));
+        let bad_month = SearchService::global_search(&state, "tokenizer".into(), None, range(None, None, Some(SearchPeriod::Month(13)))).await;
+        assert!(matches!(bad_month, Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn timezone_setting_is_checked() {
+        let conn = test_support::open_db();
+        assert!(SettingsService::set_string(&conn, SETTING_TIMEZONE, "Mars/Olympus").is_err());
+        SettingsService::set_string(&conn, SETTING_TIMEZONE, "UTC-5").unwrap();
+        assert_eq!(SettingsService::utc_offset(&conn, 0).unwrap(), -5 * 3600);
+    }
+
+    fn kinds<T>(hits: &[SearchHit<T>], title: impl Fn(&T) -> &str) -> Vec<(String, MatchKind)> {
+        hits.iter().map(|hit| (title(&hit.item).to_string(), hit.match_kind)).collect()
+    }
+
+    #[tokio::test]
+    async fn misspelled_titles_are_found_as_fuzzy_matches() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Methods");
+            test_support::note(conn, &project.id, "Bayesian inference", "priors");
+            test_support::note(conn, &project.id, "Baysian shortcuts", "typo in the title");
+            test_support::note(conn, &project.id, "Field work", "nothing alike");
+            test_support::task(conn, &project.id, "Read about Bayesian models");
+            test_support::task(conn, &project.id, "Book a room");
+            project
+        };
+
+        let notes = NoteService::search_notes(&state, project.id.clone(), "baysian".into(), false, None).await.unwrap();
+        assert_eq!(
+            kinds(&notes, |note| &note.title),
+            [("Baysian shortcuts".to_string(), MatchKind::Prefix), ("Bayesian inference".to_string(), MatchKind::Fuzzy)]
+        );
+        assert!(notes[1].score >= 0.5 && notes[1].score < 1.0, "{}", notes[1].score);
+
+        let tasks = TaskService::search_tasks(&state, project.id.clone(), "baysian models".into(), None, None).await.unwrap();
+        assert_eq!(kinds(&tasks, |task| &task.title), [("Read about Bayesian models".to_string(), MatchKind::Fuzzy)]);
+
+        SettingsService::set_i64(&state.conn().unwrap(), SETTING_FUZZY_MATCH_THRESHOLD, 95).unwrap();
+        let notes = NoteService::search_notes(&state, project.id.clone(), "baysian".into(), false, None).await.unwrap();
+        assert_eq!(kinds(&notes, |note| &note.title), [("Baysian shortcuts".to_string(), MatchKind::Prefix)]);
+    }
+
+    #[tokio::test]
+    async fn fuzzy_search_over_five_thousand_notes_is_fast() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Large");
+            let mut notes: Vec<Note> = (0..5_000)
+                .map(|i| test_support::new_note(Some(&project.id), &format!("Reading notes {} on topic {}", i, i % 97), "body"))
+                .collect();
+            notes.push(test_support::new_note(Some(&project.id), "Bayesian inference", "body"));
+            DbService::insert_notes(conn, &notes).unwrap();
+            project
+        };
+
+        let started = std::time::Instant::now();
+        let notes = NoteService::search_notes(&state, project.id, "baysian".into(), false, None).await.unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(kinds(&notes, |note| &note.title), [("Bayesian inference".to_string(), MatchKind::Fuzzy)]);
+        // Unoptimized test builds get more room than the 100 ms a release build has to meet
+        let budget = if cfg!(debug_assertions) { 1_000 } else { 100 };
+        assert!(elapsed.as_millis() < budget, "search took {:?}", elapsed);
+    }
+}
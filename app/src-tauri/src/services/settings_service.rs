@@ -0,0 +1,249 @@
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::instrument;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    setting_definition, SettingDefinition, SettingKind, SETTING_AUTOMATION_PORT, SETTING_DEFINITIONS, SETTING_NOTE_MAX_BYTES, SETTING_PROJECT_LAYOUT,
+    SETTING_PROJECT_SETTINGS_DEFAULTS, SETTING_TIMEZONE, SETTING_WEEKLY_DIGEST_WEEKDAY,
+};
+use crate::services::DbService;
+use crate::state::AppState;
+use crate::utils::limits::Limits;
+use crate::utils::{timezone, validate};
+
+/// Application-wide settings, stored as JSON values and read with their registered defaults
+pub struct SettingsService;
+
+impl SettingsService {
+    /// Get a setting, or its default when it was never set
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_setting(state: &AppState, key: String) -> AppResult<Value> {
+        state.run(move |conn| {
+            Self::get_value(conn, &key)
+        }).await
+    }
+
+    /// Change a known setting; the value must match the setting's type
+    #[instrument(level = "debug", skip_all)]
+    pub async fn set_setting(state: &AppState, key: String, value: Value) -> AppResult<Value> {
+        state.run(move |conn| {
+            DbService::with_busy_retry(|| Self::set_value(conn, &key, value.clone()))?;
+            Ok(value)
+        }).await
+    }
+
+    /// Get every known setting with its current or default value, plus any
+    /// stored setting that is no longer known
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_all_settings(state: &AppState) -> AppResult<Map<String, Value>> {
+        state.run(Self::all_settings).await
+    }
+
+    /// Every known setting with its current or default value, plus any stored
+    /// setting that is no longer known
+    pub fn all_settings(conn: &Connection) -> AppResult<Map<String, Value>> {
+        let mut settings = Map::new();
+        for (key, raw) in DbService::get_app_settings(conn)? {
+            if let Some(value) = Self::parse_stored(&key, &raw, setting_definition(&key)) {
+                settings.insert(key, value);
+            }
+        }
+        for definition in SETTING_DEFINITIONS {
+            if !settings.contains_key(definition.key) {
+                settings.insert(definition.key.to_string(), Self::default_value(definition));
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Current value of a setting. A known setting that was never set, or whose
+    /// stored value is unusable, gives its default; an unknown one gives null.
+    pub fn get_value(conn: &Connection, key: &str) -> AppResult<Value> {
+        let definition = setting_definition(key);
+        let stored = DbService::get_app_setting(conn, key)?
+            .and_then(|raw| Self::parse_stored(key, &raw, definition));
+
+        Ok(match (stored, definition) {
+            (Some(value), _) => value,
+            (None, Some(definition)) => Self::default_value(definition),
+            (None, None) => Value::Null,
+        })
+    }
+
+    pub fn get_string(conn: &Connection, key: &str) -> AppResult<String> {
+        match Self::get_value(conn, key)? {
+            Value::String(value) => Ok(value),
+            _ => Err(AppError::Internal(format!("Setting '{}' is not a string", key))),
+        }
+    }
+
+    pub fn get_bool(conn: &Connection, key: &str) -> AppResult<bool> {
+        Self::get_value(conn, key)?
+            .as_bool()
+            .ok_or_else(|| AppError::Internal(format!("Setting '{}' is not a boolean", key)))
+    }
+
+    pub fn get_i64(conn: &Connection, key: &str) -> AppResult<i64> {
+        Self::get_value(conn, key)?
+            .as_i64()
+            .ok_or_else(|| AppError::Internal(format!("Setting '{}' is not an integer", key)))
+    }
+
+    pub fn get_json<T: DeserializeOwned>(conn: &Connection, key: &str) -> AppResult<T> {
+        Ok(serde_json::from_value(Self::get_value(conn, key)?)?)
+    }
+
+    /// Input limits that come from the settings
+    pub fn limits(conn: &Connection) -> AppResult<Limits> {
+        let note_max_bytes = Self::get_i64(conn, SETTING_NOTE_MAX_BYTES)?;
+        Ok(Limits {
+            note_max_bytes: usize::try_from(note_max_bytes).ok().filter(|&max| max > 0).unwrap_or(usize::MAX),
+        })
+    }
+
+    /// Offset from UTC, in seconds, of the timezone setting at `now`; the
+    /// computer's own when the setting is empty
+    pub fn utc_offset(conn: &Connection, now: i64) -> AppResult<i32> {
+        let zone = Self::get_string(conn, SETTING_TIMEZONE)?;
+        if let Some(offset) = timezone::parse_utc_offset(&zone).filter(|_| !zone.trim().is_empty()) {
+            return Ok(offset);
+        }
+        let instant = chrono::DateTime::from_timestamp(now, 0).unwrap_or_default();
+        Ok(instant.with_timezone(&chrono::Local).offset().local_minus_utc())
+    }
+
+    /// Subdirectories created in new projects, trimmed and checked
+    pub fn project_layout(conn: &Connection) -> AppResult<Vec<String>> {
+        let mut dirs: Vec<String> = Self::get_json(conn, SETTING_PROJECT_LAYOUT)?;
+        validate::layout(SETTING_PROJECT_LAYOUT, &mut dirs)?;
+        Ok(dirs)
+    }
+
+    /// Store a known setting after checking the value against its type
+    pub fn set_value(conn: &Connection, key: &str, value: Value) -> AppResult<()> {
+        let definition = setting_definition(key)
+            .ok_or_else(|| AppError::InvalidInput(format!("Unknown setting '{}'", key)))?;
+        if !Self::matches_kind(definition.kind, &value) {
+            return Err(AppError::InvalidInput(format!(
+                "Setting '{}' expects {}",
+                key,
+                Self::kind_label(definition.kind)
+            )));
+        }
+        Self::check_structure(key, &value)?;
+
+        DbService::set_app_setting(conn, key, &value.to_string())
+    }
+
+    pub fn set_string(conn: &Connection, key: &str, value: &str) -> AppResult<()> {
+        Self::set_value(conn, key, Value::String(value.to_string()))
+    }
+
+    pub fn set_bool(conn: &Connection, key: &str, value: bool) -> AppResult<()> {
+        Self::set_value(conn, key, Value::Bool(value))
+    }
+
+    pub fn set_i64(conn: &Connection, key: &str, value: i64) -> AppResult<()> {
+        Self::set_value(conn, key, Value::from(value))
+    }
+
+    pub fn set_json<T: Serialize>(conn: &Connection, key: &str, value: &T) -> AppResult<()> {
+        Self::set_value(conn, key, serde_json::to_value(value)?)
+    }
+
+    fn default_value(definition: &SettingDefinition) -> Value {
+        serde_json::from_str(definition.default).unwrap_or(Value::Null)
+    }
+
+    /// Parse a stored value, dropping it with a warning when it is not valid
+    /// JSON or no longer matches the setting's type
+    fn parse_stored(key: &str, raw: &str, definition: Option<&SettingDefinition>) -> Option<Value> {
+        let value = match serde_json::from_str::<Value>(raw) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("Ignoring stored setting '{}': {}", key, e);
+                return None;
+            }
+        };
+
+        match definition {
+            Some(definition) if !Self::matches_kind(definition.kind, &value) => {
+                tracing::warn!(
+                    "Ignoring stored setting '{}': expected {}",
+                    key,
+                    Self::kind_label(definition.kind)
+                );
+                None
+            }
+            _ => Some(value),
+        }
+    }
+
+    fn matches_kind(kind: SettingKind, value: &Value) -> bool {
+        match kind {
+            SettingKind::String => value.is_string(),
+            SettingKind::Bool => value.is_boolean(),
+            SettingKind::Integer => value.is_i64(),
+            SettingKind::Json => true,
+        }
+    }
+
+    /// Check the shape of settings whose JSON value has one
+    fn check_structure(key: &str, value: &Value) -> AppResult<()> {
+        match key {
+            SETTING_PROJECT_LAYOUT => {
+                let mut dirs: Vec<String> = serde_json::from_value(value.clone()).map_err(|_| {
+                    AppError::InvalidInput(format!("Setting '{}' expects a list of folder paths", key))
+                })?;
+                validate::layout(key, &mut dirs)
+            }
+            SETTING_TIMEZONE => {
+                let zone = value.as_str().unwrap_or_default().trim();
+                if zone.is_empty() || timezone::parse_utc_offset(zone).is_some() {
+                    Ok(())
+                } else {
+                    Err(AppError::InvalidInput(format!(
+                        "Setting '{}' expects AoE, a UTC offset such as UTC+2 or a common abbreviation",
+                        key
+                    )))
+                }
+            }
+            SETTING_WEEKLY_DIGEST_WEEKDAY => match value.as_i64() {
+                Some(1..=7) => Ok(()),
+                _ => Err(AppError::InvalidInput(format!("Setting '{}' expects 1 (Monday) to 7 (Sunday)", key))),
+            },
+            SETTING_AUTOMATION_PORT => match value.as_i64() {
+                Some(1024..=65535) => Ok(()),
+                _ => Err(AppError::InvalidInput(format!("Setting '{}' expects a port from 1024 to 65535", key))),
+            },
+            SETTING_PROJECT_SETTINGS_DEFAULTS => {
+                let booleans_ok = value.as_object().is_some_and(|defaults| {
+                    ["auto_commit", "backup_enabled"]
+                        .iter()
+                        .all(|flag| defaults.get(*flag).is_none_or(Value::is_boolean))
+                });
+                if booleans_ok {
+                    Ok(())
+                } else {
+                    Err(AppError::InvalidInput(format!(
+                        "Setting '{}' expects a JSON object whose auto_commit and backup_enabled are booleans",
+                        key
+                    )))
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn kind_label(kind: SettingKind) -> &'static str {
+        match kind {
+            SettingKind::String => "a string",
+            SettingKind::Bool => "a boolean",
+            SettingKind::Integer => "an integer",
+            SettingKind::Json => "a JSON value",
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::instrument;
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Project, ProjectScanSummary, ProjectSort, StartupScanFiles, StartupScanReport, SETTING_STARTUP_SCAN_ENABLED};
+use crate::services::{DbService, FileIndexService, GitService, SettingsService};
+use crate::state::AppState;
+use crate::utils::research_json;
+
+/// Event sent with the report once the startup scan is done
+pub const STARTUP_SCAN_COMPLETE_EVENT: &str = "startup-scan-complete";
+
+/// App setting holding the report of the last startup scan
+const STARTUP_SCAN_REPORT_KEY: &str = "startup_scan_report";
+
+/// Time after which projects not yet started are left unscanned
+const STARTUP_SCAN_BUDGET: Duration = Duration::from_secs(10);
+
+/// Projects scanned at the same time
+const STARTUP_SCAN_WORKERS: usize = 2;
+
+/// Reconciles projects with changes made to their directories while the app was closed
+pub struct StartupScanService;
+
+impl StartupScanService {
+    /// Scan the projects in the background when the startup_scan_enabled
+    /// setting is on, then store the report and send it to the windows
+    pub fn start_if_enabled<R: Runtime>(app: &AppHandle<R>) {
+        let state = app.state::<AppState>().inner().clone();
+        let enabled = state.conn().and_then(|conn| SettingsService::get_bool(&conn, SETTING_STARTUP_SCAN_ENABLED));
+        match enabled {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                tracing::warn!("Failed to read the startup scan setting: {}", e);
+                return;
+            }
+        }
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            match Self::scan(&state, STARTUP_SCAN_BUDGET).await {
+                Ok(report) => {
+                    if let Err(e) = app.emit(STARTUP_SCAN_COMPLETE_EVENT, &report) {
+                        tracing::warn!("Failed to emit {}: {}", STARTUP_SCAN_COMPLETE_EVENT, e);
+                    }
+                }
+                Err(e) => tracing::warn!("Startup scan failed: {}", e),
+            }
+        });
+    }
+
+    /// Report of the last startup scan, None before the first one
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_startup_scan_report(state: &AppState) -> AppResult<Option<StartupScanReport>> {
+        state.run(|conn| {
+            Ok(DbService::get_app_setting(conn, STARTUP_SCAN_REPORT_KEY)?
+                .and_then(|raw| serde_json::from_str(&raw).ok()))
+        }).await
+    }
+
+    /// Re-index the files, count the uncommitted git changes and compare
+    /// research.json with the database for every project that is not archived,
+    /// and store the report. Projects whose turn comes after `budget` has run
+    /// out are listed unscanned.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn scan(state: &AppState, budget: Duration) -> AppResult<StartupScanReport> {
+        let started_at = chrono::Utc::now().timestamp();
+        let deadline = Instant::now() + budget;
+        let projects = state.run(|conn| DbService::get_all_projects(conn, false, ProjectSort::Recent)).await?;
+        let queue = Arc::new(Mutex::new(projects.into_iter().enumerate().collect::<VecDeque<_>>()));
+
+        let mut workers = Vec::with_capacity(STARTUP_SCAN_WORKERS);
+        for _ in 0..STARTUP_SCAN_WORKERS {
+            let state = state.clone();
+            let queue = Arc::clone(&queue);
+            workers.push(tauri::async_runtime::spawn(async move {
+                let mut scanned = Vec::new();
+                while Instant::now() < deadline {
+                    let Some((position, project)) = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front() else {
+                        break;
+                    };
+                    scanned.push((position, Self::scan_project(&state, project, started_at).await));
+                }
+                scanned
+            }));
+        }
+
+        let mut summaries = Vec::new();
+        for worker in workers {
+            let scanned = worker.await.map_err(|e| AppError::Internal(format!("Startup scan worker failed: {}", e)))?;
+            summaries.extend(scanned);
+        }
+        let skipped = std::mem::take(&mut *queue.lock().unwrap_or_else(|e| e.into_inner()));
+        summaries.extend(skipped.into_iter().map(|(position, project)| {
+            (position, ProjectScanSummary { project_id: project.id, name: project.name, ..ProjectScanSummary::default() })
+        }));
+        summaries.sort_by_key(|(position, _)| *position);
+
+        let report = StartupScanReport {
+            started_at,
+            finished_at: chrono::Utc::now().timestamp(),
+            projects: summaries.into_iter().map(|(_, summary)| summary).collect(),
+        };
+        let raw = serde_json::to_string(&report)?;
+        state.run(move |conn| DbService::with_busy_retry(|| DbService::set_app_setting(conn, STARTUP_SCAN_REPORT_KEY, &raw))).await?;
+        tracing::info!(
+            "Startup scan checked {} of {} projects",
+            report.projects.iter().filter(|project| project.scanned).count(),
+            report.projects.len()
+        );
+        Ok(report)
+    }
+
+    async fn scan_project(state: &AppState, project: Project, started_at: i64) -> ProjectScanSummary {
+        let mut summary = ProjectScanSummary {
+            project_id: project.id.clone(),
+            name: project.name.clone(),
+            scanned: true,
+            ..ProjectScanSummary::default()
+        };
+        if !Path::new(&project.path).is_dir() {
+            summary.error = Some(format!("Project directory {} not found", project.path));
+            return summary;
+        }
+
+        match Self::file_changes(state, &project.id, started_at).await {
+            Ok(files) => summary.files = files,
+            Err(e) => summary.error = Some(e.to_string()),
+        }
+
+        let path = project.path.clone();
+        let git = state.blocking(move |_| {
+            if !Path::new(&path).join(".git").exists() {
+                return Ok(None);
+            }
+            let status = GitService::status(&path)?;
+            Ok(Some(status.staged.len() + status.modified.len() + status.untracked.len()))
+        });
+        match git.await {
+            Ok(dirty) => summary.git_dirty = dirty,
+            Err(e) => summary.error = Some(e.to_string()),
+        }
+
+        summary.metadata_drift = Self::metadata_drift(&project);
+        summary
+    }
+
+    /// Re-index a project that was indexed before and count the files that
+    /// changed since; None when it never was
+    async fn file_changes(state: &AppState, project_id: &str, since: i64) -> AppResult<Option<StartupScanFiles>> {
+        let id = project_id.to_string();
+        let indexed = state.run(move |conn| Ok(!DbService::get_file_index(conn, &id)?.is_empty())).await?;
+        if !indexed {
+            return Ok(None);
+        }
+
+        FileIndexService::index_project_files(state, project_id.to_string()).await?;
+        let id = project_id.to_string();
+        let changes = state.run(move |conn| DbService::get_changed_files(conn, &id, since)).await?;
+        Ok(Some(StartupScanFiles {
+            added: changes.added.len(),
+            modified: changes.modified.len(),
+            deleted: changes.deleted.len(),
+        }))
+    }
+
+    /// Fields of a project's research.json that differ from its database row
+    fn metadata_drift(project: &Project) -> Vec<String> {
+        let metadata = match research_json::read(&project.path) {
+            Ok(metadata) => metadata,
+            Err(e) => return vec![e.to_string()],
+        };
+
+        let mut drift = Vec::new();
+        if metadata.get("title").and_then(Value::as_str) != Some(project.name.as_str()) {
+            drift.push("title".to_string());
+        }
+        let description = metadata.get("description").and_then(Value::as_str).filter(|d| !d.is_empty());
+        if description != project.description.as_deref().filter(|d| !d.is_empty()) {
+            drift.push("description".to_string());
+        }
+        drift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support;
+    use std::fs;
+
+    #[tokio::test]
+    async fn scan_reports_file_changes_and_metadata_drift() {
+        let state = test_support::open_state();
+        let (indexed, plain) = {
+            let conn = &state.conn().unwrap();
+            (test_support::project(conn, "Indexed"), test_support::project(conn, "Plain"))
+        };
+        let metadata = research_json::new_metadata("Indexed", indexed.description.as_deref(), "2026-01-01T00:00:00+00:00");
+        research_json::write(&indexed.path, &metadata).unwrap();
+        fs::write(Path::new(&indexed.path).join("old.txt"), "old").unwrap();
+        FileIndexService::index_project_files(&state, indexed.id.clone()).await.unwrap();
+        state.conn().unwrap().execute("UPDATE file_metadata SET first_indexed_at = first_indexed_at - 60", []).unwrap();
+
+        // Changed while the app was closed
+        fs::remove_file(Path::new(&indexed.path).join("old.txt")).unwrap();
+        fs::write(Path::new(&indexed.path).join("new.txt"), "new").unwrap();
+        let metadata = research_json::new_metadata("Renamed elsewhere", indexed.description.as_deref(), "2026-01-01T00:00:00+00:00");
+        research_json::write(&indexed.path, &metadata).unwrap();
+
+        let report = StartupScanService::scan(&state, Duration::from_secs(60)).await.unwrap();
+        let summary = |id: &str| report.projects.iter().find(|project| project.project_id == id).unwrap();
+
+        let first = summary(&indexed.id);
+        assert!(first.scanned);
+        // research.json itself is one of the indexed files
+        assert_eq!(first.files, Some(StartupScanFiles { added: 1, modified: 1, deleted: 1 }));
+        assert_eq!(first.git_dirty, None);
+        assert_eq!(first.metadata_drift, vec!["title".to_string()]);
+
+        let second = summary(&plain.id);
+        assert!(second.scanned);
+        assert_eq!(second.files, None);
+        assert_eq!(second.metadata_drift.len(), 1, "a missing research.json is reported");
+
+        let stored = StartupScanService::get_startup_scan_report(&state).await.unwrap().unwrap();
+        assert_eq!(stored.projects.len(), report.projects.len());
+    }
+
+    #[tokio::test]
+    async fn projects_past_the_budget_are_left_unscanned() {
+        let state = test_support::open_state();
+        {
+            let conn = &state.conn().unwrap();
+            test_support::project(conn, "One");
+            test_support::project(conn, "Two");
+        }
+        assert!(StartupScanService::get_startup_scan_report(&state).await.unwrap().is_none());
+
+        let report = StartupScanService::scan(&state, Duration::ZERO).await.unwrap();
+        assert_eq!(report.projects.len(), 2);
+        assert!(report.projects.iter().all(|project| !project.scanned && project.error.is_none()));
+    }
+}
@@ -0,0 +1,273 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{Tag, TagNode, TagUsage};
+use crate::services::DbService;
+use crate::state::AppState;
+use crate::utils::tag_path;
+use rusqlite::Connection;
+use tracing::instrument;
+
+/// Tag service for business logic
+pub struct TagService;
+
+impl TagService {
+    /// List tags with usage counts as a tree, optionally limited to one project
+    #[instrument(level = "debug", skip_all, fields(project_id = ?project_id))]
+    pub async fn list_tags(state: &AppState, project_id: Option<String>) -> AppResult<Vec<TagNode>> {
+        state.run(move |conn| {
+            let tags = DbService::get_tags_with_counts(conn, project_id.as_deref())?;
+            Ok(Self::build_tree(tags))
+        }).await
+    }
+
+    /// Rename a tag and every tag below it. Renaming to the name of another tag
+    /// (ignoring case) merges the two and returns the surviving tag.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn rename_tag(state: &AppState, id: String, new_name: String) -> AppResult<Tag> {
+        state.run(move |conn| {
+            let tag = DbService::get_tag_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Tag", id.clone()))?;
+            let new_name = Self::rename_path(conn, &tag.name, &new_name)?;
+
+            let tag = DbService::get_tag_by_name(conn, &new_name)?;
+            tag.ok_or(AppError::NotFound("Tag", new_name))
+        }).await
+    }
+
+    /// Rename a level of the hierarchy that may only exist through its children,
+    /// e.g. "method" when just "method/bayesian" is a tag. Returns the new tree.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn rename_tag_path(state: &AppState, path: String, new_path: String) -> AppResult<Vec<TagNode>> {
+        state.run(move |conn| {
+            Self::rename_path(conn, path.trim(), &new_path)?;
+            Ok(Self::build_tree(DbService::get_tags_with_counts(conn, None)?))
+        }).await
+    }
+
+    /// Set or clear the display color of a tag
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn set_tag_color(state: &AppState, id: String, color: Option<String>) -> AppResult<Tag> {
+        state.run(move |conn| {
+            let color = color.map(|c| c.trim().to_lowercase()).filter(|c| !c.is_empty());
+            if let Some(color) = color.as_deref() {
+                if !Self::is_hex_color(color) {
+                    return Err(AppError::InvalidInput(format!(
+                        "Invalid color '{}'. Use a hex color such as #3b82f6",
+                        color
+                    )));
+                }
+            }
+
+            if !DbService::with_busy_retry(|| DbService::set_tag_color(conn, &id, color.as_deref()))? {
+                return Err(AppError::NotFound("Tag", id));
+            }
+            let tag = DbService::get_tag_by_id(conn, &id)?;
+            tag.ok_or(AppError::NotFound("Tag", id))
+        }).await
+    }
+
+    /// Delete a tag and remove it from every project, task and note
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_tag(state: &AppState, id: String) -> AppResult<()> {
+        state.run(move |conn| {
+            if !DbService::with_busy_retry(|| DbService::delete_tag(conn, &id))? {
+                return Err(AppError::NotFound("Tag", id));
+            }
+            Ok(())
+        }).await
+    }
+
+    /// Rewrite the prefix `old` of every tag at or below it to `new_name`, merging
+    /// a tag into an existing one that already has its new name. Returns the
+    /// normalized new name.
+    fn rename_path(conn: &Connection, old: &str, new_name: &str) -> AppResult<String> {
+        let new_name = tag_path::normalize(new_name).map_err(|reason| AppError::InvalidInput(reason.into()))?;
+        if tag_path::is_within(&new_name, old) && !new_name.eq_ignore_ascii_case(old) {
+            return Err(AppError::InvalidInput("A tag cannot be moved below itself".into()));
+        }
+
+        // Shortest first: when a tag moves up a level, the tag that held its new
+        // name has already been renamed or merged away
+        let tags = DbService::get_tags_within(conn, old)?;
+        if tags.is_empty() {
+            return Err(AppError::NotFound("Tag", old.to_string()));
+        }
+
+        DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+            for tag in &tags {
+                let target = format!("{}{}", new_name, &tag.name[old.len()..]);
+                match DbService::get_tag_by_name(tx, &target)? {
+                    Some(existing) if existing.id != tag.id => DbService::merge_tags(tx, &tag.id, &existing.id)?,
+                    _ => DbService::rename_tag(tx, &tag.id, &target)?,
+                }
+            }
+            Ok(())
+        }))?;
+
+        Ok(new_name)
+    }
+
+    /// Arrange tags, ordered by name, into the hierarchy their names describe
+    fn build_tree(tags: Vec<TagUsage>) -> Vec<TagNode> {
+        let mut roots: Vec<TagNode> = Vec::new();
+
+        for usage in tags {
+            let name = usage.tag.name.clone();
+            let segments: Vec<&str> = tag_path::segments(&name).collect();
+            let Some((leaf, parents)) = segments.split_last() else { continue };
+
+            let mut level = &mut roots;
+            let mut path = String::new();
+            for segment in parents {
+                path.push_str(segment);
+                let index = Self::child_index(level, segment, &path);
+                level = &mut level[index].children;
+                path.push(tag_path::SEPARATOR);
+            }
+
+            path.push_str(leaf);
+            let index = Self::child_index(level, leaf, &path);
+            level[index].tag = Some(usage);
+        }
+
+        Self::sort_tree(&mut roots);
+        roots
+    }
+
+    /// Index of the child named `segment`, added as a bare level when missing
+    fn child_index(level: &mut Vec<TagNode>, segment: &str, path: &str) -> usize {
+        if let Some(index) = level.iter().position(|node| node.name.eq_ignore_ascii_case(segment)) {
+            return index;
+        }
+        level.push(TagNode {
+            name: segment.to_string(),
+            path: path.to_string(),
+            tag: None,
+            children: Vec::new(),
+        });
+        level.len() - 1
+    }
+
+    fn sort_tree(nodes: &mut [TagNode]) {
+        nodes.sort_by_cached_key(|node| node.name.to_lowercase());
+        for node in nodes {
+            Self::sort_tree(&mut node.children);
+        }
+    }
+
+    /// "#rgb" or "#rrggbb"
+    fn is_hex_color(color: &str) -> bool {
+        color
+            .strip_prefix('#')
+            .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EntityType, ListOptions, TagMatchMode, TaskFilterDto};
+    use crate::services::test_support;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    /// (path, whether the level is a tag itself) of every node, depth first
+    fn flatten(nodes: &[TagNode]) -> Vec<(String, bool)> {
+        nodes
+            .iter()
+            .flat_map(|node| std::iter::once((node.path.clone(), node.tag.is_some())).chain(flatten(&node.children)))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn parent_tags_match_descendants_and_rename_them() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Stats");
+            for (title, tags) in [
+                ("Priors", &["method/bayesian"][..]),
+                ("P-values", &["Method/Frequentist"]),
+                ("Reading list", &["methodology"]),
+                ("MCMC", &["method/bayesian/mcmc", "data"]),
+            ] {
+                let mut note = test_support::new_note(Some(&project.id), title, "");
+                note.tags = Some(strings(tags));
+                DbService::insert_note(conn, &note).unwrap();
+                let mut task = test_support::new_task(&project.id, title);
+                task.tags = Some(strings(tags));
+                DbService::insert_task_with_key(conn, &mut task).unwrap();
+            }
+            assert!(DbService::set_entity_tags(conn, EntityType::Note, "missing", &strings(&["method//x"])).is_err());
+            project
+        };
+
+        let tree = TagService::list_tags(&state, Some(project.id.clone())).await.unwrap();
+        assert_eq!(
+            flatten(&tree),
+            [
+                ("data".to_string(), true),
+                ("method".to_string(), false),
+                ("method/bayesian".to_string(), true),
+                ("method/bayesian/mcmc".to_string(), true),
+                ("Method/Frequentist".to_string(), true),
+                ("methodology".to_string(), true),
+            ]
+        );
+
+        let conn = &state.conn().unwrap();
+        let note_titles = |tags: &[&str], mode| -> Vec<String> {
+            let mut titles: Vec<String> = DbService::get_notes_by_tags(conn, &project.id, &strings(tags), mode, &[])
+                .unwrap()
+                .into_iter()
+                .map(|note| note.title)
+                .collect();
+            titles.sort();
+            titles
+        };
+        assert_eq!(note_titles(&["METHOD"], TagMatchMode::Any), ["MCMC", "P-values", "Priors"]);
+        assert_eq!(note_titles(&["method/bayesian"], TagMatchMode::Any), ["MCMC", "Priors"]);
+        assert_eq!(note_titles(&["method", "data"], TagMatchMode::All), ["MCMC"]);
+        assert!(note_titles(&["meth"], TagMatchMode::Any).is_empty());
+
+        let filter = TaskFilterDto { tags: Some(strings(&["method"])), ..Default::default() };
+        let tasks = DbService::filter_tasks(conn, &project.id, &filter, &ListOptions::default()).unwrap();
+        assert_eq!(tasks.total_count, 3);
+
+        let tree = TagService::rename_tag_path(&state, "method".into(), "approach".into()).await.unwrap();
+        let paths: Vec<String> = flatten(&tree).into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, ["approach", "approach/bayesian", "approach/bayesian/mcmc", "approach/Frequentist", "data", "methodology"]);
+        assert_eq!(note_titles(&["approach"], TagMatchMode::Any), ["MCMC", "P-values", "Priors"]);
+
+        let error = TagService::rename_tag_path(&state, "approach".into(), "approach/bayesian/x".into()).await.unwrap_err();
+        assert!(matches!(error, AppError::InvalidInput(_)));
+        let error = TagService::rename_tag_path(&state, "approach".into(), "/x".into()).await.unwrap_err();
+        assert!(matches!(error, AppError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn renaming_onto_an_existing_tag_merges_the_two() {
+        let state = test_support::open_state();
+        let (project, bayes) = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Stats");
+            for (title, tag) in [("Priors", "bayes"), ("Posterior", "method/bayesian")] {
+                let mut note = test_support::new_note(Some(&project.id), title, "");
+                note.tags = Some(strings(&[tag]));
+                DbService::insert_note(conn, &note).unwrap();
+            }
+            let bayes = DbService::get_tag_by_name(conn, "bayes").unwrap().unwrap();
+            (project, bayes)
+        };
+
+        let merged = TagService::rename_tag(&state, bayes.id.clone(), " method / bayesian ".into()).await.unwrap();
+        assert_eq!(merged.name, "method/bayesian");
+        assert_ne!(merged.id, bayes.id);
+
+        let conn = &state.conn().unwrap();
+        assert!(DbService::get_tag_by_id(conn, &bayes.id).unwrap().is_none());
+        let tagged = DbService::get_notes_by_tags(conn, &project.id, &strings(&["method/bayesian"]), TagMatchMode::Any, &[]).unwrap();
+        assert_eq!(tagged.len(), 2);
+    }
+}
@@ -1,5 +1,16 @@
 use crate::error::{AppError, AppResult};
-use crate::models::{CreateTaskDto, Task, UpdateTaskDto, TaskWithChildren};
+use crate::models::{
+    Changed, CreateTaskDto, EntityType, KanbanColumn, ListOptions, MoveResult, Paginated, RankTasksResult, RevertChangeDto, SearchDateRange, SearchHit, Task, TaskFilterDto, TagMatchMode, TaskProgress, TitleCollation, UpdateTaskDto, TaskWithChildren,
+    TaskWithProject, SETTING_RECURRENCE_KEEP_SCHEDULE,
+};
+use crate::services::{DbService, SearchService, SettingsService, UndoService};
+use crate::state::AppState;
+use crate::utils::recurrence::{self, Frequency, Recurrence};
+use crate::utils::validate::Validate;
+use chrono::Datelike;
+use rusqlite::Connection;
+use std::collections::HashSet;
+use tracing::instrument;
 use uuid::Uuid;
 
 /// Task service for business logic
@@ -7,153 +18,1006 @@ pub struct TaskService;
 
 impl TaskService {
     /// Create a new task
-    pub async fn create_task(data: CreateTaskDto) -> AppResult<Task> {
-        // Validate input
-        if data.title.is_empty() {
-            return Err(AppError::InvalidInput("Task title cannot be empty".into()));
-        }
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_task(state: &AppState, mut data: CreateTaskDto) -> AppResult<Task> {
+        state.run(move |conn| {
+            // Validate input
+            if data.project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+            data.validate(&SettingsService::limits(conn)?)?;
 
-        if data.project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+            let recurrence = match data.recurrence.as_deref().filter(|rule| !rule.is_empty()) {
+                Some(rule) => Some(Self::normalize_recurrence(rule, data.due_date)?),
+                None => None,
+            };
+            let remind_at = Self::resolve_reminder(data.remind_at, data.remind_before, data.due_date)?
+                .filter(|&at| at != 0);
 
-        let now = chrono::Utc::now().timestamp();
-        let task = Task {
-            id: Uuid::new_v4().to_string(),
-            project_id: data.project_id,
-            parent_id: data.parent_id,
-            title: data.title,
-            description: data.description,
-            status: data.status.unwrap_or_else(|| "todo".to_string()),
-            priority: data.priority.unwrap_or_else(|| "medium".to_string()),
-            due_date: data.due_date,
-            completed_at: None,
-            created_at: now,
-            updated_at: now,
-            order: data.order.unwrap_or(0),
-            tags: data.tags,
-        };
+            if DbService::get_project_by_id(conn, &data.project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", data.project_id));
+            }
+            let statuses = DbService::get_project_statuses(conn, &data.project_id)?;
+            let status = match data.status {
+                Some(status) => status,
+                None => statuses.first().cloned().unwrap_or_else(|| "todo".to_string()),
+            };
 
-        // TODO: Save to database via IPC to frontend repository
+            let now = chrono::Utc::now().timestamp();
+            let mut task = Task {
+                id: Uuid::new_v4().to_string(),
+                project_id: data.project_id,
+                parent_id: data.parent_id,
+                title: data.title,
+                description: data.description,
+                status,
+                priority: data.priority.unwrap_or_default(),
+                due_date: data.due_date,
+                completed_at: None,
+                created_at: now,
+                updated_at: now,
+                order: data.order.unwrap_or(0),
+                tags: data.tags,
+                task_key: None,
+                rank: None,
+                recurrence,
+                recurrence_parent_id: None,
+                remind_at,
+                reminded_at: None,
+                metadata: None,
+            };
+            if statuses.last() == Some(&task.status) {
+                task.completed_at = Some(now);
+            }
 
-        Ok(task)
+            Self::validate_status(conn, &task.project_id, &task.status)?;
+            if let Some(parent_id) = task.parent_id.as_deref() {
+                Self::validate_parent(conn, &task.project_id, parent_id)?;
+            }
+
+            DbService::with_busy_retry(|| DbService::insert_task_with_key(conn, &mut task))?;
+            Ok(task)
+        }).await
     }
 
-    /// Get all tasks for a project
-    pub async fn list_tasks(project_id: String) -> AppResult<Vec<Task>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+    /// Get a page of a project's tasks
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_tasks(state: &AppState, project_id: String, options: ListOptions) -> AppResult<Paginated<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
 
-        // TODO: Fetch from database
-        Ok(vec![])
+            DbService::get_tasks_page(conn, &project_id, &options)
+        }).await
     }
 
     /// Get root tasks (no parent) for a project
-    pub async fn list_root_tasks(project_id: String) -> AppResult<Vec<Task>> {
-        if project_id.is_empty() {
-            return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
-        }
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_root_tasks(state: &AppState, project_id: String) -> AppResult<Vec<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
 
-        // TODO: Fetch from database
-        Ok(vec![])
+            DbService::get_root_tasks(conn, &project_id)
+        }).await
     }
 
     /// Get subtasks for a parent task
-    pub async fn list_subtasks(parent_id: String) -> AppResult<Vec<Task>> {
-        if parent_id.is_empty() {
-            return Err(AppError::InvalidInput("Parent ID cannot be empty".into()));
-        }
+    #[instrument(level = "debug", skip_all, fields(parent_id = %parent_id))]
+    pub async fn list_subtasks(state: &AppState, parent_id: String) -> AppResult<Vec<Task>> {
+        state.run(move |conn| {
+            if parent_id.is_empty() {
+                return Err(AppError::InvalidInput("Parent ID cannot be empty".into()));
+            }
 
-        // TODO: Fetch from database
-        Ok(vec![])
+            DbService::get_subtasks(conn, &parent_id)
+        }).await
     }
 
     /// Get task by ID
-    pub async fn get_task(id: String) -> AppResult<Task> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
-        }
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_task(state: &AppState, id: String) -> AppResult<Task> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
 
-        // TODO: Fetch from database
-        Err(AppError::NotFound("Task", id))
+            let task = DbService::get_task_by_id(conn, &id)?;
+            task.ok_or(AppError::NotFound("Task", id))
+        }).await
     }
 
     /// Get task hierarchy (task with all descendants)
-    pub async fn get_task_hierarchy(id: String) -> AppResult<TaskWithChildren> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
-        }
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_task_hierarchy(state: &AppState, id: String) -> AppResult<TaskWithChildren> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
 
-        // TODO: Fetch from database with hierarchy
-        Err(AppError::NotFound("Task", id))
+            let task = DbService::get_task_by_id(conn, &id)?
+                .ok_or(AppError::NotFound("Task", id))?;
+            let mut visited = HashSet::new();
+            Self::build_hierarchy(conn, task, &mut visited)
+        }).await
     }
 
-    /// Update task
-    pub async fn update_task(id: String, _data: UpdateTaskDto) -> AppResult<Task> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
-        }
+    /// Update task. Setting the project's done status with `cascade` also completes every descendant.
+    /// With `expected_updated_at`, an outdated version is rejected with the stored task.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_task(state: &AppState, id: String, data: UpdateTaskDto, cascade: bool) -> AppResult<Task> {
+        Self::update_task_v2(state, id, data, cascade).await.map(|change| change.after)
+    }
+
+    /// Update task like `update_task`, returning it as it was before and after the update
+    /// Completing a repeating task, directly or through the cascade, creates its next occurrence.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn update_task_v2(state: &AppState, id: String, mut data: UpdateTaskDto, cascade: bool) -> AppResult<Changed<Task>> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
+
+            data.validate(&SettingsService::limits(conn)?)?;
+
+            let existing = DbService::get_task_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Task", id.clone()))?;
+            if let Some(status) = data.status.as_deref() {
+                Self::validate_status(conn, &existing.project_id, status)?;
+            }
+            if let Some(parent_id) = data.parent_id.as_deref() {
+                if parent_id == id {
+                    return Err(AppError::InvalidInput("A task cannot be its own parent".into()));
+                }
+                Self::validate_parent(conn, &existing.project_id, parent_id)?;
+                if DbService::is_in_subtree(conn, &id, parent_id)? {
+                    return Err(AppError::Conflict(
+                        "A task cannot be moved under itself or one of its subtasks".into(),
+                    ));
+                }
+            }
 
-        // TODO: Update in database
-        Err(AppError::NotFound("Task", id))
+            if let Some(rule) = data.recurrence.as_mut().filter(|rule| !rule.is_empty()) {
+                *rule = Self::normalize_recurrence(rule, data.due_date.or(existing.due_date))?;
+            }
+            data.remind_at = Self::resolve_reminder(data.remind_at, data.remind_before, data.due_date.or(existing.due_date))?;
+
+            DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                let before = DbService::get_task_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Task", id.clone()))?;
+                let done = DbService::get_done_status(tx, &before.project_id)?;
+                let completes_descendants = cascade && data.status.as_deref() == Some(done.as_str());
+                let recurring_descendants = if completes_descendants {
+                    DbService::get_open_recurring_descendants(tx, &id)?
+                } else {
+                    Vec::new()
+                };
+                // The task exists, so no row updated means it changed since the expected version
+                if !DbService::update_task(tx, &id, &data, cascade)? {
+                    return Err(AppError::edit_conflict("Task", &id, &before));
+                }
+                let after = DbService::get_task_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Task", id.clone()))?;
+                if before.status != done && after.status == done {
+                    Self::create_next_occurrences(tx, [&after])?;
+                }
+                Self::create_next_occurrences(tx, &recurring_descendants)?;
+                Ok(Changed::new(before, after))
+            }))
+        }).await
     }
 
-    /// Delete task (and all subtasks)
-    pub async fn delete_task(id: String) -> AppResult<()> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
-        }
+    /// Undo an update by writing back the task as it was before, unless it was
+    /// updated again since. Subtasks completed by a cascade stay done.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn revert_task(state: &AppState, id: String, payload: RevertChangeDto) -> AppResult<Task> {
+        state.run(move |conn| {
+            let before: Task = UndoService::decode(EntityType::Task, &id, payload.before)?;
+            if before.title.is_empty() {
+                return Err(AppError::InvalidInput("Task title cannot be empty".into()));
+            }
+
+            Self::validate_status(conn, &before.project_id, &before.status)?;
+            if let Some(parent_id) = before.parent_id.as_deref() {
+                Self::validate_parent(conn, &before.project_id, parent_id)?;
+                if DbService::is_in_subtree(conn, &id, parent_id)? {
+                    return Err(AppError::Conflict(
+                        "A task cannot be moved under itself or one of its subtasks".into(),
+                    ));
+                }
+            }
 
-        // TODO: Delete from database (cascade to subtasks)
-        Err(AppError::NotFound("Task", id))
+            DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                let current = DbService::get_task_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Task", id.clone()))?;
+                UndoService::ensure_unchanged(EntityType::Task, &id, current.updated_at, payload.updated_at)?;
+                if current.project_id != before.project_id {
+                    return Err(AppError::Conflict(format!("Task '{}' moved to another project", id)));
+                }
+                DbService::restore_task(tx, &before)?;
+                DbService::get_task_by_id(tx, &id)?
+                    .ok_or_else(|| AppError::NotFound("Task", id.clone()))
+            }))
+        }).await
     }
 
-    /// Move task to a different parent
-    pub async fn move_task(id: String, _new_parent_id: Option<String>) -> AppResult<Task> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
-        }
+    /// Get how many of a task's descendants are done
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn get_task_progress(state: &AppState, id: String) -> AppResult<TaskProgress> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
 
-        // TODO: Update parent_id in database
-        Err(AppError::NotFound("Task", id))
+            DbService::get_task_progress(conn, &id)?
+                .ok_or(AppError::NotFound("Task", id))
+        }).await
     }
 
-    /// Reorder task
-    pub async fn reorder_task(id: String, _new_order: i32) -> AppResult<Task> {
-        if id.is_empty() {
-            return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
-        }
+    /// Delete task and all subtasks, returning how many tasks were deleted.
+    /// They go to the trash unless `permanent` is set.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn delete_task(state: &AppState, id: String, permanent: bool) -> AppResult<usize> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
 
-        // TODO: Update order in database
-        Err(AppError::NotFound("Task", id))
+            let deleted = DbService::with_busy_retry(|| DbService::delete_task(conn, &id, !permanent))?;
+            if deleted == 0 {
+                return Err(AppError::NotFound("Task", id));
+            }
+            Ok(deleted)
+        }).await
+    }
+
+    /// Move task to a different parent, or to the root with None, as its last child
+    #[instrument(level = "debug", skip_all, fields(id = %id, new_parent_id = ?new_parent_id))]
+    pub async fn move_task(state: &AppState, id: String, new_parent_id: Option<String>) -> AppResult<Task> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
+            if new_parent_id.as_deref().is_some_and(str::is_empty) {
+                return Err(AppError::InvalidInput("Parent task ID cannot be empty".into()));
+            }
+
+            if !DbService::with_busy_retry(|| DbService::move_task(conn, &id, new_parent_id.as_deref()))? {
+                return Err(AppError::NotFound("Task", id));
+            }
+            let task = DbService::get_task_by_id(conn, &id)?;
+            task.ok_or(AppError::NotFound("Task", id))
+        }).await
+    }
+
+    /// Move a task to a position among its siblings; positions past the end
+    /// put it last and negative ones first
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn reorder_task(state: &AppState, id: String, new_order: i32) -> AppResult<Task> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
+
+            if !DbService::with_busy_retry(|| DbService::reorder_task(conn, &id, new_order))? {
+                return Err(AppError::NotFound("Task", id));
+            }
+            let task = DbService::get_task_by_id(conn, &id)?;
+            task.ok_or(AppError::NotFound("Task", id))
+        }).await
     }
 
     /// Get tasks by status
-    pub async fn list_tasks_by_status(project_id: String, status: String) -> AppResult<Vec<Task>> {
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_tasks_by_status(state: &AppState, project_id: String, status: String) -> AppResult<Vec<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            Self::validate_status(conn, &project_id, &status)?;
+            DbService::get_tasks_by_status(conn, &project_id, &status)
+        }).await
+    }
+
+    /// Get unarchived tasks carrying all or any of the given tags, or a tag below
+    /// them, and none of the excluded ones
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_tasks_by_tags(
+        state: &AppState,
+        project_id: String,
+        tags: Vec<String>,
+        match_mode: TagMatchMode,
+        exclude_tags: Vec<String>,
+    ) -> AppResult<Vec<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if tags.is_empty() {
+                return Err(AppError::InvalidInput("Tags cannot be empty".into()));
+            }
+
+            DbService::get_tasks_by_tags(conn, &project_id, &tags, match_mode, &exclude_tags)
+        }).await
+    }
+
+    /// Get a page of a project's tasks matching a filter
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn filter_tasks(
+        state: &AppState,
+        project_id: String,
+        filter: TaskFilterDto,
+        options: ListOptions,
+    ) -> AppResult<Paginated<Task>> {
         if project_id.is_empty() {
             return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
         }
 
-        // Validate status
-        if !["todo", "in_progress", "done"].contains(&status.as_str()) {
+        state.run(move |conn| DbService::filter_tasks(conn, &project_id, &filter, &options)).await
+    }
+
+    /// Get a task by its human-readable key (case-insensitive)
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_task_by_key(state: &AppState, project_id: String, key: String) -> AppResult<Task> {
+        state.run(move |conn| {
+            if key.trim().is_empty() {
+                return Err(AppError::InvalidInput("Task key cannot be empty".into()));
+            }
+
+            let task = DbService::get_task_by_key(conn, &project_id, &key)?;
+            task.ok_or(AppError::NotFound("Task", key))
+        }).await
+    }
+
+    /// Validate a status against the project's workflow
+    pub fn validate_status(conn: &Connection, project_id: &str, status: &str) -> AppResult<()> {
+        let statuses = DbService::get_project_statuses(conn, project_id)?;
+        if !statuses.iter().any(|s| s == status) {
             return Err(AppError::InvalidInput(format!(
-                "Invalid status '{}'. Must be one of: todo, in_progress, done",
-                status
+                "Invalid status '{}'. Must be one of: {}",
+                status,
+                statuses.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Parse a recurrence rule and return it in canonical form. A monthly rule
+    /// keeps the day of the month it started on, so a task due on the 31st
+    /// comes back on the last day of shorter months and on the 31st after them.
+    fn normalize_recurrence(rule: &str, due_date: Option<i64>) -> AppResult<String> {
+        let due_date = due_date.ok_or_else(|| AppError::InvalidInput("A repeating task needs a due date".into()))?;
+        let mut recurrence = Recurrence::parse(rule).map_err(AppError::InvalidInput)?;
+        if recurrence.frequency == Frequency::Monthly && recurrence.by_month_day.is_none() {
+            recurrence.by_month_day = chrono::DateTime::from_timestamp(due_date, 0).map(|due| due.day());
+        }
+        if !recurrence.allows(due_date) {
+            return Err(AppError::InvalidInput("The recurrence ends before the task is due".into()));
+        }
+        Ok(recurrence.to_rule())
+    }
+
+    /// The reminder time of a task, given as `remind_at` or as `remind_before`
+    /// minutes before its due date
+    fn resolve_reminder(remind_at: Option<i64>, remind_before: Option<i64>, due_date: Option<i64>) -> AppResult<Option<i64>> {
+        match (remind_at, remind_before) {
+            (Some(_), Some(_)) => Err(AppError::InvalidInput("Set remind_at or remind_before, not both".into())),
+            (None, Some(minutes)) => {
+                let due_date = due_date
+                    .ok_or_else(|| AppError::InvalidInput("remind_before needs a due date".into()))?;
+                Ok(Some(due_date - minutes * 60))
+            }
+            (remind_at, None) => Ok(remind_at),
+        }
+    }
+
+    /// Create the next occurrence of each repeating task in `completed`, which
+    /// were just marked done. Callers run this in the transaction that completed
+    /// them. An occurrence past UNTIL or beyond COUNT ends the series.
+    fn create_next_occurrences<'a>(conn: &Connection, completed: impl IntoIterator<Item = &'a Task>) -> AppResult<usize> {
+        let mut created = 0;
+        let keep_schedule = SettingsService::get_bool(conn, SETTING_RECURRENCE_KEEP_SCHEDULE)?;
+        let now = chrono::Utc::now().timestamp();
+
+        for task in completed {
+            let Some(rule) = task.recurrence.as_deref() else {
+                continue;
+            };
+            let rule = match Recurrence::parse(rule) {
+                Ok(rule) => rule,
+                Err(e) => {
+                    tracing::warn!("Task {} has an invalid recurrence rule: {}", task.id, e);
+                    continue;
+                }
+            };
+            let Some((due_date, next_rule)) = Self::next_occurrence(&rule, task.due_date, now, keep_schedule) else {
+                continue;
+            };
+
+            let status = DbService::get_project_statuses(conn, &task.project_id)?
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| "todo".to_string());
+            let mut next = Task {
+                id: Uuid::new_v4().to_string(),
+                project_id: task.project_id.clone(),
+                parent_id: task.parent_id.clone(),
+                title: task.title.clone(),
+                description: task.description.clone(),
+                status,
+                priority: task.priority,
+                due_date: Some(due_date),
+                completed_at: None,
+                created_at: now,
+                updated_at: now,
+                order: task.order,
+                tags: task.tags.clone(),
+                task_key: None,
+                rank: None,
+                recurrence: Some(next_rule.to_rule()),
+                recurrence_parent_id: Some(task.id.clone()),
+                // The reminder keeps its distance from the due date
+                remind_at: task.remind_at.zip(task.due_date).map(|(remind_at, due)| due_date - (due - remind_at)),
+                reminded_at: None,
+                metadata: None,
+            };
+            DbService::insert_task_with_key(conn, &mut next)?;
+            created += 1;
+        }
+
+        Ok(created)
+    }
+
+    /// Due date and rule of the occurrence after one due at `due_date`, or None
+    /// when the series is over. An occurrence completed late repeats from the day
+    /// it was completed, at its usual time, unless `keep_schedule` holds it to
+    /// the original dates.
+    fn next_occurrence(rule: &Recurrence, due_date: Option<i64>, now: i64, keep_schedule: bool) -> Option<(i64, Recurrence)> {
+        let next_rule = rule.advance()?;
+        let due_date = due_date.unwrap_or(now);
+        let from = if due_date < now && !keep_schedule {
+            let today = chrono::DateTime::from_timestamp(now, 0)?.date_naive();
+            recurrence::at_time_of(today, due_date)
+        } else {
+            due_date
+        };
+        let next = rule.next_after(from)?;
+        rule.allows(next).then_some((next, next_rule))
+    }
+
+    /// Check that a parent task exists and belongs to the same project
+    fn validate_parent(conn: &Connection, project_id: &str, parent_id: &str) -> AppResult<()> {
+        let parent = DbService::get_task_by_id(conn, parent_id)?
+            .ok_or_else(|| AppError::NotFound("Task", parent_id.to_string()))?;
+        if parent.project_id != project_id {
+            return Err(AppError::Conflict(format!(
+                "Parent task '{}' belongs to a different project",
+                parent_id
             )));
         }
+        Ok(())
+    }
+
+    /// Attach descendants to a task, skipping any task already visited so that
+    /// a corrupted parent chain cannot recurse forever
+    fn build_hierarchy(conn: &Connection, task: Task, visited: &mut HashSet<String>) -> AppResult<TaskWithChildren> {
+        visited.insert(task.id.clone());
+
+        let mut children = Vec::new();
+        for child in DbService::get_subtasks(conn, &task.id)? {
+            if visited.contains(&child.id) {
+                continue;
+            }
+            children.push(Self::build_hierarchy(conn, child, visited)?);
+        }
 
-        // TODO: Fetch from database
-        Ok(vec![])
+        Ok(TaskWithChildren { task, children })
     }
 
-    /// Search tasks
-    pub async fn search_tasks(project_id: String, _query: String) -> AppResult<Vec<Task>> {
+    /// List open tasks of all active projects due within `days` days of `now`
+    /// (defaults to the current time), soonest first
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_upcoming_tasks(state: &AppState, days: i64, now: Option<i64>) -> AppResult<Vec<TaskWithProject>> {
+        state.run(move |conn| {
+            if days <= 0 {
+                return Err(AppError::InvalidInput("Days must be greater than 0".into()));
+            }
+
+            let now = now.unwrap_or_else(|| chrono::Utc::now().timestamp());
+            let to = now.saturating_add(days.saturating_mul(86_400));
+
+            DbService::get_due_tasks(conn, Some(now), to)
+        }).await
+    }
+
+    /// List open tasks of all active projects due before `now` (defaults to the
+    /// current time), most overdue first
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_overdue_tasks(state: &AppState, now: Option<i64>) -> AppResult<Vec<TaskWithProject>> {
+        state.run(move |conn| {
+            let now = now.unwrap_or_else(|| chrono::Utc::now().timestamp());
+
+            DbService::get_due_tasks(conn, None, now)
+        }).await
+    }
+
+    /// Search tasks by title, key, description and tags, optionally within one status.
+    /// Tasks whose title is only similar to the query are added as fuzzy matches.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn search_tasks(
+        state: &AppState,
+        project_id: String,
+        query: String,
+        status: Option<String>,
+        range: Option<SearchDateRange>,
+    ) -> AppResult<Vec<SearchHit<Task>>> {
         if project_id.is_empty() {
             return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
         }
 
-        // TODO: Search in database
-        Ok(vec![])
+        state.run(move |conn| {
+            if let Some(status) = status.as_deref() {
+                Self::validate_status(conn, &project_id, status)?;
+            }
+            let (after, before) = SearchService::updated_bounds(conn, range.as_ref(), chrono::Utc::now().timestamp())?;
+            let in_range = |updated_at: i64| after.is_none_or(|a| updated_at >= a) && before.is_none_or(|b| updated_at < b);
+            let exact = DbService::search_tasks(conn, &project_id, &query, status.as_deref(), after, before)?;
+            SearchService::with_fuzzy_matches(
+                conn,
+                &query,
+                exact,
+                |task| &task.id,
+                |task| &task.title,
+                |trigrams| DbService::get_task_title_candidates(conn, &project_id, status.as_deref(), trigrams),
+                |ids| Ok(DbService::get_tasks_by_ids(conn, ids)?.into_iter().filter(|task| in_range(task.updated_at)).collect()),
+            )
+        }).await
+    }
+
+    /// Move tasks to another project
+    #[instrument(level = "debug", skip_all, fields(target_project_id = %target_project_id))]
+    pub async fn move_tasks_to_project(
+        state: &AppState,
+        task_ids: Vec<String>,
+        target_project_id: String,
+        keep_hierarchy: bool,
+    ) -> AppResult<MoveResult> {
+        state.run(move |conn| {
+            if target_project_id.is_empty() {
+                return Err(AppError::InvalidInput("Target project ID cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &target_project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", target_project_id));
+            }
+            DbService::with_busy_retry(|| DbService::move_tasks_to_project(conn, &task_ids, &target_project_id, keep_hierarchy))
+        }).await
+    }
+
+    /// Stack-rank tasks of a project in the given order (first = top)
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn rank_tasks(state: &AppState, project_id: String, ranked_ids: Vec<String>) -> AppResult<RankTasksResult> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::with_busy_retry(|| DbService::rank_tasks(conn, &project_id, &ranked_ids))
+        }).await
+    }
+
+    /// Get tasks of a project sorted by title
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_tasks_by_title(
+        state: &AppState,
+        project_id: String,
+        collation: Option<TitleCollation>,
+    ) -> AppResult<Vec<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            DbService::get_tasks_by_title(conn, &project_id, collation.unwrap_or_default())
+        }).await
+    }
+
+    /// Get tasks of a project sorted by stack rank
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_ranked_tasks(state: &AppState, project_id: String) -> AppResult<Vec<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            DbService::get_tasks_by_rank(conn, &project_id)
+        }).await
+    }
+
+    /// Get a project's Kanban board: one column per workflow status in workflow
+    /// order, then columns for statuses tasks still carry but the workflow dropped
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_kanban_board(state: &AppState, project_id: String) -> AppResult<Vec<KanbanColumn>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+
+            let mut columns: Vec<KanbanColumn> = DbService::get_project_statuses(conn, &project_id)?
+                .into_iter()
+                .map(|status| KanbanColumn { status, tasks: Vec::new() })
+                .collect();
+            for task in DbService::get_board_tasks(conn, &project_id)? {
+                match columns.iter_mut().find(|column| column.status == task.status) {
+                    Some(column) => column.tasks.push(task),
+                    None => columns.push(KanbanColumn { status: task.status.clone(), tasks: vec![task] }),
+                }
+            }
+            Ok(columns)
+        }).await
+    }
+
+    /// Move a task to a position (0 = top) in the board column of `new_status`.
+    /// The status is checked against the project's workflow as update_task does.
+    /// Moving a repeating task to the project's done status creates its next occurrence.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn move_task_on_board(
+        state: &AppState,
+        id: String,
+        new_status: String,
+        new_position: usize,
+    ) -> AppResult<Task> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
+
+            let existing = DbService::get_task_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Task", id.clone()))?;
+            Self::validate_status(conn, &existing.project_id, &new_status)?;
+            let done = DbService::get_done_status(conn, &existing.project_id)?;
+
+            let moved = DbService::with_busy_retry(|| DbService::with_tx(conn, |tx| {
+                if !DbService::move_task_on_board(tx, &id, &new_status, new_position)? {
+                    return Ok(false);
+                }
+                if existing.status != done && new_status == done {
+                    Self::create_next_occurrences(tx, [&existing])?;
+                }
+                Ok(true)
+            }))?;
+            if !moved {
+                return Err(AppError::NotFound("Task", id));
+            }
+            DbService::get_task_by_id(conn, &id)?
+                .ok_or(AppError::NotFound("Task", id))
+        }).await
+    }
+
+    /// Archive a project's done tasks completed before `completed_before`, along
+    /// with their subtrees; a task with anything unfinished below it stays.
+    /// Returns how many tasks were archived.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn archive_completed_tasks(state: &AppState, project_id: String, completed_before: i64) -> AppResult<usize> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::with_busy_retry(|| DbService::archive_completed_tasks(conn, &project_id, completed_before))
+        }).await
+    }
+
+    /// Get one page of a project's archived tasks, most recently completed first
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_archived_tasks(state: &AppState, project_id: String, options: ListOptions) -> AppResult<Paginated<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            DbService::get_archived_tasks_page(conn, &project_id, &options)
+        }).await
+    }
+
+    /// Bring an archived task back, with its subtree and archived ancestors
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn unarchive_task(state: &AppState, id: String) -> AppResult<Task> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
+
+            if DbService::get_task_by_id(conn, &id)?.is_none() {
+                return Err(AppError::NotFound("Task", id));
+            }
+            if DbService::with_busy_retry(|| DbService::unarchive_task(conn, &id))? == 0 {
+                return Err(AppError::Conflict(format!("Task {} is not archived", id)));
+            }
+            DbService::get_task_by_id(conn, &id)?
+                .ok_or(AppError::NotFound("Task", id))
+        }).await
+    }
+
+    /// Open repeating tasks of a project, soonest due first. Each is the current
+    /// occurrence of its series; completed occurrences are left out.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_recurring_tasks(state: &AppState, project_id: String) -> AppResult<Vec<Task>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::get_recurring_tasks(conn, &project_id)
+        }).await
+    }
+
+    /// Skip the current occurrence of a repeating task: move it to the date the
+    /// rule gives after its due date, using up one occurrence of a COUNT.
+    /// Fails with Conflict when the series has no occurrence left to move to.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn skip_next_occurrence(state: &AppState, id: String) -> AppResult<Task> {
+        state.run(move |conn| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("Task ID cannot be empty".into()));
+            }
+
+            let task = DbService::get_task_by_id(conn, &id)?
+                .ok_or_else(|| AppError::NotFound("Task", id.clone()))?;
+            let rule = task
+                .recurrence
+                .as_deref()
+                .ok_or_else(|| AppError::InvalidInput(format!("Task {} does not repeat", id)))?;
+            if task.status == DbService::get_done_status(conn, &task.project_id)? {
+                return Err(AppError::Conflict(format!("Task {} is already done", id)));
+            }
+            let due_date = task
+                .due_date
+                .ok_or_else(|| AppError::InvalidInput("A repeating task needs a due date".into()))?;
+
+            let rule = Recurrence::parse(rule).map_err(AppError::InvalidInput)?;
+            let (next_due, next_rule) = rule
+                .advance()
+                .zip(rule.next_after(due_date))
+                .filter(|(_, next_due)| rule.allows(*next_due))
+                .map(|(next_rule, next_due)| (next_due, next_rule))
+                .ok_or_else(|| AppError::Conflict("This is the last occurrence of the series".into()))?;
+
+            if !DbService::with_busy_retry(|| DbService::reschedule_task(conn, &id, next_due, &next_rule.to_rule()))? {
+                return Err(AppError::NotFound("Task", id));
+            }
+            DbService::get_task_by_id(conn, &id)?
+                .ok_or(AppError::NotFound("Task", id))
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskPriority;
+    use crate::services::test_support;
+    use std::time::Duration;
+
+    fn create_dto(project_id: &str, title: String) -> CreateTaskDto {
+        CreateTaskDto {
+            project_id: project_id.to_string(),
+            parent_id: None,
+            title,
+            description: None,
+            status: None,
+            priority: None,
+            due_date: None,
+            order: None,
+            tags: None,
+            recurrence: None,
+            remind_at: None,
+            remind_before: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_creates_and_lists_neither_fail_nor_deadlock() {
+        let state = test_support::open_state();
+        let project = test_support::project(&state.conn().unwrap(), "Thesis");
+
+        let calls: Vec<_> = (0..100)
+            .map(|i| {
+                let state = state.clone();
+                let project_id = project.id.clone();
+                tokio::spawn(async move {
+                    if i % 2 == 0 {
+                        TaskService::create_task(&state, create_dto(&project_id, format!("Task {}", i))).await.map(drop)
+                    } else {
+                        TaskService::list_tasks(&state, project_id, ListOptions::default()).await.map(drop)
+                    }
+                })
+            })
+            .collect();
+
+        let results = tokio::time::timeout(Duration::from_secs(60), join_all(calls))
+            .await
+            .expect("commands deadlocked");
+        for result in results {
+            result.expect("task panicked").expect("command failed");
+        }
+
+        let page = TaskService::list_tasks(&state, project.id, ListOptions { limit: Some(100), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(page.total_count, 50);
+        let keys: HashSet<_> = page.items.iter().map(|task| task.task_key.clone()).collect();
+        assert_eq!(keys.len(), 50, "task keys are unique");
+    }
+
+    async fn join_all<T>(handles: Vec<tokio::task::JoinHandle<T>>) -> Vec<Result<T, tokio::task::JoinError>> {
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            results.push(handle.await);
+        }
+        results
+    }
+
+    #[tokio::test]
+    async fn interleaved_task_edits_conflict_instead_of_overwriting() {
+        let state = test_support::open_state();
+        let task = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let task = test_support::task(conn, &project.id, "Write");
+            conn.execute("UPDATE tasks SET updated_at = 1000 WHERE id = ?1", [&task.id]).unwrap();
+            task
+        };
+        let update = |fields: serde_json::Value| -> UpdateTaskDto { serde_json::from_value(fields).unwrap() };
+
+        // Both windows loaded the task at updated_at 1000; the first one saves
+        let first = TaskService::update_task(&state, task.id.clone(), update(serde_json::json!({ "title": "Write intro", "expected_updated_at": 1000 })), false)
+            .await
+            .unwrap();
+        assert!(first.updated_at > 1000);
+
+        let second = TaskService::update_task(&state, task.id.clone(), update(serde_json::json!({ "priority": "high", "expected_updated_at": 1000 })), false).await;
+        match second {
+            Err(AppError::EditConflict { entity: "Task", id, current }) => {
+                assert_eq!(id, task.id);
+                assert_eq!(current["title"], "Write intro");
+                assert_eq!(current["updated_at"], first.updated_at);
+            }
+            other => panic!("expected an edit conflict, got {:?}", other),
+        }
+
+        // Merged against the stored version, or saved without a version, it applies
+        let merged = update(serde_json::json!({ "priority": "high", "expected_updated_at": first.updated_at }));
+        let merged = TaskService::update_task(&state, task.id.clone(), merged, false).await.unwrap();
+        assert_eq!((merged.title.as_str(), merged.priority), ("Write intro", TaskPriority::High));
+        let unchecked = TaskService::update_task(&state, task.id.clone(), update(serde_json::json!({ "title": "Last write" })), false).await.unwrap();
+        assert_eq!(unchecked.title, "Last write");
+
+        let missing = TaskService::update_task(&state, "missing".into(), update(serde_json::json!({ "expected_updated_at": 1 })), false).await;
+        assert!(matches!(missing, Err(AppError::NotFound("Task", _))));
+    }
+
+    #[tokio::test]
+    async fn a_stale_edit_in_the_same_second_is_rejected() {
+        let state = test_support::open_state();
+        // Stored a minute ahead of the clock, so every save below lands in the
+        // same second as far as the clock goes
+        let loaded = chrono::Utc::now().timestamp() + 60;
+        let task = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Thesis");
+            let task = test_support::task(conn, &project.id, "Write");
+            conn.execute("UPDATE tasks SET updated_at = ?1 WHERE id = ?2", rusqlite::params![loaded, task.id]).unwrap();
+            task
+        };
+        let update = |fields: serde_json::Value| -> UpdateTaskDto { serde_json::from_value(fields).unwrap() };
+
+        let first = update(serde_json::json!({ "title": "Write intro", "expected_updated_at": loaded }));
+        let first = TaskService::update_task(&state, task.id.clone(), first, false).await.unwrap();
+        assert_eq!(first.updated_at, loaded + 1);
+
+        let stale = update(serde_json::json!({ "priority": "high", "expected_updated_at": loaded }));
+        let stale = TaskService::update_task(&state, task.id.clone(), stale, false).await;
+        assert!(matches!(stale, Err(AppError::EditConflict { entity: "Task", .. })), "{:?}", stale);
+
+        let next = update(serde_json::json!({ "priority": "high", "expected_updated_at": first.updated_at }));
+        let next = TaskService::update_task(&state, task.id.clone(), next, false).await.unwrap();
+        assert_eq!(next.updated_at, loaded + 2);
+        assert_eq!((next.title.as_str(), next.priority), ("Write intro", TaskPriority::High));
+    }
+
+    #[tokio::test]
+    async fn completing_a_monthly_task_keeps_it_on_the_month_end() {
+        let state = test_support::open_state();
+        let project = test_support::project(&state.conn().unwrap(), "Reports");
+        let due = |month: u32, day: u32| {
+            chrono::NaiveDate::from_ymd_opt(2031, month, day).unwrap().and_hms_opt(17, 0, 0).unwrap().and_utc().timestamp()
+        };
+
+        let mut data = create_dto(&project.id, "Monthly report".into());
+        data.due_date = Some(due(1, 31));
+        data.recurrence = Some("FREQ=MONTHLY;COUNT=3".into());
+        let mut task = TaskService::create_task(&state, data).await.unwrap();
+        assert_eq!(task.recurrence.as_deref(), Some("FREQ=MONTHLY;BYMONTHDAY=31;COUNT=3"));
+
+        let mut due_dates = vec![task.due_date];
+        let done = || -> UpdateTaskDto { serde_json::from_value(serde_json::json!({ "status": "done" })).unwrap() };
+        for _ in 0..3 {
+            TaskService::update_task(&state, task.id.clone(), done(), false).await.unwrap();
+            let open = TaskService::list_tasks_by_status(&state, project.id.clone(), "todo".into()).await.unwrap();
+            match open.into_iter().next() {
+                Some(next) => {
+                    due_dates.push(next.due_date);
+                    task = next;
+                }
+                None => break,
+            }
+        }
+        assert_eq!(due_dates, [Some(due(1, 31)), Some(due(2, 28)), Some(due(3, 31))]);
+        assert_eq!(task.recurrence.as_deref(), Some("FREQ=MONTHLY;BYMONTHDAY=31;COUNT=1"));
+    }
+
+    #[tokio::test]
+    async fn listing_tasks_by_tags_combines_all_any_and_exclusions() {
+        let state = test_support::open_state();
+        let project = {
+            let conn = &state.conn().unwrap();
+            let project = test_support::project(conn, "Stats");
+            let tagged = [
+                ("Both", &["methods", "stats"][..]),
+                ("Methods", &["methods"]),
+                ("Stats draft", &["stats", "draft"]),
+                ("Both draft", &["methods", "stats", "draft"]),
+                ("Archived", &["methods", "stats"]),
+            ];
+            for (order, (title, tags)) in tagged.into_iter().enumerate() {
+                let mut task = test_support::new_task(&project.id, title);
+                task.order = order as i32;
+                task.tags = Some(tags.iter().map(|t| t.to_string()).collect());
+                DbService::insert_task_with_key(conn, &mut task).unwrap();
+            }
+            conn.execute("UPDATE tasks SET is_archived = 1 WHERE title = 'Archived'", []).unwrap();
+            project
+        };
+
+        let list = |tags: &[&str], mode, exclude: &[&str]| {
+            let state = state.clone();
+            let project_id = project.id.clone();
+            let tags = tags.iter().map(|t| t.to_string()).collect();
+            let exclude = exclude.iter().map(|t| t.to_string()).collect();
+            async move {
+                let tasks = TaskService::list_tasks_by_tags(&state, project_id, tags, mode, exclude).await.unwrap();
+                tasks.into_iter().map(|task| task.title).collect::<Vec<_>>()
+            }
+        };
+        use TagMatchMode::{All, Any};
+
+        // Archived tasks are left out and the rest keep their board order
+        assert_eq!(list(&["methods", "stats"], All, &[]).await, ["Both", "Both draft"]);
+        assert_eq!(list(&["methods", "stats"], Any, &[]).await, ["Both", "Methods", "Stats draft", "Both draft"]);
+        assert_eq!(list(&["methods", "stats"], Any, &["draft"]).await, ["Both", "Methods"]);
+        assert_eq!(list(&["stats"], All, &["DRAFT"]).await, ["Both"]);
+        assert!(list(&["unknown"], Any, &[]).await.is_empty());
+        assert!(list(&["stats", "unknown"], All, &[]).await.is_empty());
+
+        let error = TaskService::list_tasks_by_tags(&state, project.id.clone(), Vec::new(), Any, Vec::new()).await.unwrap_err();
+        assert!(matches!(error, AppError::InvalidInput(_)));
     }
 }
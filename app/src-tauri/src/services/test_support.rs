@@ -0,0 +1,139 @@
+//! Fixtures for the in-file tests of the services and utilities
+
+use rusqlite::Connection;
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::models::{Note, Project, ProjectStatus, Reference, Task, TaskPriority};
+use crate::services::DbService;
+use crate::state::AppState;
+
+/// A configured in-memory database migrated to the latest schema
+pub fn open_db() -> Connection {
+    let conn = Connection::open_in_memory().expect("open in-memory database");
+    DbService::configure(&conn).expect("configure connection");
+    DbService::init(&conn).expect("migrate schema");
+    conn
+}
+
+/// A new empty directory under the system temp directory
+pub fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("research-vault-test-{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).expect("create temp directory");
+    dir
+}
+
+/// App state on a new database file in its own temp directory
+pub fn open_state() -> AppState {
+    let state = AppState::new();
+    let path = temp_dir().join("research.db");
+    state.init_db(&path.to_string_lossy()).expect("open database");
+    state
+}
+
+/// An active project named `name` with its own, new temp directory, not yet inserted
+pub fn new_project(name: &str) -> Project {
+    let now = chrono::Utc::now().timestamp();
+    Project {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        path: temp_dir().to_string_lossy().into_owned(),
+        description: None,
+        status: ProjectStatus::Active,
+        created_at: now,
+        last_modified_at: now,
+        tags: None,
+        key_prefix: None,
+        is_favorite: false,
+        metadata: None,
+    }
+}
+
+/// Insert an active project named `name` with its own, new temp directory
+pub fn project(conn: &Connection, name: &str) -> Project {
+    let project = new_project(name);
+    DbService::insert_project(conn, &project).expect("insert project");
+    project
+}
+
+/// A "todo" task of `project_id`, not yet inserted
+pub fn new_task(project_id: &str, title: &str) -> Task {
+    let now = chrono::Utc::now().timestamp();
+    Task {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        parent_id: None,
+        title: title.to_string(),
+        description: None,
+        status: "todo".to_string(),
+        priority: TaskPriority::default(),
+        due_date: None,
+        completed_at: None,
+        created_at: now,
+        updated_at: now,
+        order: 0,
+        tags: None,
+        task_key: None,
+        rank: None,
+        recurrence: None,
+        recurrence_parent_id: None,
+        remind_at: None,
+        reminded_at: None,
+        metadata: None,
+    }
+}
+
+/// Insert a "todo" task of `project_id` with a fresh task key
+pub fn task(conn: &Connection, project_id: &str, title: &str) -> Task {
+    let mut task = new_task(project_id, title);
+    DbService::insert_task_with_key(conn, &mut task).expect("insert task");
+    task
+}
+
+/// A note of `project_id`, not yet inserted
+pub fn new_note(project_id: Option<&str>, title: &str, content: &str) -> Note {
+    let now = chrono::Utc::now().timestamp();
+    Note {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.map(str::to_string),
+        title: title.to_string(),
+        content: content.to_string(),
+        created_at: now,
+        updated_at: now,
+        tags: None,
+        is_pinned: false,
+        is_locked: false,
+        metadata: None,
+    }
+}
+
+/// Insert a note of `project_id`
+pub fn note(conn: &Connection, project_id: &str, title: &str, content: &str) -> Note {
+    let note = new_note(Some(project_id), title, content);
+    DbService::insert_note(conn, &note).expect("insert note");
+    note
+}
+
+/// Insert a "misc" reference of `project_id`
+pub fn reference(conn: &Connection, project_id: &str, citation_key: &str, title: &str) -> Reference {
+    let reference = Reference {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        citation_key: citation_key.to_string(),
+        entry_type: "misc".to_string(),
+        title: title.to_string(),
+        authors: Vec::new(),
+        year: None,
+        venue: None,
+        doi: None,
+        url: None,
+        abstract_text: None,
+        pdf_path: None,
+        created_at: chrono::Utc::now().timestamp(),
+        reading_status: "unread".to_string(),
+        tags: None,
+    };
+    DbService::upsert_reference(conn, &reference).expect("insert reference");
+    reference
+}
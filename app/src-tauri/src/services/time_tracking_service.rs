@@ -0,0 +1,128 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{TimeEntry, TimeSummary};
+use crate::services::DbService;
+use crate::state::AppState;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Timers and tracked time on tasks
+pub struct TimeTrackingService;
+
+impl TimeTrackingService {
+    /// Start a timer on a task. Only one timer runs at a time, across all projects.
+    #[instrument(level = "debug", skip_all, fields(task_id = %task_id))]
+    pub async fn start_timer(state: &AppState, task_id: String) -> AppResult<TimeEntry> {
+        state.run(move |conn| {
+            if DbService::get_task_by_id(conn, &task_id)?.is_none() {
+                return Err(AppError::NotFound("Task", task_id));
+            }
+
+            let entry = TimeEntry {
+                id: Uuid::new_v4().to_string(),
+                task_id,
+                started_at: chrono::Utc::now().timestamp(),
+                ended_at: None,
+                note: None,
+            };
+
+            DbService::with_busy_retry(|| {
+                if let Some(running) = DbService::get_running_time_entry(conn)? {
+                    return Err(AppError::Conflict(format!(
+                        "A timer is already running on task {}",
+                        running.task_id
+                    )));
+                }
+                DbService::insert_time_entry(conn, &entry)
+            })?;
+            Ok(entry)
+        }).await
+    }
+
+    /// Stop the running timer
+    #[instrument(level = "debug", skip_all)]
+    pub async fn stop_timer(state: &AppState) -> AppResult<TimeEntry> {
+        state.run(move |conn| {
+            let now = chrono::Utc::now().timestamp();
+            DbService::with_busy_retry(|| DbService::stop_running_time_entry(conn, now))?
+                .ok_or_else(|| AppError::Conflict("No timer is running".into()))
+        }).await
+    }
+
+    /// The running timer, if any
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_running_timer(state: &AppState) -> AppResult<Option<TimeEntry>> {
+        state.run(DbService::get_running_time_entry).await
+    }
+
+    /// Record time spent on a task without running a timer
+    #[instrument(level = "debug", skip_all, fields(task_id = %task_id))]
+    pub async fn add_manual_time_entry(
+        state: &AppState,
+        task_id: String,
+        started_at: i64,
+        ended_at: i64,
+        note: Option<String>,
+    ) -> AppResult<TimeEntry> {
+        state.run(move |conn| {
+            if ended_at <= started_at {
+                return Err(AppError::InvalidInput("A time entry must end after it starts".into()));
+            }
+            if DbService::get_task_by_id(conn, &task_id)?.is_none() {
+                return Err(AppError::NotFound("Task", task_id));
+            }
+
+            let entry = TimeEntry {
+                id: Uuid::new_v4().to_string(),
+                task_id,
+                started_at,
+                ended_at: Some(ended_at),
+                note: note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+            };
+
+            DbService::with_busy_retry(|| DbService::insert_time_entry(conn, &entry))?;
+            Ok(entry)
+        }).await
+    }
+
+    /// Time entries of a task, most recent first
+    #[instrument(level = "debug", skip_all, fields(task_id = %task_id))]
+    pub async fn list_time_entries(state: &AppState, task_id: String) -> AppResult<Vec<TimeEntry>> {
+        state.run(move |conn| DbService::get_time_entries_by_task(conn, &task_id)).await
+    }
+
+    /// Seconds tracked on a project between `from` and `to`, per task and per tag.
+    /// Entries overlapping the window are clipped to it; the running timer counts up to now.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn get_time_summary(
+        state: &AppState,
+        project_id: String,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> AppResult<TimeSummary> {
+        state.run(move |conn| {
+            if let (Some(from), Some(to)) = (from, to) {
+                if to < from {
+                    return Err(AppError::InvalidInput("The summary must end after it starts".into()));
+                }
+            }
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            let window_start = from.unwrap_or(i64::MIN);
+            let window_end = to.unwrap_or(i64::MAX);
+            let by_task = DbService::get_time_by_task(conn, &project_id, window_start, window_end, now)?;
+            let by_tag = DbService::get_time_by_tag(conn, &project_id, window_start, window_end, now)?;
+
+            Ok(TimeSummary {
+                total_seconds: by_task.iter().map(|t| t.seconds).sum(),
+                project_id,
+                from,
+                to,
+                by_task,
+                by_tag,
+            })
+        }).await
+    }
+}
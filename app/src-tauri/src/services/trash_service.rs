@@ -0,0 +1,67 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{EntityType, TrashEntry};
+use crate::services::{DbService, GitService};
+use crate::state::AppState;
+use tracing::instrument;
+
+/// Trash service: restoring and purging soft-deleted tasks and notes
+pub struct TrashService;
+
+impl TrashService {
+    /// List a project's trashed tasks and notes, most recently deleted first
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn list_trash(state: &AppState, project_id: String) -> AppResult<Vec<TrashEntry>> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            DbService::list_trash(conn, &project_id)
+        }).await
+    }
+
+    /// Put a trashed task or note back into its project
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn restore_from_trash(state: &AppState, entity_type: EntityType, id: String) -> AppResult<TrashEntry> {
+        state.blocking(move |state| {
+            if id.is_empty() {
+                return Err(AppError::InvalidInput("ID cannot be empty".into()));
+            }
+            if entity_type == EntityType::Project {
+                return Err(AppError::InvalidInput("Projects are archived, not trashed; use restore_project".into()));
+            }
+
+            let (entry, project_path) = {
+                let conn = &state.conn()?;
+                let entry = DbService::with_busy_retry(|| DbService::restore_from_trash(conn, entity_type, &id))?
+                    .ok_or_else(|| AppError::NotFound("Trash entry", id.clone()))?;
+                let project = DbService::get_project_by_id(conn, &entry.project_id)?;
+                (entry, project.map(|p| p.path))
+            };
+
+            if let (EntityType::Note, Some(path)) = (entity_type, project_path) {
+                GitService::auto_commit(&path, &format!("Restore note: {}", entry.title));
+            }
+            Ok(entry)
+        }).await
+    }
+
+    /// Permanently remove a project's trashed items, only those older than
+    /// `older_than_days` when given. Returns how many were removed.
+    #[instrument(level = "debug", skip_all, fields(project_id = %project_id))]
+    pub async fn empty_trash(state: &AppState, project_id: String, older_than_days: Option<u32>) -> AppResult<usize> {
+        state.run(move |conn| {
+            if project_id.is_empty() {
+                return Err(AppError::InvalidInput("Project ID cannot be empty".into()));
+            }
+
+            let deleted_before = older_than_days
+                .map(|days| chrono::Utc::now().timestamp() - i64::from(days) * 86_400);
+
+            if DbService::get_project_by_id(conn, &project_id)?.is_none() {
+                return Err(AppError::NotFound("Project", project_id));
+            }
+            DbService::with_busy_retry(|| DbService::empty_trash(conn, &project_id, deleted_before))
+        }).await
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{EntityType, RevertChangeDto, RevertedEntity};
+use crate::services::{NoteService, ProjectService, TaskService};
+use crate::state::AppState;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::instrument;
+
+/// Undo of single-entity updates made through the `*_v2` update commands
+pub struct UndoService;
+
+impl UndoService {
+    /// Write back the `before` value of an update, unless the entity was changed again since.
+    /// Returns Conflict when its update time no longer matches the one the payload recorded.
+    #[instrument(level = "debug", skip_all, fields(id = %id))]
+    pub async fn revert_change(
+        state: &AppState,
+        entity_type: EntityType,
+        id: String,
+        payload: RevertChangeDto,
+    ) -> AppResult<RevertedEntity> {
+        if id.is_empty() {
+            return Err(AppError::InvalidInput(format!("{} ID cannot be empty", entity_type.label())));
+        }
+
+        match entity_type {
+            EntityType::Project => ProjectService::revert_project(state, id, payload).await.map(RevertedEntity::Project),
+            EntityType::Task => TaskService::revert_task(state, id, payload).await.map(RevertedEntity::Task),
+            EntityType::Note => NoteService::revert_note(state, id, payload).await.map(RevertedEntity::Note),
+        }
+    }
+
+    /// Read the `before` value of an undo payload, which must describe entity `id`
+    pub(crate) fn decode<T: DeserializeOwned>(entity_type: EntityType, id: &str, before: Value) -> AppResult<T> {
+        if before.get("id").and_then(Value::as_str) != Some(id) {
+            return Err(AppError::InvalidInput(format!(
+                "Undo payload does not belong to {} '{}'",
+                entity_type.label(),
+                id
+            )));
+        }
+        serde_json::from_value(before).map_err(|e| AppError::InvalidInput(format!("Invalid undo payload: {}", e)))
+    }
+
+    /// Refuse the undo when the entity was updated after the change being undone
+    pub(crate) fn ensure_unchanged(entity_type: EntityType, id: &str, current: i64, recorded: i64) -> AppResult<()> {
+        if current != recorded {
+            return Err(AppError::Conflict(format!(
+                "{} '{}' was changed again; undoing would overwrite the newer edit",
+                entity_type.label(),
+                id
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::instrument;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{RecentVault, VaultInfo};
+use crate::state::AppState;
+use crate::utils::{path, sanitize};
+
+/// Event sent after another vault was opened, with its `VaultInfo`
+pub const VAULT_CHANGED_EVENT: &str = "vault:changed";
+
+/// File name of the database inside a vault folder
+pub const VAULT_DB_FILE: &str = "research.db";
+
+/// File in the app data folder remembering the last and recent vaults
+const VAULT_CONFIG_FILE: &str = "vaults.json";
+
+/// Most vaults listed as recent
+const MAX_RECENT_VAULTS: usize = 10;
+
+/// Contents of the vault config file. The database cannot hold this, since
+/// it is needed to find the database in the first place.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VaultConfig {
+    /// Vault opened at startup; None for the app data folder
+    current: Option<String>,
+    /// Most recently opened first
+    #[serde(default)]
+    recent: Vec<RecentVault>,
+}
+
+/// Where the research database lives, and switching between databases.
+/// A vault is a folder holding research.db; the app data folder is the
+/// default vault.
+pub struct VaultService;
+
+impl VaultService {
+    /// Folder of the vault to open at startup: the last one opened, or the app
+    /// data folder when none was or it is not reachable (e.g. an unmounted
+    /// synced folder)
+    pub fn startup_vault(app_data_dir: &Path) -> PathBuf {
+        let config = Self::read_config(app_data_dir);
+        match config.current {
+            Some(current) if Path::new(&current).join(VAULT_DB_FILE).is_file() => PathBuf::from(current),
+            Some(current) => {
+                tracing::warn!("Last vault {} is not available; opening the default vault", current);
+                app_data_dir.to_path_buf()
+            }
+            None => app_data_dir.to_path_buf(),
+        }
+    }
+
+    /// The open vault
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_vault_path(state: &AppState) -> AppResult<VaultInfo> {
+        let db_path = state.db_path().ok_or_else(|| AppError::System("Database not initialized".into()))?;
+        Ok(Self::vault_info(Path::new(&db_path)))
+    }
+
+    /// Close the current database and open the vault in folder `path`. A folder
+    /// without a database is reported as NotFound unless `create` is set, so the
+    /// frontend can offer to create one. The new database is migrated before the
+    /// switch; one written by a newer app fails with SchemaTooNew and the current
+    /// vault stays open.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn open_vault<R: Runtime>(app: &AppHandle<R>, state: &AppState, path: String, create: bool) -> AppResult<VaultInfo> {
+        let info = state.blocking(move |state| {
+            if path.trim().is_empty() {
+                return Err(AppError::InvalidInput("Vault path cannot be empty".into()));
+            }
+            let dir = path::expand_home(path.trim())
+                .ok_or_else(|| AppError::InvalidInput("The home folder is not known".into()))?;
+            if !dir.is_absolute() {
+                return Err(AppError::InvalidInput("The vault path must be absolute".into()));
+            }
+            if dir.exists() && !dir.is_dir() {
+                return Err(AppError::InvalidInput("The vault path must be a folder".into()));
+            }
+
+            let db_path = dir.join(VAULT_DB_FILE);
+            if !db_path.is_file() {
+                if !create {
+                    return Err(AppError::NotFound("Vault", dir.to_string_lossy().into_owned()));
+                }
+                fs::create_dir_all(&dir)?;
+            }
+
+            let db_path_str = db_path.to_string_lossy().into_owned();
+            if state.db_path().as_deref() != Some(db_path_str.as_str()) {
+                state.switch_db(&db_path_str)?;
+            }
+
+            let info = Self::vault_info(&db_path);
+            if let Some(app_data_dir) = sanitize::app_data_dir() {
+                Self::record_opened(Path::new(app_data_dir), &info.path);
+            }
+            Ok(info)
+        }).await?;
+
+        if let Err(e) = app.emit(VAULT_CHANGED_EVENT, &info) {
+            tracing::warn!("Failed to emit {}: {}", VAULT_CHANGED_EVENT, e);
+        }
+        Ok(info)
+    }
+
+    /// Vaults opened before, most recent first
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_recent_vaults() -> AppResult<Vec<RecentVault>> {
+        let app_data_dir = sanitize::app_data_dir()
+            .ok_or_else(|| AppError::System("App data folder is not known yet".into()))?;
+        let mut recent = Self::read_config(Path::new(app_data_dir)).recent;
+        for vault in &mut recent {
+            vault.available = Path::new(&vault.path).join(VAULT_DB_FILE).is_file();
+        }
+        Ok(recent)
+    }
+
+    /// Remember `vault` as the one to open at startup and move it to the top of
+    /// the recent list. Failing to write the config is logged, not returned:
+    /// the vault is open either way.
+    fn record_opened(app_data_dir: &Path, vault: &str) {
+        let mut config = Self::read_config(app_data_dir);
+        let is_default = Path::new(vault) == app_data_dir;
+        config.current = (!is_default).then(|| vault.to_string());
+
+        config.recent.retain(|recent| recent.path != vault);
+        config.recent.insert(0, RecentVault {
+            path: vault.to_string(),
+            last_opened_at: chrono::Utc::now().timestamp(),
+            available: true,
+        });
+        config.recent.truncate(MAX_RECENT_VAULTS);
+
+        let result = serde_json::to_string_pretty(&config)
+            .map_err(AppError::from)
+            .and_then(|json| fs::write(app_data_dir.join(VAULT_CONFIG_FILE), json).map_err(AppError::from));
+        if let Err(e) = result {
+            tracing::warn!("Failed to save the vault list: {}", e);
+        }
+    }
+
+    /// The vault config, or an empty one when it is missing or unreadable
+    fn read_config(app_data_dir: &Path) -> VaultConfig {
+        let raw = match fs::read_to_string(app_data_dir.join(VAULT_CONFIG_FILE)) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return VaultConfig::default(),
+            Err(e) => {
+                tracing::warn!("Failed to read {}: {}", VAULT_CONFIG_FILE, e);
+                return VaultConfig::default();
+            }
+        };
+        serde_json::from_str(&raw).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring malformed {}: {}", VAULT_CONFIG_FILE, e);
+            VaultConfig::default()
+        })
+    }
+
+    fn vault_info(db_path: &Path) -> VaultInfo {
+        VaultInfo {
+            path: db_path.parent().map(|dir| dir.to_string_lossy().into_owned()).unwrap_or_default(),
+            db_path: db_path.to_string_lossy().into_owned(),
+        }
+    }
+}
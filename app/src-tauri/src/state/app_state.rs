@@ -1,45 +1,319 @@
 use rusqlite::Connection;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 
-/// Application state managed by Tauri
+use super::{ConnectionPool, PooledConnection};
+use crate::error::{AppError, AppResult};
+use crate::models::MigrationStatus;
+use crate::services::{AutomationServer, DbService, HealthService};
+
+/// Connections kept open to the database
+const POOL_SIZE: u32 = 4;
+
+/// The open database, or why it could not be opened
+#[derive(Default)]
+struct Database {
+    /// File the pool is connected to
+    path: Option<String>,
+    pool: Option<ConnectionPool>,
+    /// Problems found by the startup integrity check of a damaged database
+    corruption: Option<Vec<String>>,
+}
+
+/// Application state managed by Tauri. Cloning is cheap and shares the same database.
+#[derive(Clone)]
 pub struct AppState {
-    pub db: Mutex<Option<Connection>>,
+    database: Arc<RwLock<Database>>,
+    /// Held while the database file is backed up, restored or switched, so those never overlap
+    maintenance: Arc<Mutex<()>>,
+    /// Listener of the local automation API, while it runs
+    automation: Arc<Mutex<Option<AutomationServer>>>,
+    /// Schema migrations of the database being opened at startup
+    migration: Arc<Mutex<MigrationStatus>>,
 }
 
 impl AppState {
     /// Create new application state
     pub fn new() -> Self {
         Self {
-            db: Mutex::new(None),
+            database: Arc::new(RwLock::new(Database::default())),
+            maintenance: Arc::new(Mutex::new(())),
+            automation: Arc::new(Mutex::new(None)),
+            migration: Arc::new(Mutex::new(MigrationStatus::default())),
         }
     }
 
-    /// Initialize the database
-    pub fn init_db(&self, path: &str) -> Result<(), rusqlite::Error> {
-        let conn = Connection::open(path)?;
-        
-        // Enable foreign keys
-        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
-        
-        // Initialize schema via DbService
-        if let Err(e) = crate::services::DbService::init(&conn) {
-            eprintln!("Failed to initialize database schema: {}", e);
-            return Err(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(1),
-                Some(e.to_string()),
-            ));
-        }
-        
-        let mut db = self.db.lock().unwrap();
-        *db = Some(conn);
-        
+    /// Initialize the database. A damaged file is reported as `DatabaseCorrupt`
+    /// and every later command gets the same error, so the frontend can offer to
+    /// restore a backup instead of the app failing to start.
+    pub fn init_db(&self, path: &str) -> AppResult<()> {
+        self.init_db_with_progress(path, |_| {})
+    }
+
+    /// Like `init_db`, reporting the schema migrations to `on_progress` as they
+    /// run. Until the database is open, commands fail with `Upgrading`; when it
+    /// cannot be opened, the status keeps the reason.
+    pub fn init_db_with_progress(&self, path: &str, mut on_progress: impl FnMut(&MigrationStatus)) -> AppResult<()> {
+        let result = Self::open_pool_reporting(path, &mut |current_step, total_steps| {
+            let status = MigrationStatus::progress(current_step, total_steps);
+            self.set_migration_status(status.clone());
+            on_progress(&status);
+        });
+        if let Err(e) = &result {
+            let mut status = self.migration_status();
+            status.running = false;
+            status.error = Some(e.to_string());
+            self.set_migration_status(status.clone());
+            on_progress(&status);
+        }
+
+        let mut database = self.database.write().unwrap_or_else(|e| e.into_inner());
+        database.path = Some(path.to_string());
+        match result {
+            Ok(pool) => {
+                if database.pool.is_some() {
+                    tracing::warn!("Database already initialized; keeping the existing connections");
+                } else {
+                    database.pool = Some(pool);
+                }
+                Ok(())
+            }
+            Err(AppError::DatabaseCorrupt { problems }) => {
+                tracing::error!("Database is damaged: {}", problems.join("; "));
+                database.corruption = Some(problems.clone());
+                Err(AppError::DatabaseCorrupt { problems })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Move every connection over to the database at `path`, creating and
+    /// migrating it as needed. The new database is checked and migrated before
+    /// anything is swapped, so on any failure (a damaged file, a schema newer
+    /// than this app) the current database stays open. Connections borrowed
+    /// from the old pool close as they are returned.
+    pub fn switch_db(&self, path: &str) -> AppResult<()> {
+        let _maintenance = self.begin_maintenance()?;
+        self.replace_pool(path)?;
+        tracing::info!("Switched database to {}", path);
+        Ok(())
+    }
+
+    /// Close every connection and open new ones to the same database file, for
+    /// when the connections stopped working (the file was moved back, a disk
+    /// came back) or the database could not be opened at startup. On failure the
+    /// current connections are kept.
+    pub fn reconnect(&self) -> AppResult<()> {
+        let _maintenance = self.begin_maintenance()?;
+        let path = self.db_path().ok_or_else(|| AppError::System("Database not initialized".into()))?;
+        self.replace_pool(&path)?;
+        tracing::info!("Reconnected to database {}", path);
+        Ok(())
+    }
+
+    /// Put the prepared database file `prepared` in place of the open one and
+    /// reconnect to it. `prepared` must sit in the same directory, so the swap is
+    /// a single rename: an interrupted restore leaves either the old file or the
+    /// new one, never a mix. It is checked and migrated before anything closes;
+    /// on failure the current database stays open.
+    pub fn replace_db_file(&self, prepared: &str) -> AppResult<()> {
+        let _maintenance = self.begin_maintenance()?;
+        let path = self.db_path().ok_or_else(|| AppError::System("Database not initialized".into()))?;
+        drop(Self::open_pool(prepared)?);
+
+        {
+            let mut database = self.database.write().unwrap_or_else(|e| e.into_inner());
+            if let Some(pool) = &database.pool {
+                // Nothing may stay behind in the write-ahead log of the file being replaced
+                let conn = pool.get()?;
+                DbService::checkpoint(&conn)?;
+            }
+            // Idle connections close here, so the old log is removed before the swap
+            database.pool = None;
+            std::fs::rename(prepared, &path)?;
+        }
+        self.replace_pool(&path)?;
+        tracing::info!("Replaced database {} with {}", path, prepared);
+        Ok(())
+    }
+
+    /// Open a pool on `path` and put it in place of the current one. Connections
+    /// borrowed from the old pool close as they are returned.
+    fn replace_pool(&self, path: &str) -> AppResult<()> {
+        let pool = Self::open_pool(path)?;
+
+        let mut database = self.database.write().unwrap_or_else(|e| e.into_inner());
+        *database = Database {
+            path: Some(path.to_string()),
+            pool: Some(pool),
+            corruption: None,
+        };
         Ok(())
     }
 
-    /// Get database connection (will be used by future Tauri commands)
-    #[allow(dead_code)]
-    pub fn get_db(&self) -> std::sync::MutexGuard<'_, Option<Connection>> {
-        self.db.lock().unwrap()
+    /// File of the open database, once one has been opened
+    pub fn db_path(&self) -> Option<String> {
+        self.database.read().unwrap_or_else(|e| e.into_inner()).path.clone()
+    }
+
+    /// Check and migrate the database, then open the pool's connections
+    fn open_pool(path: &str) -> AppResult<ConnectionPool> {
+        Self::open_pool_reporting(path, &mut |_, _| {})
+    }
+
+    /// Like `open_pool`, passing the migration progress to `on_step`
+    fn open_pool_reporting(path: &str, on_step: &mut dyn FnMut(usize, usize)) -> AppResult<ConnectionPool> {
+        let conn = Self::open_connection(path)?;
+
+        let problems = DbService::integrity_check(&conn, true)?;
+        if !problems.is_empty() {
+            return Err(AppError::DatabaseCorrupt { problems });
+        }
+
+        // Initialize schema via DbService
+        if let Err(e) = DbService::init_with_progress(&conn, on_step) {
+            tracing::error!("Failed to initialize database schema: {}", e);
+            return Err(e);
+        }
+
+        HealthService::startup_check(&conn);
+        drop(conn);
+
+        ConnectionPool::open(path, POOL_SIZE, DbService::configure)
+    }
+
+    /// Open one connection with the per-connection settings every query relies on:
+    /// foreign keys, WAL, a busy timeout so writers on other connections (e.g. the
+    /// frontend's SQL plugin) are waited for, and the cache size
+    fn open_connection(path: &str) -> Result<Connection, rusqlite::Error> {
+        let conn = Connection::open(path)?;
+        DbService::configure(&conn)?;
+        Ok(conn)
+    }
+
+    /// The connection pool, or why the database could not be opened
+    fn pool(&self) -> AppResult<ConnectionPool> {
+        let database = self.database.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(pool) = &database.pool {
+            return Ok(pool.clone());
+        }
+        if let Some(problems) = &database.corruption {
+            return Err(AppError::DatabaseCorrupt { problems: problems.clone() });
+        }
+        let migration = self.migration_status();
+        if migration.running {
+            return Err(AppError::Upgrading { current_step: migration.current_step, total_steps: migration.total_steps });
+        }
+        match migration.error {
+            Some(error) => Err(AppError::System(format!("Database could not be opened: {}", error))),
+            None => Err(AppError::System("Database not initialized".into())),
+        }
+    }
+
+    /// Progress of the migrations run while the database was opened at startup
+    pub fn migration_status(&self) -> MigrationStatus {
+        self.migration.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set_migration_status(&self, status: MigrationStatus) {
+        *self.migration.lock().unwrap_or_else(|e| e.into_inner()) = status;
+    }
+
+    /// Borrow a database connection from the pool
+    pub fn conn(&self) -> AppResult<PooledConnection> {
+        self.pool()?.get()
+    }
+
+    /// Claim the database file for a backup, restore or switch; fails with
+    /// `Conflict` while another one is running
+    pub fn begin_maintenance(&self) -> AppResult<MutexGuard<'_, ()>> {
+        match self.maintenance.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(std::sync::TryLockError::Poisoned(e)) => Ok(e.into_inner()),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                Err(AppError::Conflict("A backup, restore or vault switch is already running".into()))
+            }
+        }
+    }
+
+    /// Keep the automation listener that was just started
+    pub fn set_automation(&self, server: AutomationServer) {
+        *self.automation.lock().unwrap_or_else(|e| e.into_inner()) = Some(server);
+    }
+
+    /// Take the running automation listener, to stop it
+    pub fn take_automation(&self) -> Option<AutomationServer> {
+        self.automation.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Port of the running automation listener
+    pub fn automation_port(&self) -> Option<u16> {
+        self.automation.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(AutomationServer::port)
+    }
+
+    /// Run a database operation on a blocking thread so long queries
+    /// do not stall the async executor. `op` holds one connection throughout;
+    /// this is the helper for every service call that only queries.
+    pub async fn run<T, F>(&self, op: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> AppResult<T> + Send + 'static,
+    {
+        self.blocking(move |state| op(&*state.conn()?)).await
+    }
+
+    /// Like `run`, for operations that are safe to repeat: when the first
+    /// attempt fails because the connection was lost, the database is reopened
+    /// and the operation runs once more
+    pub async fn run_with_retry<T, F>(&self, op: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: Fn(&Connection) -> AppResult<T> + Send + 'static,
+    {
+        self.blocking(move |state| {
+            let first = op(&*state.conn()?);
+            match first {
+                Err(e) if e.is_connection_lost() && state.recover_connection(&e) => op(&*state.conn()?),
+                result => result,
+            }
+        })
+        .await
+    }
+
+    /// Run blocking work (file system, git) on a blocking thread. `op` gets its
+    /// own handle to the state and borrows connections as it needs them; it
+    /// should return each one before slow file or git work. Work that only
+    /// queries goes through `run` instead.
+    pub async fn blocking<T, F>(&self, op: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&AppState) -> AppResult<T> + Send + 'static,
+    {
+        let state = self.clone();
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let result = op(&state);
+            // Reopen lost connections so the next command works again
+            if let Err(e) = &result {
+                if e.is_connection_lost() {
+                    state.recover_connection(e);
+                }
+            }
+            result
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("Background task failed: {}", e)))?
+    }
+
+    /// Reconnect after `error` showed the connection was lost; whether it worked
+    fn recover_connection(&self, error: &AppError) -> bool {
+        tracing::warn!("Database connection lost ({}); reconnecting", error);
+        match self.reconnect() {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::error!("Failed to reconnect to the database: {}", e);
+                false
+            }
+        }
     }
 }
 
@@ -48,3 +322,97 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::{open_state, project};
+
+    fn projects(state: &AppState) -> i64 {
+        let conn = state.conn().unwrap();
+        conn.query_row("SELECT COUNT(*) FROM projects", [], |row| row.get(0)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_command_that_panics_mid_transaction_does_not_break_the_next_one() {
+        let state = open_state();
+        for _ in 0..POOL_SIZE * 2 {
+            let result = state
+                .run(|conn| -> AppResult<()> {
+                    conn.execute_batch("BEGIN")?;
+                    project(conn, "Half written");
+                    panic!("command panicked inside a transaction");
+                })
+                .await;
+            assert!(matches!(result, Err(AppError::Internal(_))), "{:?}", result);
+        }
+
+        // Every connection is back, none of them still in the transaction
+        let held: Vec<_> = (0..POOL_SIZE).map(|_| state.conn().unwrap()).collect();
+        assert!(held.iter().all(|conn| conn.is_autocommit()));
+        drop(held);
+        assert_eq!(projects(&state), 0);
+
+        let created = state.run(|conn| Ok(project(conn, "Next"))).await.unwrap();
+        assert_eq!(created.name, "Next");
+        assert_eq!(projects(&state), 1);
+    }
+
+    #[tokio::test]
+    async fn reconnect_reopens_the_same_database() {
+        let state = open_state();
+        state.run(|conn| Ok(project(conn, "Kept"))).await.unwrap();
+        let path = state.db_path();
+
+        state.reconnect().unwrap();
+        assert_eq!(state.db_path(), path);
+        assert_eq!(projects(&state), 1);
+
+        // Connections borrowed before the reconnect still work until returned
+        let old = state.conn().unwrap();
+        state.reconnect().unwrap();
+        assert_eq!(old.query_row("SELECT COUNT(*) FROM projects", [], |row| row.get::<_, i64>(0)).unwrap(), 1);
+        drop(old);
+        assert_eq!(projects(&state), 1);
+    }
+
+    #[test]
+    fn an_uninitialized_state_reports_instead_of_panicking() {
+        let state = AppState::new();
+        assert!(matches!(state.conn(), Err(AppError::System(_))));
+        assert!(matches!(state.reconnect(), Err(AppError::System(_))));
+    }
+
+    #[test]
+    fn reconnect_keeps_the_current_connections_when_the_file_cannot_be_opened() {
+        let state = open_state();
+        let path = state.db_path().unwrap();
+        let dir = std::path::Path::new(&path).parent().unwrap().to_path_buf();
+        let moved = dir.with_extension("moved");
+        std::fs::rename(&dir, &moved).unwrap();
+
+        assert!(state.reconnect().is_err());
+        std::fs::rename(&moved, &dir).unwrap();
+        assert_eq!(projects(&state), 0);
+        state.reconnect().unwrap();
+        assert_eq!(projects(&state), 0);
+    }
+
+    #[test]
+    fn a_poisoned_maintenance_lock_is_taken_over() {
+        let state = open_state();
+        let poisoner = state.clone();
+        let panicked = std::thread::spawn(move || {
+            let _guard = poisoner.maintenance.lock().unwrap();
+            panic!("backup panicked");
+        })
+        .join();
+        assert!(panicked.is_err());
+
+        state.reconnect().unwrap();
+        let guard = state.begin_maintenance().unwrap();
+        assert!(matches!(state.reconnect(), Err(AppError::Conflict(_))));
+        drop(guard);
+        state.reconnect().unwrap();
+    }
+}
@@ -1,3 +1,5 @@
 pub mod app_state;
+pub mod pool;
 
 pub use app_state::AppState;
+pub use pool::{ConnectionPool, PooledConnection};
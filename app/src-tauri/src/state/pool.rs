@@ -0,0 +1,181 @@
+use r2d2::{HandleError, ManageConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::Connection;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+
+/// How long a caller waits for a free connection before giving up
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Fixed-size pool of SQLite connections to one database file.
+/// Cloning is cheap and shares the same connections.
+#[derive(Clone)]
+pub struct ConnectionPool {
+    pool: r2d2::Pool<SqliteManager>,
+}
+
+/// Connection borrowed from a pool; goes back to the pool when dropped
+pub type PooledConnection = r2d2::PooledConnection<SqliteManager>;
+
+impl ConnectionPool {
+    /// Open `size` connections to the database file at `path`. `init` sets up
+    /// each one as it is opened, including those that replace a closed connection.
+    pub fn open<F>(path: &str, size: u32, init: F) -> AppResult<Self>
+    where
+        F: Fn(&Connection) -> Result<(), rusqlite::Error> + Send + Sync + 'static,
+    {
+        Self::build(path, size, ACQUIRE_TIMEOUT, init)
+    }
+
+    fn build<F>(path: &str, size: u32, timeout: Duration, init: F) -> AppResult<Self>
+    where
+        F: Fn(&Connection) -> Result<(), rusqlite::Error> + Send + Sync + 'static,
+    {
+        let manager = SqliteManager(SqliteConnectionManager::file(path).with_init(move |conn| init(conn)));
+        let pool = r2d2::Pool::builder()
+            .max_size(size)
+            .connection_timeout(timeout)
+            .error_handler(Box::new(LogConnectionErrors))
+            .build(manager)
+            .map_err(|e| AppError::System(format!("Failed to open database connections: {}", e)))?;
+        Ok(Self { pool })
+    }
+
+    /// Take a connection, waiting for one to be returned when all are in use
+    pub fn get(&self) -> AppResult<PooledConnection> {
+        self.pool
+            .get()
+            .map_err(|_| AppError::Busy("No database connection became available".into()))
+    }
+}
+
+/// Opens the pool's connections and checks each one that comes back
+#[derive(Debug)]
+pub struct SqliteManager(SqliteConnectionManager);
+
+impl ManageConnection for SqliteManager {
+    type Connection = Connection;
+    type Error = rusqlite::Error;
+
+    fn connect(&self) -> Result<Connection, rusqlite::Error> {
+        self.0.connect()
+    }
+
+    fn is_valid(&self, conn: &mut Connection) -> Result<(), rusqlite::Error> {
+        self.0.is_valid(conn)
+    }
+
+    /// A command that panicked mid-transaction must not hand the open
+    /// transaction to the next borrower, so it is rolled back. A connection
+    /// that cannot be rolled back is closed, which rolls it back, and the pool
+    /// opens another in its place.
+    fn has_broken(&self, conn: &mut Connection) -> bool {
+        if conn.is_autocommit() {
+            return false;
+        }
+        match conn.execute_batch("ROLLBACK") {
+            Ok(()) => false,
+            Err(e) => {
+                tracing::warn!("Replacing a database connection left in a transaction: {}", e);
+                true
+            }
+        }
+    }
+}
+
+/// Sends failures to open a connection to the application log
+#[derive(Debug)]
+struct LogConnectionErrors;
+
+impl HandleError<rusqlite::Error> for LogConnectionErrors {
+    fn handle_error(&self, error: rusqlite::Error) {
+        tracing::error!("Failed to open a database connection: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::temp_dir;
+
+    /// Pool of `size` connections to a new database file with one empty table
+    fn pool(size: u32) -> ConnectionPool {
+        let path = temp_dir().join("pool.db");
+        let setup = Connection::open(&path).unwrap();
+        setup.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY)").unwrap();
+        ConnectionPool::build(path.to_str().unwrap(), size, Duration::from_millis(200), |conn| {
+            conn.execute_batch("PRAGMA foreign_keys = ON")
+        })
+        .unwrap()
+    }
+
+    fn idle(pool: &ConnectionPool) -> u32 {
+        pool.pool.state().idle_connections
+    }
+
+    fn items(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn returned_connections_are_handed_out_again() {
+        let pool = pool(2);
+        let first = pool.get().unwrap();
+        let second = pool.get().unwrap();
+        assert_eq!(idle(&pool), 0);
+
+        drop(first);
+        assert_eq!(idle(&pool), 1);
+        let third = pool.get().unwrap();
+        drop((second, third));
+        assert_eq!(idle(&pool), 2);
+        assert_eq!(pool.pool.state().connections, 2);
+    }
+
+    #[test]
+    fn every_connection_is_set_up_when_opened() {
+        let pool = pool(2);
+        let a = pool.get().unwrap();
+        let b = pool.get().unwrap();
+        for conn in [&a, &b] {
+            let foreign_keys: i64 = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+            assert_eq!(foreign_keys, 1);
+        }
+    }
+
+    #[test]
+    fn a_waiting_caller_gets_the_next_returned_connection() {
+        let pool = pool(1);
+        let held = pool.get().unwrap();
+
+        let waiter = {
+            let pool = pool.clone();
+            std::thread::spawn(move || items(&pool.get().unwrap()))
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        held.execute("INSERT INTO items DEFAULT VALUES", []).unwrap();
+        drop(held);
+        assert_eq!(waiter.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn a_caller_gives_up_when_no_connection_is_returned() {
+        let pool = pool(1);
+        let _held = pool.get().unwrap();
+        assert!(matches!(pool.get(), Err(AppError::Busy(_))));
+    }
+
+    #[test]
+    fn an_open_transaction_is_rolled_back_when_returned() {
+        let pool = pool(1);
+        let conn = pool.get().unwrap();
+        conn.execute_batch("BEGIN; INSERT INTO items DEFAULT VALUES;").unwrap();
+        drop(conn);
+
+        let conn = pool.get().unwrap();
+        assert!(conn.is_autocommit());
+        assert_eq!(items(&conn), 0);
+        assert_eq!(pool.pool.state().connections, 1);
+    }
+}
@@ -0,0 +1,300 @@
+//! ZIP archives, as written by portable backups and note bundles, through the
+//! zip crate. Entries are listed up front with their names checked, and copied
+//! in chunks both ways, so large files never sit in memory whole.
+
+use chrono::{DateTime, Datelike, Timelike};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// One entry of an archive, as listed in its central directory
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub name: String,
+    /// Size unpacked
+    pub size: u64,
+    /// Whether the name is a plain relative path that stays inside the
+    /// directory the entry is extracted to
+    pub enclosed: bool,
+    index: usize,
+}
+
+/// Writes an archive entry by entry; `finish` adds the central directory
+pub struct ArchiveWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    options: SimpleFileOptions,
+}
+
+impl<W: Write + Seek> ArchiveWriter<W> {
+    /// Every entry is deflated and dated `modified_at`, a Unix timestamp
+    pub fn new(out: W, modified_at: i64) -> Self {
+        let mut options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        if let Some(modified) = DateTime::from_timestamp(modified_at, 0).and_then(|at| {
+            let date = at.date_naive();
+            let time = at.time();
+            zip::DateTime::from_date_and_time(
+                u16::try_from(date.year()).ok()?,
+                date.month() as u8,
+                date.day() as u8,
+                time.hour() as u8,
+                time.minute() as u8,
+                time.second() as u8,
+            )
+            .ok()
+        }) {
+            options = options.last_modified_time(modified);
+        }
+        Self { zip: ZipWriter::new(out), options }
+    }
+
+    pub fn add_bytes(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        self.start(name)?;
+        self.zip.write_all(data)
+    }
+
+    /// Add a file's content, read in chunks
+    pub fn add_file(&mut self, name: &str, path: &Path) -> io::Result<()> {
+        let mut file = File::open(path)?;
+        self.start(name)?;
+        io::copy(&mut file, &mut self.zip).map(|_| ())
+    }
+
+    pub fn finish(self) -> io::Result<W> {
+        Ok(self.zip.finish()?)
+    }
+
+    fn start(&mut self, name: &str) -> io::Result<()> {
+        if !is_safe_entry_name(name) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("'{}' cannot be an archive entry", name)));
+        }
+        Ok(self.zip.start_file(name, self.options)?)
+    }
+}
+
+/// An archive opened for reading, with its entries listed
+pub struct ArchiveReader<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    entries: Vec<ArchiveEntry>,
+}
+
+impl ArchiveReader<BufReader<File>> {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Read the central directory
+    pub fn new(input: R) -> io::Result<Self> {
+        let mut zip = ZipArchive::new(input).map_err(|e| match e {
+            zip::result::ZipError::Io(e) => e,
+            e => invalid(&format!("The file is not a ZIP archive: {}", e)),
+        })?;
+        let mut entries = Vec::with_capacity(zip.len());
+        for index in 0..zip.len() {
+            let file = zip.by_index_raw(index)?;
+            let name = file.name().to_string();
+            let enclosed = file.enclosed_name().is_some() && is_safe_entry_name(&name);
+            entries.push(ArchiveEntry { name, size: file.size(), enclosed, index });
+        }
+        Ok(Self { zip, entries })
+    }
+
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// Copy an entry's content to `out`; the zip crate checks its checksum
+    /// once the entry is read to the end
+    pub fn extract(&mut self, entry: &ArchiveEntry, out: &mut dyn Write) -> io::Result<u64> {
+        let mut file = self.zip.by_index(entry.index)?;
+        let copied = io::copy(&mut file, out)?;
+        if copied != entry.size {
+            return Err(invalid(&format!("'{}' is damaged", entry.name)));
+        }
+        Ok(copied)
+    }
+
+    /// An entry's content as text, failing when it is larger than `max_bytes`
+    pub fn read_to_string(&mut self, entry: &ArchiveEntry, max_bytes: u64) -> io::Result<String> {
+        if entry.size > max_bytes {
+            return Err(invalid(&format!("'{}' is larger than {} bytes", entry.name, max_bytes)));
+        }
+        let mut bytes = Vec::with_capacity(entry.size as usize);
+        self.extract(entry, &mut bytes)?;
+        String::from_utf8(bytes).map_err(|_| invalid(&format!("'{}' is not valid UTF-8", entry.name)))
+    }
+}
+
+/// Whether a name can be an entry of its own: no absolute paths, drive
+/// letters, backslashes or "." and ".." parts, which `enclosed_name` lets
+/// through on some platforms
+pub fn is_safe_entry_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('/')
+        && !name.contains(['\\', '\0'])
+        && name.chars().nth(1) != Some(':')
+        && name.split('/').all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::temp_dir;
+    use std::io::Cursor;
+
+    /// 2024-02-29 12:30:58 UTC
+    const LEAP_DAY: i64 = 1_709_209_858;
+
+    /// Stored archive written by Python's zipfile: "hello.txt" and "notes/ü.md",
+    /// with the archive comment "made elsewhere"
+    const PYTHON_ARCHIVE: &str = concat!(
+        "504b0304140000000000dd635d5818a7557b0e0000000e0000000900000068656c6c6f2e74787448656c6c6f2c20776f",
+        "726c64210a504b0304140000080000dd635d58eeaf948308000000080000000b0000006e6f7465732fc3bc2e6d642320",
+        "5469746c650a504b01021403140000000000dd635d5818a7557b0e0000000e0000000900000000000000000000008001",
+        "0000000068656c6c6f2e747874504b01021403140000080000dd635d58eeaf948308000000080000000b000000000000",
+        "00000000008001350000006e6f7465732fc3bc2e6d64504b0506000000000200020070000000660000000e006d616465",
+        "20656c73657768657265",
+    );
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    /// Archive with the given entries, written to memory
+    fn archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ArchiveWriter::new(Cursor::new(Vec::new()), LEAP_DAY);
+        for (name, data) in entries {
+            writer.add_bytes(name, data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn read_all(reader: &mut ArchiveReader<impl Read + Seek>) -> Vec<(String, Vec<u8>)> {
+        let entries = reader.entries().to_vec();
+        entries
+            .iter()
+            .map(|entry| {
+                let mut data = Vec::new();
+                assert_eq!(reader.extract(entry, &mut data).unwrap(), entry.size);
+                (entry.name.clone(), data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn written_archives_read_back() {
+        let text = "Großes Übel\n".repeat(1_000);
+        let bytes = archive(&[("manifest.json", b"{}"), ("notes/ü.md", text.as_bytes()), ("empty", b"")]);
+        let mut reader = ArchiveReader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.entries().iter().all(|entry| entry.enclosed));
+        assert_eq!(
+            read_all(&mut reader),
+            [
+                ("manifest.json".to_string(), b"{}".to_vec()),
+                ("notes/ü.md".to_string(), text.into_bytes()),
+                ("empty".to_string(), Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn files_are_archived_and_extracted_whole() {
+        let dir = temp_dir();
+        let source = dir.join("big.bin");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&source, &data).unwrap();
+
+        let path = dir.join("out.zip");
+        let mut writer = ArchiveWriter::new(File::create(&path).unwrap(), LEAP_DAY);
+        writer.add_file("attachments/big.bin", &source).unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = ArchiveReader::open(&path).unwrap();
+        let entry = reader.entries()[0].clone();
+        assert_eq!((entry.name.as_str(), entry.size), ("attachments/big.bin", data.len() as u64));
+        let mut extracted = Vec::new();
+        reader.extract(&entry, &mut extracted).unwrap();
+        assert_eq!(extracted, data);
+
+        let missing = ArchiveWriter::new(Cursor::new(Vec::new()), LEAP_DAY).add_file("gone", &dir.join("gone")).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+        assert_eq!(ArchiveReader::open(&dir.join("gone.zip")).err().unwrap().kind(), io::ErrorKind::NotFound);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn archives_written_elsewhere_can_be_read() {
+        let mut reader = ArchiveReader::new(Cursor::new(from_hex(PYTHON_ARCHIVE))).unwrap();
+        assert_eq!(
+            read_all(&mut reader),
+            [
+                ("hello.txt".to_string(), b"Hello, world!\n".to_vec()),
+                ("notes/ü.md".to_string(), b"# Title\n".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn entries_carry_the_modification_time() {
+        let mut zip = ZipArchive::new(Cursor::new(archive(&[("a", b"x")]))).unwrap();
+        let modified = zip.by_index(0).unwrap().last_modified().unwrap();
+        assert_eq!(
+            (modified.year(), modified.month(), modified.day(), modified.hour(), modified.minute(), modified.second()),
+            (2024, 2, 29, 12, 30, 58)
+        );
+    }
+
+    #[test]
+    fn unsafe_entry_names_are_refused_and_flagged() {
+        for name in ["", "/etc/passwd", "../up", "a/../b", "a//b", "./a", "a/", "c:/windows", "a\\b", "nul\0"] {
+            assert!(!is_safe_entry_name(name), "{:?}", name);
+            let error = ArchiveWriter::new(Cursor::new(Vec::new()), LEAP_DAY).add_bytes(name, b"x").unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{:?}", name);
+        }
+        for name in ["a", "notes/a.md", ".hidden", "a..b/c"] {
+            assert!(is_safe_entry_name(name), "{:?}", name);
+        }
+
+        // Written around the checks, as another tool could
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in ["../up", "/abs", "a\\..\\b", "fine.txt"] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(b"x").unwrap();
+        }
+        let reader = ArchiveReader::new(Cursor::new(zip.finish().unwrap().into_inner())).unwrap();
+        let flags: Vec<(&str, bool)> = reader.entries().iter().map(|entry| (entry.name.as_str(), entry.enclosed)).collect();
+        assert_eq!(flags, [("../up", false), ("/abs", false), ("a\\..\\b", false), ("fine.txt", true)]);
+    }
+
+    #[test]
+    fn damaged_archives_are_reported() {
+        for bytes in [b"short".to_vec(), vec![0; 100]] {
+            let error = ArchiveReader::new(Cursor::new(bytes)).err().unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+
+        // Stored, so the content sits in the archive as written
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("a.txt", SimpleFileOptions::default().compression_method(CompressionMethod::Stored)).unwrap();
+        zip.write_all(b"content").unwrap();
+        let mut bytes = zip.finish().unwrap().into_inner();
+        let at = bytes.windows(7).position(|window| window == b"content").unwrap();
+        bytes[at] ^= 1;
+        let mut reader = ArchiveReader::new(Cursor::new(bytes)).unwrap();
+        let entry = reader.entries()[0].clone();
+        assert!(reader.extract(&entry, &mut Vec::new()).is_err());
+
+        let mut reader = ArchiveReader::new(Cursor::new(archive(&[("bad.txt", &[0xff, 0xfe]), ("big.txt", b"0123456789")]))).unwrap();
+        let entries = reader.entries().to_vec();
+        assert_eq!(reader.read_to_string(&entries[0], 10).unwrap_err().to_string(), "'bad.txt' is not valid UTF-8");
+        assert_eq!(reader.read_to_string(&entries[1], 9).unwrap_err().to_string(), "'big.txt' is larger than 9 bytes");
+        assert_eq!(reader.read_to_string(&entries[1], 10).unwrap(), "0123456789");
+    }
+}
@@ -0,0 +1,347 @@
+//! Minimal BibTeX reading and writing helpers
+
+use std::collections::HashMap;
+
+/// One parsed entry with the 1-based line it starts on. Field names are
+/// lowercase; values have their braces removed and whitespace collapsed.
+#[derive(Debug)]
+pub struct Entry {
+    pub line: usize,
+    /// Lowercase entry type, e.g. "article"
+    pub entry_type: String,
+    pub key: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl Entry {
+    /// Value of a field, when present and not blank
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Entry that could not be parsed, with the 1-based line it starts on
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Split BibTeX text into entries. Unknown entry types and fields are kept,
+/// `@string` macros are expanded, `@comment` and `@preamble` are skipped.
+/// A malformed entry is reported and parsing resumes at the next `@`.
+pub fn parse(text: &str) -> (Vec<Entry>, Vec<ParseError>) {
+    let mut parser = Parser {
+        chars: text.strip_prefix('\u{feff}').unwrap_or(text).chars().collect(),
+        pos: 0,
+        macros: HashMap::new(),
+        counted: (0, 1),
+    };
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    while parser.skip_to('@') {
+        let line = parser.line();
+        let start = parser.pos;
+        parser.pos += 1;
+        match parser.entry() {
+            Ok(Some(entry)) => entries.push(Entry { line, ..entry }),
+            Ok(None) => {}
+            Err(message) => {
+                errors.push(ParseError { line, message });
+                parser.pos = start + 1;
+            }
+        }
+    }
+
+    (entries, errors)
+}
+
+/// Split an author or editor list on top-level "and"
+pub fn split_names(value: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut current = Vec::new();
+    for word in value.split_whitespace() {
+        if word.eq_ignore_ascii_case("and") {
+            if !current.is_empty() {
+                names.push(current.join(" "));
+                current.clear();
+            }
+        } else {
+            current.push(word);
+        }
+    }
+    if !current.is_empty() {
+        names.push(current.join(" "));
+    }
+    names
+}
+
+/// Family name of an author written "Last, First" or "First Last"
+pub fn family_name(name: &str) -> &str {
+    match name.split_once(',') {
+        Some((last, _)) => last.trim(),
+        None => name.split_whitespace().last().unwrap_or_default(),
+    }
+}
+
+/// Format an entry; fields with empty values are left out. Values are wrapped
+/// in braces, with unbalanced braces dropped so the output always parses.
+pub fn format_entry(entry_type: &str, key: &str, fields: &[(&str, String)]) -> String {
+    let mut out = format!("@{}{{{},\n", entry_type, key);
+    for (name, value) in fields {
+        if !value.trim().is_empty() {
+            out.push_str(&format!("  {} = {{{}}},\n", name, balance_braces(value.trim())));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn balance_braces(value: &str) -> String {
+    let mut depth = 0usize;
+    let mut kept: Vec<char> = Vec::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => continue,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        kept.push(c);
+    }
+    // Drop the openers that were never closed, innermost first
+    while depth > 0 {
+        if let Some(index) = kept.iter().rposition(|&c| c == '{') {
+            kept.remove(index);
+        }
+        depth -= 1;
+    }
+    kept.into_iter().collect()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Values of `@string` macros by lowercase name
+    macros: HashMap<String, String>,
+    /// Position up to which lines were counted, and the line there
+    counted: (usize, usize),
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn line(&mut self) -> usize {
+        let (from, line) = if self.pos >= self.counted.0 { self.counted } else { (0, 1) };
+        let to = self.pos.min(self.chars.len());
+        let line = line + self.chars[from..to].iter().filter(|&&c| c == '\n').count();
+        self.counted = (to, line);
+        line
+    }
+
+    fn skip_to(&mut self, target: char) -> bool {
+        while let Some(c) = self.peek() {
+            if c == target {
+                return true;
+            }
+            self.pos += 1;
+        }
+        false
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | '(' | ')' | ',' | '=' | '#' | '"'))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(format!("Expected '{}' but found '{}'", expected, c)),
+            None => Err(format!("Expected '{}' but the text ended", expected)),
+        }
+    }
+
+    /// Parse what follows an `@`; None for comments, preambles and macros
+    fn entry(&mut self) -> Result<Option<Entry>, String> {
+        let entry_type = self.identifier().to_lowercase();
+        if entry_type.is_empty() {
+            return Err("Missing entry type after '@'".into());
+        }
+        if entry_type == "comment" {
+            // A braced comment may span lines; a bare one ends at the next '@'
+            self.skip_whitespace();
+            if self.peek() == Some('{') {
+                self.pos += 1;
+                self.braced()?;
+            }
+            return Ok(None);
+        }
+
+        self.skip_whitespace();
+        let close = match self.peek() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => return Err(format!("Expected '{{' after @{}", entry_type)),
+        };
+        self.pos += 1;
+
+        match entry_type.as_str() {
+            "preamble" => {
+                self.value()?;
+                self.expect(close)?;
+                Ok(None)
+            }
+            "string" => {
+                self.skip_whitespace();
+                let name = self.identifier().to_lowercase();
+                self.expect('=')?;
+                let value = self.value()?;
+                self.expect(close)?;
+                self.macros.insert(name, value);
+                Ok(None)
+            }
+            _ => {
+                self.skip_whitespace();
+                let key = self.identifier();
+                if key.is_empty() {
+                    return Err(format!("@{} entry has no citation key", entry_type));
+                }
+
+                let mut fields = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(c) if c == close => {
+                            self.pos += 1;
+                            break;
+                        }
+                        Some(c) => return Err(format!("Expected ',' after the fields of '{}' but found '{}'", key, c)),
+                        None => return Err(format!("Entry '{}' is not closed", key)),
+                    }
+
+                    self.skip_whitespace();
+                    if self.peek() == Some(close) {
+                        self.pos += 1;
+                        break;
+                    }
+                    let name = self.identifier().to_lowercase();
+                    if name.is_empty() {
+                        return Err(format!("Expected a field name in '{}'", key));
+                    }
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    fields.push((name, value));
+                }
+
+                Ok(Some(Entry { line: 0, entry_type, key, fields }))
+            }
+        }
+    }
+
+    /// A field value: braced, quoted or bare parts joined with '#'
+    fn value(&mut self) -> Result<String, String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.pos += 1;
+                    value.push_str(&self.braced()?);
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    value.push_str(&self.quoted()?);
+                }
+                Some(_) => {
+                    let word = self.identifier();
+                    if word.is_empty() {
+                        return Err("Expected a field value".into());
+                    }
+                    // Numbers stand for themselves, other words are macros such as month names
+                    match self.macros.get(&word.to_lowercase()) {
+                        Some(expanded) => value.push_str(expanded),
+                        None => value.push_str(&word),
+                    }
+                }
+                None => return Err("Expected a field value but the text ended".into()),
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                return Ok(value.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
+        }
+    }
+
+    /// Text up to the brace closing one already consumed, without braces
+    fn braced(&mut self) -> Result<String, String> {
+        let mut depth = 1;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '\\' => {
+                    // Escaped braces are literal characters
+                    if let Some(next @ ('{' | '}')) = self.peek() {
+                        self.pos += 1;
+                        text.push(next);
+                    } else {
+                        text.push(c);
+                    }
+                }
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(text);
+                    }
+                }
+                _ => text.push(c),
+            }
+        }
+        Err("Unbalanced braces".into())
+    }
+
+    /// Text up to the closing quote, which only counts outside braces
+    fn quoted(&mut self) -> Result<String, String> {
+        let mut depth = 0;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '"' if depth == 0 => return Ok(text),
+                '{' => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                _ => text.push(c),
+            }
+        }
+        Err("Unterminated quoted value".into())
+    }
+}
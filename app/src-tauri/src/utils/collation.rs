@@ -0,0 +1,75 @@
+//! Unicode-aware title collation
+//!
+//! SQLite's default BINARY collation compares UTF-8 bytes, which puts "Zebra"
+//! before "apple" and "Émile" after every ASCII title. The UNICODE collation
+//! compares case- and accent-folded keys instead and falls back to the raw text
+//! so that the ordering stays total and stable.
+
+use std::cmp::Ordering;
+
+/// Name under which the collation is registered on every connection
+pub const UNICODE_COLLATION: &str = "UNICODE";
+
+/// Compare two strings ignoring case and accents, breaking ties byte-wise
+pub fn unicode_compare(a: &str, b: &str) -> Ordering {
+    fold_key(a).cmp(&fold_key(b)).then_with(|| a.cmp(b))
+}
+
+/// Lowercase the text, replace accented Latin letters with their base letter
+/// and drop combining marks
+pub fn fold_key(text: &str) -> String {
+    let mut key = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_combining_mark(c) {
+            continue;
+        }
+        match base_letter(c) {
+            Some(base) => key.push_str(base),
+            None => key.push(c),
+        }
+    }
+    key
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}')
+}
+
+/// Compatibility decomposition of precomposed lowercase Latin letters
+fn base_letter(c: char) -> Option<&'static str> {
+    const TABLE: &[(&str, &str)] = &[
+        ("àáâãäåāăą", "a"),
+        ("çćĉċč", "c"),
+        ("ďđ", "d"),
+        ("èéêëēĕėęě", "e"),
+        ("ĝğġģ", "g"),
+        ("ĥħ", "h"),
+        ("ìíîïĩīĭįı", "i"),
+        ("ĵ", "j"),
+        ("ķ", "k"),
+        ("ĺļľŀł", "l"),
+        ("ñńņňŉ", "n"),
+        ("òóôõöøōŏő", "o"),
+        ("ŕŗř", "r"),
+        ("śŝşš", "s"),
+        ("ţťŧ", "t"),
+        ("ùúûüũūŭůűų", "u"),
+        ("ŵ", "w"),
+        ("ýÿŷ", "y"),
+        ("źżž", "z"),
+        ("æ", "ae"),
+        ("œ", "oe"),
+        ("ß", "ss"),
+        ("þ", "th"),
+        ("ð", "d"),
+    ];
+
+    if c.is_ascii() {
+        return None;
+    }
+
+    TABLE
+        .iter()
+        .find(|(letters, _)| letters.contains(c))
+        .map(|(_, base)| *base)
+}
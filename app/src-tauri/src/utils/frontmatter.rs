@@ -0,0 +1,213 @@
+//! YAML frontmatter for Markdown files
+//!
+//! Values are written as JSON, which is also valid YAML flow syntax, so any
+//! YAML reader loads the header. Reading supports the subset found in note
+//! apps: top-level `key: value` pairs with plain, quoted, numeric, boolean and
+//! null scalars, `[a, b]` flow lists and `- item` block lists.
+
+use serde_json::{Map, Number, Value};
+
+/// Prefix a Markdown body with a frontmatter block holding `fields` in order
+pub fn render(fields: &[(&str, Value)], body: &str) -> String {
+    let mut output = String::from("---\n");
+    for (key, value) in fields {
+        output.push_str(key);
+        output.push_str(": ");
+        output.push_str(&value.to_string());
+        output.push('\n');
+    }
+    output.push_str("---\n\n");
+    output.push_str(body);
+    if !body.ends_with('\n') {
+        output.push('\n');
+    }
+    output
+}
+
+/// Split a document into its frontmatter block and body. Returns None when the
+/// text does not open with a "---" line or the block is never closed.
+pub fn split(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let first_end = text.find('\n')?;
+    if text[..first_end].trim_end() != "---" {
+        return None;
+    }
+
+    let header_start = first_end + 1;
+    let mut offset = header_start;
+    for line in text[header_start..].split_inclusive('\n') {
+        let marker = line.trim_end();
+        if marker == "---" || marker == "..." {
+            let body = &text[offset + line.len()..];
+            // Drop the blank line that conventionally follows the block
+            let body = body
+                .strip_prefix("\r\n")
+                .or_else(|| body.strip_prefix('\n'))
+                .unwrap_or(body);
+            return Some((&text[header_start..offset], body));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Parse a frontmatter block into its top-level fields. Nested mappings are
+/// skipped; anything else outside the supported subset is an error.
+pub fn parse(header: &str) -> Result<Map<String, Value>, String> {
+    let mut fields = Map::new();
+    // Key whose value was left empty and may continue as a block list
+    let mut open_key: Option<String> = None;
+
+    for (index, raw) in header.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let indented = raw.starts_with(' ') || raw.starts_with('\t');
+        if let Some(item) = trimmed.strip_prefix("- ").or_else(|| (trimmed == "-").then_some("")) {
+            let Some(key) = &open_key else {
+                return Err(format!("line {}: list item without a key", line_number));
+            };
+            let item = parse_scalar(item.trim()).map_err(|e| format!("line {}: {}", line_number, e))?;
+            match fields.get_mut(key) {
+                Some(Value::Array(items)) => items.push(item),
+                Some(value) => *value = Value::Array(vec![item]),
+                None => {
+                    fields.insert(key.clone(), Value::Array(vec![item]));
+                }
+            }
+            continue;
+        }
+
+        if indented {
+            // Nested mapping under the open key: not supported, left out
+            if open_key.is_some() {
+                continue;
+            }
+            return Err(format!("line {}: unexpected indentation", line_number));
+        }
+
+        let (key, value) = split_key_value(trimmed)
+            .ok_or_else(|| format!("line {}: expected \"key: value\"", line_number))?;
+        let key = unquote_key(key);
+        if value.is_empty() {
+            fields.insert(key.clone(), Value::Null);
+            open_key = Some(key);
+        } else {
+            let value = parse_value(value).map_err(|e| format!("line {}: {}", line_number, e))?;
+            fields.insert(key, value);
+            open_key = None;
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Split "key: value" at the first ": " outside quotes, or a bare "key:"
+fn split_key_value(line: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, ':') => {
+                let rest = &line[i + 1..];
+                if rest.is_empty() || rest.starts_with(' ') || rest.starts_with('\t') {
+                    let key = line[..i].trim();
+                    return (!key.is_empty()).then(|| (key, rest.trim()));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unquote_key(key: &str) -> String {
+    parse_scalar(key)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| key.to_string())
+}
+
+fn parse_value(value: &str) -> Result<Value, String> {
+    match value.strip_prefix('[') {
+        Some(inner) => {
+            let inner = inner
+                .trim_end()
+                .strip_suffix(']')
+                .ok_or("unterminated list")?;
+            split_flow_items(inner)?
+                .into_iter()
+                .map(parse_scalar)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array)
+        }
+        None if value.starts_with('{') => Err("inline mappings are not supported".into()),
+        None => parse_scalar(value),
+    }
+}
+
+/// Split the inside of a flow list at commas outside quotes
+fn split_flow_items(inner: &str) -> Result<Vec<&str>, String> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in inner.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some('"'), '\\') => escaped = true,
+            (Some(open), c) if c == open => quote = None,
+            (None, ',') => {
+                items.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            (None, '[') | (None, '{') => return Err("nested collections are not supported".into()),
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated quoted string".into());
+    }
+
+    let last = inner[start..].trim();
+    if !last.is_empty() || !items.is_empty() {
+        items.push(last);
+    }
+    Ok(items.into_iter().filter(|item| !item.is_empty()).collect())
+}
+
+fn parse_scalar(value: &str) -> Result<Value, String> {
+    if value.starts_with('"') {
+        return serde_json::from_str::<String>(value)
+            .map(Value::String)
+            .map_err(|_| "invalid double-quoted string".to_string());
+    }
+    if let Some(inner) = value.strip_prefix('\'') {
+        let inner = inner.strip_suffix('\'').ok_or("unterminated quoted string")?;
+        return Ok(Value::String(inner.replace("''", "'")));
+    }
+
+    // Plain scalars end at a comment
+    let value = value.find(" #").map_or(value, |i| &value[..i]).trim();
+    Ok(match value {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        _ => value
+            .parse::<i64>()
+            .map(Value::from)
+            .ok()
+            .or_else(|| value.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number))
+            .unwrap_or_else(|| Value::String(value.to_string())),
+    })
+}
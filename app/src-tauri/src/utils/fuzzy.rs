@@ -0,0 +1,97 @@
+//! Typo-tolerant title matching with character trigrams
+
+use std::collections::HashSet;
+
+/// Most trigrams of a query used to pre-filter candidates in SQL
+const MAX_SEARCH_TRIGRAMS: usize = 16;
+
+/// Trigrams of a lowercased word, padded so its start and end count:
+/// "cat" gives "  c", " ca", "cat" and "at "
+fn trigrams(word: &str) -> HashSet<String> {
+    let padded: Vec<char> = "  ".chars().chain(word.chars()).chain(" ".chars()).collect();
+    padded.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+/// Similarity of two words from 0 (nothing shared) to 1 (same trigrams), as the
+/// Dice coefficient of their trigram sets. Case is ignored.
+pub fn word_similarity(a: &str, b: &str) -> f64 {
+    let a = trigrams(&a.to_lowercase());
+    let b = trigrams(&b.to_lowercase());
+    let shared = a.intersection(&b).count();
+    (2 * shared) as f64 / (a.len() + b.len()) as f64
+}
+
+/// How well a title matches a query, from 0 to 1: every query word is paired
+/// with its most similar title word and the similarities are averaged
+pub fn title_similarity(query: &str, title: &str) -> f64 {
+    let title_words: Vec<String> = words(title).collect();
+    let query_words: Vec<String> = words(query).collect();
+    if query_words.is_empty() || title_words.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = query_words
+        .iter()
+        .map(|q| title_words.iter().map(|t| word_similarity(q, t)).fold(0.0, f64::max))
+        .sum();
+    total / query_words.len() as f64
+}
+
+/// Trigrams inside the query words, for a LIKE pre-filter: a title sharing none
+/// of them cannot be similar. Words shorter than three characters add none.
+pub fn search_trigrams(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    for word in words(query) {
+        let chars: Vec<char> = word.chars().collect();
+        for window in chars.windows(3) {
+            let trigram: String = window.iter().collect();
+            if seen.insert(trigram.clone()) {
+                result.push(trigram);
+            }
+        }
+    }
+    result.truncate(MAX_SEARCH_TRIGRAMS);
+    result
+}
+
+/// Lowercased words of a text, split on anything that is not a letter or digit
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_similarity_is_the_dice_coefficient_of_trigrams() {
+        assert_eq!(word_similarity("Bayesian", "bayesian"), 1.0);
+        assert_eq!(word_similarity("cat", "dog"), 0.0);
+        // "cat" and "cut" share only the padded "  c": 2 * 1 / (4 + 4)
+        assert_eq!(word_similarity("cat", "cut"), 0.25);
+
+        let typo = word_similarity("baysian", "bayesian");
+        assert!(typo > 0.6 && typo < 1.0, "{}", typo);
+        assert!(word_similarity("baysian", "bayes") < typo);
+    }
+
+    #[test]
+    fn title_similarity_pairs_each_query_word_with_its_best_title_word() {
+        assert_eq!(title_similarity("inference bayesian", "Bayesian inference, 2nd ed."), 1.0);
+        assert_eq!(title_similarity("bayesian", "Bayesian inference"), 1.0);
+        assert_eq!(title_similarity("bayesian unicorns", "Bayesian inference") , 0.5 + word_similarity("unicorns", "inference") / 2.0);
+        assert_eq!(title_similarity("", "Bayesian"), 0.0);
+        assert_eq!(title_similarity("bayesian", "--"), 0.0);
+    }
+
+    #[test]
+    fn search_trigrams_skip_short_words_and_repeats() {
+        assert_eq!(search_trigrams("Baysian of"), ["bay", "ays", "ysi", "sia", "ian"]);
+        assert_eq!(search_trigrams("anan nan"), ["ana", "nan"]);
+        assert!(search_trigrams("a an").is_empty());
+        assert_eq!(search_trigrams("abcdefghijklmnopqrstuvwxyz").len(), MAX_SEARCH_TRIGRAMS);
+    }
+}
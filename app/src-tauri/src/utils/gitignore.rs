@@ -0,0 +1,142 @@
+//! The .gitignore written into project repositories
+//!
+//! The app owns one block of the file, between two marker comments, and only
+//! ever rewrites that block; anything the user adds outside it is kept.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// File name of the ignore file
+pub const FILE_NAME: &str = ".gitignore";
+
+/// First line of the block managed by the app
+pub const BEGIN_MARKER: &str = "# >>> Research Vault managed block >>>";
+
+/// Last line of the block managed by the app
+pub const END_MARKER: &str = "# <<< Research Vault managed block <<<";
+
+/// Patterns used when the gitignore_template setting is empty
+pub const DEFAULT_TEMPLATE: &str = "\
+# Datasets are too large for git
+data/
+
+# Temporary and cache files
+*.tmp
+*.swp
+*~
+.cache/
+__pycache__/
+*.pyc
+.ipynb_checkpoints/
+.pytest_cache/
+node_modules/
+
+# Operating system files
+.DS_Store
+._*
+Thumbs.db
+desktop.ini
+";
+
+/// The managed block for a template plus extra patterns, markers included.
+/// Blank extras and extras already in the template are dropped.
+pub fn managed_block(template: &str, extra_patterns: &[String]) -> String {
+    let mut block = format!("{}\n", BEGIN_MARKER);
+    let template = template.trim_end();
+    if !template.is_empty() {
+        block.push_str(template);
+        block.push('\n');
+    }
+
+    let existing: Vec<&str> = template.lines().map(str::trim).collect();
+    let mut extras: Vec<&str> = Vec::new();
+    for pattern in extra_patterns.iter().map(|p| p.trim()) {
+        if !pattern.is_empty() && !existing.contains(&pattern) && !extras.contains(&pattern) {
+            extras.push(pattern);
+        }
+    }
+    if !extras.is_empty() {
+        block.push_str("\n# Project patterns\n");
+        for pattern in extras {
+            block.push_str(pattern);
+            block.push('\n');
+        }
+    }
+
+    block.push_str(END_MARKER);
+    block.push('\n');
+    block
+}
+
+/// Line range of the managed block in `lines`, markers included
+fn block_range(lines: &[&str]) -> Option<(usize, usize)> {
+    let begin = lines.iter().position(|line| line.trim() == BEGIN_MARKER)?;
+    let end = lines[begin..].iter().position(|line| line.trim() == END_MARKER)?;
+    Some((begin, begin + end))
+}
+
+/// Patterns of the managed block in `existing` that `template` does not have,
+/// i.e. the extra patterns added by an earlier regeneration
+fn block_extras(existing: &str, template: &str) -> Vec<String> {
+    let lines: Vec<&str> = existing.lines().collect();
+    let Some((begin, end)) = block_range(&lines) else {
+        return Vec::new();
+    };
+
+    let template: Vec<&str> = template.lines().map(str::trim).collect();
+    lines[begin + 1..end]
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !template.contains(line))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Replace the managed block of `existing` with `block`, or append the block when
+/// the file has none. Lines outside the markers are kept as they are.
+pub fn merge(existing: &str, block: &str) -> String {
+    let lines: Vec<&str> = existing.lines().collect();
+
+    match block_range(&lines) {
+        Some((begin, end)) => {
+            let mut merged = String::new();
+            for line in &lines[..begin] {
+                merged.push_str(line);
+                merged.push('\n');
+            }
+            merged.push_str(block);
+            for line in &lines[end + 1..] {
+                merged.push_str(line);
+                merged.push('\n');
+            }
+            merged
+        }
+        None if existing.trim().is_empty() => block.to_string(),
+        None => {
+            let mut merged = existing.trim_end().to_string();
+            merged.push_str("\n\n");
+            merged.push_str(block);
+            merged
+        }
+    }
+}
+
+/// Write the managed block into the project's .gitignore, keeping user edits and
+/// the extra patterns of earlier regenerations. Returns the new file contents.
+pub fn write_managed_block(project_path: &str, template: &str, extra_patterns: &[String]) -> io::Result<String> {
+    let path = Path::new(project_path).join(FILE_NAME);
+    let existing = match fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    let mut extras = block_extras(&existing, template);
+    extras.extend_from_slice(extra_patterns);
+    let merged = merge(&existing, &managed_block(template, &extras));
+    if merged != existing {
+        fs::write(&path, &merged)?;
+    }
+    Ok(merged)
+}
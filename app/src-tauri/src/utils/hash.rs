@@ -0,0 +1,114 @@
+//! Stable hashing: FNV-1a for short keys and SHA-256 for contents and files
+
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a hash of the given parts, with a separator between parts so
+/// that ("ab", "c") and ("a", "bc") differ. Stable across runs and platforms.
+pub fn fnv1a_64(parts: &[&str]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            hash ^= 0xff;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        for byte in part.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Bytes read from a file at a time while hashing it
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Lowercase hex digits of a digest
+pub fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// SHA-256 of some bytes in hex
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+/// SHA-256 of a file's content in hex, read in fixed-size chunks
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; READ_CHUNK];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => hasher.update(&buffer[..read]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::temp_dir;
+
+    #[test]
+    fn sha256_matches_the_nist_vectors() {
+        // FIPS 180-4 examples and a NIST CAVS short message
+        let vectors: [(&[u8], &str); 5] = [
+            (b"", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+            (b"abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+            ),
+            (&[0xbd], "68325720aabd7c82f30f554b313d0570c95accbb7dc4b5aae11204c08ffe732b"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(sha256_hex(input), expected, "input of {} bytes", input.len());
+        }
+    }
+
+    #[test]
+    fn sha256_file_streams_files_larger_than_a_chunk() {
+        let dir = temp_dir();
+        let path = dir.join("big.bin");
+        let data: Vec<u8> = (0..READ_CHUNK * 3 + 17).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        assert_eq!(sha256_file(&path).unwrap(), sha256_hex(&data));
+
+        let empty = dir.join("empty");
+        std::fs::write(&empty, b"").unwrap();
+        assert_eq!(
+            sha256_file(&empty).unwrap(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        let missing = sha256_file(&dir.join("missing")).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fnv1a_matches_the_reference_values_and_separates_parts() {
+        assert_eq!(fnv1a_64(&[]), FNV_OFFSET_BASIS);
+        assert_eq!(fnv1a_64(&[""]), FNV_OFFSET_BASIS);
+        assert_eq!(fnv1a_64(&["a"]), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a_64(&["foobar"]), 0x8594_4171_f739_67e8);
+
+        assert_ne!(fnv1a_64(&["ab", "c"]), fnv1a_64(&["a", "bc"]));
+        assert_ne!(fnv1a_64(&["abc"]), fnv1a_64(&["ab", "c"]));
+        assert_eq!(fnv1a_64(&["title", "body"]), fnv1a_64(&["title", "body"]));
+    }
+}
@@ -0,0 +1,305 @@
+//! Markdown to HTML for self-contained exports
+//!
+//! Notes are parsed by pulldown-cmark with tables, footnotes, strikethrough,
+//! task lists and wikilinks. Before the HTML is written, raw HTML is turned into escaped
+//! text, links that would leave the document unsafely keep only their label,
+//! and control characters are dropped, so note text cannot inject markup.
+
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use super::markdown::{wikilink_display, wikilink_target};
+
+/// Looks up where the links and images of a note point
+pub trait Resolver {
+    /// `href` of the note a wikilink target names, such as "#note-…" when it is
+    /// part of the document. None renders the link as plain text.
+    fn note_href(&mut self, target: &str) -> Option<String>;
+
+    /// `src` for an image reference, usually a data URI. None renders a placeholder.
+    fn image_source(&mut self, reference: &str) -> Option<String>;
+}
+
+/// Escape text for use in element content and quoted attribute values
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+/// Render note content as an HTML fragment
+pub fn render(text: &str, resolver: &mut dyn Resolver) -> String {
+    prerender(text).resolve(resolver)
+}
+
+/// Render note content up to its wikilinks and images, which are left as slots
+/// for `Prerendered::resolve`. The result depends on the text alone, so it can be
+/// kept for as long as the text and `RENDERER_VERSION` stay the same.
+pub fn prerender(text: &str) -> Prerendered {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_WIKILINKS;
+    let mut prerendered = Prerendered::default();
+    let events = sanitize(Parser::new_ext(text, options), &mut prerendered.slots);
+    html::push_html(&mut prerendered.html, events.into_iter());
+    prerendered
+}
+
+/// Note content rendered by `prerender`, waiting for its links and images
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Prerendered {
+    /// HTML with each slot marked by SLOT_START, its index and SLOT_END
+    html: String,
+    slots: Vec<Slot>,
+}
+
+/// Markup whose output depends on the document the note is rendered into
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Slot {
+    /// Wikilink with its escaped display text
+    Link { target: String, display: String },
+    /// Image with its unescaped alt text
+    Image { reference: String, alt: String },
+}
+
+impl Prerendered {
+    /// The finished fragment, with every wikilink and image looked up through `resolver` in order
+    pub fn resolve(&self, resolver: &mut dyn Resolver) -> String {
+        let mut out = String::with_capacity(self.html.len());
+        let mut rest = self.html.as_str();
+        while let Some(start) = rest.find(SLOT_START) {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + SLOT_START.len_utf8()..];
+            let end = after.find(SLOT_END).unwrap_or(after.len());
+            if let Some(slot) = after[..end].parse::<usize>().ok().and_then(|index| self.slots.get(index)) {
+                out.push_str(&slot.resolve(resolver));
+            }
+            rest = after.get(end + SLOT_END.len_utf8()..).unwrap_or_default();
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+impl Slot {
+    fn resolve(&self, resolver: &mut dyn Resolver) -> String {
+        match self {
+            Slot::Link { target, display } => match resolver.note_href(target) {
+                Some(href) => format!("<a href=\"{}\">{}</a>", escape(&href), display),
+                None => format!("<span class=\"missing-link\">{}</span>", display),
+            },
+            Slot::Image { reference, alt } => match resolver.image_source(reference) {
+                Some(src) => format!("<img src=\"{}\" alt=\"{}\">", escape(&src), escape(alt)),
+                None => format!("<span class=\"attachment\">[attachment: {}]</span>", escape(alt)),
+            },
+        }
+    }
+}
+
+/// Marks a slot in prerendered HTML. Control characters never reach the
+/// output through escaping, so they cannot be confused with note text.
+const SLOT_START: char = '\u{1}';
+const SLOT_END: char = '\u{2}';
+
+/// Bumped whenever rendering changes, so prerendered notes from before are not reused
+pub const RENDERER_VERSION: u32 = 2;
+
+fn push_slot(slots: &mut Vec<Slot>, slot: Slot) -> String {
+    slots.push(slot);
+    format!("{}{}{}", SLOT_START, slots.len() - 1, SLOT_END)
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        // Not allowed in HTML, and used to mark slots in prerendered HTML
+        c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+        _ => out.push(c),
+    }
+}
+
+/// The sanitising pass over parsed notes: raw HTML becomes text, unsafe links
+/// lose their target, wikilinks and images become slots, and control
+/// characters are dropped from text and code
+fn sanitize<'a>(parser: Parser<'a>, slots: &mut Vec<Slot>) -> Vec<Event<'a>> {
+    let mut events = Vec::new();
+    // Whether each open link was kept, so its end is dropped along with it
+    let mut links = Vec::new();
+    let mut parser = parser.into_iter();
+
+    while let Some(event) = parser.next() {
+        match event {
+            Event::Start(Tag::Link { link_type: LinkType::WikiLink { has_pothole }, dest_url, .. }) => {
+                let label = plain_text(&mut parser, TagEnd::Link);
+                let display = if has_pothole && !label.trim().is_empty() { label.trim() } else { wikilink_display(&dest_url) };
+                let slot = Slot::Link { target: wikilink_target(&dest_url).to_string(), display: escape(display) };
+                events.push(Event::InlineHtml(push_slot(slots, slot).into()));
+            }
+            Event::Start(Tag::Link { dest_url, title, id, link_type }) => {
+                let kept = is_safe_href(&dest_url);
+                links.push(kept);
+                if kept {
+                    events.push(Event::Start(Tag::Link { link_type, dest_url, title, id }));
+                }
+            }
+            Event::End(TagEnd::Link) => {
+                if links.pop().unwrap_or(false) {
+                    events.push(event);
+                }
+            }
+            Event::Start(Tag::Image { link_type, dest_url, .. }) => {
+                let alt = plain_text(&mut parser, TagEnd::Image);
+                let reference = match link_type {
+                    LinkType::WikiLink { .. } => wikilink_target(&dest_url),
+                    _ => &dest_url,
+                };
+                events.push(Event::InlineHtml(image(slots, reference, &alt).into()));
+            }
+            // Raw HTML is shown as written, a block of it as a paragraph
+            Event::Start(Tag::HtmlBlock) => events.push(Event::Start(Tag::Paragraph)),
+            Event::End(TagEnd::HtmlBlock) => events.push(Event::End(TagEnd::Paragraph)),
+            Event::Html(raw) | Event::InlineHtml(raw) => events.push(Event::Text(without_controls(raw))),
+            Event::Text(text) => events.push(Event::Text(without_controls(text))),
+            Event::Code(code) => events.push(Event::Code(without_controls(code))),
+            event => events.push(event),
+        }
+    }
+    events
+}
+
+/// Text of the events up to the end of the current link or image
+fn plain_text<'a>(events: &mut impl Iterator<Item = Event<'a>>, end: TagEnd) -> String {
+    let mut text = String::new();
+    let mut depth = 0;
+    for event in events {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(tag) if depth == 0 && tag == end => break,
+            Event::End(_) => depth -= 1,
+            Event::Text(part) | Event::Code(part) => text.push_str(&without_controls(part)),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Not allowed in HTML, and used to mark slots in prerendered HTML
+fn without_controls(text: CowStr<'_>) -> CowStr<'_> {
+    let is_dropped = |c: char| c.is_control() && !matches!(c, '\n' | '\r' | '\t');
+    if text.contains(is_dropped) {
+        text.replace(is_dropped, "").into()
+    } else {
+        text
+    }
+}
+
+/// Only web, mail and in-document links; file and script URLs are dropped
+fn is_safe_href(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    ["http://", "https://", "mailto:", "#"].iter().any(|scheme| lower.starts_with(scheme))
+}
+
+fn image(slots: &mut Vec<Slot>, reference: &str, alt: &str) -> String {
+    let alt = if alt.trim().is_empty() {
+        reference.rsplit(['/', '\\']).next().unwrap_or(reference)
+    } else {
+        alt.trim()
+    };
+    push_slot(slots, Slot::Image { reference: reference.to_string(), alt: alt.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Links;
+
+    impl Resolver for Links {
+        fn note_href(&mut self, target: &str) -> Option<String> {
+            (target == "Known").then(|| "known.html".to_string())
+        }
+
+        fn image_source(&mut self, reference: &str) -> Option<String> {
+            reference.ends_with(".png").then(|| format!("files/{}", reference))
+        }
+    }
+
+    #[test]
+    fn prerendered_content_resolves_links_when_used() {
+        let prerendered = prerender("[[Known]], [[Other|the other]] and ![plot](a.png) ![[b.pdf]]");
+        assert_eq!(
+            prerendered.resolve(&mut Links),
+            "<p><a href=\"known.html\">Known</a>, <span class=\"missing-link\">the other</span> and \
+             <img src=\"files/a.png\" alt=\"plot\"> <span class=\"attachment\">[attachment: b.pdf]</span></p>\n"
+        );
+
+        let json = serde_json::to_string(&prerendered).unwrap();
+        let restored: Prerendered = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.resolve(&mut Links), render("[[Known]], [[Other|the other]] and ![plot](a.png) ![[b.pdf]]", &mut Links));
+    }
+
+    #[test]
+    fn note_text_cannot_forge_a_slot() {
+        let text = "[[Known]] \u{1}0\u{2}";
+        assert_eq!(render(text, &mut Links), "<p><a href=\"known.html\">Known</a> 0</p>\n");
+    }
+
+    #[test]
+    fn blocks_render_to_their_elements() {
+        assert_eq!(
+            render("# Title ##\n\n- one\n- [x] done\n  1. nested\n\n> quote\n\n---", &mut Links),
+            "<h1>Title</h1>\n<ul>\n<li>one</li>\n<li><input disabled=\"\" type=\"checkbox\" checked=\"\"/>\ndone\n<ol>\n\
+             <li>nested</li>\n</ol>\n</li>\n</ul>\n<blockquote>\n<p>quote</p>\n</blockquote>\n<hr />\n"
+        );
+        assert_eq!(
+            render("```rust\nfn main() { \"<&>\" }\n```", &mut Links),
+            "<pre><code class=\"language-rust\">fn main() { \"&lt;&amp;&gt;\" }\n</code></pre>\n"
+        );
+        // Rows are cut or padded to the header's columns
+        assert_eq!(
+            render("| a | b |\n| :--- | ---: |\n| 1 | 2 | 3 |\n| 4 |", &mut Links),
+            "<table><thead><tr><th style=\"text-align: left\">a</th><th style=\"text-align: right\">b</th></tr></thead><tbody>\n\
+             <tr><td style=\"text-align: left\">1</td><td style=\"text-align: right\">2</td></tr>\n\
+             <tr><td style=\"text-align: left\">4</td><td style=\"text-align: right\"></td></tr>\n</tbody></table>\n"
+        );
+    }
+
+    #[test]
+    fn inline_markup_and_emphasis_runs() {
+        assert_eq!(
+            render("Some *em* and **strong** and ***both***, snake_case and `a<b`, ~~gone~~.", &mut Links),
+            "<p>Some <em>em</em> and <strong>strong</strong> and <em><strong>both</strong></em>, \
+             snake_case and <code>a&lt;b</code>, <del>gone</del>.</p>\n"
+        );
+        assert_eq!(render("*unclosed **mix and * spaced *", &mut Links), "<p>*unclosed **mix and * spaced *</p>\n");
+    }
+
+    #[test]
+    fn unknown_and_unsafe_markup_degrades_to_text() {
+        assert_eq!(
+            render("Run <script>alert(1)</script> [x](javascript:alert(1)) [ok](https://e.org)", &mut Links),
+            "<p>Run &lt;script&gt;alert(1)&lt;/script&gt; x <a href=\"https://e.org\">ok</a></p>\n"
+        );
+        assert_eq!(
+            render("<div onclick=\"x()\">raw</div>\n\nand [[Known#Intro]] [[Known|*it*]]", &mut Links),
+            "<p>&lt;div onclick=\"x()\"&gt;raw&lt;/div&gt;\n</p>\n\
+             <p>and <a href=\"known.html\">Known</a> <a href=\"known.html\">it</a></p>\n"
+        );
+        assert_eq!(
+            render("Footnote[^1]\n\n[^1]: Note", &mut Links),
+            "<p>Footnote<sup class=\"footnote-reference\"><a href=\"#1\">1</a></sup></p>\n\
+             <div class=\"footnote-definition\" id=\"1\"><sup class=\"footnote-definition-label\">1</sup>\n<p>Note</p>\n</div>\n"
+        );
+        assert_eq!(render("", &mut Links), "");
+    }
+}
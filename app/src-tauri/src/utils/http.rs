@@ -0,0 +1,80 @@
+//! The one HTTP client shared by every feature that reaches the network
+
+use std::io::Read;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+
+/// Longest a request may take, connecting included
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest response body read, in bytes
+const MAX_BODY_BYTES: u64 = 10 * 1024 * 1024;
+
+const USER_AGENT: &str = concat!("ResearchVault/", env!("CARGO_PKG_VERSION"));
+
+fn agent() -> &'static ureq::Agent {
+    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+    AGENT.get_or_init(|| {
+        ureq::AgentBuilder::new()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(USER_AGENT)
+            .build()
+    })
+}
+
+/// Whether `location` is an http(s) URL rather than a file path
+pub fn is_url(location: &str) -> bool {
+    let location = location.trim_start();
+    ["http://", "https://"]
+        .iter()
+        .any(|scheme| location.get(..scheme.len()).is_some_and(|start| start.eq_ignore_ascii_case(scheme)))
+}
+
+/// GET `url` and return its body as text. Fails without a request when
+/// `offline` is set, on any status other than 2xx and on bodies over 10 MB.
+pub fn get_text(url: &str, offline: bool) -> AppResult<String> {
+    if offline {
+        return Err(AppError::Network("Offline mode is on; turn it off to fetch from the web".into()));
+    }
+    if !is_url(url) {
+        return Err(AppError::InvalidInput(format!("Not an http(s) URL: {}", url)));
+    }
+
+    let response = agent().get(url.trim()).call().map_err(|e| match e {
+        ureq::Error::Status(code, _) => AppError::Network(format!("{} answered with status {}", url, code)),
+        ureq::Error::Transport(transport) => AppError::Network(format!("Failed to fetch {}: {}", url, transport)),
+    })?;
+
+    let mut body = String::new();
+    response
+        .into_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| AppError::Network(format!("Failed to read the response from {}: {}", url, e)))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(AppError::Network(format!("Response from {} is larger than {} bytes", url, MAX_BODY_BYTES)));
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_http_urls_are_fetched() {
+        assert!(is_url("https://example.com/feed.yml"));
+        assert!(is_url("  HTTP://example.com"));
+        assert!(!is_url("/home/me/feed.yml"));
+        assert!(!is_url("C:\\feeds\\http.yml"));
+        assert!(!is_url("ftp://example.com"));
+    }
+
+    #[test]
+    fn offline_mode_blocks_requests() {
+        assert!(matches!(get_text("https://example.com", true), Err(AppError::Network(_))));
+        assert!(matches!(get_text("file:///etc/passwd", false), Err(AppError::InvalidInput(_))));
+    }
+}
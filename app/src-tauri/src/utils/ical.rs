@@ -0,0 +1,57 @@
+//! Minimal iCalendar (RFC 5545) writing helpers
+
+/// Longest content line in octets, excluding the CRLF
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Escape a TEXT value: backslashes, semicolons, commas and line breaks
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\r' => {
+                // CRLF and lone CR both become one escaped newline
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                escaped.push_str("\\n");
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format a UTC timestamp as a DATE-TIME value, e.g. "20240501T120000Z"
+pub fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Format a content line `NAME:value`, folded at 75 octets and terminated by CRLF.
+/// The value must already be escaped; folds never split a UTF-8 character.
+pub fn content_line(name: &str, value: &str) -> String {
+    let line = format!("{}:{}", name, value);
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3 + 2);
+    let mut octets = 0;
+
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if octets + len > MAX_LINE_OCTETS {
+            // The leading space of a continuation line counts towards its length
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += len;
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
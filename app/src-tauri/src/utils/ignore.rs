@@ -0,0 +1,237 @@
+//! Gitignore-style path matching
+//!
+//! Supports comments, `!` negation, trailing `/` for directories, patterns
+//! anchored by an inner or leading `/`, `*`, `?`, `[...]` classes and `**`.
+//! The last matching pattern wins.
+
+/// One parsed ignore pattern
+#[derive(Debug, Clone)]
+struct IgnorePattern {
+    glob: Vec<char>,
+    /// Directory (relative to the project root) whose .gitignore declared it
+    base: String,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the whole path below `base` instead of the file name
+    anchored: bool,
+}
+
+/// Ordered set of ignore patterns
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the lines of a .gitignore file located in `base` ("" for the root)
+    pub fn add_gitignore(&mut self, base: &str, contents: &str) {
+        for line in contents.lines() {
+            self.add_pattern(base, line);
+        }
+    }
+
+    /// Add one pattern declared in `base`; blank lines and comments are ignored
+    pub fn add_pattern(&mut self, base: &str, line: &str) {
+        let mut pattern = line.trim_end_matches(['\r', ' ']);
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return;
+        }
+
+        // A leading "!" negates; "\\!" and "\\#" stand for a literal "!" or "#"
+        let negated = pattern.starts_with('!');
+        if negated || pattern.starts_with("\\!") || pattern.starts_with("\\#") {
+            pattern = &pattern[1..];
+        }
+
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() {
+            return;
+        }
+
+        self.patterns.push(IgnorePattern {
+            glob: pattern.chars().collect(),
+            base: base.trim_matches('/').to_string(),
+            negated,
+            dir_only,
+            anchored,
+        });
+    }
+
+    /// Whether a "/"-separated path relative to the project root is ignored
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+
+        for pattern in &self.patterns {
+            if pattern.dir_only && !is_dir {
+                continue;
+            }
+
+            let below_base = if pattern.base.is_empty() {
+                Some(path)
+            } else {
+                path.strip_prefix(pattern.base.as_str())
+                    .and_then(|rest| rest.strip_prefix('/'))
+            };
+            let Some(relative) = below_base else {
+                continue;
+            };
+
+            let candidate = if pattern.anchored {
+                relative
+            } else {
+                relative.rsplit('/').next().unwrap_or(relative)
+            };
+
+            let candidate: Vec<char> = candidate.chars().collect();
+            if glob_match(&pattern.glob, &candidate) {
+                ignored = !pattern.negated;
+            }
+        }
+
+        ignored
+    }
+}
+
+/// Match a gitignore glob against a path; `*`, `?` and classes stop at "/"
+fn glob_match(glob: &[char], path: &[char]) -> bool {
+    match glob {
+        [] => path.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            // "**/" spans zero or more whole directories
+            glob_match(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(i, c)| *c == '/' && glob_match(rest, &path[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|i| glob_match(rest, &path[i..])),
+        ['*', rest @ ..] => {
+            let segment_end = path.iter().position(|c| *c == '/').unwrap_or(path.len());
+            (0..=segment_end).any(|i| glob_match(rest, &path[i..]))
+        }
+        ['?', rest @ ..] => matches!(path.first(), Some(c) if *c != '/') && glob_match(rest, &path[1..]),
+        ['[', rest @ ..] => match match_class(rest, path.first().copied()) {
+            Some((true, after)) => glob_match(after, &path[1..]),
+            Some((false, _)) => false,
+            // No closing bracket: a literal "["
+            None => path.first() == Some(&'[') && glob_match(rest, &path[1..]),
+        },
+        ['\\', escaped, rest @ ..] => path.first() == Some(escaped) && glob_match(rest, &path[1..]),
+        [literal, rest @ ..] => path.first() == Some(literal) && glob_match(rest, &path[1..]),
+    }
+}
+
+/// Match one character against a `[...]` class (glob positioned after "[").
+/// Returns whether it matched and the glob after "]", or None if unterminated.
+fn match_class(glob: &[char], c: Option<char>) -> Option<(bool, &[char])> {
+    let (negated, mut i) = match glob.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    let mut matched = false;
+    let mut first = true;
+    while i < glob.len() {
+        if glob[i] == ']' && !first {
+            let hit = c.is_some_and(|c| c != '/' && matched != negated);
+            return Some((hit, &glob[i + 1..]));
+        }
+        first = false;
+
+        let low = glob[i];
+        if i + 2 < glob.len() && glob[i + 1] == '-' && glob[i + 2] != ']' {
+            let high = glob[i + 2];
+            matched |= c.is_some_and(|c| low <= c && c <= high);
+            i += 3;
+        } else {
+            matched |= c == Some(low);
+            i += 1;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(base: &str, gitignore: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::new();
+        rules.add_gitignore(base, gitignore);
+        rules
+    }
+
+    #[test]
+    fn later_negations_re_include_paths() {
+        let rules = parse("", "*.csv\n!keep.csv\n# comment\n\ndata/raw/\n!data/raw/\n");
+        assert!(rules.is_ignored("big.csv", false));
+        assert!(rules.is_ignored("data/big.csv", false));
+        assert!(!rules.is_ignored("keep.csv", false));
+        assert!(!rules.is_ignored("data/keep.csv", false));
+        assert!(!rules.is_ignored("data/raw", true));
+        assert!(!rules.is_ignored("# comment", false));
+
+        // The last matching pattern wins
+        let rules = parse("", "!keep.csv\n*.csv\n");
+        assert!(rules.is_ignored("keep.csv", false));
+    }
+
+    #[test]
+    fn escaped_bang_and_hash_are_literal() {
+        let rules = parse("", "\\!important.txt\n\\#notes.md\n");
+        assert!(rules.is_ignored("!important.txt", false));
+        assert!(rules.is_ignored("docs/#notes.md", false));
+        assert!(!rules.is_ignored("important.txt", false));
+        assert!(!rules.is_ignored("notes.md", false));
+    }
+
+    #[test]
+    fn trailing_slash_matches_directories_only() {
+        let rules = parse("", "build/\nlogs\n");
+        assert!(rules.is_ignored("build", true));
+        assert!(rules.is_ignored("src/build", true));
+        assert!(!rules.is_ignored("build", false));
+        assert!(rules.is_ignored("logs", true));
+        assert!(rules.is_ignored("logs", false));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_are_anchored_to_their_gitignore() {
+        let mut rules = parse("", "/out\ndocs/*.pdf\n");
+        rules.add_gitignore("data", "raw/**/*.csv\n*.tmp\n");
+
+        assert!(rules.is_ignored("out", true));
+        assert!(!rules.is_ignored("src/out", true));
+        assert!(rules.is_ignored("docs/paper.pdf", false));
+        assert!(!rules.is_ignored("docs/drafts/paper.pdf", false));
+        assert!(!rules.is_ignored("other/docs/paper.pdf", false));
+
+        assert!(rules.is_ignored("data/raw/a.csv", false));
+        assert!(rules.is_ignored("data/raw/2024/05/a.csv", false));
+        assert!(!rules.is_ignored("raw/a.csv", false));
+        assert!(rules.is_ignored("data/deep/x.tmp", false));
+        assert!(!rules.is_ignored("x.tmp", false));
+        assert!(!rules.is_ignored("database/x.tmp", false));
+    }
+
+    #[test]
+    fn globs_follow_gitignore_rules() {
+        let rules = parse("", "fig?.png\n[abc]*.log\n[!0-9]x\n**/cache\n");
+        assert!(rules.is_ignored("fig1.png", false));
+        assert!(!rules.is_ignored("fig10.png", false));
+        assert!(rules.is_ignored("b-run.log", false));
+        assert!(!rules.is_ignored("d-run.log", false));
+        assert!(rules.is_ignored("ax", false));
+        assert!(!rules.is_ignored("1x", false));
+        assert!(rules.is_ignored("cache", true));
+        assert!(rules.is_ignored("a/b/cache", true));
+    }
+}
@@ -0,0 +1,28 @@
+//! JSON Merge Patch (RFC 7396)
+
+use serde_json::{Map, Value};
+
+/// Apply a merge patch to `target` in place.
+///
+/// Objects are merged key by key, a `null` value removes the key, and any other
+/// value (including arrays) replaces the target value wholesale.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch_map) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    if let Value::Object(target_map) = target {
+        for (key, value) in patch_map {
+            if value.is_null() {
+                target_map.remove(key);
+            } else {
+                merge_patch(target_map.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Size limits of user input. Every DTO is checked against these by
+//! `validate` before it is stored; lengths are in characters.
+
+/// Longest project, template or deadline name
+pub const MAX_NAME_LEN: usize = 200;
+
+/// Longest task, note or reference title, or template title pattern
+pub const MAX_TITLE_LEN: usize = 500;
+
+/// Longest research question
+pub const MAX_QUESTION_LEN: usize = 2_000;
+
+/// Longest description, answer summary, abstract or deadline notes
+pub const MAX_DESCRIPTION_LEN: usize = 20_000;
+
+/// Longest URL, DOI, citation key, recurrence rule or other one-line value
+pub const MAX_FIELD_LEN: usize = 2_048;
+
+/// Longest file system path
+pub const MAX_PATH_LEN: usize = 4_096;
+
+/// Most tags on one project, task, note or template
+pub const MAX_TAGS: usize = 50;
+
+/// Longest tag, including its parent path such as "method/bayesian"
+pub const MAX_TAG_LEN: usize = 100;
+
+/// Most subdirectories in a project layout
+pub const MAX_LAYOUT_DIRS: usize = 100;
+
+/// Most authors of a reference
+pub const MAX_AUTHORS: usize = 500;
+
+/// Earliest accepted date: 1900-01-01T00:00:00Z
+pub const MIN_TIMESTAMP: i64 = -2_208_988_800;
+
+/// Latest accepted date: 9999-12-31T23:59:59Z
+pub const MAX_TIMESTAMP: i64 = 253_402_300_799;
+
+/// Furthest ahead of the due date a reminder can be set: one year, in minutes
+pub const MAX_REMIND_BEFORE_MINUTES: i64 = 366 * 24 * 60;
+
+/// Limits that can be changed in the settings, read with `SettingsService::limits`
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Largest note or template content, in bytes
+    pub note_max_bytes: usize,
+}
@@ -0,0 +1,238 @@
+//! Application log built on `tracing`: events go to stderr and, once `init`
+//! is given the app's logs directory, to one file per day there as well
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tracing::level_filters::LevelFilter;
+use tracing::Instrument;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+use crate::error::{AppError, AppResult};
+
+/// Daily log files are named e.g. "research-vault.2024-05-01.log"
+const LOG_FILE_PREFIX: &str = "research-vault";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Daily log files kept before the oldest are removed
+const MAX_LOG_FILES: usize = 7;
+
+/// Severity of a log line, from most to least severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+impl From<LevelFilter> for LogLevel {
+    fn from(filter: LevelFilter) -> Self {
+        // OFF is never set from here; treat it as the least verbose level
+        if filter >= LevelFilter::TRACE {
+            LogLevel::Trace
+        } else if filter >= LevelFilter::DEBUG {
+            LogLevel::Debug
+        } else if filter >= LevelFilter::INFO {
+            LogLevel::Info
+        } else if filter >= LevelFilter::WARN {
+            LogLevel::Warn
+        } else {
+            LogLevel::Error
+        }
+    }
+}
+
+/// Changes the level of the installed subscriber without reinstalling it
+static LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+/// Directory the daily files are written to, if any
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Install the global subscriber, writing to daily files in `dir` as well as
+/// stderr. If the directory can't be used the log still goes to stderr and the
+/// error is returned. Only the first call has an effect.
+pub fn init(dir: Option<&Path>, level: LogLevel) -> io::Result<()> {
+    let (appender, result) = match dir.map(open_appender).transpose() {
+        Ok(appender) => (appender, Ok(())),
+        Err(e) => (None, Err(e)),
+    };
+
+    let (filter, handle) = reload::Layer::new(LevelFilter::from(level));
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(io::stderr).with_span_events(FmtSpan::CLOSE))
+        .with(appender.map(|appender| {
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(appender)
+                .with_span_events(FmtSpan::CLOSE)
+        }))
+        .try_init();
+    if installed.is_ok() {
+        let _ = LEVEL.set(handle);
+    }
+
+    result
+}
+
+fn open_appender(dir: &Path) -> io::Result<RollingFileAppender> {
+    fs::create_dir_all(dir)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(io::Error::other)?;
+    let _ = LOG_DIR.set(dir.to_path_buf());
+    Ok(appender)
+}
+
+/// Most verbose level currently written
+pub fn level() -> LogLevel {
+    LEVEL
+        .get()
+        .and_then(|handle| handle.clone_current())
+        .map_or(LogLevel::Info, LogLevel::from)
+}
+
+/// Change the most verbose level written from now on
+pub fn set_level(level: LogLevel) {
+    if let Some(handle) = LEVEL.get() {
+        if let Err(e) = handle.reload(LevelFilter::from(level)) {
+            eprintln!("Failed to change the log level: {}", e);
+        }
+    }
+}
+
+/// An error followed by each of its sources, e.g. "outer: inner: root cause"
+pub fn error_chain(err: &dyn Error) -> String {
+    let mut chain = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        chain.push_str(": ");
+        chain.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    chain
+}
+
+/// Await a command inside a span named after it and log a failure. Only the
+/// command name and error are recorded, never arguments, so note content
+/// stays out of the log; the span's close line carries the duration.
+pub async fn timed<T, F>(command: &str, fut: F) -> AppResult<T>
+where
+    F: Future<Output = AppResult<T>>,
+{
+    let started = Instant::now();
+    let result = fut.instrument(tracing::debug_span!("command", name = command)).await;
+
+    if let Err(e) = &result {
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        let chain = error_chain(e);
+        match e {
+            AppError::Database { .. }
+            | AppError::DatabaseCorrupt { .. }
+            | AppError::FileSystem(_)
+            | AppError::Internal(_)
+            | AppError::Serialization(_)
+            | AppError::System(_) => tracing::error!(command, elapsed_ms, code = e.code(), "{}", chain),
+            _ => tracing::warn!(command, elapsed_ms, code = e.code(), "{}", chain),
+        }
+    }
+
+    result
+}
+
+/// Last `count` lines across the log files, oldest first
+pub fn recent_lines(count: usize) -> io::Result<Vec<String>> {
+    let Some(dir) = LOG_DIR.get() else {
+        return Ok(Vec::new());
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    for path in log_files(dir)?.into_iter().rev() {
+        if lines.len() >= count {
+            break;
+        }
+        let content = fs::read_to_string(&path)?;
+        let needed = count - lines.len();
+        let mut tail: Vec<String> = content.lines().rev().take(needed).map(str::to_string).collect();
+        tail.reverse();
+        tail.append(&mut lines);
+        lines = tail;
+    }
+
+    Ok(lines)
+}
+
+/// Log files in `dir`, oldest first (the date in the name sorts chronologically)
+fn log_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let prefix = format!("{}.", LOG_FILE_PREFIX);
+    let suffix = format!(".{}", LOG_FILE_SUFFIX);
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(&suffix))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::temp_dir;
+    use std::io::Write;
+
+    #[test]
+    fn daily_files_are_found_by_name() {
+        let dir = temp_dir();
+        let mut appender = open_appender(&dir).unwrap();
+        writeln!(appender, "first line").unwrap();
+        appender.flush().unwrap();
+        fs::write(dir.join("other.txt"), "not a log").unwrap();
+
+        let files = log_files(&dir).unwrap();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_str().unwrap().to_string();
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        assert_eq!(name, format!("research-vault.{}.log", today));
+        assert_eq!(fs::read_to_string(&files[0]).unwrap(), "first line\n");
+    }
+
+    #[test]
+    fn levels_convert_both_ways() {
+        for level in [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace] {
+            assert_eq!(LogLevel::from(LevelFilter::from(level)), level);
+        }
+        assert_eq!(LogLevel::from(LevelFilter::OFF), LogLevel::Error);
+    }
+}
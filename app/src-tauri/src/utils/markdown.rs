@@ -0,0 +1,464 @@
+//! Markdown helpers for sharing note content outside the vault and for
+//! moving it between vaults
+
+/// Flatten vault-specific syntax for sharing while keeping regular markdown:
+/// wikilinks become their display text and embedded files become
+/// "[attachment: name]" placeholders.
+pub fn flatten_for_sharing(text: &str) -> String {
+    render(text, false)
+}
+
+/// Render markdown as plain text for pasting into emails.
+///
+/// Headings, blockquotes, emphasis and code markers are dropped, list structure
+/// is kept (unordered bullets become "- "), links render as "text (url)",
+/// wikilinks become their display text and embedded files become placeholders.
+pub fn strip_markdown(text: &str) -> String {
+    render(text, true)
+}
+
+/// Targets of the `[[wikilinks]]` in a note, in order of first appearance and
+/// without case-insensitive repeats. Aliases and heading anchors are dropped;
+/// links inside code blocks or inline code and embeds (`![[file]]`) are ignored.
+pub fn wikilink_targets(text: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == '`' {
+                let run = chars[i..].iter().take_while(|ch| **ch == '`').count();
+                match find(&chars, i + run, &"`".repeat(run)) {
+                    Some(end) => i = end + run,
+                    None => i += run,
+                }
+                continue;
+            }
+
+            let embed = chars[i] == '!' && starts_with(&chars, i + 1, "[[");
+            let open = if embed { i + 1 } else { i };
+            if starts_with(&chars, open, "[[") {
+                if let Some(end) = find(&chars, open + 2, "]]") {
+                    let inner: String = chars[open + 2..end].iter().collect();
+                    let target = wikilink_target(&inner);
+                    if !embed
+                        && !target.is_empty()
+                        && !targets.iter().any(|t| t.to_lowercase() == target.to_lowercase())
+                    {
+                        targets.push(target.to_string());
+                    }
+                    i = end + 2;
+                    continue;
+                }
+            }
+
+            i += 1;
+        }
+    }
+
+    targets
+}
+
+/// Rewrite the targets of images, links and embeds (`![[file]]`) through
+/// `map`, which returns the new target or None to keep it. Everything else,
+/// including code blocks and inline code, is kept byte for byte.
+pub fn rewrite_file_references(text: &str, mut map: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_code_block = false;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if fence {
+            in_code_block = !in_code_block;
+        }
+        if fence || in_code_block {
+            out.push_str(line);
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == '`' {
+                let run = chars[i..].iter().take_while(|ch| **ch == '`').count();
+                let end = find(&chars, i + run, &"`".repeat(run)).map_or(i + run, |end| end + run);
+                out.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+
+            // Embedded file: ![[file.pdf|alias]]
+            if chars[i] == '!' && starts_with(&chars, i + 1, "[[") {
+                if let Some(end) = find(&chars, i + 3, "]]") {
+                    let inner: String = chars[i + 3..end].iter().collect();
+                    let target = wikilink_target(&inner);
+                    match map(target) {
+                        Some(new_target) => out.push_str(&format!("![[{}]]", inner.replacen(target, &new_target, 1))),
+                        None => out.extend(&chars[i..end + 2]),
+                    }
+                    i = end + 2;
+                    continue;
+                }
+            }
+
+            // Wikilinks name notes, not files
+            if starts_with(&chars, i, "[[") {
+                if let Some(end) = find(&chars, i + 2, "]]") {
+                    out.extend(&chars[i..end + 2]);
+                    i = end + 2;
+                    continue;
+                }
+            }
+
+            // Image ![alt](path) or link [text](path)
+            let open = if chars[i] == '!' && chars.get(i + 1) == Some(&'[') { i + 1 } else { i };
+            if chars[open] == '[' {
+                if let Some((label, url, end)) = parse_link(&chars, open) {
+                    // The target starts after "[label]("
+                    let target_start = open + label.chars().count() + 3;
+                    let target: String = chars[target_start..end - 1].iter().collect();
+                    out.extend(&chars[i..target_start]);
+                    let new_url = if url.is_empty() { None } else { map(&url) };
+                    match new_url {
+                        Some(new_url) => out.push_str(&target.replacen(&url, &new_url, 1)),
+                        None => out.push_str(&target),
+                    }
+                    out.push(')');
+                    i = end;
+                    continue;
+                }
+            }
+
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    out
+}
+
+fn render(text: &str, plain: bool) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            if !plain {
+                lines.push(line.to_string());
+            }
+            continue;
+        }
+
+        // Code is shared verbatim
+        if in_code_block {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if plain {
+            lines.push(render_plain_block(line));
+        } else {
+            lines.push(render_inline(line, false));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Strip block-level markers from one line, keeping indentation and list bullets
+fn render_plain_block(line: &str) -> String {
+    let indent_len = line.len() - line.trim_start().len();
+    let (indent, mut rest) = line.split_at(indent_len);
+
+    if is_horizontal_rule(rest) {
+        return String::new();
+    }
+
+    while let Some(quoted) = rest.strip_prefix('>') {
+        rest = quoted.trim_start();
+    }
+
+    let hashes = rest.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) && rest[hashes..].starts_with(' ') {
+        return render_inline(rest[hashes..].trim(), true);
+    }
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = rest.strip_prefix(bullet) {
+            return format!("{}- {}", indent, render_inline(item, true));
+        }
+    }
+
+    format!("{}{}", indent, render_inline(rest, true))
+}
+
+fn is_horizontal_rule(line: &str) -> bool {
+    let compact: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_'].iter().any(|marker| compact.iter().all(|c| c == marker))
+}
+
+fn render_inline(text: &str, plain: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Embedded file: ![[file.pdf]]
+        if c == '!' && starts_with(&chars, i + 1, "[[") {
+            if let Some(end) = find(&chars, i + 3, "]]") {
+                let inner: String = chars[i + 3..end].iter().collect();
+                out.push_str(&attachment_placeholder(wikilink_target(&inner)));
+                i = end + 2;
+                continue;
+            }
+        }
+
+        // Wikilink: [[target]] or [[target|display]]
+        if starts_with(&chars, i, "[[") {
+            if let Some(end) = find(&chars, i + 2, "]]") {
+                let inner: String = chars[i + 2..end].iter().collect();
+                out.push_str(wikilink_display(&inner));
+                i = end + 2;
+                continue;
+            }
+        }
+
+        // Image: ![alt](path)
+        if c == '!' && chars.get(i + 1) == Some(&'[') {
+            if let Some((alt, url, end)) = parse_link(&chars, i + 1) {
+                let name = if alt.trim().is_empty() {
+                    url.rsplit(['/', '\\']).next().unwrap_or(url.as_str()).to_string()
+                } else {
+                    alt.trim().to_string()
+                };
+                out.push_str(&attachment_placeholder(&name));
+                i = end;
+                continue;
+            }
+        }
+
+        // Link: [text](url)
+        if c == '[' {
+            if let Some((label, url, end)) = parse_link(&chars, i) {
+                if plain {
+                    let label = render_inline(&label, true);
+                    if label.is_empty() || label == url {
+                        out.push_str(&url);
+                    } else {
+                        out.push_str(&format!("{} ({})", label, url));
+                    }
+                } else {
+                    out.extend(&chars[i..end]);
+                }
+                i = end;
+                continue;
+            }
+        }
+
+        if plain {
+            // Escaped punctuation loses its backslash
+            if c == '\\' && chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) {
+                out.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+
+            // Inline code keeps its content verbatim
+            if c == '`' {
+                let run = chars[i..].iter().take_while(|ch| **ch == '`').count();
+                let fence: String = "`".repeat(run);
+                if let Some(end) = find(&chars, i + run, &fence) {
+                    out.extend(&chars[i + run..end]);
+                    i = end + run;
+                    continue;
+                }
+            }
+
+            if c == '~' && chars.get(i + 1) == Some(&'~') {
+                i += 2;
+                continue;
+            }
+
+            // Emphasis markers, unless they stand alone ("a * b") or sit inside a word ("snake_case")
+            if c == '*' || c == '_' {
+                let run = chars[i..].iter().take_while(|ch| **ch == c).count();
+                let before = if i > 0 { chars.get(i - 1) } else { None };
+                let after = chars.get(i + run);
+                let opens = after.is_some_and(|ch| !ch.is_whitespace());
+                let closes = before.is_some_and(|ch| !ch.is_whitespace());
+                let intraword = c == '_'
+                    && before.is_some_and(|ch| ch.is_alphanumeric())
+                    && after.is_some_and(|ch| ch.is_alphanumeric());
+
+                if (opens || closes) && !intraword {
+                    i += run;
+                    continue;
+                }
+
+                out.extend(&chars[i..i + run]);
+                i += run;
+                continue;
+            }
+        }
+
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+/// Parse `[label](url "title")` starting at the opening bracket.
+/// Returns the label, the url and the index just past the closing parenthesis.
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let mut depth = 0;
+    let mut close = None;
+    for (offset, c) in chars[start..].iter().enumerate() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(start + offset);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+
+    let mut depth = 0;
+    let mut end = None;
+    for (offset, c) in chars[close + 1..].iter().enumerate() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(close + 1 + offset);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let end = end?;
+
+    let label: String = chars[start + 1..close].iter().collect();
+    let target: String = chars[close + 2..end].iter().collect();
+    let url = target
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .to_string();
+
+    Some((label, url, end + 1))
+}
+
+/// Target part of a wikilink body, without alias or heading
+pub(super) fn wikilink_target(inner: &str) -> &str {
+    let target = inner.split('|').next().unwrap_or(inner);
+    target.split('#').next().unwrap_or(target).trim()
+}
+
+/// Text a reader sees for a wikilink body
+pub(super) fn wikilink_display(inner: &str) -> &str {
+    match inner.split_once('|') {
+        Some((_, display)) if !display.trim().is_empty() => display.trim(),
+        _ => {
+            let target = wikilink_target(inner);
+            if target.is_empty() {
+                inner.trim()
+            } else {
+                target
+            }
+        }
+    }
+}
+
+fn attachment_placeholder(name: &str) -> String {
+    format!("[attachment: {}]", name)
+}
+
+fn starts_with(chars: &[char], at: usize, pattern: &str) -> bool {
+    pattern.chars().enumerate().all(|(offset, p)| chars.get(at + offset) == Some(&p))
+}
+
+fn find(chars: &[char], from: usize, pattern: &str) -> Option<usize> {
+    (from..chars.len()).find(|&i| starts_with(chars, i, pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_keeps_lists_and_drops_markers() {
+        let cases = [
+            ("# Results\n\n> **Bold** and _light_ claims", "Results\n\nBold and light claims"),
+            ("* one\n  + nested\n1. first", "- one\n  - nested\n1. first"),
+            ("See [the paper](https://doi.org/10.1) and <x>", "See the paper (https://doi.org/10.1) and <x>"),
+            ("[https://a.org](https://a.org)", "https://a.org"),
+            ("Met [[Jane Doe|Jane]] about [[Plan#Goals]]", "Met Jane about Plan"),
+            ("![[scan.pdf]] and ![](img/map%20one.png) and ![Figure 2](f.png)", "[attachment: scan.pdf] and [attachment: map%20one.png] and [attachment: Figure 2]"),
+            ("snake_case, a * b, ~~gone~~, \\*kept\\*", "snake_case, a * b, gone, *kept*"),
+            ("Use `**raw**` here\n---\nafter", "Use **raw** here\n\nafter"),
+            ("```\n# not a heading\n```", "# not a heading"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(strip_markdown(input), expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn sharing_flattens_only_vault_syntax() {
+        let text = "**See** [[Plan|the plan]] and [site](https://a.org)\n![[data.csv]]\n```\n[[kept]]\n```";
+        assert_eq!(
+            flatten_for_sharing(text),
+            "**See** the plan and [site](https://a.org)\n[attachment: data.csv]\n```\n[[kept]]\n```"
+        );
+    }
+
+    #[test]
+    fn wikilink_targets_skip_code_embeds_and_repeats() {
+        let text = "[[Alpha]] then [[beta|B]] and [[alpha#Intro]]\n`[[inline]]` ![[file.png]]\n```\n[[fenced]]\n```\n[[Gamma]]";
+        assert_eq!(wikilink_targets(text), vec!["Alpha", "beta", "Gamma"]);
+        assert!(wikilink_targets("[[ ]] and [[unclosed").is_empty());
+    }
+
+    #[test]
+    fn rewriting_file_references_keeps_everything_else() {
+        let text = "![map](docs/map.png \"Map\") [notes](docs/a.md) [[Note]] ![[docs/scan.pdf|Scan]]\n`![x](docs/map.png)`\n";
+        let rewritten = rewrite_file_references(text, |target| target.strip_prefix("docs/").map(|rest| format!("files/{}", rest)));
+        assert_eq!(
+            rewritten,
+            "![map](files/map.png \"Map\") [notes](files/a.md) [[Note]] ![[files/scan.pdf|Scan]]\n`![x](docs/map.png)`\n"
+        );
+        assert_eq!(rewrite_file_references(text, |_| None), text);
+    }
+}
@@ -0,0 +1,50 @@
+//! MIME type lookup by file extension
+
+/// MIME type for a lowercase file extension, if it is a known one
+pub fn mime_from_extension(extension: &str) -> Option<&'static str> {
+    let mime = match extension {
+        "md" | "markdown" => "text/markdown",
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "tex" => "application/x-tex",
+        "bib" => "application/x-bibtex",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "py" => "text/x-python",
+        "rs" => "text/x-rust",
+        "r" => "text/x-r",
+        "ipynb" => "application/x-ipynb+json",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "parquet" => "application/vnd.apache.parquet",
+        "h5" | "hdf5" => "application/x-hdf5",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Whether a lowercase file extension usually holds plain text worth indexing
+pub fn is_text_extension(extension: &str) -> bool {
+    matches!(
+        extension,
+        "md" | "markdown" | "txt" | "log" | "csv" | "tsv" | "tex" | "bib" | "sty" | "cls"
+            | "html" | "htm" | "css" | "js" | "ts" | "json" | "xml" | "yaml" | "yml" | "toml"
+            | "ini" | "cfg" | "py" | "rs" | "r" | "rmd" | "jl" | "m" | "c" | "h" | "cpp"
+            | "hpp" | "java" | "go" | "sh" | "sql" | "ipynb" | "org" | "rst" | "svg"
+    )
+}
@@ -0,0 +1,29 @@
+pub mod archive;
+pub mod base64;
+pub mod bibtex;
+pub mod collation;
+pub mod csv;
+pub mod frontmatter;
+pub mod fuzzy;
+pub mod gitignore;
+pub mod hash;
+pub mod html;
+pub mod http;
+pub mod ical;
+pub mod ignore;
+pub mod json_patch;
+pub mod limits;
+pub mod logging;
+pub mod markdown;
+pub mod mime;
+pub mod path;
+pub mod recurrence;
+pub mod redact;
+pub mod research_json;
+pub mod sanitize;
+pub mod tag_path;
+pub mod template;
+pub mod timezone;
+pub mod text;
+pub mod validate;
+pub mod word_count;
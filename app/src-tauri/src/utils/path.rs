@@ -0,0 +1,147 @@
+//! Helpers for paths the user types in
+
+use std::path::{Component, Path, PathBuf};
+
+/// Device names Windows reserves in every directory, whatever the extension
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// The user's home directory, from HOME or, on Windows, USERPROFILE
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| if cfg!(windows) { std::env::var_os("USERPROFILE") } else { None })
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Expand a leading `~` to the home directory. `~user` forms are left alone;
+/// None when the path needs a home directory that is not known.
+pub fn expand_home(path: &str) -> Option<PathBuf> {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) => rest,
+        None => return Some(PathBuf::from(path)),
+    };
+    if rest.is_empty() {
+        return home_dir();
+    }
+    match rest.strip_prefix('/').or_else(|| if cfg!(windows) { rest.strip_prefix('\\') } else { None }) {
+        Some(rest) => home_dir().map(|home| home.join(rest)),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+/// Whether `name` is a device name Windows reserves, such as "con" or "LPT1.txt".
+/// Windows ignores trailing dots and spaces, so "NUL. " is reserved too.
+pub fn is_reserved_windows_name(name: &str) -> bool {
+    let trimmed = name.trim_end_matches(['.', ' ']);
+    let stem = trimmed.split('.').next().unwrap_or(trimmed).trim_end();
+    WINDOWS_RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Whether a directory name is one Windows cannot hold: reserved, ending in a dot
+/// or space, or containing a character it does not allow in names
+pub fn is_invalid_windows_name(name: &str) -> bool {
+    is_reserved_windows_name(name)
+        || name.ends_with(['.', ' '])
+        || name.chars().any(|c| c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*'))
+}
+
+/// Bring a canonical path to the form users see: without the `\\?\` prefix
+/// canonicalize adds on Windows and with an upper-case drive letter. Other
+/// paths are returned unchanged.
+pub fn display_form(path: PathBuf) -> PathBuf {
+    if !cfg!(windows) {
+        return path;
+    }
+    PathBuf::from(windows_display_form(&path.to_string_lossy()))
+}
+
+/// `display_form` of the text of a Windows path
+fn windows_display_form(raw: &str) -> String {
+    if let Some(share) = raw.strip_prefix(r"\\?\UNC\") {
+        return format!(r"\\{}", share);
+    }
+    let plain = raw.strip_prefix(r"\\?\").unwrap_or(raw);
+    let mut chars = plain.chars();
+    match (chars.next(), chars.next()) {
+        (Some(drive), Some(':')) if drive.is_ascii_alphabetic() => {
+            format!("{}{}", drive.to_ascii_uppercase(), &plain[1..])
+        }
+        _ => plain.to_string(),
+    }
+}
+
+/// Whether `path` is `base` or lies below it, compared component by component.
+/// On Windows the comparison ignores case, as the filesystem does.
+pub fn is_within(path: &Path, base: &Path) -> bool {
+    let mut path_components = path.components();
+    for base_component in base.components() {
+        match path_components.next() {
+            Some(component) if same_component(component, base_component) => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn same_component(a: Component, b: Component) -> bool {
+    if cfg!(windows) {
+        a.as_os_str().to_string_lossy().to_lowercase() == b.as_os_str().to_string_lossy().to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbatim_prefixes_are_dropped_and_drives_upper_cased() {
+        assert_eq!(windows_display_form(r"\\?\c:\Users\ada\Thesis"), r"C:\Users\ada\Thesis");
+        assert_eq!(windows_display_form(r"\\?\UNC\server\share\Thesis"), r"\\server\share\Thesis");
+        assert_eq!(windows_display_form(r"d:\papers"), r"D:\papers");
+        assert_eq!(windows_display_form(r"\\server\share"), r"\\server\share");
+        assert_eq!(windows_display_form(r"relative\dir"), r"relative\dir");
+        assert_eq!(windows_display_form(""), "");
+    }
+
+    #[test]
+    fn only_a_leading_tilde_is_expanded() {
+        assert_eq!(expand_home("/srv/papers"), Some(PathBuf::from("/srv/papers")));
+        assert_eq!(expand_home("papers/~draft"), Some(PathBuf::from("papers/~draft")));
+        assert_eq!(expand_home("~ada/papers"), Some(PathBuf::from("~ada/papers")));
+        if let Some(home) = home_dir() {
+            assert_eq!(expand_home("~"), Some(home.clone()));
+            assert_eq!(expand_home("~/papers"), Some(home.join("papers")));
+        }
+    }
+
+    #[test]
+    fn windows_names_are_checked_like_windows_does() {
+        for name in ["con", "NUL", "lpt1.txt", "Com9.tar.gz", "NUL. ", "aux "] {
+            assert!(is_reserved_windows_name(name), "{}", name);
+        }
+        for name in ["console", "COM10", "nullable", "my aux"] {
+            assert!(!is_reserved_windows_name(name), "{}", name);
+        }
+
+        for name in ["thesis.", "thesis ", "a:b", "what?", "x*", "tab\tname"] {
+            assert!(is_invalid_windows_name(name), "{}", name);
+        }
+        assert!(!is_invalid_windows_name("Thesis 2025 (draft)"));
+    }
+
+    #[test]
+    fn within_compares_whole_components() {
+        let base = Path::new("/srv/projects/thesis");
+        assert!(is_within(Path::new("/srv/projects/thesis"), base));
+        assert!(is_within(Path::new("/srv/projects/thesis/data"), base));
+        assert!(!is_within(Path::new("/srv/projects/thesis-old"), base));
+        assert!(!is_within(Path::new("/srv/projects"), base));
+        assert!(!is_within(Path::new("projects/thesis/data"), base));
+    }
+}
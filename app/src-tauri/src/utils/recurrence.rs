@@ -0,0 +1,351 @@
+//! Recurrence rules of repeating tasks: a subset of the iCalendar RRULE
+//! (RFC 5545) with FREQ=DAILY/WEEKLY/MONTHLY, INTERVAL, BYDAY, BYMONTHDAY,
+//! COUNT and UNTIL. Dates are computed in UTC and keep the time of day.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+/// Most days searched for the next BYDAY match before giving up
+const MAX_SEARCH_DAYS: i64 = 7 * 366;
+
+/// How often a task repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Frequency {
+    fn as_str(self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        }
+    }
+}
+
+/// A parsed recurrence rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Repeat every this many days, weeks or months
+    pub interval: u32,
+    /// Weekdays a daily or weekly rule is limited to; empty for any
+    pub by_day: Vec<Weekday>,
+    /// Day of the month of a monthly rule; months too short for it use their last day
+    pub by_month_day: Option<u32>,
+    /// Occurrences left, this one included
+    pub count: Option<u32>,
+    /// Last instant an occurrence may fall on
+    pub until: Option<i64>,
+}
+
+impl Recurrence {
+    /// Parse a rule such as "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=10".
+    /// An "RRULE:" prefix is accepted; names and values are case-insensitive.
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let rule = rule
+            .get(..6)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("RRULE:"))
+            .map_or(rule, |_| &rule[6..]);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut by_month_day = None;
+        let mut count = None;
+        let mut until = None;
+
+        for part in rule.split(';').map(str::trim).filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected NAME=value, found '{}'", part))?;
+            let value = value.trim().to_ascii_uppercase();
+            match name.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("Unsupported FREQ '{}'; use DAILY, WEEKLY or MONTHLY", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| format!("INTERVAL must be a positive number, found '{}'", value))?
+                }
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(|day| parse_weekday(day.trim()).ok_or_else(|| format!("Unsupported BYDAY value '{}'", day)))
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|day| (1..=31).contains(day))
+                            .ok_or_else(|| format!("BYMONTHDAY must be between 1 and 31, found '{}'", value))?,
+                    )
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or_else(|| format!("COUNT must be a positive number, found '{}'", value))?,
+                    )
+                }
+                "UNTIL" => until = Some(parse_until(&value).ok_or_else(|| format!("Invalid UNTIL '{}'", value))?),
+                other => return Err(format!("Unsupported rule part '{}'", other)),
+            }
+        }
+
+        let frequency = frequency.ok_or("The rule needs a FREQ")?;
+        if count.is_some() && until.is_some() {
+            return Err("COUNT and UNTIL cannot both be set".into());
+        }
+        if frequency == Frequency::Monthly && !by_day.is_empty() {
+            return Err("BYDAY is only supported with DAILY and WEEKLY".into());
+        }
+        if frequency != Frequency::Monthly && by_month_day.is_some() {
+            return Err("BYMONTHDAY is only supported with MONTHLY".into());
+        }
+        by_day.sort_by_key(|day: &Weekday| day.num_days_from_monday());
+        by_day.dedup();
+
+        Ok(Self { frequency, interval, by_day, by_month_day, count, until })
+    }
+
+    /// The rule in canonical form, parts in a fixed order
+    pub fn to_rule(&self) -> String {
+        let mut parts = vec![format!("FREQ={}", self.frequency.as_str())];
+        if self.interval != 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|day| weekday_code(*day)).collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        if let Some(day) = self.by_month_day {
+            parts.push(format!("BYMONTHDAY={}", day));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("UNTIL={}", super::ical::format_timestamp(until)));
+        }
+        parts.join(";")
+    }
+
+    /// The first date the rule yields after `from`, at the same time of day,
+    /// ignoring COUNT and UNTIL. Weekly intervals count whole weeks from the
+    /// Monday of `from`. None when no date matches or the date is out of range.
+    pub fn next_after(&self, from: i64) -> Option<i64> {
+        let start = DateTime::from_timestamp(from, 0)?.naive_utc();
+        let interval = i64::from(self.interval);
+
+        let next = match self.frequency {
+            Frequency::Daily if self.by_day.is_empty() => start.checked_add_signed(Duration::days(interval))?,
+            Frequency::Weekly if self.by_day.is_empty() => start.checked_add_signed(Duration::weeks(interval))?,
+            Frequency::Daily => (1..=MAX_SEARCH_DAYS / interval)
+                .filter_map(|step| start.checked_add_signed(Duration::days(step * interval)))
+                .find(|date| self.by_day.contains(&date.weekday()))?,
+            Frequency::Weekly => {
+                let start_week = week_index(start.date());
+                (1..=MAX_SEARCH_DAYS)
+                    .filter_map(|days| start.checked_add_signed(Duration::days(days)))
+                    .find(|date| {
+                        self.by_day.contains(&date.weekday()) && (week_index(date.date()) - start_week) % interval == 0
+                    })?
+            }
+            Frequency::Monthly => {
+                let months = i64::from(start.month0()) + interval;
+                let year = start.year().checked_add(i32::try_from(months / 12).ok()?)?;
+                let month = (months % 12) as u32 + 1;
+                let day = self.by_month_day.unwrap_or(start.day()).min(days_in_month(year, month)?);
+                NaiveDate::from_ymd_opt(year, month, day)?.and_time(start.time())
+            }
+        };
+        Some(next.and_utc().timestamp())
+    }
+
+    /// The rule of the occurrence after this one: one fewer left with COUNT.
+    /// None when this is the last occurrence.
+    pub fn advance(&self) -> Option<Self> {
+        match self.count {
+            Some(count) if count <= 1 => None,
+            Some(count) => Some(Self { count: Some(count - 1), ..self.clone() }),
+            None => Some(self.clone()),
+        }
+    }
+
+    /// Whether an occurrence at `timestamp` is still within UNTIL
+    pub fn allows(&self, timestamp: i64) -> bool {
+        self.until.is_none_or(|until| timestamp <= until)
+    }
+}
+
+/// `date` at the time of day of `timestamp`, both in UTC
+pub fn at_time_of(date: NaiveDate, timestamp: i64) -> i64 {
+    let time = DateTime::from_timestamp(timestamp, 0).map_or(NaiveTime::MIN, |dt| dt.time());
+    NaiveDateTime::new(date, time).and_utc().timestamp()
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// UNTIL as "YYYYMMDDTHHMMSSZ", or "YYYYMMDD" for the end of that day
+fn parse_until(value: &str) -> Option<i64> {
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Some(date_time.and_utc().timestamp());
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    Some(date.and_hms_opt(23, 59, 59)?.and_utc().timestamp())
+}
+
+/// Weeks since the Monday of the epoch's week, so weeks compare across years
+fn week_index(date: NaiveDate) -> i64 {
+    let monday = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+    i64::from(monday.num_days_from_ce()).div_euclid(7)
+}
+
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    let first_of_next = NaiveDate::from_ymd_opt(next_year, next_month, 1)?;
+    Some(first_of_next.pred_opt()?.day())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Timestamp of a UTC date at 09:30
+    fn at(year: i32, month: u32, day: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(9, 30, 0).unwrap().and_utc().timestamp()
+    }
+
+    fn dates(rule: &str, from: i64, n: usize) -> Vec<String> {
+        let rule = Recurrence::parse(rule).unwrap();
+        std::iter::successors(rule.next_after(from), |&previous| rule.next_after(previous))
+            .take(n)
+            .map(|timestamp| DateTime::from_timestamp(timestamp, 0).unwrap().format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn rules_parse_to_a_canonical_form() {
+        let rule = Recurrence::parse(" rrule:freq=weekly;byday=th,mo,th;interval=2;count=10 ").unwrap();
+        assert_eq!(rule.by_day, [Weekday::Mon, Weekday::Thu]);
+        assert_eq!(rule.to_rule(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=10");
+        assert_eq!(Recurrence::parse("FREQ=DAILY;UNTIL=20250131").unwrap().to_rule(), "FREQ=DAILY;UNTIL=20250131T235959Z");
+        assert_eq!(Recurrence::parse("FREQ=MONTHLY;BYMONTHDAY=31").unwrap().to_rule(), "FREQ=MONTHLY;BYMONTHDAY=31");
+
+        for invalid in [
+            "",
+            "INTERVAL=2",
+            "FREQ=YEARLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=0",
+            "FREQ=DAILY;COUNT=2;UNTIL=20250101",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=WEEKLY;BYMONTHDAY=3",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=DAILY;UNTIL=tomorrow",
+            "FREQ=DAILY;BYSETPOS=1",
+            "FREQ",
+        ] {
+            assert!(Recurrence::parse(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn daily_and_weekly_rules_keep_the_time_of_day() {
+        assert_eq!(dates("FREQ=DAILY;INTERVAL=3", at(2024, 12, 30), 2), ["2025-01-02 09:30", "2025-01-05 09:30"]);
+        // 2025-01-03 is a Friday
+        assert_eq!(
+            dates("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR", at(2025, 1, 3), 3),
+            ["2025-01-06 09:30", "2025-01-07 09:30", "2025-01-08 09:30"]
+        );
+        assert_eq!(dates("FREQ=WEEKLY", at(2025, 1, 3), 2), ["2025-01-10 09:30", "2025-01-17 09:30"]);
+        // Every other week counts from the Monday of the week started in
+        assert_eq!(
+            dates("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR", at(2025, 1, 3), 4),
+            ["2025-01-13 09:30", "2025-01-17 09:30", "2025-01-27 09:30", "2025-01-31 09:30"]
+        );
+    }
+
+    #[test]
+    fn monthly_rules_fall_back_to_the_last_day_of_short_months() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=31", at(2025, 1, 31), 4),
+            ["2025-02-28 09:30", "2025-03-31 09:30", "2025-04-30 09:30", "2025-05-31 09:30"]
+        );
+        assert_eq!(dates("FREQ=MONTHLY;BYMONTHDAY=30", at(2025, 11, 30), 3), ["2025-12-30 09:30", "2026-01-30 09:30", "2026-02-28 09:30"]);
+        assert_eq!(dates("FREQ=MONTHLY;INTERVAL=5", at(2025, 10, 15), 1), ["2026-03-15 09:30"]);
+        // Without BYMONTHDAY the day of the previous occurrence is kept, so a
+        // shortened month carries over; tasks store BYMONTHDAY to avoid that
+        assert_eq!(dates("FREQ=MONTHLY", at(2025, 1, 31), 2), ["2025-02-28 09:30", "2025-03-28 09:30"]);
+    }
+
+    #[test]
+    fn leap_years_have_a_twenty_ninth_of_february() {
+        assert_eq!(dates("FREQ=MONTHLY;BYMONTHDAY=29", at(2024, 1, 29), 2), ["2024-02-29 09:30", "2024-03-29 09:30"]);
+        assert_eq!(dates("FREQ=MONTHLY;BYMONTHDAY=29", at(2023, 1, 29), 1), ["2023-02-28 09:30"]);
+        assert_eq!(dates("FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=29", at(2024, 2, 29), 4), [
+            "2025-02-28 09:30",
+            "2026-02-28 09:30",
+            "2027-02-28 09:30",
+            "2028-02-29 09:30",
+        ]);
+        // 1900 was not a leap year, 2000 was
+        assert_eq!(days_in_month(1900, 2), Some(28));
+        assert_eq!(days_in_month(2000, 2), Some(29));
+        assert_eq!(dates("FREQ=DAILY", at(2024, 2, 28), 2), ["2024-02-29 09:30", "2024-03-01 09:30"]);
+    }
+
+    #[test]
+    fn count_and_until_end_the_series() {
+        let rule = Recurrence::parse("FREQ=DAILY;COUNT=2").unwrap();
+        let last = rule.advance().unwrap();
+        assert_eq!(last.count, Some(1));
+        assert_eq!(last.advance(), None);
+        assert_eq!(Recurrence::parse("FREQ=DAILY").unwrap().advance().unwrap().count, None);
+
+        let rule = Recurrence::parse("FREQ=DAILY;UNTIL=20250110T093000Z").unwrap();
+        assert!(rule.allows(at(2025, 1, 10)));
+        assert!(!rule.allows(at(2025, 1, 10) + 1));
+        assert_eq!(at_time_of(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(), at(2024, 2, 29)), at(2025, 3, 1));
+    }
+}
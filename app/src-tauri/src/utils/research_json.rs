@@ -0,0 +1,132 @@
+//! The research.json metadata file at the root of every project
+
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// File name of the project metadata file
+pub const FILE_NAME: &str = "research.json";
+
+/// Format version written to new files
+pub const CURRENT_VERSION: &str = "1.0.0";
+
+/// Major format version this app reads and writes
+const SUPPORTED_MAJOR_VERSION: &str = "1";
+
+/// Key of the subdirectories the project was laid out with
+pub const LAYOUT_KEY: &str = "layout";
+
+/// Contents of a new research.json, with the default settings
+pub fn new_metadata(title: &str, description: Option<&str>, created_at: &str) -> Map<String, Value> {
+    let metadata = json!({
+        "version": CURRENT_VERSION,
+        "title": title,
+        "description": description,
+        "created_at": created_at,
+        "updated_at": chrono::Utc::now().to_rfc3339(),
+        "settings": {
+            "auto_commit": true,
+            "backup_enabled": true
+        }
+    });
+    match metadata {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Read and validate the whole file. A missing file is reported as NotFound;
+/// malformed JSON, a non-object root and an unsupported version as InvalidData.
+pub fn read(project_path: &str) -> io::Result<Map<String, Value>> {
+    let raw = fs::read_to_string(Path::new(project_path).join(FILE_NAME))?;
+    let metadata = serde_json::from_str::<Value>(&raw).map_err(|e| invalid_data(format!("{}: {}", FILE_NAME, e)))?;
+    let Value::Object(metadata) = metadata else {
+        return Err(invalid_data(format!("{} is not a JSON object", FILE_NAME)));
+    };
+
+    match metadata.get("version").and_then(Value::as_str) {
+        Some(version) if version.split('.').next() == Some(SUPPORTED_MAJOR_VERSION) => Ok(metadata),
+        Some(version) => Err(invalid_data(format!("{} has unsupported version {}", FILE_NAME, version))),
+        None => Err(invalid_data(format!("{} has no version", FILE_NAME))),
+    }
+}
+
+/// Replace the file with `metadata`: written to a temporary file next to it and
+/// renamed into place, so readers never see a half-written file
+pub fn write(project_path: &str, metadata: &Map<String, Value>) -> io::Result<()> {
+    let path = Path::new(project_path).join(FILE_NAME);
+    let temp_path = Path::new(project_path).join(format!(".{}.tmp", FILE_NAME));
+
+    let pretty = serde_json::to_string_pretty(metadata).map_err(|e| invalid_data(e.to_string()))?;
+    fs::write(&temp_path, pretty)?;
+    if let Err(e) = fs::rename(&temp_path, &path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// The recorded layout of a project; None when it has none or it is not a list of strings
+pub fn layout(metadata: &Map<String, Value>) -> Option<Vec<String>> {
+    metadata
+        .get(LAYOUT_KEY)
+        .and_then(|layout| serde_json::from_value(layout.clone()).ok())
+}
+
+/// Read the `settings` object of a project; a missing or malformed file gives an empty one
+pub fn read_settings(project_path: &str) -> Map<String, Value> {
+    fs::read_to_string(Path::new(project_path).join(FILE_NAME))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .and_then(|metadata| metadata.get("settings").and_then(Value::as_object).cloned())
+        .unwrap_or_default()
+}
+
+/// Merge `changes` into the `settings` object and stamp `updated_at`, keeping the
+/// rest of the file intact. Returns the merged settings.
+pub fn update_settings(project_path: &str, changes: Map<String, Value>) -> io::Result<Map<String, Value>> {
+    let mut metadata = read(project_path)?;
+
+    let mut settings = metadata
+        .get("settings")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    settings.extend(changes);
+
+    metadata.insert("settings".to_string(), Value::Object(settings.clone()));
+    metadata.insert("updated_at".to_string(), Value::String(chrono::Utc::now().to_rfc3339()));
+    write(project_path, &metadata)?;
+    Ok(settings)
+}
+
+/// Set one key of the `settings` object, keeping the rest of the file intact.
+/// A missing file is created; a malformed one is left alone and reported as invalid data.
+pub fn write_setting(project_path: &str, key: &str, value: Value) -> io::Result<()> {
+    let mut metadata = match fs::read_to_string(Path::new(project_path).join(FILE_NAME)) {
+        Ok(raw) => match serde_json::from_str::<Value>(&raw) {
+            Ok(Value::Object(metadata)) => metadata,
+            Ok(_) => return Err(invalid_data(format!("{} is not a JSON object", FILE_NAME))),
+            Err(e) => return Err(invalid_data(format!("{}: {}", FILE_NAME, e))),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Map::new(),
+        Err(e) => return Err(e),
+    };
+
+    let settings = metadata
+        .entry("settings")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !settings.is_object() {
+        *settings = Value::Object(Map::new());
+    }
+    if let Some(settings) = settings.as_object_mut() {
+        settings.insert(key.to_string(), value);
+    }
+
+    write(project_path, &metadata)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
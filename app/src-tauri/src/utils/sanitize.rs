@@ -0,0 +1,80 @@
+//! Sanitization of error messages before they reach the webview
+
+use std::sync::OnceLock;
+
+/// Longest error message sent to the frontend
+pub const MAX_ERROR_MESSAGE_LEN: usize = 300;
+
+/// Placeholder for absolute paths removed from messages
+const PATH_PLACEHOLDER: &str = "[path]";
+
+static APP_DATA_DIR: OnceLock<String> = OnceLock::new();
+
+/// Remember the app data directory; paths inside it are kept in messages
+pub fn set_app_data_dir(path: &str) {
+    let _ = APP_DATA_DIR.set(path.to_string());
+}
+
+/// The app data directory, once it is known
+pub fn app_data_dir() -> Option<&'static str> {
+    APP_DATA_DIR.get().map(String::as_str).filter(|dir| !dir.is_empty())
+}
+
+/// Strip absolute paths outside the app data directory and cap the length
+pub fn sanitize_message(message: &str) -> String {
+    let stripped = strip_paths(message);
+
+    if stripped.chars().count() <= MAX_ERROR_MESSAGE_LEN {
+        stripped
+    } else {
+        let head: String = stripped.chars().take(MAX_ERROR_MESSAGE_LEN).collect();
+        format!("{}…", head)
+    }
+}
+
+fn strip_paths(message: &str) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    let mut out = String::with_capacity(message.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let at_boundary = i == 0 || matches!(chars[i - 1], ' ' | '\t' | '\n' | '"' | '\'' | '(' | '[' | '=' | '`');
+        if at_boundary && is_path_start(&chars[i..]) {
+            let end = chars[i..]
+                .iter()
+                .position(|c| c.is_whitespace() || matches!(c, '"' | '\'' | ')' | ']' | ',' | ';' | '`'))
+                .map_or(chars.len(), |offset| i + offset);
+            let path: String = chars[i..end].iter().collect();
+
+            if is_inside_app_data(&path) {
+                out.push_str(&path);
+            } else {
+                out.push_str(PATH_PLACEHOLDER);
+            }
+            i = end;
+            continue;
+        }
+
+        out.push(chars[i]);
+        i += 1;
+    }
+
+    out
+}
+
+/// Unix ("/home/...", "~/...") or Windows ("C:\...", "\\server\...") absolute path
+fn is_path_start(rest: &[char]) -> bool {
+    match rest {
+        ['/', next, ..] => next.is_alphanumeric() || matches!(next, '.' | '_' | '~'),
+        ['~', '/', ..] => true,
+        ['\\', '\\', ..] => true,
+        [drive, ':', sep, ..] => drive.is_ascii_alphabetic() && matches!(sep, '\\' | '/'),
+        _ => false,
+    }
+}
+
+fn is_inside_app_data(path: &str) -> bool {
+    APP_DATA_DIR
+        .get()
+        .is_some_and(|dir| !dir.is_empty() && path.starts_with(dir.as_str()))
+}
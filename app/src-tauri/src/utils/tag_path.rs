@@ -0,0 +1,116 @@
+//! Hierarchical tag names such as "method/bayesian"
+//!
+//! Tags are stored by full name only; parents and children are derived from
+//! the `/`-separated segments. A tag matches itself and every tag below it, so
+//! "method" matches "method/bayesian" but not "methodology".
+
+use super::text;
+
+/// Separator between the segments of a tag name
+pub const SEPARATOR: char = '/';
+
+/// Trim the name and each of its segments. Fails on an empty name, a leading
+/// or trailing separator, or an empty segment such as in "method//bayesian".
+pub fn normalize(name: &str) -> Result<String, &'static str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty");
+    }
+    if name.starts_with(SEPARATOR) || name.ends_with(SEPARATOR) {
+        return Err("Tag name cannot start or end with '/'");
+    }
+
+    let segments: Vec<&str> = name.split(SEPARATOR).map(str::trim).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err("Tag name cannot contain an empty level");
+    }
+    Ok(segments.join("/"))
+}
+
+/// Best-effort cleanup of a stored name that `normalize` rejects: empty
+/// segments are dropped. Empty when nothing is left.
+pub fn repair(name: &str) -> String {
+    name.split(SEPARATOR)
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Segments of a tag name, e.g. ["method", "bayesian"]
+pub fn segments(name: &str) -> impl Iterator<Item = &str> {
+    name.split(SEPARATOR)
+}
+
+/// Whether `name` is `ancestor` itself or below it, ignoring ASCII case
+pub fn is_within(name: &str, ancestor: &str) -> bool {
+    match name.get(..ancestor.len()) {
+        Some(head) if head.eq_ignore_ascii_case(ancestor) => {
+            name.len() == ancestor.len() || name[ancestor.len()..].starts_with(SEPARATOR)
+        }
+        _ => false,
+    }
+}
+
+/// SQL condition matching the tag name in `column` against one tag and its
+/// descendants; bind the two values of `sql_match_values` in order
+pub fn sql_match(column: &str) -> String {
+    format!("(lower({c}) = ? OR lower({c}) LIKE ? ESCAPE '{e}')", c = column, e = text::LIKE_ESCAPE)
+}
+
+/// SQL condition matching the tag name in `column` against a row holding the
+/// two values of `sql_match_values` in the columns `name` and `pattern`
+pub fn sql_match_row(column: &str, name: &str, pattern: &str) -> String {
+    format!(
+        "(lower({c}) = {n} OR lower({c}) LIKE {p} ESCAPE '{e}')",
+        c = column,
+        n = name,
+        p = pattern,
+        e = text::LIKE_ESCAPE
+    )
+}
+
+/// Values bound by `sql_match`: the lowercase name and a `LIKE` pattern for
+/// names below it. The pattern requires the separator after the name, so
+/// "method" does not match "methodology".
+pub fn sql_match_values(tag: &str) -> [String; 2] {
+    let tag = tag.trim().to_lowercase();
+    let descendants = text::like_prefix(&format!("{}{}", tag, SEPARATOR));
+    [tag, descendants]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_trimmed_per_level_and_need_every_level() {
+        assert_eq!(normalize(" method / bayesian "), Ok("method/bayesian".to_string()));
+        assert_eq!(normalize("methodology"), Ok("methodology".to_string()));
+        assert!(normalize("").is_err());
+        assert!(normalize("  ").is_err());
+        assert!(normalize("/method").is_err());
+        assert!(normalize("method/").is_err());
+        assert!(normalize("method//bayesian").is_err());
+        assert!(normalize("method/ /bayesian").is_err());
+
+        assert_eq!(repair("/method// bayesian /"), "method/bayesian");
+        assert_eq!(repair("//"), "");
+    }
+
+    #[test]
+    fn a_tag_contains_itself_and_its_descendants_only() {
+        assert!(is_within("method", "method"));
+        assert!(is_within("Method/Bayesian", "method"));
+        assert!(is_within("method/bayesian/mcmc", "method/bayesian"));
+        assert!(!is_within("methodology", "method"));
+        assert!(!is_within("method", "method/bayesian"));
+        assert!(!is_within("meth", "method"));
+    }
+
+    #[test]
+    fn sql_patterns_require_the_separator() {
+        assert_eq!(sql_match_values(" Method "), ["method".to_string(), "method/%".to_string()]);
+        assert_eq!(sql_match_values("100%_done")[1], format!("100{e}%{e}_done/%", e = text::LIKE_ESCAPE));
+    }
+}
@@ -0,0 +1,120 @@
+//! Variables of note templates: plain `{{name}}` values plus ones read from the
+//! project and its references when a note is created
+
+use std::collections::HashMap;
+
+use crate::models::{Project, Reference};
+use crate::utils::text;
+
+/// Namespace of the variables naming a reference by its cite key, as in `{{ref:doe2020.title}}`
+const REFERENCE_PREFIX: &str = "ref:";
+
+/// What the variables of one template resolve to
+pub struct TemplateVariables<'a> {
+    /// Plain variables by name, such as "date" or ones given by the caller;
+    /// these win over the built-in ones
+    pub values: &'a HashMap<String, String>,
+    pub project: &'a Project,
+    /// Local date, YYYY-MM-DD
+    pub today: String,
+    /// References of the project
+    pub references: &'a [Reference],
+}
+
+/// Fill the variables of `text`: `{{project.name}}`, `{{project.tags}}`,
+/// `{{today}}`, `{{ref:KEY.title}}`, `{{ref:KEY.authors}}` and the plain values.
+/// Anything else is left as written. Fails with the cite key of a reference
+/// variable that no reference of the project has.
+pub fn fill(text: &str, variables: &TemplateVariables) -> Result<String, String> {
+    text::fill_placeholders_with(text, |name| resolve(name, variables))
+}
+
+fn resolve(name: &str, variables: &TemplateVariables) -> Result<Option<String>, String> {
+    if let Some(value) = variables.values.get(name) {
+        return Ok(Some(value.clone()));
+    }
+    let value = match name {
+        "today" => Some(variables.today.clone()),
+        "project.name" => Some(variables.project.name.clone()),
+        "project.tags" => Some(variables.project.tags.as_deref().unwrap_or_default().join(", ")),
+        _ => match name.strip_prefix(REFERENCE_PREFIX).and_then(|rest| rest.rsplit_once('.')) {
+            Some((key, field)) => {
+                let reference = variables
+                    .references
+                    .iter()
+                    .find(|reference| reference.citation_key == key)
+                    .ok_or_else(|| key.to_string())?;
+                match field {
+                    "title" => Some(reference.title.clone()),
+                    "authors" => Some(reference.authors.join("; ")),
+                    _ => None,
+                }
+            }
+            None => None,
+        },
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProjectStatus;
+
+    fn reference(key: &str, title: &str, authors: &[&str]) -> Reference {
+        Reference {
+            id: key.to_string(),
+            project_id: "p".to_string(),
+            citation_key: key.to_string(),
+            entry_type: "article".to_string(),
+            title: title.to_string(),
+            authors: authors.iter().map(|author| author.to_string()).collect(),
+            year: None,
+            venue: None,
+            doi: None,
+            url: None,
+            abstract_text: None,
+            pdf_path: None,
+            created_at: 0,
+            reading_status: "unread".to_string(),
+            tags: None,
+        }
+    }
+
+    #[test]
+    fn every_kind_of_variable_resolves() {
+        let project = Project {
+            id: "p".to_string(),
+            name: "Thesis".to_string(),
+            path: "/tmp/thesis".to_string(),
+            description: None,
+            status: ProjectStatus::Active,
+            created_at: 0,
+            last_modified_at: 0,
+            tags: Some(vec!["phd".to_string(), "ml".to_string()]),
+            key_prefix: None,
+            is_favorite: false,
+            metadata: None,
+        };
+        let references = [reference("doe2020", "On fields", &["Doe, Jane", "Roe, Rick"])];
+        let values = HashMap::from([("title".to_string(), "Reading {{today}}".to_string())]);
+        let variables = TemplateVariables { values: &values, project: &project, today: "2026-10-16".to_string(), references: &references };
+
+        let cases = [
+            ("{{project.name}}", Ok("Thesis")),
+            ("{{ project.tags }}", Ok("phd, ml")),
+            ("{{today}}", Ok("2026-10-16")),
+            ("{{ref:doe2020.title}}", Ok("On fields")),
+            ("{{ref:doe2020.authors}}", Ok("Doe, Jane; Roe, Rick")),
+            // Plain values are inserted as they are, never filled again
+            ("# {{title}}", Ok("# Reading {{today}}")),
+            ("{{author.name}} {{project.owner}} {{ref:doe2020.year}} {{ref:}}", Ok("{{author.name}} {{project.owner}} {{ref:doe2020.year}} {{ref:}}")),
+            ("{{ref:nobody1999.title}}", Err("nobody1999")),
+            ("unclosed {{today", Ok("unclosed {{today")),
+        ];
+        for (template, expected) in cases {
+            let expected = expected.map(str::to_string).map_err(str::to_string);
+            assert_eq!(fill(template, &variables), expected, "{}", template);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Text helpers shared by exporters, task keys, search and templates
+
+use std::collections::HashMap;
+
+/// Marker inserted where the middle of a long text was dropped
+pub const TRUNCATION_MARKER: &str = "\n\n[… truncated …]\n\n";
+
+/// Truncate text to at most `max_chars` characters by keeping its head and tail
+/// around an omission marker. Returns the text and whether it was shortened.
+pub fn truncate_middle(text: &str, max_chars: usize) -> (String, bool) {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return (text.to_string(), false);
+    }
+
+    let marker_len = TRUNCATION_MARKER.chars().count();
+
+    if max_chars <= marker_len {
+        return (text.chars().take(max_chars).collect(), true);
+    }
+
+    // Keep two thirds at the head: openings usually carry the summary
+    let available = max_chars - marker_len;
+    let head_len = available * 2 / 3;
+    let tail_len = available - head_len;
+
+    let head: String = text.chars().take(head_len).collect();
+    let tail: String = text.chars().skip(total - tail_len).collect();
+
+    (format!("{}{}{}", head, TRUNCATION_MARKER, tail), true)
+}
+
+/// Longest slug produced by `slugify`, in characters
+pub const MAX_SLUG_LEN: usize = 80;
+
+/// Turn a title into a file-name-safe slug: lowercase letters and digits
+/// separated by single dashes, "untitled" when nothing is left
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug: String = slug.chars().take(MAX_SLUG_LEN).collect();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Longest prefix accepted for task keys
+pub const MAX_KEY_PREFIX_LEN: usize = 10;
+
+/// Derive a task key prefix from a project name: the initials of a multi-word
+/// name ("Natural Language Processing" -> "NLP") or the start of a single word.
+pub fn key_prefix_from_name(name: &str) -> String {
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let prefix: String = if words.len() > 1 {
+        words.iter().filter_map(|w| w.chars().next()).collect()
+    } else {
+        words.first().map(|w| w.chars().take(4).collect()).unwrap_or_default()
+    };
+
+    let prefix: String = prefix.to_ascii_uppercase().chars().take(MAX_KEY_PREFIX_LEN).collect();
+
+    if is_valid_key_prefix(&prefix) {
+        prefix
+    } else {
+        "TASK".to_string()
+    }
+}
+
+/// A key prefix is 1-10 uppercase ASCII letters or digits, starting with a letter
+pub fn is_valid_key_prefix(prefix: &str) -> bool {
+    prefix.len() <= MAX_KEY_PREFIX_LEN
+        && prefix.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+        && prefix.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Escape character declared by `LIKE` clauses built from `like_contains`
+pub const LIKE_ESCAPE: char = '\\';
+
+/// Build a `LIKE` pattern matching text that starts with `term`, escaped as in `like_contains`
+pub fn like_prefix(term: &str) -> String {
+    let mut pattern = like_contains(term);
+    pattern.remove(0);
+    pattern
+}
+
+/// Build a `LIKE` pattern matching `term` anywhere, with `%`, `_` and the
+/// escape character itself matched literally
+pub fn like_contains(term: &str) -> String {
+    let mut pattern = String::with_capacity(term.len() + 2);
+    pattern.push('%');
+    for c in term.chars() {
+        if matches!(c, '%' | '_') || c == LIKE_ESCAPE {
+            pattern.push(LIKE_ESCAPE);
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Build an FTS5 query requiring every whitespace-separated term as a prefix.
+/// Terms are quoted so that FTS5 operators in user input match literally.
+pub fn fts_prefix_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// 1-based number of the first line containing any of the terms (case-insensitive)
+pub fn first_matching_line(content: &str, query: &str) -> Option<usize> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+
+    content
+        .lines()
+        .position(|line| {
+            let line = line.to_lowercase();
+            terms.iter().any(|term| line.contains(term.as_str()))
+        })
+        .map(|index| index + 1)
+}
+
+/// Excerpt of at most `max_chars` characters around the first occurrence of any
+/// of the terms (case-insensitive), whitespace collapsed and cut ends marked with "…".
+/// None when no term occurs.
+pub fn snippet(content: &str, query: &str, max_chars: usize) -> Option<String> {
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let chars: Vec<char> = content.split_whitespace().collect::<Vec<_>>().join(" ").chars().collect();
+    let folded: Vec<char> = chars.iter().copied().map(fold).collect();
+
+    let start = query
+        .split_whitespace()
+        .filter_map(|term| {
+            let term: Vec<char> = term.chars().map(fold).collect();
+            folded.windows(term.len()).position(|window| window == term.as_slice())
+        })
+        .min()?;
+
+    // Start a third of the window before the match so it has some leading context
+    let from = start.saturating_sub(max_chars / 3).min(chars.len().saturating_sub(max_chars));
+    let to = (from + max_chars).min(chars.len());
+
+    let mut excerpt: String = chars[from..to].iter().collect();
+    if from > 0 {
+        excerpt.insert(0, '…');
+    }
+    if to < chars.len() {
+        excerpt.push('…');
+    }
+    Some(excerpt)
+}
+
+/// Replace `{{key}}` placeholders with their values. Whitespace inside the braces
+/// is ignored; placeholders without a value are left exactly as written.
+pub fn fill_placeholders(text: &str, values: &HashMap<String, String>) -> String {
+    let filled: Result<String, ()> = fill_placeholders_with(text, |key| Ok(values.get(key).cloned()));
+    filled.unwrap_or_default()
+}
+
+/// Replace `{{key}}` placeholders with what `resolve` returns for the trimmed
+/// key, in one pass: inserted values are never scanned for placeholders.
+/// Placeholders it returns None for are left exactly as written; the first
+/// error stops the pass.
+pub fn fill_placeholders_with<E>(text: &str, mut resolve: impl FnMut(&str) -> Result<Option<String>, E>) -> Result<String, E> {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+
+        match resolve(after_open[..end].trim())? {
+            Some(value) => filled.push_str(&value),
+            None => filled.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_middle_keeps_head_and_tail() {
+        assert_eq!(truncate_middle("short", 10), ("short".to_string(), false));
+        assert_eq!(truncate_middle("exactly ten", 11), ("exactly ten".to_string(), false));
+
+        let text: String = ('a'..='z').cycle().take(200).collect();
+        let (kept, truncated) = truncate_middle(&text, 100);
+        assert!(truncated);
+        assert_eq!(kept.chars().count(), 100);
+        let (head, tail) = kept.split_once(TRUNCATION_MARKER).unwrap();
+        // Two thirds of what is left after the marker go to the head
+        let available = 100 - TRUNCATION_MARKER.chars().count();
+        assert_eq!(head.chars().count(), available * 2 / 3);
+        assert!(text.starts_with(head) && text.ends_with(tail));
+
+        // Counted in characters, never splitting one
+        let (kept, _) = truncate_middle(&"é".repeat(300), 60);
+        assert_eq!(kept.chars().count(), 60);
+
+        // Too small for the marker: just the start
+        assert_eq!(truncate_middle("abcdefghijklmnopqrstuvwxyz", 5), ("abcde".to_string(), true));
+    }
+
+    #[test]
+    fn placeholders_are_filled_and_unknown_ones_kept() {
+        let values = HashMap::from([("title".to_string(), "Results".to_string())]);
+        assert_eq!(fill_placeholders("# {{ title }} {{date}} {{", &values), "# Results {{date}} {{");
+    }
+}
@@ -0,0 +1,196 @@
+//! Parsing of loosely formatted timezones and local date-times into UTC timestamps
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveDateTime};
+
+use crate::models::SearchPeriod;
+
+/// Common timezone abbreviations and their offsets from UTC in minutes
+const ABBREVIATIONS: &[(&str, i32)] = &[
+    ("PST", -480),
+    ("PDT", -420),
+    ("MST", -420),
+    ("MDT", -360),
+    ("CST", -360),
+    ("CDT", -300),
+    ("EST", -300),
+    ("EDT", -240),
+    ("BST", 60),
+    ("CET", 60),
+    ("CEST", 120),
+    ("EET", 120),
+    ("EEST", 180),
+    ("IST", 330),
+    ("SGT", 480),
+    ("JST", 540),
+    ("KST", 540),
+    ("AEST", 600),
+    ("AEDT", 660),
+    ("NZST", 720),
+    ("NZDT", 780),
+];
+
+/// Parse a timezone description into its offset from UTC in seconds.
+///
+/// Understands "AoE" (Anywhere on Earth, UTC-12), "UTC"/"GMT" with optional
+/// signed offsets ("UTC+8", "GMT-05:30"), POSIX-style "Etc/GMT+12" (inverted sign)
+/// and a handful of common abbreviations. Region names such as
+/// "America/New_York" are not supported and return None.
+pub fn parse_utc_offset(tz: &str) -> Option<i32> {
+    let upper = tz.trim().to_ascii_uppercase();
+
+    match upper.as_str() {
+        "AOE" | "ANYWHERE ON EARTH" => return Some(-12 * 3600),
+        "UTC" | "GMT" | "UT" | "Z" => return Some(0),
+        _ => {}
+    }
+
+    if let Some((_, minutes)) = ABBREVIATIONS.iter().find(|(abbr, _)| *abbr == upper) {
+        return Some(minutes * 60);
+    }
+
+    // Etc/GMT+12 means twelve hours *behind* UTC
+    if let Some(rest) = upper.strip_prefix("ETC/GMT") {
+        return parse_signed_offset(rest).map(|offset| -offset);
+    }
+
+    for prefix in ["UTC", "GMT"] {
+        if let Some(rest) = upper.strip_prefix(prefix) {
+            return parse_signed_offset(rest);
+        }
+    }
+
+    None
+}
+
+/// Parse a local date-time string and convert it to a UTC timestamp using the given offset.
+///
+/// RFC 3339 strings carry their own offset, which takes precedence. A bare date is
+/// interpreted as the last second of that day.
+pub fn parse_local_datetime(value: &str, offset_seconds: i32) -> Option<i64> {
+    let value = value.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.timestamp());
+    }
+
+    const FORMATS: [&str; 4] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ];
+
+    let naive = FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(23, 59, 59))
+        })?;
+
+    Some(naive.and_utc().timestamp() - i64::from(offset_seconds))
+}
+
+/// Start and end, exclusive, as UTC timestamps of a named period counted in
+/// the timezone `offset_seconds` from UTC. None for a month number out of range.
+pub fn period_bounds(period: SearchPeriod, now: i64, offset_seconds: i32) -> Option<(i64, i64)> {
+    let today = DateTime::from_timestamp(now.checked_add(i64::from(offset_seconds))?, 0)?.date_naive();
+    let (start, end) = match period {
+        SearchPeriod::Today => (today, today.succ_opt()?),
+        SearchPeriod::ThisWeek => {
+            let monday = today.checked_sub_days(Days::new(u64::from(today.weekday().num_days_from_monday())))?;
+            (monday, monday.checked_add_days(Days::new(7))?)
+        }
+        SearchPeriod::ThisMonth => month_bounds(today.year(), today.month())?,
+        SearchPeriod::Month(month) => {
+            if !(1..=12).contains(&month) {
+                return None;
+            }
+            let year = if month <= today.month() { today.year() } else { today.year() - 1 };
+            month_bounds(year, month)?
+        }
+    };
+    let utc = |date: NaiveDate| date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp() - i64::from(offset_seconds));
+    Some((utc(start)?, utc(end)?))
+}
+
+fn month_bounds(year: i32, month: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let end = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some((start, end))
+}
+
+fn parse_signed_offset(value: &str) -> Option<i32> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Some(0);
+    }
+
+    let (sign, rest) = if let Some(rest) = value.strip_prefix('+') {
+        (1, rest)
+    } else if let Some(rest) = value.strip_prefix('-').or_else(|| value.strip_prefix('\u{2212}')) {
+        (-1, rest)
+    } else {
+        return None;
+    };
+
+    let (hours, minutes): (i32, i32) = if let Some((h, m)) = rest.split_once(':') {
+        (h.parse().ok()?, m.parse().ok()?)
+    } else if rest.len() == 4 && rest.chars().all(|c| c.is_ascii_digit()) {
+        (rest[..2].parse().ok()?, rest[2..].parse().ok()?)
+    } else {
+        (rest.parse().ok()?, 0)
+    };
+
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Thursday 2025-03-13 10:00 UTC
+    const NOW: i64 = 1_741_860_000;
+
+    #[test]
+    fn periods_are_counted_in_the_given_offset() {
+        let cases = [
+            (SearchPeriod::Today, 0, Some((1_741_824_000, 1_741_910_400))),
+            // Still Wednesday the 12th at UTC-12
+            (SearchPeriod::Today, -12 * 3600, Some((1_741_780_800, 1_741_867_200))),
+            (SearchPeriod::ThisWeek, 0, Some((1_741_564_800, 1_742_169_600))),
+            (SearchPeriod::ThisMonth, 0, Some((1_740_787_200, 1_743_465_600))),
+            (SearchPeriod::ThisMonth, 3600, Some((1_740_787_200 - 3600, 1_743_465_600 - 3600))),
+            (SearchPeriod::Month(3), 0, Some((1_740_787_200, 1_743_465_600))),
+            (SearchPeriod::Month(1), 0, Some((1_735_689_600, 1_738_368_000))),
+            // December has not come yet this year, so last year's
+            (SearchPeriod::Month(12), 0, Some((1_733_011_200, 1_735_689_600))),
+            (SearchPeriod::Month(13), 0, None),
+            (SearchPeriod::Month(0), 0, None),
+        ];
+        for (period, offset, expected) in cases {
+            assert_eq!(period_bounds(period, NOW, offset), expected, "{:?} at {}", period, offset);
+        }
+    }
+
+    #[test]
+    fn offsets_and_local_times_parse() {
+        assert_eq!(parse_utc_offset("AoE"), Some(-12 * 3600));
+        assert_eq!(parse_utc_offset("utc+05:30"), Some(19_800));
+        assert_eq!(parse_utc_offset("Etc/GMT+12"), Some(-12 * 3600));
+        assert_eq!(parse_utc_offset("CEST"), Some(7200));
+        assert_eq!(parse_utc_offset("Europe/Berlin"), None);
+        assert_eq!(parse_local_datetime("2025-03-13 10:00", 0), Some(NOW));
+        assert_eq!(parse_local_datetime("2025-03-13T12:00:00+02:00", -12 * 3600), Some(NOW));
+        assert_eq!(parse_local_datetime("2025-03-13", 0), Some(1_741_910_399));
+    }
+}
@@ -0,0 +1,601 @@
+//! Checks of user input before it is stored. Text is trimmed in place, then
+//! measured against the limits in `limits`; a violation is an InvalidInput
+//! naming the field. Note content is kept as written, only its size is checked.
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateDeadlineDto, CreateNoteDto, CreateNoteTemplateDto, CreateProjectDto, CreateReferenceDto,
+    CreateResearchQuestionDto, CreateTaskDto, UpdateDeadlineDto, UpdateNoteDto, UpdateProjectDto, UpdateReferenceDto,
+    UpdateResearchQuestionDto, UpdateTaskDto, READING_STATUSES,
+};
+use crate::utils::limits::*;
+use crate::utils::path;
+
+/// Input that is checked and normalized before it is stored
+pub trait Validate {
+    /// Trim the text fields and check every field against the limits,
+    /// failing on the first one out of bounds
+    fn validate(&mut self, limits: &Limits) -> AppResult<()>;
+}
+
+/// Trim `value` and check it is not empty and at most `max` characters
+pub fn required(field: &str, value: &mut String, max: usize) -> AppResult<()> {
+    text(field, value, max)?;
+    if value.is_empty() {
+        return Err(AppError::InvalidInput(format!("{} cannot be empty", field)));
+    }
+    Ok(())
+}
+
+/// Trim `value` and check it is at most `max` characters
+pub fn text(field: &str, value: &mut String, max: usize) -> AppResult<()> {
+    let trimmed = value.trim();
+    if trimmed.len() != value.len() {
+        *value = trimmed.to_string();
+    }
+    let len = value.chars().count();
+    if len > max {
+        return Err(AppError::InvalidInput(format!(
+            "{} is too long: {} characters, at most {}",
+            field, len, max
+        )));
+    }
+    Ok(())
+}
+
+/// Check `value` is at most `max_bytes` long, without changing it
+pub fn content(field: &str, value: &str, max_bytes: usize) -> AppResult<()> {
+    if value.len() > max_bytes {
+        return Err(AppError::InvalidInput(format!(
+            "{} is too large: {} bytes, at most {}",
+            field,
+            value.len(),
+            max_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Trim each tag, drop the empty ones and check the count and length of the rest
+pub fn tags(field: &str, tags: &mut Option<Vec<String>>) -> AppResult<()> {
+    let Some(tags) = tags else {
+        return Ok(());
+    };
+    for tag in tags.iter_mut() {
+        text(field, tag, MAX_TAG_LEN)?;
+    }
+    tags.retain(|tag| !tag.is_empty());
+    if tags.len() > MAX_TAGS {
+        return Err(AppError::InvalidInput(format!(
+            "{} has too many entries: {}, at most {}",
+            field,
+            tags.len(),
+            MAX_TAGS
+        )));
+    }
+    Ok(())
+}
+
+/// Check a date is between 1900 and the end of 9999
+pub fn timestamp(field: &str, value: Option<i64>) -> AppResult<()> {
+    match value {
+        Some(value) if !(MIN_TIMESTAMP..=MAX_TIMESTAMP).contains(&value) => Err(AppError::InvalidInput(format!(
+            "{} must be between 1900-01-01 and 9999-12-31",
+            field
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Trim each subdirectory of a project layout and its trailing slashes, drop the
+/// empty and repeated ones and check the rest are relative paths that stay inside
+/// the project: no leading slash, drive or backslash, and no "." or ".." part
+pub fn layout(field: &str, dirs: &mut Vec<String>) -> AppResult<()> {
+    for dir in dirs.iter_mut() {
+        text(field, dir, MAX_PATH_LEN)?;
+        let trimmed = dir.trim_end_matches('/');
+        if trimmed.len() != dir.len() && !trimmed.is_empty() {
+            *dir = trimmed.to_string();
+        }
+        if dir.is_empty() {
+            continue;
+        }
+
+        let unsafe_part = dir.starts_with('/')
+            || dir.contains(['\\', ':'])
+            || dir.chars().any(char::is_control)
+            || dir.split('/').any(|part| part.is_empty() || part == "." || part == "..")
+            || dir.split('/').next() == Some(".git")
+            || (cfg!(windows) && dir.split('/').any(path::is_invalid_windows_name));
+        if unsafe_part {
+            return Err(AppError::InvalidInput(format!(
+                "{} entry '{}' must be a folder path inside the project",
+                field, dir
+            )));
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    dirs.retain(|dir| !dir.is_empty() && seen.insert(dir.clone()));
+    if dirs.len() > MAX_LAYOUT_DIRS {
+        return Err(AppError::InvalidInput(format!(
+            "{} has too many entries: {}, at most {}",
+            field,
+            dirs.len(),
+            MAX_LAYOUT_DIRS
+        )));
+    }
+    Ok(())
+}
+
+fn optional_required(field: &str, value: &mut Option<String>, max: usize) -> AppResult<()> {
+    value.as_mut().map_or(Ok(()), |value| required(field, value, max))
+}
+
+fn optional_text(field: &str, value: &mut Option<String>, max: usize) -> AppResult<()> {
+    value.as_mut().map_or(Ok(()), |value| text(field, value, max))
+}
+
+fn remind_before(value: Option<i64>) -> AppResult<()> {
+    match value {
+        Some(minutes) if !(0..=MAX_REMIND_BEFORE_MINUTES).contains(&minutes) => Err(AppError::InvalidInput(format!(
+            "remind_before must be between 0 and {} minutes",
+            MAX_REMIND_BEFORE_MINUTES
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn year(value: Option<i64>) -> AppResult<()> {
+    match value {
+        Some(year) if !(0..=9999).contains(&year) => {
+            Err(AppError::InvalidInput("year must be between 0 and 9999".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Trim a reading status, lowercased, and check it is one of `READING_STATUSES`
+fn reading_status(value: &mut Option<String>) -> AppResult<()> {
+    let Some(status) = value else {
+        return Ok(());
+    };
+    *status = status.trim().to_lowercase();
+    if !READING_STATUSES.contains(&status.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "reading_status must be one of: {}",
+            READING_STATUSES.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn authors(authors: &mut Vec<String>) -> AppResult<()> {
+    for author in authors.iter_mut() {
+        text("authors", author, MAX_NAME_LEN)?;
+    }
+    authors.retain(|author| !author.is_empty());
+    if authors.len() > MAX_AUTHORS {
+        return Err(AppError::InvalidInput(format!(
+            "authors has too many entries: {}, at most {}",
+            authors.len(),
+            MAX_AUTHORS
+        )));
+    }
+    Ok(())
+}
+
+impl Validate for CreateProjectDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        required("name", &mut self.name, MAX_NAME_LEN)?;
+        required("path", &mut self.path, MAX_PATH_LEN)?;
+        optional_text("description", &mut self.description, MAX_DESCRIPTION_LEN)?;
+        if let Some(dirs) = &mut self.layout {
+            layout("layout", dirs)?;
+        }
+        optional_text("template_id", &mut self.template_id, MAX_FIELD_LEN)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for UpdateProjectDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        optional_required("name", &mut self.name, MAX_NAME_LEN)?;
+        optional_text("description", &mut self.description, MAX_DESCRIPTION_LEN)?;
+        optional_text("key_prefix", &mut self.key_prefix, MAX_FIELD_LEN)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for CreateTaskDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        required("title", &mut self.title, MAX_TITLE_LEN)?;
+        optional_text("description", &mut self.description, MAX_DESCRIPTION_LEN)?;
+        timestamp("due_date", self.due_date)?;
+        timestamp("remind_at", self.remind_at.filter(|&at| at != 0))?;
+        remind_before(self.remind_before)?;
+        optional_text("recurrence", &mut self.recurrence, MAX_FIELD_LEN)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for UpdateTaskDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        optional_required("title", &mut self.title, MAX_TITLE_LEN)?;
+        optional_text("description", &mut self.description, MAX_DESCRIPTION_LEN)?;
+        timestamp("due_date", self.due_date)?;
+        timestamp("remind_at", self.remind_at.filter(|&at| at != 0))?;
+        remind_before(self.remind_before)?;
+        optional_text("recurrence", &mut self.recurrence, MAX_FIELD_LEN)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for CreateNoteDto {
+    fn validate(&mut self, limits: &Limits) -> AppResult<()> {
+        required("title", &mut self.title, MAX_TITLE_LEN)?;
+        content("content", &self.content, limits.note_max_bytes)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for UpdateNoteDto {
+    fn validate(&mut self, limits: &Limits) -> AppResult<()> {
+        optional_required("title", &mut self.title, MAX_TITLE_LEN)?;
+        if let Some(body) = &self.content {
+            content("content", body, limits.note_max_bytes)?;
+        }
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for CreateNoteTemplateDto {
+    fn validate(&mut self, limits: &Limits) -> AppResult<()> {
+        required("name", &mut self.name, MAX_NAME_LEN)?;
+        required("title_pattern", &mut self.title_pattern, MAX_TITLE_LEN)?;
+        content("content", &self.content, limits.note_max_bytes)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for CreateDeadlineDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        required("name", &mut self.name, MAX_NAME_LEN)?;
+        timestamp("date", Some(self.date))?;
+        optional_text("url", &mut self.url, MAX_FIELD_LEN)?;
+        optional_text("notes", &mut self.notes, MAX_DESCRIPTION_LEN)
+    }
+}
+
+impl Validate for UpdateDeadlineDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        optional_required("name", &mut self.name, MAX_NAME_LEN)?;
+        timestamp("date", self.date)?;
+        optional_text("url", &mut self.url, MAX_FIELD_LEN)?;
+        optional_text("notes", &mut self.notes, MAX_DESCRIPTION_LEN)
+    }
+}
+
+impl Validate for CreateReferenceDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        optional_text("citation_key", &mut self.citation_key, MAX_FIELD_LEN)?;
+        optional_text("entry_type", &mut self.entry_type, MAX_FIELD_LEN)?;
+        required("title", &mut self.title, MAX_TITLE_LEN)?;
+        authors(&mut self.authors)?;
+        year(self.year)?;
+        optional_text("venue", &mut self.venue, MAX_TITLE_LEN)?;
+        optional_text("doi", &mut self.doi, MAX_FIELD_LEN)?;
+        optional_text("url", &mut self.url, MAX_FIELD_LEN)?;
+        optional_text("abstract", &mut self.abstract_text, MAX_DESCRIPTION_LEN)?;
+        optional_text("pdf_path", &mut self.pdf_path, MAX_PATH_LEN)?;
+        reading_status(&mut self.reading_status)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for UpdateReferenceDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        optional_text("citation_key", &mut self.citation_key, MAX_FIELD_LEN)?;
+        optional_text("entry_type", &mut self.entry_type, MAX_FIELD_LEN)?;
+        optional_required("title", &mut self.title, MAX_TITLE_LEN)?;
+        if let Some(list) = &mut self.authors {
+            authors(list)?;
+        }
+        year(self.year)?;
+        optional_text("venue", &mut self.venue, MAX_TITLE_LEN)?;
+        optional_text("doi", &mut self.doi, MAX_FIELD_LEN)?;
+        optional_text("url", &mut self.url, MAX_FIELD_LEN)?;
+        optional_text("abstract", &mut self.abstract_text, MAX_DESCRIPTION_LEN)?;
+        optional_text("pdf_path", &mut self.pdf_path, MAX_PATH_LEN)?;
+        reading_status(&mut self.reading_status)?;
+        tags("tags", &mut self.tags)
+    }
+}
+
+impl Validate for CreateResearchQuestionDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        required("question", &mut self.question, MAX_QUESTION_LEN)
+    }
+}
+
+impl Validate for UpdateResearchQuestionDto {
+    fn validate(&mut self, _limits: &Limits) -> AppResult<()> {
+        optional_required("question", &mut self.question, MAX_QUESTION_LEN)?;
+        optional_text("answer_summary", &mut self.answer_summary, MAX_DESCRIPTION_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    const LIMITS: Limits = Limits { note_max_bytes: 1_000 };
+
+    /// Deserialize `input` as the DTO named `dto`, validate it and return it
+    /// serialized again, or the message of the validation error
+    fn check(dto: &str, input: Value) -> Result<Value, String> {
+        fn run<T: Validate + serde::de::DeserializeOwned + serde::Serialize>(input: Value) -> Result<Value, String> {
+            let mut dto: T = serde_json::from_value(input).expect("test input matches the DTO");
+            match dto.validate(&LIMITS) {
+                Ok(()) => Ok(serde_json::to_value(dto).unwrap()),
+                Err(AppError::InvalidInput(message)) => Err(message),
+                Err(other) => panic!("expected InvalidInput, got {:?}", other),
+            }
+        }
+
+        match dto {
+            "CreateProject" => run::<CreateProjectDto>(input),
+            "UpdateProject" => run::<UpdateProjectDto>(input),
+            "CreateTask" => run::<CreateTaskDto>(input),
+            "UpdateTask" => run::<UpdateTaskDto>(input),
+            "CreateNote" => run::<CreateNoteDto>(input),
+            "UpdateNote" => run::<UpdateNoteDto>(input),
+            "CreateNoteTemplate" => run::<CreateNoteTemplateDto>(input),
+            "CreateDeadline" => run::<CreateDeadlineDto>(input),
+            "UpdateDeadline" => run::<UpdateDeadlineDto>(input),
+            "CreateReference" => run::<CreateReferenceDto>(input),
+            "UpdateReference" => run::<UpdateReferenceDto>(input),
+            "CreateResearchQuestion" => run::<CreateResearchQuestionDto>(input),
+            "UpdateResearchQuestion" => run::<UpdateResearchQuestionDto>(input),
+            other => panic!("unknown DTO {}", other),
+        }
+    }
+
+    /// Copy of `base` with `field` set to `value`
+    fn with(base: &Value, field: &str, value: Value) -> Value {
+        let mut input = base.clone();
+        input[field] = value;
+        input
+    }
+
+    /// A valid input of every DTO, each with the text field that must not be empty
+    fn valid_inputs() -> Vec<(&'static str, Value, &'static str)> {
+        vec![
+            ("CreateProject", json!({ "name": "Thesis", "path": "/home/me/thesis" }), "name"),
+            ("UpdateProject", json!({ "name": "Thesis" }), "name"),
+            ("CreateTask", json!({ "project_id": "p", "title": "Read" }), "title"),
+            ("UpdateTask", json!({ "title": "Read" }), "title"),
+            ("CreateNote", json!({ "project_id": "p", "title": "Idea", "content": "" }), "title"),
+            ("UpdateNote", json!({ "title": "Idea" }), "title"),
+            ("CreateNoteTemplate", json!({ "name": "Daily", "title_pattern": "{date}", "content": "" }), "name"),
+            ("CreateDeadline", json!({ "name": "Submission", "date": 1_900_000_000 }), "name"),
+            ("UpdateDeadline", json!({ "name": "Submission" }), "name"),
+            ("CreateReference", json!({ "project_id": "p", "title": "A paper" }), "title"),
+            ("UpdateReference", json!({ "title": "A paper" }), "title"),
+            ("CreateResearchQuestion", json!({ "project_id": "p", "question": "Why?" }), "question"),
+            ("UpdateResearchQuestion", json!({ "question": "Why?" }), "question"),
+        ]
+    }
+
+    #[test]
+    fn every_dto_accepts_valid_input() {
+        for (dto, input, _) in valid_inputs() {
+            assert!(check(dto, input).is_ok(), "{} rejected valid input", dto);
+        }
+    }
+
+    #[test]
+    fn every_dto_trims_and_requires_its_main_field() {
+        for (dto, input, field) in valid_inputs() {
+            let trimmed = check(dto, with(&input, field, json!("  spaced  "))).unwrap();
+            assert_eq!(trimmed[field], "spaced", "{} did not trim {}", dto, field);
+
+            let message = check(dto, with(&input, field, json!(" \t\n "))).unwrap_err();
+            assert_eq!(message, format!("{} cannot be empty", field), "{}", dto);
+        }
+    }
+
+    #[test]
+    fn every_dto_rejects_an_over_long_main_field() {
+        for (dto, input, field) in valid_inputs() {
+            let max = match field {
+                "name" => MAX_NAME_LEN,
+                "question" => MAX_QUESTION_LEN,
+                _ => MAX_TITLE_LEN,
+            };
+            // Characters are counted, not bytes, and surrounding spaces are trimmed first
+            let at_limit = format!(" {} ", "é".repeat(max));
+            assert!(check(dto, with(&input, field, json!(at_limit))).is_ok(), "{}", dto);
+
+            let message = check(dto, with(&input, field, json!("x".repeat(max + 1)))).unwrap_err();
+            assert_eq!(
+                message,
+                format!("{} is too long: {} characters, at most {}", field, max + 1, max),
+                "{}",
+                dto
+            );
+        }
+    }
+
+    #[test]
+    fn optional_text_fields_are_trimmed_and_capped() {
+        let cases = [
+            ("CreateProject", json!({ "name": "n", "path": "/p" }), "description", MAX_DESCRIPTION_LEN),
+            ("UpdateProject", json!({}), "key_prefix", MAX_FIELD_LEN),
+            ("CreateTask", json!({ "project_id": "p", "title": "t" }), "description", MAX_DESCRIPTION_LEN),
+            ("UpdateTask", json!({}), "recurrence", MAX_FIELD_LEN),
+            ("CreateDeadline", json!({ "name": "n", "date": 0 }), "url", MAX_FIELD_LEN),
+            ("UpdateDeadline", json!({}), "notes", MAX_DESCRIPTION_LEN),
+            ("CreateReference", json!({ "project_id": "p", "title": "t" }), "venue", MAX_TITLE_LEN),
+            ("UpdateReference", json!({}), "abstract", MAX_DESCRIPTION_LEN),
+            ("UpdateResearchQuestion", json!({}), "answer_summary", MAX_DESCRIPTION_LEN),
+        ];
+        for (dto, input, field, max) in cases {
+            let trimmed = check(dto, with(&input, field, json!("  kept  "))).unwrap();
+            assert_eq!(trimmed[field], "kept", "{}.{}", dto, field);
+
+            // Optional text may be blank once trimmed
+            assert!(check(dto, with(&input, field, json!("   "))).is_ok(), "{}.{}", dto, field);
+
+            let message = check(dto, with(&input, field, json!("x".repeat(max + 1)))).unwrap_err();
+            assert!(message.starts_with(&format!("{} is too long", field)), "{}: {}", dto, message);
+        }
+    }
+
+    #[test]
+    fn tags_are_trimmed_emptied_and_counted() {
+        let with_tags: Vec<(&str, Value)> = valid_inputs()
+            .into_iter()
+            .filter(|(dto, _, _)| !dto.contains("Deadline") && !dto.contains("ResearchQuestion"))
+            .map(|(dto, input, _)| (dto, input))
+            .collect();
+        assert_eq!(with_tags.len(), 9);
+
+        for (dto, input) in with_tags {
+            let cleaned = check(dto, with(&input, "tags", json!([" ml ", "", "  ", "nlp"]))).unwrap();
+            assert_eq!(cleaned["tags"], json!(["ml", "nlp"]), "{}", dto);
+
+            let many: Vec<String> = (0..=MAX_TAGS).map(|i| format!("tag{}", i)).collect();
+            let message = check(dto, with(&input, "tags", json!(many))).unwrap_err();
+            assert_eq!(message, format!("tags has too many entries: {}, at most {}", MAX_TAGS + 1, MAX_TAGS));
+
+            // Blank tags do not count towards the limit
+            let mut padded: Vec<String> = (0..MAX_TAGS).map(|i| format!("tag{}", i)).collect();
+            padded.push(" ".into());
+            assert!(check(dto, with(&input, "tags", json!(padded))).is_ok(), "{}", dto);
+
+            let message = check(dto, with(&input, "tags", json!(["x".repeat(MAX_TAG_LEN + 1)]))).unwrap_err();
+            assert!(message.starts_with("tags is too long"), "{}: {}", dto, message);
+        }
+    }
+
+    #[test]
+    fn content_is_kept_as_written_and_capped_in_bytes() {
+        let cases = [
+            ("CreateNote", json!({ "project_id": "p", "title": "t", "content": "" })),
+            ("UpdateNote", json!({})),
+            ("CreateNoteTemplate", json!({ "name": "n", "title_pattern": "t", "content": "" })),
+        ];
+        for (dto, input) in cases {
+            let body = format!("  {}\n", "a".repeat(LIMITS.note_max_bytes - 3));
+            let kept = check(dto, with(&input, "content", json!(body))).unwrap();
+            assert_eq!(kept["content"], json!(body), "{}", dto);
+
+            // 501 two-byte characters are 1002 bytes
+            let message = check(dto, with(&input, "content", json!("é".repeat(501)))).unwrap_err();
+            assert_eq!(message, "content is too large: 1002 bytes, at most 1000", "{}", dto);
+        }
+    }
+
+    #[test]
+    fn dates_must_fall_between_1900_and_9999() {
+        let cases = [
+            ("CreateTask", json!({ "project_id": "p", "title": "t" }), "due_date"),
+            ("UpdateTask", json!({}), "due_date"),
+            ("CreateTask", json!({ "project_id": "p", "title": "t" }), "remind_at"),
+            ("UpdateTask", json!({}), "remind_at"),
+            ("CreateDeadline", json!({ "name": "n", "date": 0 }), "date"),
+            ("UpdateDeadline", json!({}), "date"),
+        ];
+        for (dto, input, field) in cases {
+            for ok in [MIN_TIMESTAMP, 0, MAX_TIMESTAMP] {
+                assert!(check(dto, with(&input, field, json!(ok))).is_ok(), "{}.{} = {}", dto, field, ok);
+            }
+            for bad in [MIN_TIMESTAMP - 1, MAX_TIMESTAMP + 1, i64::MAX] {
+                let message = check(dto, with(&input, field, json!(bad))).unwrap_err();
+                assert_eq!(message, format!("{} must be between 1900-01-01 and 9999-12-31", field));
+            }
+        }
+
+        // A remind_at of 0 clears the reminder
+        assert!(check("UpdateTask", json!({ "remind_at": 0 })).is_ok());
+    }
+
+    #[test]
+    fn remind_before_is_at_most_a_year() {
+        for dto in ["CreateTask", "UpdateTask"] {
+            let input = json!({ "project_id": "p", "title": "t" });
+            assert!(check(dto, with(&input, "remind_before", json!(MAX_REMIND_BEFORE_MINUTES))).is_ok());
+            for bad in [-1, MAX_REMIND_BEFORE_MINUTES + 1] {
+                let message = check(dto, with(&input, "remind_before", json!(bad))).unwrap_err();
+                assert!(message.starts_with("remind_before must be between 0 and"), "{}", message);
+            }
+        }
+    }
+
+    #[test]
+    fn project_paths_and_layouts_are_checked() {
+        let input = json!({ "name": "n", "path": "/p" });
+        let message = check("CreateProject", with(&input, "path", json!("  "))).unwrap_err();
+        assert_eq!(message, "path cannot be empty");
+
+        let cleaned = check(
+            "CreateProject",
+            with(&input, "layout", json!([" data/ ", "notes//", "", "data", "src/lib"])),
+        )
+        .unwrap();
+        assert_eq!(cleaned["layout"], json!(["data", "notes", "src/lib"]));
+
+        for bad in ["/etc", "../up", "a/../b", "a//b", "./here", ".git/hooks", "c:stuff", "a\\b", "tab\there"] {
+            let message = check("CreateProject", with(&input, "layout", json!([bad]))).unwrap_err();
+            assert_eq!(message, format!("layout entry '{}' must be a folder path inside the project", bad));
+        }
+
+        let many: Vec<String> = (0..=MAX_LAYOUT_DIRS).map(|i| format!("dir{}", i)).collect();
+        let message = check("CreateProject", with(&input, "layout", json!(many))).unwrap_err();
+        assert!(message.starts_with("layout has too many entries"), "{}", message);
+    }
+
+    #[test]
+    fn references_check_authors_year_and_reading_status() {
+        for (dto, input) in [
+            ("CreateReference", json!({ "project_id": "p", "title": "t" })),
+            ("UpdateReference", json!({})),
+        ] {
+            let cleaned = check(dto, with(&input, "authors", json!([" Ada ", " ", "Alan"]))).unwrap();
+            assert_eq!(cleaned["authors"], json!(["Ada", "Alan"]), "{}", dto);
+
+            let many: Vec<String> = (0..=MAX_AUTHORS).map(|i| format!("Author {}", i)).collect();
+            let message = check(dto, with(&input, "authors", json!(many))).unwrap_err();
+            assert!(message.starts_with("authors has too many entries"), "{}", message);
+
+            for ok in [0, 2024, 9999] {
+                assert!(check(dto, with(&input, "year", json!(ok))).is_ok(), "{} year {}", dto, ok);
+            }
+            for bad in [-1, 10_000] {
+                let message = check(dto, with(&input, "year", json!(bad))).unwrap_err();
+                assert_eq!(message, "year must be between 0 and 9999");
+            }
+
+            let status = check(dto, with(&input, "reading_status", json!(" Reading "))).unwrap();
+            assert_eq!(status["reading_status"], "reading", "{}", dto);
+            let message = check(dto, with(&input, "reading_status", json!("skimmed"))).unwrap_err();
+            assert_eq!(message, "reading_status must be one of: unread, reading, read");
+        }
+    }
+
+    #[test]
+    fn update_dtos_accept_no_changes() {
+        for dto in [
+            "UpdateProject",
+            "UpdateTask",
+            "UpdateNote",
+            "UpdateDeadline",
+            "UpdateReference",
+            "UpdateResearchQuestion",
+        ] {
+            assert!(check(dto, json!({})).is_ok(), "{}", dto);
+        }
+    }
+}
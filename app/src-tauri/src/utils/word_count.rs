@@ -0,0 +1,193 @@
+//! Word, character and heading counts of Markdown notes
+//!
+//! Chinese, Japanese and Korean text is counted by characters, since it does not
+//! separate words with spaces. Code blocks, inline code and URLs count as words
+//! but are left out of the reading-time estimate.
+
+/// Reading speed for space-separated languages
+const WORDS_PER_MINUTE: f64 = 230.0;
+
+/// Reading speed for CJK text
+const CJK_CHARS_PER_MINUTE: f64 = 500.0;
+
+/// Counts of one text
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextCounts {
+    /// Words, with every CJK character counted as one
+    pub words: i64,
+    /// Characters other than whitespace
+    pub characters: i64,
+    /// ATX headings ("# Title") outside code blocks
+    pub headings: i64,
+    /// Estimated minutes to read the prose, rounded up; 0 for an empty text
+    pub reading_minutes: i64,
+}
+
+/// Word count of a text, as stored with every note
+pub fn word_count(text: &str) -> i64 {
+    count(text).words
+}
+
+/// Count the words, characters and headings of a Markdown text and estimate its reading time
+pub fn count(text: &str) -> TextCounts {
+    let mut counts = TextCounts::default();
+    // Words and CJK characters a reader reads, outside code and URLs
+    let mut prose_words = 0;
+    let mut prose_cjk = 0;
+    let mut in_code_block = false;
+
+    for line in text.lines() {
+        counts.characters += line.chars().filter(|c| !c.is_whitespace()).count() as i64;
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            let (words, cjk) = count_words(line);
+            counts.words += words + cjk;
+            continue;
+        }
+        if is_heading(trimmed) {
+            counts.headings += 1;
+        }
+
+        let mut in_code_span = false;
+        for token in strip_link_targets(line).split_whitespace() {
+            // Inline code is rarely split by spaces, so a token toggles on its backticks
+            let ticks = token.matches('`').count();
+            let is_code = in_code_span || ticks > 0;
+            if ticks % 2 == 1 {
+                in_code_span = !in_code_span;
+            }
+
+            let (words, cjk) = count_words(token);
+            counts.words += words + cjk;
+            if !is_code && !is_url(token) {
+                prose_words += words;
+                prose_cjk += cjk;
+            }
+        }
+    }
+
+    let minutes = prose_words as f64 / WORDS_PER_MINUTE + prose_cjk as f64 / CJK_CHARS_PER_MINUTE;
+    counts.reading_minutes = minutes.ceil() as i64;
+    counts
+}
+
+/// Words and CJK characters in a piece of text. A run of letters or digits
+/// between CJK characters or spaces is one word; "don't" and "x-ray" are one too.
+fn count_words(text: &str) -> (i64, i64) {
+    let mut words = 0;
+    let mut cjk = 0;
+    for token in text.split_whitespace() {
+        let mut in_word = false;
+        for c in token.chars() {
+            if is_cjk(c) {
+                cjk += 1;
+                in_word = false;
+            } else if c.is_alphanumeric() {
+                if !in_word {
+                    words += 1;
+                    in_word = true;
+                }
+            } else if !matches!(c, '\'' | '’' | '-') {
+                in_word = false;
+            }
+        }
+    }
+    (words, cjk)
+}
+
+/// Drop the destinations of Markdown links and images, `[text](url)` becoming
+/// `[text]`, so they count neither as words nor as reading
+fn strip_link_targets(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("](") {
+        let Some(end) = rest[start + 2..].find(')') else {
+            break;
+        };
+        result.push_str(&rest[..=start]);
+        rest = &rest[start + 2 + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn is_heading(line: &str) -> bool {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&level) && line[level..].chars().next().is_none_or(char::is_whitespace)
+}
+
+fn is_url(token: &str) -> bool {
+    let token = token.trim_start_matches(['<', '(']);
+    token.contains("://") || token.starts_with("www.") || token.starts_with("mailto:")
+}
+
+/// Han ideographs, kana and Hangul syllables
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF       // Hiragana and Katakana
+            | 0x3400..=0x4DBF // CJK Extension A
+            | 0x4E00..=0x9FFF // CJK Unified Ideographs
+            | 0xAC00..=0xD7AF // Hangul syllables
+            | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+            | 0x20000..=0x2FA1F
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cjk_text_counts_every_character_as_a_word() {
+        assert_eq!(word_count("我们研究语言模型"), 8);
+        assert_eq!(word_count("ひらがなカタカナ"), 8);
+        assert_eq!(word_count("한국어 문장"), 5);
+        assert_eq!(word_count("BERT 模型 works"), 4);
+        assert_eq!(word_count("第3章"), 3);
+
+        let counts = count(&"模型".repeat(500));
+        assert_eq!((counts.words, counts.characters, counts.reading_minutes), (1000, 1000, 2));
+    }
+
+    #[test]
+    fn words_are_runs_of_letters_and_digits() {
+        assert_eq!(word_count("don't x-ray it’s 42"), 4);
+        assert_eq!(word_count("one,two;three -- four"), 4);
+        assert_eq!(count(""), TextCounts::default());
+    }
+
+    #[test]
+    fn markdown_code_urls_and_headings() {
+        let text = "# Notes\n\nPlain words here.\n\n```\nlet x = 1;\n```\n\n\
+                    See [docs](https://example.com/docs) or https://example.org now.\n#hashtag\n";
+        let counts = count(text);
+        assert_eq!(counts.words, 15);
+        assert_eq!(counts.characters, 102);
+        assert_eq!(counts.headings, 1, "#hashtag is not a heading");
+        assert_eq!(counts.reading_minutes, 1);
+
+        // Headings inside code blocks are code
+        assert_eq!(count("~~~\n# not a heading\n~~~\n###### Six\n####### Seven").headings, 1);
+    }
+
+    #[test]
+    fn code_and_urls_count_as_words_but_not_as_reading() {
+        let prose = "word ".repeat(460);
+        assert_eq!(count(&prose).reading_minutes, 2);
+
+        let with_code = format!("{}\n```\n{}\n```\nUse `cargo build --release` daily", "word ".repeat(230), "code ".repeat(1000));
+        let counts = count(&with_code);
+        assert_eq!(counts.words, 230 + 1000 + 5);
+        // 232 words of prose, just over one minute
+        assert_eq!(counts.reading_minutes, 2);
+
+        let links = format!("{} https://example.org/{}", "word ".repeat(230), "a/".repeat(500));
+        assert_eq!(count(&links).reading_minutes, 1);
+    }
+}
@@ -0,0 +1,516 @@
+//! Every registered command invoked through the real invoke handler on the mock
+//! runtime, the way the frontend calls it: camelCase arguments as JSON, success
+//! values as JSON and failures as `{ code, message, details? }`.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+
+use research_management_lib::{register_commands, state::AppState, COMMAND_NAMES};
+use serde_json::{json, Value};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{App, Listener, WebviewWindow, WebviewWindowBuilder};
+
+struct Harness {
+    app: App<MockRuntime>,
+    webview: WebviewWindow<MockRuntime>,
+    dir: PathBuf,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("research-vault-commands-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp directory");
+
+        let state = AppState::new();
+        state.init_db(&dir.join("research.db").to_string_lossy()).expect("open database");
+
+        let app = register_commands(mock_builder())
+            .manage(state)
+            .build(mock_context(noop_assets()))
+            .expect("build app");
+        let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .expect("build webview");
+
+        Harness { app, webview, dir }
+    }
+
+    /// A path under the harness directory, as the string a command receives
+    fn path(&self, relative: &str) -> String {
+        self.dir.join(relative).to_string_lossy().into_owned()
+    }
+
+    fn invoke(&self, cmd: &str, args: Value) -> Result<Value, Value> {
+        let request = InvokeRequest {
+            cmd: cmd.to_string(),
+            callback: CallbackFn(0),
+            error: CallbackFn(1),
+            url: "http://tauri.localhost".parse().unwrap(),
+            body: InvokeBody::Json(args),
+            headers: Default::default(),
+            invoke_key: INVOKE_KEY.to_string(),
+        };
+        get_ipc_response(&self.webview, request).map(|body| body.deserialize::<Value>().expect("JSON response"))
+    }
+
+    /// Invoke a command that must succeed
+    fn ok(&self, cmd: &str, args: Value) -> Value {
+        self.invoke(cmd, args).unwrap_or_else(|e| panic!("{} failed: {}", cmd, e))
+    }
+}
+
+/// IDs of the entities the table refers to
+struct Fixture {
+    project: String,
+    task: String,
+    note: String,
+    other_note: String,
+    reference: String,
+    question: String,
+    deadline: String,
+    template: String,
+}
+
+fn id_of(value: &Value) -> String {
+    value["id"].as_str().expect("string id").to_string()
+}
+
+fn fixture(h: &Harness) -> Fixture {
+    // Side effects stay inside the harness directory
+    h.ok("set_setting", json!({ "key": "backup_dir", "value": h.path("backups") }));
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    h.ok("set_setting", json!({ "key": "automation_port", "value": port }));
+
+    let project = h.ok("create_project", json!({ "data": { "name": "Thesis", "path": h.path("thesis") } }));
+    assert_eq!(project["name"], "Thesis");
+    assert!(Path::new(project["path"].as_str().unwrap()).is_dir());
+    let project = id_of(&project);
+
+    let task = h.ok(
+        "create_task",
+        json!({ "data": { "project_id": project, "title": "Write chapter 1", "tags": ["writing"], "due_date": 1_700_000_000 } }),
+    );
+    assert_eq!(task["status"], "todo");
+    assert_eq!(task["project_id"], project.as_str());
+
+    let note = h.ok(
+        "create_note",
+        json!({ "data": { "project_id": project, "title": "Outline", "content": "See [[Sources]]", "tags": ["writing"] } }),
+    );
+    let other_note = h.ok("create_note", json!({ "data": { "project_id": project, "title": "Sources", "content": "Papers" } }));
+    let reference = h.ok(
+        "create_reference",
+        json!({ "data": { "project_id": project, "title": "Attention Is All You Need", "authors": ["Vaswani"], "year": 2017 } }),
+    );
+    let question = h.ok("create_research_question", json!({ "data": { "project_id": project, "question": "Does it scale?" } }));
+    let deadline = h.ok("create_deadline", json!({ "data": { "project_id": project, "name": "Submission", "date": 4_102_444_800i64 } }));
+    let template = h.ok(
+        "create_note_template",
+        json!({ "data": { "name": "Meeting", "title_pattern": "Meeting {{date}}", "content": "# {{title}}" } }),
+    );
+
+    std::fs::write(h.dir.join("figure.txt"), "figure").unwrap();
+    std::fs::write(h.dir.join("tasks.csv"), "title,status\nImported,todo\n").unwrap();
+    std::fs::write(h.dir.join("refs.bib"), "@article{doe2020, title={A Study}, author={Doe, Jane}, year={2020}}").unwrap();
+    std::fs::create_dir_all(h.dir.join("markdown")).unwrap();
+    std::fs::write(h.dir.join("markdown").join("imported.md"), "# Imported\n\nText").unwrap();
+
+    Fixture {
+        project,
+        task: id_of(&task),
+        note: id_of(&note),
+        other_note: id_of(&other_note),
+        reference: id_of(&reference),
+        question: id_of(&question),
+        deadline: id_of(&deadline),
+        template: id_of(&template),
+    }
+}
+
+/// Every command with realistic arguments, in an order where each step finds what it needs.
+/// Destructive commands come last.
+fn table(h: &Harness, f: &Fixture) -> Vec<(&'static str, Value)> {
+    let missing = "00000000-0000-0000-0000-000000000000";
+    let (p, t, n, n2, r, q, d) = (&f.project, &f.task, &f.note, &f.other_note, &f.reference, &f.question, &f.deadline);
+    vec![
+        ("bootstrap", json!({})),
+        // Projects
+        ("create_project", json!({ "data": { "name": "Scratch", "path": h.path("scratch"), "tags": ["draft"] } })),
+        ("list_projects", json!({ "includeArchived": true })),
+        ("filter_projects", json!({ "filter": { "tags_any": ["draft"] } })),
+        ("list_projects_by_name", json!({})),
+        ("list_projects_with_counts", json!({})),
+        ("get_project", json!({ "id": p })),
+        ("get_project_summary", json!({ "id": p })),
+        ("get_project_stats", json!({ "projectId": p })),
+        ("get_project_dashboard", json!({ "projectId": p })),
+        ("get_project_graph", json!({ "projectId": p, "includeTasks": true })),
+        ("get_all_project_stats", json!({})),
+        ("get_project_git_status", json!({ "projectId": p })),
+        ("get_project_history", json!({ "projectId": p, "limit": 10 })),
+        ("get_file_diff", json!({ "projectId": p, "relativePath": "research.json" })),
+        ("set_project_remote", json!({ "projectId": p, "name": "origin", "url": h.path("remote.git") })),
+        ("push_project", json!({ "projectId": p })),
+        ("pull_project", json!({ "projectId": p })),
+        ("regenerate_gitignore", json!({ "projectId": p, "extraPatterns": ["*.tmp"] })),
+        ("apply_layout_to_project", json!({ "projectId": p })),
+        ("update_project", json!({ "id": p, "data": { "description": "PhD thesis" } })),
+        ("update_project_v2", json!({ "id": p, "data": { "key_prefix": "TH" } })),
+        ("toggle_project_favorite", json!({ "id": p })),
+        ("reorder_favorite", json!({ "id": p, "position": 0 })),
+        ("get_project_statuses", json!({ "projectId": p })),
+        ("set_project_statuses", json!({ "projectId": p, "statuses": ["todo", "in_progress", "review", "done"] })),
+        ("get_project_settings", json!({ "projectId": p })),
+        ("update_project_settings", json!({ "projectId": p, "settings": { "auto_commit": false } })),
+        ("repair_project_metadata", json!({ "projectId": p })),
+        ("analyze_project_files", json!({ "projectId": p })),
+        ("clean_project_files", json!({ "projectId": p, "actions": [{ "action": "delete", "relative_path": "missing.md" }] })),
+        ("relink_project", json!({ "id": missing, "existingPath": h.path("nowhere") })),
+        ("move_project", json!({ "id": missing, "newPath": h.path("moved") })),
+        ("save_project_as_template", json!({ "projectId": p, "name": "Thesis layout" })),
+        ("list_project_templates", json!({})),
+        // Tasks
+        ("create_task", json!({ "data": { "project_id": p, "parent_id": t, "title": "Draft section 1.1" } })),
+        ("list_tasks", json!({ "projectId": p, "limit": 50 })),
+        ("list_root_tasks", json!({ "projectId": p })),
+        ("list_subtasks", json!({ "parentId": t })),
+        ("get_task", json!({ "id": t })),
+        ("get_task_hierarchy", json!({ "id": t })),
+        ("update_task", json!({ "id": t, "data": { "priority": "high" } })),
+        ("update_task_v2", json!({ "id": t, "data": { "recurrence": "weekly" } })),
+        ("get_task_progress", json!({ "id": t })),
+        ("move_task", json!({ "id": t, "newParentId": null })),
+        ("reorder_task", json!({ "id": t, "newOrder": 0 })),
+        ("list_tasks_by_status", json!({ "projectId": p, "status": "todo" })),
+        ("list_tasks_by_tags", json!({ "projectId": p, "tags": ["writing"], "matchMode": "any" })),
+        ("filter_tasks", json!({ "projectId": p, "filter": { "statuses": ["todo"], "query": "chapter" } })),
+        ("list_tasks_by_title", json!({ "projectId": p })),
+        ("get_task_by_key", json!({ "projectId": p, "key": "TH-1" })),
+        ("search_tasks", json!({ "projectId": p, "query": "chapter" })),
+        ("list_upcoming_tasks", json!({ "days": 7 })),
+        ("list_overdue_tasks", json!({ "now": 1_800_000_000 })),
+        ("rank_tasks", json!({ "projectId": p, "rankedIds": [t] })),
+        ("list_ranked_tasks", json!({ "projectId": p })),
+        ("get_kanban_board", json!({ "projectId": p })),
+        ("move_task_on_board", json!({ "id": t, "newStatus": "in_progress", "newPosition": 0 })),
+        ("archive_completed_tasks", json!({ "projectId": p, "completedBefore": 0 })),
+        ("list_archived_tasks", json!({ "projectId": p })),
+        ("unarchive_task", json!({ "id": missing })),
+        ("list_recurring_tasks", json!({ "projectId": p })),
+        ("skip_next_occurrence", json!({ "id": t })),
+        ("move_tasks_to_project", json!({ "taskIds": [missing], "targetProjectId": p, "keepHierarchy": true })),
+        // Time tracking
+        ("start_timer", json!({ "taskId": t })),
+        ("get_running_timer", json!({})),
+        ("stop_timer", json!({})),
+        ("add_manual_time_entry", json!({ "taskId": t, "startedAt": 1_700_000_000, "endedAt": 1_700_003_600, "note": "Reading" })),
+        ("list_time_entries", json!({ "taskId": t })),
+        ("get_time_summary", json!({ "projectId": p })),
+        // Notes
+        ("create_notes_bulk", json!({ "projectId": p, "notes": [{ "project_id": p, "title": "Bulk", "content": "" }] })),
+        ("create_inbox_note", json!({ "title": "Idea", "content": "Try a smaller model", "tags": ["idea"] })),
+        ("list_inbox_notes", json!({})),
+        ("list_notes", json!({ "projectId": p })),
+        ("list_notes_by_title", json!({ "projectId": p })),
+        ("get_note", json!({ "id": n })),
+        ("update_note", json!({ "id": n, "data": { "content": "See [[Sources]] and [[Missing]]" } })),
+        ("update_note_v2", json!({ "id": n, "data": { "tags": ["writing", "outline"] } })),
+        ("toggle_note_pin", json!({ "id": n })),
+        ("list_pinned_notes", json!({ "projectId": p })),
+        ("reorder_pinned_note", json!({ "id": n, "position": 0 })),
+        ("list_recent_notes", json!({ "projectId": p, "limit": 5 })),
+        ("list_frequent_notes", json!({ "projectId": p, "limit": 5 })),
+        ("save_note_view_state", json!({ "noteId": n, "data": { "scroll_position": 120.5, "cursor_offset": 42 } })),
+        ("get_note_view_state", json!({ "noteId": n })),
+        ("duplicate_note", json!({ "id": n2, "newTitle": "Sources (copy)" })),
+        ("search_notes", json!({ "projectId": p, "query": "Sources" })),
+        ("get_note_tags", json!({ "projectId": p })),
+        ("list_notes_by_tags", json!({ "projectId": p, "tags": ["writing"], "matchMode": "all" })),
+        ("move_notes_to_project", json!({ "noteIds": [missing], "targetProjectId": p })),
+        ("move_note_to_project", json!({ "noteId": missing, "projectId": p })),
+        ("lock_note", json!({ "id": n2 })),
+        ("unlock_note", json!({ "id": n2 })),
+        ("copy_note_for_sharing", json!({ "noteId": n, "format": "markdown", "includeMetadata": true })),
+        ("get_note_backlinks", json!({ "noteId": n2 })),
+        ("get_note_outgoing_links", json!({ "noteId": n })),
+        ("get_note_stats", json!({ "noteId": n })),
+        ("get_writing_stats", json!({ "projectId": p, "from": 0, "to": 4_102_444_800i64 })),
+        ("set_writing_goal", json!({ "projectId": p, "targetWords": 80000 })),
+        ("get_writing_progress", json!({ "projectId": p })),
+        // Note templates
+        ("list_note_templates", json!({})),
+        ("create_note_from_template", json!({ "projectId": p, "templateId": f.template, "variables": { "topic": "Scaling" } })),
+        // Attachments
+        ("attach_file_to_note", json!({ "noteId": n, "sourcePath": h.path("figure.txt") })),
+        ("list_note_attachments", json!({ "noteId": n })),
+        ("open_attachment", json!({ "attachmentId": missing })),
+        ("get_attachment_store_stats", json!({})),
+        // References
+        ("list_references", json!({ "projectId": p })),
+        ("get_reference", json!({ "id": r })),
+        ("update_reference", json!({ "id": r, "data": { "reading_status": "reading" } })),
+        ("find_duplicate_references", json!({ "projectId": p })),
+        ("import_references_bibtex", json!({ "projectId": p, "bibtexTextOrPath": h.path("refs.bib") })),
+        ("export_references_bibtex", json!({ "projectId": p })),
+        ("cite_in_note", json!({ "noteId": n, "referenceId": r })),
+        ("list_note_references", json!({ "noteId": n })),
+        ("uncite_in_note", json!({ "noteId": n, "referenceId": r })),
+        ("merge_references", json!({ "keepId": r, "removeIds": [missing] })),
+        // Research questions
+        ("create_research_question", json!({ "data": { "project_id": p, "question": "Which baseline?" } })),
+        ("list_research_questions", json!({ "projectId": p })),
+        ("get_research_question", json!({ "id": q })),
+        ("update_research_question", json!({ "id": q, "data": { "status": "answered", "answer_summary": "Yes" } })),
+        ("link_question_note", json!({ "questionId": q, "noteId": n })),
+        ("link_question_task", json!({ "questionId": q, "taskId": t })),
+        ("list_question_links", json!({ "questionId": q })),
+        ("unlink_question_note", json!({ "questionId": q, "noteId": n })),
+        ("unlink_question_task", json!({ "questionId": q, "taskId": t })),
+        // Deadlines
+        ("create_deadline", json!({ "data": { "name": "Workshop", "date": 4_102_444_800i64, "url": "https://example.org/cfp" } })),
+        ("list_deadlines", json!({ "projectId": p })),
+        ("list_upcoming_deadlines", json!({ "days": 30, "includePast": false })),
+        ("get_today_view", json!({ "days": 7 })),
+        ("get_deadline", json!({ "id": d })),
+        ("update_deadline", json!({ "id": d, "data": { "notes": "Camera ready" } })),
+        ("import_deadlines_feed", json!({ "urlOrPath": h.path("missing.ics"), "projectId": p, "dryRun": true })),
+        // Tags
+        ("list_tags", json!({ "projectId": p })),
+        ("rename_tag", json!({ "id": missing, "newName": "drafting" })),
+        ("rename_tag_path", json!({ "path": "outline", "newPath": "structure" })),
+        ("set_tag_color", json!({ "id": missing, "color": "#ff8800" })),
+        // Metadata
+        ("set_entity_metadata", json!({ "entityType": "note", "id": n, "patch": { "source": "interview" }, "merge": true })),
+        ("get_entity_metadata", json!({ "entityType": "note", "id": n })),
+        // Files
+        ("index_project_files", json!({ "projectId": p })),
+        ("list_project_files", json!({ "projectId": p })),
+        ("list_files", json!({ "projectId": p, "recursive": true })),
+        ("get_file_info", json!({ "projectId": p, "relativePath": "research.json" })),
+        ("get_largest_files", json!({ "projectId": p, "limit": 5 })),
+        ("get_changed_files", json!({ "projectId": p, "since": 0 })),
+        ("set_file_ignored", json!({ "fileId": missing, "ignored": true })),
+        ("get_ignore_patterns", json!({ "projectId": p })),
+        ("set_ignore_patterns", json!({ "projectId": p, "patterns": ["*.log", "build/"] })),
+        ("search_project_files", json!({ "projectId": p, "query": "research" })),
+        ("find_duplicate_files", json!({ "minSizeBytes": 1 })),
+        ("replace_with_symlink", json!({ "keepFileId": missing, "duplicateFileId": missing })),
+        // Export and import
+        ("export_project", json!({ "projectId": p, "destPath": h.path("thesis.zip") })),
+        ("verify_export", json!({ "path": h.path("thesis.zip") })),
+        ("import_project", json!({ "srcPath": h.path("thesis.zip"), "newPath": h.path("thesis-copy") })),
+        ("export_notes_markdown", json!({ "projectId": p, "destDir": h.path("notes-out") })),
+        ("import_notes_markdown", json!({ "projectId": p, "srcDir": h.path("markdown") })),
+        ("export_tasks_ical", json!({ "projectId": p, "destPath": h.path("tasks.ics") })),
+        ("export_all_tasks_ical", json!({ "destPath": h.path("all.ics"), "component": "vevent" })),
+        ("export_tasks_csv", json!({ "projectId": p, "destPath": h.path("tasks-out.csv") })),
+        ("import_tasks_csv", json!({ "projectId": p, "srcPath": h.path("tasks.csv") })),
+        ("export_note_html", json!({ "noteId": n, "destPath": h.path("outline.html") })),
+        ("export_project_html", json!({ "projectId": p, "destPath": h.path("thesis.html") })),
+        ("export_references_csv", json!({ "projectId": p, "destPath": h.path("refs.csv"), "columns": ["title", "year"] })),
+        ("export_project_site", json!({ "projectId": p, "destDir": h.path("site") })),
+        ("clear_render_cache", json!({})),
+        ("export_note_bundle", json!({ "noteId": n, "destPath": h.path("outline.zip") })),
+        ("import_note_bundle", json!({ "projectId": p, "srcPath": h.path("outline.zip") })),
+        ("export_project_context", json!({ "projectId": p, "options": { "format": "markdown", "max_notes": 10 } })),
+        // Reports
+        ("generate_progress_report", json!({ "projectId": p, "from": 0, "to": 4_102_444_800i64, "autoCommit": false })),
+        ("generate_weekly_digest", json!({})),
+        // Search, activity and audit
+        ("global_search", json!({ "query": "chapter", "limit": 20 })),
+        ("get_jump_index", json!({})),
+        ("get_activity_heatmap", json!({ "days": 30, "projectId": p })),
+        ("list_activity", json!({ "projectId": p, "limit": 20 })),
+        ("list_audit_log", json!({ "filter": { "outcome": "ok" }, "limit": 20 })),
+        ("export_audit_log_csv", json!({ "path": h.path("audit.csv") })),
+        ("clear_activity", json!({ "olderThanDays": 365 })),
+        // Reminders
+        ("list_pending_reminders", json!({})),
+        ("snooze_reminder", json!({ "taskId": t, "minutes": 15 })),
+        // Settings
+        ("get_setting", json!({ "key": "theme" })),
+        ("get_all_settings", json!({})),
+        // Onboarding
+        ("is_first_run", json!({})),
+        ("create_sample_project", json!({ "destDir": h.path("sample") })),
+        // Automation
+        ("start_automation_server", json!({})),
+        ("get_automation_status", json!({})),
+        ("get_automation_token", json!({})),
+        ("stop_automation_server", json!({})),
+        ("get_startup_scan_report", json!({})),
+        // Backups
+        ("run_backup_now", json!({})),
+        ("create_portable_backup", json!({ "path": h.path("portable.zip") })),
+        ("restore_portable_backup", json!({ "path": h.path("missing.zip"), "restoreProjectsTo": h.path("restored") })),
+        // Vaults
+        ("get_vault_path", json!({})),
+        ("list_recent_vaults", json!({})),
+        ("open_vault", json!({ "path": h.path("no-vault"), "create": false })),
+        // Health
+        ("list_orphaned_entities", json!({})),
+        ("adopt_orphans", json!({ "targetProjectId": p })),
+        ("purge_orphans", json!({})),
+        ("repair_database", json!({ "dryRun": true })),
+        ("get_db_info", json!({})),
+        ("get_schema_version", json!({})),
+        ("get_migration_status", json!({})),
+        ("check_database_health", json!({})),
+        ("optimize_database", json!({})),
+        ("checkpoint_database", json!({})),
+        ("reconnect_database", json!({})),
+        ("get_recent_logs", json!({ "lines": 20 })),
+        ("set_log_level", json!({ "level": "info" })),
+        // Undo
+        (
+            "revert_change",
+            json!({ "entityType": "task", "id": t, "beforePayload": { "before": { "title": "Write chapter 1" }, "updated_at": 0 } }),
+        ),
+        // Destructive commands last
+        ("remove_attachment", json!({ "attachmentId": missing, "deleteFile": true })),
+        ("delete_tag", json!({ "id": missing })),
+        ("delete_deadline", json!({ "id": d })),
+        ("delete_reference", json!({ "id": r })),
+        ("delete_research_question", json!({ "id": q })),
+        ("delete_note_template", json!({ "id": f.template })),
+        ("delete_project_template", json!({ "id": missing })),
+        ("delete_task", json!({ "id": t })),
+        ("delete_note", json!({ "id": n2, "force": true })),
+        ("list_trash", json!({ "projectId": p })),
+        ("restore_from_trash", json!({ "entityType": "task", "id": t })),
+        ("empty_trash", json!({ "projectId": p })),
+        ("delete_project", json!({ "id": p })),
+        ("restore_project", json!({ "id": p })),
+        ("purge_project", json!({ "id": missing, "deleteFiles": false })),
+        ("set_setting", json!({ "key": "theme", "value": "dark" })),
+    ]
+}
+
+/// A failure must be a structured `AppError`; a bare string means Tauri rejected the
+/// arguments before the command ran
+fn assert_structured_error(cmd: &str, error: &Value) {
+    assert!(
+        error["code"].as_str().is_some_and(|code| !code.is_empty()) && error["message"].is_string(),
+        "{} failed without a structured error: {}",
+        cmd,
+        error
+    );
+}
+
+#[test]
+fn every_command_answers_with_a_value_or_a_structured_error() {
+    let h = Harness::new();
+    let f = fixture(&h);
+    let table = table(&h, &f);
+
+    let covered: HashSet<&str> = table.iter().map(|(cmd, _)| *cmd).collect();
+    let uncovered: Vec<&&str> = COMMAND_NAMES.iter().filter(|name| !covered.contains(**name)).collect();
+    assert!(uncovered.is_empty(), "commands missing from the table: {:?}", uncovered);
+    let unknown: Vec<&&str> = covered.iter().filter(|cmd| !COMMAND_NAMES.contains(cmd)).collect();
+    assert!(unknown.is_empty(), "table entries that are not registered: {:?}", unknown);
+
+    for (cmd, args) in table {
+        if let Err(error) = h.invoke(cmd, args) {
+            assert_structured_error(cmd, &error);
+        }
+    }
+}
+
+#[test]
+fn success_values_have_the_shapes_the_frontend_reads() {
+    let h = Harness::new();
+    let f = fixture(&h);
+
+    let summary = h.ok("get_project_summary", json!({ "id": f.project }));
+    assert_eq!(summary["id"], f.project.as_str());
+    assert!(summary["task_counts"].is_object());
+    assert_eq!(summary["note_count"], 2);
+
+    let tasks = h.ok("list_tasks", json!({ "projectId": f.project }));
+    assert_eq!(tasks["total_count"], 1);
+    assert_eq!(tasks["items"][0]["id"], f.task.as_str());
+
+    let note = h.ok("get_note", json!({ "id": f.note }));
+    assert_eq!(note["title"], "Outline");
+    assert_eq!(note["tags"], json!(["writing"]));
+
+    let links = h.ok("get_note_outgoing_links", json!({ "noteId": f.note }));
+    assert!(links.is_array());
+
+    let setting = h.ok("get_setting", json!({ "key": "theme" }));
+    assert_eq!(setting, "system");
+
+    let updated = h.ok("update_task", json!({ "id": f.task, "data": { "status": "done" } }));
+    assert_eq!(updated["status"], "done");
+    assert!(updated["completed_at"].is_number());
+}
+
+#[test]
+fn failures_use_the_structured_error_format() {
+    let h = Harness::new();
+
+    let error = h.invoke("get_project", json!({ "id": "missing" })).unwrap_err();
+    assert_eq!(error["code"], "NOT_FOUND");
+    assert!(error["message"].as_str().unwrap().contains("missing"));
+
+    let error = h.invoke("set_setting", json!({ "key": "automation_port", "value": 80 })).unwrap_err();
+    assert_eq!(error["code"], "INVALID_INPUT");
+
+    // Arguments Tauri cannot deserialize never reach the command
+    let error = h.invoke("get_project", json!({ "identifier": "missing" })).unwrap_err();
+    assert!(error.is_string(), "expected Tauri's own error, got {}", error);
+}
+
+#[test]
+fn mutations_announce_themselves_to_every_window() {
+    let h = Harness::new();
+    let events: Arc<Mutex<Vec<(String, Value)>>> = Arc::default();
+    for entity in ["project", "task", "note"] {
+        for action in ["created", "updated", "deleted"] {
+            let name = format!("{}:{}", entity, action);
+            let events = events.clone();
+            h.app.listen_any(name.clone(), move |event| {
+                let payload = serde_json::from_str(event.payload()).expect("JSON payload");
+                events.lock().unwrap().push((name.clone(), payload));
+            });
+        }
+    }
+    let take = || std::mem::take(&mut *events.lock().unwrap());
+
+    let project = id_of(&h.ok("create_project", json!({ "data": { "name": "Thesis", "path": h.path("thesis") } })));
+    let task = id_of(&h.ok("create_task", json!({ "data": { "project_id": project, "title": "Write" } })));
+    let note = id_of(&h.ok("create_note", json!({ "data": { "project_id": project, "title": "Outline", "content": "" } })));
+    h.ok("update_project", json!({ "id": project, "data": { "description": "PhD" } }));
+    h.ok("update_task", json!({ "id": task, "data": { "priority": "high" } }));
+    h.ok("update_note", json!({ "id": note, "data": { "content": "Draft" } }));
+    h.ok("delete_task", json!({ "id": task }));
+    h.ok("delete_note", json!({ "id": note, "force": true }));
+
+    let payload = |entity: &str, id: &str, action: &str| {
+        json!({ "entity_type": entity, "entity_id": id, "project_id": project, "action": action })
+    };
+    assert_eq!(
+        take(),
+        [
+            ("project:created".to_string(), payload("project", &project, "created")),
+            ("task:created".to_string(), payload("task", &task, "created")),
+            ("note:created".to_string(), payload("note", &note, "created")),
+            ("project:updated".to_string(), payload("project", &project, "updated")),
+            ("task:updated".to_string(), payload("task", &task, "updated")),
+            ("note:updated".to_string(), payload("note", &note, "updated")),
+            ("task:deleted".to_string(), payload("task", &task, "deleted")),
+            ("note:deleted".to_string(), payload("note", &note, "deleted")),
+        ]
+    );
+
+    // Failed mutations announce nothing
+    assert!(h.invoke("update_task", json!({ "id": "missing", "data": { "title": "x" } })).is_err());
+    assert!(h.invoke("delete_note", json!({ "id": "missing" })).is_err());
+    assert!(take().is_empty());
+
+    h.ok("delete_project", json!({ "id": project }));
+    assert_eq!(take(), [("project:deleted".to_string(), payload("project", &project, "deleted"))]);
+}
@@ -23,7 +23,7 @@ export class NoteRepository extends BaseRepository<Note> {
     async findAll(): Promise<Note[]> {
         if (isTauri) {
             try {
-                const notes = await invoke<Note[]>('list_notes', { projectId: null })
+                const { items: notes } = await invoke<{ items: Note[] }>('list_notes', { projectId: null })
                 return notes.map(this.normalizeNote)
             } catch (error) {
                 console.error('Failed to list notes:', error)
@@ -56,7 +56,7 @@ export class NoteRepository extends BaseRepository<Note> {
     async findByProjectId(projectId: string): Promise<Note[]> {
         if (isTauri) {
             try {
-                const notes = await invoke<Note[]>('list_notes', { projectId })
+                const { items: notes } = await invoke<{ items: Note[] }>('list_notes', { projectId })
                 return notes.map(this.normalizeNote)
             } catch (error) {
                 console.error('Failed to list project notes:', error)
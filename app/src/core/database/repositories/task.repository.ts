@@ -23,7 +23,7 @@ export class TaskRepository extends BaseRepository<Task> {
     async findAll(): Promise<Task[]> {
         if (isTauri) {
             try {
-                const tasks = await invoke<Task[]>('list_tasks', { projectId: null })
+                const { items: tasks } = await invoke<{ items: Task[] }>('list_tasks', { projectId: null })
                 return tasks.map(this.normalizeTask)
             } catch (error) {
                 console.error('Failed to list tasks:', error)
@@ -54,7 +54,7 @@ export class TaskRepository extends BaseRepository<Task> {
     async findByProjectId(projectId: string): Promise<Task[]> {
         if (isTauri) {
             try {
-                const tasks = await invoke<Task[]>('list_tasks', { projectId })
+                const { items: tasks } = await invoke<{ items: Task[] }>('list_tasks', { projectId })
                 return tasks.map(this.normalizeTask)
             } catch (error) {
                 console.error('Failed to list project tasks:', error)